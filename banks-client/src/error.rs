@@ -1,4 +1,5 @@
 use {
+    solana_program_runtime::profile_report::ProfileReportError,
     solana_transaction_context::TransactionReturnData,
     solana_transaction_error::{TransactionError, TransportError},
     std::io,
@@ -28,6 +29,9 @@ pub enum BanksClientError {
         units_consumed: u64,
         return_data: Option<TransactionReturnData>,
     },
+
+    #[error(transparent)]
+    ProfileReportError(#[from] ProfileReportError),
 }
 
 impl BanksClientError {
@@ -48,6 +52,7 @@ impl From<BanksClientError> for io::Error {
             BanksClientError::RpcError(err) => Self::other(err.to_string()),
             BanksClientError::TransactionError(err) => Self::other(err.to_string()),
             BanksClientError::SimulationError { err, .. } => Self::other(err.to_string()),
+            BanksClientError::ProfileReportError(err) => Self::other(err.to_string()),
         }
     }
 }
@@ -60,6 +65,9 @@ impl From<BanksClientError> for TransportError {
             BanksClientError::RpcError(err) => Self::IoError(io::Error::other(err.to_string())),
             BanksClientError::TransactionError(err) => Self::TransactionError(err),
             BanksClientError::SimulationError { err, .. } => Self::TransactionError(err),
+            BanksClientError::ProfileReportError(err) => {
+                Self::IoError(io::Error::other(err.to_string()))
+            }
         }
     }
 }
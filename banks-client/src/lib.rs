@@ -22,6 +22,7 @@ use {
     solana_hash::Hash,
     solana_message::Message,
     solana_program_pack::Pack,
+    solana_program_runtime::profile_report::ProfileReport,
     solana_pubkey::Pubkey,
     solana_rent::Rent,
     solana_signature::Signature,
@@ -170,6 +171,17 @@ impl BanksClient {
             .map_err(Into::into)
     }
 
+    pub async fn get_transaction_profile_with_context(
+        &self,
+        ctx: Context,
+        signature: Signature,
+    ) -> Result<Option<String>, BanksClientError> {
+        self.inner
+            .get_transaction_profile_with_context(ctx, signature)
+            .await
+            .map_err(Into::into)
+    }
+
     /// Send a transaction and return immediately. The server will resend the
     /// transaction until either it is accepted by the cluster or the transaction's
     /// blockhash expires.
@@ -427,6 +439,21 @@ impl BanksClient {
             .await
     }
 
+    /// Return the profile report of a processed transaction with the given first signature.
+    /// Return None if the transaction was not profiled or is not found. Only local servers keep
+    /// the profiles, and only of their most recently processed transactions. Transactions are
+    /// only profiled if they request it or if the bank profiles every transaction, as
+    /// solana-program-test does.
+    pub async fn get_transaction_profile(
+        &self,
+        signature: Signature,
+    ) -> Result<Option<ProfileReport>, BanksClientError> {
+        self.get_transaction_profile_with_context(context::current(), signature)
+            .await?
+            .map(|json| ProfileReport::from_json(&json).map_err(Into::into))
+            .transpose()
+    }
+
     /// Same as get_transaction_status, but for multiple transactions.
     pub async fn get_transaction_statuses(
         &self,
@@ -534,8 +561,11 @@ mod tests {
         super::*,
         solana_banks_server::banks_server::start_local_server,
         solana_runtime::{
-            bank::Bank, bank_forks::BankForks, commitment::BlockCommitmentCache,
+            bank::{Bank, BankTestConfig},
+            bank_forks::BankForks,
+            commitment::BlockCommitmentCache,
             genesis_utils::create_genesis_config,
+            runtime_config::RuntimeConfig,
         },
         solana_signer::Signer,
         solana_system_interface::instruction as system_instruction,
@@ -641,4 +671,58 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    #[allow(clippy::result_large_err)]
+    fn test_banks_server_get_transaction_profile() -> Result<(), BanksClientError> {
+        let genesis = create_genesis_config(10);
+        let bank = Bank::new_with_paths_for_tests(
+            &genesis.genesis_config,
+            Arc::new(RuntimeConfig {
+                profile_all_transactions: true,
+                ..RuntimeConfig::default()
+            }),
+            BankTestConfig::default(),
+            Vec::new(),
+        );
+        let slot = bank.slot();
+        let block_commitment_cache = Arc::new(RwLock::new(
+            BlockCommitmentCache::new_for_tests_with_slots(slot, slot),
+        ));
+        let bank_forks = BankForks::new_rw_arc(bank);
+
+        let mint_pubkey = genesis.mint_keypair.pubkey();
+        let bob_pubkey = solana_pubkey::new_rand();
+        let instruction = system_instruction::transfer(&mint_pubkey, &bob_pubkey, 1);
+        let message = Message::new(&[instruction], Some(&mint_pubkey));
+
+        Runtime::new()?.block_on(async {
+            let client_transport =
+                start_local_server(bank_forks, block_commitment_cache, Duration::from_millis(1))
+                    .await;
+            let banks_client = start_client(client_transport).await?;
+
+            let recent_blockhash = banks_client.get_latest_blockhash().await?;
+            let transaction = Transaction::new(&[&genesis.mint_keypair], message, recent_blockhash);
+            let signature = transaction.signatures[0];
+            assert!(banks_client
+                .get_transaction_profile(signature)
+                .await?
+                .is_none());
+
+            banks_client.process_transaction(transaction).await?;
+            let report = banks_client
+                .get_transaction_profile(signature)
+                .await?
+                .unwrap();
+            assert_eq!(report.transaction.signature, Some(signature.to_string()));
+            assert_eq!(report.transaction.error, None);
+            assert_eq!(report.instructions.len(), 1);
+            assert_eq!(
+                report.instructions[0].program_id,
+                solana_system_interface::program::id()
+            );
+            Ok(())
+        })
+    }
 }
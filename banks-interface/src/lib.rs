@@ -97,6 +97,8 @@ pub trait Banks {
         message: Message,
         commitment: CommitmentLevel,
     ) -> Option<u64>;
+    /// JSON profile report of a recently processed transaction, only kept by local servers
+    async fn get_transaction_profile_with_context(signature: Signature) -> Option<String>;
 }
 
 #[cfg(test)]
@@ -9,10 +9,13 @@ use {
         TransactionSimulationDetails, TransactionStatus,
     },
     solana_client::connection_cache::ConnectionCache,
-    solana_clock::Slot,
+    solana_clock::{Slot, MAX_PROCESSING_AGE},
     solana_commitment_config::CommitmentLevel,
     solana_hash::Hash,
     solana_message::{Message, SanitizedMessage},
+    solana_program_runtime::profile_report::{
+        ProfileReport, TransactionMetadata as ProfileMetadata,
+    },
     solana_pubkey::Pubkey,
     solana_runtime::{
         bank::{Bank, TransactionSimulationResult},
@@ -26,11 +29,17 @@ use {
         transaction_client::ConnectionCacheClient,
     },
     solana_signature::Signature,
+    solana_svm::{
+        transaction_commit_result::CommittedTransaction,
+        transaction_processor::ExecutionRecordingConfig,
+    },
+    solana_svm_timings::ExecuteTimings,
     solana_transaction::{
         sanitized::{MessageHash, SanitizedTransaction},
         versioned::VersionedTransaction,
     },
     std::{
+        collections::{HashMap, VecDeque},
         io,
         net::{Ipv4Addr, SocketAddr},
         sync::{atomic::AtomicBool, Arc, RwLock},
@@ -52,12 +61,71 @@ mod transaction {
     pub use solana_transaction_error::TransactionResult as Result;
 }
 
+/// Maximum number of transaction profiles kept by a server
+const MAX_TRANSACTION_PROFILES: usize = 1024;
+
+/// JSON profile reports of the most recently processed transactions
+#[derive(Default)]
+struct TransactionProfiles {
+    profiles: HashMap<Signature, String>,
+    /// Signatures of the kept profiles, oldest first
+    signatures: VecDeque<Signature>,
+}
+
+impl TransactionProfiles {
+    fn insert(&mut self, signature: Signature, profile: String) {
+        if self.profiles.insert(signature, profile).is_none() {
+            self.signatures.push_back(signature);
+        }
+        if self.signatures.len() > MAX_TRANSACTION_PROFILES {
+            if let Some(oldest) = self.signatures.pop_front() {
+                self.profiles.remove(&oldest);
+            }
+        }
+    }
+
+    fn get(&self, signature: &Signature) -> Option<String> {
+        self.profiles.get(signature).cloned()
+    }
+
+    /// Keep the profile of a committed transaction, if it was profiled
+    fn insert_committed(
+        &mut self,
+        bank: &Bank,
+        signature: &Signature,
+        committed_tx: &CommittedTransaction,
+    ) {
+        let Some(instructions) = committed_tx
+            .profiling_results
+            .as_ref()
+            .filter(|instructions| !instructions.is_empty())
+        else {
+            return;
+        };
+        let report = ProfileReport::new(
+            ProfileMetadata {
+                signature: Some(signature.to_string()),
+                slot: Some(bank.slot()),
+                compute_units_consumed: committed_tx.executed_units,
+                error: committed_tx.status.as_ref().err().map(ToString::to_string),
+            },
+            instructions.clone(),
+            committed_tx
+                .program_load_profiles
+                .clone()
+                .unwrap_or_default(),
+        );
+        self.insert(*signature, report.to_json());
+    }
+}
+
 #[derive(Clone)]
 struct BanksServer {
     bank_forks: Arc<RwLock<BankForks>>,
     block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
     transaction_sender: Sender<TransactionInfo>,
     poll_signature_status_sleep_duration: Duration,
+    transaction_profiles: Arc<RwLock<TransactionProfiles>>,
 }
 
 impl BanksServer {
@@ -70,16 +138,22 @@ impl BanksServer {
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
         transaction_sender: Sender<TransactionInfo>,
         poll_signature_status_sleep_duration: Duration,
+        transaction_profiles: Arc<RwLock<TransactionProfiles>>,
     ) -> Self {
         Self {
             bank_forks,
             block_commitment_cache,
             transaction_sender,
             poll_signature_status_sleep_duration,
+            transaction_profiles,
         }
     }
 
-    fn run(bank_forks: Arc<RwLock<BankForks>>, transaction_receiver: Receiver<TransactionInfo>) {
+    fn run(
+        bank_forks: Arc<RwLock<BankForks>>,
+        transaction_receiver: Receiver<TransactionInfo>,
+        transaction_profiles: Arc<RwLock<TransactionProfiles>>,
+    ) {
         while let Ok(info) = transaction_receiver.recv() {
             let mut transaction_infos = vec![info];
             while let Ok(info) = transaction_receiver.try_recv() {
@@ -96,7 +170,7 @@ impl BanksServer {
                 // has been processed
                 let lock = bank.freeze_lock();
                 if *lock == Hash::default() {
-                    let _ = bank.try_process_entry_transactions(transactions);
+                    let _ = process_entry_transactions(&bank, transactions, &transaction_profiles);
                     // break out of inner loop and release bank freeze lock
                     break;
                 }
@@ -119,15 +193,24 @@ impl BanksServer {
             w_block_commitment_cache.set_all_slots(slot, slot);
         }
         let server_bank_forks = bank_forks.clone();
+        let transaction_profiles = Arc::<RwLock<TransactionProfiles>>::default();
+        let server_transaction_profiles = transaction_profiles.clone();
         Builder::new()
             .name("solBankForksCli".to_string())
-            .spawn(move || Self::run(server_bank_forks, transaction_receiver))
+            .spawn(move || {
+                Self::run(
+                    server_bank_forks,
+                    transaction_receiver,
+                    server_transaction_profiles,
+                )
+            })
             .unwrap();
         Self::new(
             bank_forks,
             block_commitment_cache,
             transaction_sender,
             poll_signature_status_sleep_duration,
+            transaction_profiles,
         )
     }
 
@@ -164,6 +247,33 @@ impl BanksServer {
     }
 }
 
+/// Process the transactions in a single batch like
+/// [`Bank::try_process_entry_transactions`], keeping the profiles of the committed transactions
+fn process_entry_transactions(
+    bank: &Bank,
+    transactions: Vec<VersionedTransaction>,
+    transaction_profiles: &RwLock<TransactionProfiles>,
+) -> transaction::Result<()> {
+    let batch = bank.prepare_entry_batch(transactions)?;
+    let (commit_results, _) = bank.load_execute_and_commit_transactions(
+        &batch,
+        MAX_PROCESSING_AGE,
+        ExecutionRecordingConfig {
+            enable_profiling_recording: true,
+            ..ExecutionRecordingConfig::new_single_setting(false)
+        },
+        &mut ExecuteTimings::default(),
+        None,
+    );
+    let mut transaction_profiles = transaction_profiles.write().unwrap();
+    for (transaction, commit_result) in batch.sanitized_transactions().iter().zip(commit_results) {
+        if let Ok(committed_tx) = commit_result {
+            transaction_profiles.insert_committed(bank, transaction.signature(), &committed_tx);
+        }
+    }
+    Ok(())
+}
+
 fn simulate_transaction(
     bank: &Bank,
     transaction: VersionedTransaction,
@@ -191,11 +301,13 @@ fn simulate_transaction(
         loaded_accounts_data_size,
         return_data,
         inner_instructions,
+        instruction_trace: _,
         fee: _,
         pre_balances: _,
         post_balances: _,
         pre_token_balances: _,
         post_token_balances: _,
+        profile: _,
     } = bank.simulate_transaction_unchecked(&sanitized_transaction, true);
 
     let simulation_details = TransactionSimulationDetails {
@@ -358,19 +470,26 @@ impl Banks for BanksServer {
         transaction: VersionedTransaction,
     ) -> BanksTransactionResultWithMetadata {
         let bank = self.bank_forks.read().unwrap().working_bank();
+        let signature = transaction.signatures.first().cloned().unwrap_or_default();
         match bank.process_transaction_with_metadata(transaction) {
             Err(error) => BanksTransactionResultWithMetadata {
                 result: Err(error),
                 metadata: None,
             },
-            Ok(details) => BanksTransactionResultWithMetadata {
-                result: details.status,
-                metadata: Some(TransactionMetadata {
-                    compute_units_consumed: details.executed_units,
-                    log_messages: details.log_messages.unwrap_or_default(),
-                    return_data: details.return_data,
-                }),
-            },
+            Ok(details) => {
+                self.transaction_profiles
+                    .write()
+                    .unwrap()
+                    .insert_committed(&bank, &signature, &details);
+                BanksTransactionResultWithMetadata {
+                    result: details.status,
+                    metadata: Some(TransactionMetadata {
+                        compute_units_consumed: details.executed_units,
+                        log_messages: details.log_messages.unwrap_or_default(),
+                        return_data: details.return_data,
+                    }),
+                }
+            }
         }
     }
 
@@ -412,6 +531,14 @@ impl Banks for BanksServer {
                 .ok()?;
         bank.get_fee_for_message(&sanitized_message)
     }
+
+    async fn get_transaction_profile_with_context(
+        self,
+        _: Context,
+        signature: Signature,
+    ) -> Option<String> {
+        self.transaction_profiles.read().unwrap().get(&signature)
+    }
 }
 
 pub async fn start_local_server(
@@ -480,6 +607,7 @@ pub async fn start_tcp_server(
                 block_commitment_cache.clone(),
                 sender,
                 Duration::from_millis(200),
+                Arc::default(),
             );
             chan.execute(server.serve())
         })
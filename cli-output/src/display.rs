@@ -820,6 +820,8 @@ mod test {
             }),
             compute_units_consumed: Some(1234u64),
             cost_units: Some(5678),
+            profile: None,
+            instruction_compute_units_consumed: None,
         };
 
         let output = {
@@ -900,6 +902,8 @@ Rewards:
             }),
             compute_units_consumed: Some(2345u64),
             cost_units: Some(5678),
+            profile: None,
+            instruction_compute_units_consumed: None,
         };
 
         let output = {
@@ -2179,6 +2179,7 @@ pub fn process_transaction_history(
                         encoding: Some(UiTransactionEncoding::Base64),
                         commitment: Some(CommitmentConfig::confirmed()),
                         max_supported_transaction_version: Some(0),
+                        include_profile: false,
                     },
                 ) {
                     Ok(confirmed_transaction) => {
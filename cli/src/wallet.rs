@@ -764,6 +764,7 @@ pub fn process_confirm(
                             encoding: Some(UiTransactionEncoding::Base64),
                             commitment: Some(CommitmentConfig::confirmed()),
                             max_supported_transaction_version: Some(0),
+                            include_profile: false,
                         },
                     ) {
                         Ok(confirmed_transaction) => {
@@ -3,7 +3,7 @@ use {
         builtin_programs_filter::{BuiltinProgramsFilter, ProgramKind},
         compute_budget_program_id_filter::ComputeBudgetProgramIdFilter,
     },
-    agave_feature_set::FeatureSet,
+    agave_feature_set::{enable_profiling_syscalls, FeatureSet},
     solana_borsh::v1::try_from_slice_unchecked,
     solana_builtins_default_costs::{get_migration_feature_id, MIGRATING_BUILTINS_COSTS},
     solana_compute_budget::compute_budget_limits::*,
@@ -43,6 +43,8 @@ pub struct ComputeBudgetInstructionDetails {
     requested_compute_unit_price: Option<(u8, u64)>,
     requested_heap_size: Option<(u8, u32)>,
     requested_loaded_accounts_data_size_limit: Option<(u8, u32)>,
+    // instruction index of the request to profile the transaction
+    requested_profiling: Option<u8>,
     num_non_compute_budget_instructions: Saturating<u16>,
     // Additional builtin program counters
     num_non_migratable_builtin_instructions: Saturating<u16>,
@@ -98,10 +100,27 @@ impl ComputeBudgetInstructionDetails {
         Ok(compute_budget_instruction_details)
     }
 
+    /// The compute unit limit requested by the transaction, before it is capped to
+    /// `MAX_COMPUTE_UNIT_LIMIT`
+    pub fn requested_compute_unit_limit(&self) -> Option<u32> {
+        self.requested_compute_unit_limit
+            .map(|(_index, requested_compute_unit_limit)| requested_compute_unit_limit)
+    }
+
     pub fn sanitize_and_convert_to_compute_budget_limits(
         &self,
         feature_set: &FeatureSet,
     ) -> Result<ComputeBudgetLimits> {
+        // Profiling can only be requested once the profiling syscalls are enabled
+        if let Some(index) = self.requested_profiling {
+            if !feature_set.is_active(&enable_profiling_syscalls::id()) {
+                return Err(TransactionError::InstructionError(
+                    index,
+                    InstructionError::InvalidInstructionData,
+                ));
+            }
+        }
+
         // Sanitize requested heap size
         let updated_heap_bytes =
             if let Some((index, requested_heap_size)) = self.requested_heap_size {
@@ -157,6 +176,14 @@ impl ComputeBudgetInstructionDetails {
             TransactionError::InstructionError(index, InstructionError::InvalidInstructionData);
         let duplicate_instruction_error = TransactionError::DuplicateInstruction(index);
 
+        if instruction.data == ENABLE_PROFILING_INSTRUCTION_DATA {
+            if self.requested_profiling.is_some() {
+                return Err(duplicate_instruction_error);
+            }
+            self.requested_profiling = Some(index);
+            return Ok(());
+        }
+
         match try_from_slice_unchecked(instruction.data) {
             Ok(ComputeBudgetInstruction::RequestHeapFrame(bytes)) => {
                 if self.requested_heap_size.is_some() {
@@ -289,6 +316,10 @@ mod test {
             ComputeBudgetInstructionDetails::try_from(SVMMessage::program_instructions_iter(&tx),),
             expected_details
         );
+        assert_eq!(
+            expected_details.unwrap().requested_compute_unit_limit(),
+            Some(u32::MAX)
+        );
 
         let tx = build_sanitized_transaction(&[
             Instruction::new_with_bincode(Pubkey::new_unique(), &(), vec![]),
@@ -375,6 +406,74 @@ mod test {
                 * MAX_BUILTIN_ALLOCATION_COMPUTE_UNIT_LIMIT
     }
 
+    #[test]
+    fn test_try_from_enable_profiling() {
+        let tx = build_sanitized_transaction(&[
+            Instruction::new_with_bincode(Pubkey::new_unique(), &(), vec![]),
+            crate::enable_profiling_instruction(),
+            Instruction::new_with_bincode(Pubkey::new_unique(), &(), vec![]),
+        ]);
+        let expected_details = Ok(ComputeBudgetInstructionDetails {
+            requested_profiling: Some(1),
+            num_non_compute_budget_instructions: Saturating(2),
+            num_non_migratable_builtin_instructions: Saturating(1),
+            num_non_builtin_instructions: Saturating(2),
+            ..ComputeBudgetInstructionDetails::default()
+        });
+        assert_eq!(
+            ComputeBudgetInstructionDetails::try_from(SVMMessage::program_instructions_iter(&tx),),
+            expected_details
+        );
+
+        let tx = build_sanitized_transaction(&[
+            Instruction::new_with_bincode(Pubkey::new_unique(), &(), vec![]),
+            crate::enable_profiling_instruction(),
+            crate::enable_profiling_instruction(),
+        ]);
+        assert_eq!(
+            ComputeBudgetInstructionDetails::try_from(SVMMessage::program_instructions_iter(&tx),),
+            Err(TransactionError::DuplicateInstruction(2))
+        );
+
+        // The next tag of `ComputeBudgetInstruction` does not request profiling
+        let tx = build_sanitized_transaction(&[Instruction::new_with_bytes(
+            solana_sdk_ids::compute_budget::id(),
+            &[5],
+            vec![],
+        )]);
+        assert_eq!(
+            ComputeBudgetInstructionDetails::try_from(SVMMessage::program_instructions_iter(&tx),),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidInstructionData
+            ))
+        );
+    }
+
+    #[test]
+    fn test_sanitize_enable_profiling() {
+        let instruction_details = ComputeBudgetInstructionDetails {
+            requested_profiling: Some(1),
+            ..ComputeBudgetInstructionDetails::default()
+        };
+        assert_eq!(
+            instruction_details
+                .sanitize_and_convert_to_compute_budget_limits(&FeatureSet::default()),
+            Err(TransactionError::InstructionError(
+                1,
+                InstructionError::InvalidInstructionData
+            ))
+        );
+        assert_eq!(
+            instruction_details
+                .sanitize_and_convert_to_compute_budget_limits(&FeatureSet::all_enabled()),
+            Ok(ComputeBudgetLimits {
+                compute_unit_limit: 0,
+                ..ComputeBudgetLimits::default()
+            })
+        );
+    }
+
     #[test]
     fn test_sanitize_and_convert_to_compute_budget_limits() {
         let feature_set = FeatureSet::default();
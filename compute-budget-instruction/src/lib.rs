@@ -4,3 +4,20 @@ mod builtin_programs_filter;
 pub mod compute_budget_instruction_details;
 mod compute_budget_program_id_filter;
 pub mod instructions_processor;
+
+use {
+    solana_compute_budget::compute_budget_limits::ENABLE_PROFILING_INSTRUCTION_DATA,
+    solana_instruction::Instruction,
+};
+
+/// Compute budget instruction enabling profiling for the instructions following it in the same
+/// transaction
+///
+/// Only accepted once the profiling syscalls are enabled.
+pub fn enable_profiling_instruction() -> Instruction {
+    Instruction::new_with_bytes(
+        solana_sdk_ids::compute_budget::id(),
+        &ENABLE_PROFILING_INSTRUCTION_DATA,
+        vec![],
+    )
+}
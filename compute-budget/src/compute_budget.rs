@@ -197,6 +197,7 @@ impl ComputeBudget {
             max_call_depth: self.max_call_depth,
             stack_frame_size: self.stack_frame_size,
             heap_size: self.heap_size,
+            requested_compute_unit_limit: None,
         }
     }
 
@@ -7,6 +7,7 @@ use {
     solana_gossip::{cluster_info::ClusterInfo, node::NodeMultihoming},
     solana_pubkey::Pubkey,
     solana_quic_definitions::NotifyKeyUpdate,
+    solana_rpc::profile_histogram::ProfileHistogramRegistry,
     solana_runtime::bank_forks::BankForks,
     std::{
         collections::{HashMap, HashSet},
@@ -81,4 +82,5 @@ pub struct AdminRpcRequestMetadataPostInit {
     pub cluster_slots: Arc<ClusterSlots>,
     pub node: Option<Arc<NodeMultihoming>>,
     pub banking_stage: Arc<RwLock<Option<BankingStage>>>,
+    pub profile_histograms: Option<Arc<ProfileHistogramRegistry>>,
 }
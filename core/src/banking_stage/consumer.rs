@@ -206,16 +206,22 @@ impl Consumer {
             commit_transactions_result.as_ref().ok(),
             bank,
         );
+        let (scheduled_cu, consumed_cu) = self.qos_service.accumulate_batch_compute_units(
+            transaction_qos_cost_results.iter(),
+            commit_transactions_result.as_ref().ok(),
+        );
 
         // reports qos service stats for this batch
         self.qos_service.report_metrics(bank.slot());
 
         debug!(
-            "bank: {} lock: {}us unlock: {}us txs_len: {}",
+            "bank: {} lock: {}us unlock: {}us txs_len: {} scheduled_cu: {} consumed_cu: {}",
             bank.slot(),
             lock_us,
             unlock_us,
             txs.len(),
+            scheduled_cu,
+            consumed_cu,
         );
 
         ProcessTransactionBatchOutput {
@@ -294,6 +300,15 @@ impl Consumer {
                     account_overrides: None,
                     check_program_modification_slot: bank.check_program_modification_slot(),
                     log_messages_bytes_limit: self.log_messages_bytes_limit,
+                    min_log_level: Default::default(),
+                    dedup_log_messages: bank.dedup_log_messages(),
+                    log_program_filter: bank.log_program_filter(),
+                    profile_all_transactions: bank.profile_all_transactions(),
+                    profile_return_data_bytes_limit: None,
+                    profile_verbosity: bank.profile_verbosity(),
+                    implicit_profiling_sections: bank.implicit_profiling_sections(),
+                    profile_cpis: bank.profile_cpis(),
+                    instrumentation_observer: None,
                     limit_to_load_programs: true,
                     recording_config: ExecutionRecordingConfig::new_single_setting(
                         transaction_status_sender_enabled
@@ -1636,8 +1651,11 @@ mod tests {
             Arc::new(AtomicU64::default()),
             true,
             None,
+            None,
+            None,
             blockstore.clone(),
             false,
+            false,
             None, // no work dependency tracker
             tss_exit.clone(),
         );
@@ -1782,8 +1800,11 @@ mod tests {
             Arc::new(AtomicU64::default()),
             true,
             None,
+            None,
+            None,
             blockstore.clone(),
             false,
+            false,
             None, // no work dependency tracker
             tss_exit.clone(),
         );
@@ -1842,6 +1863,8 @@ mod tests {
                 loaded_addresses: sanitized_tx.get_loaded_addresses(),
                 compute_units_consumed: Some(0),
                 cost_units: Some(tx_cost.sum()),
+                profile: None,
+                instruction_compute_units_consumed: None,
                 ..TransactionStatusMeta::default()
             }
         );
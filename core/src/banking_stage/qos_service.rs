@@ -236,6 +236,51 @@ impl QosService {
         cost_tracker.sub_transactions_in_flight(num_included);
     }
 
+    /// Compare the program compute units reserved for a batch against the units consumed by its
+    /// committed transactions, so that batch sizing can be tuned against actual usage.
+    /// Returns the scheduled and consumed compute units of the batch.
+    pub fn accumulate_batch_compute_units<'a, Tx: TransactionWithMeta + 'a>(
+        &self,
+        transaction_cost_results: impl Iterator<Item = &'a transaction::Result<TransactionCost<'a, Tx>>>,
+        transaction_committed_status: Option<&Vec<CommitTransactionDetails>>,
+    ) -> (u64, u64) {
+        let mut scheduled_cu = Saturating(0u64);
+        let mut consumed_cu = Saturating(0u64);
+        let mut committed_status = transaction_committed_status.into_iter().flatten();
+        for tx_cost in transaction_cost_results {
+            let committed_details = committed_status.next();
+            let Ok(tx_cost) = tx_cost else {
+                continue;
+            };
+            scheduled_cu += tx_cost.programs_execution_cost();
+            if let Some(CommitTransactionDetails::Committed { compute_units, .. }) =
+                committed_details
+            {
+                consumed_cu += *compute_units;
+            }
+        }
+        let (Saturating(scheduled_cu), Saturating(consumed_cu)) = (scheduled_cu, consumed_cu);
+
+        let stats = &self.metrics.stats;
+        stats.batch_count.fetch_add(1, Ordering::Relaxed);
+        stats
+            .batch_scheduled_cu
+            .fetch_add(scheduled_cu, Ordering::Relaxed);
+        stats
+            .batch_consumed_cu
+            .fetch_add(consumed_cu, Ordering::Relaxed);
+        stats
+            .max_batch_scheduled_cu
+            .fetch_max(scheduled_cu, Ordering::Relaxed);
+        stats
+            .max_batch_consumed_cu
+            .fetch_max(consumed_cu, Ordering::Relaxed);
+        stats
+            .max_batch_unused_cu
+            .fetch_max(scheduled_cu.saturating_sub(consumed_cu), Ordering::Relaxed);
+        (scheduled_cu, consumed_cu)
+    }
+
     // metrics are reported by bank slot
     pub fn report_metrics(&self, slot: Slot) {
         self.metrics.report(slot);
@@ -439,6 +484,24 @@ struct QosServiceMetricsStats {
 
     /// accumulated actual program execute micro-sec that have been packed into block
     actual_execute_time_us: AtomicU64,
+
+    /// number of batches processed
+    batch_count: AtomicU64,
+
+    /// accumulated program Compute Units reserved for the transactions selected in each batch
+    batch_scheduled_cu: AtomicU64,
+
+    /// accumulated program Compute Units consumed by the committed transactions of each batch
+    batch_consumed_cu: AtomicU64,
+
+    /// largest program Compute Units reserved by a single batch
+    max_batch_scheduled_cu: AtomicU64,
+
+    /// largest program Compute Units consumed by a single batch
+    max_batch_consumed_cu: AtomicU64,
+
+    /// largest difference between reserved and consumed program Compute Units of a single batch
+    max_batch_unused_cu: AtomicU64,
 }
 
 #[derive(Debug, Default)]
@@ -541,6 +604,38 @@ impl QosServiceMetrics {
                     self.stats.actual_execute_time_us.swap(0, Ordering::Relaxed),
                     i64
                 ),
+                (
+                    "batch_count",
+                    self.stats.batch_count.swap(0, Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "batch_scheduled_cu",
+                    self.stats.batch_scheduled_cu.swap(0, Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "batch_consumed_cu",
+                    self.stats.batch_consumed_cu.swap(0, Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "max_batch_scheduled_cu",
+                    self.stats
+                        .max_batch_scheduled_cu
+                        .swap(0, Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "max_batch_consumed_cu",
+                    self.stats.max_batch_consumed_cu.swap(0, Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "max_batch_unused_cu",
+                    self.stats.max_batch_unused_cu.swap(0, Ordering::Relaxed),
+                    i64
+                ),
             );
             datapoint_info!(
                 "qos-service-errors",
@@ -919,6 +1014,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_accumulate_batch_compute_units() {
+        solana_logger::setup();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+
+        let keypair = Keypair::new();
+        let transfer_tx = RuntimeTransaction::from_transaction_for_tests(
+            system_transaction::transfer(&keypair, &keypair.pubkey(), 1, Hash::default()),
+        );
+        let txs = vec![transfer_tx.clone(), transfer_tx.clone(), transfer_tx];
+
+        let qos_service = QosService::new(1);
+        let txs_costs = qos_service.compute_transaction_costs(
+            &FeatureSet::all_enabled(),
+            txs.iter(),
+            vec![Ok(()), Ok(()), Err(TransactionError::AccountInUse)].into_iter(),
+        );
+        let (qos_cost_results, _num_included) =
+            qos_service.select_transactions_per_cost(txs.iter(), txs_costs.into_iter(), &bank);
+        let programs_execution_cost = qos_cost_results[0]
+            .as_ref()
+            .unwrap()
+            .programs_execution_cost();
+
+        // first transaction is committed using fewer units than reserved, second is not committed
+        let consumed_units = programs_execution_cost - 10;
+        let committed_status = vec![
+            CommitTransactionDetails::Committed {
+                compute_units: consumed_units,
+                loaded_accounts_data_size: 0,
+                result: Ok(()),
+                fee_payer_post_balance: 0,
+            },
+            CommitTransactionDetails::NotCommitted(TransactionError::AccountInUse),
+            CommitTransactionDetails::NotCommitted(TransactionError::AccountInUse),
+        ];
+        assert_eq!(
+            qos_service
+                .accumulate_batch_compute_units(qos_cost_results.iter(), Some(&committed_status)),
+            (2 * programs_execution_cost, consumed_units)
+        );
+
+        // nothing is consumed if the batch was not recorded
+        assert_eq!(
+            qos_service.accumulate_batch_compute_units(qos_cost_results.iter(), None),
+            (2 * programs_execution_cost, 0)
+        );
+
+        let stats = &qos_service.metrics.stats;
+        assert_eq!(stats.batch_count.load(Ordering::Relaxed), 2);
+        assert_eq!(
+            stats.batch_scheduled_cu.load(Ordering::Relaxed),
+            4 * programs_execution_cost
+        );
+        assert_eq!(
+            stats.batch_consumed_cu.load(Ordering::Relaxed),
+            consumed_units
+        );
+        assert_eq!(
+            stats.max_batch_scheduled_cu.load(Ordering::Relaxed),
+            2 * programs_execution_cost
+        );
+        assert_eq!(
+            stats.max_batch_consumed_cu.load(Ordering::Relaxed),
+            consumed_units
+        );
+        assert_eq!(
+            stats.max_batch_unused_cu.load(Ordering::Relaxed),
+            2 * programs_execution_cost
+        );
+    }
+
     #[test]
     fn test_accumulate_batched_transaction_costs() {
         let signature_cost = 1;
@@ -96,6 +96,8 @@ use {
             BankNotificationSenderConfig, OptimisticallyConfirmedBank,
             OptimisticallyConfirmedBankTracker,
         },
+        profile_histogram::ProfileHistogramRegistry,
+        profile_sink_interface::ProfileSinkArc,
         rpc::JsonRpcConfig,
         rpc_completed_slots_service::RpcCompletedSlotsService,
         rpc_pubsub_service::{PubSubConfig, PubSubService},
@@ -502,6 +504,7 @@ struct TransactionHistoryServices {
     transaction_status_sender: Option<TransactionStatusSender>,
     transaction_status_service: Option<TransactionStatusService>,
     max_complete_transaction_status_slot: Arc<AtomicU64>,
+    profile_histograms: Option<Arc<ProfileHistogramRegistry>>,
 }
 
 /// A struct easing passing Validator TPU Configurations
@@ -797,6 +800,7 @@ impl Validator {
                 transaction_status_sender,
                 transaction_status_service,
                 max_complete_transaction_status_slot,
+                profile_histograms,
             },
             blockstore_process_options,
             blockstore_root_scan,
@@ -1209,6 +1213,7 @@ impl Validator {
                 leader_schedule_cache: leader_schedule_cache.clone(),
                 max_complete_transaction_status_slot: max_complete_transaction_status_slot.clone(),
                 prioritization_fee_cache: prioritization_fee_cache.clone(),
+                profile_histograms: profile_histograms.clone(),
                 client_option,
             };
             let json_rpc_service =
@@ -1721,6 +1726,7 @@ impl Validator {
             cluster_slots,
             node: Some(node_multihoming),
             banking_stage: tpu.banking_stage(),
+            profile_histograms,
         });
 
         Ok(Self {
@@ -2138,9 +2144,12 @@ fn load_blockstore(
                 exit.clone(),
                 enable_rpc_transaction_history,
                 config.rpc_config.enable_extended_tx_metadata_storage,
+                config.rpc_config.enable_profile_persistence,
+                enable_rpc_transaction_history && config.rpc_config.enable_profile_leaderboard,
+                config.rpc_config.profile_sqlite_path.as_deref(),
                 transaction_notifier,
                 dependency_tracker,
-            )
+            )?
         } else {
             TransactionHistoryServices::default()
         };
@@ -2558,31 +2567,56 @@ fn initialize_rpc_transaction_history_services(
     exit: Arc<AtomicBool>,
     enable_rpc_transaction_history: bool,
     enable_extended_tx_metadata_storage: bool,
+    enable_profile_persistence: bool,
+    enable_profile_leaderboard: bool,
+    profile_sqlite_path: Option<&Path>,
     transaction_notifier: Option<TransactionNotifierArc>,
     dependency_tracker: Option<Arc<DependencyTracker>>,
-) -> TransactionHistoryServices {
+) -> Result<TransactionHistoryServices, String> {
     let max_complete_transaction_status_slot = Arc::new(AtomicU64::new(blockstore.max_root()));
     let (transaction_status_sender, transaction_status_receiver) = unbounded();
     let transaction_status_sender = Some(TransactionStatusSender {
         sender: transaction_status_sender,
         dependency_tracker: dependency_tracker.clone(),
     });
+    let profile_histograms =
+        enable_profile_leaderboard.then(|| Arc::new(ProfileHistogramRegistry::default()));
+    let profile_sink = profile_sqlite_path.map(open_profile_sink).transpose()?;
     let transaction_status_service = Some(TransactionStatusService::new(
         transaction_status_receiver,
         max_complete_transaction_status_slot.clone(),
         enable_rpc_transaction_history,
         transaction_notifier,
+        profile_histograms.clone(),
+        profile_sink,
         blockstore.clone(),
         enable_extended_tx_metadata_storage,
+        enable_profile_persistence,
         dependency_tracker,
         exit.clone(),
     ));
 
-    TransactionHistoryServices {
+    Ok(TransactionHistoryServices {
         transaction_status_sender,
         transaction_status_service,
         max_complete_transaction_status_slot,
-    }
+        profile_histograms,
+    })
+}
+
+#[cfg(feature = "profile-sqlite")]
+fn open_profile_sink(path: &Path) -> Result<ProfileSinkArc, String> {
+    solana_rpc::profile_sqlite_sink::ProfileSqliteSink::open(path)
+        .map(|sink| Arc::new(sink) as ProfileSinkArc)
+        .map_err(|err| format!("Unable to open profile database {}: {err}", path.display()))
+}
+
+#[cfg(not(feature = "profile-sqlite"))]
+fn open_profile_sink(path: &Path) -> Result<ProfileSinkArc, String> {
+    Err(format!(
+        "Unable to open profile database {}: built without the profile-sqlite feature",
+        path.display()
+    ))
 }
 
 #[derive(Error, Debug)]
@@ -317,8 +317,11 @@ pub fn load_and_process_ledger(
                 Arc::default(),
                 enable_rpc_transaction_history,
                 transaction_notifier,
+                None,
+                None,
                 write_blockstore.clone(),
                 arg_matches.is_present("enable_extended_tx_metadata_storage"),
+                arg_matches.is_present("enable_profile_persistence"),
                 None,
                 tss_exit.clone(),
             );
@@ -1156,6 +1156,16 @@ fn main() {
                              historical transaction info stored",
                         ),
                 )
+                .arg(
+                    Arg::with_name("enable_profile_persistence")
+                        .long("enable-profile-persistence")
+                        .requires("enable_rpc_transaction_history")
+                        .takes_value(false)
+                        .help(
+                            "Include the profile report of each transaction in the historical \
+                             transaction info stored",
+                        ),
+                )
                 .arg(
                     Arg::with_name("run_final_hash_calc")
                         .long("run-final-accounts-hash-calculation")
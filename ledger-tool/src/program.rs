@@ -1,8 +1,9 @@
 use {
     crate::{args::*, canonicalize_ledger_path, ledger_utils::*},
     agave_syscalls::create_program_runtime_environment_v1,
-    clap::{App, AppSettings, Arg, ArgMatches, SubCommand},
+    clap::{value_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand},
     log::*,
+    prost::Message,
     serde_derive::{Deserialize, Serialize},
     serde_json::Result,
     solana_account::{
@@ -14,11 +15,19 @@ use {
     solana_ledger::blockstore_options::AccessType,
     solana_loader_v3_interface::state::UpgradeableLoaderState,
     solana_program_runtime::{
+        csv::profile_csv,
+        folded_stacks::{folded_stacks, folded_stacks_with_heap},
         invoke_context::InvokeContext,
         loaded_programs::{
             LoadProgramMetrics, ProgramCacheEntryType, DELAY_VISIBILITY_SLOT_OFFSET,
         },
+        profile_diff::{check_budgets, profile_diff, SectionBudgets, SectionDiff},
+        profile_report::{ProfileReport, TransactionMetadata},
+        profile_rollup::{profile_rollup, ProfileRollup},
+        profiling::{InstructionProfile, ProfileEntry, ProfileVerbosity},
+        protobuf::{generated, transaction_profile},
         serialization::serialize_parameters,
+        symbolize::Symbolizer,
         with_mock_invoke_context,
     },
     solana_pubkey::Pubkey,
@@ -28,9 +37,9 @@ use {
         verifier::RequisiteVerifier,
     },
     solana_sdk_ids::{bpf_loader_upgradeable, sysvar},
-    solana_transaction_context::{IndexOfAccount, InstructionAccount},
+    solana_transaction_context::{IndexOfAccount, InstructionAccount, TransactionAccount},
     std::{
-        collections::HashMap,
+        collections::{BTreeMap, HashMap},
         fmt::{self, Debug, Formatter},
         fs::File,
         io::{Read, Seek, Write},
@@ -121,6 +130,50 @@ impl ProgramSubCommand for App<'_, '_> {
                 .about("dumps disassembled code of the program.")
                 .arg(&program_arg)
         )
+        .subcommand(
+            SubCommand::with_name("diff-profiles")
+                .about(
+                    "Compares the profiling sections of two profile reports written by 'run \
+                     --profile-output', largest compute unit regression first.",
+                )
+                .arg(
+                    Arg::with_name("BASE")
+                        .help("Profile to compare against")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("HEAD")
+                        .help("Profile to compare")
+                        .required(true)
+                        .index(2),
+                )
+                .arg(
+                    Arg::with_name("budgets")
+                        .help(
+                            "JSON or TOML file of per-section compute unit budgets, exit with \
+                             an error if a section of HEAD exceeds its budget by more than the \
+                             tolerance",
+                        )
+                        .long("budgets")
+                        .takes_value(true)
+                        .value_name("FILE"),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("aggregate-profiles")
+                .about(
+                    "Adds up the profile reports written by 'run --profile-output', or by a \
+                     test suite, per program and per profiling section.",
+                )
+                .arg(
+                    Arg::with_name("PROFILES")
+                        .help("Profiles to aggregate, in the order in which they were recorded")
+                        .required(true)
+                        .multiple(true)
+                        .index(1),
+                )
+        )
         .subcommand(
             SubCommand::with_name("run")
                 .about(
@@ -206,6 +259,141 @@ and the following fields are required
                         .takes_value(true)
                         .value_name("FILE"),
                 )
+                .arg(
+                    Arg::with_name("folded_stacks")
+                        .help(
+                            "Output the profiling sections of the program as folded stacks, \
+                             which flamegraph tools take as input",
+                        )
+                        .long("folded-stacks")
+                        .takes_value(true)
+                        .value_name("FILE"),
+                )
+                .arg(
+                    Arg::with_name("folded_stacks_heap")
+                        .help(
+                            "Add the heap bytes allocated by each folded stack as a second \
+                             weight, as differential flamegraph tools take it",
+                        )
+                        .long("folded-stacks-heap")
+                        .requires("folded_stacks"),
+                )
+                .arg(
+                    Arg::with_name("profile_csv")
+                        .help(
+                            "Output the profiling entries of the program as CSV, along with the \
+                             heap usage of each section",
+                        )
+                        .long("profile-csv")
+                        .takes_value(true)
+                        .value_name("FILE"),
+                )
+                .arg(
+                    Arg::with_name("profile_output")
+                        .help(
+                            "Write the profile report of the program to FILE, which \
+                             'diff-profiles' compares. Encoded as JSON if FILE ends with .json, \
+                             as protobuf otherwise",
+                        )
+                        .long("profile-output")
+                        .takes_value(true)
+                        .value_name("FILE"),
+                )
+                .arg(
+                    Arg::with_name("symbolize")
+                        .help(
+                            "Resolve the call sites of the profiling entries and the frames of \
+                             logged backtraces to source locations, using the DWARF debug info \
+                             of the program's ELF",
+                        )
+                        .long("symbolize")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("repeat")
+                        .help(
+                            "Execute the program COUNT times, each time against the same input \
+                             accounts, and report the variance of the compute units consumed by \
+                             the program and by each of its profiling sections",
+                        )
+                        .long("repeat")
+                        .takes_value(true)
+                        .value_name("COUNT")
+                        .default_value("1"),
+                )
+                .arg(
+                    Arg::with_name("warmup")
+                        .help(
+                            "Number of executions to run before the measured ones and exclude \
+                             from the report, so that the first measured execution does not run \
+                             with cold caches",
+                        )
+                        .long("warmup")
+                        .takes_value(true)
+                        .value_name("COUNT")
+                        .default_value("0"),
+                )
+                .arg(
+                    Arg::with_name("metered_profiling")
+                        .help(
+                            "Charge the syscall base cost for each profiling section and marker \
+                             syscall, as if the instrumentation were implemented by the program, \
+                             and remove those charges from the reported sections",
+                        )
+                        .long("metered-profiling")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("profile_cpis")
+                        .help(
+                            "Record a profiling section named after the callee around every CPI, \
+                             even outside of the sections opened by the program",
+                        )
+                        .long("profile-cpis")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("implicit_profiling_sections")
+                        .help(
+                            "Record a profiling section named after the program around every \
+                             instruction and CPI, so that uninstrumented programs are profiled \
+                             too",
+                        )
+                        .long("implicit-profiling-sections")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("json_profile_logs")
+                        .help(
+                            "Log every completed profiling entry as a single line JSON object \
+                             instead of the text format",
+                        )
+                        .long("json-profile-logs")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("profile_summary")
+                        .help(
+                            "Log the COUNT profiling sections which consumed the most compute \
+                             units of their own, with their share of the instruction, instead of \
+                             every completed entry",
+                        )
+                        .long("profile-summary")
+                        .takes_value(true)
+                        .value_name("COUNT")
+                        .conflicts_with("json_profile_logs"),
+                )
+                .arg(
+                    Arg::with_name("profile_tree")
+                        .help(
+                            "Log the profiling sections as an indented tree, with the compute \
+                             units of each section including and excluding its nested sections, \
+                             instead of every completed entry",
+                        )
+                        .long("profile-tree")
+                        .takes_value(false)
+                        .conflicts_with_all(&["json_profile_logs", "profile_summary"]),
+                )
                 .arg(&program_arg)
         )
         )
@@ -236,6 +424,332 @@ impl fmt::Display for Output {
 impl QuietDisplay for Output {}
 impl VerboseDisplay for Output {}
 
+/// Maximum number of buckets of a [`Samples::histogram`]
+const MAX_HISTOGRAM_BUCKETS: u64 = 10;
+
+/// Width of the bar of the fullest histogram bucket in characters
+const HISTOGRAM_BAR_WIDTH: usize = 40;
+
+/// Values of the measured executions within `start..=end`
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct HistogramBucket {
+    start: u64,
+    end: u64,
+    count: usize,
+}
+
+/// Distribution of a value across the measured executions
+#[derive(Serialize)]
+struct Samples {
+    samples: usize,
+    min: u64,
+    max: u64,
+    mean: f64,
+    variance: f64,
+    /// Buckets of equal width covering `min..=max`
+    histogram: Vec<HistogramBucket>,
+}
+
+impl Samples {
+    fn new(values: &[u64]) -> Self {
+        let samples = values.len();
+        let mean = values.iter().map(|value| *value as f64).sum::<f64>() / samples.max(1) as f64;
+        let variance = values
+            .iter()
+            .map(|value| (*value as f64 - mean).powi(2))
+            .sum::<f64>()
+            / samples.max(1) as f64;
+        let min = values.iter().copied().min().unwrap_or_default();
+        let max = values.iter().copied().max().unwrap_or_default();
+        Self {
+            samples,
+            min,
+            max,
+            mean,
+            variance,
+            histogram: Self::histogram(values, min, max),
+        }
+    }
+
+    fn histogram(values: &[u64], min: u64, max: u64) -> Vec<HistogramBucket> {
+        if values.is_empty() {
+            return Vec::new();
+        }
+        let range = max.saturating_sub(min).saturating_add(1);
+        let width = range.div_ceil(MAX_HISTOGRAM_BUCKETS).max(1);
+        let mut histogram: Vec<_> = (0..range.div_ceil(width))
+            .map(|index| {
+                let start = min.saturating_add(index.saturating_mul(width));
+                HistogramBucket {
+                    start,
+                    end: start.saturating_add(width.saturating_sub(1)).min(max),
+                    count: 0,
+                }
+            })
+            .collect();
+        for value in values {
+            let index = value.saturating_sub(min).checked_div(width).unwrap_or(0);
+            if let Some(bucket) = histogram.get_mut(index as usize) {
+                bucket.count = bucket.count.saturating_add(1);
+            }
+        }
+        histogram
+    }
+
+    fn is_constant(&self) -> bool {
+        self.min == self.max
+    }
+
+    /// One line per histogram bucket, with a bar proportional to its count
+    fn fmt_histogram(&self, f: &mut Formatter<'_>, indent: &str) -> std::fmt::Result {
+        let max_count = self
+            .histogram
+            .iter()
+            .map(|bucket| bucket.count)
+            .max()
+            .unwrap_or_default();
+        for bucket in &self.histogram {
+            let bar = bucket
+                .count
+                .saturating_mul(HISTOGRAM_BAR_WIDTH)
+                .checked_div(max_count)
+                .unwrap_or(0);
+            writeln!(
+                f,
+                "{indent}{:>10}..={:<10} {:>6} {}",
+                bucket.start,
+                bucket.end,
+                bucket.count,
+                "#".repeat(bar),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Samples {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "min {} max {} mean {:.2} variance {:.2}",
+            self.min, self.max, self.mean, self.variance
+        )
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SectionVariance {
+    name: String,
+    /// Distinguishes sections with the same name opened more than once per execution
+    occurrence: usize,
+    consumed_cu: Samples,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VarianceReport {
+    runs: usize,
+    warmup_runs: usize,
+    deterministic: bool,
+    results: Vec<String>,
+    instruction_count: Samples,
+    execution_time_us: Samples,
+    sections: Vec<SectionVariance>,
+}
+
+impl VarianceReport {
+    fn new(executions: &[Execution], warmup_runs: usize) -> Self {
+        let runs = executions.len();
+        let mut results: Vec<String> = Vec::new();
+        let mut section_values: Vec<(String, usize, Vec<u64>)> = Vec::new();
+        for execution in executions {
+            if !results.contains(&execution.output.result) {
+                results.push(execution.output.result.clone());
+            }
+            let mut occurrences = HashMap::<&str, usize>::new();
+            for entry in &execution.entries {
+                let occurrence = occurrences.entry(entry.name.as_str()).or_default();
+                match section_values
+                    .iter_mut()
+                    .find(|(name, nth, _)| *name == entry.name && *nth == *occurrence)
+                {
+                    Some((_, _, values)) => values.push(entry.consumed()),
+                    None => section_values.push((
+                        entry.name.clone(),
+                        *occurrence,
+                        vec![entry.consumed()],
+                    )),
+                }
+                *occurrence = occurrence.saturating_add(1);
+            }
+        }
+        let instruction_count = Samples::new(
+            &executions
+                .iter()
+                .map(|execution| execution.output.instruction_count)
+                .collect::<Vec<_>>(),
+        );
+        let execution_time_us = Samples::new(
+            &executions
+                .iter()
+                .map(|execution| execution.output.execution_time.as_micros() as u64)
+                .collect::<Vec<_>>(),
+        );
+        let sections: Vec<_> = section_values
+            .into_iter()
+            .map(|(name, occurrence, values)| SectionVariance {
+                name,
+                occurrence,
+                consumed_cu: Samples::new(&values),
+            })
+            .collect();
+        // Wall clock time is expected to vary, everything metered must not
+        let deterministic = results.len() <= 1
+            && instruction_count.is_constant()
+            && sections.iter().all(|section| {
+                section.consumed_cu.samples == runs && section.consumed_cu.is_constant()
+            });
+        Self {
+            runs,
+            warmup_runs,
+            deterministic,
+            results,
+            instruction_count,
+            execution_time_us,
+            sections,
+        }
+    }
+}
+
+impl fmt::Display for VarianceReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Runs: {} ({} warm-up runs excluded)",
+            self.runs, self.warmup_runs
+        )?;
+        writeln!(f, "Results: {}", self.results.join(", "))?;
+        writeln!(f, "Instruction Count: {}", self.instruction_count)?;
+        writeln!(f, "Execution time (us): {}", self.execution_time_us)?;
+        if !self.sections.is_empty() {
+            writeln!(f, "Sections (consumed compute units):")?;
+        }
+        for section in &self.sections {
+            let consistent =
+                section.consumed_cu.samples == self.runs && section.consumed_cu.is_constant();
+            writeln!(
+                f,
+                "  {}#{}: {} in {} runs{}",
+                section.name,
+                section.occurrence,
+                section.consumed_cu,
+                section.consumed_cu.samples,
+                if consistent {
+                    ""
+                } else {
+                    " (nondeterministic)"
+                },
+            )?;
+            // The distribution shows whether outliers or a spread cause the variance
+            if !section.consumed_cu.is_constant() {
+                section.consumed_cu.fmt_histogram(f, "    ")?;
+            }
+        }
+        if self.deterministic {
+            writeln!(f, "Metering is deterministic")
+        } else {
+            writeln!(f, "Metering is NOT deterministic")
+        }
+    }
+}
+
+impl QuietDisplay for VarianceReport {}
+impl VerboseDisplay for VarianceReport {}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SectionDiffOutput {
+    program_id: String,
+    name: String,
+    base_cu: Option<u64>,
+    head_cu: Option<u64>,
+    delta_cu: i128,
+    /// Relative to the base profile in hundredths of a percent, `None` for added sections
+    delta_basis_points: Option<i128>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileDiffOutput {
+    /// Ordered from the largest regression to the largest improvement
+    sections: Vec<SectionDiffOutput>,
+}
+
+impl ProfileDiffOutput {
+    fn new(sections: &[SectionDiff]) -> Self {
+        Self {
+            sections: sections
+                .iter()
+                .map(|section| SectionDiffOutput {
+                    program_id: section.program_id.to_string(),
+                    name: section.name.clone(),
+                    base_cu: section.base_cu,
+                    head_cu: section.head_cu,
+                    delta_cu: section.delta_cu(),
+                    delta_basis_points: section.delta_basis_points(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for ProfileDiffOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Sections (consumed compute units, base -> head):")?;
+        let cu = |cu: Option<u64>| cu.map_or_else(|| "-".to_string(), |cu| cu.to_string());
+        for section in &self.sections {
+            let change = match (section.base_cu, section.head_cu, section.delta_basis_points) {
+                (None, _, _) => "added".to_string(),
+                (_, None, _) => "removed".to_string(),
+                (_, _, Some(basis_points)) => {
+                    let sign = if basis_points < 0 { "-" } else { "+" };
+                    let basis_points = basis_points.unsigned_abs();
+                    format!("{sign}{}.{:02}%", basis_points / 100, basis_points % 100)
+                }
+                (_, _, None) => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "  {:>+9} {:>9} {:>9} -> {:<9} {}: {}",
+                section.delta_cu,
+                change,
+                cu(section.base_cu),
+                cu(section.head_cu),
+                section.program_id,
+                section.name,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl QuietDisplay for ProfileDiffOutput {}
+impl VerboseDisplay for ProfileDiffOutput {}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct ProfileRollupOutput(ProfileRollup);
+
+impl fmt::Display for ProfileRollupOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl QuietDisplay for ProfileRollupOutput {}
+impl VerboseDisplay for ProfileRollupOutput {}
+
 // Replace with std::lazy::Lazy when stabilized.
 // https://github.com/rust-lang/rust/issues/74465
 struct LazyAnalysis<'a, 'b> {
@@ -390,6 +904,39 @@ pub fn program(ledger_path: &Path, matches: &ArgMatches<'_>) {
             process_static_action(Action::Dis, arg_matches);
             return;
         }
+        ("diff-profiles", Some(arg_matches)) => {
+            let base = load_profile_report(Path::new(arg_matches.value_of("BASE").unwrap()));
+            let head = load_profile_report(Path::new(arg_matches.value_of("HEAD").unwrap()));
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+            let diff = profile_diff(&base.instructions, &head.instructions);
+            println!(
+                "{}",
+                output_format.formatted_string(&ProfileDiffOutput::new(&diff))
+            );
+            if let Some(budgets) = arg_matches.value_of("budgets") {
+                let budgets = load_section_budgets(Path::new(budgets));
+                if let Err(violations) = check_budgets(&diff, &budgets) {
+                    for violation in violations {
+                        eprintln!("Budget exceeded: {violation}");
+                    }
+                    exit(1);
+                }
+            }
+            return;
+        }
+        ("aggregate-profiles", Some(arg_matches)) => {
+            let reports: Vec<_> = arg_matches
+                .values_of("PROFILES")
+                .unwrap()
+                .map(|path| load_profile_report(Path::new(path)))
+                .collect();
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+            println!(
+                "{}",
+                output_format.formatted_string(&ProfileRollupOutput(profile_rollup(&reports)))
+            );
+            return;
+        }
         ("run", Some(arg_matches)) => arg_matches,
         _ => unreachable!(),
     };
@@ -502,6 +1049,189 @@ pub fn program(ledger_path: &Path, matches: &ArgMatches<'_>) {
         sysvar::epoch_schedule::id(),
         create_account_shared_data_for_test(bank.epoch_schedule()),
     ));
+
+    let repeat = value_t_or_exit!(matches, "repeat", usize).max(1);
+    let warmup = value_t_or_exit!(matches, "warmup", usize);
+    let run = |output_traces| {
+        execute(
+            matches,
+            &bank,
+            program_id,
+            program_index,
+            transaction_accounts.clone(),
+            instruction_accounts.clone(),
+            &instruction_data,
+            &cached_account_keys,
+            output_traces,
+        )
+    };
+    let output_format = OutputFormat::from_matches(matches, "output_format", false);
+    let symbolizer = matches
+        .is_present("symbolize")
+        .then(|| load_symbolizer(Path::new(matches.value_of("PROGRAM").unwrap())));
+    if repeat == 1 && warmup == 0 {
+        let mut execution = run(matches.occurrences_of("trace") > 0);
+        if let Some(symbolizer) = &symbolizer {
+            for message in execution.output.log.iter_mut() {
+                if let Some(symbolized) = symbolizer.symbolize_backtrace_frame(program_id, message)
+                {
+                    *message = symbolized;
+                }
+            }
+        }
+        println!("{}", output_format.formatted_string(&execution.output));
+        output_profiles(matches, program_id, &execution.entries, symbolizer.as_ref());
+        return;
+    }
+    for _ in 0..warmup {
+        run(false);
+    }
+    let executions: Vec<_> = (0..repeat)
+        .map(|index| run(index == 0 && matches.occurrences_of("trace") > 0))
+        .collect();
+    let report = VarianceReport::new(&executions, warmup);
+    println!("{}", output_format.formatted_string(&report));
+    if let Some(execution) = executions.first() {
+        output_profiles(matches, program_id, &execution.entries, symbolizer.as_ref());
+    }
+}
+
+fn load_symbolizer(path: &Path) -> Symbolizer {
+    let elf = std::fs::read(path).unwrap_or_else(|err| {
+        eprintln!("Failed to read program {}: {err}", path.display());
+        exit(1);
+    });
+    Symbolizer::new(&elf).unwrap_or_else(|err| {
+        eprintln!("Failed to symbolize program {}: {err}", path.display());
+        exit(1);
+    })
+}
+
+fn output_profiles(
+    matches: &ArgMatches<'_>,
+    program_id: Pubkey,
+    entries: &[ProfileEntry],
+    symbolizer: Option<&Symbolizer>,
+) {
+    let profile = InstructionProfile {
+        program_id,
+        stack_height: 1,
+        entries: entries.to_vec(),
+        ..InstructionProfile::default()
+    };
+    let mut report = ProfileReport::new(TransactionMetadata::default(), vec![profile], vec![]);
+    if let Some(symbolizer) = symbolizer {
+        symbolizer.symbolize_report(&mut report, &program_id);
+    }
+    output_folded_stacks(matches, &report.instructions);
+    if let Some(filename) = matches.value_of("profile_csv") {
+        let csv = profile_csv(&report.instructions);
+        if filename == "stdout" {
+            print!("{csv}");
+        } else {
+            File::create(filename)
+                .unwrap()
+                .write_all(csv.as_bytes())
+                .unwrap();
+        }
+    }
+    if let Some(filename) = matches.value_of("profile_output") {
+        let bytes = if is_json_path(Path::new(filename)) {
+            report.to_json().into_bytes()
+        } else {
+            transaction_profile(&report).encode_to_vec()
+        };
+        File::create(filename).unwrap().write_all(&bytes).unwrap();
+    }
+}
+
+fn is_json_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "json")
+}
+
+fn load_profile_report(path: &Path) -> ProfileReport {
+    let mut bytes = Vec::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut bytes))
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to read profile {}: {err}", path.display());
+            exit(1);
+        });
+    let report = if is_json_path(path) {
+        String::from_utf8(bytes)
+            .map_err(|err| err.to_string())
+            .and_then(|json| ProfileReport::from_json(&json).map_err(|err| err.to_string()))
+    } else {
+        generated::TransactionProfile::decode(bytes.as_slice())
+            .map_err(|err| err.to_string())
+            .and_then(|artifact| ProfileReport::try_from(artifact).map_err(str::to_string))
+    };
+    report.unwrap_or_else(|err| {
+        eprintln!("Invalid profile {}: {err}", path.display());
+        exit(1);
+    })
+}
+
+fn load_section_budgets(path: &Path) -> SectionBudgets {
+    let budgets = std::fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Failed to read budgets {}: {err}", path.display());
+        exit(1);
+    });
+    let budgets = if path
+        .extension()
+        .is_some_and(|extension| extension == "toml")
+    {
+        toml::from_str(&budgets).map_err(|err| err.to_string())
+    } else {
+        serde_json::from_str(&budgets).map_err(|err| err.to_string())
+    };
+    budgets.unwrap_or_else(|err| {
+        eprintln!("Invalid budgets {}: {err}", path.display());
+        exit(1);
+    })
+}
+
+fn output_folded_stacks(matches: &ArgMatches<'_>, profiles: &[InstructionProfile]) {
+    let Some(filename) = matches.value_of("folded_stacks") else {
+        return;
+    };
+    let stacks = if matches.is_present("folded_stacks_heap") {
+        folded_stacks_with_heap(profiles)
+    } else {
+        folded_stacks(profiles)
+    };
+    if filename == "stdout" {
+        for stack in stacks {
+            println!("{stack}");
+        }
+    } else {
+        let mut fd = File::create(filename).unwrap();
+        for stack in stacks {
+            writeln!(fd, "{stack}").unwrap();
+        }
+    }
+}
+
+/// Outcome of a single execution of the program
+struct Execution {
+    output: Output,
+    /// Profiling entries recorded by the top level instruction
+    entries: Vec<ProfileEntry>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn execute(
+    matches: &ArgMatches<'_>,
+    bank: &Bank,
+    program_id: Pubkey,
+    program_index: u16,
+    transaction_accounts: Vec<TransactionAccount>,
+    instruction_accounts: Vec<InstructionAccount>,
+    instruction_data: &[u8],
+    cached_account_keys: &[Pubkey],
+    output_traces: bool,
+) -> Execution {
     let interpreted = matches.value_of("mode").unwrap() != "jit";
     with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
 
@@ -510,20 +1240,34 @@ pub fn program(ledger_path: &Path, matches: &ArgMatches<'_>) {
         bank.new_program_cache_for_tx_batch_for_slot(bank.slot() + DELAY_VISIBILITY_SLOT_OFFSET);
     for key in cached_account_keys {
         program_cache_for_tx_batch.replenish(
-            key,
-            bank.load_program(&key, false, bank.epoch())
+            *key,
+            bank.load_program(key, false, bank.epoch())
                 .expect("Couldn't find program account"),
         );
         debug!("Loaded program {key}");
     }
     invoke_context.program_cache_for_tx_batch = &mut program_cache_for_tx_batch;
+    if matches.is_present("metered_profiling") {
+        invoke_context.enable_metered_profiling();
+    }
+    invoke_context.profile_cpis = matches.is_present("profile_cpis");
+    invoke_context.implicit_profiling_sections = matches.is_present("implicit_profiling_sections");
+    if matches.is_present("json_profile_logs") {
+        invoke_context.profile_verbosity = ProfileVerbosity::FullJson;
+    } else if matches.is_present("profile_summary") {
+        invoke_context.profile_verbosity = ProfileVerbosity::Summary;
+        invoke_context.profile_summary_sections =
+            value_t_or_exit!(matches, "profile_summary", usize);
+    } else if matches.is_present("profile_tree") {
+        invoke_context.profile_verbosity = ProfileVerbosity::Tree;
+    }
 
     invoke_context
         .transaction_context
         .configure_next_instruction_for_tests(
             program_index.saturating_add(1),
             instruction_accounts,
-            &instruction_data,
+            instruction_data,
         )
         .unwrap();
     invoke_context.push().unwrap();
@@ -556,7 +1300,7 @@ pub fn program(ledger_path: &Path, matches: &ArgMatches<'_>) {
     vm.registers[1] = MM_INPUT_START;
     let (instruction_count, result) = vm.execute_program(&verified_executable, interpreted);
     let duration = Instant::now() - start_time;
-    if matches.occurrences_of("trace") > 0 {
+    if output_traces {
         // top level trace is stored in syscall_context
         if let Some(Some(syscall_context)) = vm.context_object_pointer.syscall_context.last() {
             let trace = syscall_context.trace_log.as_slice();
@@ -569,6 +1313,17 @@ pub fn program(ledger_path: &Path, matches: &ArgMatches<'_>) {
             output_trace(matches, trace, frame + 1, &mut analysis);
         }
     }
+    let entries = vm
+        .context_object_pointer
+        .syscall_context
+        .last_mut()
+        .and_then(|syscall_context| syscall_context.as_mut())
+        .and_then(|syscall_context| syscall_context.profiling.as_mut())
+        .map(|profiling| {
+            profiling.post_process();
+            profiling.entries().to_vec()
+        })
+        .unwrap_or_default();
     drop(vm);
 
     let output = Output {
@@ -579,9 +1334,161 @@ pub fn program(ledger_path: &Path, matches: &ArgMatches<'_>) {
             .get_log_collector()
             .unwrap()
             .borrow()
-            .get_recorded_content()
-            .to_vec(),
+            .get_recorded_content(),
     };
-    let output_format = OutputFormat::from_matches(matches, "output_format", false);
-    println!("{}", output_format.formatted_string(&output));
+    Execution { output, entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_program_runtime::profiling::ProfileEntryKind};
+
+    fn execution(instruction_count: u64, sections: &[(&str, u64)]) -> Execution {
+        Execution {
+            output: Output {
+                result: "Ok(0)".to_string(),
+                instruction_count,
+                execution_time: Duration::from_micros(instruction_count),
+                log: Vec::new(),
+            },
+            entries: sections
+                .iter()
+                .map(|(name, consumed)| ProfileEntry {
+                    kind: ProfileEntryKind::Section,
+                    name: name.to_string(),
+                    depth: 0,
+                    start_cu: 0,
+                    end_cu: *consumed,
+                    heap_start: None,
+                    heap_end: None,
+                    return_data_bytes: 0,
+                    bulk_closed: false,
+                    attributes: BTreeMap::new(),
+                    call_site: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_samples() {
+        let samples = Samples::new(&[2, 4, 4, 4, 5, 5, 7, 9]);
+        assert_eq!(samples.samples, 8);
+        assert_eq!(samples.min, 2);
+        assert_eq!(samples.max, 9);
+        assert_eq!(samples.mean, 5.0);
+        assert_eq!(samples.variance, 4.0);
+        assert!(!samples.is_constant());
+        assert!(Samples::new(&[3, 3]).is_constant());
+    }
+
+    #[test]
+    fn test_samples_histogram() {
+        let bucket = |start, end, count| HistogramBucket { start, end, count };
+        assert_eq!(
+            Samples::new(&[2, 4, 4, 4, 5, 5, 7, 9]).histogram,
+            vec![
+                bucket(2, 2, 1),
+                bucket(3, 3, 0),
+                bucket(4, 4, 3),
+                bucket(5, 5, 2),
+                bucket(6, 6, 0),
+                bucket(7, 7, 1),
+                bucket(8, 8, 0),
+                bucket(9, 9, 1),
+            ]
+        );
+
+        let histogram = Samples::new(&[100, 105, 119, 200]).histogram;
+        assert_eq!(histogram.len(), 10);
+        assert_eq!(histogram[0], bucket(100, 110, 2));
+        assert_eq!(histogram[1], bucket(111, 121, 1));
+        assert_eq!(histogram[9], bucket(199, 200, 1));
+        assert_eq!(
+            histogram.iter().map(|bucket| bucket.count).sum::<usize>(),
+            4
+        );
+
+        assert_eq!(Samples::new(&[3, 3]).histogram, vec![bucket(3, 3, 2)]);
+        assert!(Samples::new(&[]).histogram.is_empty());
+    }
+
+    #[test]
+    fn test_profile_diff_output() {
+        let program_id = Pubkey::new_unique();
+        let section = |name: &str, base_cu, head_cu| SectionDiff {
+            program_id,
+            name: name.to_string(),
+            base_cu,
+            head_cu,
+        };
+        let output = ProfileDiffOutput::new(&[
+            section("verify", None, Some(60)),
+            section("parse", Some(150), Some(180)),
+            section("transfer", Some(300), Some(270)),
+            section("log", Some(40), None),
+        ])
+        .to_string();
+        let lines: Vec<_> = output.lines().skip(1).collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains("+60") && lines[0].contains("added"));
+        assert!(lines[0].ends_with(&format!("{program_id}: verify")));
+        assert!(lines[1].contains("+30") && lines[1].contains("+20.00%"));
+        assert!(lines[1].contains("150 -> 180"));
+        assert!(lines[2].contains("-30") && lines[2].contains("-10.00%"));
+        assert!(lines[3].contains("-40") && lines[3].contains("removed"));
+        assert!(lines[3].contains("40 -> -"));
+    }
+
+    #[test]
+    fn test_variance_report() {
+        let executions = [
+            execution(100, &[("loop", 10), ("loop", 20), ("hash", 30)]),
+            execution(100, &[("loop", 10), ("loop", 20), ("hash", 30)]),
+        ];
+        let report = VarianceReport::new(&executions, 1);
+        assert!(report.deterministic);
+        assert_eq!(report.warmup_runs, 1);
+        let sections: Vec<_> = report
+            .sections
+            .iter()
+            .map(|section| {
+                (
+                    section.name.as_str(),
+                    section.occurrence,
+                    section.consumed_cu.max,
+                )
+            })
+            .collect();
+        assert_eq!(
+            sections,
+            [("loop", 0, 10), ("loop", 1, 20), ("hash", 0, 30)]
+        );
+
+        // A section consuming a different amount of compute units
+        let executions = [
+            execution(100, &[("loop", 10), ("hash", 30)]),
+            execution(100, &[("loop", 10), ("hash", 31)]),
+        ];
+        let report = VarianceReport::new(&executions, 0);
+        assert!(!report.deterministic);
+        // Only the distribution of the varying section is shown
+        let output = report.to_string();
+        assert!(output.contains("hash#0: min 30 max 31"));
+        assert!(output.contains(&format!(
+            "    {:>10}..={:<10} {:>6} {}\n",
+            30,
+            30,
+            1,
+            "#".repeat(HISTOGRAM_BAR_WIDTH)
+        )));
+        assert_eq!(output.matches("..=").count(), 2);
+
+        // A section missing from one of the runs
+        let executions = [
+            execution(100, &[("loop", 10), ("hash", 30)]),
+            execution(100, &[("loop", 10)]),
+        ];
+        assert!(!VarianceReport::new(&executions, 0).deterministic);
+    }
 }
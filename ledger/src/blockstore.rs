@@ -8048,6 +8048,8 @@ pub mod tests {
                     return_data: Some(TransactionReturnData::default()),
                     compute_units_consumed,
                     cost_units,
+                    profile: None,
+                    instruction_compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -8068,6 +8070,8 @@ pub mod tests {
                     return_data: Some(TransactionReturnData::default()),
                     compute_units_consumed,
                     cost_units,
+                    profile: None,
+                    instruction_compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -8088,6 +8092,8 @@ pub mod tests {
                     return_data: Some(TransactionReturnData::default()),
                     compute_units_consumed,
                     cost_units,
+                    profile: None,
+                    instruction_compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -8110,6 +8116,8 @@ pub mod tests {
                         return_data: Some(TransactionReturnData::default()),
                         compute_units_consumed,
                         cost_units,
+                        profile: None,
+                        instruction_compute_units_consumed: None,
                     },
                 }
             })
@@ -8236,6 +8244,10 @@ pub mod tests {
         let cost_units_1 = Some(1234);
         let compute_units_consumed_2 = Some(42u64);
         let cost_units_2 = Some(5678);
+        let profile_1 = Some(vec![8, 1, 16, 4]);
+        let profile_2 = None;
+        let instruction_compute_units_consumed_1 = Some(vec![3812000, 649]);
+        let instruction_compute_units_consumed_2 = None;
 
         // result not found
         assert!(transaction_status_cf
@@ -8260,6 +8272,8 @@ pub mod tests {
             return_data: Some(test_return_data.clone()),
             compute_units_consumed: compute_units_consumed_1,
             cost_units: cost_units_1,
+            profile: profile_1.clone(),
+            instruction_compute_units_consumed: instruction_compute_units_consumed_1.clone(),
         }
         .into();
         assert!(transaction_status_cf
@@ -8281,6 +8295,8 @@ pub mod tests {
             return_data,
             compute_units_consumed,
             cost_units,
+            profile,
+            instruction_compute_units_consumed,
         } = transaction_status_cf
             .get_protobuf((Signature::default(), 0))
             .unwrap()
@@ -8300,6 +8316,11 @@ pub mod tests {
         assert_eq!(return_data.unwrap(), test_return_data);
         assert_eq!(compute_units_consumed, compute_units_consumed_1);
         assert_eq!(cost_units, cost_units_1);
+        assert_eq!(profile, profile_1);
+        assert_eq!(
+            instruction_compute_units_consumed,
+            instruction_compute_units_consumed_1
+        );
 
         // insert value
         let status = TransactionStatusMeta {
@@ -8316,6 +8337,8 @@ pub mod tests {
             return_data: Some(test_return_data.clone()),
             compute_units_consumed: compute_units_consumed_2,
             cost_units: cost_units_2,
+            profile: profile_2.clone(),
+            instruction_compute_units_consumed: instruction_compute_units_consumed_2.clone(),
         }
         .into();
         assert!(transaction_status_cf
@@ -8337,6 +8360,8 @@ pub mod tests {
             return_data,
             compute_units_consumed,
             cost_units,
+            profile,
+            instruction_compute_units_consumed,
         } = transaction_status_cf
             .get_protobuf((Signature::from([2u8; 64]), 9))
             .unwrap()
@@ -8358,6 +8383,11 @@ pub mod tests {
         assert_eq!(return_data.unwrap(), test_return_data);
         assert_eq!(compute_units_consumed, compute_units_consumed_2);
         assert_eq!(cost_units, cost_units_2);
+        assert_eq!(profile, profile_2);
+        assert_eq!(
+            instruction_compute_units_consumed,
+            instruction_compute_units_consumed_2
+        );
     }
 
     #[test]
@@ -8455,6 +8485,8 @@ pub mod tests {
             return_data: Some(TransactionReturnData::default()),
             compute_units_consumed: Some(42u64),
             cost_units: Some(1234),
+            profile: None,
+            instruction_compute_units_consumed: None,
         }
         .into();
 
@@ -8632,6 +8664,8 @@ pub mod tests {
             return_data: Some(TransactionReturnData::default()),
             compute_units_consumed: Some(42u64),
             cost_units: Some(1234),
+            profile: None,
+            instruction_compute_units_consumed: None,
         }
         .into();
 
@@ -8760,6 +8794,8 @@ pub mod tests {
             return_data: Some(TransactionReturnData::default()),
             compute_units_consumed: Some(42u64),
             cost_units: Some(1234),
+            profile: None,
+            instruction_compute_units_consumed: None,
         }
         .into();
 
@@ -8929,6 +8965,8 @@ pub mod tests {
                     return_data: return_data.clone(),
                     compute_units_consumed: Some(42),
                     cost_units: Some(1234),
+                    profile: None,
+                    instruction_compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -8951,6 +8989,8 @@ pub mod tests {
                         return_data,
                         compute_units_consumed: Some(42),
                         cost_units: Some(1234),
+                        profile: None,
+                        instruction_compute_units_consumed: None,
                     },
                 }
             })
@@ -9052,6 +9092,8 @@ pub mod tests {
                     return_data: return_data.clone(),
                     compute_units_consumed: Some(42u64),
                     cost_units: Some(1234),
+                    profile: None,
+                    instruction_compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -9074,6 +9116,8 @@ pub mod tests {
                         return_data,
                         compute_units_consumed: Some(42u64),
                         cost_units: Some(1234),
+                        profile: None,
+                        instruction_compute_units_consumed: None,
                     },
                 }
             })
@@ -9749,6 +9793,8 @@ pub mod tests {
                 return_data: Some(TransactionReturnData::default()),
                 compute_units_consumed: None,
                 cost_units: None,
+                profile: None,
+                instruction_compute_units_consumed: None,
             }
             .into();
             transaction_status_cf
@@ -10553,6 +10599,8 @@ pub mod tests {
             }),
             compute_units_consumed: Some(23456),
             cost_units: Some(5678),
+            profile: None,
+            instruction_compute_units_consumed: None,
         };
         let deprecated_status: StoredTransactionStatusMeta = status.clone().try_into().unwrap();
         let protobuf_status: generated::TransactionStatusMeta = status.into();
@@ -0,0 +1,209 @@
+//! Export of instruction profiles to the Chrome Trace Event format
+//!
+//! The resulting JSON can be opened in Perfetto or `chrome://tracing`. Compute units are used as
+//! the timeline, one compute unit being shown as one microsecond, and every program gets its own
+//! track. Sections become complete (`X`) events and markers instant (`i`) events, both placed at
+//! the compute units the transaction had consumed when they were recorded, see
+//! [`InstructionProfile::start_cu`].
+
+use {
+    crate::profiling::{
+        heap_metrics, HeapMetrics, InstructionProfile, ProfileEntry, ProfileEntryKind,
+    },
+    serde_json::{json, Value},
+    solana_pubkey::Pubkey,
+};
+
+/// Process id of all events, a trace covers a single transaction
+const TRACE_PID: u64 = 1;
+
+/// Convert the profiles of a transaction's instructions into a Chrome trace
+///
+/// The tracks are numbered in the order in which the programs first completed an instruction.
+/// Recursive invocations of a program share its track, their sections nest within the caller's.
+pub fn chrome_trace(profiles: &[InstructionProfile]) -> Value {
+    let mut programs: Vec<Pubkey> = Vec::new();
+    let mut events = vec![json!({
+        "name": "process_name",
+        "ph": "M",
+        "pid": TRACE_PID,
+        "args": { "name": "transaction" },
+    })];
+    for profile in profiles {
+        let tid = match programs
+            .iter()
+            .position(|program_id| *program_id == profile.program_id)
+        {
+            Some(index) => index.saturating_add(1),
+            None => {
+                programs.push(profile.program_id);
+                events.push(json!({
+                    "name": "thread_name",
+                    "ph": "M",
+                    "pid": TRACE_PID,
+                    "tid": programs.len(),
+                    "args": { "name": profile.program_id.to_string() },
+                }));
+                programs.len()
+            }
+        };
+        events.extend(
+            profile
+                .entries
+                .iter()
+                .zip(heap_metrics(&profile.entries))
+                .map(|(entry, heap)| trace_event(profile, entry, heap, tid)),
+        );
+    }
+    json!({
+        "traceEvents": events,
+        "displayTimeUnit": "ns",
+    })
+}
+
+fn trace_event(
+    profile: &InstructionProfile,
+    entry: &ProfileEntry,
+    heap: Option<HeapMetrics>,
+    tid: usize,
+) -> Value {
+    let mut event = json!({
+        "name": entry.name,
+        "cat": entry.kind.as_str(),
+        "pid": TRACE_PID,
+        "tid": tid,
+        "ts": profile.start_cu.saturating_add(entry.start_cu),
+        "args": {
+            "stack_height": profile.stack_height,
+            "depth": entry.depth,
+            "heap_start": entry.heap_start,
+            "heap_end": entry.heap_end,
+            "heap_total_bytes": heap.map(|heap| heap.total_bytes),
+            "heap_net_bytes": heap.map(|heap| heap.net_bytes),
+            "heap_peak_bytes": heap.map(|heap| heap.peak_bytes),
+            "return_data_bytes": entry.return_data_bytes,
+            "bulk_closed": entry.bulk_closed,
+            "attributes": entry.attributes,
+        },
+    });
+    if let Some(event) = event.as_object_mut() {
+        match entry.kind {
+            ProfileEntryKind::Section => {
+                event.insert("ph".to_string(), json!("X"));
+                event.insert("dur".to_string(), json!(entry.consumed()));
+            }
+            ProfileEntryKind::Mark => {
+                event.insert("ph".to_string(), json!("i"));
+                // Scoped to the track of the program
+                event.insert("s".to_string(), json!("t"));
+            }
+        }
+    }
+    event
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use {super::*, std::collections::BTreeMap};
+
+    fn entry(
+        kind: ProfileEntryKind,
+        name: &str,
+        depth: usize,
+        start_cu: u64,
+        end_cu: u64,
+    ) -> ProfileEntry {
+        ProfileEntry {
+            kind,
+            name: name.to_string(),
+            depth,
+            start_cu,
+            end_cu,
+            heap_start: None,
+            heap_end: None,
+            return_data_bytes: 0,
+            bulk_closed: false,
+            attributes: BTreeMap::new(),
+            call_site: None,
+        }
+    }
+
+    #[test]
+    fn test_chrome_trace() {
+        let outer = Pubkey::new_unique();
+        let inner = Pubkey::new_unique();
+        let mut handle = entry(ProfileEntryKind::Section, "handle", 0, 0, 120);
+        handle.heap_start = Some(32);
+        handle.heap_end = Some(96);
+        // The callee completes before its caller
+        let profiles = [
+            InstructionProfile {
+                program_id: inner,
+                stack_height: 2,
+                start_cu: 150,
+                entries: vec![entry(ProfileEntryKind::Section, "transfer", 0, 10, 40)],
+                ..InstructionProfile::default()
+            },
+            InstructionProfile {
+                program_id: outer,
+                stack_height: 1,
+                start_cu: 100,
+                entries: vec![
+                    entry(ProfileEntryKind::Mark, "checkpoint", 1, 20, 20),
+                    handle,
+                ],
+                ..InstructionProfile::default()
+            },
+            InstructionProfile {
+                program_id: inner,
+                stack_height: 1,
+                start_cu: 300,
+                entries: vec![entry(ProfileEntryKind::Section, "transfer", 0, 0, 5)],
+                ..InstructionProfile::default()
+            },
+        ];
+
+        let trace = chrome_trace(&profiles);
+        assert_eq!(trace["displayTimeUnit"], "ns");
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| event["ph"] == "M")
+                .map(|event| (event["tid"].as_u64(), event["args"]["name"].clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (None, json!("transaction")),
+                (Some(1), json!(inner.to_string())),
+                (Some(2), json!(outer.to_string())),
+            ]
+        );
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| event["ph"] != "M")
+                .map(|event| (
+                    event["name"].as_str().unwrap(),
+                    event["ph"].as_str().unwrap(),
+                    event["tid"].as_u64().unwrap(),
+                    event["ts"].as_u64().unwrap(),
+                    event["dur"].as_u64(),
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("transfer", "X", 1, 160, Some(30)),
+                ("checkpoint", "i", 2, 120, None),
+                ("handle", "X", 2, 100, Some(120)),
+                ("transfer", "X", 1, 300, Some(5)),
+            ]
+        );
+        assert_eq!(events[2]["args"]["stack_height"], 2);
+        assert_eq!(events[2]["args"]["heap_end"], Value::Null);
+        assert_eq!(events[2]["args"]["heap_total_bytes"], Value::Null);
+        let handle = &events[5]["args"];
+        assert_eq!(handle["heap_total_bytes"], 64);
+        assert_eq!(handle["heap_net_bytes"], 64);
+        assert_eq!(handle["heap_peak_bytes"], 96);
+    }
+}
@@ -0,0 +1,136 @@
+//! Export of instruction profiles as CSV
+//!
+//! Every entry becomes a row, so that the profiles of a transaction can be loaded into a
+//! spreadsheet or a dataframe. Entries are placed on the timeline described in
+//! [`chrome_trace`](crate::chrome_trace) and carry the heap usage of their section, see
+//! [`heap_metrics`]. Optional values which are absent are left empty.
+
+use {
+    crate::profiling::{heap_metrics, InstructionProfile},
+    std::fmt::Write,
+};
+
+/// Header row of [`profile_csv`]
+pub const PROFILE_CSV_HEADER: &str = "program_id,stack_height,kind,name,depth,start_cu,end_cu,\
+                                      consumed_cu,heap_start,heap_end,heap_total_bytes,\
+                                      heap_net_bytes,heap_peak_bytes,return_data_bytes,bulk_closed";
+
+/// Render the entries of a transaction's instruction profiles as CSV, starting with
+/// [`PROFILE_CSV_HEADER`]
+///
+/// Rows are ordered like the instruction profiles and their entries, in the order in which they
+/// completed.
+pub fn profile_csv(profiles: &[InstructionProfile]) -> String {
+    let mut csv = String::new();
+    let _ = writeln!(csv, "{PROFILE_CSV_HEADER}");
+    let optional = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+    for profile in profiles {
+        for (entry, heap) in profile.entries.iter().zip(heap_metrics(&profile.entries)) {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                profile.program_id,
+                profile.stack_height,
+                entry.kind.as_str(),
+                escape(&entry.name),
+                entry.depth,
+                profile.start_cu.saturating_add(entry.start_cu),
+                profile.start_cu.saturating_add(entry.end_cu),
+                entry.consumed(),
+                optional(entry.heap_start),
+                optional(entry.heap_end),
+                optional(heap.map(|heap| heap.total_bytes)),
+                optional(heap.map(|heap| heap.net_bytes)),
+                optional(heap.map(|heap| heap.peak_bytes)),
+                entry.return_data_bytes,
+                entry.bulk_closed,
+            );
+        }
+    }
+    csv
+}
+
+/// Quote a field as described in RFC 4180 if it contains a separator, a quote or a line break
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use {
+        super::*,
+        crate::profiling::{ProfileEntry, ProfileEntryKind},
+        solana_pubkey::Pubkey,
+        std::collections::BTreeMap,
+    };
+
+    fn entry(
+        kind: ProfileEntryKind,
+        name: &str,
+        depth: usize,
+        start_cu: u64,
+        end_cu: u64,
+    ) -> ProfileEntry {
+        ProfileEntry {
+            kind,
+            name: name.to_string(),
+            depth,
+            start_cu,
+            end_cu,
+            heap_start: None,
+            heap_end: None,
+            return_data_bytes: 0,
+            bulk_closed: false,
+            attributes: BTreeMap::new(),
+            call_site: None,
+        }
+    }
+
+    #[test]
+    fn test_profile_csv() {
+        let program_id = Pubkey::new_unique();
+        let mut parse = entry(ProfileEntryKind::Section, "parse, \"quoted\"", 1, 5, 20);
+        parse.heap_start = Some(32);
+        parse.heap_end = Some(160);
+        let mut handle = entry(ProfileEntryKind::Section, "handle", 0, 0, 120);
+        handle.heap_start = Some(0);
+        handle.heap_end = Some(256);
+        handle.bulk_closed = true;
+        let profiles = [InstructionProfile {
+            program_id,
+            stack_height: 1,
+            start_cu: 100,
+            entries: vec![
+                parse,
+                entry(ProfileEntryKind::Mark, "checkpoint", 1, 20, 20),
+                handle,
+            ],
+            ..InstructionProfile::default()
+        }];
+
+        let csv = profile_csv(&profiles);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            rows,
+            vec![
+                PROFILE_CSV_HEADER.to_string(),
+                format!(
+                    "{program_id},1,section,\"parse, \"\"quoted\"\"\",1,105,120,15,32,160,128,128,\
+                     160,0,false"
+                ),
+                format!("{program_id},1,mark,checkpoint,1,120,120,0,,,,,,0,false"),
+                format!("{program_id},1,section,handle,0,100,220,120,0,256,256,128,256,0,true"),
+            ]
+        );
+        assert_eq!(
+            PROFILE_CSV_HEADER.split(',').count(),
+            rows[3].split(',').count()
+        );
+        assert_eq!(profile_csv(&[]), format!("{PROFILE_CSV_HEADER}\n"));
+    }
+}
@@ -34,6 +34,18 @@ pub const MAX_BUILTIN_ALLOCATION_COMPUTE_UNIT_LIMIT: u32 = 3_000;
 pub const MAX_HEAP_FRAME_BYTES: u32 = 256 * 1024;
 pub const MIN_HEAP_FRAME_BYTES: u32 = HEAP_LENGTH as u32;
 
+/// Data of the compute budget instruction which enables profiling for the instructions following
+/// it in the same transaction
+///
+/// It is not a `ComputeBudgetInstruction` and does not take the next tag of the enum, which the
+/// next variant added to it would collide with. It starts with `0xff`, the last tag the enum could
+/// ever use, followed by a magic which a variant with that tag would also have to match.
+///
+/// The data does not deserialize into any `ComputeBudgetInstruction`, so until
+/// `enable_profiling_syscalls` is active it is rejected with `InvalidInstructionData` exactly like
+/// before, which keeps replaying older blocks unchanged.
+pub const ENABLE_PROFILING_INSTRUCTION_DATA: [u8; 8] = *b"\xffprofile";
+
 /// The total accounts data a transaction can load is limited to 64MiB to not break
 /// anyone in Mainnet-beta today. It can be set by set_loaded_accounts_data_size_limit instruction
 pub const MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES: NonZeroU32 =
@@ -60,6 +72,10 @@ pub struct SVMTransactionExecutionBudget {
     pub stack_frame_size: usize,
     /// program heap region size, default: solana_program_entrypoint::HEAP_LENGTH
     pub heap_size: u32,
+    /// Compute unit limit requested by the transaction with a compute budget instruction, `None`
+    /// if `compute_unit_limit` is the default one. Only reported by profiles, it does not change
+    /// the execution.
+    pub requested_compute_unit_limit: Option<u32>,
 }
 
 #[cfg(feature = "dev-context-only-utils")]
@@ -79,6 +95,7 @@ impl SVMTransactionExecutionBudget {
             max_call_depth: MAX_CALL_DEPTH,
             stack_frame_size: STACK_FRAME_SIZE,
             heap_size: u32::try_from(solana_program_entrypoint::HEAP_LENGTH).unwrap(),
+            requested_compute_unit_limit: None,
         }
     }
 }
@@ -0,0 +1,265 @@
+//! Rendering of instruction profiles as SVG flamegraphs
+//!
+//! Unlike [`folded_stacks`](crate::folded_stacks), which needs external tooling, the SVG is
+//! self-contained: it can be opened in a browser as is. Every frame shows its name, the program
+//! which recorded it and its compute units as a tooltip.
+
+use {
+    crate::profiling::{timeline_sections, InstructionProfile, TimelineSection},
+    solana_pubkey::Pubkey,
+    std::fmt::Write,
+};
+
+/// Width of the rendered SVG in pixels
+const WIDTH: f64 = 1200.0;
+/// Height of a frame in pixels
+const FRAME_HEIGHT: u64 = 16;
+/// Approximate width of a character of the frame labels in pixels
+const CHAR_WIDTH: f64 = 7.0;
+
+/// A node of the section hierarchy, merging the sections with the same path
+struct Frame<'a> {
+    name: &'a str,
+    program_id: Option<&'a Pubkey>,
+    consumed_cu: u64,
+    children: Vec<Frame<'a>>,
+}
+
+impl<'a> Frame<'a> {
+    fn child(&mut self, section: &TimelineSection<'a>) -> &mut Frame<'a> {
+        let index = match self.children.iter().position(|child| {
+            child.name == section.entry.name && child.program_id == Some(section.program_id)
+        }) {
+            Some(index) => index,
+            None => {
+                self.children.push(Frame {
+                    name: &section.entry.name,
+                    program_id: Some(section.program_id),
+                    consumed_cu: 0,
+                    children: Vec::new(),
+                });
+                self.children.len().saturating_sub(1)
+            }
+        };
+        self.children.get_mut(index).unwrap()
+    }
+
+    fn height(&self) -> u64 {
+        self.children
+            .iter()
+            .map(Frame::height)
+            .max()
+            .unwrap_or(0)
+            .saturating_add(1)
+    }
+}
+
+/// Render the sections of a transaction's instruction profiles as an SVG flamegraph titled
+/// `title`
+///
+/// Sections with the same path in the section hierarchy are merged, the width of each frame is
+/// proportional to the compute units it consumed and its color is derived from the program which
+/// recorded it. The sections of the invoked instructions nest within the sections of their
+/// callers, see [`timeline_sections`].
+pub fn flamegraph_svg(profiles: &[InstructionProfile], title: &str) -> String {
+    let mut root = Frame {
+        name: "all",
+        program_id: None,
+        consumed_cu: 0,
+        children: Vec::new(),
+    };
+    let sections = timeline_sections(profiles);
+    let mut open: Vec<&TimelineSection> = Vec::new();
+    for section in &sections {
+        while open.pop_if(|parent| !parent.encloses(section)).is_some() {}
+        let mut frame = &mut root;
+        for parent in &open {
+            frame = frame.child(parent);
+        }
+        let frame = frame.child(section);
+        frame.consumed_cu = frame.consumed_cu.saturating_add(section.entry.consumed());
+        if open.is_empty() {
+            root.consumed_cu = root.consumed_cu.saturating_add(section.entry.consumed());
+        }
+        open.push(section);
+    }
+
+    let height = root.height().saturating_add(2).saturating_mul(FRAME_HEIGHT);
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{height}" viewBox="0 0 {WIDTH} {height}" font-family="monospace" font-size="12">"#
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect width="100%" height="100%" fill="#ffffff"/>"##
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{FRAME_HEIGHT}" text-anchor="middle">{}</text>"#,
+        WIDTH / 2.0,
+        escape(title)
+    );
+    render_frame(&mut svg, &root, root.consumed_cu, 0.0, height, 0);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn render_frame(svg: &mut String, frame: &Frame, total_cu: u64, x: f64, height: u64, depth: u64) {
+    let width = if total_cu == 0 {
+        WIDTH
+    } else {
+        frame.consumed_cu as f64 / total_cu as f64 * WIDTH
+    };
+    // The root is at the bottom
+    let y = height.saturating_sub(depth.saturating_add(1).saturating_mul(FRAME_HEIGHT));
+    let (label, fill) = match frame.program_id {
+        Some(program_id) => (format!("{} ({program_id})", frame.name), color(program_id)),
+        None => (frame.name.to_string(), "rgb(200,200,200)".to_string()),
+    };
+    let percent = if total_cu == 0 {
+        100.0
+    } else {
+        frame.consumed_cu as f64 * 100.0 / total_cu as f64
+    };
+    let _ = writeln!(
+        svg,
+        r#"<g><title>{} {} CU ({percent:.2}%)</title><rect x="{x:.2}" y="{y}" width="{width:.2}" height="{}" fill="{fill}" stroke="white" stroke-width="0.5"/>"#,
+        escape(&label),
+        frame.consumed_cu,
+        FRAME_HEIGHT.saturating_sub(1),
+    );
+    // Only the part of the name which fits the frame is shown
+    let fitting_chars = (width / CHAR_WIDTH) as usize;
+    if fitting_chars >= 3 {
+        let text: String = if frame.name.chars().count() > fitting_chars {
+            frame
+                .name
+                .chars()
+                .take(fitting_chars.saturating_sub(2))
+                .chain("..".chars())
+                .collect()
+        } else {
+            frame.name.to_string()
+        };
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.2}" y="{}">{}</text>"#,
+            x + 3.0,
+            y.saturating_add(FRAME_HEIGHT.saturating_sub(4)),
+            escape(&text)
+        );
+    }
+    svg.push_str("</g>\n");
+
+    let mut child_x = x;
+    for child in &frame.children {
+        render_frame(
+            svg,
+            child,
+            total_cu,
+            child_x,
+            height,
+            depth.saturating_add(1),
+        );
+        child_x += if total_cu == 0 {
+            0.0
+        } else {
+            child.consumed_cu as f64 / total_cu as f64 * WIDTH
+        };
+    }
+}
+
+/// Warm color derived from the program id, so that the frames of a program share it
+fn color(program_id: &Pubkey) -> String {
+    // FNV-1a, the leading bytes of program ids are not necessarily distributed evenly
+    let hash = program_id
+        .as_ref()
+        .iter()
+        .fold(0x811c_9dc5_u32, |hash, byte| {
+            (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+        });
+    let [red, green, blue, _] = hash.to_le_bytes().map(u16::from);
+    format!(
+        "rgb({},{},{})",
+        205u16.saturating_add(red % 50),
+        green % 180,
+        blue % 55,
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::profiling::{ProfileEntry, ProfileEntryKind},
+        std::collections::BTreeMap,
+    };
+
+    fn section(name: &str, depth: usize, start_cu: u64, end_cu: u64) -> ProfileEntry {
+        ProfileEntry {
+            kind: ProfileEntryKind::Section,
+            name: name.to_string(),
+            depth,
+            start_cu,
+            end_cu,
+            heap_start: None,
+            heap_end: None,
+            return_data_bytes: 0,
+            bulk_closed: false,
+            attributes: BTreeMap::new(),
+            call_site: None,
+        }
+    }
+
+    #[test]
+    fn test_flamegraph_svg() {
+        let outer = Pubkey::new_unique();
+        let inner = Pubkey::new_unique();
+        let profiles = [
+            InstructionProfile {
+                program_id: inner,
+                stack_height: 2,
+                start_cu: 150,
+                entries: vec![section("transfer", 1, 10, 40)],
+                ..InstructionProfile::default()
+            },
+            InstructionProfile {
+                program_id: outer,
+                stack_height: 1,
+                start_cu: 100,
+                entries: vec![
+                    section("parse", 1, 5, 20),
+                    section("parse", 1, 45, 60),
+                    section("handle <accounts>", 0, 0, 200),
+                ],
+                ..InstructionProfile::default()
+            },
+        ];
+
+        let svg = flamegraph_svg(&profiles, "transaction");
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(">transaction</text>"));
+        // Sections with the same path are merged
+        assert!(svg.contains(&format!("<title>parse ({outer}) 30 CU (15.00%)</title>")));
+        assert!(svg.contains(&format!("<title>transfer ({inner}) 30 CU (15.00%)</title>")));
+        assert!(svg.contains(&format!(
+            "<title>handle &lt;accounts&gt; ({outer}) 200 CU (100.00%)</title>"
+        )));
+        assert_eq!(svg.matches("<rect ").count(), 5);
+        // The frames of a program share its color
+        let outer_color = format!(r#"fill="{}""#, color(&outer));
+        assert_eq!(svg.matches(&outer_color).count(), 2);
+
+        let empty = flamegraph_svg(&[], "empty");
+        assert!(empty.contains("<title>all 0 CU (100.00%)</title>"));
+    }
+}
@@ -0,0 +1,221 @@
+//! Export of instruction profiles to the folded stacks format
+//!
+//! Each line holds the names of nested sections joined by `;`, outermost first, followed by the
+//! compute units consumed by that stack:
+//!
+//! ```notrust
+//! handle;parse 15
+//! handle;transfer 30
+//! handle 75
+//! ```
+//!
+//! The lines can be piped into `inferno-flamegraph` or `flamegraph.pl` without further
+//! processing. [`folded_stacks_with_heap`] adds the heap bytes allocated by each stack as a
+//! second weight, the input format of differential flamegraphs.
+
+use {
+    crate::profiling::{timeline_sections, InstructionProfile, TimelineSection},
+    std::collections::BTreeMap,
+};
+
+/// Render the sections of a transaction's instruction profiles as folded stacks
+///
+/// The compute units of each stack exclude those of the sections nested within it, the
+/// flamegraph tools add them back up. Identical stacks are merged and stacks which did not
+/// consume any compute units of their own are left out. The sections of the invoked instructions
+/// nest within the sections of their callers, see [`timeline_sections`].
+pub fn folded_stacks(profiles: &[InstructionProfile]) -> Vec<String> {
+    weighted_stacks(profiles)
+        .into_iter()
+        .filter(|(_stack, (cu, _heap_bytes))| *cu > 0)
+        .map(|(stack, (cu, _heap_bytes))| format!("{stack} {cu}"))
+        .collect()
+}
+
+/// Render the sections of a transaction's instruction profiles as folded stacks weighted by
+/// compute units and heap bytes, `<stack> <compute-units> <heap-bytes>`
+///
+/// The heap bytes of each stack are the net heap usage of its sections, see
+/// [`heap_metrics`](crate::profiling::heap_metrics), and are 0 for sections which did not sample
+/// the heap. Stacks which neither consumed compute units nor allocated heap of their own are left
+/// out, see [`folded_stacks`].
+pub fn folded_stacks_with_heap(profiles: &[InstructionProfile]) -> Vec<String> {
+    weighted_stacks(profiles)
+        .into_iter()
+        .filter(|(_stack, (cu, heap_bytes))| *cu > 0 || *heap_bytes > 0)
+        .map(|(stack, (cu, heap_bytes))| format!("{stack} {cu} {heap_bytes}"))
+        .collect()
+}
+
+/// The compute units and net heap bytes of each stack
+fn weighted_stacks(profiles: &[InstructionProfile]) -> BTreeMap<String, (u64, u64)> {
+    let sections = timeline_sections(profiles);
+    let mut stacks: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+    let mut open: Vec<(&TimelineSection, String, u64)> = Vec::new();
+    let mut close = |(section, stack, self_cu): (&TimelineSection, String, u64)| {
+        let heap_bytes = section.heap.map_or(0, |heap| heap.net_bytes);
+        let (cu, total_heap_bytes) = stacks.entry(stack).or_default();
+        *cu = cu.saturating_add(self_cu);
+        *total_heap_bytes = total_heap_bytes.saturating_add(heap_bytes);
+    };
+    for section in &sections {
+        while let Some(parent) = open.pop_if(|(parent, _, _)| !parent.encloses(section)) {
+            close(parent);
+        }
+        let name = frame_name(&section.entry.name);
+        let stack = match open.last_mut() {
+            Some((_parent, parent_stack, parent_self_cu)) => {
+                *parent_self_cu = parent_self_cu.saturating_sub(section.entry.consumed());
+                format!("{parent_stack};{name}")
+            }
+            None => name,
+        };
+        open.push((section, stack, section.entry.consumed()));
+    }
+    while let Some(parent) = open.pop() {
+        close(parent);
+    }
+    stacks
+}
+
+/// Section name as a frame of a folded stack, which cannot contain the frame separator
+fn frame_name(name: &str) -> String {
+    let name = name.replace(';', ":");
+    if name.is_empty() {
+        "-".to_string()
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::profiling::{ProfileEntry, ProfileEntryKind},
+        solana_pubkey::Pubkey,
+    };
+
+    fn entry(
+        kind: ProfileEntryKind,
+        name: &str,
+        depth: usize,
+        start_cu: u64,
+        end_cu: u64,
+    ) -> ProfileEntry {
+        ProfileEntry {
+            kind,
+            name: name.to_string(),
+            depth,
+            start_cu,
+            end_cu,
+            heap_start: None,
+            heap_end: None,
+            return_data_bytes: 0,
+            bulk_closed: false,
+            attributes: BTreeMap::new(),
+            call_site: None,
+        }
+    }
+
+    #[test]
+    fn test_folded_stacks() {
+        let profiles = [
+            // Invoked from within the `handle` section of the caller
+            InstructionProfile {
+                program_id: Pubkey::new_unique(),
+                stack_height: 2,
+                start_cu: 150,
+                entries: vec![entry(ProfileEntryKind::Section, "transfer", 1, 10, 40)],
+                ..InstructionProfile::default()
+            },
+            InstructionProfile {
+                program_id: Pubkey::new_unique(),
+                stack_height: 1,
+                start_cu: 100,
+                entries: vec![
+                    entry(ProfileEntryKind::Section, "parse", 1, 5, 20),
+                    entry(ProfileEntryKind::Mark, "checkpoint", 1, 20, 20),
+                    entry(ProfileEntryKind::Section, "verify", 1, 60, 60),
+                    entry(ProfileEntryKind::Section, "handle", 0, 0, 120),
+                    entry(ProfileEntryKind::Section, "parse;retry", 0, 120, 130),
+                ],
+                ..InstructionProfile::default()
+            },
+        ];
+
+        assert_eq!(
+            folded_stacks(&profiles),
+            vec![
+                "handle 75".to_string(),
+                "handle;parse 15".to_string(),
+                "handle;transfer 30".to_string(),
+                "parse:retry 10".to_string(),
+            ]
+        );
+        assert!(folded_stacks(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_folded_stacks_with_heap() {
+        let with_heap = |mut entry: ProfileEntry, heap_start, heap_end| {
+            entry.heap_start = Some(heap_start);
+            entry.heap_end = Some(heap_end);
+            entry
+        };
+        let profiles = [
+            // Invoked from within the `handle` section of the caller, with its own heap
+            InstructionProfile {
+                program_id: Pubkey::new_unique(),
+                stack_height: 2,
+                start_cu: 150,
+                entries: vec![with_heap(
+                    entry(ProfileEntryKind::Section, "transfer", 1, 10, 40),
+                    0,
+                    64,
+                )],
+                ..InstructionProfile::default()
+            },
+            InstructionProfile {
+                program_id: Pubkey::new_unique(),
+                stack_height: 1,
+                start_cu: 100,
+                entries: vec![
+                    with_heap(entry(ProfileEntryKind::Section, "parse", 1, 5, 20), 32, 160),
+                    with_heap(
+                        entry(ProfileEntryKind::Section, "alloc", 1, 60, 60),
+                        160,
+                        192,
+                    ),
+                    with_heap(
+                        entry(ProfileEntryKind::Section, "handle", 0, 0, 120),
+                        0,
+                        256,
+                    ),
+                    entry(ProfileEntryKind::Section, "log", 0, 120, 130),
+                ],
+                ..InstructionProfile::default()
+            },
+        ];
+
+        assert_eq!(
+            folded_stacks_with_heap(&profiles),
+            vec![
+                "handle 75 96".to_string(),
+                "handle;alloc 0 32".to_string(),
+                "handle;parse 15 128".to_string(),
+                "handle;transfer 30 64".to_string(),
+                "log 10 0".to_string(),
+            ]
+        );
+        assert_eq!(
+            folded_stacks(&profiles),
+            vec![
+                "handle 75".to_string(),
+                "handle;parse 15".to_string(),
+                "handle;transfer 30".to_string(),
+                "log 10".to_string(),
+            ]
+        );
+    }
+}
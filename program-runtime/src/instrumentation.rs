@@ -0,0 +1,57 @@
+//! Hooks for tools observing program execution
+//!
+//! An [`InstrumentationObserver`] registered with the
+//! [`EnvironmentConfig`](crate::invoke_context::EnvironmentConfig) of an invoke context is
+//! notified whenever an instruction, a syscall or a CPI begins and ends, and of every message
+//! logged. Tracers, coverage tools or fault injectors can be built on top of these hooks instead
+//! of patching the invoke context.
+
+use {
+    solana_instruction::error::InstructionError, solana_pubkey::Pubkey,
+    solana_svm_log_collector::LogEntry,
+};
+
+/// Receives the execution events of an invoke context
+///
+/// Every hook does nothing by default, so that observers only implement the events they are
+/// interested in. Hooks are called synchronously on the thread executing the transaction and
+/// should return quickly.
+pub trait InstrumentationObserver: Send + Sync {
+    /// A program instruction, either top level or invoked by a CPI, starts executing
+    fn instruction_begin(&self, _program_id: &Pubkey, _stack_height: usize) {}
+
+    /// The program instruction which began at `stack_height` returned
+    fn instruction_end(
+        &self,
+        _program_id: &Pubkey,
+        _stack_height: usize,
+        _compute_units_consumed: u64,
+        _result: &Result<(), InstructionError>,
+    ) {
+    }
+
+    /// The executing program calls the syscall registered as `name`
+    ///
+    /// Syscalls are only observed in the program runtime environments created to be observed,
+    /// see `agave_syscalls::create_observed_program_runtime_environment_v1`, so that the
+    /// environments taking part in consensus call them directly.
+    fn syscall_begin(&self, _name: &str) {}
+
+    /// The syscall registered as `name` returned `result` to the program
+    fn syscall_end(&self, _name: &str, _result: &Result<u64, Box<dyn std::error::Error>>) {}
+
+    /// `caller_id` invokes `callee_id`, the callee will execute at `stack_height`
+    fn cpi_begin(&self, _caller_id: &Pubkey, _callee_id: &Pubkey, _stack_height: usize) {}
+
+    /// The CPI of `callee_id` by `caller_id` returned
+    fn cpi_end(
+        &self,
+        _caller_id: &Pubkey,
+        _callee_id: &Pubkey,
+        _result: &Result<(), InstructionError>,
+    ) {
+    }
+
+    /// A message was logged, whether or not the log collector records it
+    fn log_emitted(&self, _entry: &LogEntry) {}
+}
@@ -1,14 +1,21 @@
+#[cfg(feature = "dev-context-only-utils")]
+use qualifier_attr::qualifiers;
 use {
     crate::{
         execution_budget::{SVMTransactionExecutionBudget, SVMTransactionExecutionCost},
+        instrumentation::InstrumentationObserver,
         loaded_programs::{
             ProgramCacheEntry, ProgramCacheEntryType, ProgramCacheForTxBatch,
             ProgramRuntimeEnvironments,
         },
+        profiling::{
+            ActiveSections, InstructionProfile, ProfileReturnData, ProfileVerbosity,
+            ProfilingState, DEFAULT_PROFILE_SUMMARY_SECTIONS, PROFILE_RETURN_DATA_BYTES_LIMIT,
+        },
         stable_log,
         sysvar_cache::SysvarCache,
     },
-    solana_account::{create_account_shared_data_for_test, AccountSharedData},
+    solana_account::{create_account_shared_data_for_test, AccountSharedData, ReadableAccount},
     solana_clock::Slot,
     solana_epoch_schedule::EpochSchedule,
     solana_hash::Hash,
@@ -38,6 +45,7 @@ use {
     std::{
         alloc::Layout,
         cell::RefCell,
+        collections::BTreeMap,
         fmt::{self, Debug},
         rc::Rc,
     },
@@ -117,11 +125,16 @@ impl fmt::Display for AllocErr {
 pub struct BpfAllocator {
     len: u64,
     pos: u64,
+    allocations: u64,
 }
 
 impl BpfAllocator {
     pub fn new(len: u64) -> Self {
-        Self { len, pos: 0 }
+        Self {
+            len,
+            pos: 0,
+            allocations: 0,
+        }
     }
 
     pub fn alloc(&mut self, layout: Layout) -> Result<u64, AllocErr> {
@@ -135,11 +148,32 @@ impl BpfAllocator {
             self.pos = self.pos.saturating_add(bytes_to_align);
             let addr = MM_HEAP_START.saturating_add(self.pos);
             self.pos = self.pos.saturating_add(layout.size() as u64);
+            self.allocations = self.allocations.saturating_add(1);
             Ok(addr)
         } else {
             Err(AllocErr)
         }
     }
+
+    /// Bytes of the heap region not yet handed out by [`alloc`](Self::alloc)
+    pub fn remaining(&self) -> u64 {
+        self.len.saturating_sub(self.pos)
+    }
+
+    /// Length of the heap region requested by the transaction
+    pub fn size(&self) -> u64 {
+        self.len
+    }
+
+    /// Bytes of the heap region handed out by [`alloc`](Self::alloc), including alignment padding
+    pub fn used(&self) -> u64 {
+        self.pos
+    }
+
+    /// Number of successful calls to [`alloc`](Self::alloc)
+    pub fn allocations(&self) -> u64 {
+        self.allocations
+    }
 }
 
 pub struct EnvironmentConfig<'a> {
@@ -148,6 +182,7 @@ pub struct EnvironmentConfig<'a> {
     epoch_stake_callback: &'a dyn InvokeContextCallback,
     feature_set: &'a SVMFeatureSet,
     sysvar_cache: &'a SysvarCache,
+    instrumentation_observer: Option<std::sync::Arc<dyn InstrumentationObserver>>,
 }
 impl<'a> EnvironmentConfig<'a> {
     pub fn new(
@@ -163,14 +198,26 @@ impl<'a> EnvironmentConfig<'a> {
             epoch_stake_callback,
             feature_set,
             sysvar_cache,
+            instrumentation_observer: None,
         }
     }
+
+    /// Notify `instrumentation_observer` of the execution, see [`InstrumentationObserver`]
+    pub fn with_instrumentation_observer(
+        mut self,
+        instrumentation_observer: Option<std::sync::Arc<dyn InstrumentationObserver>>,
+    ) -> Self {
+        self.instrumentation_observer = instrumentation_observer;
+        self
+    }
 }
 
 pub struct SyscallContext {
     pub allocator: BpfAllocator,
     pub accounts_metadata: Vec<SerializedAccountMetadata>,
     pub trace_log: Vec<[u64; 12]>,
+    /// Profile recorded by the instruction, `None` if profiling is disabled
+    pub profiling: Option<ProfilingState>,
 }
 
 #[derive(Debug, Clone)]
@@ -182,6 +229,15 @@ pub struct SerializedAccountMetadata {
     pub vm_owner_addr: u64,
 }
 
+/// Outcome of an instruction executed by [`InvokeContext::process_instruction`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct InstructionOutcome {
+    /// Compute units consumed by the instruction, including the instructions it invoked
+    pub compute_units_consumed: u64,
+    /// Whether the instruction returned an error
+    pub failed: bool,
+}
+
 /// Main pipeline from runtime to program execution.
 pub struct InvokeContext<'a> {
     /// Information about the currently executing transaction.
@@ -203,8 +259,41 @@ pub struct InvokeContext<'a> {
     pub timings: ExecuteDetailsTimings,
     pub syscall_context: Vec<Option<SyscallContext>>,
     traces: Vec<Vec<[u64; 12]>>,
+    /// Profiles of the completed instructions, see [`InvokeContext::take_profiling_results`]
+    profiling_results: Vec<InstructionProfile>,
+    /// Profiling sections active in the transaction, shared by the profiling states of its
+    /// instructions
+    profiling_sections: Rc<RefCell<ActiveSections>>,
+    /// Outcome of each instruction of the instruction trace, see
+    /// [`InvokeContext::take_instruction_trace_outcomes`]
+    instruction_trace_outcomes: Vec<InstructionOutcome>,
     /// Stops copying account data if stricter_abi_and_runtime_constraints is enabled
     pub account_data_direct_mapping: bool,
+    /// Attaches a [`ProfilingState`] to every program instruction executed by this context
+    ///
+    /// Off by default, it is only turned on for the transactions which are profiled on request,
+    /// see `TransactionProcessingConfig::profile_all_transactions`.
+    pub enable_profiling: bool,
+    /// Charges the profiling syscalls, see [`InvokeContext::enable_metered_profiling`]
+    metered_profiling: bool,
+    /// Records a profiling section around every CPI, even if no program on the invocation stack
+    /// has opened a section, see `TransactionProcessingConfig::profile_cpis`
+    pub profile_cpis: bool,
+    /// Opens a profiling section named by the program id around every program instruction, see
+    /// [`InvokeContext::new_profiling_state`]
+    ///
+    /// As the section stays active while the instruction executes, every CPI is recorded as a
+    /// section too, which breaks the consumed compute units down per CPI even for programs
+    /// without any instrumentation.
+    pub implicit_profiling_sections: bool,
+    /// Maximum number of return data bytes kept in the profile of each instruction, see
+    /// [`InstructionProfile::return_data`]
+    pub profile_return_data_bytes_limit: usize,
+    /// How much of the profile of each instruction is logged
+    pub profile_verbosity: ProfileVerbosity,
+    /// Number of sections logged with [`ProfileVerbosity::Summary`], see
+    /// [`stable_log::program_profile_summary`]
+    pub profile_summary_sections: usize,
 }
 
 impl<'a> InvokeContext<'a> {
@@ -217,6 +306,13 @@ impl<'a> InvokeContext<'a> {
         compute_budget: SVMTransactionExecutionBudget,
         execution_cost: SVMTransactionExecutionCost,
     ) -> Self {
+        if let (Some(observer), Some(log_collector)) =
+            (&environment_config.instrumentation_observer, &log_collector)
+        {
+            let observer = observer.clone();
+            log_collector.borrow_mut().listener =
+                Some(Box::new(move |entry| observer.log_emitted(entry)));
+        }
         Self {
             transaction_context,
             program_cache_for_tx_batch,
@@ -229,7 +325,17 @@ impl<'a> InvokeContext<'a> {
             timings: ExecuteDetailsTimings::default(),
             syscall_context: Vec::new(),
             traces: Vec::new(),
+            profiling_results: Vec::new(),
+            profiling_sections: Rc::default(),
+            instruction_trace_outcomes: Vec::new(),
             account_data_direct_mapping: false,
+            enable_profiling: false,
+            metered_profiling: false,
+            profile_cpis: false,
+            implicit_profiling_sections: false,
+            profile_return_data_bytes_limit: PROFILE_RETURN_DATA_BYTES_LIMIT,
+            profile_verbosity: ProfileVerbosity::default(),
+            profile_summary_sections: DEFAULT_PROFILE_SUMMARY_SECTIONS,
         }
     }
 
@@ -251,7 +357,7 @@ impl<'a> InvokeContext<'a> {
             .get_instruction_context_at_index_in_trace(
                 self.transaction_context.get_instruction_trace_length(),
             )?;
-        let program_id = instruction_context
+        let program_id = *instruction_context
             .get_program_key()
             .map_err(|_| InstructionError::UnsupportedProgramId)?;
         if self.transaction_context.get_instruction_stack_height() != 0 {
@@ -260,14 +366,14 @@ impl<'a> InvokeContext<'a> {
                     self.transaction_context
                         .get_instruction_context_at_nesting_level(level)
                         .and_then(|instruction_context| instruction_context.get_program_key())
-                        .map(|program_key| program_key == program_id)
+                        .map(|program_key| *program_key == program_id)
                         .unwrap_or(false)
                 });
             let is_last = self
                 .transaction_context
                 .get_current_instruction_context()
                 .and_then(|instruction_context| instruction_context.get_program_key())
-                .map(|program_key| program_key == program_id)
+                .map(|program_key| *program_key == program_id)
                 .unwrap_or(false);
             if contains && !is_last {
                 // Reentrancy not allowed unless caller is calling itself
@@ -276,13 +382,35 @@ impl<'a> InvokeContext<'a> {
         }
 
         self.syscall_context.push(None);
-        self.transaction_context.push()
+        self.transaction_context.push()?;
+        if let Some(log_collector) = &self.log_collector {
+            log_collector.borrow_mut().push_program(program_id);
+        }
+        Ok(())
     }
 
     /// Pop a stack frame from the invocation stack
     fn pop(&mut self) -> Result<(), InstructionError> {
+        let stack_height = self.get_stack_height();
         if let Some(Some(syscall_context)) = self.syscall_context.pop() {
             self.traces.push(syscall_context.trace_log);
+            if let Some(profiling) = syscall_context
+                .profiling
+                .filter(|profiling| !profiling.is_empty())
+            {
+                let program_id = self
+                    .transaction_context
+                    .get_current_instruction_context()
+                    .and_then(|instruction_context| instruction_context.get_program_key().copied());
+                if let Ok(program_id) = program_id {
+                    self.profiling_results
+                        .push(profiling.into_instruction_profile(program_id, stack_height));
+                }
+            }
+        }
+        self.profiling_sections.borrow_mut().adopt(stack_height);
+        if let Some(log_collector) = &self.log_collector {
+            log_collector.borrow_mut().pop_program();
         }
         self.transaction_context.pop()
     }
@@ -300,9 +428,12 @@ impl<'a> InvokeContext<'a> {
         signers: &[Pubkey],
     ) -> Result<(), InstructionError> {
         self.prepare_next_instruction(&instruction, signers)?;
+        self.notify_cpi_begin(&instruction.program_id);
         let mut compute_units_consumed = 0;
-        self.process_instruction(&mut compute_units_consumed, &mut ExecuteTimings::default())?;
-        Ok(())
+        let result =
+            self.process_instruction(&mut compute_units_consumed, &mut ExecuteTimings::default());
+        self.notify_cpi_end(&instruction.program_id, &result);
+        result
     }
 
     /// Helper to prepare for process_instruction() when the instruction is not a top level one,
@@ -494,11 +625,245 @@ impl<'a> InvokeContext<'a> {
         timings: &mut ExecuteTimings,
     ) -> Result<(), InstructionError> {
         *compute_units_consumed = 0;
+        let index_in_trace = self.transaction_context.get_instruction_trace_length();
         self.push()?;
-        self.process_executable_chain(compute_units_consumed, timings)
-            // MUST pop if and only if `push` succeeded, independent of `result`.
-            // Thus, the `.and()` instead of an `.and_then()`.
-            .and(self.pop())
+        let observed_program_id = self.instrumentation_observer().and_then(|observer| {
+            let program_id = *self
+                .transaction_context
+                .get_current_instruction_context()
+                .ok()?
+                .get_program_key()
+                .ok()?;
+            observer.instruction_begin(&program_id, self.get_stack_height());
+            Some(program_id)
+        });
+        let first_profile = self.profiling_results.len();
+        let start_cu = self
+            .compute_budget
+            .compute_unit_limit
+            .saturating_sub(self.get_remaining());
+        let lamports_before = self.profiled_instruction_account_lamports();
+        let result = self.process_executable_chain(compute_units_consumed, timings);
+        if self.instruction_trace_outcomes.len() <= index_in_trace {
+            self.instruction_trace_outcomes.resize(
+                index_in_trace.saturating_add(1),
+                InstructionOutcome::default(),
+            );
+        }
+        if let Some(outcome) = self.instruction_trace_outcomes.get_mut(index_in_trace) {
+            *outcome = InstructionOutcome {
+                compute_units_consumed: *compute_units_consumed,
+                failed: result.is_err(),
+            };
+        }
+        let lamport_deltas = lamports_before
+            .map(|lamports_before| self.instruction_lamport_deltas(lamports_before))
+            .filter(|lamport_deltas| !lamport_deltas.is_empty());
+        let return_data = self.profile_return_data();
+        if let (Some(observer), Some(program_id)) =
+            (self.instrumentation_observer(), observed_program_id)
+        {
+            observer.instruction_end(
+                &program_id,
+                self.get_stack_height(),
+                *compute_units_consumed,
+                &result,
+            );
+        }
+        let stack_height = self.get_stack_height();
+        let program_id = self
+            .transaction_context
+            .get_current_instruction_context()
+            .and_then(|instruction_context| instruction_context.get_program_key().copied());
+        // MUST pop if and only if `push` succeeded, independent of `result`.
+        // Thus, the `.and()` instead of an `.and_then()`.
+        let result = result.and(self.pop());
+        if let Ok(program_id) = program_id {
+            if lamport_deltas.is_some() || return_data.is_some() {
+                self.update_instruction_profile(
+                    first_profile,
+                    program_id,
+                    stack_height,
+                    |profile| {
+                        profile.lamport_deltas = lamport_deltas.unwrap_or_default();
+                        profile.return_data = return_data;
+                    },
+                );
+            }
+        }
+        if let Some(profile) = self.instruction_profile_mut(first_profile, stack_height) {
+            profile.start_cu = start_cu;
+        }
+        result
+    }
+
+    /// Record the profile of the builtin program which just completed, only if it is profiled as
+    /// naming its section allocates, see [`InstructionProfile::builtin`]
+    #[cfg_attr(feature = "dev-context-only-utils", qualifiers(pub))]
+    fn record_builtin_profile(&mut self, program_id: Pubkey, compute_units_consumed: u64) {
+        if !self.enable_profiling {
+            return;
+        }
+        self.profiling_results.push(InstructionProfile::builtin(
+            program_id,
+            self.get_stack_height(),
+            compute_units_consumed,
+        ));
+    }
+
+    /// Lamports of the accounts of the current instruction, only snapshotted if it is profiled as
+    /// computing its lamport deltas allocates, see [`InvokeContext::enable_profiling`]
+    #[cfg_attr(feature = "dev-context-only-utils", qualifiers(pub))]
+    fn profiled_instruction_account_lamports(&self) -> Option<BTreeMap<Pubkey, u64>> {
+        self.enable_profiling
+            .then(|| self.instruction_account_lamports())
+    }
+
+    /// Lamports of the accounts of the current instruction
+    fn instruction_account_lamports(&self) -> BTreeMap<Pubkey, u64> {
+        let Ok(instruction_context) = self.transaction_context.get_current_instruction_context()
+        else {
+            return BTreeMap::new();
+        };
+        (0..instruction_context.get_number_of_instruction_accounts())
+            .filter_map(|index_in_instruction| {
+                let index_in_transaction = instruction_context
+                    .get_index_of_instruction_account_in_transaction(index_in_instruction)
+                    .ok()?;
+                let key = *self
+                    .transaction_context
+                    .get_key_of_account_at_index(index_in_transaction)
+                    .ok()?;
+                let lamports = self
+                    .transaction_context
+                    .accounts()
+                    .try_borrow(index_in_transaction)
+                    .ok()?
+                    .lamports();
+                Some((key, lamports))
+            })
+            .collect()
+    }
+
+    /// Net lamport change of the accounts of the current instruction whose balance changed
+    fn instruction_lamport_deltas(
+        &self,
+        lamports_before: BTreeMap<Pubkey, u64>,
+    ) -> BTreeMap<Pubkey, i128> {
+        self.instruction_account_lamports()
+            .into_iter()
+            .filter_map(|(key, lamports)| {
+                let before = lamports_before.get(&key).copied().unwrap_or_default();
+                let delta = i128::from(lamports).saturating_sub(i128::from(before));
+                (delta != 0).then_some((key, delta))
+            })
+            .collect()
+    }
+
+    /// Return data of the instruction which just completed, truncated to
+    /// [`InvokeContext::profile_return_data_bytes_limit`]
+    ///
+    /// Only captured if the instruction is profiled, as copying it allocates, see
+    /// [`InvokeContext::enable_profiling`]. As the return data is shared by the whole transaction,
+    /// it is only attributed to the instruction if its program set it, not to the caller of the
+    /// program which did.
+    #[cfg_attr(feature = "dev-context-only-utils", qualifiers(pub))]
+    fn profile_return_data(&self) -> Option<ProfileReturnData> {
+        if !self.enable_profiling {
+            return None;
+        }
+        let current_program_id = self
+            .transaction_context
+            .get_current_instruction_context()
+            .and_then(|instruction_context| instruction_context.get_program_key().copied())
+            .ok()?;
+        let (program_id, data) = self.transaction_context.get_return_data();
+        (*program_id == current_program_id && !data.is_empty()).then(|| ProfileReturnData {
+            program_id: *program_id,
+            data: data
+                .iter()
+                .take(self.profile_return_data_bytes_limit)
+                .copied()
+                .collect(),
+            len: data.len(),
+        })
+    }
+
+    /// The profile of the instruction which just completed at `stack_height`, if it recorded one
+    ///
+    /// The profile of the instruction is the last one, if any was recorded since `first_profile`,
+    /// as the profiles of the instructions it invoked are recorded before it completes.
+    fn instruction_profile_mut(
+        &mut self,
+        first_profile: usize,
+        stack_height: usize,
+    ) -> Option<&mut InstructionProfile> {
+        self.profiling_results
+            .get_mut(first_profile..)
+            .and_then(|profiles| profiles.last_mut())
+            .filter(|profile| profile.stack_height == stack_height)
+    }
+
+    /// Update the profile of the instruction which just completed, or record a new profile if the
+    /// instruction did not record anything yet, see [`InvokeContext::instruction_profile_mut`]
+    fn update_instruction_profile(
+        &mut self,
+        first_profile: usize,
+        program_id: Pubkey,
+        stack_height: usize,
+        update: impl FnOnce(&mut InstructionProfile),
+    ) {
+        match self.instruction_profile_mut(first_profile, stack_height) {
+            Some(profile) => update(profile),
+            None => {
+                let mut profile = InstructionProfile {
+                    program_id,
+                    stack_height,
+                    ..InstructionProfile::default()
+                };
+                update(&mut profile);
+                self.profiling_results.push(profile);
+            }
+        }
+    }
+
+    /// The observer notified of the execution, see
+    /// [`EnvironmentConfig::with_instrumentation_observer`]
+    pub fn instrumentation_observer(&self) -> Option<&std::sync::Arc<dyn InstrumentationObserver>> {
+        self.environment_config.instrumentation_observer.as_ref()
+    }
+
+    /// Notify the instrumentation observer that the executing program invokes `callee_id`, once
+    /// the callee instruction was prepared
+    pub fn notify_cpi_begin(&self, callee_id: &Pubkey) {
+        let Some(observer) = self.instrumentation_observer() else {
+            return;
+        };
+        if let Ok(caller_id) = self
+            .transaction_context
+            .get_current_instruction_context()
+            .and_then(|instruction_context| instruction_context.get_program_key())
+        {
+            observer.cpi_begin(
+                caller_id,
+                callee_id,
+                self.get_stack_height().saturating_add(1),
+            );
+        }
+    }
+
+    /// Notify the instrumentation observer that the CPI of `callee_id` returned `result`
+    pub fn notify_cpi_end(&self, callee_id: &Pubkey, result: &Result<(), InstructionError>) {
+        let Some(observer) = self.instrumentation_observer() else {
+            return;
+        };
+        if let Ok(caller_id) = self
+            .transaction_context
+            .get_current_instruction_context()
+            .and_then(|instruction_context| instruction_context.get_program_key())
+        {
+            observer.cpi_end(caller_id, callee_id, result);
+        }
     }
 
     /// Processes a precompile instruction
@@ -601,9 +966,26 @@ impl<'a> InvokeContext<'a> {
                 }
             }
         };
+        if result.is_err() {
+            // Show which phase of the program failed
+            if let Some(profiling) = self
+                .get_syscall_context()
+                .ok()
+                .and_then(|syscall_context| syscall_context.profiling.as_ref())
+            {
+                for section in profiling.failed_sections() {
+                    stable_log::program_failed_section(&logger, &program_id, section);
+                }
+            }
+        }
         let post_remaining_units = self.get_remaining();
         *compute_units_consumed = pre_remaining_units.saturating_sub(post_remaining_units);
 
+        // Programs executed by a loader are profiled by their VM
+        if builtin_id == program_id {
+            self.record_builtin_profile(program_id, *compute_units_consumed);
+        }
+
         if builtin_id == program_id && result.is_ok() && *compute_units_consumed == 0 {
             return Err(InstructionError::BuiltinProgramsMustConsumeComputeUnits);
         }
@@ -715,6 +1097,95 @@ impl<'a> InvokeContext<'a> {
         Ok(())
     }
 
+    /// Charge the profiling syscalls of the instructions executed by this context, see
+    /// [`ProfilingState::new_metered`]
+    ///
+    /// Metered profiling changes the compute units consumed by the profiled programs, so it is
+    /// reserved to `agave-ledger-tool program run` and never available to the validator.
+    #[cfg(feature = "dev-context-only-utils")]
+    pub fn enable_metered_profiling(&mut self) {
+        self.metered_profiling = true;
+    }
+
+    /// Profiling state of the program instruction about to be executed, `None` if profiling is
+    /// disabled
+    ///
+    /// If [`InvokeContext::implicit_profiling_sections`] is set, the state starts with a section
+    /// named by the program id, which is closed by
+    /// [`InvokeContext::end_implicit_profiling_section`].
+    pub fn new_profiling_state(&self) -> Result<Option<ProfilingState>, InstructionError> {
+        if !self.enable_profiling {
+            return Ok(None);
+        }
+        let remaining = self.get_remaining();
+        let mut profiling = if self.metered_profiling {
+            ProfilingState::new_metered(remaining)
+        } else {
+            ProfilingState::new(remaining)
+        }
+        .with_active_sections(self.profiling_sections.clone(), self.get_stack_height());
+        if self.implicit_profiling_sections {
+            let program_id = self
+                .transaction_context
+                .get_current_instruction_context()?
+                .get_program_key()?
+                .to_string();
+            profiling.start_section(&program_id, remaining, None);
+        }
+        Ok(Some(profiling))
+    }
+
+    /// Close the section opened around the current instruction by
+    /// [`InvokeContext::new_profiling_state`]
+    ///
+    /// Does nothing if the section was already closed, e.g. by `sol_abort_with_message`.
+    pub fn end_implicit_profiling_section(&mut self) -> Result<(), InstructionError> {
+        if !self.implicit_profiling_sections {
+            return Ok(());
+        }
+        let program_id = self
+            .transaction_context
+            .get_current_instruction_context()?
+            .get_program_key()?
+            .to_string();
+        let remaining = self.get_remaining();
+        if let Some(profiling) = &mut self.get_syscall_context_mut()?.profiling {
+            profiling.end_section(&program_id, remaining, None);
+        }
+        Ok(())
+    }
+
+    /// Profiling state of the instruction at the given stack height, `None` if it is not
+    /// profiled
+    ///
+    /// Sections are shared by the instructions of a transaction, see [`ActiveSections`], and
+    /// recorded by the instruction which opened them. This gives access to that instruction when
+    /// one of the instructions it invoked closes the section.
+    pub fn get_profiling_state_mut(&mut self, stack_height: usize) -> Option<&mut ProfilingState> {
+        self.syscall_context
+            .get_mut(stack_height.checked_sub(1)?)?
+            .as_mut()?
+            .profiling
+            .as_mut()
+    }
+
+    /// Whether a program on the current invocation stack has an active profiling section
+    pub fn is_profiling_active(&self) -> bool {
+        !self.profiling_sections.borrow().is_empty()
+    }
+
+    /// Whether a CPI made by the current instruction is recorded as a profiling section
+    ///
+    /// CPIs are recorded while a program on the invocation stack has an active profiling section,
+    /// or whenever the current instruction is profiled if [`InvokeContext::profile_cpis`] is set.
+    pub fn should_profile_cpi(&self) -> bool {
+        self.is_profiling_active()
+            || (self.profile_cpis
+                && self
+                    .get_syscall_context()
+                    .is_ok_and(|syscall_context| syscall_context.profiling.is_some()))
+    }
+
     // Get this instruction's SyscallContext
     pub fn get_syscall_context(&self) -> Result<&SyscallContext, InstructionError> {
         self.syscall_context
@@ -735,6 +1206,26 @@ impl<'a> InvokeContext<'a> {
     pub fn get_traces(&self) -> &Vec<Vec<[u64; 12]>> {
         &self.traces
     }
+
+    /// Take the profiles of the instructions completed so far, in the order in which they
+    /// completed
+    ///
+    /// Instructions which did not record anything, move lamports nor set return data are left
+    /// out, see [`InstructionProfile::lamport_deltas`] and [`InstructionProfile::return_data`]. This gives harnesses the data of
+    /// the `Program profile:` log lines without parsing them. Builtin program invocations are
+    /// included as well, see [`InstructionProfile::builtin`], although they are not logged.
+    pub fn take_profiling_results(&mut self) -> Vec<InstructionProfile> {
+        std::mem::take(&mut self.profiling_results)
+    }
+
+    /// Takes the outcome of each instruction of the instruction trace
+    ///
+    /// Indexed like the instruction trace of the transaction context. Instructions which were
+    /// not executed by [`InvokeContext::process_instruction`], such as precompiles, have the
+    /// default outcome and may be missing at the end.
+    pub fn take_instruction_trace_outcomes(&mut self) -> Vec<InstructionOutcome> {
+        std::mem::take(&mut self.instruction_trace_outcomes)
+    }
 }
 
 #[macro_export]
@@ -960,6 +1451,12 @@ mod tests {
         Resize {
             new_len: u64,
         },
+        TransferLamports {
+            lamports: u64,
+        },
+        SetReturnData {
+            data: Vec<u8>,
+        },
     }
 
     const MOCK_BUILTIN_COMPUTE_UNIT_COST: u64 = 1;
@@ -1049,6 +1546,20 @@ mod tests {
                     MockInstruction::Resize { new_len } => instruction_context
                         .try_borrow_instruction_account(0)?
                         .set_data_from_slice(&vec![0; new_len as usize])?,
+                    MockInstruction::TransferLamports { lamports } => {
+                        instruction_context
+                            .try_borrow_instruction_account(0)?
+                            .checked_sub_lamports(lamports)?;
+                        instruction_context
+                            .try_borrow_instruction_account(1)?
+                            .checked_add_lamports(lamports)?;
+                    }
+                    MockInstruction::SetReturnData { data } => {
+                        let program_id = *program_id;
+                        invoke_context
+                            .transaction_context
+                            .set_return_data(program_id, data)?;
+                    }
                 }
             } else {
                 return Err(InstructionError::InvalidInstructionData);
@@ -1242,6 +1753,7 @@ mod tests {
             Arc::new(ProgramCacheEntry::new_builtin(0, 1, MockBuiltin::vm)),
         );
         invoke_context.program_cache_for_tx_batch = &mut program_cache_for_tx_batch;
+        invoke_context.enable_profiling = true;
 
         // Compute unit consumption tests
         let compute_units_to_consume = 10;
@@ -1276,6 +1788,145 @@ mod tests {
         );
         assert_eq!(result, expected_result);
 
+        // Builtin invocations are profiled with the compute units they charged
+        assert_eq!(
+            invoke_context.take_profiling_results(),
+            vec![InstructionProfile::builtin(
+                callee_program_id,
+                2,
+                compute_units_consumed
+            )]
+        );
+
+        // The outer instruction was pushed without being processed
+        assert_eq!(
+            invoke_context.take_instruction_trace_outcomes(),
+            vec![
+                InstructionOutcome::default(),
+                InstructionOutcome {
+                    compute_units_consumed,
+                    failed: expected_result.is_err(),
+                },
+            ]
+        );
+
+        invoke_context.pop().unwrap();
+    }
+
+    #[test]
+    fn test_profiling_lamport_deltas_and_return_data() {
+        let callee_program_id = solana_pubkey::new_rand();
+        let owned_account = AccountSharedData::new(42, 1, &callee_program_id);
+        let not_owned_account = AccountSharedData::new(84, 1, &solana_pubkey::new_rand());
+        let readonly_account = AccountSharedData::new(168, 1, &solana_pubkey::new_rand());
+        let loader_account = AccountSharedData::new(0, 1, &native_loader::id());
+        let mut program_account = AccountSharedData::new(1, 1, &native_loader::id());
+        program_account.set_executable(true);
+        let transaction_accounts = vec![
+            (solana_pubkey::new_rand(), owned_account),
+            (solana_pubkey::new_rand(), not_owned_account),
+            (solana_pubkey::new_rand(), readonly_account),
+            (callee_program_id, program_account),
+            (solana_pubkey::new_rand(), loader_account),
+        ];
+        let owned_key = transaction_accounts.first().unwrap().0;
+        let not_owned_key = transaction_accounts.get(1).unwrap().0;
+        let metas = vec![
+            AccountMeta::new(owned_key, false),
+            AccountMeta::new(not_owned_key, false),
+            AccountMeta::new_readonly(transaction_accounts.get(2).unwrap().0, false),
+        ];
+        let instruction_accounts = (0..4)
+            .map(|instruction_account_index| {
+                InstructionAccount::new(
+                    instruction_account_index,
+                    false,
+                    instruction_account_index < 2,
+                )
+            })
+            .collect::<Vec<_>>();
+        with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+        let mut program_cache_for_tx_batch = ProgramCacheForTxBatch::default();
+        program_cache_for_tx_batch.replenish(
+            callee_program_id,
+            Arc::new(ProgramCacheEntry::new_builtin(0, 1, MockBuiltin::vm)),
+        );
+        invoke_context.program_cache_for_tx_batch = &mut program_cache_for_tx_batch;
+
+        invoke_context
+            .transaction_context
+            .configure_next_instruction_for_tests(4, instruction_accounts, &[])
+            .unwrap();
+        invoke_context.push().unwrap();
+        invoke_context.enable_profiling = true;
+        invoke_context.profile_return_data_bytes_limit = 2;
+        for instruction in [
+            MockInstruction::TransferLamports { lamports: 10 },
+            MockInstruction::NoopSuccess,
+            MockInstruction::SetReturnData {
+                data: vec![1, 2, 3],
+            },
+        ] {
+            let inner_instruction =
+                Instruction::new_with_bincode(callee_program_id, &instruction, metas.clone());
+            invoke_context
+                .native_invoke(inner_instruction, &[])
+                .unwrap();
+        }
+
+        // Only the balances which changed are part of the profile
+        let results = invoke_context.take_profiling_results();
+        assert_eq!(
+            results
+                .iter()
+                .map(|profile| profile.lamport_deltas.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                BTreeMap::from([(owned_key, -10), (not_owned_key, 10)]),
+                BTreeMap::new(),
+                BTreeMap::new(),
+            ]
+        );
+        // Return data is truncated to the configured limit
+        assert_eq!(
+            results
+                .iter()
+                .map(|profile| profile.return_data.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                None,
+                None,
+                Some(ProfileReturnData {
+                    program_id: callee_program_id,
+                    data: vec![1, 2],
+                    len: 3,
+                }),
+            ]
+        );
+        // Each instruction starts where the previous one left the compute meter
+        assert_eq!(
+            results
+                .iter()
+                .map(|profile| profile.start_cu)
+                .collect::<Vec<_>>(),
+            vec![
+                0,
+                MOCK_BUILTIN_COMPUTE_UNIT_COST,
+                MOCK_BUILTIN_COMPUTE_UNIT_COST.saturating_mul(2),
+            ]
+        );
+
+        // Without profiling nothing is recorded
+        invoke_context.enable_profiling = false;
+        let inner_instruction = Instruction::new_with_bincode(
+            callee_program_id,
+            &MockInstruction::TransferLamports { lamports: 10 },
+            metas,
+        );
+        invoke_context
+            .native_invoke(inner_instruction, &[])
+            .unwrap();
+        assert!(invoke_context.take_profiling_results().is_empty());
         invoke_context.pop().unwrap();
     }
 
@@ -1299,6 +1950,395 @@ mod tests {
         invoke_context.pop().unwrap();
     }
 
+    #[test]
+    fn test_should_profile_cpi() {
+        let transaction_accounts = vec![(solana_pubkey::new_rand(), AccountSharedData::default())];
+        with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+        invoke_context
+            .transaction_context
+            .configure_next_instruction_for_tests(0, vec![], &[])
+            .unwrap();
+        invoke_context.push().unwrap();
+        invoke_context.enable_profiling = true;
+        let profiling = invoke_context.new_profiling_state().unwrap();
+        invoke_context
+            .set_syscall_context(SyscallContext {
+                allocator: BpfAllocator::new(0),
+                accounts_metadata: Vec::new(),
+                trace_log: Vec::new(),
+                profiling,
+            })
+            .unwrap();
+        assert!(!invoke_context.should_profile_cpi());
+
+        invoke_context.profile_cpis = true;
+        assert!(invoke_context.should_profile_cpi());
+
+        invoke_context.profile_cpis = false;
+        invoke_context
+            .get_syscall_context_mut()
+            .unwrap()
+            .profiling
+            .as_mut()
+            .unwrap()
+            .start_section("section", 1_000, None);
+        assert!(invoke_context.should_profile_cpi());
+
+        // Instructions which are not profiled do not record CPIs
+        invoke_context.profile_cpis = true;
+        invoke_context
+            .get_syscall_context_mut()
+            .unwrap()
+            .profiling
+            .as_mut()
+            .unwrap()
+            .end_section("section", 900, None);
+        invoke_context.get_syscall_context_mut().unwrap().profiling = None;
+        assert!(!invoke_context.should_profile_cpi());
+        invoke_context.pop().unwrap();
+    }
+
+    #[test]
+    fn test_implicit_profiling_sections() {
+        let program_id = Pubkey::new_unique();
+        let transaction_accounts = vec![(program_id, AccountSharedData::default())];
+        with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+        invoke_context
+            .transaction_context
+            .configure_next_instruction_for_tests(0, vec![], &[])
+            .unwrap();
+        invoke_context.push().unwrap();
+
+        invoke_context.enable_profiling = false;
+        assert!(invoke_context.new_profiling_state().unwrap().is_none());
+        invoke_context.enable_profiling = true;
+        let profiling = invoke_context.new_profiling_state().unwrap().unwrap();
+        assert_eq!(profiling.active_sections(), 0);
+
+        invoke_context.implicit_profiling_sections = true;
+        let profiling = invoke_context.new_profiling_state().unwrap();
+        invoke_context
+            .set_syscall_context(SyscallContext {
+                allocator: BpfAllocator::new(0),
+                accounts_metadata: Vec::new(),
+                trace_log: Vec::new(),
+                profiling,
+            })
+            .unwrap();
+        // The implicit section labels every CPI of the instruction
+        assert!(invoke_context.should_profile_cpi());
+
+        invoke_context.consume_checked(100).unwrap();
+        invoke_context.end_implicit_profiling_section().unwrap();
+        let profiling = invoke_context
+            .get_syscall_context()
+            .unwrap()
+            .profiling
+            .as_ref()
+            .unwrap();
+        assert_eq!(profiling.active_sections(), 0);
+        assert_eq!(profiling.entries().len(), 1);
+        let entry = profiling.entries().first().unwrap();
+        assert_eq!(entry.name, program_id.to_string());
+        assert_eq!(entry.depth, 0);
+        assert_eq!(entry.consumed(), 100);
+
+        // Closing the section again does nothing
+        invoke_context.end_implicit_profiling_section().unwrap();
+        assert_eq!(
+            invoke_context
+                .get_syscall_context()
+                .unwrap()
+                .profiling
+                .as_ref()
+                .unwrap()
+                .entries()
+                .len(),
+            1
+        );
+        invoke_context.pop().unwrap();
+    }
+
+    #[test]
+    fn test_profile_return_data_of_callee() {
+        let caller_program_id = Pubkey::new_unique();
+        let callee_program_id = Pubkey::new_unique();
+        let transaction_accounts = vec![
+            (caller_program_id, AccountSharedData::default()),
+            (callee_program_id, AccountSharedData::default()),
+        ];
+        with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+        invoke_context.enable_profiling = true;
+        for program_index in [0, 1] {
+            invoke_context
+                .transaction_context
+                .configure_next_instruction_for_tests(program_index, vec![], &[])
+                .unwrap();
+            invoke_context.push().unwrap();
+        }
+
+        // The return data set by the callee is part of its profile
+        invoke_context
+            .transaction_context
+            .set_return_data(callee_program_id, vec![1, 2, 3])
+            .unwrap();
+        assert_eq!(
+            invoke_context.profile_return_data(),
+            Some(ProfileReturnData {
+                program_id: callee_program_id,
+                data: vec![1, 2, 3],
+                len: 3,
+            }),
+        );
+        invoke_context.pop().unwrap();
+
+        // But not of the profile of the caller, which returns without setting any
+        assert_eq!(invoke_context.profile_return_data(), None);
+        invoke_context
+            .transaction_context
+            .set_return_data(caller_program_id, vec![4])
+            .unwrap();
+        assert_eq!(
+            invoke_context.profile_return_data(),
+            Some(ProfileReturnData {
+                program_id: caller_program_id,
+                data: vec![4],
+                len: 1,
+            }),
+        );
+        invoke_context.pop().unwrap();
+    }
+
+    #[test]
+    fn test_profiling_section_closed_by_caller() {
+        let program_id = Pubkey::new_unique();
+        let transaction_accounts = vec![(program_id, AccountSharedData::default())];
+        with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+        invoke_context.enable_profiling = true;
+        invoke_context.mock_set_remaining(1_000);
+        for _ in 0..2 {
+            invoke_context
+                .transaction_context
+                .configure_next_instruction_for_tests(0, vec![], &[])
+                .unwrap();
+            invoke_context.push().unwrap();
+            let profiling = invoke_context.new_profiling_state().unwrap();
+            invoke_context
+                .set_syscall_context(SyscallContext {
+                    allocator: BpfAllocator::new(0),
+                    accounts_metadata: Vec::new(),
+                    trace_log: Vec::new(),
+                    profiling,
+                })
+                .unwrap();
+        }
+
+        // The callee opens a section and returns without closing it
+        invoke_context
+            .get_syscall_context_mut()
+            .unwrap()
+            .profiling
+            .as_mut()
+            .unwrap()
+            .start_section("callee", 900, Some(16));
+        invoke_context.pop().unwrap();
+        assert!(invoke_context.take_profiling_results().is_empty());
+        assert!(invoke_context.is_profiling_active());
+
+        // The caller adopted the section and closes it
+        let profiling = invoke_context
+            .get_syscall_context_mut()
+            .unwrap()
+            .profiling
+            .as_mut()
+            .unwrap();
+        assert_eq!(profiling.section_owner("callee"), Some(1));
+        assert!(profiling.end_section("callee", 825, None));
+        assert!(!invoke_context.is_profiling_active());
+        let entries = invoke_context
+            .get_syscall_context()
+            .unwrap()
+            .profiling
+            .as_ref()
+            .unwrap()
+            .entries()
+            .to_vec();
+        assert_eq!(entries.len(), 1);
+        let entry = entries.first().unwrap();
+        assert_eq!(entry.name, "callee");
+        assert_eq!(entry.depth, 0);
+        assert_eq!((entry.start_cu, entry.end_cu), (100, 175));
+        // The callee's heap sample does not describe the caller's heap
+        assert_eq!(entry.heap_start, None);
+
+        // Sections still active when the transaction level instruction returns are discarded
+        invoke_context
+            .get_syscall_context_mut()
+            .unwrap()
+            .profiling
+            .as_mut()
+            .unwrap()
+            .start_section("unclosed", 800, None);
+        invoke_context.pop().unwrap();
+        assert!(!invoke_context.is_profiling_active());
+        let results = invoke_context.take_profiling_results();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results.first().unwrap().entries, entries);
+    }
+
+    #[test]
+    fn test_take_profiling_results() {
+        let program_id = Pubkey::new_unique();
+        let transaction_accounts = vec![(program_id, AccountSharedData::default())];
+        with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+        for name in [Some("section"), None] {
+            invoke_context
+                .transaction_context
+                .configure_next_instruction_for_tests(0, vec![], &[])
+                .unwrap();
+            invoke_context.push().unwrap();
+            let mut profiling = ProfilingState::new(1_000);
+            if let Some(name) = name {
+                profiling.start_section(name, 900, None);
+                profiling.end_section(name, 800, Some(64));
+                profiling.counter_add(1, 2);
+            }
+            invoke_context
+                .set_syscall_context(SyscallContext {
+                    allocator: BpfAllocator::new(0),
+                    accounts_metadata: Vec::new(),
+                    trace_log: Vec::new(),
+                    profiling: Some(profiling),
+                })
+                .unwrap();
+            invoke_context.pop().unwrap();
+        }
+
+        // The instruction which did not record anything is left out
+        let results = invoke_context.take_profiling_results();
+        assert_eq!(results.len(), 1);
+        let result = results.first().unwrap();
+        assert_eq!(result.program_id, program_id);
+        assert_eq!(result.stack_height, 1);
+        assert_eq!(
+            result
+                .entries
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.start_cu, entry.consumed()))
+                .collect::<Vec<_>>(),
+            vec![("section", 100, 100)]
+        );
+        assert_eq!(result.counters, BTreeMap::from([(1, 2)]));
+        assert!(invoke_context.take_profiling_results().is_empty());
+    }
+
+    #[test]
+    fn test_instrumentation_observer() {
+        #[derive(Default)]
+        struct MockObserver {
+            events: std::sync::Mutex<Vec<String>>,
+        }
+        impl InstrumentationObserver for MockObserver {
+            fn instruction_begin(&self, program_id: &Pubkey, stack_height: usize) {
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push(format!("begin {program_id} {stack_height}"));
+            }
+            fn instruction_end(
+                &self,
+                program_id: &Pubkey,
+                stack_height: usize,
+                compute_units_consumed: u64,
+                result: &Result<(), InstructionError>,
+            ) {
+                self.events.lock().unwrap().push(format!(
+                    "end {program_id} {stack_height} {compute_units_consumed} {result:?}"
+                ));
+            }
+            fn cpi_begin(&self, caller_id: &Pubkey, callee_id: &Pubkey, stack_height: usize) {
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push(format!("cpi {caller_id} {callee_id} {stack_height}"));
+            }
+            fn cpi_end(
+                &self,
+                caller_id: &Pubkey,
+                callee_id: &Pubkey,
+                result: &Result<(), InstructionError>,
+            ) {
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push(format!("cpi end {caller_id} {callee_id} {result:?}"));
+            }
+        }
+
+        let callee_program_id = solana_pubkey::new_rand();
+        let caller_program_id = solana_pubkey::new_rand();
+        let mut program_account = AccountSharedData::new(1, 1, &native_loader::id());
+        program_account.set_executable(true);
+        let transaction_accounts = vec![
+            (
+                solana_pubkey::new_rand(),
+                AccountSharedData::new(42, 1, &callee_program_id),
+            ),
+            (
+                solana_pubkey::new_rand(),
+                AccountSharedData::new(84, 1, &solana_pubkey::new_rand()),
+            ),
+            (callee_program_id, program_account),
+            (
+                caller_program_id,
+                AccountSharedData::new(0, 1, &native_loader::id()),
+            ),
+        ];
+        let metas = vec![
+            AccountMeta::new(transaction_accounts.first().unwrap().0, false),
+            AccountMeta::new_readonly(transaction_accounts.get(1).unwrap().0, false),
+        ];
+        with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+        let mut program_cache_for_tx_batch = ProgramCacheForTxBatch::default();
+        program_cache_for_tx_batch.replenish(
+            callee_program_id,
+            Arc::new(ProgramCacheEntry::new_builtin(0, 1, MockBuiltin::vm)),
+        );
+        invoke_context.program_cache_for_tx_batch = &mut program_cache_for_tx_batch;
+        let observer = std::sync::Arc::new(MockObserver::default());
+        invoke_context.environment_config.instrumentation_observer = Some(observer.clone());
+
+        invoke_context
+            .transaction_context
+            .configure_next_instruction_for_tests(
+                3,
+                vec![
+                    InstructionAccount::new(0, false, true),
+                    InstructionAccount::new(1, false, false),
+                    InstructionAccount::new(2, false, false),
+                ],
+                &[],
+            )
+            .unwrap();
+        invoke_context.push().unwrap();
+        let inner_instruction =
+            Instruction::new_with_bincode(callee_program_id, &MockInstruction::NoopSuccess, metas);
+        invoke_context
+            .native_invoke(inner_instruction, &[])
+            .unwrap();
+        invoke_context.pop().unwrap();
+
+        assert_eq!(
+            *observer.events.lock().unwrap(),
+            vec![
+                format!("cpi {caller_program_id} {callee_program_id} 2"),
+                format!("begin {callee_program_id} 2"),
+                format!("end {callee_program_id} 2 {MOCK_BUILTIN_COMPUTE_UNIT_COST} Ok(())"),
+                format!("cpi end {caller_program_id} {callee_program_id} Ok(())"),
+            ]
+        );
+    }
+
     #[test_case(0; "Resize the account to *the same size*, so not consuming any additional size")]
     #[test_case(1; "Resize the account larger")]
     #[test_case(-1; "Resize the account smaller")]
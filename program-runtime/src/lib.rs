@@ -7,12 +7,28 @@
 extern crate solana_svm_metrics;
 
 pub use solana_sbpf;
+pub mod chrome_trace;
+pub mod csv;
 pub mod execution_budget;
+#[cfg(feature = "flamegraph")]
+pub mod flamegraph;
+pub mod folded_stacks;
+pub mod instrumentation;
 pub mod invoke_context;
 pub mod loaded_programs;
 pub mod mem_pool;
+pub mod otlp;
+pub mod profile_diff;
+pub mod profile_report;
+pub mod profile_rollup;
+pub mod profiling;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
 pub mod serialization;
+pub mod speedscope;
 pub mod stable_log;
+#[cfg(feature = "symbolize")]
+pub mod symbolize;
 pub mod sysvar_cache;
 
 // re-exports for macros
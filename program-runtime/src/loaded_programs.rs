@@ -1,5 +1,8 @@
 use {
-    crate::invoke_context::{BuiltinFunctionWithContext, InvokeContext},
+    crate::{
+        invoke_context::{BuiltinFunctionWithContext, InvokeContext},
+        profiling::ProgramLoadProfile,
+    },
     log::{debug, error, log_enabled, trace},
     percentage::PercentageInteger,
     solana_clock::{Epoch, Slot},
@@ -693,6 +696,9 @@ pub struct ProgramCacheForTxBatch {
     pub hit_max_limit: bool,
     pub loaded_missing: bool,
     pub merged_modified: bool,
+    /// Load timings of the programs loaded into this batch which were not reported yet, in the
+    /// order in which they were loaded
+    pub loaded_program_profiles: Vec<ProgramLoadProfile>,
 }
 
 impl ProgramCacheForTxBatch {
@@ -712,6 +718,7 @@ impl ProgramCacheForTxBatch {
             hit_max_limit: false,
             loaded_missing: false,
             merged_modified: false,
+            loaded_program_profiles: Vec::new(),
         }
     }
 
@@ -730,6 +737,7 @@ impl ProgramCacheForTxBatch {
             hit_max_limit: false,
             loaded_missing: false,
             merged_modified: false,
+            loaded_program_profiles: Vec::new(),
         }
     }
 
@@ -0,0 +1,294 @@
+//! Export of instruction profiles as OpenTelemetry spans
+//!
+//! The resulting JSON is an OTLP `ExportTraceServiceRequest`, which can be posted as is to the
+//! `/v1/traces` endpoint of an OTLP/HTTP collector, such as the ones of Jaeger or Tempo. Every
+//! entry of a transaction becomes a span of a single trace, markers being spans without duration.
+//! As in [`chrome_trace`](crate::chrome_trace), one compute unit is shown as one microsecond.
+
+use {
+    crate::profiling::{timeline_sections, InstructionProfile, ProfileEntryKind, TimelineSection},
+    serde_json::{json, Value},
+};
+
+/// Name of the instrumentation scope of the spans
+pub const OTLP_SCOPE_NAME: &str = "solana-program-runtime";
+
+/// `SPAN_KIND_INTERNAL`, the spans do not cross process boundaries
+const SPAN_KIND_INTERNAL: u64 = 1;
+
+/// Convert the entries of a transaction's instruction profiles into OTLP spans
+///
+/// The spans belong to the trace `trace_id`, which starts at `start_time_unix_nano`. Span names
+/// are the names of the entries and spans nest like the sections they were recorded in, the
+/// sections of the invoked instructions nesting within the sections of their callers, see
+/// [`timeline_sections`]. The compute units, heap samples, heap usage and attributes of the
+/// entries are attached as span attributes.
+pub fn otlp_trace(
+    profiles: &[InstructionProfile],
+    service_name: &str,
+    trace_id: [u8; 16],
+    start_time_unix_nano: u64,
+) -> Value {
+    let mut entries = timeline_sections(profiles);
+    entries.extend(profiles.iter().flat_map(|profile| {
+        profile
+            .entries
+            .iter()
+            .filter(|entry| entry.kind == ProfileEntryKind::Mark)
+            .map(|entry| TimelineSection {
+                program_id: &profile.program_id,
+                entry,
+                start_cu: profile.start_cu.saturating_add(entry.start_cu),
+                end_cu: profile.start_cu.saturating_add(entry.end_cu),
+                heap: None,
+            })
+    }));
+    // Stable, so sections still precede the markers recorded when they were opened
+    entries.sort_by_key(|entry| (entry.start_cu, entry.entry.depth));
+
+    let trace_id = format!("{:032x}", u128::from_be_bytes(trace_id));
+    let time = |cu: u64| {
+        start_time_unix_nano
+            .saturating_add(cu.saturating_mul(1_000))
+            .to_string()
+    };
+    let mut spans = Vec::with_capacity(entries.len());
+    let mut open: Vec<(&TimelineSection, u64)> = Vec::new();
+    for (section, span_id) in entries.iter().zip(1u64..) {
+        while open
+            .pop_if(|(parent, _)| !parent.encloses(section))
+            .is_some()
+        {}
+        let entry = section.entry;
+        let mut attributes = vec![
+            attribute(
+                "solana.program_id",
+                json!({ "stringValue": section.program_id.to_string() }),
+            ),
+            attribute(
+                "solana.profile.kind",
+                json!({ "stringValue": entry.kind.as_str() }),
+            ),
+            attribute("solana.profile.depth", int_value(entry.depth as u64)),
+            attribute("solana.profile.start_cu", int_value(section.start_cu)),
+            attribute("solana.profile.consumed_cu", int_value(entry.consumed())),
+            attribute(
+                "solana.profile.return_data_bytes",
+                int_value(entry.return_data_bytes),
+            ),
+            attribute(
+                "solana.profile.bulk_closed",
+                json!({ "boolValue": entry.bulk_closed }),
+            ),
+        ];
+        attributes.extend(
+            [
+                ("solana.profile.heap_start", entry.heap_start),
+                ("solana.profile.heap_end", entry.heap_end),
+                (
+                    "solana.profile.heap_total_bytes",
+                    section.heap.map(|heap| heap.total_bytes),
+                ),
+                (
+                    "solana.profile.heap_net_bytes",
+                    section.heap.map(|heap| heap.net_bytes),
+                ),
+                (
+                    "solana.profile.heap_peak_bytes",
+                    section.heap.map(|heap| heap.peak_bytes),
+                ),
+            ]
+            .into_iter()
+            .filter_map(|(key, bytes)| Some(attribute(key, int_value(bytes?)))),
+        );
+        attributes.extend(entry.attributes.iter().map(|(key, value)| {
+            attribute(
+                &format!("solana.profile.attribute.{key}"),
+                json!({ "stringValue": value }),
+            )
+        }));
+        let mut span = json!({
+            "traceId": trace_id,
+            "spanId": format!("{span_id:016x}"),
+            "name": entry.name,
+            "kind": SPAN_KIND_INTERNAL,
+            "startTimeUnixNano": time(section.start_cu),
+            "endTimeUnixNano": time(section.end_cu),
+            "attributes": attributes,
+        });
+        if let Some((_parent, parent_span_id)) = open.last() {
+            if let Some(span) = span.as_object_mut() {
+                span.insert(
+                    "parentSpanId".to_string(),
+                    json!(format!("{parent_span_id:016x}")),
+                );
+            }
+        }
+        spans.push(span);
+        open.push((section, span_id));
+    }
+
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [attribute("service.name", json!({ "stringValue": service_name }))],
+            },
+            "scopeSpans": [{
+                "scope": { "name": OTLP_SCOPE_NAME },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+fn attribute(key: &str, value: Value) -> Value {
+    json!({ "key": key, "value": value })
+}
+
+/// 64 bit integers are encoded as strings in OTLP/JSON
+fn int_value(value: u64) -> Value {
+    json!({ "intValue": value.to_string() })
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use {
+        super::*, crate::profiling::ProfileEntry, solana_pubkey::Pubkey, std::collections::BTreeMap,
+    };
+
+    fn entry(
+        kind: ProfileEntryKind,
+        name: &str,
+        depth: usize,
+        start_cu: u64,
+        end_cu: u64,
+    ) -> ProfileEntry {
+        ProfileEntry {
+            kind,
+            name: name.to_string(),
+            depth,
+            start_cu,
+            end_cu,
+            heap_start: None,
+            heap_end: None,
+            return_data_bytes: 0,
+            bulk_closed: false,
+            attributes: BTreeMap::new(),
+            call_site: None,
+        }
+    }
+
+    fn attribute_value<'a>(span: &'a Value, key: &str) -> Option<&'a Value> {
+        span["attributes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|attribute| attribute["key"] == key)
+            .map(|attribute| &attribute["value"])
+    }
+
+    #[test]
+    fn test_otlp_trace() {
+        let outer = Pubkey::new_unique();
+        let inner = Pubkey::new_unique();
+        let mut handle = entry(ProfileEntryKind::Section, "handle", 0, 0, 120);
+        handle.heap_end = Some(2048);
+        handle
+            .attributes
+            .insert("mint".to_string(), "usdc".to_string());
+        let profiles = [
+            // Invoked from within the `handle` section of the caller
+            InstructionProfile {
+                program_id: inner,
+                stack_height: 2,
+                start_cu: 150,
+                entries: vec![entry(ProfileEntryKind::Section, "transfer", 1, 10, 40)],
+                ..InstructionProfile::default()
+            },
+            InstructionProfile {
+                program_id: outer,
+                stack_height: 1,
+                start_cu: 100,
+                entries: vec![
+                    entry(ProfileEntryKind::Mark, "checkpoint", 1, 20, 20),
+                    handle,
+                    entry(ProfileEntryKind::Section, "log", 0, 120, 130),
+                ],
+                ..InstructionProfile::default()
+            },
+        ];
+
+        let trace = otlp_trace(&profiles, "test-validator", [0xab; 16], 1_000_000);
+        let resource_spans = &trace["resourceSpans"][0];
+        assert_eq!(
+            resource_spans["resource"]["attributes"][0]["value"]["stringValue"],
+            "test-validator"
+        );
+        assert_eq!(
+            resource_spans["scopeSpans"][0]["scope"]["name"],
+            OTLP_SCOPE_NAME
+        );
+        let spans = resource_spans["scopeSpans"][0]["spans"].as_array().unwrap();
+        assert_eq!(
+            spans
+                .iter()
+                .map(|span| (
+                    span["name"].as_str().unwrap(),
+                    span["spanId"].as_str().unwrap(),
+                    span["parentSpanId"].as_str(),
+                    span["startTimeUnixNano"].as_str().unwrap(),
+                    span["endTimeUnixNano"].as_str().unwrap(),
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("handle", "0000000000000001", None, "1100000", "1220000"),
+                (
+                    "checkpoint",
+                    "0000000000000002",
+                    Some("0000000000000001"),
+                    "1120000",
+                    "1120000"
+                ),
+                (
+                    "transfer",
+                    "0000000000000003",
+                    Some("0000000000000001"),
+                    "1160000",
+                    "1190000"
+                ),
+                ("log", "0000000000000004", None, "1220000", "1230000"),
+            ]
+        );
+        assert!(spans
+            .iter()
+            .all(|span| span["traceId"] == "abababababababababababababababab"));
+
+        let handle = &spans[0];
+        assert_eq!(
+            attribute_value(handle, "solana.program_id"),
+            Some(&json!({ "stringValue": outer.to_string() }))
+        );
+        assert_eq!(
+            attribute_value(handle, "solana.profile.consumed_cu"),
+            Some(&json!({ "intValue": "120" }))
+        );
+        assert_eq!(
+            attribute_value(handle, "solana.profile.heap_end"),
+            Some(&json!({ "intValue": "2048" }))
+        );
+        assert_eq!(attribute_value(handle, "solana.profile.heap_start"), None);
+        assert_eq!(
+            attribute_value(handle, "solana.profile.heap_total_bytes"),
+            None
+        );
+        assert_eq!(
+            attribute_value(handle, "solana.profile.attribute.mint"),
+            Some(&json!({ "stringValue": "usdc" }))
+        );
+        assert_eq!(
+            attribute_value(&spans[2], "solana.program_id"),
+            Some(&json!({ "stringValue": inner.to_string() }))
+        );
+    }
+}
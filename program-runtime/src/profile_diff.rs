@@ -0,0 +1,347 @@
+//! Structural comparison of the profiles of two executions of a transaction
+//!
+//! Sections are identified by the program which recorded them and their name, occurrences of
+//! the same section being added up. Comparing the profile of a transaction before and after a
+//! program change shows which sections appeared, disappeared or changed their compute units,
+//! largest regression first. [`check_budgets`] turns such a comparison into a gate for CI, which
+//! fails once a section exceeds its compute unit budget.
+
+use {
+    crate::profiling::{InstructionProfile, ProfileEntryKind},
+    serde::Deserialize,
+    solana_pubkey::Pubkey,
+    std::{collections::BTreeMap, fmt},
+};
+
+/// Compute units consumed by a section in both profiles, see [`profile_diff`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionDiff {
+    pub program_id: Pubkey,
+    pub name: String,
+    /// Compute units consumed by all occurrences of the section in the base profile, `None` if
+    /// the section was not recorded there
+    pub base_cu: Option<u64>,
+    /// Compute units consumed by all occurrences of the section in the head profile, `None` if
+    /// the section was not recorded there
+    pub head_cu: Option<u64>,
+}
+
+impl SectionDiff {
+    /// Whether the section was only recorded in the head profile
+    pub fn is_added(&self) -> bool {
+        self.base_cu.is_none()
+    }
+
+    /// Whether the section was only recorded in the base profile
+    pub fn is_removed(&self) -> bool {
+        self.head_cu.is_none()
+    }
+
+    /// Change of the compute units from the base to the head profile, positive for regressions
+    pub fn delta_cu(&self) -> i128 {
+        i128::from(self.head_cu.unwrap_or(0)).saturating_sub(i128::from(self.base_cu.unwrap_or(0)))
+    }
+
+    /// Change of the compute units relative to the base profile in hundredths of a percent,
+    /// `None` if the section did not consume any compute units in the base profile
+    pub fn delta_basis_points(&self) -> Option<i128> {
+        self.delta_cu()
+            .saturating_mul(10_000)
+            .checked_div(i128::from(self.base_cu.unwrap_or(0)))
+    }
+}
+
+/// Sum of the compute units consumed by the sections of the profiles, keyed by program and name
+fn section_totals(profiles: &[InstructionProfile]) -> BTreeMap<(Pubkey, &str), u64> {
+    let mut totals = BTreeMap::new();
+    for profile in profiles {
+        for entry in &profile.entries {
+            if entry.kind == ProfileEntryKind::Section {
+                let total: &mut u64 = totals
+                    .entry((profile.program_id, entry.name.as_str()))
+                    .or_default();
+                *total = total.saturating_add(entry.consumed());
+            }
+        }
+    }
+    totals
+}
+
+/// Compare the sections of the instruction profiles of two executions of a transaction
+///
+/// Every section recorded in either profile is listed, ordered from the largest regression to
+/// the largest improvement, sections with the same change being ordered by program and name.
+pub fn profile_diff(base: &[InstructionProfile], head: &[InstructionProfile]) -> Vec<SectionDiff> {
+    let base_totals = section_totals(base);
+    let mut head_totals = section_totals(head);
+    let mut sections: Vec<SectionDiff> = base_totals
+        .into_iter()
+        .map(|((program_id, name), base_cu)| SectionDiff {
+            program_id,
+            name: name.to_string(),
+            base_cu: Some(base_cu),
+            head_cu: head_totals.remove(&(program_id, name)),
+        })
+        .collect();
+    sections.extend(
+        head_totals
+            .into_iter()
+            .map(|((program_id, name), head_cu)| SectionDiff {
+                program_id,
+                name: name.to_string(),
+                base_cu: None,
+                head_cu: Some(head_cu),
+            }),
+    );
+    sections.sort_by(|a, b| {
+        b.delta_cu()
+            .cmp(&a.delta_cu())
+            .then_with(|| (a.program_id, &a.name).cmp(&(b.program_id, &b.name)))
+    });
+    sections
+}
+
+/// Compute unit budgets of sections, see [`check_budgets`]
+///
+/// In JSON, or the equivalent TOML:
+///
+/// ```json
+/// {
+///     "tolerance_percent": 5,
+///     "sections": [
+///         { "name": "parse", "max_cu": 1200 },
+///         {
+///             "name": "transfer",
+///             "program_id": "<address>",
+///             "max_cu": 8000,
+///             "tolerance_percent": 0
+///         }
+///     ]
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SectionBudgets {
+    /// Percentage of their budget by which sections may exceed it, unless they set their own
+    #[serde(default)]
+    pub tolerance_percent: u64,
+    #[serde(default)]
+    pub sections: Vec<SectionBudget>,
+}
+
+/// Compute unit budget of a section, see [`SectionBudgets`]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SectionBudget {
+    pub name: String,
+    /// Address of the program which records the section, any program if `None`
+    #[serde(default)]
+    pub program_id: Option<String>,
+    /// Compute units the section may consume in total
+    pub max_cu: u64,
+    /// Overrides [`SectionBudgets::tolerance_percent`]
+    #[serde(default)]
+    pub tolerance_percent: Option<u64>,
+}
+
+/// A section which consumed more compute units than its budget and tolerance allow
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BudgetViolation {
+    pub program_id: Pubkey,
+    pub name: String,
+    pub max_cu: u64,
+    /// Budget including the tolerance
+    pub allowed_cu: u64,
+    pub consumed_cu: u64,
+}
+
+impl fmt::Display for BudgetViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "section {} of {} consumed {} CU, exceeding its budget of {} CU by more than the \
+             tolerance of {} CU",
+            self.name,
+            self.program_id,
+            self.consumed_cu,
+            self.max_cu,
+            self.allowed_cu.saturating_sub(self.max_cu),
+        )
+    }
+}
+
+impl std::error::Error for BudgetViolation {}
+
+/// Check the head profile of a [`profile_diff`] against the compute unit budgets of its sections
+///
+/// Sections without a budget and sections which were removed are not checked. Each section
+/// matching a budget may exceed it by the tolerance, a percentage of the budget.
+pub fn check_budgets(
+    diff: &[SectionDiff],
+    budgets: &SectionBudgets,
+) -> Result<(), Vec<BudgetViolation>> {
+    let violations: Vec<_> = budgets
+        .sections
+        .iter()
+        .flat_map(|budget| {
+            let tolerance_percent = budget
+                .tolerance_percent
+                .unwrap_or(budgets.tolerance_percent);
+            let allowed_cu = budget
+                .max_cu
+                .saturating_add(budget.max_cu.saturating_mul(tolerance_percent) / 100);
+            diff.iter()
+                .filter(move |section| {
+                    section.name == budget.name
+                        && budget
+                            .program_id
+                            .as_ref()
+                            .is_none_or(|program_id| *program_id == section.program_id.to_string())
+                })
+                .filter_map(move |section| {
+                    let consumed_cu = section.head_cu?;
+                    (consumed_cu > allowed_cu).then(|| BudgetViolation {
+                        program_id: section.program_id,
+                        name: section.name.clone(),
+                        max_cu: budget.max_cu,
+                        allowed_cu,
+                        consumed_cu,
+                    })
+                })
+        })
+        .collect();
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use {super::*, crate::profiling::ProfileEntry};
+
+    fn profile(program_id: Pubkey, sections: &[(&str, u64)]) -> InstructionProfile {
+        InstructionProfile {
+            program_id,
+            stack_height: 1,
+            entries: sections
+                .iter()
+                .map(|(name, consumed)| ProfileEntry {
+                    kind: ProfileEntryKind::Section,
+                    name: name.to_string(),
+                    depth: 0,
+                    start_cu: 0,
+                    end_cu: *consumed,
+                    heap_start: None,
+                    heap_end: None,
+                    return_data_bytes: 0,
+                    bulk_closed: false,
+                    attributes: BTreeMap::new(),
+                    call_site: None,
+                })
+                .collect(),
+            ..InstructionProfile::default()
+        }
+    }
+
+    #[test]
+    fn test_profile_diff() {
+        let program_id = Pubkey::new_unique();
+        let callee = Pubkey::new_unique();
+        let base = [
+            profile(program_id, &[("parse", 100), ("parse", 50), ("hash", 200)]),
+            profile(callee, &[("transfer", 300), ("log", 40)]),
+        ];
+        let head = [
+            profile(program_id, &[("parse", 180), ("hash", 200), ("verify", 60)]),
+            profile(callee, &[("transfer", 270)]),
+        ];
+
+        let diff = profile_diff(&base, &head);
+        assert_eq!(
+            diff.iter()
+                .map(|section| (
+                    section.name.as_str(),
+                    section.base_cu,
+                    section.head_cu,
+                    section.delta_cu(),
+                    section.delta_basis_points(),
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("verify", None, Some(60), 60, None),
+                ("parse", Some(150), Some(180), 30, Some(2_000)),
+                ("hash", Some(200), Some(200), 0, Some(0)),
+                ("transfer", Some(300), Some(270), -30, Some(-1_000)),
+                ("log", Some(40), None, -40, Some(-10_000)),
+            ]
+        );
+        assert!(diff[0].is_added());
+        assert!(!diff[0].is_removed());
+        assert!(diff[4].is_removed());
+        assert_eq!(diff[3].program_id, callee);
+        assert!(profile_diff(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_check_budgets() {
+        let program_id = Pubkey::new_unique();
+        let callee = Pubkey::new_unique();
+        let diff = profile_diff(
+            &[profile(program_id, &[("parse", 100), ("log", 40)])],
+            &[
+                profile(program_id, &[("parse", 105), ("hash", 200)]),
+                profile(callee, &[("hash", 300)]),
+            ],
+        );
+        let budgets: SectionBudgets = serde_json::from_str(&format!(
+            r#"{{
+                "tolerance_percent": 5,
+                "sections": [
+                    {{ "name": "parse", "max_cu": 100 }},
+                    {{ "name": "log", "max_cu": 10 }},
+                    {{ "name": "hash", "program_id": "{program_id}", "max_cu": 250 }},
+                    {{
+                        "name": "hash",
+                        "program_id": "{callee}",
+                        "max_cu": 290,
+                        "tolerance_percent": 0
+                    }}
+                ]
+            }}"#
+        ))
+        .unwrap();
+        // Removed sections and sections within the tolerance pass
+        assert_eq!(
+            check_budgets(&diff, &budgets),
+            Err(vec![BudgetViolation {
+                program_id: callee,
+                name: "hash".to_string(),
+                max_cu: 290,
+                allowed_cu: 290,
+                consumed_cu: 300,
+            }])
+        );
+
+        let budgets = SectionBudgets {
+            tolerance_percent: 0,
+            sections: vec![SectionBudget {
+                name: "parse".to_string(),
+                max_cu: 100,
+                ..SectionBudget::default()
+            }],
+        };
+        let violations = check_budgets(&diff, &budgets).unwrap_err();
+        assert_eq!(
+            violations[0].to_string(),
+            format!(
+                "section parse of {program_id} consumed 105 CU, exceeding its budget of 100 CU by \
+                 more than the tolerance of 0 CU"
+            )
+        );
+        assert_eq!(check_budgets(&diff, &SectionBudgets::default()), Ok(()));
+        assert!(serde_json::from_str::<SectionBudgets>(r#"{ "tolerance": 5 }"#).is_err());
+    }
+}
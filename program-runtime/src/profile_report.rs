@@ -0,0 +1,435 @@
+//! The profile report of a transaction, the artifact which profiling tools exchange
+//!
+//! A [`ProfileReport`] bundles everything recorded while profiling a transaction: the
+//! transaction it belongs to, the entries, counters and timings of each of its instructions and
+//! the time spent loading its programs. The exporters of this crate render the instruction
+//! profiles of a report, and the report itself serializes to JSON, or to protobuf with the
+//! `protobuf` feature, so that third-party tools can consume it without parsing program logs.
+//!
+//! The JSON representation follows the field names of the Rust types. Addresses are base58
+//! strings, return data is base64 encoded and map keys are strings. It is versioned by
+//! [`ProfileReport::schema_version`]: fields may be added without bumping the version, so
+//! consumers should ignore the fields they do not know, while renaming, removing or changing the
+//! meaning of a field bumps it. [`ProfileReport::from_json`] rejects reports written with a
+//! newer schema.
+//!
+//! ```json
+//! {
+//!     "schema_version": 1,
+//!     "profile_log_version": 6,
+//!     "transaction": {
+//!         "signature": "<signature>",
+//!         "slot": 42,
+//!         "compute_units_consumed": 1200,
+//!         "error": null
+//!     },
+//!     "instructions": [{
+//!         "program_id": "<address>",
+//!         "stack_height": 1,
+//!         "start_cu": 0,
+//!         "entries": [{
+//!             "kind": "section",
+//!             "name": "transfer",
+//!             "depth": 0,
+//!             "start_cu": 100,
+//!             "end_cu": 900,
+//!             "heap_start": null,
+//!             "heap_end": 2048,
+//!             "return_data_bytes": 0,
+//!             "bulk_closed": false,
+//!             "attributes": { "mint": "usdc" },
+//!             "call_site": 1234
+//!         }],
+//!         "counters": { "1": 3 },
+//!         "heap_tags": {},
+//!         "dropped_entries": 0,
+//!         "timings": null,
+//!         "data_transfer": null,
+//!         "lamport_deltas": { "<address>": -5000 },
+//!         "return_data": null,
+//!         "failure": null
+//!     }],
+//!     "program_loads": [],
+//!     "source_locations": [{
+//!         "program_id": "<address>",
+//!         "pc": 1234,
+//!         "function": "token::processor::transfer",
+//!         "file": "src/processor.rs",
+//!         "line": 87
+//!     }]
+//! }
+//! ```
+
+use {
+    crate::profiling::{InstructionProfile, ProgramLoadProfile, PROFILE_LOG_VERSION},
+    serde::{Deserialize, Serialize},
+    solana_pubkey::Pubkey,
+    std::fmt,
+};
+
+/// Version of the schema of [`ProfileReport`]
+pub const PROFILE_REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Everything recorded while profiling a transaction, see the [module documentation](self)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileReport {
+    /// Version of the schema the report was written with, see [`PROFILE_REPORT_SCHEMA_VERSION`]
+    pub schema_version: u32,
+    /// Version of the profile log format the instruction profiles correspond to
+    pub profile_log_version: u8,
+    pub transaction: TransactionMetadata,
+    /// Instruction profiles in the order in which the instructions completed
+    pub instructions: Vec<InstructionProfile>,
+    #[serde(default)]
+    pub program_loads: Vec<ProgramLoadProfile>,
+    /// Source locations of the call sites of the entries, filled in when the programs were
+    /// symbolized, see [`ProfileReport::source_location`]
+    #[serde(default)]
+    pub source_locations: Vec<SourceLocation>,
+}
+
+/// Location in the source of a program of the SBF instruction at a program counter
+///
+/// Resolved from the DWARF debug info of the program's ELF, fields which it does not cover are
+/// `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    #[serde(with = "serde_pubkey")]
+    pub program_id: Pubkey,
+    /// Program counter, counted in SBF instructions like [`ProfileEntry::call_site`]
+    ///
+    /// [`ProfileEntry::call_site`]: crate::profiling::ProfileEntry::call_site
+    pub pc: u64,
+    /// Demangled name of the innermost function, including inlined functions
+    #[serde(default)]
+    pub function: Option<String>,
+    #[serde(default)]
+    pub file: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+}
+
+impl fmt::Display for SourceLocation {
+    /// `function at file:line`, leaving out what is unknown
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.function.as_deref().unwrap_or("?"))?;
+        if let Some(file) = &self.file {
+            write!(f, " at {file}")?;
+            if let Some(line) = self.line {
+                write!(f, ":{line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The transaction a [`ProfileReport`] belongs to
+///
+/// Reports of executions outside of a bank, such as those of `agave-ledger-tool program run`, do
+/// not have a signature or a slot.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransactionMetadata {
+    /// Base58 encoded signature of the transaction
+    pub signature: Option<String>,
+    /// Slot the transaction was executed in
+    pub slot: Option<u64>,
+    /// Compute units consumed by the whole transaction
+    pub compute_units_consumed: u64,
+    /// Why the transaction failed, `None` if it succeeded
+    pub error: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ProfileReportError {
+    Json(serde_json::Error),
+    /// The report was written with a newer schema than [`PROFILE_REPORT_SCHEMA_VERSION`]
+    UnsupportedSchemaVersion(u32),
+}
+
+impl fmt::Display for ProfileReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "invalid profile report: {err}"),
+            Self::UnsupportedSchemaVersion(version) => write!(
+                f,
+                "profile report schema version {version} is newer than the supported version \
+                 {PROFILE_REPORT_SCHEMA_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProfileReportError {}
+
+impl ProfileReport {
+    /// Report of the profiles of a transaction, in the current schema
+    pub fn new(
+        transaction: TransactionMetadata,
+        instructions: Vec<InstructionProfile>,
+        program_loads: Vec<ProgramLoadProfile>,
+    ) -> Self {
+        Self {
+            schema_version: PROFILE_REPORT_SCHEMA_VERSION,
+            profile_log_version: PROFILE_LOG_VERSION,
+            transaction,
+            instructions,
+            program_loads,
+            source_locations: Vec::new(),
+        }
+    }
+
+    /// Source location of the instruction at `pc` of the program, if it was symbolized
+    pub fn source_location(&self, program_id: &Pubkey, pc: u64) -> Option<&SourceLocation> {
+        self.source_locations
+            .iter()
+            .find(|location| location.program_id == *program_id && location.pc == pc)
+    }
+
+    /// Render the report as pretty printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Parse a report rendered by [`ProfileReport::to_json`]
+    pub fn from_json(json: &str) -> Result<Self, ProfileReportError> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            schema_version: u32,
+        }
+
+        // Checked first, as a newer schema may not deserialize into the current types
+        let Versioned { schema_version } =
+            serde_json::from_str(json).map_err(ProfileReportError::Json)?;
+        if schema_version > PROFILE_REPORT_SCHEMA_VERSION {
+            return Err(ProfileReportError::UnsupportedSchemaVersion(schema_version));
+        }
+        serde_json::from_str(json).map_err(ProfileReportError::Json)
+    }
+}
+
+/// Addresses as base58 strings
+pub(crate) mod serde_pubkey {
+    use {
+        serde::{de::Error, Deserialize, Deserializer, Serializer},
+        solana_pubkey::Pubkey,
+        std::str::FromStr,
+    };
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(pubkey)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let pubkey = String::deserialize(deserializer)?;
+        Pubkey::from_str(&pubkey).map_err(Error::custom)
+    }
+}
+
+/// Lamport changes keyed by base58 encoded addresses
+pub(crate) mod serde_lamport_deltas {
+    use {
+        serde::{de::Error, Deserialize, Deserializer, Serializer},
+        solana_pubkey::Pubkey,
+        std::{collections::BTreeMap, str::FromStr},
+    };
+
+    pub fn serialize<S: Serializer>(
+        deltas: &BTreeMap<Pubkey, i128>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            deltas
+                .iter()
+                .map(|(pubkey, delta)| (pubkey.to_string(), delta)),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<Pubkey, i128>, D::Error> {
+        BTreeMap::<String, i128>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(pubkey, delta)| Ok((Pubkey::from_str(&pubkey).map_err(Error::custom)?, delta)))
+            .collect()
+    }
+}
+
+/// Bytes as a base64 string
+pub(crate) mod serde_base64 {
+    use {
+        base64::{prelude::BASE64_STANDARD, Engine},
+        serde::{de::Error, Deserialize, Deserializer, Serializer},
+    };
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64_STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let bytes = String::deserialize(deserializer)?;
+        BASE64_STANDARD.decode(bytes).map_err(Error::custom)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use {
+        super::*,
+        crate::profiling::{
+            InstructionDataTransfer, InstructionTimings, ProfileEntry, ProfileEntryKind,
+            ProfileReturnData,
+        },
+        assert_matches::assert_matches,
+        std::collections::BTreeMap,
+    };
+
+    fn report() -> ProfileReport {
+        let program_id = Pubkey::new_unique();
+        let instruction = InstructionProfile {
+            program_id,
+            stack_height: 1,
+            start_cu: 100,
+            entries: vec![ProfileEntry {
+                kind: ProfileEntryKind::Section,
+                name: "transfer".to_string(),
+                depth: 0,
+                start_cu: 10,
+                end_cu: 90,
+                heap_start: None,
+                heap_end: Some(2048),
+                return_data_bytes: 8,
+                bulk_closed: true,
+                attributes: BTreeMap::from([("mint".to_string(), "usdc".to_string())]),
+                call_site: Some(1234),
+            }],
+            counters: BTreeMap::from([(7, 3)]),
+            heap_tags: BTreeMap::from([("accounts".to_string(), 1024)]),
+            dropped_entries: 1,
+            timings: Some(InstructionTimings {
+                serialize_us: 1,
+                create_vm_us: 2,
+                execute_us: 3,
+                deserialize_us: 4,
+            }),
+            data_transfer: Some(InstructionDataTransfer {
+                serialized_bytes: 512,
+                written_back_bytes: 64,
+            }),
+            lamport_deltas: BTreeMap::from([(Pubkey::new_unique(), -5), (Pubkey::new_unique(), 5)]),
+            return_data: Some(ProfileReturnData {
+                program_id,
+                data: vec![1, 2, 3],
+                len: 40,
+            }),
+            failure: Some("custom program error: 0x1".to_string()),
+        };
+        let mut report = ProfileReport::new(
+            TransactionMetadata {
+                signature: Some("sig".to_string()),
+                slot: Some(42),
+                compute_units_consumed: 1200,
+                error: None,
+            },
+            vec![InstructionProfile::default(), instruction],
+            vec![ProgramLoadProfile {
+                program_id,
+                register_syscalls_us: 1,
+                load_elf_us: 2,
+                verify_code_us: 3,
+                jit_compile_us: 4,
+            }],
+        );
+        report.source_locations.push(SourceLocation {
+            program_id,
+            pc: 1234,
+            function: Some("token::processor::transfer".to_string()),
+            file: Some("src/processor.rs".to_string()),
+            line: Some(87),
+        });
+        report
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let report = report();
+        assert_eq!(report.schema_version, PROFILE_REPORT_SCHEMA_VERSION);
+        assert_eq!(report.profile_log_version, PROFILE_LOG_VERSION);
+        let json = report.to_json();
+        assert_eq!(ProfileReport::from_json(&json).unwrap(), report);
+
+        // The documented representation of the fields which are not plain numbers
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let instruction = &value["instructions"][1];
+        let program_id = report.instructions[1].program_id.to_string();
+        assert_eq!(instruction["program_id"], program_id);
+        assert_eq!(instruction["entries"][0]["kind"], "section");
+        assert_eq!(
+            instruction["entries"][0]["heap_start"],
+            serde_json::Value::Null
+        );
+        assert_eq!(instruction["counters"]["7"], 3);
+        assert_eq!(instruction["return_data"]["program_id"], program_id);
+        assert_eq!(instruction["return_data"]["data"], "AQID");
+        let (pubkey, delta) = report.instructions[1]
+            .lamport_deltas
+            .first_key_value()
+            .unwrap();
+        assert_eq!(
+            instruction["lamport_deltas"][pubkey.to_string()],
+            i64::try_from(*delta).unwrap()
+        );
+        assert_eq!(value["program_loads"][0]["program_id"], program_id);
+        assert_eq!(value["transaction"]["slot"], 42);
+        assert_eq!(instruction["entries"][0]["call_site"], 1234);
+        assert_eq!(value["source_locations"][0]["line"], 87);
+    }
+
+    #[test]
+    fn test_source_location() {
+        let report = report();
+        let program_id = report.instructions[1].program_id;
+        let location = report.source_location(&program_id, 1234).unwrap();
+        assert_eq!(
+            location.to_string(),
+            "token::processor::transfer at src/processor.rs:87"
+        );
+        assert_eq!(report.source_location(&program_id, 1235), None);
+        assert_eq!(report.source_location(&Pubkey::new_unique(), 1234), None);
+        assert_eq!(
+            SourceLocation {
+                function: Some("entrypoint".to_string()),
+                ..SourceLocation::default()
+            }
+            .to_string(),
+            "entrypoint"
+        );
+        assert_eq!(SourceLocation::default().to_string(), "?");
+    }
+
+    #[test]
+    fn test_from_json_compatibility() {
+        let report = report();
+        let mut value = serde_json::to_value(&report).unwrap();
+        // Fields added without bumping the schema version are ignored
+        value["transaction"]["fee"] = 5000.into();
+        value["instructions"][0]["added_later"] = true.into();
+        assert_eq!(
+            ProfileReport::from_json(&value.to_string()).unwrap(),
+            report
+        );
+
+        value["schema_version"] = PROFILE_REPORT_SCHEMA_VERSION.saturating_add(1).into();
+        value["instructions"] = "renamed".into();
+        assert_matches!(
+            ProfileReport::from_json(&value.to_string()),
+            Err(ProfileReportError::UnsupportedSchemaVersion(version))
+                if version == PROFILE_REPORT_SCHEMA_VERSION.saturating_add(1)
+        );
+        assert_matches!(
+            ProfileReport::from_json(r#"{ "schema_version": 1 }"#),
+            Err(ProfileReportError::Json(_))
+        );
+    }
+}
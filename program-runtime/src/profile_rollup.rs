@@ -0,0 +1,429 @@
+//! Aggregation of the profile reports of many transactions
+//!
+//! A test campaign or an integration test suite produces a [`ProfileReport`] per transaction.
+//! [`profile_rollup`] adds them up per program and per section, so that the cost of a section
+//! can be followed across the whole run rather than transaction by transaction. The rollup
+//! serializes to JSON and its [`Display`](fmt::Display) implementation renders a summary for
+//! humans.
+//!
+//! Like in [`profile_diff`](crate::profile_diff), sections are identified by the program which
+//! recorded them and their name. The trend of a section compares the compute units it consumed
+//! per transaction in the earlier half of the transactions which recorded it to the later half,
+//! in the order of the reports.
+
+use {
+    crate::{
+        profile_report::{serde_pubkey, ProfileReport},
+        profiling::ProfileEntryKind,
+    },
+    serde::Serialize,
+    solana_pubkey::Pubkey,
+    std::{collections::BTreeMap, fmt},
+};
+
+/// Sections and programs of a set of profile reports, see [`profile_rollup`]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ProfileRollup {
+    /// Number of reports which were aggregated
+    pub transactions: usize,
+    pub failed_transactions: usize,
+    /// Compute units consumed by all transactions
+    pub compute_units_consumed: u64,
+    /// Ordered by decreasing compute units, then by address
+    pub programs: Vec<ProgramRollup>,
+}
+
+/// Instructions and sections of a program across the aggregated reports
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ProgramRollup {
+    #[serde(with = "serde_pubkey")]
+    pub program_id: Pubkey,
+    /// Number of reports in which the program executed an instruction
+    pub transactions: usize,
+    /// Number of instructions of the program, including CPIs
+    pub instructions: u64,
+    pub failed_instructions: u64,
+    /// Compute units consumed by the outermost sections of the program's instructions
+    ///
+    /// Sections nested within others are already covered, and the compute units consumed outside
+    /// of any section are not attributed to the program.
+    pub total_cu: u64,
+    /// Ordered by decreasing compute units, then by name
+    pub sections: Vec<SectionRollup>,
+}
+
+/// All occurrences of a section across the aggregated reports
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SectionRollup {
+    pub name: String,
+    /// Number of reports which recorded the section
+    pub transactions: usize,
+    /// Number of times the section was recorded
+    pub count: u64,
+    /// Compute units consumed by all occurrences of the section
+    pub total_cu: u64,
+    /// Fewest compute units consumed by a single occurrence
+    pub min_cu: u64,
+    /// Most compute units consumed by a single occurrence
+    pub max_cu: u64,
+    /// Change of the compute units consumed per transaction from the earlier to the later half
+    /// of the transactions which recorded the section, in hundredths of a percent
+    ///
+    /// `None` if fewer than two transactions recorded the section, or if it did not consume any
+    /// compute units in the earlier half.
+    pub trend_basis_points: Option<i128>,
+}
+
+impl ProfileRollup {
+    /// Render the rollup as pretty printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl fmt::Display for ProfileRollup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} transactions ({} failed) consumed {} CU",
+            self.transactions, self.failed_transactions, self.compute_units_consumed,
+        )?;
+        for program in &self.programs {
+            writeln!(
+                f,
+                "Program {}: {} CU in sections, {} instructions ({} failed) in {} transactions",
+                program.program_id,
+                program.total_cu,
+                program.instructions,
+                program.failed_instructions,
+                program.transactions,
+            )?;
+            for section in &program.sections {
+                let trend = section.trend_basis_points.map_or_else(
+                    || "-".to_string(),
+                    |basis_points| {
+                        let sign = if basis_points < 0 { "-" } else { "+" };
+                        let basis_points = basis_points.unsigned_abs();
+                        format!("{sign}{}.{:02}%", basis_points / 100, basis_points % 100)
+                    },
+                );
+                writeln!(
+                    f,
+                    "  {:>10} CU x{:<6} {:>8}..{:<8} {:>9} {}",
+                    section.total_cu,
+                    section.count,
+                    section.min_cu,
+                    section.max_cu,
+                    trend,
+                    section.name,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct SectionTotals {
+    count: u64,
+    total_cu: u64,
+    min_cu: u64,
+    max_cu: u64,
+    /// Compute units consumed in each report which recorded the section, in report order
+    per_transaction: Vec<u64>,
+}
+
+impl SectionTotals {
+    fn add(&mut self, consumed_cu: u64) {
+        self.min_cu = if self.count == 0 {
+            consumed_cu
+        } else {
+            self.min_cu.min(consumed_cu)
+        };
+        self.max_cu = self.max_cu.max(consumed_cu);
+        self.count = self.count.saturating_add(1);
+        self.total_cu = self.total_cu.saturating_add(consumed_cu);
+    }
+
+    fn trend_basis_points(&self) -> Option<i128> {
+        let half = self.per_transaction.len() / 2;
+        let mean = |totals: &[u64]| {
+            totals
+                .iter()
+                .map(|cu| i128::from(*cu))
+                .sum::<i128>()
+                .checked_div(i128::try_from(totals.len()).ok()?)
+        };
+        let (earlier, _) = self.per_transaction.split_at(half);
+        let (_, later) = self
+            .per_transaction
+            .split_at(self.per_transaction.len().saturating_sub(half));
+        let earlier = mean(earlier)?;
+        mean(later)?
+            .saturating_sub(earlier)
+            .saturating_mul(10_000)
+            .checked_div(earlier)
+    }
+}
+
+#[derive(Default)]
+struct ProgramTotals {
+    transactions: usize,
+    instructions: u64,
+    failed_instructions: u64,
+    total_cu: u64,
+    sections: BTreeMap<String, SectionTotals>,
+}
+
+/// Aggregate the profile reports of many transactions per program and per section, see the
+/// [module documentation](self)
+pub fn profile_rollup(reports: &[ProfileReport]) -> ProfileRollup {
+    let mut programs: BTreeMap<Pubkey, ProgramTotals> = BTreeMap::new();
+    for report in reports {
+        // Compute units of each section in this report, to follow their trend
+        let mut report_sections: BTreeMap<(Pubkey, &str), u64> = BTreeMap::new();
+        for profile in &report.instructions {
+            let program = programs.entry(profile.program_id).or_default();
+            program.instructions = program.instructions.saturating_add(1);
+            if profile.failure.is_some() {
+                program.failed_instructions = program.failed_instructions.saturating_add(1);
+            }
+            let sections = profile
+                .entries
+                .iter()
+                .filter(|entry| entry.kind == ProfileEntryKind::Section);
+            let outermost_depth = sections.clone().map(|entry| entry.depth).min();
+            for entry in sections {
+                let consumed_cu = entry.consumed();
+                if Some(entry.depth) == outermost_depth {
+                    program.total_cu = program.total_cu.saturating_add(consumed_cu);
+                }
+                program
+                    .sections
+                    .entry(entry.name.clone())
+                    .or_default()
+                    .add(consumed_cu);
+                let report_cu = report_sections
+                    .entry((profile.program_id, entry.name.as_str()))
+                    .or_default();
+                *report_cu = report_cu.saturating_add(consumed_cu);
+            }
+        }
+        let mut report_programs: Vec<&Pubkey> = report
+            .instructions
+            .iter()
+            .map(|profile| &profile.program_id)
+            .collect();
+        report_programs.sort_unstable();
+        report_programs.dedup();
+        for program_id in report_programs {
+            if let Some(program) = programs.get_mut(program_id) {
+                program.transactions = program.transactions.saturating_add(1);
+            }
+        }
+        for ((program_id, name), consumed_cu) in report_sections {
+            if let Some(section) = programs
+                .get_mut(&program_id)
+                .and_then(|program| program.sections.get_mut(name))
+            {
+                section.per_transaction.push(consumed_cu);
+            }
+        }
+    }
+
+    let mut programs: Vec<ProgramRollup> = programs
+        .into_iter()
+        .map(|(program_id, program)| {
+            let mut sections: Vec<SectionRollup> = program
+                .sections
+                .into_iter()
+                .map(|(name, section)| SectionRollup {
+                    trend_basis_points: section.trend_basis_points(),
+                    name,
+                    transactions: section.per_transaction.len(),
+                    count: section.count,
+                    total_cu: section.total_cu,
+                    min_cu: section.min_cu,
+                    max_cu: section.max_cu,
+                })
+                .collect();
+            // Stable, so sections with the same compute units stay ordered by name
+            sections.sort_by(|a, b| b.total_cu.cmp(&a.total_cu));
+            ProgramRollup {
+                program_id,
+                transactions: program.transactions,
+                instructions: program.instructions,
+                failed_instructions: program.failed_instructions,
+                total_cu: program.total_cu,
+                sections,
+            }
+        })
+        .collect();
+    programs.sort_by(|a, b| b.total_cu.cmp(&a.total_cu));
+    ProfileRollup {
+        transactions: reports.len(),
+        failed_transactions: reports
+            .iter()
+            .filter(|report| report.transaction.error.is_some())
+            .count(),
+        compute_units_consumed: reports
+            .iter()
+            .map(|report| report.transaction.compute_units_consumed)
+            .fold(0, u64::saturating_add),
+        programs,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            profile_report::TransactionMetadata,
+            profiling::{InstructionProfile, ProfileEntry},
+        },
+        std::collections::BTreeMap,
+    };
+
+    fn profile(program_id: Pubkey, sections: &[(&str, usize, u64)]) -> InstructionProfile {
+        InstructionProfile {
+            program_id,
+            stack_height: 1,
+            entries: sections
+                .iter()
+                .map(|(name, depth, consumed)| ProfileEntry {
+                    kind: ProfileEntryKind::Section,
+                    name: name.to_string(),
+                    depth: *depth,
+                    start_cu: 0,
+                    end_cu: *consumed,
+                    heap_start: None,
+                    heap_end: None,
+                    return_data_bytes: 0,
+                    bulk_closed: false,
+                    attributes: BTreeMap::new(),
+                    call_site: None,
+                })
+                .collect(),
+            ..InstructionProfile::default()
+        }
+    }
+
+    fn report(
+        compute_units_consumed: u64,
+        error: Option<&str>,
+        instructions: Vec<InstructionProfile>,
+    ) -> ProfileReport {
+        ProfileReport::new(
+            TransactionMetadata {
+                compute_units_consumed,
+                error: error.map(str::to_string),
+                ..TransactionMetadata::default()
+            },
+            instructions,
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_profile_rollup() {
+        let program_id = Pubkey::new_unique();
+        let callee = Pubkey::new_unique();
+        let mut failed = profile(callee, &[("transfer", 1, 50)]);
+        failed.failure = Some("custom program error: 0x1".to_string());
+        let reports = [
+            report(
+                1_000,
+                None,
+                vec![
+                    profile(callee, &[("transfer", 1, 100)]),
+                    profile(program_id, &[("parse", 1, 100), ("handle", 0, 400)]),
+                ],
+            ),
+            report(
+                800,
+                None,
+                vec![profile(
+                    program_id,
+                    &[("parse", 1, 50), ("parse", 1, 70), ("handle", 0, 300)],
+                )],
+            ),
+            report(
+                900,
+                Some("custom program error: 0x1"),
+                vec![
+                    failed,
+                    profile(program_id, &[("parse", 1, 150), ("handle", 0, 500)]),
+                ],
+            ),
+        ];
+
+        let rollup = profile_rollup(&reports);
+        assert_eq!(rollup.transactions, 3);
+        assert_eq!(rollup.failed_transactions, 1);
+        assert_eq!(rollup.compute_units_consumed, 2_700);
+        assert_eq!(
+            rollup.programs,
+            vec![
+                ProgramRollup {
+                    program_id,
+                    transactions: 3,
+                    instructions: 3,
+                    failed_instructions: 0,
+                    total_cu: 1_200,
+                    sections: vec![
+                        SectionRollup {
+                            name: "handle".to_string(),
+                            transactions: 3,
+                            count: 3,
+                            total_cu: 1_200,
+                            min_cu: 300,
+                            max_cu: 500,
+                            trend_basis_points: Some(2_500),
+                        },
+                        SectionRollup {
+                            name: "parse".to_string(),
+                            transactions: 3,
+                            count: 4,
+                            total_cu: 370,
+                            min_cu: 50,
+                            max_cu: 150,
+                            trend_basis_points: Some(5_000),
+                        },
+                    ],
+                },
+                ProgramRollup {
+                    program_id: callee,
+                    transactions: 2,
+                    instructions: 2,
+                    failed_instructions: 1,
+                    total_cu: 150,
+                    sections: vec![SectionRollup {
+                        name: "transfer".to_string(),
+                        transactions: 2,
+                        count: 2,
+                        total_cu: 150,
+                        min_cu: 50,
+                        max_cu: 100,
+                        trend_basis_points: Some(-5_000),
+                    }],
+                },
+            ]
+        );
+
+        let summary = rollup.to_string();
+        assert!(summary.starts_with("3 transactions (1 failed) consumed 2700 CU\n"));
+        assert!(summary.contains(&format!(
+            "Program {callee}: 150 CU in sections, 2 instructions (1 failed) in 2 transactions"
+        )));
+        assert!(summary.contains("        1200 CU x3           300..500        +25.00% handle"));
+        let json: serde_json::Value = serde_json::from_str(&rollup.to_json()).unwrap();
+        assert_eq!(json["programs"][1]["program_id"], callee.to_string());
+        assert_eq!(
+            json["programs"][1]["sections"][0]["trend_basis_points"],
+            -5_000
+        );
+        assert_eq!(profile_rollup(&[]), ProfileRollup::default());
+    }
+}
@@ -0,0 +1,2676 @@
+//! Per-instruction profiling state populated by the profiling syscalls
+//!
+//! Programs annotate their execution with named sections, point markers and counters. Everything
+//! is recorded host side, costs no compute units, and is reported through
+//! [`stable_log::program_profile`](crate::stable_log::program_profile) once the program returns.
+//!
+//! In metered mode, which only `agave-ledger-tool program run` enables, the profiling syscalls
+//! which record something are charged like regular syscalls instead, and
+//! [`ProfilingState::post_process`] removes those charges from the report.
+//!
+//! Profiling does not change the outcome, the compute units consumed nor the non-profile logs of
+//! an instruction. The only exceptions are the `sol_profile_enabled` and `sol_profile_section_cu`
+//! syscalls, which deliberately let a program query whether it is profiled.
+
+use {
+    crate::profile_report::{serde_base64, serde_lamport_deltas, serde_pubkey},
+    serde::{Deserialize, Serialize},
+    solana_pubkey::Pubkey,
+    spl_generic_token::{token, token_2022},
+    std::{cell::RefCell, collections::BTreeMap, fmt, rc::Rc, str::FromStr},
+};
+
+/// Heap argument value indicating that the program did not sample its heap usage
+///
+/// The section syscalls sample the heap usage on the program's behalf in that case.
+pub const NO_HEAP_SAMPLE: u64 = u64::MAX;
+
+/// Maximum length in bytes of a section or marker name
+pub const MAX_PROFILE_NAME_LEN: u64 = 64;
+
+/// Maximum number of entries recorded per instruction, further entries are dropped
+pub const MAX_PROFILE_ENTRIES: usize = 1024;
+
+/// Maximum number of distinct counters per instruction, further counters are dropped
+pub const MAX_PROFILE_COUNTERS: usize = 64;
+
+/// Maximum number of section ids which can be given a name per instruction
+pub const MAX_PROFILE_REGISTERED_NAMES: usize = 256;
+
+/// Maximum number of attributes per section, further attributes are dropped
+pub const MAX_PROFILE_ATTRIBUTES: usize = 16;
+
+/// Maximum length in bytes of a section attribute value
+pub const MAX_PROFILE_ATTRIBUTE_VALUE_LEN: u64 = 128;
+
+/// Maximum number of distinct allocation tags per instruction, further tags are refused
+pub const MAX_PROFILE_HEAP_TAGS: usize = 32;
+
+/// Attribute of a heap checkpoint marker holding the free heap bytes
+pub const HEAP_CHECKPOINT_FREE_ATTRIBUTE: &str = "heap.free";
+
+/// Attribute of a heap checkpoint marker holding the size of the largest contiguous free block
+pub const HEAP_CHECKPOINT_LARGEST_FREE_ATTRIBUTE: &str = "heap.largest_free";
+
+/// Instruction data prefix of the transfer hook interface's `Execute` instruction
+pub const TRANSFER_HOOK_EXECUTE_DISCRIMINATOR: [u8; 8] = [105, 37, 101, 197, 75, 251, 102, 26];
+
+/// Name of the section covering the runtime's translation of the accounts passed to a transfer
+/// hook, which includes the extra accounts resolved by the token program
+pub const TRANSFER_HOOK_RESOLVE_SECTION: &str = "transfer-hook:resolve";
+
+/// Name of the child section of a CPI covering the translation of the caller's accounts into the
+/// callee's, before the callee is invoked
+pub const CPI_SERIALIZE_SECTION: &str = "cpi_serialize";
+
+/// Name of the child section of a CPI covering the synchronization of the callee's account
+/// changes back into the caller's memory, after the callee returned
+pub const CPI_DESERIALIZE_SECTION: &str = "cpi_deserialize";
+
+/// Names of the SPL token instructions shared by token and token-2022, indexed by tag
+const TOKEN_INSTRUCTION_NAMES: [&str; 16] = [
+    "initialize_mint",
+    "initialize_account",
+    "initialize_multisig",
+    "transfer",
+    "approve",
+    "revoke",
+    "set_authority",
+    "mint_to",
+    "burn",
+    "close_account",
+    "freeze_account",
+    "thaw_account",
+    "transfer_checked",
+    "approve_checked",
+    "mint_to_checked",
+    "burn_checked",
+];
+
+/// Version of the `Program profile:` log line format
+pub const PROFILE_LOG_VERSION: u8 = 7;
+
+/// Oldest version of the `Program profile:` log line format which can still be parsed
+pub const MIN_PROFILE_LOG_VERSION: u8 = 1;
+
+/// Default number of return data bytes kept in the profile of each instruction, the maximum size
+/// of return data
+pub const PROFILE_RETURN_DATA_BYTES_LIMIT: usize = 1024;
+
+/// Flag of sections closed by [`ProfilingState::end_all_sections`]
+pub const PROFILE_FLAG_BULK_CLOSED: &str = "bulk-closed";
+
+/// Prefix of all profiling log lines
+pub const PROFILE_LOG_PREFIX: &str = "Program profile: ";
+
+/// Number of sections logged by [`ProfileVerbosity::Summary`] unless configured otherwise
+pub const DEFAULT_PROFILE_SUMMARY_SECTIONS: usize = 10;
+
+/// How much of the profile of an instruction is logged once the program returns
+///
+/// Selecting the verbosity at runtime lets the same instrumented program be used for quick checks
+/// as well as for deep analysis.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProfileVerbosity {
+    /// Nothing is logged
+    Off,
+    /// The sections with the most net compute units, see
+    /// [`stable_log::program_profile_summary`](crate::stable_log::program_profile_summary)
+    Summary,
+    /// The section hierarchy, see
+    /// [`stable_log::program_profile_tree`](crate::stable_log::program_profile_tree)
+    Tree,
+    /// Every completed entry, see
+    /// [`stable_log::program_profile`](crate::stable_log::program_profile)
+    #[default]
+    Full,
+    /// Every completed entry as a JSON object, see
+    /// [`stable_log::program_profile_json`](crate::stable_log::program_profile_json)
+    FullJson,
+}
+
+impl ProfileVerbosity {
+    pub const ALL: [ProfileVerbosity; 5] = [
+        ProfileVerbosity::Off,
+        ProfileVerbosity::Summary,
+        ProfileVerbosity::Tree,
+        ProfileVerbosity::Full,
+        ProfileVerbosity::FullJson,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProfileVerbosity::Off => "off",
+            ProfileVerbosity::Summary => "summary",
+            ProfileVerbosity::Tree => "tree",
+            ProfileVerbosity::Full => "full",
+            ProfileVerbosity::FullJson => "full-json",
+        }
+    }
+}
+
+impl fmt::Display for ProfileVerbosity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseProfileVerbosityError(String);
+
+impl fmt::Display for ParseProfileVerbosityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid profile verbosity {:?}, expected one of off, summary, tree, full or full-json",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseProfileVerbosityError {}
+
+impl FromStr for ProfileVerbosity {
+    type Err = ParseProfileVerbosityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|verbosity| verbosity.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ParseProfileVerbosityError(s.to_string()))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileEntryKind {
+    /// A code region delimited by a start and an end call
+    Section,
+    /// A zero-length point event
+    Mark,
+}
+
+impl ProfileEntryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Section => "section",
+            Self::Mark => "mark",
+        }
+    }
+}
+
+/// Allocator state recorded by [`ProfilingState::heap_checkpoint`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeapCheckpoint {
+    /// Heap bytes in use
+    pub used: u64,
+    /// Heap bytes not in use
+    pub free: u64,
+    /// Size of the largest contiguous free block, `None` if it is not known to the runtime
+    pub largest_free: Option<u64>,
+}
+
+/// Host time spent in the phases of an instruction executed by an SBF loader, as accumulated
+/// into `ExecuteDetailsTimings`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstructionTimings {
+    /// Microseconds spent serializing the accounts into the input region
+    pub serialize_us: u64,
+    /// Microseconds spent creating the VM
+    pub create_vm_us: u64,
+    /// Microseconds spent executing the program, including the programs it invoked
+    pub execute_us: u64,
+    /// Microseconds spent copying the account changes back from the input region
+    pub deserialize_us: u64,
+}
+
+/// Bytes copied between the accounts and the input region of an instruction executed by an SBF
+/// loader
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstructionDataTransfer {
+    /// Size of the serialized input region, which includes the account data unless it is mapped
+    /// directly
+    pub serialized_bytes: u64,
+    /// Account data bytes copied back from the input region after the program returned
+    pub written_back_bytes: u64,
+}
+
+/// A completed profiling entry
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileEntry {
+    pub kind: ProfileEntryKind,
+    pub name: String,
+    /// Number of sections that were active when this entry was opened
+    pub depth: usize,
+    /// Compute units consumed by the instruction when the entry was opened
+    pub start_cu: u64,
+    /// Compute units consumed by the instruction when the entry was closed
+    pub end_cu: u64,
+    /// Heap usage reported by the program when the entry was opened
+    pub heap_start: Option<u64>,
+    /// Heap usage reported by the program when the entry was closed
+    pub heap_end: Option<u64>,
+    /// Largest return data set by a CPI callee while the entry was open
+    ///
+    /// The runtime holds this buffer outside of the program's heap, so it is not covered by the
+    /// heap samples.
+    pub return_data_bytes: u64,
+    /// Whether the section was closed by [`ProfilingState::end_all_sections`] rather than by
+    /// ending it explicitly
+    pub bulk_closed: bool,
+    /// Metadata attached by the program while the section was open, see
+    /// [`ProfilingState::set_attribute`]
+    pub attributes: BTreeMap<String, String>,
+    /// Program counter of the SBF instruction which opened the entry, if known, see
+    /// [`ProfilingState::set_call_site`]
+    #[serde(default)]
+    pub call_site: Option<u64>,
+}
+
+impl ProfileEntry {
+    /// Compute units consumed between opening and closing this entry
+    pub fn consumed(&self) -> u64 {
+        self.end_cu.saturating_sub(self.start_cu)
+    }
+
+    /// Comma separated flags of this entry as logged, `-` if there are none
+    pub fn flags(&self) -> &'static str {
+        if self.bulk_closed {
+            PROFILE_FLAG_BULK_CLOSED
+        } else {
+            "-"
+        }
+    }
+
+    /// Render this entry as a single line JSON object, including its attributes and its heap
+    /// usage within the instruction, see [`heap_metrics`]
+    ///
+    /// The object has the keys `kind`, `name`, `depth`, `start_cu`, `consumed_cu`, `heap_start`,
+    /// `heap_end`, `heap_total_bytes`, `heap_net_bytes`, `heap_peak_bytes`, `return_data_bytes`,
+    /// `flags` and `attributes`. Heap samples the program did not take and the heap usage of
+    /// entries without it are `null`, `flags` is an array of strings and `attributes` an object
+    /// of strings.
+    pub fn to_json(&self, heap: Option<HeapMetrics>) -> String {
+        let flags: Vec<&str> = self
+            .bulk_closed
+            .then_some(PROFILE_FLAG_BULK_CLOSED)
+            .into_iter()
+            .collect();
+        serde_json::json!({
+            "kind": self.kind.as_str(),
+            "name": self.name,
+            "depth": self.depth,
+            "start_cu": self.start_cu,
+            "consumed_cu": self.consumed(),
+            "heap_start": self.heap_start,
+            "heap_end": self.heap_end,
+            "heap_total_bytes": heap.map(|heap| heap.total_bytes),
+            "heap_net_bytes": heap.map(|heap| heap.net_bytes),
+            "heap_peak_bytes": heap.map(|heap| heap.peak_bytes),
+            "return_data_bytes": self.return_data_bytes,
+            "flags": flags,
+            "attributes": self.attributes,
+        })
+        .to_string()
+    }
+
+    /// Parse an entry rendered by [`ProfileEntry::to_json`]
+    ///
+    /// Unknown keys and flags are ignored, so that the schema can be extended.
+    pub fn from_json(json: &str) -> Option<Self> {
+        let value: serde_json::Value = serde_json::from_str(json).ok()?;
+        let number = |key: &str| value.get(key)?.as_u64();
+        let heap = |key: &str| match value.get(key)? {
+            serde_json::Value::Null => Some(None),
+            heap => heap.as_u64().map(Some),
+        };
+        let kind = match value.get("kind")?.as_str()? {
+            "section" => ProfileEntryKind::Section,
+            "mark" => ProfileEntryKind::Mark,
+            _ => return None,
+        };
+        let start_cu = number("start_cu")?;
+        Some(Self {
+            kind,
+            name: value.get("name")?.as_str()?.to_string(),
+            depth: usize::try_from(number("depth")?).ok()?,
+            start_cu,
+            end_cu: start_cu.saturating_add(number("consumed_cu")?),
+            heap_start: heap("heap_start")?,
+            heap_end: heap("heap_end")?,
+            return_data_bytes: number("return_data_bytes")?,
+            bulk_closed: value
+                .get("flags")?
+                .as_array()?
+                .iter()
+                .any(|flag| flag.as_str() == Some(PROFILE_FLAG_BULK_CLOSED)),
+            attributes: value
+                .get("attributes")?
+                .as_object()?
+                .iter()
+                .map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+                .collect::<Option<_>>()?,
+            call_site: None,
+        })
+    }
+}
+
+/// Identifies an active section, either by name or by a program-chosen id
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SectionKey {
+    Name(String),
+    Id(u64),
+}
+
+#[derive(Clone, Debug)]
+struct ActiveSection {
+    key: SectionKey,
+    /// Stack height of the instruction which records the section, see [`ActiveSections::adopt`]
+    stack_height: usize,
+    /// Remaining compute units when the section was opened
+    start_remaining: u64,
+    /// Instrumentation compute units charged in the transaction when the section was opened
+    start_instrumentation_cu: u64,
+    heap_start: Option<u64>,
+    /// Latest heap usage recorded by [`ProfilingState::record_heap_usage`] while the section was
+    /// open, reported if the section is closed without a heap sample
+    heap_recorded: Option<u64>,
+    return_data_bytes: u64,
+    attributes: BTreeMap<String, String>,
+    call_site: Option<u64>,
+}
+
+/// Sections active in a transaction, shared by the profiling states of all its instructions
+///
+/// A section opened by an instruction can be closed by the instructions it invokes, and by its
+/// caller once it returned. Either way it is recorded by the instruction which opened it, or by
+/// the caller which adopted it.
+#[derive(Debug, Default)]
+pub struct ActiveSections {
+    /// Active sections, outermost first
+    sections: Vec<ActiveSection>,
+    /// Compute units charged by the profiling syscalls of the transaction so far
+    instrumentation_cu: u64,
+}
+
+impl ActiveSections {
+    /// Hand the sections still active when the instruction at `stack_height` returns over to its
+    /// caller, which then records them
+    ///
+    /// Their heap samples are dropped, as the caller uses a separate heap. The sections of a top
+    /// level instruction are discarded, as it has no caller.
+    pub fn adopt(&mut self, stack_height: usize) {
+        let caller = stack_height.saturating_sub(1);
+        self.sections.retain_mut(|section| {
+            if section.stack_height < stack_height {
+                return true;
+            }
+            section.stack_height = caller;
+            section.heap_start = None;
+            section.heap_recorded = None;
+            caller > 0
+        });
+    }
+
+    /// Whether no section is active
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Stack height of the instruction which records the innermost active section satisfying
+    /// `is_target`
+    fn owner(&self, is_target: impl Fn(&SectionKey) -> bool) -> Option<usize> {
+        self.sections
+            .iter()
+            .rfind(|section| is_target(&section.key))
+            .map(|section| section.stack_height)
+    }
+}
+
+/// A section which was still active when its instruction failed, see [`ProfilingState::fail`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FailedSection {
+    pub name: String,
+    /// Nesting depth, counting the sections active in the calling instructions
+    pub depth: usize,
+    /// Compute units consumed from the start of the section until the failure
+    pub consumed_cu: u64,
+}
+
+/// Heap usage of a section, see [`heap_metrics`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeapMetrics {
+    /// Heap bytes allocated while the section was open, including those of the sections nested
+    /// within
+    pub total_bytes: u64,
+    /// Heap bytes allocated while the section was open, excluding those of the sections nested
+    /// within
+    pub net_bytes: u64,
+    /// Highest heap usage sampled by the section or the sections nested within
+    pub peak_bytes: u64,
+}
+
+impl HeapMetrics {
+    /// Combine the heap usage of two occurrences of a section
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            total_bytes: self.total_bytes.saturating_add(other.total_bytes),
+            net_bytes: self.net_bytes.saturating_add(other.net_bytes),
+            peak_bytes: self.peak_bytes.max(other.peak_bytes),
+        }
+    }
+}
+
+/// Sections of an instruction with the same name, see [`ProfilingState::summary`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionSummary {
+    pub name: String,
+    /// Number of times the section was recorded
+    pub count: u64,
+    /// Compute units consumed by the sections, excluding those of the sections nested within
+    pub net_cu: u64,
+    /// Heap usage of the sections, `None` if none of them sampled the heap
+    pub heap: Option<HeapMetrics>,
+}
+
+/// The sections of an instruction which consumed the most compute units, see
+/// [`ProfilingState::summary`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProfileSummary {
+    /// Compute units consumed by the instruction
+    pub total_cu: u64,
+    /// Number of distinct section names
+    pub section_count: usize,
+    /// Ordered by decreasing net compute units
+    pub top: Vec<SectionSummary>,
+}
+
+impl ProfileSummary {
+    /// Share of the instruction's compute units in hundredths of a percent
+    pub fn basis_points(&self, cu: u64) -> u64 {
+        cu.saturating_mul(10_000)
+            .checked_div(self.total_cu)
+            .unwrap_or(0)
+    }
+}
+
+/// A section of a [`ProfileTree`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfileTreeRow {
+    /// Number of sections the section is nested within
+    pub level: usize,
+    pub name: String,
+    /// Compute units consumed by the section, including those of the sections nested within
+    pub total_cu: u64,
+    /// Compute units consumed by the section, excluding those of the sections nested within
+    pub net_cu: u64,
+}
+
+/// The section hierarchy of an instruction, see [`ProfilingState::tree`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProfileTree {
+    /// Compute units consumed by the instruction
+    pub total_cu: u64,
+    /// Every section followed by the sections nested within it, depth first
+    pub rows: Vec<ProfileTreeRow>,
+}
+
+impl ProfileTree {
+    /// Render the rows with the indentation and connectors of `cargo tree --charset ascii`:
+    ///
+    /// ```notrust
+    /// handle 800 CU 80.00% (net 500 CU 50.00%)
+    /// |-- parse 100 CU 10.00% (net 100 CU 10.00%)
+    /// `-- transfer 200 CU 20.00% (net 200 CU 20.00%)
+    /// log 50 CU 5.00% (net 50 CU 5.00%)
+    /// ```
+    pub fn lines(&self) -> Vec<String> {
+        let percent = |cu: u64| {
+            let basis_points = cu
+                .saturating_mul(10_000)
+                .checked_div(self.total_cu)
+                .unwrap_or(0);
+            format!("{}.{:02}%", basis_points / 100, basis_points % 100)
+        };
+        // Whether the ancestors at each level have siblings after them
+        let mut continued: Vec<bool> = Vec::new();
+        let mut rows = self.rows.iter();
+        let mut lines = Vec::with_capacity(self.rows.len());
+        while let Some(row) = rows.next() {
+            let is_last = rows
+                .clone()
+                .find(|next| next.level <= row.level)
+                .is_none_or(|next| next.level < row.level);
+            continued.truncate(row.level.saturating_sub(1));
+            let mut line: String = continued
+                .iter()
+                .map(|continued| if *continued { "|   " } else { "    " })
+                .collect();
+            if row.level > 0 {
+                line.push_str(if is_last { "`-- " } else { "|-- " });
+                continued.push(!is_last);
+            }
+            line.push_str(&format!(
+                "{} {} CU {} (net {} CU {})",
+                row.name,
+                row.total_cu,
+                percent(row.total_cu),
+                row.net_cu,
+                percent(row.net_cu),
+            ));
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+/// An allocation tag opened by [`ProfilingState::heap_tag_begin`]
+#[derive(Clone, Debug)]
+struct ActiveHeapTag {
+    name: String,
+    heap_start: u64,
+    /// Heap bytes attributed to the tags opened while this one was active
+    nested_bytes: u64,
+}
+
+/// Instrumentation compute units charged when an entry was opened and closed
+#[derive(Clone, Copy, Debug, Default)]
+struct InstrumentationCharges {
+    start: u64,
+    end: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ProfilingState {
+    /// Remaining compute units when the instruction started executing
+    initial_remaining: u64,
+    /// Sections active in the transaction, see [`ProfilingState::with_active_sections`]
+    active: Rc<RefCell<ActiveSections>>,
+    /// Stack height of the instruction
+    stack_height: usize,
+    entries: Vec<ProfileEntry>,
+    dropped_entries: usize,
+    counters: BTreeMap<u64, u64>,
+    /// Names registered for section ids
+    names: BTreeMap<u64, String>,
+    active_heap_tags: Vec<ActiveHeapTag>,
+    /// Heap bytes attributed to each allocation tag
+    heap_tags: BTreeMap<String, u64>,
+    /// Whether the profiling syscalls are charged, see [`ProfilingState::new_metered`]
+    metered: bool,
+    /// Instrumentation compute units charged in the transaction when the instruction started
+    initial_instrumentation_cu: u64,
+    /// Instrumentation charges of each completed entry, not yet removed by
+    /// [`ProfilingState::post_process`]
+    entry_charges: Vec<InstrumentationCharges>,
+    /// Host timings of the instruction, set once it completed
+    timings: Option<InstructionTimings>,
+    /// Account data copied in and out of the instruction, set once it completed
+    data_transfer: Option<InstructionDataTransfer>,
+    /// Why the instruction failed, see [`ProfilingState::fail`]
+    failure: Option<String>,
+    /// Sections which were active when the instruction failed, outermost first
+    failed_sections: Vec<FailedSection>,
+    /// Call site of the next section or marker, see [`ProfilingState::set_call_site`]
+    call_site: Option<u64>,
+}
+
+impl ProfilingState {
+    pub fn new(initial_remaining: u64) -> Self {
+        Self {
+            initial_remaining,
+            ..Self::default()
+        }
+    }
+
+    /// Profiling state whose syscalls are charged like regular syscalls
+    ///
+    /// The instrumentation then affects the compute units consumed by the program as it would if
+    /// it were implemented on chain, while [`ProfilingState::post_process`] removes the charges
+    /// from the reported entries. As this changes the outcome of transactions running out of
+    /// compute units, it is only used by `agave-ledger-tool program run`.
+    pub fn new_metered(initial_remaining: u64) -> Self {
+        Self {
+            initial_remaining,
+            metered: true,
+            ..Self::default()
+        }
+    }
+
+    /// Share the active sections with the other instructions of the transaction
+    ///
+    /// `stack_height` is the stack height of this instruction. The sections active in the calling
+    /// instructions enclose the entries of this instruction, and the sections it opens can be
+    /// closed by them.
+    pub fn with_active_sections(
+        mut self,
+        active: Rc<RefCell<ActiveSections>>,
+        stack_height: usize,
+    ) -> Self {
+        self.initial_instrumentation_cu = active.borrow().instrumentation_cu;
+        self.active = active;
+        self.stack_height = stack_height;
+        self
+    }
+
+    /// Whether the profiling syscalls are charged
+    pub fn is_metered(&self) -> bool {
+        self.metered
+    }
+
+    /// Account for the compute units charged by a profiling syscall
+    pub fn charge_instrumentation(&mut self, cost: u64) {
+        let mut active = self.active.borrow_mut();
+        active.instrumentation_cu = active.instrumentation_cu.saturating_add(cost);
+    }
+
+    /// Compute units charged by the profiling syscalls since the instruction started,
+    /// including those of the instructions it invoked
+    ///
+    /// Always 0 unless the state is metered: profiling must not change the compute units consumed
+    /// by the program, otherwise profiled and unprofiled validators would diverge.
+    pub fn instrumentation_cu(&self) -> u64 {
+        self.active
+            .borrow()
+            .instrumentation_cu
+            .saturating_sub(self.initial_instrumentation_cu)
+    }
+
+    /// Convert a raw syscall heap argument into a heap sample
+    pub fn heap_sample(heap: u64) -> Option<u64> {
+        (heap != NO_HEAP_SAMPLE).then_some(heap)
+    }
+
+    /// Compute units consumed by the instruction given the current remaining units
+    pub fn consumed_at(&self, remaining: u64) -> u64 {
+        self.initial_remaining.saturating_sub(remaining)
+    }
+
+    /// Record the program counter of the syscall which is about to open a section or record a
+    /// marker
+    ///
+    /// The next entry takes the call site, so that it can be mapped to the source location of
+    /// the program, see [`ProfileReport::source_locations`](crate::profile_report::ProfileReport).
+    pub fn set_call_site(&mut self, pc: u64) {
+        self.call_site = Some(pc);
+    }
+
+    /// Open a named section
+    pub fn start_section(&mut self, name: &str, remaining: u64, heap: Option<u64>) {
+        self.start(SectionKey::Name(name.to_string()), remaining, heap);
+    }
+
+    /// Open a section identified by a program-chosen id
+    pub fn start_section_id(&mut self, id: u64, remaining: u64, heap: Option<u64>) {
+        self.start(SectionKey::Id(id), remaining, heap);
+    }
+
+    fn start(&mut self, key: SectionKey, remaining: u64, heap: Option<u64>) {
+        let call_site = self.call_site.take();
+        let mut active = self.active.borrow_mut();
+        if active.sections.len() >= MAX_PROFILE_ENTRIES {
+            drop(active);
+            self.dropped_entries = self.dropped_entries.saturating_add(1);
+            return;
+        }
+        let start_instrumentation_cu = active.instrumentation_cu;
+        active.sections.push(ActiveSection {
+            key,
+            stack_height: self.stack_height,
+            start_remaining: remaining,
+            start_instrumentation_cu,
+            heap_start: heap,
+            heap_recorded: None,
+            return_data_bytes: 0,
+            attributes: BTreeMap::new(),
+            call_site,
+        });
+    }
+
+    /// Close the innermost active section with the given name recorded by this instruction
+    ///
+    /// Returns `false` if no such section is active, see [`ProfilingState::section_owner`].
+    pub fn end_section(&mut self, name: &str, remaining: u64, heap: Option<u64>) -> bool {
+        self.end(
+            |key| matches!(key, SectionKey::Name(section_name) if section_name == name),
+            remaining,
+            heap,
+        )
+    }
+
+    /// Close the innermost active section with the given id recorded by this instruction
+    ///
+    /// Returns `false` if no such section is active, see [`ProfilingState::section_owner_id`].
+    pub fn end_section_id(&mut self, id: u64, remaining: u64, heap: Option<u64>) -> bool {
+        self.end(|key| *key == SectionKey::Id(id), remaining, heap)
+    }
+
+    fn end(
+        &mut self,
+        is_target: impl Fn(&SectionKey) -> bool,
+        remaining: u64,
+        heap: Option<u64>,
+    ) -> bool {
+        let mut active = self.active.borrow_mut();
+        let Some(position) = active.sections.iter().rposition(|section| {
+            section.stack_height == self.stack_height && is_target(&section.key)
+        }) else {
+            return false;
+        };
+        let section = active.sections.remove(position);
+        drop(active);
+        self.push_section(section, position, remaining, heap, false);
+        true
+    }
+
+    /// Record a section removed from the active sections at `position`
+    fn push_section(
+        &mut self,
+        section: ActiveSection,
+        position: usize,
+        remaining: u64,
+        heap: Option<u64>,
+        bulk_closed: bool,
+    ) {
+        let charges = InstrumentationCharges {
+            start: section
+                .start_instrumentation_cu
+                .saturating_sub(self.initial_instrumentation_cu),
+            end: self.instrumentation_cu(),
+        };
+        let name = self.section_name(section.key);
+        self.push_entry(
+            ProfileEntry {
+                kind: ProfileEntryKind::Section,
+                name,
+                depth: position,
+                start_cu: self.consumed_at(section.start_remaining),
+                end_cu: self.consumed_at(remaining),
+                heap_start: section.heap_start,
+                heap_end: heap.or(section.heap_recorded),
+                return_data_bytes: section.return_data_bytes,
+                bulk_closed,
+                attributes: section.attributes,
+                call_site: section.call_site,
+            },
+            charges,
+        );
+    }
+
+    /// Compute units consumed so far by the innermost active section with the given id
+    ///
+    /// The section may have been opened by another instruction. Returns `None` if no such section
+    /// is active.
+    pub fn section_consumed_id(&self, id: u64, remaining: u64) -> Option<u64> {
+        let active = self.active.borrow();
+        let section = active
+            .sections
+            .iter()
+            .rfind(|section| section.key == SectionKey::Id(id))?;
+        let instrumentation_cu = active
+            .instrumentation_cu
+            .saturating_sub(section.start_instrumentation_cu);
+        Some(
+            section
+                .start_remaining
+                .saturating_sub(remaining)
+                .saturating_sub(instrumentation_cu),
+        )
+    }
+
+    /// Close all active sections recorded by this instruction, innermost first
+    ///
+    /// The sections of the calling instructions stay active. Returns the number of sections which
+    /// were closed.
+    pub fn end_all_sections(&mut self, remaining: u64, heap: Option<u64>) -> usize {
+        let mut count: usize = 0;
+        loop {
+            let mut active = self.active.borrow_mut();
+            let Some(position) = active
+                .sections
+                .iter()
+                .rposition(|section| section.stack_height == self.stack_height)
+            else {
+                break;
+            };
+            let section = active.sections.remove(position);
+            drop(active);
+            self.push_section(section, position, remaining, heap, true);
+            count = count.saturating_add(1);
+        }
+        count
+    }
+
+    fn section_name(&self, key: SectionKey) -> String {
+        match key {
+            SectionKey::Name(name) => name,
+            SectionKey::Id(id) => self
+                .names
+                .get(&id)
+                .cloned()
+                .unwrap_or_else(|| format!("#{id}")),
+        }
+    }
+
+    /// Record a zero-length point event
+    pub fn mark(&mut self, name: &str, remaining: u64, heap: Option<u64>) {
+        self.push_mark(name, remaining, heap, BTreeMap::new());
+    }
+
+    /// Record a marker carrying a snapshot of the allocator state
+    ///
+    /// The heap usage is reported as the marker's heap sample, the free bytes and the largest
+    /// free block as the [`HEAP_CHECKPOINT_FREE_ATTRIBUTE`] and
+    /// [`HEAP_CHECKPOINT_LARGEST_FREE_ATTRIBUTE`] attributes.
+    pub fn heap_checkpoint(&mut self, name: &str, remaining: u64, checkpoint: HeapCheckpoint) {
+        let mut attributes = BTreeMap::from([(
+            HEAP_CHECKPOINT_FREE_ATTRIBUTE.to_string(),
+            checkpoint.free.to_string(),
+        )]);
+        if let Some(largest_free) = checkpoint.largest_free {
+            attributes.insert(
+                HEAP_CHECKPOINT_LARGEST_FREE_ATTRIBUTE.to_string(),
+                largest_free.to_string(),
+            );
+        }
+        self.push_mark(name, remaining, Some(checkpoint.used), attributes);
+    }
+
+    fn push_mark(
+        &mut self,
+        name: &str,
+        remaining: u64,
+        heap: Option<u64>,
+        attributes: BTreeMap<String, String>,
+    ) {
+        let cu = self.consumed_at(remaining);
+        let call_site = self.call_site.take();
+        let instrumentation_cu = self.instrumentation_cu();
+        let charges = InstrumentationCharges {
+            start: instrumentation_cu,
+            end: instrumentation_cu,
+        };
+        self.push_entry(
+            ProfileEntry {
+                kind: ProfileEntryKind::Mark,
+                name: name.to_string(),
+                depth: self.active_sections(),
+                start_cu: cu,
+                end_cu: cu,
+                heap_start: heap,
+                heap_end: heap,
+                return_data_bytes: 0,
+                bulk_closed: false,
+                attributes,
+                call_site,
+            },
+            charges,
+        );
+    }
+
+    /// Attribute return data set by a CPI callee to all active sections recorded by this
+    /// instruction
+    pub fn callee_return_data(&mut self, len: u64) {
+        for section in self
+            .active
+            .borrow_mut()
+            .sections
+            .iter_mut()
+            .filter(|section| section.stack_height == self.stack_height)
+        {
+            section.return_data_bytes = section.return_data_bytes.max(len);
+        }
+    }
+
+    /// Innermost active section recorded by this instruction
+    fn innermost_section_mut<'b>(
+        &self,
+        active: &'b mut ActiveSections,
+    ) -> Option<&'b mut ActiveSection> {
+        active
+            .sections
+            .iter_mut()
+            .rfind(|section| section.stack_height == self.stack_height)
+    }
+
+    /// Attach a key/value attribute to the innermost active section, replacing any previous value
+    /// of the key
+    ///
+    /// Returns `false` if no section is active, or if the key is new and the section already has
+    /// [`MAX_PROFILE_ATTRIBUTES`] attributes.
+    pub fn set_attribute(&mut self, key: &str, value: &str) -> bool {
+        let mut active = self.active.borrow_mut();
+        let Some(section) = self.innermost_section_mut(&mut active) else {
+            return false;
+        };
+        if section.attributes.len() >= MAX_PROFILE_ATTRIBUTES
+            && !section.attributes.contains_key(key)
+        {
+            return false;
+        }
+        section
+            .attributes
+            .insert(key.to_string(), value.to_string());
+        true
+    }
+
+    /// Record the heap usage reported by the runtime's allocator into the innermost active
+    /// section
+    ///
+    /// Returns `false` if no section is active.
+    pub fn record_heap_usage(&mut self, used: u64) -> bool {
+        let mut active = self.active.borrow_mut();
+        let Some(section) = self.innermost_section_mut(&mut active) else {
+            return false;
+        };
+        section.heap_recorded = Some(used);
+        true
+    }
+
+    /// Open an allocation tag, the heap growth until the matching
+    /// [`heap_tag_end`](Self::heap_tag_end) is attributed to `name`
+    ///
+    /// Tags can be nested, the heap growth of an inner tag is then only attributed to the inner
+    /// tag, so that the heap report adds up. Returns `false` if the tag could not be opened
+    /// because the name is new and [`MAX_PROFILE_HEAP_TAGS`] was reached, or because as many tags
+    /// are already active.
+    pub fn heap_tag_begin(&mut self, name: &str, heap: u64) -> bool {
+        if self.active_heap_tags.len() >= MAX_PROFILE_HEAP_TAGS
+            || (self.heap_tags.len() >= MAX_PROFILE_HEAP_TAGS && !self.heap_tags.contains_key(name))
+        {
+            return false;
+        }
+        self.heap_tags.entry(name.to_string()).or_default();
+        self.active_heap_tags.push(ActiveHeapTag {
+            name: name.to_string(),
+            heap_start: heap,
+            nested_bytes: 0,
+        });
+        true
+    }
+
+    /// Close the innermost active allocation tag
+    ///
+    /// Returns `false` if no tag is active. Tags which are still active when the instruction
+    /// completes are not attributed anything.
+    pub fn heap_tag_end(&mut self, heap: u64) -> bool {
+        let Some(tag) = self.active_heap_tags.pop() else {
+            return false;
+        };
+        let bytes = heap.saturating_sub(tag.heap_start);
+        let attributed = self.heap_tags.entry(tag.name).or_default();
+        *attributed = attributed.saturating_add(bytes.saturating_sub(tag.nested_bytes));
+        if let Some(parent) = self.active_heap_tags.last_mut() {
+            parent.nested_bytes = parent.nested_bytes.saturating_add(bytes);
+        }
+        true
+    }
+
+    fn push_entry(&mut self, entry: ProfileEntry, charges: InstrumentationCharges) {
+        if self.entries.len() < MAX_PROFILE_ENTRIES {
+            self.entries.push(entry);
+            self.entry_charges.push(charges);
+        } else {
+            self.dropped_entries = self.dropped_entries.saturating_add(1);
+        }
+    }
+
+    /// Add `delta` to the counter with the given id, creating it if necessary
+    ///
+    /// Returns `false` if the counter does not exist and [`MAX_PROFILE_COUNTERS`] was reached.
+    pub fn counter_add(&mut self, id: u64, delta: u64) -> bool {
+        if let Some(value) = self.counters.get_mut(&id) {
+            *value = value.saturating_add(delta);
+        } else if self.counters.len() < MAX_PROFILE_COUNTERS {
+            self.counters.insert(id, delta);
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Name the sections opened with the given id
+    ///
+    /// The name is resolved when the section ends, sections which end before their id is named
+    /// are reported as `#<id>`. Returns `false` if the id has no name yet and
+    /// [`MAX_PROFILE_REGISTERED_NAMES`] was reached.
+    pub fn register_name(&mut self, id: u64, name: &str) -> bool {
+        if self.names.len() >= MAX_PROFILE_REGISTERED_NAMES && !self.names.contains_key(&id) {
+            return false;
+        }
+        self.names.insert(id, name.to_string());
+        true
+    }
+
+    /// Remove the instrumentation charges of metered profiling from the completed entries
+    ///
+    /// The compute units charged by the profiling syscalls up to each entry are subtracted from
+    /// its bounds, so that the entries report the compute units the program would have consumed
+    /// without instrumentation. Entries which were already post-processed are left unchanged.
+    pub fn post_process(&mut self) {
+        let processed = self.entries.len().saturating_sub(self.entry_charges.len());
+        for (entry, charges) in self
+            .entries
+            .iter_mut()
+            .skip(processed)
+            .zip(self.entry_charges.drain(..))
+        {
+            entry.start_cu = entry.start_cu.saturating_sub(charges.start);
+            entry.end_cu = entry.end_cu.saturating_sub(charges.end);
+        }
+    }
+
+    /// Counter values ordered by id
+    pub fn counters(&self) -> &BTreeMap<u64, u64> {
+        &self.counters
+    }
+
+    /// Heap bytes attributed to each allocation tag ordered by name
+    pub fn heap_tags(&self) -> &BTreeMap<String, u64> {
+        &self.heap_tags
+    }
+
+    /// Completed entries in the order they were closed
+    pub fn entries(&self) -> &[ProfileEntry] {
+        &self.entries
+    }
+
+    /// Number of entries dropped because [`MAX_PROFILE_ENTRIES`] was reached
+    pub fn dropped_entries(&self) -> usize {
+        self.dropped_entries
+    }
+
+    /// Attach the host timings of the instruction, so that they can be correlated with the
+    /// recorded entries
+    pub fn set_timings(&mut self, timings: InstructionTimings) {
+        self.timings = Some(timings);
+    }
+
+    /// Host timings of the instruction, `None` until it completed
+    pub fn timings(&self) -> Option<&InstructionTimings> {
+        self.timings.as_ref()
+    }
+
+    /// Attach the number of bytes copied in and out of the instruction, which can dominate its
+    /// cost for large accounts
+    pub fn set_data_transfer(&mut self, data_transfer: InstructionDataTransfer) {
+        self.data_transfer = Some(data_transfer);
+    }
+
+    /// Bytes copied in and out of the instruction, `None` until it completed
+    pub fn data_transfer(&self) -> Option<&InstructionDataTransfer> {
+        self.data_transfer.as_ref()
+    }
+
+    /// Close the active sections of an instruction which failed and record why
+    ///
+    /// The sections are closed as by [`ProfilingState::end_all_sections`], so that the partial
+    /// profile still shows where the compute units went until the failure. They are kept as
+    /// [`failed_sections`](Self::failed_sections) for the failure log.
+    pub fn fail(&mut self, reason: String, remaining: u64) {
+        self.failed_sections = self
+            .active
+            .borrow()
+            .sections
+            .iter()
+            .enumerate()
+            .filter(|(_, section)| section.stack_height == self.stack_height)
+            .map(|(index, section)| FailedSection {
+                name: self.section_name(section.key.clone()),
+                depth: index,
+                consumed_cu: section.start_remaining.saturating_sub(remaining),
+            })
+            .collect();
+        self.end_all_sections(remaining, None);
+        self.failure = Some(reason);
+    }
+
+    /// Why the instruction failed, `None` unless [`ProfilingState::fail`] was called
+    pub fn failure(&self) -> Option<&str> {
+        self.failure.as_deref()
+    }
+
+    /// Sections which were active when the instruction failed, outermost first
+    pub fn failed_sections(&self) -> &[FailedSection] {
+        &self.failed_sections
+    }
+
+    /// Summarize the completed sections as the `limit` ones with the most net compute units
+    ///
+    /// Sections with the same name are aggregated and the compute units of nested sections are
+    /// only counted towards the innermost one. `total_cu` are the compute units consumed by the
+    /// instruction.
+    pub fn summary(&self, limit: usize, total_cu: u64) -> ProfileSummary {
+        let heap = heap_metrics(&self.entries);
+        let mut totals: BTreeMap<&str, (u64, u64, Option<HeapMetrics>)> = BTreeMap::new();
+        for (_level, index, section, net_cu) in self.nested_sections() {
+            let (count, total, total_heap) = totals.entry(&section.name).or_default();
+            *count = count.saturating_add(1);
+            *total = total.saturating_add(net_cu);
+            if let Some(heap) = heap.get(index).copied().flatten() {
+                *total_heap = Some(total_heap.map_or(heap, |total_heap| total_heap.merge(heap)));
+            }
+        }
+        let mut top: Vec<SectionSummary> = totals
+            .into_iter()
+            .map(|(name, (count, net_cu, heap))| SectionSummary {
+                name: name.to_string(),
+                count,
+                net_cu,
+                heap,
+            })
+            .collect();
+        let section_count = top.len();
+        top.sort_by(|a, b| b.net_cu.cmp(&a.net_cu).then_with(|| a.name.cmp(&b.name)));
+        top.truncate(limit);
+        ProfileSummary {
+            total_cu,
+            section_count,
+            top,
+        }
+    }
+
+    /// Render the completed sections as a tree, each section followed by the sections nested
+    /// within it in the order they were started
+    ///
+    /// `total_cu` are the compute units consumed by the instruction, which the percentages refer
+    /// to.
+    pub fn tree(&self, total_cu: u64) -> ProfileTree {
+        ProfileTree {
+            total_cu,
+            rows: self
+                .nested_sections()
+                .into_iter()
+                .map(|(level, _index, section, net_cu)| ProfileTreeRow {
+                    level,
+                    name: section.name.clone(),
+                    total_cu: section.consumed(),
+                    net_cu,
+                })
+                .collect(),
+        }
+    }
+
+    /// The completed sections in the order they were started, along with their nesting level
+    /// within the instruction, their index among the entries and their compute units excluding
+    /// those of the nested sections
+    fn nested_sections(&self) -> Vec<(usize, usize, &ProfileEntry, u64)> {
+        let mut sections: Vec<(usize, &ProfileEntry)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.kind == ProfileEntryKind::Section)
+            .collect();
+        sections.sort_by_key(|(_, entry)| (entry.start_cu, entry.depth));
+        let mut nested: Vec<(usize, usize, &ProfileEntry, u64)> =
+            Vec::with_capacity(sections.len());
+        let mut open: Vec<usize> = Vec::new();
+        for (index, section) in sections {
+            while open
+                .pop_if(|parent| {
+                    nested
+                        .get(*parent)
+                        .is_none_or(|(_, _, parent, _)| !encloses(parent, section))
+                })
+                .is_some()
+            {}
+            if let Some((_, _, _, parent_net_cu)) =
+                open.last().and_then(|parent| nested.get_mut(*parent))
+            {
+                *parent_net_cu = parent_net_cu.saturating_sub(section.consumed());
+            }
+            open.push(nested.len());
+            nested.push((
+                open.len().saturating_sub(1),
+                index,
+                section,
+                section.consumed(),
+            ));
+        }
+        nested
+    }
+
+    /// Number of sections which were started but not yet ended, including those of the calling
+    /// instructions
+    pub fn active_sections(&self) -> usize {
+        self.active.borrow().sections.len()
+    }
+
+    /// Stack height of the instruction which records the innermost active section with the given
+    /// name, `None` if no such section is active
+    ///
+    /// This is either this instruction or one of its callers.
+    pub fn section_owner(&self, name: &str) -> Option<usize> {
+        self.active
+            .borrow()
+            .owner(|key| matches!(key, SectionKey::Name(section_name) if section_name == name))
+    }
+
+    /// Stack height of the instruction which records the innermost active section with the given
+    /// id, `None` if no such section is active
+    pub fn section_owner_id(&self, id: u64) -> Option<usize> {
+        self.active.borrow().owner(|key| *key == SectionKey::Id(id))
+    }
+
+    /// Stack height of the instruction, see [`ProfilingState::with_active_sections`]
+    pub fn stack_height(&self) -> usize {
+        self.stack_height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+            && self.dropped_entries == 0
+            && self.counters.is_empty()
+            && self.heap_tags.is_empty()
+    }
+}
+
+/// Profile recorded for a single instruction, see
+/// [`InvokeContext::take_profiling_results`](crate::invoke_context::InvokeContext::take_profiling_results)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstructionProfile {
+    #[serde(with = "serde_pubkey")]
+    pub program_id: Pubkey,
+    /// Stack height of the instruction, top level instructions are at
+    /// `solana_instruction::TRANSACTION_LEVEL_STACK_HEIGHT`
+    pub stack_height: usize,
+    /// Compute units consumed by the transaction when the instruction started, which the
+    /// `start_cu` and `end_cu` of its entries are relative to
+    pub start_cu: u64,
+    /// Post-processed entries in the order in which they were completed
+    pub entries: Vec<ProfileEntry>,
+    pub counters: BTreeMap<u64, u64>,
+    pub heap_tags: BTreeMap<String, u64>,
+    pub dropped_entries: usize,
+    /// Host timings of the instruction, `None` for builtins
+    pub timings: Option<InstructionTimings>,
+    /// Bytes copied in and out of the instruction, `None` for builtins
+    pub data_transfer: Option<InstructionDataTransfer>,
+    /// Net lamport change of every instruction account whose balance changed, including the
+    /// changes made by the instructions it invoked
+    #[serde(with = "serde_lamport_deltas")]
+    pub lamport_deltas: BTreeMap<Pubkey, i128>,
+    /// Return data left by the instruction, `None` if it is empty
+    pub return_data: Option<ProfileReturnData>,
+    /// Why the instruction failed, the entries are then partial
+    pub failure: Option<String>,
+}
+
+/// Return data of an instruction, see [`InstructionProfile::return_data`]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileReturnData {
+    /// Program which set the return data, either the instruction's program or one it invoked
+    #[serde(with = "serde_pubkey")]
+    pub program_id: Pubkey,
+    /// Leading bytes of the return data, up to the configured limit
+    #[serde(with = "serde_base64")]
+    pub data: Vec<u8>,
+    /// Length of the return data before it was truncated
+    pub len: usize,
+}
+
+/// Host time spent loading a program into the program cache before it could be executed
+///
+/// Mirrors `LoadProgramMetrics`, which is only available with the `metrics` feature.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramLoadProfile {
+    #[serde(with = "serde_pubkey")]
+    pub program_id: Pubkey,
+    /// Microseconds spent creating the program runtime environment
+    pub register_syscalls_us: u64,
+    /// Microseconds spent parsing the ELF
+    pub load_elf_us: u64,
+    /// Microseconds spent verifying the bytecode
+    pub verify_code_us: u64,
+    /// Microseconds spent compiling the program to native code
+    pub jit_compile_us: u64,
+}
+
+impl InstructionProfile {
+    /// Profile of a builtin program invocation
+    ///
+    /// Builtins execute outside of the SBF VM and cannot be instrumented, so their profile
+    /// consists of a single `builtin:<program id>` section covering the compute units they
+    /// charged.
+    pub fn builtin(program_id: Pubkey, stack_height: usize, consumed: u64) -> Self {
+        Self {
+            program_id,
+            stack_height,
+            entries: vec![ProfileEntry {
+                kind: ProfileEntryKind::Section,
+                name: format!("builtin:{program_id}"),
+                depth: 0,
+                start_cu: 0,
+                end_cu: consumed,
+                heap_start: None,
+                heap_end: None,
+                return_data_bytes: 0,
+                bulk_closed: false,
+                attributes: BTreeMap::new(),
+                call_site: None,
+            }],
+            ..Self::default()
+        }
+    }
+}
+
+/// Whether `section` was opened while `parent`, a section of the same instruction, was active
+fn encloses(parent: &ProfileEntry, section: &ProfileEntry) -> bool {
+    parent.depth < section.depth && section.start_cu < parent.end_cu
+}
+
+/// Heap usage of the sections of an instruction, in the order of its entries
+///
+/// Markers and sections which did not sample the heap when they were opened and closed, such as
+/// those of builtins, have no heap usage. Each program invocation has its own heap, so only the
+/// sections nested within the same instruction count towards the net and peak heap usage.
+pub fn heap_metrics(entries: &[ProfileEntry]) -> Vec<Option<HeapMetrics>> {
+    let mut metrics: Vec<Option<HeapMetrics>> = entries
+        .iter()
+        .map(|entry| {
+            if entry.kind != ProfileEntryKind::Section {
+                return None;
+            }
+            let (heap_start, heap_end) = (entry.heap_start?, entry.heap_end?);
+            let total_bytes = heap_end.saturating_sub(heap_start);
+            Some(HeapMetrics {
+                total_bytes,
+                net_bytes: total_bytes,
+                peak_bytes: heap_start.max(heap_end),
+            })
+        })
+        .collect();
+    let mut sections: Vec<(usize, &ProfileEntry)> = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.kind == ProfileEntryKind::Section)
+        .collect();
+    sections.sort_by_key(|(_, entry)| (entry.start_cu, entry.depth));
+    // The usage of a section is complete once the sections nested within it are closed
+    let close = |metrics: &mut [Option<HeapMetrics>], section: usize, parent: Option<usize>| {
+        let Some(Some(section)) = metrics.get(section).copied() else {
+            return;
+        };
+        if let Some(Some(parent)) = parent.and_then(|parent| metrics.get_mut(parent)) {
+            parent.net_bytes = parent.net_bytes.saturating_sub(section.total_bytes);
+            parent.peak_bytes = parent.peak_bytes.max(section.peak_bytes);
+        }
+    };
+    let mut open: Vec<(usize, &ProfileEntry)> = Vec::new();
+    for (index, section) in sections {
+        while let Some((closed, _)) = open.pop_if(|(_, parent)| !encloses(parent, section)) {
+            close(&mut metrics, closed, open.last().map(|(parent, _)| *parent));
+        }
+        open.push((index, section));
+    }
+    while let Some((closed, _)) = open.pop() {
+        close(&mut metrics, closed, open.last().map(|(parent, _)| *parent));
+    }
+    metrics
+}
+
+/// A section of an instruction profile placed on the transaction timeline, see
+/// [`InstructionProfile::start_cu`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimelineSection<'a> {
+    /// Program which recorded the section
+    pub program_id: &'a Pubkey,
+    pub entry: &'a ProfileEntry,
+    /// Compute units consumed by the transaction when the section was opened
+    pub start_cu: u64,
+    /// Compute units consumed by the transaction when the section was closed
+    pub end_cu: u64,
+    /// Heap usage of the section within its instruction, see [`heap_metrics`]
+    pub heap: Option<HeapMetrics>,
+}
+
+impl TimelineSection<'_> {
+    /// Whether `other` was opened while this section was active, one level or more below it
+    pub fn encloses(&self, other: &TimelineSection) -> bool {
+        self.entry.depth < other.entry.depth && other.start_cu < self.end_cu
+    }
+}
+
+/// The sections of a transaction's instruction profiles, in the order in which they were opened
+///
+/// Sections opened at the same time are ordered from the outermost to the innermost. As the
+/// depths of the sections of invoked instructions count the sections active in their callers,
+/// the sections of a transaction form a single tree, see [`TimelineSection::encloses`].
+pub fn timeline_sections(profiles: &[InstructionProfile]) -> Vec<TimelineSection<'_>> {
+    let mut sections: Vec<_> = profiles
+        .iter()
+        .flat_map(|profile| {
+            profile
+                .entries
+                .iter()
+                .zip(heap_metrics(&profile.entries))
+                .filter(|(entry, _)| entry.kind == ProfileEntryKind::Section)
+                .map(|(entry, heap)| TimelineSection {
+                    program_id: &profile.program_id,
+                    entry,
+                    start_cu: profile.start_cu.saturating_add(entry.start_cu),
+                    end_cu: profile.start_cu.saturating_add(entry.end_cu),
+                    heap,
+                })
+        })
+        .collect();
+    sections.sort_by_key(|section| (section.start_cu, section.entry.depth));
+    sections
+}
+
+impl ProfilingState {
+    /// Post-process the state and convert it into the profile of the instruction it was attached
+    /// to
+    ///
+    /// Sections which are still active are not part of the profile, as in the program logs.
+    pub fn into_instruction_profile(
+        mut self,
+        program_id: Pubkey,
+        stack_height: usize,
+    ) -> InstructionProfile {
+        self.post_process();
+        InstructionProfile {
+            program_id,
+            stack_height,
+            start_cu: 0,
+            entries: self.entries,
+            counters: self.counters,
+            heap_tags: self.heap_tags,
+            dropped_entries: self.dropped_entries,
+            timings: self.timings,
+            data_transfer: self.data_transfer,
+            lamport_deltas: BTreeMap::new(),
+            return_data: None,
+            failure: self.failure,
+        }
+    }
+}
+
+/// Whether an instruction invokes the `Execute` entrypoint of a transfer hook program
+pub fn is_transfer_hook_execute(data: &[u8]) -> bool {
+    data.starts_with(&TRANSFER_HOOK_EXECUTE_DISCRIMINATOR)
+}
+
+/// Name of the section recorded around a CPI
+///
+/// Token instructions are named after the instruction, so that the cost of a transfer including
+/// any transfer hook it invokes is reported as e.g. `token-2022:transfer_checked`. Transfer hook
+/// invocations are named `transfer-hook:<program id>`, all other CPIs `cpi:<program id>:<tag>`
+/// where the tag is the first byte of the instruction data.
+pub fn cpi_section_name(program_id: &Pubkey, data: &[u8]) -> String {
+    let token_program = if *program_id == token::id() {
+        Some("token")
+    } else if *program_id == token_2022::id() {
+        Some("token-2022")
+    } else {
+        None
+    };
+    let tag = data.first();
+    match (token_program, tag) {
+        (Some(token_program), Some(tag)) => match TOKEN_INSTRUCTION_NAMES.get(usize::from(*tag)) {
+            Some(instruction) => format!("{token_program}:{instruction}"),
+            None => format!("{token_program}:{tag}"),
+        },
+        _ if is_transfer_hook_execute(data) => format!("transfer-hook:{program_id}"),
+        (_, Some(tag)) => format!("cpi:{program_id}:{tag}"),
+        (_, None) => format!("cpi:{program_id}"),
+    }
+}
+
+/// Kind of a value submitted by `sol_emit_metric`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgramMetricKind {
+    /// The current value of a quantity
+    Gauge,
+    /// An amount to be summed over all submissions
+    Counter,
+}
+
+impl ProgramMetricKind {
+    /// Convert the syscall argument, `0` for a gauge and `1` for a counter
+    pub fn from_u64(kind: u64) -> Option<Self> {
+        match kind {
+            0 => Some(Self::Gauge),
+            1 => Some(Self::Counter),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gauge => "gauge",
+            Self::Counter => "counter",
+        }
+    }
+}
+
+/// Submit a value emitted by a program to the metrics pipeline
+///
+/// The value is reported as a `program-metric` datapoint tagged with the program id, the metric
+/// name and its kind. Nothing is submitted unless the `metrics` feature is enabled.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub fn submit_program_metric(program_id: &Pubkey, name: &str, kind: ProgramMetricKind, value: u64) {
+    #[cfg(feature = "metrics")]
+    datapoint_info!(
+        "program-metric",
+        "program_id" => program_id.to_string(),
+        "name" => name,
+        "kind" => kind.as_str(),
+        ("value", value, i64),
+    );
+}
+
+/// Execution envelope of a profiled transaction, logged once after its last instruction
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProfileBudget {
+    /// Compute unit limit the transaction executed with
+    pub compute_unit_limit: u64,
+    /// Limit requested by a compute budget instruction, `None` if the limit is the default one
+    ///
+    /// The requested limit exceeds `compute_unit_limit` if it was capped to the maximum.
+    pub requested_compute_unit_limit: Option<u32>,
+    /// Compute units consumed by all instructions of the transaction
+    pub consumed_cu: u64,
+    /// Heap region size, in bytes, available to each program
+    pub heap_bytes: u32,
+    /// Prioritization fee paid by the transaction, in lamports
+    pub prioritization_fee: u64,
+}
+
+/// A parsed `Program profile:` log line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProfileLogLine {
+    /// A completed section or marker, `heap_start` and the attributes are only part of the JSON
+    /// log format
+    Entry(ProfileEntry),
+    Counter {
+        id: u64,
+        value: u64,
+    },
+    Dropped(usize),
+    /// An attribute of the entry logged on the preceding line
+    Attribute {
+        key: String,
+        value: String,
+    },
+    /// Heap bytes attributed to an allocation tag
+    HeapTag {
+        name: String,
+        bytes: u64,
+    },
+    /// Execution envelope of the whole transaction, not tied to an instruction
+    Budget(ProfileBudget),
+    /// Reason the instruction failed, its entries are partial
+    Failed(String),
+}
+
+impl ProfileLogLine {
+    /// Parse a log line produced by [`stable_log`](crate::stable_log)
+    ///
+    /// Lines of older format versions are accepted down to [`MIN_PROFILE_LOG_VERSION`], fields
+    /// they lack are left at their defaults. Returns `None` for unrelated log lines and for
+    /// unsupported format versions.
+    pub fn parse(line: &str) -> Option<Self> {
+        let rest = line.strip_prefix(PROFILE_LOG_PREFIX)?.strip_prefix('v')?;
+        let (version, rest) = rest.split_once(' ')?;
+        let version: u8 = version.parse().ok()?;
+        if !(MIN_PROFILE_LOG_VERSION..=PROFILE_LOG_VERSION).contains(&version) {
+            return None;
+        }
+        let (kind, rest) = rest.split_once(' ')?;
+        let kind = match kind {
+            "section" => ProfileEntryKind::Section,
+            "mark" => ProfileEntryKind::Mark,
+            "counter" => {
+                let (id, value) = rest.split_once(' ')?;
+                return Some(Self::Counter {
+                    id: id.parse().ok()?,
+                    value: value.parse().ok()?,
+                });
+            }
+            "dropped" => return Some(Self::Dropped(rest.parse().ok()?)),
+            "attr" => {
+                let (key, value) = rest.split_once(' ')?;
+                return Some(Self::Attribute {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                });
+            }
+            "heap" => {
+                let (bytes, name) = rest.split_once(' ')?;
+                return Some(Self::HeapTag {
+                    name: name.to_string(),
+                    bytes: bytes.parse().ok()?,
+                });
+            }
+            "failed" if version >= 5 => return Some(Self::Failed(rest.to_string())),
+            "json" if version >= 6 => return ProfileEntry::from_json(rest).map(Self::Entry),
+            "budget" if version >= 4 => {
+                let mut fields = rest.split(' ');
+                let compute_unit_limit = fields.next()?.parse().ok()?;
+                // Before v7 only whether the limit was requested was logged, the limit it
+                // executed with is the requested one then, unless it was capped
+                let requested_compute_unit_limit = match fields.next()? {
+                    "default" => None,
+                    "requested" if version < 7 => Some(u32::try_from(compute_unit_limit).ok()?),
+                    requested_compute_unit_limit if version >= 7 => {
+                        Some(requested_compute_unit_limit.parse().ok()?)
+                    }
+                    _ => return None,
+                };
+                let budget = ProfileBudget {
+                    compute_unit_limit,
+                    requested_compute_unit_limit,
+                    consumed_cu: fields.next()?.parse().ok()?,
+                    heap_bytes: fields.next()?.parse().ok()?,
+                    prioritization_fee: fields.next()?.parse().ok()?,
+                };
+                return fields.next().is_none().then_some(Self::Budget(budget));
+            }
+            _ => return None,
+        };
+        // v2 added the return data field and v3 the flags field
+        let field_count = match version {
+            1 => 5,
+            2 => 6,
+            _ => 7,
+        };
+        let mut fields = rest.splitn(field_count, ' ');
+        let depth = fields.next()?.parse().ok()?;
+        let start_cu: u64 = fields.next()?.parse().ok()?;
+        let consumed: u64 = fields.next()?.parse().ok()?;
+        let heap_end = match fields.next()? {
+            "-" => None,
+            heap => Some(heap.parse().ok()?),
+        };
+        let return_data_bytes = if version >= 2 {
+            fields.next()?.parse().ok()?
+        } else {
+            0
+        };
+        let bulk_closed = version >= 3
+            && fields
+                .next()?
+                .split(',')
+                .any(|flag| flag == PROFILE_FLAG_BULK_CLOSED);
+        let name = fields.next()?.to_string();
+        Some(Self::Entry(ProfileEntry {
+            kind,
+            name,
+            depth,
+            start_cu,
+            end_cu: start_cu.saturating_add(consumed),
+            heap_start: (kind == ProfileEntryKind::Mark)
+                .then_some(heap_end)
+                .flatten(),
+            heap_end,
+            return_data_bytes,
+            bulk_closed,
+            attributes: BTreeMap::new(),
+            call_site: None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_program_metric_kind() {
+        assert_eq!(
+            ProgramMetricKind::from_u64(0),
+            Some(ProgramMetricKind::Gauge)
+        );
+        assert_eq!(
+            ProgramMetricKind::from_u64(1),
+            Some(ProgramMetricKind::Counter)
+        );
+        assert_eq!(ProgramMetricKind::from_u64(2), None);
+        assert_eq!(ProgramMetricKind::Gauge.as_str(), "gauge");
+        assert_eq!(ProgramMetricKind::Counter.as_str(), "counter");
+    }
+
+    #[test]
+    fn test_sections_and_marks() {
+        let mut state = ProfilingState::new(1_000);
+        assert!(state.is_empty());
+
+        state.start_section("outer", 900, Some(16));
+        state.mark("checkpoint", 850, None);
+        state.start_section("inner", 800, None);
+        assert!(state.end_section("inner", 700, None));
+        assert!(!state.end_section("missing", 650, None));
+        assert!(state.end_section("outer", 600, Some(48)));
+        assert_eq!(state.active_sections(), 0);
+
+        assert_eq!(
+            state.entries(),
+            &[
+                ProfileEntry {
+                    kind: ProfileEntryKind::Mark,
+                    name: "checkpoint".to_string(),
+                    depth: 1,
+                    start_cu: 150,
+                    end_cu: 150,
+                    heap_start: None,
+                    heap_end: None,
+                    return_data_bytes: 0,
+                    bulk_closed: false,
+                    attributes: BTreeMap::new(),
+                    call_site: None,
+                },
+                ProfileEntry {
+                    kind: ProfileEntryKind::Section,
+                    name: "inner".to_string(),
+                    depth: 1,
+                    start_cu: 200,
+                    end_cu: 300,
+                    heap_start: None,
+                    heap_end: None,
+                    return_data_bytes: 0,
+                    bulk_closed: false,
+                    attributes: BTreeMap::new(),
+                    call_site: None,
+                },
+                ProfileEntry {
+                    kind: ProfileEntryKind::Section,
+                    name: "outer".to_string(),
+                    depth: 0,
+                    start_cu: 100,
+                    end_cu: 400,
+                    heap_start: Some(16),
+                    heap_end: Some(48),
+                    return_data_bytes: 0,
+                    bulk_closed: false,
+                    attributes: BTreeMap::new(),
+                    call_site: None,
+                },
+            ]
+        );
+        assert_eq!(state.entries().first().unwrap().consumed(), 0);
+        assert_eq!(state.entries().last().unwrap().consumed(), 300);
+    }
+
+    #[test]
+    fn test_callee_return_data() {
+        let mut state = ProfilingState::new(1_000);
+        state.callee_return_data(8);
+        state.start_section("outer", 1_000, None);
+        state.callee_return_data(16);
+        state.start_section("inner", 900, None);
+        state.callee_return_data(32);
+        state.mark("checkpoint", 850, None);
+        assert!(state.end_section("inner", 800, None));
+        state.callee_return_data(4);
+        assert!(state.end_section("outer", 700, None));
+
+        assert_eq!(
+            state
+                .entries()
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.return_data_bytes))
+                .collect::<Vec<_>>(),
+            vec![("checkpoint", 0), ("inner", 32), ("outer", 32)]
+        );
+    }
+
+    #[test]
+    fn test_call_site() {
+        let mut state = ProfilingState::new(1_000);
+        state.set_call_site(10);
+        state.start_section("outer", 1_000, None);
+        state.start_section_id(1, 900, None);
+        state.set_call_site(30);
+        state.mark("checkpoint", 850, None);
+        assert!(state.end_section_id(1, 800, None));
+        // Ending a section does not take the call site
+        state.set_call_site(50);
+        assert!(state.end_section("outer", 700, None));
+        state.start_section("dangling", 600, None);
+        state.end_all_sections(500, None);
+
+        assert_eq!(
+            state
+                .entries()
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.call_site))
+                .collect::<Vec<_>>(),
+            vec![
+                ("checkpoint", Some(30)),
+                ("#1", None),
+                ("outer", Some(10)),
+                ("dangling", Some(50)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_heap_usage() {
+        let mut state = ProfilingState::new(1_000);
+        assert!(!state.record_heap_usage(8));
+        state.start_section("outer", 1_000, None);
+        state.start_section("inner", 900, None);
+        assert!(state.record_heap_usage(16));
+        assert!(state.record_heap_usage(32));
+        assert!(state.end_section("inner", 800, None));
+        assert!(state.record_heap_usage(64));
+        // Heap samples passed by the program take precedence
+        assert!(state.end_section("outer", 700, Some(128)));
+
+        assert_eq!(
+            state
+                .entries()
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.heap_end))
+                .collect::<Vec<_>>(),
+            vec![("inner", Some(32)), ("outer", Some(128))]
+        );
+    }
+
+    #[test]
+    fn test_heap_checkpoint() {
+        let mut state = ProfilingState::new(1_000);
+        state.start_section("outer", 900, None);
+        state.heap_checkpoint(
+            "after-parse",
+            850,
+            HeapCheckpoint {
+                used: 96,
+                free: 928,
+                largest_free: Some(928),
+            },
+        );
+        state.heap_checkpoint(
+            "custom",
+            800,
+            HeapCheckpoint {
+                used: 128,
+                free: 896,
+                largest_free: None,
+            },
+        );
+
+        let entries = state.entries();
+        assert_eq!(entries.len(), 2);
+        let (checkpoint, custom) = (entries.first().unwrap(), entries.last().unwrap());
+        assert_eq!(checkpoint.kind, ProfileEntryKind::Mark);
+        assert_eq!(checkpoint.name, "after-parse");
+        assert_eq!(checkpoint.depth, 1);
+        assert_eq!(checkpoint.start_cu, 150);
+        assert_eq!(checkpoint.heap_end, Some(96));
+        assert_eq!(
+            checkpoint.attributes,
+            BTreeMap::from([
+                (
+                    HEAP_CHECKPOINT_FREE_ATTRIBUTE.to_string(),
+                    "928".to_string()
+                ),
+                (
+                    HEAP_CHECKPOINT_LARGEST_FREE_ATTRIBUTE.to_string(),
+                    "928".to_string()
+                ),
+            ])
+        );
+        assert_eq!(custom.heap_end, Some(128));
+        assert_eq!(
+            custom.attributes,
+            BTreeMap::from([(
+                HEAP_CHECKPOINT_FREE_ATTRIBUTE.to_string(),
+                "896".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn test_heap_tags() {
+        let mut state = ProfilingState::new(1_000);
+        assert!(!state.heap_tag_end(8));
+        assert!(state.heap_tag_begin("accounts", 0));
+        assert!(state.heap_tag_begin("vec", 64));
+        assert!(state.heap_tag_end(96));
+        assert!(state.heap_tag_end(256));
+        assert!(state.heap_tag_begin("vec", 256));
+        assert!(state.heap_tag_end(288));
+        // Still active when the instruction completes
+        assert!(state.heap_tag_begin("unfinished", 288));
+        assert!(!state.is_empty());
+        assert_eq!(
+            state.heap_tags(),
+            &BTreeMap::from([
+                ("accounts".to_string(), 224),
+                ("unfinished".to_string(), 0),
+                ("vec".to_string(), 64),
+            ])
+        );
+
+        let mut state = ProfilingState::new(1_000);
+        for index in 0..MAX_PROFILE_HEAP_TAGS {
+            assert!(state.heap_tag_begin(&index.to_string(), 0));
+            assert!(state.heap_tag_end(0));
+        }
+        assert!(!state.heap_tag_begin("new", 0));
+        assert!(state.heap_tag_begin("0", 0));
+    }
+
+    #[test]
+    fn test_set_attribute() {
+        let mut state = ProfilingState::new(1_000);
+        assert!(!state.set_attribute("len", "1"));
+        state.start_section("outer", 1_000, None);
+        assert!(state.set_attribute("len", "1"));
+        state.start_section("inner", 900, None);
+        assert!(state.set_attribute("len", "2"));
+        assert!(state.set_attribute("len", "3"));
+        assert!(state.end_section("inner", 800, None));
+        for index in 1..MAX_PROFILE_ATTRIBUTES {
+            assert!(state.set_attribute(&index.to_string(), "x"));
+        }
+        assert!(!state.set_attribute("full", "x"));
+        // Existing keys can still be updated
+        assert!(state.set_attribute("len", "4"));
+        assert!(state.end_section("outer", 700, None));
+
+        let entries = state.entries();
+        assert_eq!(
+            entries.first().unwrap().attributes,
+            BTreeMap::from([("len".to_string(), "3".to_string())])
+        );
+        let outer = &entries.last().unwrap().attributes;
+        assert_eq!(outer.len(), MAX_PROFILE_ATTRIBUTES);
+        assert_eq!(outer.get("len").unwrap(), "4");
+    }
+
+    #[test]
+    fn test_section_ids() {
+        let mut state = ProfilingState::new(1_000);
+        assert!(state.register_name(1, "deserialize"));
+        state.start_section_id(1, 900, None);
+        state.start_section_id(2, 800, None);
+        state.start_section("2", 750, None);
+        assert!(state.end_section_id(2, 700, None));
+        assert!(!state.end_section_id(3, 700, None));
+        assert!(state.end_section("2", 650, None));
+        assert!(state.end_section_id(1, 600, None));
+
+        assert_eq!(
+            state
+                .entries()
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.depth, entry.consumed()))
+                .collect::<Vec<_>>(),
+            vec![("#2", 1, 100), ("2", 1, 100), ("deserialize", 0, 300)]
+        );
+
+        let mut state = ProfilingState::new(0);
+        for id in 0..MAX_PROFILE_REGISTERED_NAMES as u64 {
+            assert!(state.register_name(id, "name"));
+        }
+        assert!(!state.register_name(u64::MAX, "name"));
+        assert!(state.register_name(0, "renamed"));
+    }
+
+    #[test]
+    fn test_section_consumed_id() {
+        let mut state = ProfilingState::new(1_000);
+        assert_eq!(state.section_consumed_id(1, 1_000), None);
+
+        state.start_section_id(1, 900, None);
+        state.start_section_id(2, 800, None);
+        state.start_section_id(1, 700, None);
+        assert_eq!(state.section_consumed_id(1, 650), Some(50));
+        assert_eq!(state.section_consumed_id(2, 650), Some(150));
+
+        // The outer section with the same id is found once the inner one ends
+        assert!(state.end_section_id(1, 600, None));
+        assert_eq!(state.section_consumed_id(1, 600), Some(300));
+        assert_eq!(state.section_consumed_id(3, 600), None);
+    }
+
+    #[test]
+    fn test_metered_post_process() {
+        // Every section and marker syscall charges 100 compute units before sampling
+        let mut state = ProfilingState::new_metered(1_000);
+        assert!(state.is_metered());
+        state.charge_instrumentation(100);
+        state.start_section("outer", 900, None);
+        state.charge_instrumentation(100);
+        state.mark("checkpoint", 750, None);
+        state.charge_instrumentation(100);
+        state.start_section_id(1, 650, None);
+        assert_eq!(state.section_consumed_id(1, 630), Some(20));
+        state.charge_instrumentation(100);
+        assert!(state.end_section_id(1, 530, None));
+        state.charge_instrumentation(100);
+        assert!(state.end_section("outer", 430, None));
+        assert_eq!(state.instrumentation_cu(), 500);
+
+        let bounds = |state: &ProfilingState| {
+            state
+                .entries()
+                .iter()
+                .map(|entry| (entry.start_cu, entry.end_cu))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(bounds(&state), vec![(250, 250), (350, 470), (100, 570)]);
+        state.post_process();
+        assert_eq!(bounds(&state), vec![(50, 50), (50, 70), (0, 70)]);
+
+        // Only entries completed since the last call are adjusted
+        state.charge_instrumentation(100);
+        state.mark("done", 300, None);
+        state.post_process();
+        assert_eq!(
+            bounds(&state),
+            vec![(50, 50), (50, 70), (0, 70), (100, 100)]
+        );
+
+        // Nothing is charged without metering
+        let mut state = ProfilingState::new(1_000);
+        assert!(!state.is_metered());
+        state.start_section("outer", 900, None);
+        assert!(state.end_section("outer", 800, None));
+        assert_eq!(state.instrumentation_cu(), 0);
+        state.post_process();
+        assert_eq!(bounds(&state), vec![(100, 200)]);
+    }
+
+    #[test]
+    fn test_end_all_sections() {
+        let mut state = ProfilingState::new(1_000);
+        assert_eq!(state.end_all_sections(1_000, None), 0);
+
+        state.start_section("outer", 900, None);
+        state.start_section_id(1, 800, None);
+        state.start_section("inner", 700, Some(8));
+        assert!(state.end_section("inner", 650, None));
+        assert_eq!(state.end_all_sections(500, Some(64)), 2);
+        assert_eq!(state.active_sections(), 0);
+
+        assert_eq!(
+            state
+                .entries()
+                .iter()
+                .map(|entry| (
+                    entry.name.as_str(),
+                    entry.depth,
+                    entry.consumed(),
+                    entry.bulk_closed
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("inner", 2, 50, false),
+                ("#1", 1, 300, true),
+                ("outer", 0, 400, true)
+            ]
+        );
+        assert_eq!(state.entries().last().unwrap().heap_end, Some(64));
+        assert_eq!(
+            state.entries().last().unwrap().flags(),
+            PROFILE_FLAG_BULK_CLOSED
+        );
+    }
+
+    #[test]
+    fn test_fail() {
+        let mut state = ProfilingState::new(1_000);
+        state.start_section("outer", 900, None);
+        state.start_section("inner", 800, None);
+        assert_eq!(state.failure(), None);
+        state.fail("exceeded CUs meter".to_string(), 0);
+        assert_eq!(state.active_sections(), 0);
+        assert_eq!(state.failure(), Some("exceeded CUs meter"));
+        assert_eq!(
+            state.failed_sections(),
+            &[
+                FailedSection {
+                    name: "outer".to_string(),
+                    depth: 0,
+                    consumed_cu: 900,
+                },
+                FailedSection {
+                    name: "inner".to_string(),
+                    depth: 1,
+                    consumed_cu: 800,
+                },
+            ]
+        );
+
+        // The sections active when the instruction failed are part of the partial profile
+        let profile = state.into_instruction_profile(Pubkey::new_unique(), 1);
+        assert_eq!(
+            profile
+                .entries
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.consumed(), entry.bulk_closed))
+                .collect::<Vec<_>>(),
+            vec![("inner", 800, true), ("outer", 900, true)]
+        );
+        assert_eq!(profile.failure.as_deref(), Some("exceeded CUs meter"));
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut state = ProfilingState::new(1_000);
+        state.start_section("handle", 1_000, None);
+        for (start, end) in [(900, 850), (700, 650)] {
+            state.start_section("parse", start, None);
+            state.end_section("parse", end, None);
+        }
+        state.mark("checkpoint", 600, None);
+        state.start_section("transfer", 500, None);
+        state.start_section("parse", 450, None);
+        state.end_section("parse", 440, None);
+        state.end_section("transfer", 200, None);
+        state.end_section("handle", 100, None);
+        state.start_section("log", 100, None);
+        state.end_section("log", 100, None);
+
+        let summary = state.summary(2, 1_000);
+        assert_eq!(summary.total_cu, 1_000);
+        assert_eq!(summary.section_count, 4);
+        assert_eq!(
+            summary.top,
+            vec![
+                SectionSummary {
+                    name: "handle".to_string(),
+                    count: 1,
+                    net_cu: 500,
+                    heap: None,
+                },
+                SectionSummary {
+                    name: "transfer".to_string(),
+                    count: 1,
+                    net_cu: 290,
+                    heap: None,
+                },
+            ]
+        );
+        assert_eq!(summary.basis_points(290), 2_900);
+        assert_eq!(
+            state.summary(10, 1_000).top.last(),
+            Some(&SectionSummary {
+                name: "log".to_string(),
+                count: 1,
+                net_cu: 0,
+                heap: None,
+            })
+        );
+        assert_eq!(
+            state.summary(10, 1_000).top.get(2),
+            Some(&SectionSummary {
+                name: "parse".to_string(),
+                count: 3,
+                net_cu: 110,
+                heap: None,
+            })
+        );
+        assert_eq!(ProfileSummary::default().basis_points(10), 0);
+    }
+
+    #[test]
+    fn test_heap_metrics() {
+        let mut state = ProfilingState::new(1_000);
+        state.start_section("handle", 1_000, Some(100));
+        state.start_section("parse", 900, Some(150));
+        state.end_section("parse", 850, Some(400));
+        state.mark("checkpoint", 800, Some(400));
+        state.start_section("transfer", 700, Some(400));
+        state.start_section("log", 650, Some(700));
+        state.end_section("log", 600, Some(900));
+        state.end_section("transfer", 500, Some(600));
+        state.end_section("handle", 400, Some(650));
+        // Builtins and programs which do not sample the heap
+        state.start_section("parse", 300, None);
+        state.end_section("parse", 200, None);
+        state.post_process();
+
+        let metrics = |total_bytes, net_bytes, peak_bytes| {
+            Some(HeapMetrics {
+                total_bytes,
+                net_bytes,
+                peak_bytes,
+            })
+        };
+        assert_eq!(
+            state
+                .entries()
+                .iter()
+                .map(|entry| entry.name.as_str())
+                .zip(heap_metrics(state.entries()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("parse", metrics(250, 250, 400)),
+                ("checkpoint", None),
+                ("log", metrics(200, 200, 900)),
+                ("transfer", metrics(200, 0, 900)),
+                ("handle", metrics(550, 100, 900)),
+                ("parse", None),
+            ]
+        );
+
+        let summary = state.summary(10, 1_000);
+        assert_eq!(
+            summary
+                .top
+                .iter()
+                .map(|section| (section.name.as_str(), section.heap))
+                .collect::<Vec<_>>(),
+            vec![
+                ("handle", metrics(550, 100, 900)),
+                ("parse", metrics(250, 250, 400)),
+                ("transfer", metrics(200, 0, 900)),
+                ("log", metrics(200, 200, 900)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tree() {
+        let mut state = ProfilingState::new(1_000);
+        state.start_section("handle", 1_000, None);
+        state.start_section("parse", 900, None);
+        state.end_section("parse", 850, None);
+        state.mark("checkpoint", 600, None);
+        state.start_section("transfer", 500, None);
+        state.start_section("parse", 450, None);
+        state.end_section("parse", 440, None);
+        state.end_section("transfer", 200, None);
+        state.end_section("handle", 100, None);
+        state.start_section("log", 100, None);
+        state.end_section("log", 50, None);
+
+        let tree = state.tree(1_000);
+        assert_eq!(
+            tree.rows
+                .iter()
+                .map(|row| (row.level, row.name.as_str(), row.total_cu, row.net_cu))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, "handle", 900, 550),
+                (1, "parse", 50, 50),
+                (1, "transfer", 300, 290),
+                (2, "parse", 10, 10),
+                (0, "log", 50, 50),
+            ]
+        );
+        assert_eq!(
+            tree.lines(),
+            vec![
+                "handle 900 CU 90.00% (net 550 CU 55.00%)",
+                "|-- parse 50 CU 5.00% (net 50 CU 5.00%)",
+                "`-- transfer 300 CU 30.00% (net 290 CU 29.00%)",
+                "    `-- parse 10 CU 1.00% (net 10 CU 1.00%)",
+                "log 50 CU 5.00% (net 50 CU 5.00%)",
+            ]
+        );
+        assert!(ProfileTree::default().lines().is_empty());
+    }
+
+    #[test]
+    fn test_profile_verbosity() {
+        for verbosity in ProfileVerbosity::ALL {
+            assert_eq!(verbosity.to_string().parse(), Ok(verbosity));
+        }
+        assert_eq!("Full-JSON".parse(), Ok(ProfileVerbosity::FullJson));
+        assert_eq!(ProfileVerbosity::default(), ProfileVerbosity::Full);
+        assert_eq!(
+            "verbose"
+                .parse::<ProfileVerbosity>()
+                .unwrap_err()
+                .to_string(),
+            "invalid profile verbosity \"verbose\", expected one of off, summary, tree, full or \
+             full-json"
+        );
+    }
+
+    #[test]
+    fn test_cpi_section_name() {
+        assert_eq!(
+            cpi_section_name(&token_2022::id(), &[12, 1, 2, 3]),
+            "token-2022:transfer_checked"
+        );
+        assert_eq!(cpi_section_name(&token::id(), &[3]), "token:transfer");
+        assert_eq!(
+            cpi_section_name(&token_2022::id(), &[26, 1]),
+            "token-2022:26"
+        );
+
+        let hook_program = Pubkey::new_unique();
+        let mut execute = TRANSFER_HOOK_EXECUTE_DISCRIMINATOR.to_vec();
+        execute.extend_from_slice(&42u64.to_le_bytes());
+        assert!(is_transfer_hook_execute(&execute));
+        assert_eq!(
+            cpi_section_name(&hook_program, &execute),
+            format!("transfer-hook:{hook_program}")
+        );
+
+        let program = Pubkey::new_unique();
+        assert_eq!(
+            cpi_section_name(&program, &[7, 0]),
+            format!("cpi:{program}:7")
+        );
+        assert_eq!(cpi_section_name(&program, &[]), format!("cpi:{program}"));
+    }
+
+    #[test]
+    fn test_entry_limit() {
+        let mut state = ProfilingState::new(0);
+        for _ in 0..MAX_PROFILE_ENTRIES.saturating_add(3) {
+            state.mark("m", 0, None);
+        }
+        assert_eq!(state.entries().len(), MAX_PROFILE_ENTRIES);
+        assert_eq!(state.dropped_entries(), 3);
+    }
+
+    #[test]
+    fn test_instruction_timings() {
+        let mut profiling = ProfilingState::new(1_000);
+        assert_eq!(profiling.timings(), None);
+        let timings = InstructionTimings {
+            serialize_us: 1,
+            create_vm_us: 2,
+            execute_us: 3,
+            deserialize_us: 4,
+        };
+        profiling.set_timings(timings);
+        assert_eq!(profiling.timings(), Some(&timings));
+        // Timings alone do not make a profile worth reporting
+        assert!(profiling.is_empty());
+
+        let profile = profiling.into_instruction_profile(Pubkey::new_unique(), 1);
+        assert_eq!(profile.timings, Some(timings));
+    }
+
+    #[test]
+    fn test_instruction_data_transfer() {
+        let mut profiling = ProfilingState::new(1_000);
+        assert_eq!(profiling.data_transfer(), None);
+        let data_transfer = InstructionDataTransfer {
+            serialized_bytes: 10_240,
+            written_back_bytes: 128,
+        };
+        profiling.set_data_transfer(data_transfer);
+        assert_eq!(profiling.data_transfer(), Some(&data_transfer));
+        assert!(profiling.is_empty());
+
+        let profile = profiling.into_instruction_profile(Pubkey::new_unique(), 1);
+        assert_eq!(profile.data_transfer, Some(data_transfer));
+    }
+
+    #[test]
+    fn test_counters() {
+        let mut state = ProfilingState::new(0);
+        assert!(state.counter_add(7, 3));
+        assert!(state.counter_add(1, 1));
+        assert!(state.counter_add(7, u64::MAX));
+        assert!(!state.is_empty());
+        assert_eq!(
+            state.counters().iter().collect::<Vec<_>>(),
+            vec![(&1, &1), (&7, &u64::MAX)]
+        );
+
+        let mut state = ProfilingState::new(0);
+        for id in 0..MAX_PROFILE_COUNTERS as u64 {
+            assert!(state.counter_add(id, 1));
+        }
+        assert!(!state.counter_add(u64::MAX, 1));
+        assert!(state.counter_add(0, 1));
+        assert_eq!(state.counters().len(), MAX_PROFILE_COUNTERS);
+    }
+
+    #[test]
+    fn test_parse_profile_log_line() {
+        assert_eq!(
+            ProfileLogLine::parse(
+                "Program profile: v3 section 2 100 250 4096 512 - deserialize accounts"
+            ),
+            Some(ProfileLogLine::Entry(ProfileEntry {
+                kind: ProfileEntryKind::Section,
+                name: "deserialize accounts".to_string(),
+                depth: 2,
+                start_cu: 100,
+                end_cu: 350,
+                heap_start: None,
+                heap_end: Some(4096),
+                return_data_bytes: 512,
+                bulk_closed: false,
+                attributes: BTreeMap::new(),
+                call_site: None,
+            }))
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v3 mark 0 42 0 - 0 - "),
+            Some(ProfileLogLine::Entry(ProfileEntry {
+                kind: ProfileEntryKind::Mark,
+                name: String::new(),
+                depth: 0,
+                start_cu: 42,
+                end_cu: 42,
+                heap_start: None,
+                heap_end: None,
+                return_data_bytes: 0,
+                bulk_closed: false,
+                attributes: BTreeMap::new(),
+                call_site: None,
+            }))
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v3 section 0 0 10 - 0 bulk-closed outer"),
+            Some(ProfileLogLine::Entry(ProfileEntry {
+                kind: ProfileEntryKind::Section,
+                name: "outer".to_string(),
+                depth: 0,
+                start_cu: 0,
+                end_cu: 10,
+                heap_start: None,
+                heap_end: None,
+                return_data_bytes: 0,
+                bulk_closed: true,
+                attributes: BTreeMap::new(),
+                call_site: None,
+            }))
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v3 counter 7 15"),
+            Some(ProfileLogLine::Counter { id: 7, value: 15 })
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v3 dropped 3"),
+            Some(ProfileLogLine::Dropped(3))
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v3 attr accounts 3 writable"),
+            Some(ProfileLogLine::Attribute {
+                key: "accounts".to_string(),
+                value: "3 writable".to_string(),
+            })
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v3 heap 224 parse accounts"),
+            Some(ProfileLogLine::HeapTag {
+                name: "parse accounts".to_string(),
+                bytes: 224,
+            })
+        );
+        assert_eq!(ProfileLogLine::parse("Program profile: v0 dropped 3"), None);
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v4 budget 200000 default 1234 32768 5000"),
+            Some(ProfileLogLine::Budget(ProfileBudget {
+                compute_unit_limit: 200_000,
+                requested_compute_unit_limit: None,
+                consumed_cu: 1234,
+                heap_bytes: 32768,
+                prioritization_fee: 5000,
+            }))
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v4 budget 1400000 requested 7 262144 0"),
+            Some(ProfileLogLine::Budget(ProfileBudget {
+                compute_unit_limit: 1_400_000,
+                requested_compute_unit_limit: Some(1_400_000),
+                consumed_cu: 7,
+                heap_bytes: 262_144,
+                prioritization_fee: 0,
+            }))
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v7 budget 1400000 2000000 7 262144 0"),
+            Some(ProfileLogLine::Budget(ProfileBudget {
+                compute_unit_limit: 1_400_000,
+                requested_compute_unit_limit: Some(2_000_000),
+                consumed_cu: 7,
+                heap_bytes: 262_144,
+                prioritization_fee: 0,
+            }))
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v7 budget 1400000 requested 7 262144 0"),
+            None
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v3 budget 200000 default 1234 32768 5000"),
+            None
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v4 budget 200000 maybe 1234 32768 5000"),
+            None
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v5 failed custom program error: 0x1"),
+            Some(ProfileLogLine::Failed(
+                "custom program error: 0x1".to_string()
+            ))
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v4 failed custom program error: 0x1"),
+            None
+        );
+        assert_eq!(ProfileLogLine::parse("Program profile: v8 dropped 3"), None);
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v3 section 0 1"),
+            None
+        );
+        assert_eq!(ProfileLogLine::parse("Program log: v3 dropped 3"), None);
+
+        // Older format versions lack the trailing fields
+        let expected = ProfileEntry {
+            kind: ProfileEntryKind::Section,
+            name: "deserialize accounts".to_string(),
+            depth: 1,
+            start_cu: 100,
+            end_cu: 350,
+            heap_start: None,
+            heap_end: None,
+            return_data_bytes: 0,
+            bulk_closed: false,
+            attributes: BTreeMap::new(),
+            call_site: None,
+        };
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v1 section 1 100 250 - deserialize accounts"),
+            Some(ProfileLogLine::Entry(expected.clone()))
+        );
+        assert_eq!(
+            ProfileLogLine::parse(
+                "Program profile: v2 section 1 100 250 - 64 deserialize accounts"
+            ),
+            Some(ProfileLogLine::Entry(ProfileEntry {
+                return_data_bytes: 64,
+                ..expected
+            }))
+        );
+        assert_eq!(
+            ProfileLogLine::parse("Program profile: v2 dropped 3"),
+            Some(ProfileLogLine::Dropped(3))
+        );
+    }
+
+    #[test]
+    fn test_profile_entry_json() {
+        let entry = ProfileEntry {
+            kind: ProfileEntryKind::Section,
+            name: "parse \"accounts\"".to_string(),
+            depth: 1,
+            start_cu: 10,
+            end_cu: 30,
+            heap_start: Some(16),
+            heap_end: None,
+            return_data_bytes: 4,
+            bulk_closed: true,
+            attributes: BTreeMap::from([("accounts".to_string(), "3 writable".to_string())]),
+            call_site: None,
+        };
+        let json = entry.to_json(None);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!({
+                "kind": "section",
+                "name": "parse \"accounts\"",
+                "depth": 1,
+                "start_cu": 10,
+                "consumed_cu": 20,
+                "heap_start": 16,
+                "heap_end": null,
+                "heap_total_bytes": null,
+                "heap_net_bytes": null,
+                "heap_peak_bytes": null,
+                "return_data_bytes": 4,
+                "flags": ["bulk-closed"],
+                "attributes": {"accounts": "3 writable"},
+            })
+        );
+        assert!(!json.contains('\n'));
+        assert_eq!(ProfileEntry::from_json(&json), Some(entry.clone()));
+        let heap = HeapMetrics {
+            total_bytes: 64,
+            net_bytes: 32,
+            peak_bytes: 96,
+        };
+        let json_with_heap = entry.to_json(Some(heap));
+        let value = serde_json::from_str::<serde_json::Value>(&json_with_heap).unwrap();
+        assert_eq!(value.get("heap_total_bytes").unwrap(), 64);
+        assert_eq!(value.get("heap_net_bytes").unwrap(), 32);
+        assert_eq!(value.get("heap_peak_bytes").unwrap(), 96);
+        assert_eq!(
+            ProfileEntry::from_json(&json_with_heap),
+            Some(entry.clone())
+        );
+        assert_eq!(
+            ProfileLogLine::parse(&format!("Program profile: v6 json {json}")),
+            Some(ProfileLogLine::Entry(entry))
+        );
+
+        // Unknown keys and flags are ignored
+        assert_eq!(
+            ProfileEntry::from_json(
+                r#"{"kind":"mark","name":"","depth":0,"start_cu":42,"consumed_cu":0,"heap_start":null,"heap_end":null,"return_data_bytes":0,"flags":["new"],"attributes":{},"new":1}"#
+            ),
+            Some(ProfileEntry {
+                kind: ProfileEntryKind::Mark,
+                name: String::new(),
+                depth: 0,
+                start_cu: 42,
+                end_cu: 42,
+                heap_start: None,
+                heap_end: None,
+                return_data_bytes: 0,
+                bulk_closed: false,
+                attributes: BTreeMap::new(),
+                call_site: None,
+            })
+        );
+        assert_eq!(ProfileEntry::from_json(r#"{"kind":"section"}"#), None);
+        assert_eq!(ProfileEntry::from_json("section"), None);
+        assert_eq!(
+            ProfileLogLine::parse(&format!("Program profile: v5 json {json}")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_heap_sample() {
+        assert_eq!(ProfilingState::heap_sample(NO_HEAP_SAMPLE), None);
+        assert_eq!(ProfilingState::heap_sample(0), Some(0));
+    }
+}
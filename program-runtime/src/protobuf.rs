@@ -0,0 +1,602 @@
+//! Protobuf encodings of instruction profiles
+//!
+//! [`generated::TransactionProfile`] is a lossless encoding of the [`ProfileReport`] of a
+//! transaction, see [`transaction_profile`]. [`pprof_profile`]
+//! converts the sections of the instruction profiles into the profile format of pprof instead, so
+//! that the compute units of transactions can be analyzed and diffed with `go tool pprof`.
+
+use {
+    crate::{
+        profile_report::{
+            ProfileReport, SourceLocation, TransactionMetadata, PROFILE_REPORT_SCHEMA_VERSION,
+        },
+        profiling::{
+            timeline_sections, InstructionDataTransfer, InstructionProfile, InstructionTimings,
+            ProfileEntry, ProfileEntryKind, ProfileReturnData, ProgramLoadProfile, TimelineSection,
+        },
+    },
+    solana_pubkey::Pubkey,
+    std::collections::BTreeMap,
+};
+
+pub mod generated {
+    include!(concat!(
+        env!("OUT_DIR"),
+        "/solana.program_runtime.profile.rs"
+    ));
+}
+
+pub mod pprof {
+    include!(concat!(env!("OUT_DIR"), "/perftools.profiles.rs"));
+}
+
+/// Unit of the samples of [`pprof_profile`]
+pub const PPROF_SAMPLE_TYPE: &str = "compute_units";
+
+/// Encode the profile report of a transaction
+pub fn transaction_profile(report: &ProfileReport) -> generated::TransactionProfile {
+    let TransactionMetadata {
+        signature,
+        slot,
+        compute_units_consumed,
+        error,
+    } = &report.transaction;
+    generated::TransactionProfile {
+        version: u32::from(report.profile_log_version),
+        instructions: report.instructions.iter().map(Into::into).collect(),
+        program_loads: report.program_loads.iter().map(Into::into).collect(),
+        transaction: Some(generated::TransactionMetadata {
+            signature: signature.clone(),
+            slot: *slot,
+            compute_units_consumed: *compute_units_consumed,
+            error: error.clone(),
+        }),
+        source_locations: report.source_locations.iter().map(Into::into).collect(),
+    }
+}
+
+impl TryFrom<generated::TransactionProfile> for ProfileReport {
+    type Error = &'static str;
+
+    /// Decode a profile report encoded by [`transaction_profile`], in the current schema
+    fn try_from(profile: generated::TransactionProfile) -> Result<Self, Self::Error> {
+        let transaction = profile.transaction.unwrap_or_default();
+        Ok(Self {
+            schema_version: PROFILE_REPORT_SCHEMA_VERSION,
+            profile_log_version: u8::try_from(profile.version)
+                .map_err(|_| "Invalid profile log version")?,
+            transaction: TransactionMetadata {
+                signature: transaction.signature,
+                slot: transaction.slot,
+                compute_units_consumed: transaction.compute_units_consumed,
+                error: transaction.error,
+            },
+            instructions: profile
+                .instructions
+                .into_iter()
+                .map(InstructionProfile::try_from)
+                .collect::<Result<_, _>>()?,
+            program_loads: profile
+                .program_loads
+                .into_iter()
+                .map(ProgramLoadProfile::try_from)
+                .collect::<Result<_, _>>()?,
+            source_locations: profile
+                .source_locations
+                .into_iter()
+                .map(SourceLocation::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+fn pubkey(bytes: &[u8]) -> Result<Pubkey, &'static str> {
+    Pubkey::try_from(bytes).map_err(|_| "Invalid pubkey")
+}
+
+fn to_usize(value: u64) -> Result<usize, &'static str> {
+    usize::try_from(value).map_err(|_| "Value exceeds usize")
+}
+
+impl From<&ProfileEntry> for generated::ProfileEntry {
+    fn from(entry: &ProfileEntry) -> Self {
+        let kind = match entry.kind {
+            ProfileEntryKind::Section => generated::ProfileEntryKind::Section,
+            ProfileEntryKind::Mark => generated::ProfileEntryKind::Mark,
+        };
+        Self {
+            kind: kind.into(),
+            name: entry.name.clone(),
+            depth: entry.depth as u64,
+            start_cu: entry.start_cu,
+            end_cu: entry.end_cu,
+            heap_start: entry.heap_start,
+            heap_end: entry.heap_end,
+            return_data_bytes: entry.return_data_bytes,
+            bulk_closed: entry.bulk_closed,
+            attributes: entry.attributes.clone().into_iter().collect(),
+            call_site: entry.call_site,
+        }
+    }
+}
+
+impl TryFrom<generated::ProfileEntry> for ProfileEntry {
+    type Error = &'static str;
+
+    fn try_from(entry: generated::ProfileEntry) -> Result<Self, Self::Error> {
+        let kind = match generated::ProfileEntryKind::from_i32(entry.kind) {
+            Some(generated::ProfileEntryKind::Section) => ProfileEntryKind::Section,
+            Some(generated::ProfileEntryKind::Mark) => ProfileEntryKind::Mark,
+            None => return Err("Invalid profile entry kind"),
+        };
+        Ok(Self {
+            kind,
+            name: entry.name,
+            depth: to_usize(entry.depth)?,
+            start_cu: entry.start_cu,
+            end_cu: entry.end_cu,
+            heap_start: entry.heap_start,
+            heap_end: entry.heap_end,
+            return_data_bytes: entry.return_data_bytes,
+            bulk_closed: entry.bulk_closed,
+            attributes: entry.attributes.into_iter().collect(),
+            call_site: entry.call_site,
+        })
+    }
+}
+
+impl From<&SourceLocation> for generated::SourceLocation {
+    fn from(location: &SourceLocation) -> Self {
+        Self {
+            program_id: location.program_id.to_bytes().to_vec(),
+            pc: location.pc,
+            function: location.function.clone(),
+            file: location.file.clone(),
+            line: location.line,
+        }
+    }
+}
+
+impl TryFrom<generated::SourceLocation> for SourceLocation {
+    type Error = &'static str;
+
+    fn try_from(location: generated::SourceLocation) -> Result<Self, Self::Error> {
+        Ok(Self {
+            program_id: pubkey(&location.program_id)?,
+            pc: location.pc,
+            function: location.function,
+            file: location.file,
+            line: location.line,
+        })
+    }
+}
+
+impl From<&InstructionProfile> for generated::InstructionProfile {
+    fn from(profile: &InstructionProfile) -> Self {
+        Self {
+            program_id: profile.program_id.to_bytes().to_vec(),
+            stack_height: profile.stack_height as u64,
+            start_cu: profile.start_cu,
+            entries: profile.entries.iter().map(Into::into).collect(),
+            counters: profile.counters.clone().into_iter().collect(),
+            heap_tags: profile.heap_tags.clone().into_iter().collect(),
+            dropped_entries: profile.dropped_entries as u64,
+            timings: profile
+                .timings
+                .map(|timings| generated::InstructionTimings {
+                    serialize_us: timings.serialize_us,
+                    create_vm_us: timings.create_vm_us,
+                    execute_us: timings.execute_us,
+                    deserialize_us: timings.deserialize_us,
+                }),
+            data_transfer: profile.data_transfer.map(|data_transfer| {
+                generated::InstructionDataTransfer {
+                    serialized_bytes: data_transfer.serialized_bytes,
+                    written_back_bytes: data_transfer.written_back_bytes,
+                }
+            }),
+            lamport_deltas: profile
+                .lamport_deltas
+                .iter()
+                .map(|(pubkey, delta)| generated::LamportDelta {
+                    pubkey: pubkey.to_bytes().to_vec(),
+                    // Balances are u64, the change of a single account always fits
+                    delta: i64::try_from(*delta).unwrap_or(if delta.is_negative() {
+                        i64::MIN
+                    } else {
+                        i64::MAX
+                    }),
+                })
+                .collect(),
+            return_data: profile
+                .return_data
+                .as_ref()
+                .map(|return_data| generated::ReturnData {
+                    program_id: return_data.program_id.to_bytes().to_vec(),
+                    data: return_data.data.clone(),
+                    len: return_data.len as u64,
+                }),
+            failure: profile.failure.clone(),
+        }
+    }
+}
+
+impl TryFrom<generated::InstructionProfile> for InstructionProfile {
+    type Error = &'static str;
+
+    fn try_from(profile: generated::InstructionProfile) -> Result<Self, Self::Error> {
+        Ok(Self {
+            program_id: pubkey(&profile.program_id)?,
+            stack_height: to_usize(profile.stack_height)?,
+            start_cu: profile.start_cu,
+            entries: profile
+                .entries
+                .into_iter()
+                .map(ProfileEntry::try_from)
+                .collect::<Result<_, _>>()?,
+            counters: profile.counters.into_iter().collect(),
+            heap_tags: profile.heap_tags.into_iter().collect(),
+            dropped_entries: to_usize(profile.dropped_entries)?,
+            timings: profile.timings.map(|timings| InstructionTimings {
+                serialize_us: timings.serialize_us,
+                create_vm_us: timings.create_vm_us,
+                execute_us: timings.execute_us,
+                deserialize_us: timings.deserialize_us,
+            }),
+            data_transfer: profile
+                .data_transfer
+                .map(|data_transfer| InstructionDataTransfer {
+                    serialized_bytes: data_transfer.serialized_bytes,
+                    written_back_bytes: data_transfer.written_back_bytes,
+                }),
+            lamport_deltas: profile
+                .lamport_deltas
+                .into_iter()
+                .map(|delta| Ok((pubkey(&delta.pubkey)?, i128::from(delta.delta))))
+                .collect::<Result<_, Self::Error>>()?,
+            return_data: profile
+                .return_data
+                .map(|return_data| {
+                    Ok::<_, Self::Error>(ProfileReturnData {
+                        program_id: pubkey(&return_data.program_id)?,
+                        data: return_data.data,
+                        len: to_usize(return_data.len)?,
+                    })
+                })
+                .transpose()?,
+            failure: profile.failure,
+        })
+    }
+}
+
+impl From<&ProgramLoadProfile> for generated::ProgramLoadProfile {
+    fn from(profile: &ProgramLoadProfile) -> Self {
+        Self {
+            program_id: profile.program_id.to_bytes().to_vec(),
+            register_syscalls_us: profile.register_syscalls_us,
+            load_elf_us: profile.load_elf_us,
+            verify_code_us: profile.verify_code_us,
+            jit_compile_us: profile.jit_compile_us,
+        }
+    }
+}
+
+impl TryFrom<generated::ProgramLoadProfile> for ProgramLoadProfile {
+    type Error = &'static str;
+
+    fn try_from(profile: generated::ProgramLoadProfile) -> Result<Self, Self::Error> {
+        Ok(Self {
+            program_id: pubkey(&profile.program_id)?,
+            register_syscalls_us: profile.register_syscalls_us,
+            load_elf_us: profile.load_elf_us,
+            verify_code_us: profile.verify_code_us,
+            jit_compile_us: profile.jit_compile_us,
+        })
+    }
+}
+
+/// Strings of a pprof profile, which its other messages reference by index
+struct StringTable(Vec<String>);
+
+impl StringTable {
+    fn new() -> Self {
+        // The first string must be empty
+        Self(vec![String::new()])
+    }
+
+    fn index(&mut self, string: &str) -> i64 {
+        let index = self
+            .0
+            .iter()
+            .position(|existing| existing == string)
+            .unwrap_or_else(|| {
+                self.0.push(string.to_string());
+                self.0.len().saturating_sub(1)
+            });
+        index as i64
+    }
+}
+
+/// Convert the sections of a transaction's instruction profiles into a pprof profile
+///
+/// Every section becomes a function named after it, with the program which recorded it as its
+/// file name, and the section hierarchy forms the stacks of the samples. Like with
+/// [`folded_stacks`](crate::folded_stacks::folded_stacks), each stack is sampled once with the
+/// compute units it consumed itself and pprof adds those of the nested sections back up. The
+/// sections of the invoked instructions nest within the sections of their callers, see
+/// [`timeline_sections`].
+pub fn pprof_profile(profiles: &[InstructionProfile]) -> pprof::Profile {
+    let sections = timeline_sections(profiles);
+    let mut functions: Vec<(&str, &Pubkey)> = Vec::new();
+    // Keyed by the ids of the locations of the stack, outermost first
+    let mut stacks: BTreeMap<Vec<u64>, u64> = BTreeMap::new();
+    let mut open: Vec<(&TimelineSection, Vec<u64>, u64)> = Vec::new();
+    let mut close = |(_section, stack, self_cu): (&TimelineSection, Vec<u64>, u64)| {
+        if self_cu > 0 {
+            let cu = stacks.entry(stack).or_default();
+            *cu = cu.saturating_add(self_cu);
+        }
+    };
+    for section in &sections {
+        while let Some(parent) = open.pop_if(|(parent, _, _)| !parent.encloses(section)) {
+            close(parent);
+        }
+        let key = (section.entry.name.as_str(), section.program_id);
+        // Locations map one to one to functions and share their ids, which must be nonzero
+        let id = functions
+            .iter()
+            .position(|function| *function == key)
+            .unwrap_or_else(|| {
+                functions.push(key);
+                functions.len().saturating_sub(1)
+            })
+            .saturating_add(1) as u64;
+        let stack = match open.last_mut() {
+            Some((_parent, parent_stack, parent_self_cu)) => {
+                *parent_self_cu = parent_self_cu.saturating_sub(section.entry.consumed());
+                parent_stack.iter().copied().chain([id]).collect()
+            }
+            None => vec![id],
+        };
+        open.push((section, stack, section.entry.consumed()));
+    }
+    while let Some(parent) = open.pop() {
+        close(parent);
+    }
+
+    let mut strings = StringTable::new();
+    let sample_type = pprof::ValueType {
+        r#type: strings.index(PPROF_SAMPLE_TYPE),
+        unit: strings.index("count"),
+    };
+    let function = functions
+        .iter()
+        .zip(1u64..)
+        .map(|((name, program_id), id)| pprof::Function {
+            id,
+            name: strings.index(name),
+            system_name: strings.index(name),
+            filename: strings.index(&program_id.to_string()),
+            start_line: 0,
+        })
+        .collect();
+    let location = (1..=functions.len() as u64)
+        .map(|id| pprof::Location {
+            id,
+            line: vec![pprof::Line {
+                function_id: id,
+                ..pprof::Line::default()
+            }],
+            ..pprof::Location::default()
+        })
+        .collect();
+    let sample = stacks
+        .into_iter()
+        .map(|(mut stack, cu)| {
+            // pprof expects the leaf first
+            stack.reverse();
+            pprof::Sample {
+                location_id: stack,
+                value: vec![i64::try_from(cu).unwrap_or(i64::MAX)],
+                label: Vec::new(),
+            }
+        })
+        .collect();
+    pprof::Profile {
+        sample_type: vec![sample_type.clone()],
+        sample,
+        location,
+        function,
+        string_table: strings.0,
+        period_type: Some(sample_type),
+        period: 1,
+        ..pprof::Profile::default()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use {super::*, crate::profiling::PROFILE_LOG_VERSION, prost::Message};
+
+    fn entry(
+        kind: ProfileEntryKind,
+        name: &str,
+        depth: usize,
+        start_cu: u64,
+        end_cu: u64,
+    ) -> ProfileEntry {
+        ProfileEntry {
+            kind,
+            name: name.to_string(),
+            depth,
+            start_cu,
+            end_cu,
+            heap_start: None,
+            heap_end: None,
+            return_data_bytes: 0,
+            bulk_closed: false,
+            attributes: BTreeMap::new(),
+            call_site: None,
+        }
+    }
+
+    #[test]
+    fn test_transaction_profile_roundtrip() {
+        let program_id = Pubkey::new_unique();
+        let mut section = entry(ProfileEntryKind::Section, "handle", 0, 10, 120);
+        section.heap_start = Some(32);
+        section.heap_end = Some(96);
+        section.return_data_bytes = 8;
+        section.bulk_closed = true;
+        section.call_site = Some(1234);
+        section
+            .attributes
+            .insert("mint".to_string(), "usdc".to_string());
+        let instruction = InstructionProfile {
+            program_id,
+            stack_height: 2,
+            start_cu: 300,
+            entries: vec![
+                entry(ProfileEntryKind::Mark, "checkpoint", 1, 20, 20),
+                section,
+            ],
+            counters: BTreeMap::from([(7, 3)]),
+            heap_tags: BTreeMap::from([("accounts".to_string(), 1024)]),
+            dropped_entries: 1,
+            timings: Some(InstructionTimings {
+                serialize_us: 1,
+                create_vm_us: 2,
+                execute_us: 3,
+                deserialize_us: 4,
+            }),
+            data_transfer: Some(InstructionDataTransfer {
+                serialized_bytes: 512,
+                written_back_bytes: 64,
+            }),
+            lamport_deltas: BTreeMap::from([(Pubkey::new_unique(), -5), (Pubkey::new_unique(), 5)]),
+            return_data: Some(ProfileReturnData {
+                program_id,
+                data: vec![1, 2, 3],
+                len: 40,
+            }),
+            failure: Some("custom program error: 0x1".to_string()),
+        };
+        let program_load = ProgramLoadProfile {
+            program_id,
+            register_syscalls_us: 1,
+            load_elf_us: 2,
+            verify_code_us: 3,
+            jit_compile_us: 4,
+        };
+
+        let mut report = ProfileReport::new(
+            TransactionMetadata {
+                signature: Some("sig".to_string()),
+                slot: Some(42),
+                compute_units_consumed: 1200,
+                error: Some("custom program error: 0x1".to_string()),
+            },
+            vec![InstructionProfile::default(), instruction],
+            vec![program_load],
+        );
+        report.source_locations.push(SourceLocation {
+            program_id,
+            pc: 1234,
+            function: Some("handle".to_string()),
+            file: Some("src/lib.rs".to_string()),
+            line: None,
+        });
+
+        let encoded = transaction_profile(&report).encode_to_vec();
+        let decoded = generated::TransactionProfile::decode(encoded.as_slice()).unwrap();
+        assert_eq!(decoded.version, u32::from(PROFILE_LOG_VERSION));
+        assert_eq!(ProfileReport::try_from(decoded), Ok(report));
+        // Artifacts encoded before the transaction metadata was added
+        assert_eq!(
+            ProfileReport::try_from(generated::TransactionProfile {
+                version: u32::from(PROFILE_LOG_VERSION),
+                ..generated::TransactionProfile::default()
+            }),
+            Ok(ProfileReport::new(
+                TransactionMetadata::default(),
+                vec![],
+                vec![]
+            ))
+        );
+
+        let invalid = generated::InstructionProfile {
+            program_id: vec![0; 31],
+            ..generated::InstructionProfile::default()
+        };
+        assert_eq!(InstructionProfile::try_from(invalid), Err("Invalid pubkey"));
+    }
+
+    #[test]
+    fn test_pprof_profile() {
+        let outer = Pubkey::new_unique();
+        let inner = Pubkey::new_unique();
+        let profiles = [
+            // Invoked from within the `handle` section of the caller
+            InstructionProfile {
+                program_id: inner,
+                stack_height: 2,
+                start_cu: 150,
+                entries: vec![entry(ProfileEntryKind::Section, "transfer", 1, 10, 40)],
+                ..InstructionProfile::default()
+            },
+            InstructionProfile {
+                program_id: outer,
+                stack_height: 1,
+                start_cu: 100,
+                entries: vec![
+                    entry(ProfileEntryKind::Section, "parse", 1, 5, 20),
+                    entry(ProfileEntryKind::Mark, "checkpoint", 1, 20, 20),
+                    entry(ProfileEntryKind::Section, "verify", 1, 60, 60),
+                    entry(ProfileEntryKind::Section, "handle", 0, 0, 120),
+                ],
+                ..InstructionProfile::default()
+            },
+        ];
+
+        let profile = pprof_profile(&profiles);
+        let string = |index: i64| profile.string_table[index as usize].as_str();
+        assert_eq!(string(0), "");
+        assert_eq!(string(profile.sample_type[0].r#type), PPROF_SAMPLE_TYPE);
+        assert_eq!(
+            profile
+                .function
+                .iter()
+                .map(|function| (
+                    function.id,
+                    string(function.name),
+                    string(function.filename)
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, "handle", outer.to_string().as_str()),
+                (2, "parse", outer.to_string().as_str()),
+                (3, "transfer", inner.to_string().as_str()),
+                (4, "verify", outer.to_string().as_str()),
+            ]
+        );
+        assert_eq!(profile.location.len(), 4);
+        assert_eq!(profile.location[2].line[0].function_id, 3);
+        assert_eq!(
+            profile
+                .sample
+                .iter()
+                .map(|sample| (sample.location_id.clone(), sample.value.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (vec![1], vec![75]),
+                (vec![2, 1], vec![15]),
+                (vec![3, 1], vec![30]),
+            ]
+        );
+
+        let empty = pprof_profile(&[]);
+        assert!(empty.sample.is_empty());
+        assert!(empty.function.is_empty());
+    }
+}
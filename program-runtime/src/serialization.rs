@@ -281,13 +281,16 @@ pub fn serialize_parameters(
     }
 }
 
+/// Copy the account changes made by the program back from the input region
+///
+/// Returns the number of account data bytes which were copied back.
 pub fn deserialize_parameters(
     instruction_context: &InstructionContext,
     stricter_abi_and_runtime_constraints: bool,
     account_data_direct_mapping: bool,
     buffer: &[u8],
     accounts_metadata: &[SerializedAccountMetadata],
-) -> Result<(), InstructionError> {
+) -> Result<u64, InstructionError> {
     let is_loader_deprecated =
         instruction_context.get_program_owner()? == bpf_loader_deprecated::id();
     let account_lengths = accounts_metadata.iter().map(|a| a.original_data_len);
@@ -407,8 +410,9 @@ fn deserialize_parameters_unaligned<I: IntoIterator<Item = usize>>(
     account_data_direct_mapping: bool,
     buffer: &[u8],
     account_lengths: I,
-) -> Result<(), InstructionError> {
+) -> Result<u64, InstructionError> {
     let mut start = size_of::<u64>(); // number of accounts
+    let mut copied_bytes = 0u64;
     for (instruction_account_index, pre_len) in (0..instruction_context
         .get_number_of_instruction_accounts())
         .zip(account_lengths.into_iter())
@@ -439,7 +443,10 @@ fn deserialize_parameters_unaligned<I: IntoIterator<Item = usize>>(
                     .ok_or(InstructionError::InvalidArgument)?;
                 // The redundant check helps to avoid the expensive data comparison if we can
                 match borrowed_account.can_data_be_resized(pre_len) {
-                    Ok(()) => borrowed_account.set_data_from_slice(data)?,
+                    Ok(()) => {
+                        borrowed_account.set_data_from_slice(data)?;
+                        copied_bytes = copied_bytes.saturating_add(data.len() as u64);
+                    }
                     Err(err) if borrowed_account.get_data() != data => return Err(err),
                     _ => {}
                 }
@@ -449,6 +456,7 @@ fn deserialize_parameters_unaligned<I: IntoIterator<Item = usize>>(
                     .get(start..start + pre_len)
                     .ok_or(InstructionError::InvalidArgument)?;
                 borrowed_account.set_data_from_slice(data)?;
+                copied_bytes = copied_bytes.saturating_add(data.len() as u64);
             } else if borrowed_account.get_data().len() != pre_len {
                 borrowed_account.set_data_length(pre_len)?;
             }
@@ -460,7 +468,7 @@ fn deserialize_parameters_unaligned<I: IntoIterator<Item = usize>>(
                 + size_of::<u64>(); // rent_epoch
         }
     }
-    Ok(())
+    Ok(copied_bytes)
 }
 
 fn serialize_parameters_aligned(
@@ -569,8 +577,9 @@ fn deserialize_parameters_aligned<I: IntoIterator<Item = usize>>(
     account_data_direct_mapping: bool,
     buffer: &[u8],
     account_lengths: I,
-) -> Result<(), InstructionError> {
+) -> Result<u64, InstructionError> {
     let mut start = size_of::<u64>(); // number of accounts
+    let mut copied_bytes = 0u64;
     for (instruction_account_index, pre_len) in (0..instruction_context
         .get_number_of_instruction_accounts())
         .zip(account_lengths.into_iter())
@@ -620,7 +629,10 @@ fn deserialize_parameters_aligned<I: IntoIterator<Item = usize>>(
                     .ok_or(InstructionError::InvalidArgument)?;
                 // The redundant check helps to avoid the expensive data comparison if we can
                 match borrowed_account.can_data_be_resized(post_len) {
-                    Ok(()) => borrowed_account.set_data_from_slice(data)?,
+                    Ok(()) => {
+                        borrowed_account.set_data_from_slice(data)?;
+                        copied_bytes = copied_bytes.saturating_add(data.len() as u64);
+                    }
                     Err(err) if borrowed_account.get_data() != data => return Err(err),
                     _ => {}
                 }
@@ -630,6 +642,7 @@ fn deserialize_parameters_aligned<I: IntoIterator<Item = usize>>(
                     .get(start..start + post_len)
                     .ok_or(InstructionError::InvalidArgument)?;
                 borrowed_account.set_data_from_slice(data)?;
+                copied_bytes = copied_bytes.saturating_add(data.len() as u64);
             } else if borrowed_account.get_data().len() != post_len {
                 borrowed_account.set_data_length(post_len)?;
             }
@@ -649,7 +662,7 @@ fn deserialize_parameters_aligned<I: IntoIterator<Item = usize>>(
             }
         }
     }
-    Ok(())
+    Ok(copied_bytes)
 }
 
 #[cfg(test)]
@@ -0,0 +1,165 @@
+//! Export of instruction profiles to the speedscope file format
+//!
+//! The resulting JSON opens directly in [speedscope](https://www.speedscope.app), which shows the
+//! sections of a transaction as a flame graph, a left-heavy graph or a table. The sections are
+//! rendered as a single evented profile weighted by compute units, on the timeline described in
+//! [`chrome_trace`](crate::chrome_trace). Markers carry no weight and are left out.
+
+use {
+    crate::profiling::{timeline_sections, InstructionProfile, TimelineSection},
+    serde_json::{json, Value},
+    solana_pubkey::Pubkey,
+};
+
+/// Schema of the speedscope file format
+pub const SPEEDSCOPE_SCHEMA: &str = "https://www.speedscope.app/file-format-schema.json";
+
+/// Render the sections of a transaction's instruction profiles as a speedscope file named `name`
+///
+/// Each frame is a section name qualified by the program which recorded it. The sections of the
+/// invoked instructions nest within the sections of their callers, see [`timeline_sections`].
+pub fn speedscope(profiles: &[InstructionProfile], name: &str) -> Value {
+    let sections = timeline_sections(profiles);
+    let mut frames: Vec<(&str, &Pubkey)> = Vec::new();
+    let mut events = Vec::new();
+    let mut open: Vec<(&TimelineSection, usize)> = Vec::new();
+    let mut at = 0;
+    for section in &sections {
+        while let Some((parent, frame)) = open.pop_if(|(parent, _)| !parent.encloses(section)) {
+            at = parent.end_cu.max(at);
+            events.push(json!({ "type": "C", "frame": frame, "at": at }));
+        }
+        let key = (section.entry.name.as_str(), section.program_id);
+        let frame = frames
+            .iter()
+            .position(|frame| *frame == key)
+            .unwrap_or_else(|| {
+                frames.push(key);
+                frames.len().saturating_sub(1)
+            });
+        at = section.start_cu.max(at);
+        events.push(json!({ "type": "O", "frame": frame, "at": at }));
+        open.push((section, frame));
+    }
+    while let Some((parent, frame)) = open.pop() {
+        at = parent.end_cu.max(at);
+        events.push(json!({ "type": "C", "frame": frame, "at": at }));
+    }
+
+    json!({
+        "$schema": SPEEDSCOPE_SCHEMA,
+        "name": name,
+        "exporter": "solana-program-runtime",
+        "activeProfileIndex": 0,
+        "shared": {
+            "frames": frames
+                .iter()
+                .map(|(name, program_id)| json!({ "name": name, "file": program_id.to_string() }))
+                .collect::<Vec<_>>(),
+        },
+        "profiles": [{
+            "type": "evented",
+            "name": name,
+            "unit": "none",
+            "startValue": 0,
+            "endValue": at,
+            "events": events,
+        }],
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use {
+        super::*,
+        crate::profiling::{ProfileEntry, ProfileEntryKind},
+        std::collections::BTreeMap,
+    };
+
+    fn entry(
+        kind: ProfileEntryKind,
+        name: &str,
+        depth: usize,
+        start_cu: u64,
+        end_cu: u64,
+    ) -> ProfileEntry {
+        ProfileEntry {
+            kind,
+            name: name.to_string(),
+            depth,
+            start_cu,
+            end_cu,
+            heap_start: None,
+            heap_end: None,
+            return_data_bytes: 0,
+            bulk_closed: false,
+            attributes: BTreeMap::new(),
+            call_site: None,
+        }
+    }
+
+    #[test]
+    fn test_speedscope() {
+        let outer = Pubkey::new_unique();
+        let inner = Pubkey::new_unique();
+        let profiles = [
+            // Invoked from within the `handle` section of the caller
+            InstructionProfile {
+                program_id: inner,
+                stack_height: 2,
+                start_cu: 150,
+                entries: vec![entry(ProfileEntryKind::Section, "transfer", 1, 10, 40)],
+                ..InstructionProfile::default()
+            },
+            InstructionProfile {
+                program_id: outer,
+                stack_height: 1,
+                start_cu: 100,
+                entries: vec![
+                    entry(ProfileEntryKind::Section, "parse", 1, 5, 20),
+                    entry(ProfileEntryKind::Mark, "checkpoint", 1, 20, 20),
+                    entry(ProfileEntryKind::Section, "handle", 0, 0, 120),
+                    entry(ProfileEntryKind::Section, "parse", 0, 120, 130),
+                ],
+                ..InstructionProfile::default()
+            },
+        ];
+
+        let file = speedscope(&profiles, "transaction");
+        assert_eq!(file["$schema"], SPEEDSCOPE_SCHEMA);
+        assert_eq!(
+            file["shared"]["frames"],
+            json!([
+                { "name": "handle", "file": outer.to_string() },
+                { "name": "parse", "file": outer.to_string() },
+                { "name": "transfer", "file": inner.to_string() },
+            ])
+        );
+        let profile = &file["profiles"][0];
+        assert_eq!(profile["type"], "evented");
+        assert_eq!(profile["endValue"], 230);
+        assert_eq!(
+            profile["events"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|event| (
+                    event["type"].as_str().unwrap(),
+                    event["frame"].as_u64().unwrap(),
+                    event["at"].as_u64().unwrap(),
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("O", 0, 100),
+                ("O", 1, 105),
+                ("C", 1, 120),
+                ("O", 2, 160),
+                ("C", 2, 190),
+                ("C", 0, 220),
+                ("O", 1, 220),
+                ("C", 1, 230),
+            ]
+        );
+    }
+}
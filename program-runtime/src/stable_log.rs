@@ -3,10 +3,14 @@
 //! The format of these log messages should not be modified to avoid breaking downstream consumers
 //! of program logging
 use {
+    crate::profiling::{
+        FailedSection, HeapMetrics, ProfileBudget, ProfileEntry, ProfileSummary, ProfileTree,
+        PROFILE_LOG_VERSION,
+    },
     base64::{prelude::BASE64_STANDARD, Engine},
     itertools::Itertools,
     solana_pubkey::Pubkey,
-    solana_svm_log_collector::{ic_logger_msg, LogCollector},
+    solana_svm_log_collector::{ic_logger_msg, LogCollector, LogEntry, LogLevel, ProfileLogEntry},
     std::{cell::RefCell, rc::Rc},
 };
 
@@ -22,12 +26,50 @@ pub fn program_invoke(
     program_id: &Pubkey,
     invoke_depth: usize,
 ) {
-    ic_logger_msg!(
-        log_collector,
-        "Program {} invoke [{}]",
-        program_id,
-        invoke_depth
+    log_entry(log_collector, || LogEntry::ProgramInvoke {
+        program_id: *program_id,
+        depth: invoke_depth,
+    });
+}
+
+/// Record a structured entry, built only if it is recorded by the log collector or the debug
+/// logger
+fn log_entry(log_collector: &Option<Rc<RefCell<LogCollector>>>, entry: impl FnOnce() -> LogEntry) {
+    record_entry(log_collector, entry, LogCollector::log_entry);
+}
+
+/// Record a profiling entry like [`log_entry`], counting it against the profile byte limit of the
+/// log collector instead of the log byte limit
+fn log_profile(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    entry: impl FnOnce() -> LogEntry,
+) {
+    record_entry(log_collector, entry, LogCollector::log_profile);
+}
+
+fn record_entry(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    entry: impl FnOnce() -> LogEntry,
+    record: fn(&mut LogCollector, LogEntry),
+) {
+    let log_collector = log_collector
+        .as_ref()
+        .and_then(|log_collector| log_collector.try_borrow_mut().ok());
+    let debug_enabled = log::log_enabled!(
+        target: "solana_runtime::message_processor::stable_log",
+        log::Level::Debug
+    );
+    if log_collector.is_none() && !debug_enabled {
+        return;
+    }
+    let entry = entry();
+    log::debug!(
+        target: "solana_runtime::message_processor::stable_log",
+        "{entry}"
     );
+    if let Some(mut log_collector) = log_collector {
+        record(&mut log_collector, entry);
+    }
 }
 
 /// Log a message from the program itself.
@@ -40,7 +82,27 @@ pub fn program_invoke(
 ///
 /// That is, any program-generated output is guaranteed to be prefixed by "Program log: "
 pub fn program_log(log_collector: &Option<Rc<RefCell<LogCollector>>>, message: &str) {
-    ic_logger_msg!(log_collector, "Program log: {}", message);
+    if is_recorded(
+        log_collector,
+        "Program log: ".len().saturating_add(message.len()),
+    ) {
+        log_entry(log_collector, || LogEntry::ProgramLog(message.to_string()));
+    }
+}
+
+/// Whether a message of `len` bytes would be recorded by the log collector or the debug logger
+///
+/// Lets callers skip formatting messages which would be discarded, e.g. once the log collector
+/// hit its byte limit.
+pub fn is_recorded(log_collector: &Option<Rc<RefCell<LogCollector>>>, len: usize) -> bool {
+    log::log_enabled!(
+        target: "solana_runtime::message_processor::stable_log",
+        log::Level::Debug
+    ) || log_collector.as_ref().is_some_and(|log_collector| {
+        log_collector
+            .try_borrow()
+            .is_ok_and(|log_collector| log_collector.would_log(len))
+    })
 }
 
 /// Emit a program data.
@@ -53,11 +115,240 @@ pub fn program_log(log_collector: &Option<Rc<RefCell<LogCollector>>>, message: &
 ///
 /// That is, any program-generated output is guaranteed to be prefixed by "Program data: "
 pub fn program_data(log_collector: &Option<Rc<RefCell<LogCollector>>>, data: &[&[u8]]) {
+    let encoded_len = data
+        .iter()
+        .map(|field| base64::encoded_len(field.len(), true).unwrap_or(usize::MAX))
+        .fold(data.len().saturating_sub(1), usize::saturating_add);
+    if is_recorded(
+        log_collector,
+        "Program data: ".len().saturating_add(encoded_len),
+    ) {
+        let mut encoded = String::with_capacity(encoded_len);
+        for (index, field) in data.iter().enumerate() {
+            encode_program_data_field(&mut encoded, index, field);
+        }
+        log_entry(log_collector, || LogEntry::ProgramData(encoded));
+    }
+}
+
+/// Emit a program data, encoding each field as soon as `fields` yields it.
+///
+/// Logs the same message as [`program_data`] without collecting the fields first. `fields` is
+/// always consumed so that its errors do not depend on whether logging is enabled, but nothing is
+/// encoded when the message would be discarded.
+pub fn program_data_streamed<'a, E>(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    fields: impl IntoIterator<Item = Result<&'a [u8], E>>,
+) -> Result<(), E> {
+    // The length of the fields is only known once they are translated, but a message whose
+    // prefix alone would be discarded does not need to be encoded at all
+    let mut encoded = is_recorded(log_collector, "Program data: ".len()).then(String::new);
+    for (index, field) in fields.into_iter().enumerate() {
+        let field = field?;
+        if let Some(encoded) = &mut encoded {
+            encode_program_data_field(encoded, index, field);
+        }
+    }
+    if let Some(encoded) = encoded {
+        log_entry(log_collector, || LogEntry::ProgramData(encoded));
+    }
+    Ok(())
+}
+
+/// Append the base64 encoding of the field at `index` to the fields encoded so far
+fn encode_program_data_field(encoded: &mut String, index: usize, field: &[u8]) {
+    if index > 0 {
+        encoded.push(' ');
+    }
+    BASE64_STANDARD.encode_string(field, encoded);
+}
+
+/// Log a message from the program itself with a severity level.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program log: [<level>] <program-generated output>"
+/// ```
+///
+/// Messages below the log collector's minimum level are dropped.
+pub fn program_log_level(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    level: LogLevel,
+    message: &str,
+) {
+    if log_collector.as_ref().is_some_and(|log_collector| {
+        log_collector
+            .try_borrow()
+            .is_ok_and(|log_collector| !log_collector.is_level_enabled(level))
+    }) {
+        return;
+    }
+    log_entry(log_collector, || LogEntry::ProgramLogLevel {
+        level,
+        message: message.to_string(),
+    });
+}
+
+/// Log the metadata of an instruction account.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program account: #<index-in-instruction> <key> owner <owner> lamports <lamports> data_len <bytes>"
+/// ```
+pub fn program_account(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    index_in_instruction: u16,
+    key: &Pubkey,
+    owner: &Pubkey,
+    lamports: u64,
+    data_len: usize,
+) {
     ic_logger_msg!(
         log_collector,
-        "Program data: {}",
-        data.iter().map(|v| BASE64_STANDARD.encode(v)).join(" ")
+        "Program account: #{} {} owner {} lamports {} data_len {}",
+        index_in_instruction,
+        key,
+        owner,
+        lamports,
+        data_len,
+    );
+}
+
+/// Log the statistics of the runtime's heap allocator.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program heap: used <bytes> free <bytes> allocations <count>"
+/// ```
+pub fn program_heap_stats(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    used: u64,
+    free: u64,
+    allocations: u64,
+) {
+    ic_logger_msg!(
+        log_collector,
+        "Program heap: used {} free {} allocations {}",
+        used,
+        free,
+        allocations,
+    );
+}
+
+/// Emit a structured key/value record.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program kv: <key>=<quoted-value>*"
+/// ```
+///
+/// Values are quoted and escaped like Rust string literals. The fields are also recorded as a
+/// [`LogRecord`](solana_svm_log_collector::LogRecord) so they can be consumed without parsing
+/// the message.
+pub fn program_kv(log_collector: &Option<Rc<RefCell<LogCollector>>>, fields: &[(&str, &str)]) {
+    let message = format!(
+        "Program kv: {}",
+        fields
+            .iter()
+            .map(|(key, value)| format!("{key}={value:?}"))
+            .join(" ")
+    );
+    log::debug!(
+        target: "solana_runtime::message_processor::stable_log",
+        "{message}"
+    );
+    if let Some(log_collector) = log_collector.as_ref() {
+        if let Ok(mut log_collector) = log_collector.try_borrow_mut() {
+            log_collector.log_record(
+                &message,
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+            );
+        }
+    }
+}
+
+/// Emit a program data tagged with an event name.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program event: <name> <base64-encoded-field>*"
+/// ```
+///
+/// The event name and the encoded fields are also recorded as the `event` and `data` fields of a
+/// [`LogRecord`](solana_svm_log_collector::LogRecord), so events can be grouped by name without
+/// parsing the message.
+pub fn program_event(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    name: &str,
+    data: &[&[u8]],
+) {
+    let encoded = data.iter().map(|v| BASE64_STANDARD.encode(v)).join(" ");
+    let message = if encoded.is_empty() {
+        format!("Program event: {name}")
+    } else {
+        format!("Program event: {name} {encoded}")
+    };
+    log::debug!(
+        target: "solana_runtime::message_processor::stable_log",
+        "{message}"
     );
+    if let Some(log_collector) = log_collector.as_ref() {
+        if let Ok(mut log_collector) = log_collector.try_borrow_mut() {
+            log_collector.log_record(
+                &message,
+                vec![
+                    ("event".to_string(), name.to_string()),
+                    ("data".to_string(), encoded),
+                ],
+            );
+        }
+    }
+}
+
+/// Emit a hexdump of program memory, one log line per `bytes_per_line` bytes.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program log: <offset-in-hex>: <bytes-in-hex>  <printable-ascii>"
+/// ```
+///
+/// Non-printable bytes are shown as `.` in the ASCII column, which is aligned on the last line.
+pub fn program_log_hex(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    data: &[u8],
+    bytes_per_line: usize,
+) {
+    let bytes_per_line = bytes_per_line.max(1);
+    for (index, line) in data.chunks(bytes_per_line).enumerate() {
+        let hex = line.iter().map(|byte| format!("{byte:02x}")).join(" ");
+        let ascii: String = line
+            .iter()
+            .map(|byte| {
+                if byte.is_ascii_graphic() || *byte == b' ' {
+                    *byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        ic_logger_msg!(
+            log_collector,
+            "Program log: {:08x}: {:width$}  {}",
+            index.saturating_mul(bytes_per_line),
+            hex,
+            ascii,
+            width = bytes_per_line.saturating_mul(3).saturating_sub(1),
+        );
+    }
 }
 
 /// Log return data as from the program itself. This line will not be present if no return
@@ -75,12 +366,30 @@ pub fn program_return(
     program_id: &Pubkey,
     data: &[u8],
 ) {
-    ic_logger_msg!(
-        log_collector,
-        "Program return: {} {}",
-        program_id,
-        BASE64_STANDARD.encode(data)
-    );
+    log_entry(log_collector, || LogEntry::ProgramReturn {
+        program_id: *program_id,
+        data: data.to_vec(),
+    });
+}
+
+/// Log the compute units consumed by a program.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program <address> consumed <consumed> of <limit> compute units"
+/// ```
+pub fn program_consumed(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    program_id: &Pubkey,
+    consumed: u64,
+    limit: u64,
+) {
+    log_entry(log_collector, || LogEntry::ProgramConsumed {
+        program_id: *program_id,
+        consumed,
+        limit,
+    });
 }
 
 /// Log successful program execution.
@@ -91,7 +400,25 @@ pub fn program_return(
 /// "Program <address> success"
 /// ```
 pub fn program_success(log_collector: &Option<Rc<RefCell<LogCollector>>>, program_id: &Pubkey) {
-    ic_logger_msg!(log_collector, "Program {} success", program_id);
+    log_entry(log_collector, || LogEntry::ProgramSuccess {
+        program_id: *program_id,
+    });
+}
+
+/// Log the message a program aborted with.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program aborted with code <code>: <program-generated message>"
+/// ```
+pub fn program_abort(log_collector: &Option<Rc<RefCell<LogCollector>>>, code: u32, message: &str) {
+    ic_logger_msg!(
+        log_collector,
+        "Program aborted with code {}: {}",
+        code,
+        message
+    );
 }
 
 /// Log program execution failure
@@ -106,5 +433,355 @@ pub fn program_failure<E: std::fmt::Display>(
     program_id: &Pubkey,
     err: &E,
 ) {
-    ic_logger_msg!(log_collector, "Program {} failed: {}", program_id, err);
+    log_entry(log_collector, || LogEntry::ProgramFailure {
+        program_id: *program_id,
+        error: err.to_string(),
+    });
+}
+
+/// Log a profiling section which was still active when the program failed, outermost first,
+/// after the failure.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program <address> failed in section: depth <depth> consumed <compute-units> <name>"
+/// ```
+pub fn program_failed_section(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    program_id: &Pubkey,
+    section: &FailedSection,
+) {
+    ic_logger_msg!(
+        log_collector,
+        "Program {} failed in section: depth {} consumed {} {}",
+        program_id,
+        section.depth,
+        section.consumed_cu,
+        section.name,
+    );
+}
+
+/// Log a frame of a program's call stack, innermost frame first.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program backtrace: #<index> <pc> <function>+<offset>"
+/// ```
+///
+/// `<function>+<offset>` is `?` if the program counter could not be symbolized.
+pub fn program_backtrace_frame(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    index: usize,
+    pc: u64,
+    symbol: Option<(&str, u64)>,
+) {
+    match symbol {
+        Some((function, offset)) => {
+            ic_logger_msg!(
+                log_collector,
+                "Program backtrace: #{} {} {}+{}",
+                index,
+                pc,
+                function,
+                offset,
+            );
+        }
+        None => {
+            ic_logger_msg!(log_collector, "Program backtrace: #{} {} ?", index, pc);
+        }
+    }
+}
+
+/// Log the state of the VM when a program hits `sol_debug_break`.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program debug break: pc <pc> remaining <compute-units>"
+/// "Program debug break: r0=0x<value> r1=0x<value> ... r10=0x<value>"
+/// ```
+///
+/// The call stack follows as `Program backtrace:` lines.
+pub fn program_debug_break(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    pc: u64,
+    remaining: u64,
+    registers: &[u64],
+) {
+    ic_logger_msg!(
+        log_collector,
+        "Program debug break: pc {} remaining {}",
+        pc,
+        remaining,
+    );
+    let registers: Vec<_> = registers
+        .iter()
+        .enumerate()
+        .map(|(index, value)| format!("r{index}={value:#x}"))
+        .collect();
+    ic_logger_msg!(
+        log_collector,
+        "Program debug break: {}",
+        registers.join(" ")
+    );
+}
+
+/// Log a completed profiling entry.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program profile: v<version> <kind> <depth> <start-cu> <consumed-cu> <heap-bytes> <return-data-bytes> <flags> <name>"
+/// ```
+///
+/// `<heap-bytes>` is `-` if the program did not sample its heap usage. `<return-data-bytes>` is
+/// the largest return data set by a CPI callee while the entry was open. `<flags>` is a comma
+/// separated list such as `bulk-closed`, or `-` if empty. The name is last so that it may contain
+/// spaces.
+///
+/// The entry line is followed by one line per attribute of the entry:
+///
+/// ```notrust
+/// "Program profile: v<version> attr <key> <value>"
+/// ```
+///
+/// Profiling messages are counted against their own byte limit, see
+/// [`LogCollector::log_profile`], and are followed by `Profile truncated` once it is hit.
+pub fn program_profile(log_collector: &Option<Rc<RefCell<LogCollector>>>, entry: &ProfileEntry) {
+    log_profile(log_collector, || {
+        LogEntry::ProfileEntry(ProfileLogEntry {
+            version: PROFILE_LOG_VERSION,
+            kind: entry.kind.as_str().to_string(),
+            depth: entry.depth,
+            start_cu: entry.start_cu,
+            consumed_cu: entry.consumed(),
+            heap: entry.heap_end,
+            return_data_bytes: entry.return_data_bytes,
+            flags: entry.flags().to_string(),
+            name: entry.name.clone(),
+        })
+    });
+    for (key, value) in &entry.attributes {
+        log_profile(log_collector, || {
+            LogEntry::Message(format!(
+                "Program profile: v{} attr {} {}",
+                PROFILE_LOG_VERSION, key, value
+            ))
+        });
+    }
+}
+
+/// Log a completed profiling entry as a single line JSON object, including its attributes.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program profile: v<version> json <object>"
+/// ```
+///
+/// See [`ProfileEntry::to_json`] for the keys of the object, `heap` being the heap usage of the
+/// entry. This replaces the `section`, `mark` and `attr` lines logged by [`program_profile`].
+pub fn program_profile_json(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    entry: &ProfileEntry,
+    heap: Option<HeapMetrics>,
+) {
+    log_profile(log_collector, || {
+        LogEntry::Message(format!(
+            "Program profile: v{} json {}",
+            PROFILE_LOG_VERSION,
+            entry.to_json(heap)
+        ))
+    });
+}
+
+/// Log a summary of the sections of an instruction instead of its entries, a header followed by
+/// one line per section ordered by decreasing net compute units.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program profile: v<version> summary top <count> of <sections> sections, <compute-units> CU"
+/// "Program profile: v<version> summary <net-cu> <percent>% x<count> <heap> <name>"
+/// ```
+///
+/// The percentages refer to the compute units of the whole instruction. `<heap>` is the total,
+/// net and peak heap usage of the sections in bytes, `<total>/<net>/<peak>`, or `-` if none of
+/// them sampled the heap. Parsers of the profile logs skip these lines, they only repeat what the
+/// entries show.
+pub fn program_profile_summary(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    summary: &ProfileSummary,
+) {
+    log_profile(log_collector, || {
+        LogEntry::Message(format!(
+            "Program profile: v{} summary top {} of {} sections, {} CU",
+            PROFILE_LOG_VERSION,
+            summary.top.len(),
+            summary.section_count,
+            summary.total_cu,
+        ))
+    });
+    for section in &summary.top {
+        log_profile(log_collector, || {
+            let basis_points = summary.basis_points(section.net_cu);
+            let heap = section.heap.map_or_else(
+                || "-".to_string(),
+                |heap| {
+                    format!(
+                        "{}/{}/{}",
+                        heap.total_bytes, heap.net_bytes, heap.peak_bytes
+                    )
+                },
+            );
+            LogEntry::Message(format!(
+                "Program profile: v{} summary {:>8} {:>3}.{:02}% x{:<4} {} {}",
+                PROFILE_LOG_VERSION,
+                section.net_cu,
+                basis_points / 100,
+                basis_points % 100,
+                section.count,
+                heap,
+                section.name,
+            ))
+        });
+    }
+}
+
+/// Log the section hierarchy of an instruction as an indented tree instead of its entries, a
+/// header followed by one line per section, see [`ProfileTree::lines`].
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program profile: v<version> tree <compute-units> CU"
+/// "Program profile: v<version> tree <indent><name> <cu> CU <pct>% (net <net-cu> CU <pct>%)"
+/// ```
+///
+/// The percentages refer to the compute units of the whole instruction. Parsers of the profile
+/// logs skip these lines, they only repeat what the entries show.
+pub fn program_profile_tree(log_collector: &Option<Rc<RefCell<LogCollector>>>, tree: &ProfileTree) {
+    log_profile(log_collector, || {
+        LogEntry::Message(format!(
+            "Program profile: v{} tree {} CU",
+            PROFILE_LOG_VERSION, tree.total_cu,
+        ))
+    });
+    for line in tree.lines() {
+        log_profile(log_collector, || {
+            LogEntry::Message(format!(
+                "Program profile: v{} tree {}",
+                PROFILE_LOG_VERSION, line
+            ))
+        });
+    }
+}
+
+/// Log the final value of a profiling counter.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program profile: v<version> counter <id> <value>"
+/// ```
+pub fn program_profile_counter(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    id: u64,
+    value: u64,
+) {
+    log_profile(log_collector, || {
+        LogEntry::Message(format!(
+            "Program profile: v{} counter {} {}",
+            PROFILE_LOG_VERSION, id, value
+        ))
+    });
+}
+
+/// Log the heap bytes attributed to an allocation tag.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program profile: v<version> heap <bytes> <name>"
+/// ```
+pub fn program_profile_heap_tag(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    name: &str,
+    bytes: u64,
+) {
+    log_profile(log_collector, || {
+        LogEntry::Message(format!(
+            "Program profile: v{} heap {} {}",
+            PROFILE_LOG_VERSION, bytes, name
+        ))
+    });
+}
+
+/// Log the number of profiling entries which were dropped because the per-instruction limit was
+/// reached.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program profile: v<version> dropped <count>"
+/// ```
+pub fn program_profile_dropped(log_collector: &Option<Rc<RefCell<LogCollector>>>, count: usize) {
+    log_profile(log_collector, || {
+        LogEntry::Message(format!(
+            "Program profile: v{} dropped {}",
+            PROFILE_LOG_VERSION, count
+        ))
+    });
+}
+
+/// Log why a profiled instruction failed, after its partial profile.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program profile: v<version> failed <reason>"
+/// ```
+///
+/// The sections which were active when the instruction failed are closed with the `bulk-closed`
+/// flag, see [`ProfilingState::fail`](crate::profiling::ProfilingState::fail).
+pub fn program_profile_failure(log_collector: &Option<Rc<RefCell<LogCollector>>>, reason: &str) {
+    log_profile(log_collector, || {
+        LogEntry::Message(format!(
+            "Program profile: v{} failed {}",
+            PROFILE_LOG_VERSION, reason
+        ))
+    });
+}
+
+/// Log the execution envelope of a profiled transaction, after its last instruction.
+///
+/// The general form is:
+///
+/// ```notrust
+/// "Program profile: v<version> budget <cu-limit> <requested-cu-limit|default> <consumed-cu> <heap-bytes> <prioritization-fee>"
+/// ```
+///
+/// `<requested-cu-limit>` is the compute unit limit set by a compute budget instruction, before
+/// it is capped to the maximum, `default` marks a limit derived from the instructions of the
+/// transaction.
+pub fn program_profile_budget(
+    log_collector: &Option<Rc<RefCell<LogCollector>>>,
+    budget: &ProfileBudget,
+) {
+    log_profile(log_collector, || {
+        let requested_compute_unit_limit = budget
+            .requested_compute_unit_limit
+            .map_or_else(|| "default".to_string(), |limit| limit.to_string());
+        LogEntry::Message(format!(
+            "Program profile: v{} budget {} {} {} {} {}",
+            PROFILE_LOG_VERSION,
+            budget.compute_unit_limit,
+            requested_compute_unit_limit,
+            budget.consumed_cu,
+            budget.heap_bytes,
+            budget.prioritization_fee,
+        ))
+    });
 }
@@ -0,0 +1,159 @@
+//! Resolution of program counters to source locations with the DWARF debug info of a program
+//!
+//! Programs built with debug info keep the DWARF sections in their ELF, even though they are not
+//! loaded on chain. A [`Symbolizer`] maps the call sites of profiling entries, see
+//! [`ProfileEntry::call_site`](crate::profiling::ProfileEntry::call_site), and the frames logged
+//! by `sol_backtrace` back to the functions, files and lines of the program's source, so that
+//! profiles read like the profiles of native code.
+
+use {
+    crate::profile_report::{ProfileReport, SourceLocation},
+    addr2line::{
+        gimli,
+        object::{self, Object, ObjectSection},
+        Context,
+    },
+    solana_pubkey::Pubkey,
+    solana_sbpf::ebpf::INSN_SIZE,
+    std::{collections::BTreeSet, fmt},
+};
+
+#[derive(Debug)]
+pub enum SymbolizeError {
+    Elf(object::Error),
+    /// The ELF does not have a `.text` section, which program counters are relative to
+    MissingTextSection,
+    /// The ELF was stripped of its DWARF sections, or built without debug info
+    MissingDebugInfo,
+    Dwarf(gimli::Error),
+}
+
+impl fmt::Display for SymbolizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Elf(err) => write!(f, "invalid ELF: {err}"),
+            Self::MissingTextSection => write!(f, "the ELF does not have a .text section"),
+            Self::MissingDebugInfo => write!(f, "the ELF does not contain DWARF debug info"),
+            Self::Dwarf(err) => write!(f, "invalid DWARF debug info: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SymbolizeError {}
+
+/// Maps the program counters of a program to source locations, see the
+/// [module documentation](self)
+pub struct Symbolizer {
+    context: Context<gimli::EndianRcSlice<gimli::RunTimeEndian>>,
+    /// Virtual address of the first instruction, the one at program counter 0
+    text_address: u64,
+}
+
+impl Symbolizer {
+    /// Load the debug info of the program ELF `elf`
+    pub fn new(elf: &[u8]) -> Result<Self, SymbolizeError> {
+        let file = object::File::parse(elf).map_err(SymbolizeError::Elf)?;
+        let text_address = file
+            .section_by_name(".text")
+            .ok_or(SymbolizeError::MissingTextSection)?
+            .address();
+        if file.section_by_name(".debug_info").is_none() {
+            return Err(SymbolizeError::MissingDebugInfo);
+        }
+        let context = Context::new(&file).map_err(SymbolizeError::Dwarf)?;
+        Ok(Self {
+            context,
+            text_address,
+        })
+    }
+
+    /// Source location of the SBF instruction at `pc` of the program `program_id`
+    ///
+    /// The function is the innermost one, so that code inlined into its caller is attributed to
+    /// the inlined function. Returns `None` if the debug info does not cover the instruction.
+    pub fn source_location(&self, program_id: Pubkey, pc: u64) -> Option<SourceLocation> {
+        let address = pc
+            .checked_mul(INSN_SIZE as u64)?
+            .checked_add(self.text_address)?;
+        let mut frames = self.context.find_frames(address).skip_all_loads().ok()?;
+        let frame = frames.next().ok()??;
+        let function = frame
+            .function
+            .and_then(|function| function.demangle().ok().map(|name| name.into_owned()));
+        let (file, line) = frame
+            .location
+            .map(|location| (location.file.map(str::to_string), location.line))
+            .unwrap_or_default();
+        (function.is_some() || file.is_some()).then_some(SourceLocation {
+            program_id,
+            pc,
+            function,
+            file,
+            line,
+        })
+    }
+
+    /// Add the source locations of the call sites of the entries recorded by `program_id` to
+    /// [`ProfileReport::source_locations`]
+    ///
+    /// Call sites which are already symbolized or which the debug info does not cover are
+    /// skipped.
+    pub fn symbolize_report(&self, report: &mut ProfileReport, program_id: &Pubkey) {
+        let call_sites: BTreeSet<u64> = report
+            .instructions
+            .iter()
+            .filter(|profile| profile.program_id == *program_id)
+            .flat_map(|profile| profile.entries.iter().filter_map(|entry| entry.call_site))
+            .filter(|pc| report.source_location(program_id, *pc).is_none())
+            .collect();
+        report.source_locations.extend(
+            call_sites
+                .into_iter()
+                .filter_map(|pc| self.source_location(*program_id, pc)),
+        );
+    }
+
+    /// Append the source location of the frame to a `Program backtrace:` log message of the
+    /// program `program_id`, see [`program_backtrace_frame`]
+    ///
+    /// Returns `None` if the message is not a backtrace frame or its program counter could not
+    /// be symbolized.
+    ///
+    /// [`program_backtrace_frame`]: crate::stable_log::program_backtrace_frame
+    pub fn symbolize_backtrace_frame(&self, program_id: Pubkey, message: &str) -> Option<String> {
+        let location = self.source_location(program_id, backtrace_frame_pc(message)?)?;
+        Some(format!("{message} [{location}]"))
+    }
+}
+
+/// Program counter of a `Program backtrace: #<index> <pc> ...` log message
+fn backtrace_frame_pc(message: &str) -> Option<u64> {
+    let mut fields = message
+        .strip_prefix("Program backtrace: #")?
+        .split_whitespace();
+    fields.next()?.parse::<usize>().ok()?;
+    fields.next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, assert_matches::assert_matches, std::fs};
+
+    #[test]
+    fn test_new() {
+        let elf = fs::read("../programs/bpf_loader/test_elfs/out/noop_aligned.so").unwrap();
+        assert_matches!(Symbolizer::new(&elf), Err(SymbolizeError::MissingDebugInfo));
+        assert_matches!(Symbolizer::new(b"not an elf"), Err(SymbolizeError::Elf(_)));
+    }
+
+    #[test]
+    fn test_backtrace_frame_pc() {
+        assert_eq!(
+            backtrace_frame_pc("Program backtrace: #0 1234 entrypoint+12"),
+            Some(1234)
+        );
+        assert_eq!(backtrace_frame_pc("Program backtrace: #3 56 ?"), Some(56));
+        assert_eq!(backtrace_frame_pc("Program backtrace: #x 56 ?"), None);
+        assert_eq!(backtrace_frame_pc("Program log: #0 1234 ?"), None);
+    }
+}
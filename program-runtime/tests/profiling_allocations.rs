@@ -0,0 +1,138 @@
+//! Checks that instructions which are not profiled do not allocate for profiling
+//!
+//! This lives in its own test binary as it replaces the global allocator to count allocations.
+
+use {
+    solana_account::AccountSharedData,
+    solana_program_runtime::{
+        profiling::{InstructionProfile, ProfileReturnData},
+        with_mock_invoke_context,
+    },
+    solana_transaction_context::InstructionAccount,
+    std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+    },
+};
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts the allocations of each thread, so that tests running concurrently do not interfere
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get().saturating_add(1)));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The result of `f` and the number of allocations it made on the current thread
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    let allocations = ALLOCATIONS.with(Cell::get).saturating_sub(before);
+    (result, allocations)
+}
+
+#[test]
+fn test_profiling_off_does_not_snapshot_lamports() {
+    let program_id = solana_pubkey::new_rand();
+    let mut transaction_accounts = (0..3)
+        .map(|_| {
+            (
+                solana_pubkey::new_rand(),
+                AccountSharedData::new(42, 1, &program_id),
+            )
+        })
+        .collect::<Vec<_>>();
+    transaction_accounts.push((program_id, AccountSharedData::default()));
+    let instruction_accounts = (0..3)
+        .map(|index_in_transaction| InstructionAccount::new(index_in_transaction, false, true))
+        .collect::<Vec<_>>();
+    with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+    invoke_context
+        .transaction_context
+        .configure_next_instruction_for_tests(3, instruction_accounts, &[])
+        .unwrap();
+    invoke_context.push().unwrap();
+
+    // Nothing is allocated for instructions which are not profiled
+    invoke_context.enable_profiling = false;
+    let (lamports, allocations) =
+        count_allocations(|| invoke_context.profiled_instruction_account_lamports());
+    assert_eq!(lamports, None);
+    assert_eq!(allocations, 0);
+
+    invoke_context.enable_profiling = true;
+    let (lamports, allocations) =
+        count_allocations(|| invoke_context.profiled_instruction_account_lamports());
+    assert_eq!(lamports.map(|lamports| lamports.len()), Some(3));
+    assert!(allocations > 0);
+}
+
+#[test]
+fn test_profiling_off_does_not_capture_return_data() {
+    let program_id = solana_pubkey::new_rand();
+    let transaction_accounts = vec![(program_id, AccountSharedData::default())];
+    with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+    invoke_context
+        .transaction_context
+        .configure_next_instruction_for_tests(0, vec![], &[])
+        .unwrap();
+    invoke_context.push().unwrap();
+    invoke_context
+        .transaction_context
+        .set_return_data(program_id, vec![1, 2, 3])
+        .unwrap();
+
+    // Nothing is allocated for return data of instructions which are not profiled
+    invoke_context.enable_profiling = false;
+    let (return_data, allocations) = count_allocations(|| invoke_context.profile_return_data());
+    assert_eq!(return_data, None);
+    assert_eq!(allocations, 0);
+
+    invoke_context.enable_profiling = true;
+    let (return_data, allocations) = count_allocations(|| invoke_context.profile_return_data());
+    assert_eq!(
+        return_data,
+        Some(ProfileReturnData {
+            program_id,
+            data: vec![1, 2, 3],
+            len: 3,
+        }),
+    );
+    assert!(allocations > 0);
+}
+
+#[test]
+fn test_profiling_off_does_not_record_builtin_profiles() {
+    let program_id = solana_pubkey::new_rand();
+    let transaction_accounts = vec![(program_id, AccountSharedData::default())];
+    with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+
+    // Nothing is allocated for builtins which are not profiled
+    invoke_context.enable_profiling = false;
+    let ((), allocations) =
+        count_allocations(|| invoke_context.record_builtin_profile(program_id, 42));
+    assert_eq!(allocations, 0);
+    assert!(invoke_context.take_profiling_results().is_empty());
+
+    invoke_context.enable_profiling = true;
+    let ((), allocations) =
+        count_allocations(|| invoke_context.record_builtin_profile(program_id, 42));
+    assert!(allocations > 0);
+    assert_eq!(
+        invoke_context.take_profiling_results(),
+        vec![InstructionProfile::builtin(program_id, 0, 42)],
+    );
+}
@@ -846,6 +846,7 @@ impl ProgramTest {
                     )
                 }),
                 transaction_account_lock_limit: self.transaction_account_lock_limit,
+                profile_all_transactions: true,
                 ..RuntimeConfig::default()
             }),
             Vec::default(),
@@ -19,6 +19,7 @@ use {
             ProgramCacheForTxBatch, ProgramRuntimeEnvironment, DELAY_VISIBILITY_SLOT_OFFSET,
         },
         mem_pool::VmMemoryPool,
+        profiling::{heap_metrics, InstructionDataTransfer, InstructionTimings, ProfileVerbosity},
         serialization, stable_log,
         sysvar_cache::get_sysvar_with_account_check,
     },
@@ -274,6 +275,7 @@ fn create_vm<'a, 'b>(
         allocator: BpfAllocator::new(heap_size as u64),
         accounts_metadata,
         trace_log: Vec::new(),
+        profiling: invoke_context.new_profiling_state()?,
     })?;
     Ok(EbpfVm::new(
         program.get_loader().clone(),
@@ -1493,6 +1495,7 @@ fn execute<'a, 'b: 'a>(
         })
         .collect::<Vec<_>>();
 
+    let execute_us_before = invoke_context.timings.execute_us.0;
     let mut create_vm_time = Measure::start("create_vm");
     let execution_result = {
         let compute_meter_prev = invoke_context.get_remaining();
@@ -1521,18 +1524,72 @@ fn execute<'a, 'b: 'a>(
             invoke_context.timings.execute_us += execute_time.as_us();
         }
 
-        ic_logger_msg!(
-            log_collector,
-            "Program {} consumed {} of {} compute units",
+        stable_log::program_consumed(
+            &log_collector,
             &program_id,
             compute_units_consumed,
-            compute_meter_prev
+            compute_meter_prev,
         );
         let (_returned_from_program_id, return_data) =
             invoke_context.transaction_context.get_return_data();
         if !return_data.is_empty() {
             stable_log::program_return(&log_collector, &program_id, return_data);
         }
+        invoke_context.end_implicit_profiling_section()?;
+        let remaining = invoke_context.get_remaining();
+        let profile_verbosity = invoke_context.profile_verbosity;
+        let profile_summary_sections = invoke_context.profile_summary_sections;
+        if let Some(profiling) = &mut invoke_context.get_syscall_context_mut()?.profiling {
+            // Keep what was recorded until the failure, it is most needed then
+            match &result {
+                ProgramResult::Ok(status) if *status != SUCCESS => {
+                    profiling.fail(InstructionError::from(*status).to_string(), remaining);
+                }
+                ProgramResult::Err(error) => profiling.fail(error.to_string(), remaining),
+                ProgramResult::Ok(_) => {}
+            }
+            profiling.post_process();
+            match profile_verbosity {
+                ProfileVerbosity::Off => {}
+                ProfileVerbosity::Summary => {
+                    let summary =
+                        profiling.summary(profile_summary_sections, compute_units_consumed);
+                    stable_log::program_profile_summary(&log_collector, &summary);
+                }
+                ProfileVerbosity::Tree => {
+                    let tree = profiling.tree(compute_units_consumed);
+                    stable_log::program_profile_tree(&log_collector, &tree);
+                }
+                ProfileVerbosity::Full => {
+                    for entry in profiling.entries() {
+                        stable_log::program_profile(&log_collector, entry);
+                    }
+                }
+                ProfileVerbosity::FullJson => {
+                    let heap = heap_metrics(profiling.entries());
+                    for (entry, heap) in profiling.entries().iter().zip(heap) {
+                        stable_log::program_profile_json(&log_collector, entry, heap);
+                    }
+                }
+            }
+            if profile_verbosity != ProfileVerbosity::Off {
+                for (id, value) in profiling.counters() {
+                    stable_log::program_profile_counter(&log_collector, *id, *value);
+                }
+                for (name, bytes) in profiling.heap_tags() {
+                    stable_log::program_profile_heap_tag(&log_collector, name, *bytes);
+                }
+                if profiling.dropped_entries() > 0 {
+                    stable_log::program_profile_dropped(
+                        &log_collector,
+                        profiling.dropped_entries(),
+                    );
+                }
+                if let Some(reason) = profiling.failure().filter(|_| !profiling.is_empty()) {
+                    stable_log::program_profile_failure(&log_collector, reason);
+                }
+            }
+        }
         match result {
             ProgramResult::Ok(status) if status != SUCCESS => {
                 let error: InstructionError = status.into();
@@ -1631,7 +1688,7 @@ fn execute<'a, 'b: 'a>(
         invoke_context: &mut InvokeContext,
         parameter_bytes: &[u8],
         stricter_abi_and_runtime_constraints: bool,
-    ) -> Result<(), InstructionError> {
+    ) -> Result<u64, InstructionError> {
         serialization::deserialize_parameters(
             &invoke_context
                 .transaction_context
@@ -1658,8 +1715,34 @@ fn execute<'a, 'b: 'a>(
     invoke_context.timings.serialize_us += serialize_time.as_us();
     invoke_context.timings.create_vm_us += create_vm_time.as_us();
     invoke_context.timings.deserialize_us += deserialize_time.as_us();
+    let timings = InstructionTimings {
+        serialize_us: serialize_time.as_us(),
+        create_vm_us: create_vm_time.as_us(),
+        execute_us: invoke_context
+            .timings
+            .execute_us
+            .0
+            .saturating_sub(execute_us_before),
+        deserialize_us: deserialize_time.as_us(),
+    };
+    let data_transfer = InstructionDataTransfer {
+        serialized_bytes: parameter_bytes.len() as u64,
+        written_back_bytes: execute_or_deserialize_result
+            .as_ref()
+            .ok()
+            .copied()
+            .unwrap_or_default(),
+    };
+    if let Some(profiling) = invoke_context
+        .get_syscall_context_mut()
+        .ok()
+        .and_then(|syscall_context| syscall_context.profiling.as_mut())
+    {
+        profiling.set_timings(timings);
+        profiling.set_data_transfer(data_transfer);
+    }
 
-    execute_or_deserialize_result
+    execute_or_deserialize_result.map(|_written_back_bytes| ())
 }
 
 #[cfg_attr(feature = "svm-internal", qualifiers(pub))]
@@ -1750,7 +1833,8 @@ mod tests {
         solana_epoch_schedule::EpochSchedule,
         solana_instruction::{error::InstructionError, AccountMeta},
         solana_program_runtime::{
-            invoke_context::mock_process_instruction, with_mock_invoke_context,
+            invoke_context::mock_process_instruction, profiling::PROFILE_LOG_PREFIX,
+            with_mock_invoke_context,
         },
         solana_pubkey::Pubkey,
         solana_rent::Rent,
@@ -1975,6 +2059,117 @@ mod tests {
         );
     }
 
+    /// Execute an instruction with profiling disabled and enabled, and check that profiling does
+    /// not change the result, the compute units consumed, the logs other than the profile lines
+    /// nor the resulting accounts
+    ///
+    /// Programs which branch on `sol_profile_enabled` or `sol_profile_section_cu` are exempt, as
+    /// those syscalls deliberately expose whether profiling is enabled.
+    ///
+    /// Returns the resulting accounts and the profile lines logged with profiling enabled.
+    fn assert_profiling_is_transparent(
+        loader_id: &Pubkey,
+        transaction_accounts: Vec<(Pubkey, AccountSharedData)>,
+        instruction_accounts: Vec<AccountMeta>,
+        expected_result: Result<(), InstructionError>,
+    ) -> (Vec<AccountSharedData>, Vec<String>) {
+        let execute = |enable_profiling: bool, implicit_profiling_sections: bool| {
+            let mut remaining = 0;
+            let mut logs = Vec::new();
+            let accounts = mock_process_instruction(
+                loader_id,
+                Some(0),
+                &[],
+                transaction_accounts.clone(),
+                instruction_accounts.clone(),
+                expected_result.clone(),
+                Entrypoint::vm,
+                |invoke_context| {
+                    invoke_context.enable_profiling = enable_profiling;
+                    invoke_context.implicit_profiling_sections = implicit_profiling_sections;
+                    test_utils::load_all_invoked_programs(invoke_context);
+                },
+                |invoke_context| {
+                    remaining = invoke_context.get_remaining();
+                    logs = invoke_context
+                        .get_log_collector()
+                        .unwrap()
+                        .borrow()
+                        .get_recorded_content();
+                },
+            );
+            let (profile, logs) = logs
+                .into_iter()
+                .partition::<Vec<_>, _>(|line| line.starts_with(PROFILE_LOG_PREFIX));
+            ((remaining, logs, accounts), profile)
+        };
+
+        let (unprofiled, profile) = execute(false, false);
+        assert!(profile.is_empty());
+        let (profiled, profile) = execute(true, false);
+        assert_eq!(profiled, unprofiled);
+        let (implicitly_profiled, _profile) = execute(true, true);
+        assert_eq!(implicitly_profiled, unprofiled);
+        let (_remaining, _logs, accounts) = unprofiled;
+        (accounts, profile)
+    }
+
+    #[test]
+    fn test_profiling_does_not_change_execution() {
+        let parameter_id = Pubkey::new_unique();
+        let parameter_meta = AccountMeta {
+            pubkey: parameter_id,
+            is_signer: false,
+            is_writable: true,
+        };
+        for (loader_id, path) in [
+            (bpf_loader::id(), "test_elfs/out/sbpfv3_return_ok.so"),
+            (bpf_loader::id(), "test_elfs/out/noop_aligned.so"),
+            (
+                bpf_loader_deprecated::id(),
+                "test_elfs/out/noop_unaligned.so",
+            ),
+        ] {
+            let program_account = load_program_account_from_elf(&loader_id, path);
+            let parameter_account = AccountSharedData::new(1, 8, &loader_id);
+            assert_profiling_is_transparent(
+                &loader_id,
+                vec![
+                    (Pubkey::new_unique(), program_account),
+                    (parameter_id, parameter_account),
+                ],
+                vec![parameter_meta.clone()],
+                Ok(()),
+            );
+        }
+    }
+
+    #[test]
+    fn test_profiling_does_not_change_execution_with_cpi() {
+        let loader_id = bpf_loader::id();
+        let program_id = Pubkey::new_unique();
+        let program_account =
+            load_program_account_from_elf(&loader_id, "test_elfs/out/profiling_cpi.so");
+        let parameter_id = Pubkey::new_unique();
+        let parameter_account = AccountSharedData::new(1, 1, &program_id);
+
+        // The program opens sections, records marks and counters and invokes itself
+        let (accounts, profile) = assert_profiling_is_transparent(
+            &loader_id,
+            vec![
+                (program_id, program_account),
+                (parameter_id, parameter_account),
+            ],
+            vec![
+                AccountMeta::new(parameter_id, false),
+                AccountMeta::new_readonly(program_id, false),
+            ],
+            Ok(()),
+        );
+        assert_eq!(accounts.get(1).unwrap().data(), &[2]);
+        assert!(profile.iter().any(|line| line.ends_with(" invoke")));
+    }
+
     #[test]
     fn test_bpf_loader_upgradeable_initialize_buffer() {
         let loader_id = bpf_loader_upgradeable::id();
@@ -0,0 +1,62 @@
+/**
+ * @brief Example C based SBF program that opens profiling sections, records
+ * marks and counters and invokes itself, so that executing it with and without
+ * profiling can be compared
+ */
+#include <solana_sdk.h>
+
+uint64_t sol_log_compute_units_start(const char *name, uint64_t len, uint64_t heap);
+uint64_t sol_log_compute_units_end(const char *name, uint64_t len, uint64_t heap);
+uint64_t sol_profile_mark(const char *name, uint64_t len, uint64_t heap);
+uint64_t sol_profile_counter_add(uint64_t id, uint64_t delta);
+
+/** Let the runtime sample the heap usage, see `NO_HEAP_SAMPLE` */
+#define NO_HEAP_SAMPLE UINT64_MAX
+#define NAME(name) name, sizeof(name) - 1
+
+static const int ARGUMENT_INDEX = 0;
+static const int PROGRAM_INDEX = 1;
+
+extern uint64_t entrypoint(const uint8_t *input) {
+  SolAccountInfo ka[2];
+  SolParameters params = (SolParameters) { .ka = ka };
+
+  if (!sol_deserialize(input, &params, SOL_ARRAY_SIZE(ka))) {
+    return ERROR_INVALID_ARGUMENT;
+  }
+  if (params.ka_num < 1 || ka[ARGUMENT_INDEX].data_len == 0) {
+    return ERROR_NOT_ENOUGH_ACCOUNT_KEYS;
+  }
+
+  // The return values of the profiling syscalls are ignored, as they differ
+  // when profiling is disabled
+  sol_log_compute_units_start(NAME("entrypoint"), NO_HEAP_SAMPLE);
+  sol_profile_counter_add(0, 1);
+
+  if (params.data_len == 0) {
+    // Invoke this program, which is passed after the argument
+    if (params.ka_num != SOL_ARRAY_SIZE(ka)) {
+      return ERROR_NOT_ENOUGH_ACCOUNT_KEYS;
+    }
+    sol_log("invoking self");
+    sol_profile_mark(NAME("invoke"), NO_HEAP_SAMPLE);
+    sol_log_compute_units_start(NAME("invoke"), NO_HEAP_SAMPLE);
+    SolAccountMeta arguments[] = {{ka[ARGUMENT_INDEX].key, true, false}};
+    uint8_t data[] = {1};
+    const SolInstruction instruction = {
+      ka[PROGRAM_INDEX].key, arguments, SOL_ARRAY_SIZE(arguments), data, SOL_ARRAY_SIZE(data)
+    };
+    uint64_t result = sol_invoke(&instruction, ka, SOL_ARRAY_SIZE(ka));
+    sol_log_compute_units_end(NAME("invoke"), NO_HEAP_SAMPLE);
+    if (result != SUCCESS) {
+      return result;
+    }
+  } else {
+    sol_log("invoked");
+    sol_profile_counter_add(1, params.data[0]);
+  }
+
+  ka[ARGUMENT_INDEX].data[0] += 1;
+  sol_log_compute_units_end(NAME("entrypoint"), NO_HEAP_SAMPLE);
+  return SUCCESS;
+}
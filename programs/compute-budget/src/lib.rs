@@ -1,8 +1,17 @@
-use solana_program_runtime::declare_process_instruction;
+use solana_program_runtime::{
+    declare_process_instruction, execution_budget::ENABLE_PROFILING_INSTRUCTION_DATA,
+};
 
 pub const DEFAULT_COMPUTE_UNITS: u64 = 150;
 
-declare_process_instruction!(Entrypoint, DEFAULT_COMPUTE_UNITS, |_invoke_context| {
-    // Do nothing, compute budget instructions handled by the runtime
+declare_process_instruction!(Entrypoint, DEFAULT_COMPUTE_UNITS, |invoke_context| {
+    // Compute budget instructions are handled by the runtime, except for enabling profiling which
+    // applies to the instructions executed after this one
+    let instruction_context = invoke_context
+        .transaction_context
+        .get_current_instruction_context()?;
+    if instruction_context.get_instruction_data() == ENABLE_PROFILING_INSTRUCTION_DATA {
+        invoke_context.enable_profiling = true;
+    }
     Ok(())
 });
@@ -181,13 +181,13 @@ use {
     solana_rpc_client_types::{
         config::{
             RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
-            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
-            RpcTransactionLogsFilter,
+            RpcProfileSubscribeConfig, RpcProfileSubscribeFilter, RpcProgramAccountsConfig,
+            RpcSignatureSubscribeConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
         },
         error_object::RpcErrorObject,
         response::{
             Response as RpcResponse, RpcBlockUpdate, RpcKeyedAccount, RpcLogsResponse,
-            RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
+            RpcProfileResponse, RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
         },
     },
     solana_signature::Signature,
@@ -377,6 +377,22 @@ impl PubsubClient {
         self.subscribe("logs", json!([filter, config])).await
     }
 
+    /// Subscribe to program profile events.
+    ///
+    /// Receives messages of type [`RpcProfileResponse`] when a transaction in which a program
+    /// reported a profile is committed.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the `profileSubscribe` RPC method.
+    pub async fn profile_subscribe(
+        &self,
+        filter: RpcProfileSubscribeFilter,
+        config: Option<RpcProfileSubscribeConfig>,
+    ) -> SubscribeResult<'_, RpcResponse<RpcProfileResponse>> {
+        self.subscribe("profile", json!([filter, config])).await
+    }
+
     /// Subscribe to program account events.
     ///
     /// Receives messages of type [`RpcKeyedAccount`] when an account owned
@@ -5,11 +5,13 @@ pub use solana_rpc_client_types::response::{
     RpcBlockProductionRange, RpcBlockUpdate, RpcBlockUpdateError, RpcBlockhash,
     RpcBlockhashFeeCalculator, RpcConfirmedTransactionStatusWithSignature, RpcContactInfo,
     RpcFeeCalculator, RpcFeeRateGovernor, RpcIdentity, RpcInflationGovernor, RpcInflationRate,
-    RpcInflationReward, RpcKeyedAccount, RpcLeaderSchedule, RpcLogsResponse, RpcPerfSample,
-    RpcPrioritizationFee, RpcResponseContext, RpcSignatureConfirmation, RpcSignatureResult,
-    RpcSimulateTransactionResult, RpcSnapshotSlotInfo, RpcStorageTurn, RpcSupply,
-    RpcTokenAccountBalance, RpcVersionInfo, RpcVote, RpcVoteAccountInfo, RpcVoteAccountStatus,
-    SlotInfo, SlotTransactionStats, SlotUpdate, StakeActivationState,
+    RpcInflationReward, RpcInstructionProfile, RpcKeyedAccount, RpcLeaderSchedule, RpcLogsResponse,
+    RpcPerfSample, RpcPrioritizationFee, RpcProfileCounter, RpcProfileEntry, RpcProfileHeapTag,
+    RpcResponseContext, RpcSignatureConfirmation, RpcSignatureResult, RpcSimulateTransactionResult,
+    RpcSnapshotSlotInfo, RpcStorageTurn, RpcSupply, RpcTokenAccountBalance, RpcTransactionProfile,
+    RpcVersionInfo, RpcVote, RpcVoteAccountInfo, RpcVoteAccountStatus, SlotInfo,
+    SlotTransactionStats, SlotUpdate, StakeActivationState, MIN_RPC_TRANSACTION_PROFILE_VERSION,
+    RPC_TRANSACTION_PROFILE_VERSION,
 };
 
 pub type RpcResult<T> = client_error::Result<Response<T>>;
@@ -1,9 +1,10 @@
 use {
     crate::filter::RpcFilterType,
-    solana_account_decoder_client_types::{UiAccountEncoding, UiDataSliceConfig},
+    solana_account_decoder_client_types::{UiAccount, UiAccountEncoding, UiDataSliceConfig},
     solana_clock::{Epoch, Slot},
     solana_commitment_config::{CommitmentConfig, CommitmentLevel},
     solana_transaction_status_client_types::{TransactionDetails, UiTransactionEncoding},
+    std::collections::HashMap,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -30,6 +31,19 @@ pub struct RpcSimulateTransactionAccountsConfig {
     pub addresses: Vec<String>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateTransactionAccountDiffsConfig {
+    pub encoding: Option<UiAccountEncoding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcLogProgramFilter {
+    Allow(Vec<String>), // base58-encoded list of program ids
+    Deny(Vec<String>),  // base58-encoded list of program ids
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcSimulateTransactionConfig {
@@ -44,6 +58,56 @@ pub struct RpcSimulateTransactionConfig {
     pub min_context_slot: Option<Slot>,
     #[serde(default)]
     pub inner_instructions: bool,
+    /// Drop program messages logged with `sol_log_level` below this level: one of `trace`,
+    /// `debug`, `info`, `warn` or `error`
+    pub min_log_level: Option<String>,
+    /// Profile this simulation even if the validator only profiles transactions which request it
+    #[serde(default)]
+    pub enable_profiling: bool,
+    /// Maximum number of bytes logged before the logs are truncated, instead of the limit
+    /// configured for the validator
+    pub log_messages_bytes_limit: Option<usize>,
+    /// Only record the messages logged by the selected programs, instead of the filter configured
+    /// for the validator
+    pub log_program_filter: Option<RpcLogProgramFilter>,
+    /// How much of the profile of each instruction is logged, instead of the verbosity
+    /// configured for the validator: one of `off`, `summary`, `tree`, `full` or `full-json`
+    pub profile_verbosity: Option<String>,
+    /// Profile this simulation and return its profile report in the `profile` field of the
+    /// [`RpcSimulateTransactionResult`](crate::response::RpcSimulateTransactionResult)
+    #[serde(default)]
+    pub include_profile: bool,
+    /// Accounts to simulate against instead of their stored state, keyed by base-58 encoded
+    /// address. Account data must be binary encoded; the overrides are not stored.
+    pub account_overrides: Option<HashMap<String, UiAccount>>,
+    /// Return the state before and after the simulation of every writable account which it
+    /// changed in the `accountDiffs` field of the
+    /// [`RpcSimulateTransactionResult`](crate::response::RpcSimulateTransactionResult)
+    pub account_diffs: Option<RpcSimulateTransactionAccountDiffsConfig>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateBundleConfig {
+    #[serde(default)]
+    pub sig_verify: bool,
+    #[serde(default)]
+    pub replace_recent_blockhash: bool,
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub encoding: Option<UiTransactionEncoding>,
+    pub min_context_slot: Option<Slot>,
+    #[serde(default)]
+    pub inner_instructions: bool,
+    /// Drop program messages logged with `sol_log_level` below this level: one of `trace`,
+    /// `debug`, `info`, `warn` or `error`
+    pub min_log_level: Option<String>,
+    /// Profile every transaction of the bundle and return their profile reports
+    #[serde(default)]
+    pub include_profile: bool,
+    /// Accounts to simulate the first transaction against instead of their stored state, see
+    /// [`RpcSimulateTransactionConfig::account_overrides`]
+    pub account_overrides: Option<HashMap<String, UiAccount>>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -181,6 +245,23 @@ pub struct RpcTransactionLogsConfig {
     pub commitment: Option<CommitmentConfig>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcProfileSubscribeFilter {
+    All,
+    Program(String), // base58-encoded program id
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProfileSubscribeConfig {
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    /// Highest profile schema version understood by the subscriber, see
+    /// [`RpcTransactionProfileConfig::max_supported_profile_version`]
+    pub max_supported_profile_version: Option<u8>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum RpcTokenAccountsFilter {
@@ -306,6 +387,10 @@ pub struct RpcTransactionConfig {
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
     pub max_supported_transaction_version: Option<u8>,
+    /// Return the stored profile report of the transaction in the `profile` field of its meta,
+    /// only available from nodes which persist profiles
+    #[serde(default)]
+    pub include_profile: bool,
 }
 
 impl EncodingConfig for RpcTransactionConfig {
@@ -317,6 +402,27 @@ impl EncodingConfig for RpcTransactionConfig {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionProfileConfig {
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    /// Highest profile schema version understood by the caller, the node responds with the
+    /// highest version supported by both. Defaults to the node's version.
+    pub max_supported_profile_version: Option<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramComputeStatsConfig {
+    /// Only report these base58-encoded programs, rather than the busiest ones
+    pub program_ids: Option<Vec<String>>,
+    /// Length of the trailing window to aggregate, capped to the history retained by the node
+    pub window_secs: Option<u64>,
+    /// Maximum number of programs to report
+    pub limit: Option<usize>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum RpcBlocksConfigWrapper {
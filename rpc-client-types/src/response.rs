@@ -10,7 +10,12 @@ use {
         UiInnerInstructions, UiLoadedAddresses, UiTransactionError, UiTransactionReturnData,
         UiTransactionTokenBalance,
     },
-    std::{collections::HashMap, fmt, net::SocketAddr, str::FromStr},
+    std::{
+        collections::{BTreeMap, HashMap},
+        fmt,
+        net::SocketAddr,
+        str::FromStr,
+    },
     thiserror::Error,
 };
 
@@ -412,6 +417,48 @@ pub struct RpcSimulateTransactionResult {
     pub pre_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub post_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub loaded_addresses: Option<UiLoadedAddresses>,
+    /// Profile report of the simulated transaction, only set if `includeProfile` was requested
+    ///
+    /// The report follows the versioned JSON schema of the program runtime's `ProfileReport`,
+    /// which carries the entries, counters and timings of every profiled instruction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<serde_json::Value>,
+    /// Every instruction which was executed, including the top-level ones, in execution order,
+    /// only set if `innerInstructions` was requested
+    ///
+    /// Unlike `innerInstructions`, the trace carries the compute units consumed by each
+    /// invocation and which invocations failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instruction_trace: Option<Vec<RpcTracedInstruction>>,
+    /// State before and after the simulation of every writable account which it changed, only
+    /// set if `accountDiffs` was requested, and empty if the simulation failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_diffs: Option<Vec<RpcAccountDiff>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountDiff {
+    pub pubkey: String,
+    /// State of the account which the transaction was simulated against, `None` if it did not
+    /// exist
+    pub pre: Option<UiAccount>,
+    /// State of the account after the simulation, `None` if it was closed
+    pub post: Option<UiAccount>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTracedInstruction {
+    /// Index of the top-level instruction which is or invoked this instruction
+    pub instruction_index: usize,
+    /// Stack height of the instruction, top-level instructions are at height 1
+    pub stack_height: usize,
+    pub program_id: String,
+    /// Compute units consumed by the instruction, including the instructions it invoked
+    pub compute_units_consumed: u64,
+    /// Whether the instruction returned an error
+    pub failed: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -535,11 +582,165 @@ pub struct RpcPrioritizationFee {
     pub prioritization_fee: u64,
 }
 
+/// Highest `getTransactionProfile` schema version understood by this crate
+///
+/// Version 2 added [`RpcProfileEntry::return_data_bytes`], version 3 added
+/// [`RpcProfileEntry::bulk_closed`], version 4 added [`RpcTransactionProfile::budget`] and
+/// version 5 added [`RpcInstructionProfile::failure`]. Version 6 has no new fields, it follows
+/// the profiles of programs logging their entries as JSON. Version 7 added
+/// [`RpcProfileBudget::requested_compute_units`].
+pub const RPC_TRANSACTION_PROFILE_VERSION: u8 = 7;
+
+/// Oldest `getTransactionProfile` schema version a node can respond with
+pub const MIN_RPC_TRANSACTION_PROFILE_VERSION: u8 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionProfile {
+    /// Negotiated schema version, fields introduced by later versions are omitted
+    pub version: u8,
+    pub slot: Slot,
+    /// Profiled instructions in the order they completed
+    pub instructions: Vec<RpcInstructionProfile>,
+    /// Compute budget the transaction executed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<RpcProfileBudget>,
+}
+
+/// Notification of the `profileSubscribe` PubSub method
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProfileResponse {
+    pub signature: String, // Signature as base58 string
+    pub err: Option<UiTransactionError>,
+    pub profile: RpcTransactionProfile,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcInstructionProfile {
+    pub program_id: String,
+    pub stack_height: usize,
+    pub entries: Vec<RpcProfileEntry>,
+    pub counters: Vec<RpcProfileCounter>,
+    pub dropped_entries: usize,
+    /// Heap bytes attributed to each allocation tag, ordered by name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub heap_tags: Vec<RpcProfileHeapTag>,
+    /// Why the instruction failed, its entries then stop at the failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProfileEntry {
+    /// Either `section` or `mark`
+    pub kind: String,
+    pub name: String,
+    pub depth: usize,
+    pub start_cu: u64,
+    pub consumed_cu: u64,
+    pub heap_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bulk_closed: Option<bool>,
+    /// Key/value metadata attached to the section by the program
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProfileCounter {
+    pub id: u64,
+    pub value: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProfileHeapTag {
+    pub name: String,
+    pub bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProfileBudget {
+    pub compute_unit_limit: u64,
+    /// Whether the limit was set by a compute budget instruction rather than derived by default
+    pub requested_compute_unit_limit: bool,
+    /// Limit set by the compute budget instruction, before it is capped to the maximum
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_compute_units: Option<u32>,
+    pub consumed_cu: u64,
+    pub heap_bytes: u32,
+    pub prioritization_fee: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramComputeStats {
+    /// Length of the aggregated trailing window
+    pub window_secs: u64,
+    /// Programs ordered by decreasing total compute units
+    pub programs: Vec<RpcProgramComputeStat>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramComputeStat {
+    pub program_id: String,
+    pub invocations: u64,
+    /// Compute units consumed by the program's invocations, including the programs it invoked
+    pub total_cu: u64,
+    pub mean_cu: u64,
+    pub min_cu: u64,
+    pub max_cu: u64,
+    /// Profiling sections reported by the program, ordered by decreasing total compute units
+    pub sections: Vec<RpcSectionComputeStat>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSectionComputeStat {
+    pub name: String,
+    pub occurrences: u64,
+    pub total_cu: u64,
+    pub mean_cu: u64,
+    pub min_cu: u64,
+    pub max_cu: u64,
+}
+
 #[cfg(test)]
 pub mod tests {
 
     use {super::*, serde_json::json};
 
+    // Make sure that entries of older profile schema versions, which lack the fields added
+    // later, can be read.
+    #[test]
+    fn rpc_profile_entry_deserializes_previous_versions() {
+        let entry: RpcProfileEntry = serde_json::from_value(json!({
+            "kind": "section",
+            "name": "deserialize",
+            "depth": 0,
+            "startCu": 10,
+            "consumedCu": 200,
+            "heapBytes": null,
+        }))
+        .unwrap();
+        assert_eq!(entry.return_data_bytes, None);
+        assert_eq!(entry.bulk_closed, None);
+        assert!(entry.attributes.is_empty());
+
+        let actual = serde_json::to_value(entry).unwrap();
+        assert_eq!(actual.get("returnDataBytes"), None);
+        assert_eq!(actual.get("bulkClosed"), None);
+        assert_eq!(actual.get("attributes"), None);
+    }
+
     // Make sure that `RpcPerfSample` can read previous version JSON, one without the
     // `num_non_vote_transactions` field.
     #[test]
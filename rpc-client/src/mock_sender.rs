@@ -19,10 +19,11 @@ use {
         response::{
             Response, RpcAccountBalance, RpcBlockProduction, RpcBlockProductionRange, RpcBlockhash,
             RpcConfirmedTransactionStatusWithSignature, RpcContactInfo, RpcIdentity,
-            RpcInflationGovernor, RpcInflationRate, RpcInflationReward, RpcKeyedAccount,
-            RpcPerfSample, RpcPrioritizationFee, RpcResponseContext, RpcSimulateTransactionResult,
-            RpcSnapshotSlotInfo, RpcSupply, RpcVersionInfo, RpcVoteAccountInfo,
-            RpcVoteAccountStatus,
+            RpcInflationGovernor, RpcInflationRate, RpcInflationReward, RpcInstructionProfile,
+            RpcKeyedAccount, RpcPerfSample, RpcPrioritizationFee, RpcProfileBudget,
+            RpcProfileCounter, RpcProfileEntry, RpcResponseContext, RpcSimulateTransactionResult,
+            RpcSnapshotSlotInfo, RpcSupply, RpcTransactionProfile, RpcVersionInfo,
+            RpcVoteAccountInfo, RpcVoteAccountStatus, RPC_TRANSACTION_PROFILE_VERSION,
         },
     },
     solana_signature::Signature,
@@ -37,7 +38,7 @@ use {
     },
     solana_version::Version,
     std::{
-        collections::{HashMap, VecDeque},
+        collections::{BTreeMap, HashMap, VecDeque},
         net::SocketAddr,
         str::FromStr,
         sync::RwLock,
@@ -247,10 +248,43 @@ impl RpcSender for MockSender {
                             return_data: OptionSerializer::Skip,
                             compute_units_consumed: OptionSerializer::Skip,
                             cost_units: OptionSerializer::Skip,
+                            profile: OptionSerializer::Skip,
+                            instruction_compute_units_consumed: OptionSerializer::Skip,
                         }),
                 },
                 block_time: Some(1628633791),
             })?,
+            "getTransactionProfile" => serde_json::to_value(RpcTransactionProfile {
+                version: RPC_TRANSACTION_PROFILE_VERSION,
+                slot: 2,
+                instructions: vec![RpcInstructionProfile {
+                    program_id: PUBKEY.to_string(),
+                    stack_height: TRANSACTION_LEVEL_STACK_HEIGHT,
+                    entries: vec![RpcProfileEntry {
+                        kind: "section".to_string(),
+                        name: "transfer".to_string(),
+                        depth: 0,
+                        start_cu: 0,
+                        consumed_cu: 150,
+                        heap_bytes: None,
+                        return_data_bytes: Some(0),
+                        bulk_closed: Some(false),
+                        attributes: BTreeMap::new(),
+                    }],
+                    counters: vec![RpcProfileCounter { id: 0, value: 1 }],
+                    dropped_entries: 0,
+                    heap_tags: Vec::new(),
+                    failure: None,
+                }],
+                budget: Some(RpcProfileBudget {
+                    compute_unit_limit: 200_000,
+                    requested_compute_unit_limit: false,
+                    requested_compute_units: None,
+                    consumed_cu: 150,
+                    heap_bytes: 32 * 1024,
+                    prioritization_fee: 0,
+                }),
+            })?,
             "getTransactionCount" => json![1234],
             "getSlot" => json![0],
             "getMaxShredInsertSlot" => json![0],
@@ -362,6 +396,9 @@ impl RpcSender for MockSender {
                     pre_token_balances: None,
                     post_token_balances: None,
                     loaded_addresses: None,
+                    profile: None,
+                    instruction_trace: None,
+                    account_diffs: None,
                 }
             })?,
             "getMinimumBalanceForRentExemption" => json![20],
@@ -2884,6 +2884,7 @@ impl RpcClient {
     ///     encoding: Some(UiTransactionEncoding::Json),
     ///     commitment: Some(CommitmentConfig::confirmed()),
     ///     max_supported_transaction_version: Some(0),
+    ///     include_profile: false,
     /// };
     /// let transaction = rpc_client.get_transaction_with_config(
     ///     &signature,
@@ -2905,6 +2906,65 @@ impl RpcClient {
         .await
     }
 
+    /// Returns the profile reported by the programs of a confirmed transaction.
+    ///
+    /// The request advertises [`RPC_TRANSACTION_PROFILE_VERSION`] as the highest profile schema
+    /// version understood by this client. Nodes running an older version respond with their own
+    /// schema version, in which case the fields introduced later are `None`.
+    ///
+    /// This method returns an error if the given [commitment level][cl] is below
+    /// [`Confirmed`].
+    ///
+    /// [cl]: https://solana.com/docs/rpc#configuring-state-commitment
+    /// [`Confirmed`]: solana_commitment_config::CommitmentLevel::Confirmed
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the `getTransactionProfile` RPC method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_rpc_client_api::client_error::Error;
+    /// # use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+    /// # use solana_commitment_config::CommitmentConfig;
+    /// # use solana_keypair::Keypair;
+    /// # use solana_system_transaction as system_transaction;
+    /// # use solana_signer::Signer;
+    /// # futures::executor::block_on(async {
+    /// #     let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// #     let alice = Keypair::new();
+    /// #     let bob = Keypair::new();
+    /// #     let lamports = 50;
+    /// #     let latest_blockhash = rpc_client.get_latest_blockhash().await?;
+    /// #     let tx = system_transaction::transfer(&alice, &bob.pubkey(), lamports, latest_blockhash);
+    /// let signature = rpc_client.send_and_confirm_transaction(&tx).await?;
+    /// let profile = rpc_client.get_transaction_profile(
+    ///     &signature,
+    ///     CommitmentConfig::confirmed(),
+    /// ).await?;
+    /// #     Ok::<(), Error>(())
+    /// # })?;
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub async fn get_transaction_profile(
+        &self,
+        signature: &Signature,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<RpcTransactionProfile> {
+        self.send(
+            RpcRequest::GetTransactionProfile,
+            json!([
+                signature.to_string(),
+                RpcTransactionProfileConfig {
+                    commitment: Some(commitment_config),
+                    max_supported_profile_version: Some(RPC_TRANSACTION_PROFILE_VERSION),
+                }
+            ]),
+        )
+        .await
+    }
+
     /// Returns the estimated production time of a block.
     ///
     /// # RPC Reference
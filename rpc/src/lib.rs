@@ -4,6 +4,10 @@ pub mod filter;
 pub mod max_slots;
 pub mod optimistically_confirmed_bank_tracker;
 pub mod parsed_token_accounts;
+pub mod profile_histogram;
+pub mod profile_sink_interface;
+#[cfg(feature = "profile-sqlite")]
+pub mod profile_sqlite_sink;
 pub mod rpc;
 mod rpc_cache;
 pub mod rpc_completed_slots_service;
@@ -15,6 +19,7 @@ pub mod rpc_subscription_tracker;
 pub mod rpc_subscriptions;
 pub mod slot_status_notifier;
 pub mod transaction_notifier_interface;
+pub mod transaction_profile;
 pub mod transaction_status_service;
 
 #[macro_use]
@@ -0,0 +1,553 @@
+//! Aggregates the compute units consumed by programs and their profiling sections over a
+//! trailing window, so that a node profiling cluster traffic can report where the compute units
+//! are going.
+//!
+//! Totals are kept in fixed-width time buckets which are dropped once they fall out of the
+//! longest supported window, so memory use is bounded by the number of distinct programs and
+//! sections seen during that window. The busiest programs and sections are also reported as
+//! metrics periodically, see [`ProfileHistogramRegistry::report_metrics`], and served by the
+//! `getProgramComputeStats` RPC method.
+
+use {
+    crate::transaction_profile::instruction_profiles_from_logs,
+    solana_program_runtime::profiling::{ProfileEntryKind, PROFILE_LOG_VERSION},
+    solana_rpc_client_api::response::{
+        RpcProgramComputeStat, RpcProgramComputeStats, RpcSectionComputeStat,
+    },
+    solana_time_utils::timestamp,
+    std::{
+        collections::{HashMap, VecDeque},
+        sync::RwLock,
+    },
+};
+
+/// Width of a histogram bucket
+pub const PROFILE_HISTOGRAM_BUCKET_SECS: u64 = 10;
+
+/// Longest trailing window which can be queried
+pub const MAX_PROFILE_LEADERBOARD_WINDOW_SECS: u64 = 60 * 60;
+
+/// Interval at which the busiest programs and sections are reported as metrics
+pub const PROFILE_METRICS_REPORT_INTERVAL_SECS: u64 = 60;
+
+/// Number of programs, and of sections, reported as metrics per interval
+const MAX_REPORTED_PROFILE_METRICS: usize = 20;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct CuTotal {
+    total_cu: u64,
+    count: u64,
+    min_cu: u64,
+    max_cu: u64,
+}
+
+impl CuTotal {
+    fn single(consumed_cu: u64) -> Self {
+        Self {
+            total_cu: consumed_cu,
+            count: 1,
+            min_cu: consumed_cu,
+            max_cu: consumed_cu,
+        }
+    }
+
+    fn add(&mut self, other: CuTotal) {
+        self.min_cu = if self.count == 0 {
+            other.min_cu
+        } else {
+            self.min_cu.min(other.min_cu)
+        };
+        self.max_cu = self.max_cu.max(other.max_cu);
+        self.total_cu = self.total_cu.saturating_add(other.total_cu);
+        self.count = self.count.saturating_add(other.count);
+    }
+
+    fn mean_cu(&self) -> u64 {
+        self.total_cu / self.count.max(1)
+    }
+}
+
+#[derive(Default)]
+struct Bucket {
+    start_secs: u64,
+    programs: HashMap<String, CuTotal>,
+    /// Keyed by program id and section name
+    sections: HashMap<(String, String), CuTotal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramCuTotal {
+    pub program_id: String,
+    /// Compute units consumed by the program's invocations, including the programs it invoked
+    pub total_cu: u64,
+    pub invocations: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionCuTotal {
+    pub program_id: String,
+    pub section: String,
+    pub total_cu: u64,
+    pub occurrences: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileLeaderboard {
+    pub window_secs: u64,
+    /// Programs ordered by decreasing total compute units
+    pub programs: Vec<ProgramCuTotal>,
+    /// Sections ordered by decreasing total compute units
+    pub sections: Vec<SectionCuTotal>,
+}
+
+/// Totals of the buckets overlapping a trailing window
+struct WindowTotals {
+    /// Length of the window, capped to the retained history
+    window_secs: u64,
+    programs: HashMap<String, CuTotal>,
+    /// Keyed by program id and section name
+    sections: HashMap<(String, String), CuTotal>,
+}
+
+/// Per-program and per-section compute unit histograms of recently executed transactions
+#[derive(Default)]
+pub struct ProfileHistogramRegistry {
+    buckets: RwLock<VecDeque<Bucket>>,
+}
+
+impl ProfileHistogramRegistry {
+    /// Record the compute units reported by the log messages of an executed transaction
+    pub fn record_transaction(&self, log_messages: &[String]) {
+        self.record_transaction_at(timestamp() / 1000, log_messages)
+    }
+
+    fn record_transaction_at(&self, now_secs: u64, log_messages: &[String]) {
+        let programs: Vec<_> = log_messages
+            .iter()
+            .filter_map(|line| program_consumed_units(line))
+            .collect();
+        if programs.is_empty() {
+            return;
+        }
+        let sections: Vec<_> = instruction_profiles_from_logs(log_messages, PROFILE_LOG_VERSION)
+            .into_iter()
+            .flat_map(|profile| {
+                let program_id = profile.program_id;
+                profile
+                    .entries
+                    .into_iter()
+                    .filter(|entry| entry.kind == ProfileEntryKind::Section.as_str())
+                    .map(move |entry| ((program_id.clone(), entry.name), entry.consumed_cu))
+            })
+            .collect();
+
+        let start_secs = now_secs - now_secs % PROFILE_HISTOGRAM_BUCKET_SECS;
+        let mut buckets = self.buckets.write().unwrap();
+        if buckets
+            .back()
+            .is_none_or(|bucket| bucket.start_secs < start_secs)
+        {
+            buckets.push_back(Bucket {
+                start_secs,
+                ..Bucket::default()
+            });
+        }
+        while buckets.front().is_some_and(|bucket| {
+            bucket
+                .start_secs
+                .saturating_add(MAX_PROFILE_LEADERBOARD_WINDOW_SECS)
+                <= start_secs
+        }) {
+            buckets.pop_front();
+        }
+        let bucket = buckets.back_mut().unwrap();
+        for (program_id, consumed_cu) in programs {
+            bucket
+                .programs
+                .entry(program_id.to_string())
+                .or_default()
+                .add(CuTotal::single(consumed_cu));
+        }
+        for (key, consumed_cu) in sections {
+            bucket
+                .sections
+                .entry(key)
+                .or_default()
+                .add(CuTotal::single(consumed_cu));
+        }
+    }
+
+    /// The `limit` programs and sections which consumed the most compute units during the last
+    /// `window_secs` seconds
+    pub fn leaderboard(&self, window_secs: u64, limit: usize) -> ProfileLeaderboard {
+        self.leaderboard_at(timestamp() / 1000, window_secs, limit)
+    }
+
+    /// Compute unit statistics of the `limit` programs which consumed the most compute units
+    /// during the last `window_secs` seconds, each with the statistics of its sections. Only the
+    /// given programs are reported unless `program_ids` is empty.
+    pub fn program_compute_stats(
+        &self,
+        window_secs: u64,
+        program_ids: &[String],
+        limit: usize,
+    ) -> RpcProgramComputeStats {
+        self.program_compute_stats_at(timestamp() / 1000, window_secs, program_ids, limit)
+    }
+
+    /// Report the compute units consumed by the busiest programs and sections during the last
+    /// report interval as `profile-program-cu` and `profile-section-cu` datapoints, tagged with
+    /// the program id and section name, so that dashboards can track their cost over time
+    pub fn report_metrics(&self) {
+        let leaderboard = self.leaderboard(
+            PROFILE_METRICS_REPORT_INTERVAL_SECS,
+            MAX_REPORTED_PROFILE_METRICS,
+        );
+        for program in leaderboard.programs {
+            datapoint_info!(
+                "profile-program-cu",
+                "program_id" => program.program_id,
+                ("total_cu", program.total_cu, i64),
+                ("invocations", program.invocations, i64),
+                ("mean_cu", program.total_cu / program.invocations.max(1), i64),
+            );
+        }
+        for section in leaderboard.sections {
+            datapoint_info!(
+                "profile-section-cu",
+                "program_id" => section.program_id,
+                "section" => section.section,
+                ("total_cu", section.total_cu, i64),
+                ("occurrences", section.occurrences, i64),
+                ("mean_cu", section.total_cu / section.occurrences.max(1), i64),
+            );
+        }
+    }
+
+    fn window_totals(&self, now_secs: u64, window_secs: u64) -> WindowTotals {
+        let window_secs = window_secs.min(MAX_PROFILE_LEADERBOARD_WINDOW_SECS);
+        let oldest_secs = now_secs.saturating_sub(window_secs);
+        let mut programs: HashMap<String, CuTotal> = HashMap::new();
+        let mut sections: HashMap<(String, String), CuTotal> = HashMap::new();
+        let buckets = self.buckets.read().unwrap();
+        for bucket in buckets.iter().filter(|bucket| {
+            bucket
+                .start_secs
+                .saturating_add(PROFILE_HISTOGRAM_BUCKET_SECS)
+                > oldest_secs
+                && bucket.start_secs <= now_secs
+        }) {
+            for (program_id, total) in &bucket.programs {
+                programs.entry(program_id.clone()).or_default().add(*total);
+            }
+            for (key, total) in &bucket.sections {
+                sections.entry(key.clone()).or_default().add(*total);
+            }
+        }
+        WindowTotals {
+            window_secs,
+            programs,
+            sections,
+        }
+    }
+
+    fn leaderboard_at(&self, now_secs: u64, window_secs: u64, limit: usize) -> ProfileLeaderboard {
+        let WindowTotals {
+            window_secs,
+            programs,
+            sections,
+        } = self.window_totals(now_secs, window_secs);
+
+        let mut programs: Vec<_> = programs
+            .into_iter()
+            .map(|(program_id, total)| ProgramCuTotal {
+                program_id,
+                total_cu: total.total_cu,
+                invocations: total.count,
+            })
+            .collect();
+        programs.sort_by(|a, b| {
+            b.total_cu
+                .cmp(&a.total_cu)
+                .then_with(|| a.program_id.cmp(&b.program_id))
+        });
+        programs.truncate(limit);
+
+        let mut sections: Vec<_> = sections
+            .into_iter()
+            .map(|((program_id, section), total)| SectionCuTotal {
+                program_id,
+                section,
+                total_cu: total.total_cu,
+                occurrences: total.count,
+            })
+            .collect();
+        sections.sort_by(|a, b| {
+            b.total_cu
+                .cmp(&a.total_cu)
+                .then_with(|| a.program_id.cmp(&b.program_id))
+                .then_with(|| a.section.cmp(&b.section))
+        });
+        sections.truncate(limit);
+
+        ProfileLeaderboard {
+            window_secs,
+            programs,
+            sections,
+        }
+    }
+
+    fn program_compute_stats_at(
+        &self,
+        now_secs: u64,
+        window_secs: u64,
+        program_ids: &[String],
+        limit: usize,
+    ) -> RpcProgramComputeStats {
+        let WindowTotals {
+            window_secs,
+            programs,
+            sections,
+        } = self.window_totals(now_secs, window_secs);
+
+        let mut sections_by_program: HashMap<String, Vec<RpcSectionComputeStat>> = HashMap::new();
+        for ((program_id, name), total) in sections {
+            sections_by_program
+                .entry(program_id)
+                .or_default()
+                .push(RpcSectionComputeStat {
+                    name,
+                    occurrences: total.count,
+                    total_cu: total.total_cu,
+                    mean_cu: total.mean_cu(),
+                    min_cu: total.min_cu,
+                    max_cu: total.max_cu,
+                });
+        }
+
+        let mut programs: Vec<_> = programs
+            .into_iter()
+            .filter(|(program_id, _)| program_ids.is_empty() || program_ids.contains(program_id))
+            .map(|(program_id, total)| {
+                let mut sections = sections_by_program.remove(&program_id).unwrap_or_default();
+                sections.sort_by(|a, b| {
+                    b.total_cu
+                        .cmp(&a.total_cu)
+                        .then_with(|| a.name.cmp(&b.name))
+                });
+                RpcProgramComputeStat {
+                    program_id,
+                    invocations: total.count,
+                    total_cu: total.total_cu,
+                    mean_cu: total.mean_cu(),
+                    min_cu: total.min_cu,
+                    max_cu: total.max_cu,
+                    sections,
+                }
+            })
+            .collect();
+        programs.sort_by(|a, b| {
+            b.total_cu
+                .cmp(&a.total_cu)
+                .then_with(|| a.program_id.cmp(&b.program_id))
+        });
+        programs.truncate(limit);
+
+        RpcProgramComputeStats {
+            window_secs,
+            programs,
+        }
+    }
+}
+
+/// Parse a `Program <id> consumed <units> of <budget> compute units` line
+fn program_consumed_units(line: &str) -> Option<(&str, u64)> {
+    let (program_id, rest) = line.strip_prefix("Program ")?.split_once(' ')?;
+    let (consumed, _) = rest.strip_prefix("consumed ")?.split_once(" of ")?;
+    Some((program_id, consumed.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logs(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_profile_leaderboard() {
+        let registry = ProfileHistogramRegistry::default();
+        let now = 10_000;
+        let later = now + 3 * PROFILE_HISTOGRAM_BUCKET_SECS;
+        registry.record_transaction_at(
+            now,
+            &logs(&[
+                "Program AAA invoke [1]",
+                "Program BBB invoke [2]",
+                "Program BBB consumed 300 of 1000 compute units",
+                "Program BBB success",
+                "Program profile: v3 section 0 10 700 - 0 - deserialize",
+                "Program AAA consumed 1000 of 1400 compute units",
+                "Program AAA success",
+            ]),
+        );
+        registry.record_transaction_at(
+            later,
+            &logs(&[
+                "Program BBB invoke [1]",
+                "Program profile: v3 section 0 5 50 - 0 - transfer",
+                "Program profile: v3 mark 0 60 0 - 0 - checkpoint",
+                "Program BBB consumed 2000 of 2000 compute units",
+                "Program BBB success",
+            ]),
+        );
+        // Transactions without program invocations are ignored
+        registry.record_transaction_at(now, &[]);
+
+        let leaderboard = registry.leaderboard_at(later, 60, 10);
+        assert_eq!(leaderboard.window_secs, 60);
+        assert_eq!(
+            leaderboard.programs,
+            vec![
+                ProgramCuTotal {
+                    program_id: "BBB".to_string(),
+                    total_cu: 2300,
+                    invocations: 2,
+                },
+                ProgramCuTotal {
+                    program_id: "AAA".to_string(),
+                    total_cu: 1000,
+                    invocations: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            leaderboard.sections,
+            vec![
+                SectionCuTotal {
+                    program_id: "AAA".to_string(),
+                    section: "deserialize".to_string(),
+                    total_cu: 700,
+                    occurrences: 1,
+                },
+                SectionCuTotal {
+                    program_id: "BBB".to_string(),
+                    section: "transfer".to_string(),
+                    total_cu: 50,
+                    occurrences: 1,
+                },
+            ]
+        );
+
+        // The limit applies to programs and sections separately
+        let leaderboard = registry.leaderboard_at(later, 60, 1);
+        assert_eq!(leaderboard.programs.len(), 1);
+        assert_eq!(leaderboard.programs[0].program_id, "BBB");
+        assert_eq!(leaderboard.sections.len(), 1);
+        assert_eq!(leaderboard.sections[0].section, "deserialize");
+
+        // Only the most recent bucket falls within a short window
+        let leaderboard = registry.leaderboard_at(later, PROFILE_HISTOGRAM_BUCKET_SECS, 10);
+        assert_eq!(leaderboard.programs.len(), 1);
+        assert_eq!(leaderboard.programs[0].total_cu, 2000);
+
+        // Windows are capped to the retained history
+        let much_later = now + MAX_PROFILE_LEADERBOARD_WINDOW_SECS + PROFILE_HISTOGRAM_BUCKET_SECS;
+        let leaderboard = registry.leaderboard_at(much_later, u64::MAX, 10);
+        assert_eq!(leaderboard.window_secs, MAX_PROFILE_LEADERBOARD_WINDOW_SECS);
+        assert_eq!(leaderboard.programs.len(), 1);
+
+        // Buckets older than the longest window are dropped
+        registry.record_transaction_at(
+            much_later + MAX_PROFILE_LEADERBOARD_WINDOW_SECS,
+            &logs(&[
+                "Program CCC invoke [1]",
+                "Program CCC consumed 1 of 2 compute units",
+                "Program CCC success",
+            ]),
+        );
+        assert_eq!(registry.buckets.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_program_compute_stats() {
+        let registry = ProfileHistogramRegistry::default();
+        let now = 10_000;
+        for (consumed_cu, section_cu) in [(1000, 700), (400, 100)] {
+            registry.record_transaction_at(
+                now,
+                &logs(&[
+                    "Program AAA invoke [1]",
+                    &format!("Program profile: v3 section 0 10 {section_cu} - 0 - deserialize"),
+                    "Program profile: v3 section 0 20 50 - 0 - transfer",
+                    &format!("Program AAA consumed {consumed_cu} of 1400 compute units"),
+                    "Program AAA success",
+                ]),
+            );
+        }
+        registry.record_transaction_at(
+            now,
+            &logs(&[
+                "Program BBB invoke [1]",
+                "Program BBB consumed 300 of 1400 compute units",
+                "Program BBB success",
+            ]),
+        );
+
+        let stats = registry.program_compute_stats_at(now, 60, &[], 10);
+        assert_eq!(stats.window_secs, 60);
+        assert_eq!(
+            stats.programs,
+            vec![
+                RpcProgramComputeStat {
+                    program_id: "AAA".to_string(),
+                    invocations: 2,
+                    total_cu: 1400,
+                    mean_cu: 700,
+                    min_cu: 400,
+                    max_cu: 1000,
+                    sections: vec![
+                        RpcSectionComputeStat {
+                            name: "deserialize".to_string(),
+                            occurrences: 2,
+                            total_cu: 800,
+                            mean_cu: 400,
+                            min_cu: 100,
+                            max_cu: 700,
+                        },
+                        RpcSectionComputeStat {
+                            name: "transfer".to_string(),
+                            occurrences: 2,
+                            total_cu: 100,
+                            mean_cu: 50,
+                            min_cu: 50,
+                            max_cu: 50,
+                        },
+                    ],
+                },
+                RpcProgramComputeStat {
+                    program_id: "BBB".to_string(),
+                    invocations: 1,
+                    total_cu: 300,
+                    mean_cu: 300,
+                    min_cu: 300,
+                    max_cu: 300,
+                    sections: vec![],
+                },
+            ]
+        );
+
+        // Only the requested programs are reported
+        let stats = registry.program_compute_stats_at(now, 60, &["BBB".to_string()], 10);
+        assert_eq!(stats.programs.len(), 1);
+        assert_eq!(stats.programs[0].program_id, "BBB");
+
+        let stats = registry.program_compute_stats_at(now, 60, &[], 1);
+        assert_eq!(stats.programs.len(), 1);
+        assert_eq!(stats.programs[0].program_id, "AAA");
+    }
+}
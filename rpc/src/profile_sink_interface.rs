@@ -0,0 +1,18 @@
+use {
+    solana_clock::Slot,
+    solana_signature::Signature,
+    std::{error::Error, sync::Arc},
+};
+
+pub trait ProfileSink {
+    /// Record the profiling entries reported by the log messages of an executed transaction,
+    /// returning how many were recorded
+    fn record_transaction(
+        &self,
+        slot: Slot,
+        signature: &Signature,
+        log_messages: &[String],
+    ) -> Result<usize, Box<dyn Error>>;
+}
+
+pub type ProfileSinkArc = Arc<dyn ProfileSink + Sync + Send>;
@@ -0,0 +1,252 @@
+//! Appends the profiling entries reported by executed transactions to a SQLite database, so that
+//! a long test campaign accumulates a queryable history of compute unit behavior.
+//!
+//! Every entry becomes a row of the `profile_entries` table, along with the slot and signature of
+//! its transaction and the program which recorded it. The database is opened in WAL mode, so it
+//! can be queried while the validator is still appending to it:
+//!
+//! ```sql
+//! SELECT slot, AVG(consumed_cu) FROM profile_entries WHERE name = 'transfer' GROUP BY slot;
+//! ```
+//!
+//! Only available with the `profile-sqlite` feature, which builds SQLite along with the crate.
+
+use {
+    crate::{
+        profile_sink_interface::ProfileSink, transaction_profile::instruction_profiles_from_logs,
+    },
+    rusqlite::{params, Connection},
+    solana_clock::Slot,
+    solana_program_runtime::profiling::PROFILE_LOG_VERSION,
+    solana_signature::Signature,
+    solana_time_utils::timestamp,
+    std::{error::Error, path::Path, sync::Mutex},
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS profile_entries (
+    recorded_at_ms INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    signature TEXT NOT NULL,
+    instruction INTEGER NOT NULL,
+    program_id TEXT NOT NULL,
+    stack_height INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL,
+    depth INTEGER NOT NULL,
+    start_cu INTEGER NOT NULL,
+    consumed_cu INTEGER NOT NULL,
+    heap_bytes INTEGER,
+    attributes TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS profile_entries_by_section ON profile_entries (program_id, name);
+CREATE INDEX IF NOT EXISTS profile_entries_by_slot ON profile_entries (slot);
+";
+
+const INSERT_ENTRY: &str = "
+INSERT INTO profile_entries (
+    recorded_at_ms, slot, signature, instruction, program_id, stack_height, kind, name, depth,
+    start_cu, consumed_cu, heap_bytes, attributes
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+";
+
+pub struct ProfileSqliteSink {
+    connection: Mutex<Connection>,
+}
+
+impl ProfileSqliteSink {
+    /// Open the database at `path`, creating it and its table if they do not exist yet
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        Self::new(connection)
+    }
+
+    fn new(connection: Connection) -> rusqlite::Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// Append the profiling entries reported by the log messages of an executed transaction,
+    /// returning how many were appended
+    pub fn record_transaction(
+        &self,
+        slot: Slot,
+        signature: &Signature,
+        log_messages: &[String],
+    ) -> rusqlite::Result<usize> {
+        let profiles = instruction_profiles_from_logs(log_messages, PROFILE_LOG_VERSION);
+        if profiles.iter().all(|profile| profile.entries.is_empty()) {
+            return Ok(0);
+        }
+        let recorded_at_ms = timestamp();
+        let signature = signature.to_string();
+
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        let mut count = 0;
+        {
+            let mut statement = transaction.prepare_cached(INSERT_ENTRY)?;
+            for (instruction, profile) in profiles.iter().enumerate() {
+                for entry in &profile.entries {
+                    statement.execute(params![
+                        recorded_at_ms,
+                        slot,
+                        signature,
+                        instruction,
+                        profile.program_id,
+                        profile.stack_height,
+                        entry.kind,
+                        entry.name,
+                        entry.depth,
+                        entry.start_cu,
+                        entry.consumed_cu,
+                        entry.heap_bytes,
+                        serde_json::to_string(&entry.attributes).unwrap(),
+                    ])?;
+                    count += 1;
+                }
+            }
+        }
+        transaction.commit()?;
+        Ok(count)
+    }
+}
+
+impl ProfileSink for ProfileSqliteSink {
+    fn record_transaction(
+        &self,
+        slot: Slot,
+        signature: &Signature,
+        log_messages: &[String],
+    ) -> Result<usize, Box<dyn Error>> {
+        Ok(ProfileSqliteSink::record_transaction(
+            self,
+            slot,
+            signature,
+            log_messages,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logs(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_record_transaction() {
+        let sink = ProfileSqliteSink::new(Connection::open_in_memory().unwrap()).unwrap();
+        let signature = Signature::from([7; 64]);
+        assert_eq!(
+            sink.record_transaction(
+                42,
+                &signature,
+                &logs(&[
+                    "Program AAA invoke [1]",
+                    "Program BBB invoke [2]",
+                    "Program profile: v3 section 0 5 50 - 0 - transfer",
+                    "Program BBB consumed 300 of 1000 compute units",
+                    "Program BBB success",
+                    "Program profile: v3 section 0 10 700 1024 0 - deserialize",
+                    "Program profile: v3 mark 0 60 0 - 0 - checkpoint",
+                    "Program AAA consumed 1000 of 1400 compute units",
+                    "Program AAA success",
+                ]),
+            ),
+            Ok(3)
+        );
+        // Transactions without profiling entries are not recorded
+        assert_eq!(
+            sink.record_transaction(
+                43,
+                &signature,
+                &logs(&[
+                    "Program AAA invoke [1]",
+                    "Program AAA consumed 1000 of 1400 compute units",
+                    "Program AAA success",
+                ]),
+            ),
+            Ok(0)
+        );
+
+        let connection = sink.connection.lock().unwrap();
+        let mut statement = connection
+            .prepare(
+                "SELECT slot, signature, instruction, program_id, stack_height, kind, name, \
+                 consumed_cu, heap_bytes FROM profile_entries ORDER BY instruction, start_cu",
+            )
+            .unwrap();
+        let rows: Vec<(
+            u64,
+            String,
+            usize,
+            String,
+            usize,
+            String,
+            String,
+            u64,
+            Option<u64>,
+        )> = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get(7)?,
+                    row.get(8)?,
+                ))
+            })
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        let signature = signature.to_string();
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    42,
+                    signature.clone(),
+                    0,
+                    "BBB".to_string(),
+                    2,
+                    "section".to_string(),
+                    "transfer".to_string(),
+                    50,
+                    None,
+                ),
+                (
+                    42,
+                    signature.clone(),
+                    1,
+                    "AAA".to_string(),
+                    1,
+                    "section".to_string(),
+                    "deserialize".to_string(),
+                    700,
+                    Some(1024),
+                ),
+                (
+                    42,
+                    signature,
+                    1,
+                    "AAA".to_string(),
+                    1,
+                    "mark".to_string(),
+                    "checkpoint".to_string(),
+                    0,
+                    None,
+                ),
+            ]
+        );
+    }
+}
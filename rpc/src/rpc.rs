@@ -3,9 +3,16 @@
 use solana_runtime::installed_scheduler_pool::BankWithScheduler;
 use {
     crate::{
-        filter::filter_allows, max_slots::MaxSlots,
+        filter::filter_allows,
+        max_slots::MaxSlots,
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
-        parsed_token_accounts::*, rpc_cache::LargestAccountsCache, rpc_health::*,
+        parsed_token_accounts::*,
+        profile_histogram::{ProfileHistogramRegistry, MAX_PROFILE_LEADERBOARD_WINDOW_SECS},
+        rpc_cache::LargestAccountsCache,
+        rpc_health::*,
+        transaction_profile::{
+            instruction_profiles_from_logs, negotiate_profile_version, transaction_budget_from_logs,
+        },
     },
     base64::{prelude::BASE64_STANDARD, Engine},
     bincode::{config::Options, serialize},
@@ -44,11 +51,13 @@ use {
         blockstore::{Blockstore, BlockstoreError, SignatureInfosForAddress},
         blockstore_meta::{PerfSample, PerfSampleV1, PerfSampleV2},
         leader_schedule_cache::LeaderScheduleCache,
+        transaction_balances::svm_token_info_to_token_balance,
     },
     solana_message::{AddressLoader, SanitizedMessage},
     solana_metrics::inc_new_counter_info,
     solana_perf::packet::PACKET_DATA_SIZE,
     solana_program_pack::Pack,
+    solana_program_runtime::profiling::ProfileVerbosity,
     solana_pubkey::{Pubkey, PUBKEY_BYTES},
     solana_rpc_client_api::{
         config::*,
@@ -58,8 +67,9 @@ use {
             TokenAccountsFilter, DELINQUENT_VALIDATOR_SLOT_DISTANCE,
             MAX_GET_CONFIRMED_BLOCKS_RANGE, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
             MAX_GET_PROGRAM_ACCOUNT_FILTERS, MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
-            MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
-            MAX_RPC_VOTE_ACCOUNT_INFO_EPOCH_CREDITS_HISTORY, NUM_LARGEST_ACCOUNTS,
+            MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS, MAX_PROGRAM_COMPUTE_STATS_PROGRAMS,
+            MAX_RPC_VOTE_ACCOUNT_INFO_EPOCH_CREDITS_HISTORY, MAX_SIMULATE_BUNDLE_TRANSACTIONS,
+            NUM_LARGEST_ACCOUNTS,
         },
         response::{Response as RpcResponse, *},
     },
@@ -78,6 +88,7 @@ use {
     solana_signer::Signer,
     solana_stake_program,
     solana_storage_bigtable::Error as StorageError,
+    solana_svm_log_collector::{LogLevel, ProgramLogFilter},
     solana_transaction::{
         sanitized::{MessageHash, SanitizedTransaction, MAX_TX_ACCOUNT_LOCKS},
         versioned::VersionedTransaction,
@@ -85,11 +96,11 @@ use {
     solana_transaction_context::TransactionAccount,
     solana_transaction_error::TransactionError,
     solana_transaction_status::{
-        map_inner_instructions, BlockEncodingOptions, ConfirmedBlock,
-        ConfirmedTransactionStatusWithSignature, ConfirmedTransactionWithStatusMeta,
-        EncodedConfirmedTransactionWithStatusMeta, Reward, RewardType, Rewards,
-        TransactionBinaryEncoding, TransactionConfirmationStatus, TransactionStatus,
-        UiConfirmedBlock, UiTransactionEncoding,
+        map_inner_instructions, option_serializer::OptionSerializer, BlockEncodingOptions,
+        ConfirmedBlock, ConfirmedTransactionStatusWithSignature,
+        ConfirmedTransactionWithStatusMeta, EncodedConfirmedTransactionWithStatusMeta, Reward,
+        RewardType, Rewards, TransactionBinaryEncoding, TransactionConfirmationStatus,
+        TransactionStatus, UiConfirmedBlock, UiTransactionEncoding, UiTransactionTokenBalance,
     },
     solana_validator_exit::Exit,
     solana_vote_program::vote_state::MAX_LOCKOUT_HISTORY,
@@ -110,6 +121,7 @@ use {
         collections::{BinaryHeap, HashMap, HashSet},
         convert::TryFrom,
         net::SocketAddr,
+        path::PathBuf,
         str::FromStr,
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
@@ -119,6 +131,11 @@ use {
     },
     tokio::runtime::Runtime,
 };
+#[cfg(feature = "protobuf")]
+use {
+    prost::Message as _,
+    solana_program_runtime::{profile_report::ProfileReport, protobuf::generated},
+};
 #[cfg(test)]
 use {
     solana_gossip::contact_info::ContactInfo,
@@ -163,6 +180,17 @@ fn is_finalized(
 pub struct JsonRpcConfig {
     pub enable_rpc_transaction_history: bool,
     pub enable_extended_tx_metadata_storage: bool,
+    /// Aggregate the compute units reported by executed transactions for the admin
+    /// `profileLeaderboard` method and the `getProgramComputeStats` method
+    pub enable_profile_leaderboard: bool,
+    /// Append the profiling entries reported by executed transactions to this SQLite database
+    pub profile_sqlite_path: Option<PathBuf>,
+    /// Store the profile report of executed transactions with their status, so that
+    /// `getTransaction` can return it
+    pub enable_profile_persistence: bool,
+    /// Program messages logged below this level are dropped from `simulateTransaction` results,
+    /// unless the request overrides it
+    pub simulation_min_log_level: LogLevel,
     pub faucet_addr: Option<SocketAddr>,
     pub health_check_slot_distance: u64,
     pub skip_preflight_health_check: bool,
@@ -184,6 +212,10 @@ impl Default for JsonRpcConfig {
         Self {
             enable_rpc_transaction_history: Default::default(),
             enable_extended_tx_metadata_storage: Default::default(),
+            enable_profile_leaderboard: Default::default(),
+            profile_sqlite_path: Option::default(),
+            enable_profile_persistence: Default::default(),
+            simulation_min_log_level: LogLevel::default(),
             faucet_addr: Option::default(),
             health_check_slot_distance: Default::default(),
             skip_preflight_health_check: bool::default(),
@@ -254,6 +286,7 @@ pub struct JsonRpcRequestProcessor {
     leader_schedule_cache: Arc<LeaderScheduleCache>,
     max_complete_transaction_status_slot: Arc<AtomicU64>,
     prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+    profile_histograms: Option<Arc<ProfileHistogramRegistry>>,
     runtime: Arc<Runtime>,
 }
 impl Metadata for JsonRpcRequestProcessor {}
@@ -417,6 +450,7 @@ impl JsonRpcRequestProcessor {
         leader_schedule_cache: Arc<LeaderScheduleCache>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+        profile_histograms: Option<Arc<ProfileHistogramRegistry>>,
         runtime: Arc<Runtime>,
     ) -> (Self, Receiver<TransactionInfo>) {
         let (transaction_sender, transaction_receiver) = unbounded();
@@ -439,6 +473,7 @@ impl JsonRpcRequestProcessor {
                 leader_schedule_cache,
                 max_complete_transaction_status_slot,
                 prioritization_fee_cache,
+                profile_histograms,
                 runtime,
             },
             transaction_receiver,
@@ -526,6 +561,7 @@ impl JsonRpcRequestProcessor {
             leader_schedule_cache,
             max_complete_transaction_status_slot: Arc::new(AtomicU64::default()),
             prioritization_fee_cache: Arc::new(PrioritizationFeeCache::default()),
+            profile_histograms: None,
             runtime,
         }
     }
@@ -1749,6 +1785,7 @@ impl JsonRpcRequestProcessor {
             .unwrap_or_default();
         let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Json);
         let max_supported_transaction_version = config.max_supported_transaction_version;
+        let include_profile = config.include_profile;
         let commitment = config.commitment.unwrap_or_default();
         check_is_at_least_confirmed(commitment)?;
 
@@ -1770,10 +1807,21 @@ impl JsonRpcRequestProcessor {
             .await
             .expect("Failed to spawn blocking task");
 
-        let encode_transaction =
-                |confirmed_tx_with_meta: ConfirmedTransactionWithStatusMeta| -> Result<EncodedConfirmedTransactionWithStatusMeta> {
-                    Ok(confirmed_tx_with_meta.encode(encoding, max_supported_transaction_version).map_err(RpcCustomError::from)?)
-                };
+        let encode_transaction = |confirmed_tx_with_meta: ConfirmedTransactionWithStatusMeta|
+         -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+            let profile = include_profile
+                .then(|| confirmed_tx_with_meta.tx_with_meta.get_status_meta())
+                .flatten()
+                .and_then(|meta| meta.profile)
+                .and_then(|profile| decode_stored_profile(&profile));
+            let mut encoded = confirmed_tx_with_meta
+                .encode(encoding, max_supported_transaction_version)
+                .map_err(RpcCustomError::from)?;
+            if let (Some(profile), Some(meta)) = (profile, encoded.transaction.meta.as_mut()) {
+                meta.profile = OptionSerializer::Some(profile);
+            }
+            Ok(encoded)
+        };
 
         match confirmed_transaction.unwrap_or(None) {
             Some(mut confirmed_transaction) => {
@@ -1816,6 +1864,45 @@ impl JsonRpcRequestProcessor {
         Ok(None)
     }
 
+    pub async fn get_transaction_profile(
+        &self,
+        signature: Signature,
+        config: Option<RpcTransactionProfileConfig>,
+    ) -> Result<Option<RpcTransactionProfile>> {
+        let RpcTransactionProfileConfig {
+            commitment,
+            max_supported_profile_version,
+        } = config.unwrap_or_default();
+        let version = negotiate_profile_version(max_supported_profile_version)?;
+
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment,
+            max_supported_transaction_version: Some(0),
+            include_profile: false,
+        };
+        let Some(transaction) = self
+            .get_transaction(
+                signature,
+                Some(RpcEncodingConfigWrapper::Current(Some(config))),
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let log_messages: Option<Vec<String>> = transaction
+            .transaction
+            .meta
+            .and_then(|meta| meta.log_messages.into());
+        let log_messages = log_messages.unwrap_or_default();
+        Ok(Some(RpcTransactionProfile {
+            version,
+            slot: transaction.slot,
+            instructions: instruction_profiles_from_logs(&log_messages, version),
+            budget: transaction_budget_from_logs(&log_messages, version),
+        }))
+    }
+
     pub async fn get_signatures_for_address(
         &self,
         address: Pubkey,
@@ -2400,6 +2487,26 @@ impl JsonRpcRequestProcessor {
             })
             .collect())
     }
+
+    fn get_program_compute_stats(
+        &self,
+        program_ids: Vec<Pubkey>,
+        window_secs: u64,
+        limit: usize,
+    ) -> Result<RpcProgramComputeStats> {
+        let Some(profile_histograms) = &self.profile_histograms else {
+            return Err(error::Error {
+                code: error::ErrorCode::InvalidRequest,
+                message: "Program compute stats are not aggregated by this node".to_string(),
+                data: None,
+            });
+        };
+        let program_ids: Vec<_> = program_ids
+            .iter()
+            .map(|program_id| program_id.to_string())
+            .collect();
+        Ok(profile_histograms.program_compute_stats(window_secs, &program_ids, limit))
+    }
 }
 
 pub(crate) fn optimize_filters(filters: &mut [RpcFilterType]) {
@@ -3517,6 +3624,14 @@ pub mod rpc_full {
             config: Option<RpcSimulateTransactionConfig>,
         ) -> Result<RpcResponse<RpcSimulateTransactionResult>>;
 
+        #[rpc(meta, name = "simulateBundle")]
+        fn simulate_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSimulateBundleConfig>,
+        ) -> Result<RpcResponse<Vec<RpcSimulateTransactionResult>>>;
+
         #[rpc(meta, name = "minimumLedgerSlot")]
         fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot>;
 
@@ -3561,6 +3676,14 @@ pub mod rpc_full {
             config: Option<RpcEncodingConfigWrapper<RpcTransactionConfig>>,
         ) -> BoxFuture<Result<Option<EncodedConfirmedTransactionWithStatusMeta>>>;
 
+        #[rpc(meta, name = "getTransactionProfile")]
+        fn get_transaction_profile(
+            &self,
+            meta: Self::Metadata,
+            signature_str: String,
+            config: Option<RpcTransactionProfileConfig>,
+        ) -> BoxFuture<Result<Option<RpcTransactionProfile>>>;
+
         #[rpc(meta, name = "getSignaturesForAddress")]
         fn get_signatures_for_address(
             &self,
@@ -3608,6 +3731,13 @@ pub mod rpc_full {
             meta: Self::Metadata,
             pubkey_strs: Option<Vec<String>>,
         ) -> Result<Vec<RpcPrioritizationFee>>;
+
+        #[rpc(meta, name = "getProgramComputeStats")]
+        fn get_program_compute_stats(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcProgramComputeStatsConfig>,
+        ) -> Result<RpcProgramComputeStats>;
     }
 
     pub struct FullImpl;
@@ -3891,11 +4021,13 @@ pub mod rpc_full {
                     loaded_accounts_data_size,
                     return_data,
                     inner_instructions: _, // Always `None` due to `enable_cpi_recording = false`
+                    instruction_trace: _,  // Always `None` due to `enable_cpi_recording = false`
                     fee,
                     pre_balances: _,
                     post_balances: _,
                     pre_token_balances: _,
                     post_token_balances: _,
+                    profile: _,
                 } = preflight_bank.simulate_transaction(&transaction, false)
                 {
                     match err {
@@ -3923,6 +4055,9 @@ pub mod rpc_full {
                             pre_token_balances: None,
                             post_token_balances: None,
                             loaded_addresses: None,
+                            profile: None,
+                            instruction_trace: None,
+                            account_diffs: None,
                         },
                     }
                     .into());
@@ -3956,6 +4091,14 @@ pub mod rpc_full {
                 accounts: config_accounts,
                 min_context_slot,
                 inner_instructions: enable_cpi_recording,
+                min_log_level,
+                enable_profiling,
+                log_messages_bytes_limit,
+                log_program_filter,
+                profile_verbosity,
+                include_profile,
+                account_overrides,
+                account_diffs: config_account_diffs,
             } = config.unwrap_or_default();
             let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
             let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
@@ -3963,56 +4106,80 @@ pub mod rpc_full {
                     "unsupported encoding: {tx_encoding}. Supported encodings: base58, base64"
                 ))
             })?;
-            let (_, mut unsanitized_tx) =
-                decode_and_deserialize::<VersionedTransaction>(data, binary_encoding)?;
-
+            let min_log_level = match min_log_level {
+                Some(min_log_level) => min_log_level
+                    .parse::<LogLevel>()
+                    .map_err(|err| Error::invalid_params(err.to_string()))?,
+                None => meta.config.simulation_min_log_level,
+            };
+            let profile_verbosity = profile_verbosity
+                .map(|profile_verbosity| {
+                    profile_verbosity
+                        .parse::<ProfileVerbosity>()
+                        .map_err(|err| Error::invalid_params(err.to_string()))
+                })
+                .transpose()?;
+            let log_program_filter = log_program_filter
+                .map(|log_program_filter| {
+                    let parse_program_ids = |program_ids: Vec<String>| {
+                        program_ids
+                            .iter()
+                            .map(|program_id| verify_pubkey(program_id))
+                            .collect::<Result<HashSet<_>>>()
+                    };
+                    Ok::<_, Error>(Arc::new(match log_program_filter {
+                        RpcLogProgramFilter::Allow(program_ids) => {
+                            ProgramLogFilter::Allow(parse_program_ids(program_ids)?)
+                        }
+                        RpcLogProgramFilter::Deny(program_ids) => {
+                            ProgramLogFilter::Deny(parse_program_ids(program_ids)?)
+                        }
+                    }))
+                })
+                .transpose()?;
+            let account_overrides = parse_account_overrides(account_overrides)?;
+            let account_diffs_encoding = config_account_diffs
+                .map(|config_account_diffs| {
+                    let encoding = config_account_diffs
+                        .encoding
+                        .unwrap_or(UiAccountEncoding::Base64);
+                    if encoding == UiAccountEncoding::Binary
+                        || encoding == UiAccountEncoding::Base58
+                    {
+                        return Err(Error::invalid_params("base58 encoding not supported"));
+                    }
+                    Ok(encoding)
+                })
+                .transpose()?;
+            // The simulation consumes the overrides, but the diffs start from them
+            let pre_simulation_overrides = account_diffs_encoding
+                .and_then(|_| account_overrides.clone())
+                .unwrap_or_default();
             let bank = &*meta.get_bank_with_config(RpcContextConfig {
                 commitment,
                 min_context_slot,
             })?;
-            let mut blockhash: Option<RpcBlockhash> = None;
-            if replace_recent_blockhash {
-                if sig_verify {
-                    return Err(Error::invalid_params(
-                        "sigVerify may not be used with replaceRecentBlockhash",
-                    ));
-                }
-                let recent_blockhash = bank.last_blockhash();
-                unsanitized_tx
-                    .message
-                    .set_recent_blockhash(recent_blockhash);
-                let last_valid_block_height = bank
-                    .get_blockhash_last_valid_block_height(&recent_blockhash)
-                    .expect("bank blockhash queue should contain blockhash");
-                blockhash.replace(RpcBlockhash {
-                    blockhash: recent_blockhash.to_string(),
-                    last_valid_block_height,
-                });
-            }
+            let (transaction, blockhash) = decode_simulation_transaction(
+                data,
+                binary_encoding,
+                bank,
+                sig_verify,
+                replace_recent_blockhash,
+            )?;
 
-            let transaction =
-                sanitize_transaction(unsanitized_tx, bank, bank.get_reserved_account_keys())?;
-            if sig_verify {
-                verify_transaction(&transaction)?;
-            }
+            let simulation = bank.simulate_transaction_with_min_log_level(
+                &transaction,
+                enable_cpi_recording,
+                min_log_level,
+                enable_profiling,
+                log_messages_bytes_limit,
+                log_program_filter,
+                profile_verbosity,
+                include_profile,
+                account_overrides,
+            );
 
-            let TransactionSimulationResult {
-                result,
-                logs,
-                post_simulation_accounts,
-                units_consumed,
-                loaded_accounts_data_size,
-                return_data,
-                inner_instructions,
-                fee,
-                pre_balances,
-                post_balances,
-                pre_token_balances,
-                post_token_balances,
-            } = bank.simulate_transaction(&transaction, enable_cpi_recording);
-
-            let account_keys = transaction.message().account_keys();
-            let number_of_accounts = account_keys.len();
+            let number_of_accounts = transaction.message().account_keys().len();
 
             let accounts = if let Some(config_accounts) = config_accounts {
                 let accounts_encoding = config_accounts
@@ -4031,12 +4198,12 @@ pub mod rpc_full {
                     )));
                 }
 
-                if result.is_err() {
+                if simulation.result.is_err() {
                     Some(vec![None; config_accounts.addresses.len()])
                 } else {
                     let mut post_simulation_accounts_map = HashMap::new();
-                    for (pubkey, data) in post_simulation_accounts {
-                        post_simulation_accounts_map.insert(pubkey, data);
+                    for (pubkey, data) in &simulation.post_simulation_accounts {
+                        post_simulation_accounts_map.insert(*pubkey, data.clone());
                     }
 
                     Some(
@@ -4060,34 +4227,116 @@ pub mod rpc_full {
                 None
             };
 
-            let inner_instructions = inner_instructions.map(|info| {
-                map_inner_instructions(info)
-                    .map(|converted| parse_ui_inner_instructions(converted, &account_keys))
-                    .collect()
-            });
+            let account_diffs = account_diffs_encoding
+                .map(|encoding| {
+                    if simulation.result.is_err() {
+                        Ok(vec![])
+                    } else {
+                        get_simulation_account_diffs(
+                            bank,
+                            &transaction,
+                            &simulation.post_simulation_accounts,
+                            &pre_simulation_overrides,
+                            encoding,
+                        )
+                    }
+                })
+                .transpose()?;
 
             Ok(new_response(
                 bank,
-                RpcSimulateTransactionResult {
-                    err: result.err().map(Into::into),
-                    logs: Some(logs),
+                new_rpc_simulate_transaction_result(
+                    &transaction,
+                    simulation,
                     accounts,
-                    units_consumed: Some(units_consumed),
-                    loaded_accounts_data_size: Some(loaded_accounts_data_size),
-                    return_data: return_data.map(|return_data| return_data.into()),
-                    inner_instructions,
-                    replacement_blockhash: blockhash,
-                    fee,
-                    pre_balances,
-                    post_balances,
-                    pre_token_balances: pre_token_balances.map(|balances| {
-                        balances.into_iter().map(|balance| solana_ledger::transaction_balances::svm_token_info_to_token_balance(balance).into()).collect()
-                    }),
-                    post_token_balances: post_token_balances.map(|balances| {
-                        balances.into_iter().map(|balance| solana_ledger::transaction_balances::svm_token_info_to_token_balance(balance).into()).collect()
-                    }),
-                    loaded_addresses: Some(UiLoadedAddresses::from(&transaction.get_loaded_addresses())),
-                },
+                    account_diffs,
+                    blockhash,
+                ),
+            ))
+        }
+
+        fn simulate_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSimulateBundleConfig>,
+        ) -> Result<RpcResponse<Vec<RpcSimulateTransactionResult>>> {
+            debug!("simulate_bundle rpc request received");
+            let RpcSimulateBundleConfig {
+                sig_verify,
+                replace_recent_blockhash,
+                commitment,
+                encoding,
+                min_context_slot,
+                inner_instructions: enable_cpi_recording,
+                min_log_level,
+                include_profile,
+                account_overrides,
+            } = config.unwrap_or_default();
+            if data.len() > MAX_SIMULATE_BUNDLE_TRANSACTIONS {
+                return Err(Error::invalid_params(format!(
+                    "Too many transactions provided; max {MAX_SIMULATE_BUNDLE_TRANSACTIONS}"
+                )));
+            }
+            let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
+            let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
+                Error::invalid_params(format!(
+                    "unsupported encoding: {tx_encoding}. Supported encodings: base58, base64"
+                ))
+            })?;
+            let min_log_level = match min_log_level {
+                Some(min_log_level) => min_log_level
+                    .parse::<LogLevel>()
+                    .map_err(|err| Error::invalid_params(err.to_string()))?,
+                None => meta.config.simulation_min_log_level,
+            };
+            let account_overrides = parse_account_overrides(account_overrides)?;
+            let bank = &*meta.get_bank_with_config(RpcContextConfig {
+                commitment,
+                min_context_slot,
+            })?;
+            let (transactions, blockhashes): (Vec<_>, Vec<_>) = data
+                .into_iter()
+                .map(|data| {
+                    decode_simulation_transaction(
+                        data,
+                        binary_encoding,
+                        bank,
+                        sig_verify,
+                        replace_recent_blockhash,
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .unzip();
+
+            let simulations = bank.simulate_transaction_bundle(
+                &transactions,
+                enable_cpi_recording,
+                min_log_level,
+                false,
+                None,
+                None,
+                None,
+                include_profile,
+                account_overrides,
+            );
+
+            Ok(new_response(
+                bank,
+                simulations
+                    .into_iter()
+                    .zip(transactions.iter().zip(blockhashes))
+                    .map(|(simulation, (transaction, blockhash))| {
+                        new_rpc_simulate_transaction_result(
+                            transaction,
+                            simulation,
+                            None,
+                            None,
+                            blockhash,
+                        )
+                    })
+                    .collect(),
             ))
         }
 
@@ -4155,6 +4404,23 @@ pub mod rpc_full {
             Box::pin(async move { meta.get_transaction(signature.unwrap(), config).await })
         }
 
+        fn get_transaction_profile(
+            &self,
+            meta: Self::Metadata,
+            signature_str: String,
+            config: Option<RpcTransactionProfileConfig>,
+        ) -> BoxFuture<Result<Option<RpcTransactionProfile>>> {
+            debug!("get_transaction_profile rpc request received: {signature_str:?}");
+            let signature = verify_signature(&signature_str);
+            if let Err(err) = signature {
+                return Box::pin(future::err(err));
+            }
+            Box::pin(async move {
+                meta.get_transaction_profile(signature.unwrap(), config)
+                    .await
+            })
+        }
+
         fn get_signatures_for_address(
             &self,
             meta: Self::Metadata,
@@ -4294,6 +4560,37 @@ pub mod rpc_full {
                 .collect::<Result<Vec<_>>>()?;
             meta.get_recent_prioritization_fees(pubkeys)
         }
+
+        fn get_program_compute_stats(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcProgramComputeStatsConfig>,
+        ) -> Result<RpcProgramComputeStats> {
+            debug!("get_program_compute_stats rpc request received");
+            let RpcProgramComputeStatsConfig {
+                program_ids,
+                window_secs,
+                limit,
+            } = config.unwrap_or_default();
+            let program_ids = program_ids.unwrap_or_default();
+            let limit = limit.unwrap_or(MAX_PROGRAM_COMPUTE_STATS_PROGRAMS);
+            if program_ids.len() > MAX_PROGRAM_COMPUTE_STATS_PROGRAMS
+                || limit > MAX_PROGRAM_COMPUTE_STATS_PROGRAMS
+            {
+                return Err(Error::invalid_params(format!(
+                    "Too many programs requested; max {MAX_PROGRAM_COMPUTE_STATS_PROGRAMS}"
+                )));
+            }
+            let program_ids = program_ids
+                .iter()
+                .map(|program_id| verify_pubkey(program_id))
+                .collect::<Result<Vec<_>>>()?;
+            meta.get_program_compute_stats(
+                program_ids,
+                window_secs.unwrap_or(MAX_PROFILE_LEADERBOARD_WINDOW_SECS),
+                limit,
+            )
+        }
     }
 }
 
@@ -4404,6 +4701,208 @@ fn sanitize_transaction(
     .map_err(|err| Error::invalid_params(format!("invalid transaction: {err}")))
 }
 
+/// Decode the accounts which replace the stored state of their addresses during a simulation
+fn parse_account_overrides(
+    account_overrides: Option<HashMap<String, UiAccount>>,
+) -> Result<Option<HashMap<Pubkey, AccountSharedData>>> {
+    account_overrides
+        .map(|account_overrides| {
+            account_overrides
+                .iter()
+                .map(|(address, account)| {
+                    let pubkey = verify_pubkey(address)?;
+                    let account = account.decode::<AccountSharedData>().ok_or_else(|| {
+                        Error::invalid_params(format!("invalid account override for {address}"))
+                    })?;
+                    Ok((pubkey, account))
+                })
+                .collect::<Result<HashMap<_, _>>>()
+        })
+        .transpose()
+}
+
+/// Decode and sanitize a transaction to simulate against `bank`
+///
+/// With `replace_recent_blockhash`, the recent blockhash of the transaction is replaced by the
+/// latest blockhash of `bank`, which is returned along with the transaction.
+fn decode_simulation_transaction(
+    data: String,
+    binary_encoding: TransactionBinaryEncoding,
+    bank: &Bank,
+    sig_verify: bool,
+    replace_recent_blockhash: bool,
+) -> Result<(
+    RuntimeTransaction<SanitizedTransaction>,
+    Option<RpcBlockhash>,
+)> {
+    let (_, mut unsanitized_tx) =
+        decode_and_deserialize::<VersionedTransaction>(data, binary_encoding)?;
+
+    let mut blockhash: Option<RpcBlockhash> = None;
+    if replace_recent_blockhash {
+        if sig_verify {
+            return Err(Error::invalid_params(
+                "sigVerify may not be used with replaceRecentBlockhash",
+            ));
+        }
+        let recent_blockhash = bank.last_blockhash();
+        unsanitized_tx
+            .message
+            .set_recent_blockhash(recent_blockhash);
+        let last_valid_block_height = bank
+            .get_blockhash_last_valid_block_height(&recent_blockhash)
+            .expect("bank blockhash queue should contain blockhash");
+        blockhash.replace(RpcBlockhash {
+            blockhash: recent_blockhash.to_string(),
+            last_valid_block_height,
+        });
+    }
+
+    let transaction = sanitize_transaction(unsanitized_tx, bank, bank.get_reserved_account_keys())?;
+    if sig_verify {
+        verify_transaction(&transaction)?;
+    }
+    Ok((transaction, blockhash))
+}
+
+/// Decode a profile stored with a transaction's status into its JSON profile report
+#[cfg(feature = "protobuf")]
+fn decode_stored_profile(profile: &[u8]) -> Option<serde_json::Value> {
+    let profile = generated::TransactionProfile::decode(profile)
+        .map_err(|err| warn!("Failed to decode stored transaction profile: {err}"))
+        .ok()?;
+    let report = ProfileReport::try_from(profile)
+        .map_err(|err| warn!("Failed to convert stored transaction profile: {err}"))
+        .ok()?;
+    serde_json::to_value(report).ok()
+}
+
+/// Profiles are stored as protobuf, without the `protobuf` feature they cannot be decoded
+#[cfg(not(feature = "protobuf"))]
+fn decode_stored_profile(_profile: &[u8]) -> Option<serde_json::Value> {
+    None
+}
+
+/// Diff the writable accounts of a simulated transaction which the simulation changed
+///
+/// The accounts are diffed against `pre_simulation_overrides`, or the state stored in `bank` for
+/// accounts which were not overridden. Accounts left without lamports were closed.
+fn get_simulation_account_diffs(
+    bank: &Bank,
+    transaction: &RuntimeTransaction<SanitizedTransaction>,
+    post_simulation_accounts: &[TransactionAccount],
+    pre_simulation_overrides: &HashMap<Pubkey, AccountSharedData>,
+    encoding: UiAccountEncoding,
+) -> Result<Vec<RpcAccountDiff>> {
+    let post_simulation_accounts_map: HashMap<_, _> =
+        post_simulation_accounts.iter().cloned().collect();
+    post_simulation_accounts
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| transaction.message().is_writable(*index))
+        .filter(|(_, (pubkey, post_account))| {
+            let pre_account = account_resolver::get_account_from_overwrites_or_bank(
+                pubkey,
+                bank,
+                Some(pre_simulation_overrides),
+            )
+            .unwrap_or_default();
+            pre_account.lamports() != post_account.lamports()
+                || pre_account.owner() != post_account.owner()
+                || pre_account.executable() != post_account.executable()
+                || pre_account.data() != post_account.data()
+        })
+        .map(|(_, (pubkey, post_account))| {
+            let pre =
+                get_encoded_account(bank, pubkey, encoding, None, Some(pre_simulation_overrides))?;
+            let post = if post_account.lamports() == 0 {
+                None
+            } else {
+                get_encoded_account(
+                    bank,
+                    pubkey,
+                    encoding,
+                    None,
+                    Some(&post_simulation_accounts_map),
+                )?
+            };
+            Ok(RpcAccountDiff {
+                pubkey: pubkey.to_string(),
+                pre,
+                post,
+            })
+        })
+        .collect()
+}
+
+fn new_rpc_simulate_transaction_result(
+    transaction: &RuntimeTransaction<SanitizedTransaction>,
+    simulation: TransactionSimulationResult,
+    accounts: Option<Vec<Option<UiAccount>>>,
+    account_diffs: Option<Vec<RpcAccountDiff>>,
+    replacement_blockhash: Option<RpcBlockhash>,
+) -> RpcSimulateTransactionResult {
+    let TransactionSimulationResult {
+        result,
+        logs,
+        post_simulation_accounts: _,
+        units_consumed,
+        loaded_accounts_data_size,
+        return_data,
+        inner_instructions,
+        instruction_trace,
+        fee,
+        pre_balances,
+        post_balances,
+        pre_token_balances,
+        post_token_balances,
+        profile,
+    } = simulation;
+    let account_keys = transaction.message().account_keys();
+    let inner_instructions = inner_instructions.map(|info| {
+        map_inner_instructions(info)
+            .map(|converted| parse_ui_inner_instructions(converted, &account_keys))
+            .collect()
+    });
+    let to_token_balances = |balances: Vec<_>| {
+        balances
+            .into_iter()
+            .map(|balance| svm_token_info_to_token_balance(balance).into())
+            .collect::<Vec<UiTransactionTokenBalance>>()
+    };
+
+    RpcSimulateTransactionResult {
+        err: result.err().map(Into::into),
+        logs: Some(logs),
+        accounts,
+        units_consumed: Some(units_consumed),
+        loaded_accounts_data_size: Some(loaded_accounts_data_size),
+        return_data: return_data.map(|return_data| return_data.into()),
+        inner_instructions,
+        replacement_blockhash,
+        fee,
+        pre_balances,
+        post_balances,
+        pre_token_balances: pre_token_balances.map(to_token_balances),
+        post_token_balances: post_token_balances.map(to_token_balances),
+        loaded_addresses: Some(UiLoadedAddresses::from(&transaction.get_loaded_addresses())),
+        profile: profile.map(|profile| serde_json::to_value(profile).unwrap()),
+        instruction_trace: instruction_trace.map(|instruction_trace| {
+            instruction_trace
+                .into_iter()
+                .map(|instruction| RpcTracedInstruction {
+                    instruction_index: instruction.top_level_instruction_index,
+                    stack_height: instruction.stack_height,
+                    program_id: instruction.program_id.to_string(),
+                    compute_units_consumed: instruction.compute_units_consumed,
+                    failed: instruction.failed,
+                })
+                .collect()
+        }),
+        account_diffs,
+    }
+}
+
 pub fn create_validator_exit(exit: Arc<AtomicBool>) -> Arc<RwLock<Exit>> {
     let mut validator_exit = Exit::default();
     validator_exit.register_exit(Box::new(move || exit.store(true, Ordering::Relaxed)));
@@ -4467,8 +4966,11 @@ pub fn populate_blockstore_for_tests(
             max_complete_transaction_status_slot,
             true,
             None,
+            None,
+            None,
             blockstore,
             false,
+            false,
             None,
             tss_exit.clone(),
         );
@@ -4818,6 +5320,7 @@ pub mod tests {
                 Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
                 max_complete_transaction_status_slot.clone(),
                 Arc::new(PrioritizationFeeCache::default()),
+                None,
                 service_runtime(rpc_threads, rpc_blocking_threads, rpc_niceness_adj),
             )
             .0;
@@ -6213,6 +6716,29 @@ pub mod tests {
             .expect("actual response deserialization");
         assert_eq!(result, expected);
 
+        // Unknown minLogLevel
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"simulateTransaction","params":["{}", {}]}}"#,
+            tx_serialized_encoded,
+            json!({
+                "minLogLevel": "verbose",
+            })
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let expected = json!({
+            "jsonrpc":"2.0",
+            "error": {
+                "code": ErrorCode::InvalidParams,
+                "message": "invalid log level \"verbose\", expected one of trace, debug, info, warn or error"
+            },
+            "id":1
+        });
+        let expected: Response =
+            serde_json::from_value(expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result, expected);
+
         // Bad recent blockhash with replaceRecentBlockhash=false
         let req = format!(
             r#"{{"jsonrpc":"2.0","id":1,"method":"simulateTransaction","params":["{tx_invalid_recent_blockhash}", {{"replaceRecentBlockhash": false}}]}}"#,
@@ -6627,6 +7153,22 @@ pub mod tests {
                         ]
                         }
                     ],
+                    "instructionTrace": [
+                        {
+                            "instructionIndex": 0,
+                            "stackHeight": 1,
+                            "programId": TestBuiltinEntrypoint::PROGRAM_ID.to_string(),
+                            "computeUnitsConsumed": TestBuiltinEntrypoint::COMPUTE_UNITS + 150,
+                            "failed": false
+                        },
+                        {
+                            "instructionIndex": 0,
+                            "stackHeight": 2,
+                            "programId": "11111111111111111111111111111111",
+                            "computeUnitsConsumed": 150,
+                            "failed": false
+                        }
+                    ],
                     "loadedAccountsDataSize": loaded_accounts_data_size,
                     "fee": 10000,
                     "loadedAddresses": {"readonly": [], "writable": []},
@@ -6657,6 +7199,293 @@ pub mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_rpc_simulate_transaction_include_profile() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        let RpcHandler {
+            ref meta, ref io, ..
+        } = rpc;
+
+        let tx = system_transaction::transfer(
+            &rpc.mint_keypair,
+            &solana_pubkey::new_rand(),
+            bank.get_minimum_balance_for_rent_exemption(0),
+            recent_blockhash,
+        );
+        let tx_serialized_encoded = bs58::encode(serialize(&tx).unwrap()).into_string();
+
+        // Simulation bank must be frozen
+        bank.freeze();
+
+        let simulate = |config: &str| {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0",
+                     "id":1,
+                     "method":"simulateTransaction",
+                     "params":["{tx_serialized_encoded}", {config}]
+                }}"#,
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            result["result"]["value"].clone()
+        };
+
+        // `includeProfile` not provided, should not be in response
+        let value = simulate("{}");
+        assert_eq!(value["err"], Value::Null);
+        assert!(value.get("profile").is_none());
+
+        let value = simulate(r#"{ "includeProfile": true }"#);
+        assert_eq!(value["err"], Value::Null);
+        let profile = &value["profile"];
+        assert_eq!(
+            profile["schema_version"],
+            solana_program_runtime::profile_report::PROFILE_REPORT_SCHEMA_VERSION
+        );
+        assert_eq!(
+            profile["transaction"]["signature"],
+            tx.signatures[0].to_string()
+        );
+        assert_eq!(
+            profile["transaction"]["compute_units_consumed"],
+            value["unitsConsumed"]
+        );
+        assert_eq!(
+            profile["instructions"][0]["program_id"],
+            system_program::id().to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "protobuf")]
+    fn test_decode_stored_profile() {
+        use {
+            prost::Message,
+            solana_program_runtime::{
+                profile_report::TransactionMetadata, protobuf::transaction_profile,
+            },
+        };
+
+        let report = ProfileReport::new(
+            TransactionMetadata {
+                signature: Some(Signature::default().to_string()),
+                slot: Some(42),
+                compute_units_consumed: 150,
+                error: None,
+            },
+            vec![],
+            vec![],
+        );
+        let stored = transaction_profile(&report).encode_to_vec();
+        assert_eq!(
+            decode_stored_profile(&stored),
+            Some(serde_json::to_value(&report).unwrap())
+        );
+        assert_eq!(decode_stored_profile(&[0xff, 0xff]), None);
+    }
+
+    #[test]
+    fn test_rpc_simulate_transaction_account_overrides() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        let RpcHandler {
+            ref meta, ref io, ..
+        } = rpc;
+
+        let payer = Keypair::new();
+        let tx = system_transaction::transfer(
+            &payer,
+            &solana_pubkey::new_rand(),
+            bank.get_minimum_balance_for_rent_exemption(0),
+            recent_blockhash,
+        );
+        let tx_serialized_encoded = bs58::encode(serialize(&tx).unwrap()).into_string();
+
+        // Simulation bank must be frozen
+        bank.freeze();
+
+        let simulate = |config: &str| {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0",
+                     "id":1,
+                     "method":"simulateTransaction",
+                     "params":["{tx_serialized_encoded}", {config}]
+                }}"#,
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            serde_json::from_str::<Value>(&res.expect("actual response"))
+                .expect("actual response deserialization")
+        };
+        let account_overrides = |data: &str| {
+            format!(
+                r#"{{ "accountOverrides": {{ "{}": {{
+                    "lamports": 1000000000,
+                    "data": ["{data}", "base64"],
+                    "owner": "{}",
+                    "executable": false,
+                    "rentEpoch": 0
+                }} }} }}"#,
+                payer.pubkey(),
+                system_program::id(),
+            )
+        };
+
+        // The payer does not exist without the override
+        let result = simulate("{}");
+        assert_eq!(result["result"]["value"]["err"], json!("AccountNotFound"));
+
+        let result = simulate(&account_overrides(""));
+        assert_eq!(result["result"]["value"]["err"], Value::Null);
+        assert_eq!(bank.get_balance(&payer.pubkey()), 0);
+
+        let result = simulate(&account_overrides("not base64"));
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
+    #[test]
+    fn test_rpc_simulate_transaction_account_diffs() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        let RpcHandler {
+            ref meta, ref io, ..
+        } = rpc;
+
+        let payer = Keypair::new();
+        let recipient = solana_pubkey::new_rand();
+        let amount = bank.get_minimum_balance_for_rent_exemption(0);
+        let tx = system_transaction::transfer(&payer, &recipient, amount, recent_blockhash);
+        let tx_serialized_encoded = bs58::encode(serialize(&tx).unwrap()).into_string();
+
+        // Simulation bank must be frozen
+        bank.freeze();
+
+        let simulate = |config: Value| {
+            let req = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "simulateTransaction",
+                "params": [tx_serialized_encoded, config],
+            });
+            let res = io.handle_request_sync(&req.to_string(), meta.clone());
+            serde_json::from_str::<Value>(&res.expect("actual response"))
+                .expect("actual response deserialization")
+        };
+        let payer_override = json!({
+            payer.pubkey().to_string(): {
+                "lamports": 1_000_000_000,
+                "data": ["", "base64"],
+                "owner": system_program::id().to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+            }
+        });
+
+        // The diffs are only returned if they were requested
+        let result = simulate(json!({ "accountOverrides": payer_override }));
+        assert_eq!(result["result"]["value"]["err"], Value::Null);
+        assert_eq!(result["result"]["value"].get("accountDiffs"), None);
+
+        // The diffs of the payer start from its override, the recipient did not exist
+        let result = simulate(json!({
+            "accountOverrides": payer_override,
+            "accountDiffs": { "encoding": "base64" },
+        }));
+        assert_eq!(result["result"]["value"]["err"], Value::Null);
+        let account_diffs = result["result"]["value"]["accountDiffs"]
+            .as_array()
+            .unwrap();
+        assert_eq!(account_diffs.len(), 2);
+        assert_eq!(
+            account_diffs[0]["pubkey"],
+            json!(payer.pubkey().to_string())
+        );
+        assert_eq!(account_diffs[0]["pre"]["lamports"], json!(1_000_000_000));
+        assert_eq!(
+            account_diffs[0]["post"]["lamports"],
+            json!(1_000_000_000 - amount - 5_000)
+        );
+        assert_eq!(account_diffs[1]["pubkey"], json!(recipient.to_string()));
+        assert_eq!(account_diffs[1]["pre"], Value::Null);
+        assert_eq!(account_diffs[1]["post"]["lamports"], json!(amount));
+        assert_eq!(bank.get_balance(&recipient), 0);
+
+        // Failed simulations change nothing
+        let result = simulate(json!({ "accountDiffs": {} }));
+        assert_eq!(result["result"]["value"]["err"], json!("AccountNotFound"));
+        assert_eq!(result["result"]["value"]["accountDiffs"], json!([]));
+
+        let result = simulate(json!({ "accountDiffs": { "encoding": "base58" } }));
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
+    #[test]
+    fn test_rpc_simulate_bundle() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        let RpcHandler {
+            ref meta, ref io, ..
+        } = rpc;
+
+        let keypair = Keypair::new();
+        let rent_exempt_amount = bank.get_minimum_balance_for_rent_exemption(0);
+        let fund = system_transaction::transfer(
+            &rpc.mint_keypair,
+            &keypair.pubkey(),
+            rent_exempt_amount * 2,
+            recent_blockhash,
+        );
+        let spend = system_transaction::transfer(
+            &keypair,
+            &solana_pubkey::new_rand(),
+            rent_exempt_amount,
+            recent_blockhash,
+        );
+        let fund = bs58::encode(serialize(&fund).unwrap()).into_string();
+        let spend = bs58::encode(serialize(&spend).unwrap()).into_string();
+
+        // Simulation bank must be frozen
+        bank.freeze();
+
+        let simulate = |transactions: &[&String]| {
+            let req = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "simulateBundle",
+                "params": [transactions, { "includeProfile": true }],
+            });
+            let res = io.handle_request_sync(&req.to_string(), meta.clone());
+            serde_json::from_str::<Value>(&res.expect("actual response"))
+                .expect("actual response deserialization")
+        };
+
+        let result = simulate(&[&fund, &spend]);
+        let value = result["result"]["value"].as_array().unwrap();
+        assert_eq!(value.len(), 2);
+        for transaction in value {
+            assert_eq!(transaction["err"], Value::Null);
+            assert_eq!(
+                transaction["profile"]["instructions"][0]["program_id"],
+                system_program::id().to_string()
+            );
+        }
+        assert_eq!(bank.get_balance(&keypair.pubkey()), 0);
+
+        // The bundle stops at the first transaction which fails
+        let result = simulate(&[&spend, &fund]);
+        let value = result["result"]["value"].as_array().unwrap();
+        assert_eq!(value.len(), 1);
+        assert_eq!(value[0]["err"], json!("AccountNotFound"));
+
+        let result = simulate(&[&fund; MAX_SIMULATE_BUNDLE_TRANSACTIONS + 1]);
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
     #[test]
     #[should_panic(expected = "simulation bank must be frozen")]
     fn test_rpc_simulate_transaction_panic_on_unfrozen_bank() {
@@ -6858,6 +7687,7 @@ pub mod tests {
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
             Arc::new(PrioritizationFeeCache::default()),
+            None,
             runtime.clone(),
         );
 
@@ -7161,6 +7991,7 @@ pub mod tests {
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
             Arc::new(PrioritizationFeeCache::default()),
+            None,
             runtime,
         );
 
@@ -8863,6 +9694,7 @@ pub mod tests {
             Arc::new(LeaderScheduleCache::default()),
             max_complete_transaction_status_slot,
             Arc::new(PrioritizationFeeCache::default()),
+            None,
             service_runtime(rpc_threads, rpc_blocking_threads, rpc_niceness_adj),
         );
 
@@ -9202,6 +10034,43 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_rpc_get_program_compute_stats() {
+        let mut rpc = RpcHandler::start();
+        let request = create_test_request("getProgramComputeStats", None);
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidRequest.code());
+
+        let profile_histograms = Arc::new(ProfileHistogramRegistry::default());
+        rpc.meta.profile_histograms = Some(profile_histograms.clone());
+        let program_id = Pubkey::new_unique();
+        profile_histograms.record_transaction(&[
+            format!("Program {program_id} invoke [1]"),
+            "Program profile: v3 section 0 10 700 - 0 - deserialize".to_string(),
+            format!("Program {program_id} consumed 1000 of 1400 compute units"),
+            format!("Program {program_id} success"),
+        ]);
+
+        let request = create_test_request(
+            "getProgramComputeStats",
+            Some(json!([{ "programIds": [program_id.to_string()], "windowSecs": 60 }])),
+        );
+        let stats: RpcProgramComputeStats = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(stats.window_secs, 60);
+        assert_eq!(stats.programs.len(), 1);
+        assert_eq!(stats.programs[0].program_id, program_id.to_string());
+        assert_eq!(stats.programs[0].total_cu, 1000);
+        assert_eq!(stats.programs[0].sections[0].name, "deserialize");
+        assert_eq!(stats.programs[0].sections[0].total_cu, 700);
+
+        let request = create_test_request(
+            "getProgramComputeStats",
+            Some(json!([{ "limit": MAX_PROGRAM_COMPUTE_STATS_PROGRAMS + 1 }])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+    }
+
     #[test]
     fn test_rpc_get_recent_prioritization_fees() {
         fn wait_for_cache_blocks(cache: &PrioritizationFeeCache, num_blocks: usize) {
@@ -7,10 +7,11 @@ use {
         rpc_pubsub_service::PubSubConfig,
         rpc_subscription_tracker::{
             AccountSubscriptionParams, BlockSubscriptionKind, BlockSubscriptionParams,
-            LogsSubscriptionKind, LogsSubscriptionParams, ProgramSubscriptionParams,
-            SignatureSubscriptionParams, SubscriptionControl, SubscriptionId, SubscriptionParams,
-            SubscriptionToken,
+            LogsSubscriptionKind, LogsSubscriptionParams, ProfileSubscriptionParams,
+            ProgramSubscriptionParams, SignatureSubscriptionParams, SubscriptionControl,
+            SubscriptionId, SubscriptionParams, SubscriptionToken,
         },
+        transaction_profile::negotiate_profile_version,
     },
     dashmap::DashMap,
     jsonrpc_core::{Error, ErrorCode, Result},
@@ -22,12 +23,12 @@ use {
     solana_rpc_client_api::{
         config::{
             RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
-            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
-            RpcTransactionLogsFilter,
+            RpcProfileSubscribeConfig, RpcProfileSubscribeFilter, RpcProgramAccountsConfig,
+            RpcSignatureSubscribeConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
         },
         response::{
             Response as RpcResponse, RpcBlockUpdate, RpcKeyedAccount, RpcLogsResponse,
-            RpcSignatureResult, RpcVersionInfo, RpcVote, SlotInfo, SlotUpdate,
+            RpcProfileResponse, RpcSignatureResult, RpcVersionInfo, RpcVote, SlotInfo, SlotUpdate,
         },
     },
     solana_signature::Signature,
@@ -127,6 +128,32 @@ pub trait RpcSolPubSub {
         id: PubSubSubscriptionId,
     ) -> Result<bool>;
 
+    // Get the profiles reported by programs in transactions that reference the specified program
+    #[pubsub(
+        subscription = "profileNotification",
+        subscribe,
+        name = "profileSubscribe"
+    )]
+    fn profile_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<RpcProfileResponse>>,
+        filter: RpcProfileSubscribeFilter,
+        config: Option<RpcProfileSubscribeConfig>,
+    );
+
+    // Unsubscribe from profile notification subscription.
+    #[pubsub(
+        subscription = "profileNotification",
+        unsubscribe,
+        name = "profileUnsubscribe"
+    )]
+    fn profile_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
     // Get notification when signature is verified
     // Accepts signature parameter as base-58 encoded string
     #[pubsub(
@@ -295,6 +322,19 @@ mod internal {
         #[rpc(name = "logsUnsubscribe")]
         fn logs_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
 
+        // Get the profiles reported by programs in transactions that reference the specified
+        // program
+        #[rpc(name = "profileSubscribe")]
+        fn profile_subscribe(
+            &self,
+            filter: RpcProfileSubscribeFilter,
+            config: Option<RpcProfileSubscribeConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from profile notification subscription.
+        #[rpc(name = "profileUnsubscribe")]
+        fn profile_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
         // Get notification when signature is verified
         // Accepts signature parameter as base-58 encoded string
         #[rpc(name = "signatureSubscribe")]
@@ -508,6 +548,32 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         self.unsubscribe(id)
     }
 
+    fn profile_subscribe(
+        &self,
+        filter: RpcProfileSubscribeFilter,
+        config: Option<RpcProfileSubscribeConfig>,
+    ) -> Result<SubscriptionId> {
+        let RpcProfileSubscribeConfig {
+            commitment,
+            max_supported_profile_version,
+        } = config.unwrap_or_default();
+        let params = ProfileSubscriptionParams {
+            program_id: match filter {
+                RpcProfileSubscribeFilter::All => None,
+                RpcProfileSubscribeFilter::Program(program_id) => {
+                    Some(param::<Pubkey>(&program_id, "program")?)
+                }
+            },
+            version: negotiate_profile_version(max_supported_profile_version)?,
+            commitment: commitment.unwrap_or_default(),
+        };
+        self.subscribe(SubscriptionParams::Profile(params))
+    }
+
+    fn profile_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
     fn signature_subscribe(
         &self,
         signature_str: String,
@@ -130,6 +130,7 @@ const METRICS_REPORT_INTERVAL_MS: u64 = 10_000;
 struct SentNotificationStats {
     num_account: AtomicUsize,
     num_logs: AtomicUsize,
+    num_profile: AtomicUsize,
     num_program: AtomicUsize,
     num_signature: AtomicUsize,
     num_slot: AtomicUsize,
@@ -156,6 +157,11 @@ impl SentNotificationStats {
                     self.num_logs.swap(0, Ordering::Relaxed) as i64,
                     i64
                 ),
+                (
+                    "num_profile",
+                    self.num_profile.swap(0, Ordering::Relaxed) as i64,
+                    i64
+                ),
                 (
                     "num_program",
                     self.num_program.swap(0, Ordering::Relaxed) as i64,
@@ -219,6 +225,9 @@ fn increment_sent_notification_stats(
         SubscriptionParams::Logs(_) => {
             stats.num_logs.fetch_add(1, Ordering::Relaxed);
         }
+        SubscriptionParams::Profile(_) => {
+            stats.num_profile.fetch_add(1, Ordering::Relaxed);
+        }
         SubscriptionParams::Program(_) => {
             stats.num_program.fetch_add(1, Ordering::Relaxed);
         }
@@ -5,6 +5,7 @@ use {
         cluster_tpu_info::ClusterTpuInfo,
         max_slots::MaxSlots,
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
+        profile_histogram::ProfileHistogramRegistry,
         rpc::{rpc_accounts::*, rpc_accounts_scan::*, rpc_bank::*, rpc_full::*, rpc_minimal::*, *},
         rpc_cache::LargestAccountsCache,
         rpc_health::*,
@@ -491,6 +492,7 @@ pub struct JsonRpcServiceConfig<'a> {
     pub leader_schedule_cache: Arc<LeaderScheduleCache>,
     pub max_complete_transaction_status_slot: Arc<AtomicU64>,
     pub prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+    pub profile_histograms: Option<Arc<ProfileHistogramRegistry>>,
     pub client_option: ClientOption<'a>,
 }
 
@@ -543,6 +545,7 @@ impl JsonRpcService {
                     client.clone(),
                     config.max_complete_transaction_status_slot,
                     config.prioritization_fee_cache,
+                    config.profile_histograms,
                     runtime,
                 )?;
                 Ok(json_rpc_service)
@@ -593,6 +596,7 @@ impl JsonRpcService {
                     client,
                     config.max_complete_transaction_status_slot,
                     config.prioritization_fee_cache,
+                    config.profile_histograms,
                     runtime,
                 )?;
                 Ok(json_rpc_service)
@@ -623,6 +627,7 @@ impl JsonRpcService {
         connection_cache: Arc<ConnectionCache>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+        profile_histograms: Option<Arc<ProfileHistogramRegistry>>,
     ) -> Result<Self, String> {
         let runtime = service_runtime(
             config.rpc_threads,
@@ -670,6 +675,7 @@ impl JsonRpcService {
             client.clone(),
             max_complete_transaction_status_slot,
             prioritization_fee_cache,
+            profile_histograms,
             runtime,
         )?;
         Ok(json_rpc_service)
@@ -704,6 +710,7 @@ impl JsonRpcService {
         client: Client,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+        profile_histograms: Option<Arc<ProfileHistogramRegistry>>,
         runtime: Arc<TokioRuntime>,
     ) -> Result<Self, String> {
         info!("rpc bound to {rpc_addr:?}");
@@ -796,6 +803,7 @@ impl JsonRpcService {
             leader_schedule_cache,
             max_complete_transaction_status_slot,
             prioritization_fee_cache,
+            profile_histograms,
             Arc::clone(&runtime),
         );
 
@@ -1012,6 +1020,7 @@ mod tests {
             connection_cache,
             Arc::new(AtomicU64::default()),
             Arc::new(PrioritizationFeeCache::default()),
+            None,
         )
         .expect("assume successful JsonRpcService start");
         let thread = rpc_service.thread_hdl.thread();
@@ -45,6 +45,7 @@ pub enum SubscriptionParams {
     Account(AccountSubscriptionParams),
     Block(BlockSubscriptionParams),
     Logs(LogsSubscriptionParams),
+    Profile(ProfileSubscriptionParams),
     Program(ProgramSubscriptionParams),
     Signature(SignatureSubscriptionParams),
     Slot,
@@ -58,6 +59,7 @@ impl SubscriptionParams {
         match self {
             SubscriptionParams::Account(_) => "accountNotification",
             SubscriptionParams::Logs(_) => "logsNotification",
+            SubscriptionParams::Profile(_) => "profileNotification",
             SubscriptionParams::Program(_) => "programNotification",
             SubscriptionParams::Signature(_) => "signatureNotification",
             SubscriptionParams::Slot => "slotNotification",
@@ -72,6 +74,7 @@ impl SubscriptionParams {
         match self {
             SubscriptionParams::Account(params) => Some(params.commitment),
             SubscriptionParams::Logs(params) => Some(params.commitment),
+            SubscriptionParams::Profile(params) => Some(params.commitment),
             SubscriptionParams::Program(params) => Some(params.commitment),
             SubscriptionParams::Signature(params) => Some(params.commitment),
             SubscriptionParams::Block(params) => Some(params.commitment),
@@ -87,6 +90,7 @@ impl SubscriptionParams {
             SubscriptionParams::Account(params) => &params.commitment,
             SubscriptionParams::Block(params) => &params.commitment,
             SubscriptionParams::Logs(params) => &params.commitment,
+            SubscriptionParams::Profile(params) => &params.commitment,
             SubscriptionParams::Program(params) => &params.commitment,
             SubscriptionParams::Signature(params) => &params.commitment,
             SubscriptionParams::Root
@@ -102,6 +106,7 @@ impl SubscriptionParams {
             SubscriptionParams::Account(params) => &params.commitment,
             SubscriptionParams::Block(params) => &params.commitment,
             SubscriptionParams::Logs(params) => &params.commitment,
+            SubscriptionParams::Profile(params) => &params.commitment,
             SubscriptionParams::Program(params) => &params.commitment,
             SubscriptionParams::Signature(params) => &params.commitment,
             SubscriptionParams::Root
@@ -160,6 +165,25 @@ pub enum LogsSubscriptionKind {
     Single(Pubkey),
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProfileSubscriptionParams {
+    /// Only notify transactions which reference this program
+    pub program_id: Option<Pubkey>,
+    /// Negotiated profile schema version
+    pub version: u8,
+    pub commitment: CommitmentConfig,
+}
+
+impl ProfileSubscriptionParams {
+    /// Profiles are reconstructed from the logs of the transactions they were reported in
+    pub(crate) fn logs_kind(&self) -> LogsSubscriptionKind {
+        match self.program_id {
+            Some(program_id) => LogsSubscriptionKind::Single(program_id),
+            None => LogsSubscriptionKind::All,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ProgramSubscriptionParams {
     pub pubkey: Pubkey,
@@ -303,6 +327,17 @@ impl SubscriptionControl {
         })
     }
 
+    #[cfg(test)]
+    pub fn profile_subscribed(&self, program_id: Option<&Pubkey>) -> bool {
+        self.0.subscriptions.iter().any(|item| {
+            if let SubscriptionParams::Profile(params) = item.key() {
+                params.program_id.as_ref() == program_id
+            } else {
+                false
+            }
+        })
+    }
+
     #[cfg(test)]
     pub fn signature_subscribed(&self, signature: &Signature) -> bool {
         self.0.subscriptions.iter().any(|item| {
@@ -357,8 +392,8 @@ struct LogsSubscriptionsIndex {
 }
 
 impl LogsSubscriptionsIndex {
-    fn add(&mut self, params: &LogsSubscriptionParams) {
-        match params.kind {
+    fn add(&mut self, kind: &LogsSubscriptionKind) {
+        match *kind {
             LogsSubscriptionKind::All => self.all_count += 1,
             LogsSubscriptionKind::AllWithVotes => self.all_with_votes_count += 1,
             LogsSubscriptionKind::Single(key) => {
@@ -368,8 +403,8 @@ impl LogsSubscriptionsIndex {
         self.update_config();
     }
 
-    fn remove(&mut self, params: &LogsSubscriptionParams) {
-        match params.kind {
+    fn remove(&mut self, kind: &LogsSubscriptionKind) {
+        match *kind {
             LogsSubscriptionKind::All => self.all_count -= 1,
             LogsSubscriptionKind::AllWithVotes => self.all_with_votes_count -= 1,
             LogsSubscriptionKind::Single(key) => match self.single_count.entry(key) {
@@ -418,9 +453,9 @@ impl LogsSubscriptionsIndex {
 pub struct SubscriptionsTracker {
     logs_subscriptions_index: LogsSubscriptionsIndex,
     by_signature: HashMap<Signature, HashMap<SubscriptionId, Arc<SubscriptionInfo>>>,
-    // Accounts, logs, programs, signatures (not gossip)
+    // Accounts, logs, profiles, programs, signatures (not gossip)
     commitment_watchers: HashMap<SubscriptionId, Arc<SubscriptionInfo>>,
-    // Accounts, logs, programs, signatures (gossip)
+    // Accounts, logs, profiles, programs, signatures (gossip)
     gossip_watchers: HashMap<SubscriptionId, Arc<SubscriptionInfo>>,
     // Slots, slots updates, roots, votes.
     node_progress_watchers: HashMap<SubscriptionParams, Arc<SubscriptionInfo>>,
@@ -457,7 +492,10 @@ impl SubscriptionsTracker {
         });
         match &params {
             SubscriptionParams::Logs(params) => {
-                self.logs_subscriptions_index.add(params);
+                self.logs_subscriptions_index.add(&params.kind);
+            }
+            SubscriptionParams::Profile(params) => {
+                self.logs_subscriptions_index.add(&params.logs_kind());
             }
             SubscriptionParams::Signature(params) => {
                 self.by_signature
@@ -483,7 +521,10 @@ impl SubscriptionsTracker {
     pub fn unsubscribe(&mut self, params: SubscriptionParams, id: SubscriptionId) {
         match &params {
             SubscriptionParams::Logs(params) => {
-                self.logs_subscriptions_index.remove(params);
+                self.logs_subscriptions_index.remove(&params.kind);
+            }
+            SubscriptionParams::Profile(params) => {
+                self.logs_subscriptions_index.remove(&params.logs_kind());
             }
             SubscriptionParams::Signature(params) => {
                 if let Entry::Occupied(mut entry) = self.by_signature.entry(params.signature) {
@@ -8,10 +8,11 @@ use {
         rpc_pubsub_service::PubSubConfig,
         rpc_subscription_tracker::{
             AccountSubscriptionParams, BlockSubscriptionKind, BlockSubscriptionParams,
-            LogsSubscriptionKind, LogsSubscriptionParams, ProgramSubscriptionParams,
-            SignatureSubscriptionParams, SubscriptionControl, SubscriptionId, SubscriptionInfo,
-            SubscriptionParams, SubscriptionsTracker,
+            LogsSubscriptionKind, LogsSubscriptionParams, ProfileSubscriptionParams,
+            ProgramSubscriptionParams, SignatureSubscriptionParams, SubscriptionControl,
+            SubscriptionId, SubscriptionInfo, SubscriptionParams, SubscriptionsTracker,
         },
+        transaction_profile::{instruction_profiles_from_logs, transaction_budget_from_logs},
     },
     crossbeam_channel::{Receiver, RecvTimeoutError, SendError, Sender},
     itertools::Either,
@@ -27,8 +28,9 @@ use {
     solana_pubkey::Pubkey,
     solana_rpc_client_api::response::{
         ProcessedSignatureResult, ReceivedSignatureResult, Response as RpcResponse, RpcBlockUpdate,
-        RpcBlockUpdateError, RpcKeyedAccount, RpcLogsResponse, RpcResponseContext,
-        RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
+        RpcBlockUpdateError, RpcKeyedAccount, RpcLogsResponse, RpcProfileResponse,
+        RpcResponseContext, RpcSignatureResult, RpcTransactionProfile, RpcVote, SlotInfo,
+        SlotUpdate,
     },
     solana_runtime::{
         bank::{Bank, TransactionLogInfo},
@@ -79,6 +81,19 @@ fn get_transaction_logs(
     }
     logs
 }
+
+fn get_profiled_transaction_logs(
+    bank: &Bank,
+    params: &ProfileSubscriptionParams,
+) -> Option<Vec<TransactionLogInfo>> {
+    let mut logs = bank.get_transaction_logs(params.program_id.as_ref());
+    // Votes are executed by a builtin program which never reports a profile
+    if let Some(logs) = &mut logs {
+        logs.retain(|log| !log.is_vote);
+    }
+    logs
+}
+
 #[derive(Debug)]
 pub struct TimestampedNotificationEntry {
     pub entry: NotificationEntry,
@@ -454,6 +469,31 @@ fn filter_logs_results(
     (responses, last_notified_slot)
 }
 
+fn filter_profile_results(
+    logs: Option<Vec<TransactionLogInfo>>,
+    params: &ProfileSubscriptionParams,
+    last_notified_slot: Slot,
+    bank: Arc<Bank>,
+) -> (impl Iterator<Item = RpcProfileResponse>, Slot) {
+    let version = params.version;
+    let slot = bank.slot();
+    let responses = logs.into_iter().flatten().filter_map(move |log| {
+        let instructions = instruction_profiles_from_logs(&log.log_messages, version);
+        // Transactions in which no program reported a profile are not notified
+        (!instructions.is_empty()).then(|| RpcProfileResponse {
+            signature: log.signature.to_string(),
+            err: log.result.err().map(Into::into),
+            profile: RpcTransactionProfile {
+                version,
+                slot,
+                instructions,
+                budget: transaction_budget_from_logs(&log.log_messages, version),
+            },
+        })
+    });
+    (responses, last_notified_slot)
+}
+
 fn initial_last_notified_slot(
     params: &SubscriptionParams,
     bank_forks: &RwLock<BankForks>,
@@ -930,6 +970,9 @@ impl RpcSubscriptions {
         let num_logs_found = AtomicUsize::new(0);
         let num_logs_notified = AtomicUsize::new(0);
 
+        let num_profiles_found = AtomicUsize::new(0);
+        let num_profiles_notified = AtomicUsize::new(0);
+
         let num_programs_found = AtomicUsize::new(0);
         let num_programs_notified = AtomicUsize::new(0);
 
@@ -1074,6 +1117,25 @@ impl RpcSubscriptions {
                         }
                     }
                 }
+                SubscriptionParams::Profile(params) => {
+                    num_profiles_found.fetch_add(1, Ordering::Relaxed);
+                    if let Some(slot) = slot {
+                        let notified = check_commitment_and_notify(
+                            params,
+                            subscription,
+                            bank_forks,
+                            slot,
+                            get_profiled_transaction_logs,
+                            filter_profile_results,
+                            notifier,
+                            false,
+                        );
+
+                        if notified {
+                            num_profiles_notified.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                }
                 SubscriptionParams::Program(params) => {
                     num_programs_found.fetch_add(1, Ordering::Relaxed);
                     if let Some(slot) = slot {
@@ -1124,18 +1186,21 @@ impl RpcSubscriptions {
 
         let total_notified = num_accounts_notified.load(Ordering::Relaxed)
             + num_logs_notified.load(Ordering::Relaxed)
+            + num_profiles_notified.load(Ordering::Relaxed)
             + num_programs_notified.load(Ordering::Relaxed)
             + num_signatures_notified.load(Ordering::Relaxed);
         let total_ms = total_time.as_ms();
         if total_notified > 0 || total_ms > 10 {
             debug!(
-                "notified({}): accounts: {} / {} logs: {} / {} programs: {} / {} signatures: {} / \
-                 {}",
+                "notified({}): accounts: {} / {} logs: {} / {} profiles: {} / {} programs: {} / {} \
+                 signatures: {} / {}",
                 source,
                 num_accounts_found.load(Ordering::Relaxed),
                 num_accounts_notified.load(Ordering::Relaxed),
                 num_logs_found.load(Ordering::Relaxed),
                 num_logs_notified.load(Ordering::Relaxed),
+                num_profiles_found.load(Ordering::Relaxed),
+                num_profiles_notified.load(Ordering::Relaxed),
                 num_programs_found.load(Ordering::Relaxed),
                 num_programs_notified.load(Ordering::Relaxed),
                 num_signatures_found.load(Ordering::Relaxed),
@@ -1164,6 +1229,16 @@ impl RpcSubscriptions {
                     num_logs_notified.load(Ordering::Relaxed),
                     i64
                 ),
+                (
+                    "num_profile_subscriptions",
+                    num_profiles_found.load(Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "num_profiles_notified",
+                    num_profiles_notified.load(Ordering::Relaxed),
+                    i64
+                ),
                 (
                     "num_program_subscriptions",
                     num_programs_found.load(Ordering::Relaxed),
@@ -1227,8 +1302,8 @@ pub(crate) mod tests {
         solana_message::Message,
         solana_rpc_client_api::config::{
             RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
-            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
-            RpcTransactionLogsFilter,
+            RpcProfileSubscribeFilter, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
+            RpcTransactionLogsConfig, RpcTransactionLogsFilter,
         },
         solana_runtime::{
             commitment::BlockCommitment,
@@ -3009,6 +3084,105 @@ pub(crate) mod tests {
         assert!(!subscriptions.control.logs_subscribed(Some(&alice.pubkey())));
     }
 
+    #[test]
+    #[serial]
+    fn test_profile_subscribe() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let exit = Arc::new(AtomicBool::new(false));
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::default());
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            exit,
+            max_complete_transaction_status_slot,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+        ));
+
+        let program_id = solana_pubkey::new_rand();
+        let (rpc, _receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc
+            .profile_subscribe(
+                RpcProfileSubscribeFilter::Program(program_id.to_string()),
+                None,
+            )
+            .unwrap();
+        assert!(subscriptions.control.profile_subscribed(Some(&program_id)));
+        rpc.block_until_processed(&subscriptions);
+
+        // Profiles are reconstructed from the logs collected for the program
+        assert!(bank_forks
+            .read()
+            .unwrap()
+            .root_bank()
+            .transaction_log_collector_config
+            .read()
+            .unwrap()
+            .mentioned_addresses
+            .contains(&program_id));
+
+        rpc.profile_unsubscribe(sub_id).unwrap();
+        assert!(!subscriptions.control.profile_subscribed(Some(&program_id)));
+    }
+
+    #[test]
+    fn test_filter_profile_results() {
+        let bank = Arc::new(Bank::default_for_tests());
+        let profiled = TransactionLogInfo {
+            signature: Signature::new_unique(),
+            result: Ok(()),
+            is_vote: false,
+            log_messages: vec![
+                "Program Prog111 invoke [1]".to_string(),
+                "Program Prog111 consumed 500 of 1000 compute units".to_string(),
+                "Program profile: v3 section 0 10 400 64 32 bulk-closed handle transfer"
+                    .to_string(),
+                "Program Prog111 success".to_string(),
+                "Program profile: v4 budget 1000 requested 600 65536 42".to_string(),
+            ],
+        };
+        let unprofiled = TransactionLogInfo {
+            signature: Signature::new_unique(),
+            result: Ok(()),
+            is_vote: false,
+            log_messages: vec![
+                "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+                "Program 11111111111111111111111111111111 success".to_string(),
+            ],
+        };
+        let params = ProfileSubscriptionParams {
+            program_id: None,
+            version: 4,
+            commitment: CommitmentConfig::processed(),
+        };
+
+        let (responses, last_notified_slot) = filter_profile_results(
+            Some(vec![profiled.clone(), unprofiled]),
+            &params,
+            3,
+            bank.clone(),
+        );
+        let responses: Vec<_> = responses.collect();
+        assert_eq!(last_notified_slot, 3);
+        assert_eq!(responses.len(), 1);
+        let RpcProfileResponse {
+            signature,
+            err,
+            profile,
+        } = &responses[0];
+        assert_eq!(signature, &profiled.signature.to_string());
+        assert_eq!(err, &None);
+        assert_eq!(profile.version, 4);
+        assert_eq!(profile.slot, bank.slot());
+        assert_eq!(profile.instructions.len(), 1);
+        assert_eq!(profile.instructions[0].program_id, "Prog111");
+        assert_eq!(profile.instructions[0].entries[0].name, "handle transfer");
+        assert_eq!(profile.budget.unwrap().consumed_cu, 600);
+    }
+
     #[test]
     fn test_total_subscriptions() {
         let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
@@ -0,0 +1,503 @@
+//! Reconstructs the profiles reported by programs from a transaction's log messages for the
+//! `getTransactionProfile` RPC method.
+//!
+//! Besides the `Program profile:` lines of every supported format version, the legacy text
+//! sections printed by the `compute_fn!` pattern are recognized, so that transactions recorded
+//! before programs adopted the profiling syscalls can still be queried:
+//!
+//! ```notrust
+//! Program log: <name> {
+//! Program consumption: <remaining> units remaining
+//! ...
+//! Program consumption: <remaining> units remaining
+//! Program log:  } // <name>
+//! ```
+
+use {
+    jsonrpc_core::{Error, Result},
+    solana_program_runtime::profiling::{
+        ProfileEntry, ProfileEntryKind, ProfileLogLine, PROFILE_LOG_VERSION,
+    },
+    solana_rpc_client_api::response::{
+        RpcInstructionProfile, RpcProfileBudget, RpcProfileCounter, RpcProfileEntry,
+        RpcProfileHeapTag, MIN_RPC_TRANSACTION_PROFILE_VERSION,
+    },
+    std::collections::BTreeMap,
+};
+
+/// Pick the schema version to respond with given the highest version the caller understands
+pub fn negotiate_profile_version(max_supported_profile_version: Option<u8>) -> Result<u8> {
+    match max_supported_profile_version {
+        None => Ok(PROFILE_LOG_VERSION),
+        Some(version) if version < MIN_RPC_TRANSACTION_PROFILE_VERSION => {
+            Err(Error::invalid_params(format!(
+                "Profile version {version} is not supported, the oldest supported version is \
+                 {MIN_RPC_TRANSACTION_PROFILE_VERSION}"
+            )))
+        }
+        Some(version) => Ok(version.min(PROFILE_LOG_VERSION)),
+    }
+}
+
+const LEGACY_SECTION_START_SUFFIX: &str = " {";
+const LEGACY_SECTION_END_PREFIX: &str = "} // ";
+
+/// A legacy text section which has been opened but not closed yet
+struct LegacySection {
+    name: String,
+    depth: usize,
+    /// Remaining compute units reported by the first consumption line inside the section
+    start_remaining: Option<u64>,
+}
+
+/// A closed legacy text section, converted once the instruction's compute budget is known
+struct LegacyEntry {
+    name: String,
+    depth: usize,
+    start_remaining: u64,
+    end_remaining: u64,
+}
+
+/// Profile of an instruction which has been invoked but has not completed yet
+struct InstructionFrame {
+    profile: RpcInstructionProfile,
+    legacy_sections: Vec<LegacySection>,
+    legacy_entries: Vec<LegacyEntry>,
+    last_remaining: Option<u64>,
+}
+
+impl InstructionFrame {
+    /// Track the legacy text sections of a `Program log:` or `Program consumption:` line
+    fn legacy_log(&mut self, line: &str) {
+        if let Some(remaining) = line
+            .strip_prefix("Program consumption: ")
+            .and_then(|rest| rest.strip_suffix(" units remaining"))
+            .and_then(|remaining| remaining.parse::<u64>().ok())
+        {
+            if let Some(section) = self.legacy_sections.last_mut() {
+                section.start_remaining.get_or_insert(remaining);
+            }
+            self.last_remaining = Some(remaining);
+        } else if let Some(message) = line.strip_prefix("Program log: ") {
+            let message = message.trim();
+            if let Some(name) = message.strip_prefix(LEGACY_SECTION_END_PREFIX) {
+                self.end_legacy_section(name);
+            } else if let Some(name) = message.strip_suffix(LEGACY_SECTION_START_SUFFIX) {
+                self.legacy_sections.push(LegacySection {
+                    name: name.to_string(),
+                    depth: self.legacy_sections.len(),
+                    start_remaining: None,
+                });
+            }
+        }
+    }
+
+    fn end_legacy_section(&mut self, name: &str) {
+        let Some(position) = self
+            .legacy_sections
+            .iter()
+            .rposition(|section| section.name == name)
+        else {
+            return;
+        };
+        // Sections opened inside this one were never closed, drop them
+        let section = self.legacy_sections.drain(position..).next().unwrap();
+        if let (Some(start_remaining), Some(end_remaining)) =
+            (section.start_remaining, self.last_remaining)
+        {
+            self.legacy_entries.push(LegacyEntry {
+                name: section.name,
+                depth: section.depth,
+                start_remaining,
+                end_remaining,
+            });
+        }
+    }
+
+    /// Convert the legacy sections using the compute units available to the instruction, as
+    /// reported by its `consumed <n> of <budget> compute units` line
+    fn finish_legacy_entries(&mut self, budget: Option<u64>) {
+        for entry in self.legacy_entries.drain(..) {
+            let start_cu = budget
+                .map(|budget| budget.saturating_sub(entry.start_remaining))
+                .unwrap_or_default();
+            self.profile.entries.push(RpcProfileEntry {
+                kind: ProfileEntryKind::Section.as_str().to_string(),
+                name: entry.name,
+                depth: entry.depth,
+                start_cu,
+                consumed_cu: entry.start_remaining.saturating_sub(entry.end_remaining),
+                heap_bytes: None,
+                return_data_bytes: None,
+                bulk_closed: None,
+                attributes: BTreeMap::new(),
+            });
+        }
+    }
+}
+
+/// Parse the budget out of the rest of a `Program <id> consumed <n> of <budget> compute units`
+/// line
+fn consumed_budget(rest: &str) -> Option<u64> {
+    rest.strip_prefix("consumed ")?
+        .strip_suffix(" compute units")?
+        .split_once(" of ")?
+        .1
+        .parse()
+        .ok()
+}
+
+fn rpc_profile_entry(entry: ProfileEntry, version: u8) -> RpcProfileEntry {
+    let consumed_cu = entry.consumed();
+    RpcProfileEntry {
+        kind: entry.kind.as_str().to_string(),
+        name: entry.name,
+        depth: entry.depth,
+        start_cu: entry.start_cu,
+        consumed_cu,
+        heap_bytes: entry.heap_end,
+        return_data_bytes: (version >= 2).then_some(entry.return_data_bytes),
+        bulk_closed: (version >= 3).then_some(entry.bulk_closed),
+        attributes: entry.attributes,
+    }
+}
+
+/// Collect the profile of every instruction which reported one, in the order the instructions
+/// completed, using the fields of the given schema version
+pub fn instruction_profiles_from_logs(
+    log_messages: &[String],
+    version: u8,
+) -> Vec<RpcInstructionProfile> {
+    let mut stack: Vec<InstructionFrame> = Vec::new();
+    let mut profiles = Vec::new();
+    for line in log_messages {
+        if let Some(profile_line) = ProfileLogLine::parse(line) {
+            let Some(InstructionFrame { profile, .. }) = stack.last_mut() else {
+                continue;
+            };
+            match profile_line {
+                ProfileLogLine::Entry(entry) => {
+                    profile.entries.push(rpc_profile_entry(entry, version));
+                }
+                ProfileLogLine::Counter { id, value } => {
+                    profile.counters.push(RpcProfileCounter { id, value });
+                }
+                ProfileLogLine::Dropped(count) => profile.dropped_entries = count,
+                ProfileLogLine::HeapTag { name, bytes } => {
+                    profile.heap_tags.push(RpcProfileHeapTag { name, bytes });
+                }
+                ProfileLogLine::Attribute { key, value } => {
+                    if let Some(entry) = profile.entries.last_mut() {
+                        entry.attributes.insert(key, value);
+                    }
+                }
+                ProfileLogLine::Failed(reason) => {
+                    profile.failure = (version >= 5).then_some(reason);
+                }
+                // Logged after the last instruction, see `transaction_budget_from_logs`
+                ProfileLogLine::Budget(_) => {}
+            }
+            continue;
+        }
+        if let Some(frame) = stack.last_mut() {
+            frame.legacy_log(line);
+        }
+
+        let Some((program_id, rest)) = line
+            .strip_prefix("Program ")
+            .and_then(|line| line.split_once(' '))
+        else {
+            continue;
+        };
+        if let Some(stack_height) = rest
+            .strip_prefix("invoke [")
+            .and_then(|rest| rest.strip_suffix(']'))
+            .and_then(|stack_height| stack_height.parse().ok())
+        {
+            stack.push(InstructionFrame {
+                profile: RpcInstructionProfile {
+                    program_id: program_id.to_string(),
+                    stack_height,
+                    entries: Vec::new(),
+                    counters: Vec::new(),
+                    dropped_entries: 0,
+                    heap_tags: Vec::new(),
+                    failure: None,
+                },
+                legacy_sections: Vec::new(),
+                legacy_entries: Vec::new(),
+                last_remaining: None,
+            });
+        } else if let Some(budget) = consumed_budget(rest) {
+            if let Some(frame) = stack
+                .last_mut()
+                .filter(|frame| frame.profile.program_id == program_id)
+            {
+                frame.finish_legacy_entries(Some(budget));
+            }
+        } else if (rest == "success" || rest.starts_with("failed"))
+            && stack
+                .last()
+                .is_some_and(|frame| frame.profile.program_id == program_id)
+        {
+            let mut frame = stack.pop().unwrap();
+            frame.finish_legacy_entries(None);
+            let profile = frame.profile;
+            if !profile.entries.is_empty()
+                || !profile.counters.is_empty()
+                || profile.dropped_entries > 0
+            {
+                profiles.push(profile);
+            }
+        }
+    }
+    profiles
+}
+
+/// Find the compute budget a profiled transaction executed with, which is only part of schema
+/// versions 4 and later
+pub fn transaction_budget_from_logs(
+    log_messages: &[String],
+    version: u8,
+) -> Option<RpcProfileBudget> {
+    if version < 4 {
+        return None;
+    }
+    log_messages
+        .iter()
+        .rev()
+        .find_map(|line| match ProfileLogLine::parse(line)? {
+            ProfileLogLine::Budget(budget) => Some(RpcProfileBudget {
+                compute_unit_limit: budget.compute_unit_limit,
+                requested_compute_unit_limit: budget.requested_compute_unit_limit.is_some(),
+                requested_compute_units: budget
+                    .requested_compute_unit_limit
+                    .filter(|_| version >= 7),
+                consumed_cu: budget.consumed_cu,
+                heap_bytes: budget.heap_bytes,
+                prioritization_fee: budget.prioritization_fee,
+            }),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_rpc_client_api::response::RPC_TRANSACTION_PROFILE_VERSION};
+
+    #[test]
+    fn test_negotiate_profile_version() {
+        assert_eq!(
+            negotiate_profile_version(None).unwrap(),
+            PROFILE_LOG_VERSION
+        );
+        assert_eq!(negotiate_profile_version(Some(1)).unwrap(), 1);
+        assert_eq!(
+            negotiate_profile_version(Some(u8::MAX)).unwrap(),
+            PROFILE_LOG_VERSION
+        );
+        assert!(negotiate_profile_version(Some(0)).is_err());
+
+        // The client types must describe the schema produced by this node
+        assert_eq!(RPC_TRANSACTION_PROFILE_VERSION, PROFILE_LOG_VERSION);
+    }
+
+    #[test]
+    fn test_instruction_profiles_from_logs() {
+        let log_messages = [
+            "Program Outer111 invoke [1]",
+            "Program log: success",
+            "Program Inner111 invoke [2]",
+            "Program Inner111 consumed 100 of 1000 compute units",
+            "Program Inner111 success",
+            "Program Outer111 consumed 500 of 1000 compute units",
+            "Program profile: v3 section 0 10 400 64 32 bulk-closed handle transfer",
+            "Program profile: v3 attr amount 1 000",
+            "Program profile: v3 counter 1 5",
+            "Program profile: v3 heap 224 parse accounts",
+            "Program profile: v3 dropped 2",
+            "Program Outer111 success",
+            "Program profile: v4 budget 1000 requested 600 65536 42",
+        ]
+        .map(str::to_string);
+
+        let expected_entry = RpcProfileEntry {
+            kind: "section".to_string(),
+            name: "handle transfer".to_string(),
+            depth: 0,
+            start_cu: 10,
+            consumed_cu: 400,
+            heap_bytes: Some(64),
+            return_data_bytes: Some(32),
+            bulk_closed: Some(true),
+            attributes: BTreeMap::from([("amount".to_string(), "1 000".to_string())]),
+        };
+        assert_eq!(
+            instruction_profiles_from_logs(&log_messages, 3),
+            vec![RpcInstructionProfile {
+                program_id: "Outer111".to_string(),
+                stack_height: 1,
+                entries: vec![expected_entry.clone()],
+                counters: vec![RpcProfileCounter { id: 1, value: 5 }],
+                dropped_entries: 2,
+                heap_tags: vec![RpcProfileHeapTag {
+                    name: "parse accounts".to_string(),
+                    bytes: 224,
+                }],
+                failure: None,
+            }]
+        );
+
+        let expected_budget = RpcProfileBudget {
+            compute_unit_limit: 1_000,
+            requested_compute_unit_limit: true,
+            requested_compute_units: None,
+            consumed_cu: 600,
+            heap_bytes: 65_536,
+            prioritization_fee: 42,
+        };
+        assert_eq!(
+            transaction_budget_from_logs(&log_messages, 4),
+            Some(expected_budget)
+        );
+        assert_eq!(transaction_budget_from_logs(&log_messages, 3), None);
+        assert_eq!(
+            instruction_profiles_from_logs(&log_messages, 4),
+            instruction_profiles_from_logs(&log_messages, 3)
+        );
+
+        // The requested limit is only reported from version 7
+        let requested_log_messages =
+            ["Program profile: v7 budget 1000 2000000 600 65536 42".to_string()];
+        assert_eq!(
+            transaction_budget_from_logs(&requested_log_messages, 7),
+            Some(RpcProfileBudget {
+                requested_compute_units: Some(2_000_000),
+                ..expected_budget
+            })
+        );
+        assert_eq!(
+            transaction_budget_from_logs(&requested_log_messages, 6),
+            Some(expected_budget)
+        );
+
+        // Failed instructions report their partial profile
+        let failed_log_messages = [
+            "Program Outer111 invoke [1]",
+            "Program profile: v5 section 0 10 400 - 0 bulk-closed handle transfer",
+            "Program profile: v5 failed custom program error: 0x1",
+            "Program Outer111 failed: custom program error: 0x1",
+        ]
+        .map(str::to_string);
+        let failure = |version| {
+            instruction_profiles_from_logs(&failed_log_messages, version)
+                .first()
+                .unwrap()
+                .failure
+                .clone()
+        };
+        assert_eq!(failure(5).as_deref(), Some("custom program error: 0x1"));
+        assert_eq!(failure(4), None);
+
+        // Entries logged as JSON carry their attributes
+        let json_log_messages = [
+            "Program Outer111 invoke [1]",
+            concat!(
+                r#"Program profile: v6 json {"kind":"section","name":"handle transfer","#,
+                r#""depth":0,"start_cu":10,"consumed_cu":400,"heap_start":null,"heap_end":64,"#,
+                r#""return_data_bytes":32,"flags":["bulk-closed"],"#,
+                r#""attributes":{"amount":"1 000"}}"#,
+            ),
+            "Program Outer111 success",
+        ]
+        .map(str::to_string);
+        assert_eq!(
+            instruction_profiles_from_logs(&json_log_messages, 6)
+                .first()
+                .unwrap()
+                .entries,
+            vec![expected_entry.clone()]
+        );
+
+        // Older log format versions are accepted
+        let v1_log_messages = [
+            "Program Outer111 invoke [1]",
+            "Program profile: v1 section 0 10 400 64 handle transfer",
+            "Program Outer111 success",
+        ]
+        .map(str::to_string);
+        assert_eq!(
+            instruction_profiles_from_logs(&v1_log_messages, 3)
+                .first()
+                .unwrap()
+                .entries,
+            vec![RpcProfileEntry {
+                return_data_bytes: Some(0),
+                bulk_closed: Some(false),
+                attributes: BTreeMap::new(),
+                ..expected_entry.clone()
+            }]
+        );
+
+        // Older schema versions omit the fields added later
+        let profiles = instruction_profiles_from_logs(&log_messages, 1);
+        assert_eq!(
+            profiles.first().unwrap().entries,
+            vec![RpcProfileEntry {
+                return_data_bytes: None,
+                bulk_closed: None,
+                ..expected_entry
+            }]
+        );
+    }
+
+    #[test]
+    fn test_legacy_text_sections() {
+        let log_messages = [
+            "Program Legacy111 invoke [1]",
+            "Program log: deserialize {",
+            "Program consumption: 199000 units remaining",
+            "Program log: inner {",
+            "Program consumption: 198500 units remaining",
+            "Program Callee111 invoke [2]",
+            "Program log: callee {",
+            "Program Callee111 consumed 100 of 198000 compute units",
+            "Program Callee111 success",
+            "Program consumption: 197000 units remaining",
+            "Program log:  } // inner",
+            "Program consumption: 196000 units remaining",
+            "Program log:  } // deserialize",
+            "Program log: unclosed {",
+            "Program Legacy111 consumed 5000 of 200000 compute units",
+            "Program Legacy111 success",
+        ]
+        .map(str::to_string);
+
+        let section = |name: &str, depth, start_cu, consumed_cu| RpcProfileEntry {
+            kind: "section".to_string(),
+            name: name.to_string(),
+            depth,
+            start_cu,
+            consumed_cu,
+            heap_bytes: None,
+            return_data_bytes: None,
+            bulk_closed: None,
+            attributes: BTreeMap::new(),
+        };
+        assert_eq!(
+            instruction_profiles_from_logs(&log_messages, 3),
+            vec![RpcInstructionProfile {
+                program_id: "Legacy111".to_string(),
+                stack_height: 1,
+                entries: vec![
+                    section("inner", 1, 1_500, 1_500),
+                    section("deserialize", 0, 1_000, 3_000),
+                ],
+                counters: Vec::new(),
+                dropped_entries: 0,
+                heap_tags: Vec::new(),
+                failure: None,
+            }]
+        );
+    }
+}
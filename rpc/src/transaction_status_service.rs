@@ -4,7 +4,11 @@
 //! frozen banks it receives.
 
 use {
-    crate::transaction_notifier_interface::TransactionNotifierArc,
+    crate::{
+        profile_histogram::{ProfileHistogramRegistry, PROFILE_METRICS_REPORT_INTERVAL_SECS},
+        profile_sink_interface::{ProfileSink, ProfileSinkArc},
+        transaction_notifier_interface::TransactionNotifierArc,
+    },
     crossbeam_channel::{Receiver, RecvTimeoutError},
     itertools::izip,
     solana_clock::Slot,
@@ -12,6 +16,7 @@ use {
         blockstore::{Blockstore, BlockstoreError},
         blockstore_processor::{TransactionStatusBatch, TransactionStatusMessage},
     },
+    solana_program_runtime::profile_report::{ProfileReport, TransactionMetadata},
     solana_runtime::{
         bank::{Bank, KeyedRewardsAndNumPartitions},
         dependency_tracker::DependencyTracker,
@@ -27,10 +32,12 @@ use {
             Arc,
         },
         thread::{self, Builder, JoinHandle},
-        time::Duration,
+        time::{Duration, Instant},
     },
     thiserror::Error,
 };
+#[cfg(feature = "protobuf")]
+use {prost::Message, solana_program_runtime::protobuf::transaction_profile};
 
 #[derive(Error, Debug)]
 enum Error {
@@ -57,27 +64,44 @@ pub struct TransactionStatusService {
 impl TransactionStatusService {
     const SERVICE_NAME: &str = "TransactionStatusService";
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         transaction_status_receiver: Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         enable_rpc_transaction_history: bool,
         transaction_notifier: Option<TransactionNotifierArc>,
+        profile_histograms: Option<Arc<ProfileHistogramRegistry>>,
+        profile_sink: Option<ProfileSinkArc>,
         blockstore: Arc<Blockstore>,
         enable_extended_tx_metadata_storage: bool,
+        enable_profile_persistence: bool,
         depenency_tracker: Option<Arc<DependencyTracker>>,
         exit: Arc<AtomicBool>,
     ) -> Self {
+        if enable_profile_persistence && cfg!(not(feature = "protobuf")) {
+            warn!("Transaction profiles are not persisted without the protobuf feature");
+        }
         let thread_hdl = Builder::new()
             .name("solTxStatusWrtr".to_string())
             .spawn({
                 let transaction_status_receiver = transaction_status_receiver.clone();
                 move || {
                     info!("{} has started", Self::SERVICE_NAME);
+                    let mut last_profile_metrics_report = Instant::now();
                     loop {
                         if exit.load(Ordering::Relaxed) {
                             break;
                         }
 
+                        if let Some(profile_histograms) = profile_histograms.as_deref() {
+                            if last_profile_metrics_report.elapsed()
+                                >= Duration::from_secs(PROFILE_METRICS_REPORT_INTERVAL_SECS)
+                            {
+                                profile_histograms.report_metrics();
+                                last_profile_metrics_report = Instant::now();
+                            }
+                        }
+
                         let message = match transaction_status_receiver
                             .recv_timeout(Duration::from_secs(1))
                         {
@@ -96,8 +120,11 @@ impl TransactionStatusService {
                             &max_complete_transaction_status_slot,
                             enable_rpc_transaction_history,
                             transaction_notifier.clone(),
+                            profile_histograms.as_deref(),
+                            profile_sink.as_deref(),
                             &blockstore,
                             enable_extended_tx_metadata_storage,
+                            enable_profile_persistence,
                             depenency_tracker.clone(),
                         ) {
                             Ok(_) => {}
@@ -119,13 +146,17 @@ impl TransactionStatusService {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn write_transaction_status_batch(
         transaction_status_message: TransactionStatusMessage,
         max_complete_transaction_status_slot: &Arc<AtomicU64>,
         enable_rpc_transaction_history: bool,
         transaction_notifier: Option<TransactionNotifierArc>,
+        profile_histograms: Option<&ProfileHistogramRegistry>,
+        profile_sink: Option<&(dyn ProfileSink + Sync + Send)>,
         blockstore: &Blockstore,
         enable_extended_tx_metadata_storage: bool,
+        enable_profile_persistence: bool,
         dependency_tracker: Option<Arc<DependencyTracker>>,
     ) -> Result<()> {
         match transaction_status_message {
@@ -172,10 +203,30 @@ impl TransactionStatusService {
                         inner_instructions,
                         return_data,
                         executed_units,
+                        instruction_executed_units,
                         fee_details,
+                        profiling_results,
+                        program_load_profiles,
                         ..
                     } = committed_tx;
 
+                    let profile = profiling_results
+                        .filter(|instructions| {
+                            enable_profile_persistence && !instructions.is_empty()
+                        })
+                        .and_then(|instructions| {
+                            let report = ProfileReport::new(
+                                TransactionMetadata {
+                                    signature: Some(transaction.signature().to_string()),
+                                    slot: Some(slot),
+                                    compute_units_consumed: executed_units,
+                                    error: status.as_ref().err().map(ToString::to_string),
+                                },
+                                instructions,
+                                program_load_profiles.unwrap_or_default(),
+                            );
+                            encode_stored_profile(&report)
+                        });
                     let fee = fee_details.total_fee();
                     let inner_instructions = inner_instructions.map(|inner_instructions| {
                         map_inner_instructions(inner_instructions).collect()
@@ -199,6 +250,8 @@ impl TransactionStatusService {
                         return_data,
                         compute_units_consumed: Some(executed_units),
                         cost_units: cost,
+                        profile,
+                        instruction_compute_units_consumed: Some(instruction_executed_units),
                     };
 
                     if let Some(transaction_notifier) = transaction_notifier.as_ref() {
@@ -217,6 +270,25 @@ impl TransactionStatusService {
                         );
                     }
 
+                    if let (Some(profile_histograms), Some(log_messages)) = (
+                        profile_histograms,
+                        transaction_status_meta.log_messages.as_ref(),
+                    ) {
+                        profile_histograms.record_transaction(log_messages);
+                    }
+
+                    if let (Some(profile_sink), Some(log_messages)) =
+                        (profile_sink, transaction_status_meta.log_messages.as_ref())
+                    {
+                        if let Err(err) = profile_sink.record_transaction(
+                            slot,
+                            transaction.signature(),
+                            log_messages,
+                        ) {
+                            warn!("failed to record transaction profile: {err}");
+                        }
+                    }
+
                     if !(enable_extended_tx_metadata_storage || transaction_notifier.is_some()) {
                         transaction_status_meta.log_messages.take();
                         transaction_status_meta.inner_instructions.take();
@@ -328,6 +400,18 @@ impl TransactionStatusService {
     }
 }
 
+/// Encode the profile report of a committed transaction to be stored with its status
+#[cfg(feature = "protobuf")]
+fn encode_stored_profile(report: &ProfileReport) -> Option<Vec<u8>> {
+    Some(transaction_profile(report).encode_to_vec())
+}
+
+/// Profiles are stored as protobuf, without the `protobuf` feature they are not stored
+#[cfg(not(feature = "protobuf"))]
+fn encode_stored_profile(_report: &ProfileReport) -> Option<Vec<u8>> {
+    None
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use {
@@ -461,9 +545,12 @@ pub(crate) mod tests {
             inner_instructions: None,
             return_data: None,
             executed_units: 0,
+            instruction_executed_units: vec![],
             fee_details: FeeDetails::default(),
             loaded_account_stats: TransactionLoadedAccountsStats::default(),
             fee_payer_post_balance: 0,
+            profiling_results: None,
+            program_load_profiles: None,
         });
 
         let balances = TransactionBalancesSet {
@@ -521,8 +608,11 @@ pub(crate) mod tests {
             Arc::new(AtomicU64::default()),
             false,
             Some(test_notifier.clone()),
+            None,
+            None,
             blockstore,
             false,
+            false,
             None, // No work dependency tracker
             exit.clone(),
         );
@@ -592,9 +682,12 @@ pub(crate) mod tests {
             inner_instructions: None,
             return_data: None,
             executed_units: 0,
+            instruction_executed_units: vec![],
             fee_details: FeeDetails::default(),
             loaded_account_stats: TransactionLoadedAccountsStats::default(),
             fee_payer_post_balance: 0,
+            profiling_results: None,
+            program_load_profiles: None,
         });
 
         let balances = TransactionBalancesSet {
@@ -630,8 +723,11 @@ pub(crate) mod tests {
             Arc::new(AtomicU64::default()),
             true,
             Some(test_notifier.clone()),
+            None,
+            None,
             blockstore,
             false,
+            false,
             Some(dependency_tracker.clone()),
             exit.clone(),
         );
@@ -194,9 +194,14 @@ mod tests {
                     status,
                     log_messages: None,
                     inner_instructions: None,
+                    instruction_trace: None,
                     return_data: None,
                     executed_units: 0,
+                    instruction_executed_units: vec![],
                     accounts_data_len_delta: 0,
+                    profiling_results: None,
+                    program_load_profiles: None,
+                    load_profile: None,
                 },
                 loaded_transaction,
                 programs_modified_by_tx: HashMap::new(),
@@ -62,6 +62,7 @@ use {
     agave_reserved_account_keys::ReservedAccountKeys,
     agave_syscalls::{
         create_program_runtime_environment_v1, create_program_runtime_environment_v2,
+        register_diagnostics_syscalls,
     },
     ahash::{AHashSet, RandomState},
     dashmap::DashMap,
@@ -111,7 +112,10 @@ use {
     solana_packet::PACKET_DATA_SIZE,
     solana_precompile_error::PrecompileError,
     solana_program_runtime::{
-        invoke_context::BuiltinFunctionWithContext, loaded_programs::ProgramCacheEntry,
+        invoke_context::BuiltinFunctionWithContext,
+        loaded_programs::ProgramCacheEntry,
+        profile_report::{ProfileReport, TransactionMetadata},
+        profiling::ProfileVerbosity,
     },
     solana_pubkey::Pubkey,
     solana_reward_info::RewardInfo,
@@ -134,7 +138,7 @@ use {
         transaction_commit_result::{CommittedTransaction, TransactionCommitResult},
         transaction_error_metrics::TransactionErrorMetrics,
         transaction_execution_result::{
-            TransactionExecutionDetails, TransactionLoadedAccountsStats,
+            TracedInstruction, TransactionExecutionDetails, TransactionLoadedAccountsStats,
         },
         transaction_processing_result::{
             ProcessedTransaction, TransactionProcessingResult,
@@ -146,6 +150,7 @@ use {
         },
     },
     solana_svm_callback::{AccountState, InvokeContextCallback, TransactionProcessingCallback},
+    solana_svm_log_collector::{LogLevel, ProgramLogFilter},
     solana_svm_timings::{ExecuteTimingType, ExecuteTimings},
     solana_svm_transaction::svm_message::SVMMessage,
     solana_system_transaction as system_transaction,
@@ -314,11 +319,17 @@ pub struct TransactionSimulationResult {
     pub loaded_accounts_data_size: u32,
     pub return_data: Option<TransactionReturnData>,
     pub inner_instructions: Option<Vec<InnerInstructions>>,
+    /// Every executed instruction with the compute units it consumed, only set if CPI
+    /// recording was enabled and the transaction executed, even if it failed
+    pub instruction_trace: Option<Vec<TracedInstruction>>,
     pub fee: Option<u64>,
     pub pre_balances: Option<Vec<u64>>,
     pub post_balances: Option<Vec<u64>>,
     pub pre_token_balances: Option<Vec<SvmTokenInfo>>,
     pub post_token_balances: Option<Vec<SvmTokenInfo>>,
+    /// Profile report of the transaction, only set if it was requested and the transaction
+    /// executed
+    pub profile: Option<ProfileReport>,
 }
 
 #[derive(Clone, Debug)]
@@ -551,6 +562,14 @@ impl PartialEq for Bank {
             collector_fee_details: _,
             compute_budget: _,
             transaction_account_lock_limit: _,
+            enable_diagnostics_syscalls: _,
+            dedup_log_messages: _,
+            log_program_filter: _,
+            profile_all_transactions: _,
+            profile_verbosity: _,
+            implicit_profiling_sections: _,
+            profile_cpis: _,
+            log_messages_bytes_limit: _,
             fee_structure: _,
             cache_for_accounts_lt_hash: _,
             stats_for_accounts_lt_hash: _,
@@ -870,6 +889,31 @@ pub struct Bank {
     /// The max number of accounts that a transaction may lock.
     transaction_account_lock_limit: Option<usize>,
 
+    /// Whether programs may call syscalls exposing non-deterministic host state.
+    enable_diagnostics_syscalls: bool,
+
+    /// Whether to collapse consecutive identical log messages.
+    dedup_log_messages: bool,
+
+    /// Selects the programs whose log messages are recorded.
+    log_program_filter: Option<Arc<ProgramLogFilter>>,
+
+    /// Whether every transaction is profiled, not only the ones requesting it.
+    profile_all_transactions: bool,
+
+    /// How much of the profile of each instruction is logged.
+    profile_verbosity: ProfileVerbosity,
+
+    /// Whether to open a profiling section around every instruction of the profiled transactions.
+    implicit_profiling_sections: bool,
+
+    /// Whether to record a profiling section around every CPI of the profiled transactions.
+    profile_cpis: bool,
+
+    /// The max number of bytes logged by a simulated transaction, the log collector's default if
+    /// not set.
+    log_messages_bytes_limit: Option<usize>,
+
     /// Fee structure to use for assessing transaction fees.
     fee_structure: FeeStructure,
 
@@ -1095,6 +1139,14 @@ impl Bank {
             collector_fee_details: RwLock::new(CollectorFeeDetails::default()),
             compute_budget: None,
             transaction_account_lock_limit: None,
+            enable_diagnostics_syscalls: false,
+            dedup_log_messages: false,
+            log_program_filter: None,
+            profile_all_transactions: false,
+            profile_verbosity: ProfileVerbosity::default(),
+            implicit_profiling_sections: false,
+            profile_cpis: false,
+            log_messages_bytes_limit: None,
             fee_structure: FeeStructure::default(),
             #[cfg(feature = "dev-context-only-utils")]
             hash_overrides: Arc::new(Mutex::new(HashOverrides::default())),
@@ -1135,6 +1187,14 @@ impl Bank {
         bank.ancestors = Ancestors::from(vec![bank.slot()]);
         bank.compute_budget = runtime_config.compute_budget;
         bank.transaction_account_lock_limit = runtime_config.transaction_account_lock_limit;
+        bank.enable_diagnostics_syscalls = runtime_config.enable_diagnostics_syscalls;
+        bank.dedup_log_messages = runtime_config.dedup_log_messages;
+        bank.log_program_filter = runtime_config.log_program_filter.clone();
+        bank.profile_all_transactions = runtime_config.profile_all_transactions;
+        bank.profile_verbosity = runtime_config.profile_verbosity;
+        bank.implicit_profiling_sections = runtime_config.implicit_profiling_sections;
+        bank.profile_cpis = runtime_config.profile_cpis;
+        bank.log_messages_bytes_limit = runtime_config.log_messages_bytes_limit;
         bank.transaction_debug_keys = debug_keys;
         bank.cluster_type = Some(genesis_config.cluster_type);
 
@@ -1343,6 +1403,14 @@ impl Bank {
             collector_fee_details: RwLock::new(CollectorFeeDetails::default()),
             compute_budget: parent.compute_budget,
             transaction_account_lock_limit: parent.transaction_account_lock_limit,
+            enable_diagnostics_syscalls: parent.enable_diagnostics_syscalls,
+            dedup_log_messages: parent.dedup_log_messages,
+            log_program_filter: parent.log_program_filter.clone(),
+            profile_all_transactions: parent.profile_all_transactions,
+            profile_verbosity: parent.profile_verbosity,
+            implicit_profiling_sections: parent.implicit_profiling_sections,
+            profile_cpis: parent.profile_cpis,
+            log_messages_bytes_limit: parent.log_messages_bytes_limit,
             fee_structure: parent.fee_structure.clone(),
             #[cfg(feature = "dev-context-only-utils")]
             hash_overrides: parent.hash_overrides.clone(),
@@ -1539,17 +1607,29 @@ impl Bank {
                 .global_program_cache
                 .write()
                 .unwrap();
-            let program_runtime_environment_v1 = create_program_runtime_environment_v1(
+            let mut program_runtime_environment_v1 = create_program_runtime_environment_v1(
                 &upcoming_feature_set.runtime_features(),
                 &compute_budget,
                 false, /* deployment */
                 false, /* debugging_features */
             )
             .unwrap();
-            let program_runtime_environment_v2 = create_program_runtime_environment_v2(
+            let mut program_runtime_environment_v2 = create_program_runtime_environment_v2(
+                &upcoming_feature_set.runtime_features(),
                 &compute_budget,
                 false, /* debugging_features */
-            );
+            )
+            .unwrap();
+            for program_runtime_environment in [
+                &mut program_runtime_environment_v1,
+                &mut program_runtime_environment_v2,
+            ] {
+                register_diagnostics_syscalls(
+                    program_runtime_environment,
+                    self.enable_diagnostics_syscalls,
+                )
+                .unwrap();
+            }
             let mut upcoming_environments = program_cache.environments.clone();
             let changed_program_runtime_v1 =
                 *upcoming_environments.program_runtime_v1 != program_runtime_environment_v1;
@@ -1802,6 +1882,14 @@ impl Bank {
             collector_fee_details: RwLock::new(CollectorFeeDetails::default()),
             compute_budget: runtime_config.compute_budget,
             transaction_account_lock_limit: runtime_config.transaction_account_lock_limit,
+            enable_diagnostics_syscalls: runtime_config.enable_diagnostics_syscalls,
+            dedup_log_messages: runtime_config.dedup_log_messages,
+            log_program_filter: runtime_config.log_program_filter.clone(),
+            profile_all_transactions: runtime_config.profile_all_transactions,
+            profile_verbosity: runtime_config.profile_verbosity,
+            implicit_profiling_sections: runtime_config.implicit_profiling_sections,
+            profile_cpis: runtime_config.profile_cpis,
+            log_messages_bytes_limit: runtime_config.log_messages_bytes_limit,
             fee_structure: FeeStructure::default(),
             #[cfg(feature = "dev-context-only-utils")]
             hash_overrides: Arc::new(Mutex::new(HashOverrides::default())),
@@ -2972,11 +3060,11 @@ impl Bank {
                 )
             })
             .collect::<Result<Vec<_>>>()?;
-        Ok(TransactionBatch::new(
-            self.try_lock_accounts(&sanitized_txs),
-            self,
-            OwnedOrBorrowed::Owned(sanitized_txs),
-        ))
+        let (lock_results, lock_us) = measure_us!(self.try_lock_accounts(&sanitized_txs));
+        let mut batch =
+            TransactionBatch::new(lock_results, self, OwnedOrBorrowed::Owned(sanitized_txs));
+        batch.set_lock_us(lock_us);
+        Ok(batch)
     }
 
     /// Attempt to take locks on the accounts in a transaction batch
@@ -3038,11 +3126,12 @@ impl Bank {
         transaction_results: impl Iterator<Item = Result<()>>,
     ) -> TransactionBatch<'a, 'b, Tx> {
         // this lock_results could be: Ok, AccountInUse, WouldExceedBlockMaxLimit or WouldExceedAccountMaxLimit
-        TransactionBatch::new(
-            self.try_lock_accounts_with_results(transactions, transaction_results),
-            self,
-            OwnedOrBorrowed::Borrowed(transactions),
-        )
+        let (lock_results, lock_us) =
+            measure_us!(self.try_lock_accounts_with_results(transactions, transaction_results));
+        let mut batch =
+            TransactionBatch::new(lock_results, self, OwnedOrBorrowed::Borrowed(transactions));
+        batch.set_lock_us(lock_us);
+        batch
     }
 
     /// Prepare a transaction batch from a single transaction without locking accounts
@@ -3074,10 +3163,57 @@ impl Bank {
         &self,
         transaction: &impl TransactionWithMeta,
         enable_cpi_recording: bool,
+    ) -> TransactionSimulationResult {
+        self.simulate_transaction_with_min_log_level(
+            transaction,
+            enable_cpi_recording,
+            LogLevel::default(),
+            false,
+            None,
+            None,
+            None,
+            false,
+            None,
+        )
+    }
+
+    /// Run transactions against a frozen bank without committing the results, dropping program
+    /// messages logged with a level below `min_log_level`
+    ///
+    /// With `enable_profiling`, the transaction is profiled even if this bank only profiles
+    /// transactions which request it. `log_messages_bytes_limit`, `log_program_filter` and
+    /// `profile_verbosity` override the byte limit and the program filter of the logs and the
+    /// profile verbosity configured for this bank. With `include_profile`, the transaction is
+    /// profiled as well and its profile report is returned along with the logs.
+    ///
+    /// `account_overrides` replace the stored state of the given accounts for this simulation
+    /// only. They do not replace the programs already in the program cache.
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulate_transaction_with_min_log_level(
+        &self,
+        transaction: &impl TransactionWithMeta,
+        enable_cpi_recording: bool,
+        min_log_level: LogLevel,
+        enable_profiling: bool,
+        log_messages_bytes_limit: Option<usize>,
+        log_program_filter: Option<Arc<ProgramLogFilter>>,
+        profile_verbosity: Option<ProfileVerbosity>,
+        include_profile: bool,
+        account_overrides: Option<HashMap<Pubkey, AccountSharedData>>,
     ) -> TransactionSimulationResult {
         assert!(self.is_frozen(), "simulation bank must be frozen");
 
-        self.simulate_transaction_unchecked(transaction, enable_cpi_recording)
+        self.simulate_transaction_unchecked_with_min_log_level(
+            transaction,
+            enable_cpi_recording,
+            min_log_level,
+            enable_profiling,
+            log_messages_bytes_limit,
+            log_program_filter,
+            profile_verbosity,
+            include_profile,
+            account_overrides,
+        )
     }
 
     /// Run transactions against a bank without committing the results; does not check if the bank
@@ -3086,10 +3222,83 @@ impl Bank {
         &self,
         transaction: &impl TransactionWithMeta,
         enable_cpi_recording: bool,
+    ) -> TransactionSimulationResult {
+        self.simulate_transaction_unchecked_with_min_log_level(
+            transaction,
+            enable_cpi_recording,
+            LogLevel::default(),
+            false,
+            None,
+            None,
+            None,
+            false,
+            None,
+        )
+    }
+
+    /// Run transactions in order against a frozen bank without committing the results, each
+    /// one against the accounts left by the transactions before it
+    ///
+    /// The transactions are simulated like [`Self::simulate_transaction_with_min_log_level`]
+    /// simulates a single transaction, and `account_overrides` apply to the first transaction.
+    /// The simulation stops at the first transaction which fails, so that the results end with
+    /// it. Programs deployed by a transaction are not visible to the transactions after it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulate_transaction_bundle(
+        &self,
+        transactions: &[impl TransactionWithMeta],
+        enable_cpi_recording: bool,
+        min_log_level: LogLevel,
+        enable_profiling: bool,
+        log_messages_bytes_limit: Option<usize>,
+        log_program_filter: Option<Arc<ProgramLogFilter>>,
+        profile_verbosity: Option<ProfileVerbosity>,
+        include_profile: bool,
+        account_overrides: Option<HashMap<Pubkey, AccountSharedData>>,
+    ) -> Vec<TransactionSimulationResult> {
+        assert!(self.is_frozen(), "simulation bank must be frozen");
+
+        let mut account_overrides = account_overrides.unwrap_or_default();
+        let mut results = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            let simulation = self.simulate_transaction_unchecked_with_min_log_level(
+                transaction,
+                enable_cpi_recording,
+                min_log_level,
+                enable_profiling,
+                log_messages_bytes_limit,
+                log_program_filter.clone(),
+                profile_verbosity,
+                include_profile,
+                Some(account_overrides.clone()),
+            );
+            let succeeded = simulation.result.is_ok();
+            account_overrides.extend(simulation.post_simulation_accounts.iter().cloned());
+            results.push(simulation);
+            if !succeeded {
+                break;
+            }
+        }
+        results
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn simulate_transaction_unchecked_with_min_log_level(
+        &self,
+        transaction: &impl TransactionWithMeta,
+        enable_cpi_recording: bool,
+        min_log_level: LogLevel,
+        enable_profiling: bool,
+        log_messages_bytes_limit: Option<usize>,
+        log_program_filter: Option<Arc<ProgramLogFilter>>,
+        profile_verbosity: Option<ProfileVerbosity>,
+        include_profile: bool,
+        account_overrides: Option<HashMap<Pubkey, AccountSharedData>>,
     ) -> TransactionSimulationResult {
         let account_keys = transaction.account_keys();
         let number_of_accounts = account_keys.len();
-        let account_overrides = self.get_account_overrides_for_simulation(&account_keys);
+        let account_overrides =
+            self.get_account_overrides_for_simulation(&account_keys, account_overrides);
         let batch = self.prepare_unlocked_batch_from_single_tx(transaction);
         let mut timings = ExecuteTimings::default();
 
@@ -3108,13 +3317,26 @@ impl Bank {
             TransactionProcessingConfig {
                 account_overrides: Some(&account_overrides),
                 check_program_modification_slot: self.check_program_modification_slot,
-                log_messages_bytes_limit: None,
+                log_messages_bytes_limit: log_messages_bytes_limit
+                    .or(self.log_messages_bytes_limit),
+                min_log_level,
+                dedup_log_messages: self.dedup_log_messages,
+                log_program_filter: log_program_filter.or_else(|| self.log_program_filter.clone()),
+                profile_all_transactions: self.profile_all_transactions
+                    || enable_profiling
+                    || include_profile,
+                profile_return_data_bytes_limit: None,
+                profile_verbosity: profile_verbosity.unwrap_or(self.profile_verbosity),
+                implicit_profiling_sections: self.implicit_profiling_sections,
+                profile_cpis: self.profile_cpis,
+                instrumentation_observer: None,
                 limit_to_load_programs: true,
                 recording_config: ExecutionRecordingConfig {
                     enable_cpi_recording,
                     enable_log_recording: true,
                     enable_return_data_recording: true,
                     enable_transaction_balance_recording: true,
+                    enable_profiling_recording: include_profile,
                 },
             },
         );
@@ -3131,8 +3353,10 @@ impl Bank {
             logs,
             return_data,
             inner_instructions,
+            instruction_trace,
             units_consumed,
             loaded_accounts_data_size,
+            profiles,
         ) = match processing_result {
             Ok(processed_tx) => {
                 let executed_units = processed_tx.executed_units();
@@ -3154,8 +3378,15 @@ impl Bank {
                             details.log_messages,
                             details.return_data,
                             details.inner_instructions,
+                            details.instruction_trace,
                             executed_units,
                             loaded_accounts_data_size,
+                            details.profiling_results.map(|instructions| {
+                                (
+                                    instructions,
+                                    details.program_load_profiles.unwrap_or_default(),
+                                )
+                            }),
                         )
                     }
                     ProcessedTransaction::FeesOnly(fees_only_tx) => (
@@ -3165,14 +3396,28 @@ impl Bank {
                         None,
                         None,
                         None,
+                        None,
                         executed_units,
                         loaded_accounts_data_size,
+                        None,
                     ),
                 }
             }
-            Err(error) => (vec![], Err(error), None, None, None, None, 0, 0),
+            Err(error) => (vec![], Err(error), None, None, None, None, None, 0, 0, None),
         };
         let logs = logs.unwrap_or_default();
+        let profile = profiles.map(|(instructions, program_loads)| {
+            ProfileReport::new(
+                TransactionMetadata {
+                    signature: Some(transaction.signature().to_string()),
+                    slot: Some(self.slot()),
+                    compute_units_consumed: units_consumed,
+                    error: result.as_ref().err().map(ToString::to_string),
+                },
+                instructions,
+                program_loads,
+            )
+        });
 
         let (pre_balances, post_balances, pre_token_balances, post_token_balances) =
             match balance_collector {
@@ -3198,15 +3443,21 @@ impl Bank {
             loaded_accounts_data_size,
             return_data,
             inner_instructions,
+            instruction_trace,
             fee,
             pre_balances,
             post_balances,
             pre_token_balances,
             post_token_balances,
+            profile,
         }
     }
 
-    fn get_account_overrides_for_simulation(&self, account_keys: &AccountKeys) -> AccountOverrides {
+    fn get_account_overrides_for_simulation(
+        &self,
+        account_keys: &AccountKeys,
+        requested_overrides: Option<HashMap<Pubkey, AccountSharedData>>,
+    ) -> AccountOverrides {
         let mut account_overrides = AccountOverrides::default();
         let slot_history_id = sysvar::slot_history::id();
         if account_keys.iter().any(|pubkey| *pubkey == slot_history_id) {
@@ -3224,6 +3475,9 @@ impl Bank {
                 }
             }
         }
+        for (pubkey, account) in requested_overrides.into_iter().flatten() {
+            account_overrides.set_account(&pubkey, Some(account));
+        }
         account_overrides
     }
 
@@ -3292,7 +3546,7 @@ impl Bank {
             rent: self.rent_collector.rent.clone(),
         };
 
-        let sanitized_output = self
+        let mut sanitized_output = self
             .transaction_processor
             .load_and_execute_sanitized_transactions(
                 self,
@@ -3302,6 +3556,15 @@ impl Bank {
                 &processing_config,
             );
 
+        // The batch processor cannot tell how long it took to lock the accounts
+        for processing_result in sanitized_output.processing_results.iter_mut().flatten() {
+            if let ProcessedTransaction::Executed(executed_tx) = processing_result {
+                if let Some(load_profile) = &mut executed_tx.execution_details.load_profile {
+                    load_profile.lock_wait_us = batch.lock_us();
+                }
+            }
+        }
+
         // Accumulate the errors returned by the batch processor.
         error_counters.accumulate(&sanitized_output.error_metrics);
 
@@ -3709,12 +3972,16 @@ impl Bank {
                             inner_instructions: execution_details.inner_instructions,
                             return_data: execution_details.return_data,
                             executed_units,
+                            instruction_executed_units: execution_details
+                                .instruction_executed_units,
                             fee_details,
                             loaded_account_stats: TransactionLoadedAccountsStats {
                                 loaded_accounts_count: loaded_accounts.len(),
                                 loaded_accounts_data_size,
                             },
                             fee_payer_post_balance,
+                            profiling_results: execution_details.profiling_results,
+                            program_load_profiles: execution_details.program_load_profiles,
                         })
                     }
                     ProcessedTransaction::FeesOnly(fees_only_tx) => Ok(CommittedTransaction {
@@ -3723,6 +3990,7 @@ impl Bank {
                         inner_instructions: None,
                         return_data: None,
                         executed_units,
+                        instruction_executed_units: vec![],
                         fee_details: fees_only_tx.fee_details,
                         loaded_account_stats: TransactionLoadedAccountsStats {
                             loaded_accounts_count: fees_only_tx.rollback_accounts.count(),
@@ -3733,6 +4001,8 @@ impl Bank {
                             .fee_payer()
                             .1
                             .lamports(),
+                        profiling_results: None,
+                        program_load_profiles: None,
                     }),
                 }
             })
@@ -3834,6 +4104,15 @@ impl Bank {
                 account_overrides: None,
                 check_program_modification_slot: self.check_program_modification_slot,
                 log_messages_bytes_limit,
+                min_log_level: LogLevel::default(),
+                dedup_log_messages: self.dedup_log_messages,
+                log_program_filter: self.log_program_filter.clone(),
+                profile_all_transactions: self.profile_all_transactions,
+                profile_return_data_bytes_limit: None,
+                profile_verbosity: self.profile_verbosity,
+                implicit_profiling_sections: self.implicit_profiling_sections,
+                profile_cpis: self.profile_cpis,
+                instrumentation_observer: None,
                 limit_to_load_programs: false,
                 recording_config,
             },
@@ -3881,6 +4160,7 @@ impl Bank {
                 enable_log_recording: true,
                 enable_return_data_recording: true,
                 enable_transaction_balance_recording: false,
+                enable_profiling_recording: true,
             },
             &mut ExecuteTimings::default(),
             Some(1000 * 1000),
@@ -4134,27 +4414,39 @@ impl Bank {
             .feature_set
             .is_active(&raise_cpi_nesting_limit_to_8::id());
 
+        let mut program_runtime_environment_v1 = create_program_runtime_environment_v1(
+            &self.feature_set.runtime_features(),
+            &self
+                .compute_budget()
+                .unwrap_or(ComputeBudget::new_with_defaults(simd_0296_active))
+                .to_budget(),
+            false, /* deployment */
+            false, /* debugging_features */
+        )
+        .unwrap();
+        let mut program_runtime_environment_v2 = create_program_runtime_environment_v2(
+            &self.feature_set.runtime_features(),
+            &self
+                .compute_budget()
+                .unwrap_or(ComputeBudget::new_with_defaults(simd_0296_active))
+                .to_budget(),
+            false, /* debugging_features */
+        )
+        .unwrap();
+        for program_runtime_environment in [
+            &mut program_runtime_environment_v1,
+            &mut program_runtime_environment_v2,
+        ] {
+            register_diagnostics_syscalls(
+                program_runtime_environment,
+                self.enable_diagnostics_syscalls,
+            )
+            .unwrap();
+        }
         self.transaction_processor
             .configure_program_runtime_environments(
-                Some(Arc::new(
-                    create_program_runtime_environment_v1(
-                        &self.feature_set.runtime_features(),
-                        &self
-                            .compute_budget()
-                            .unwrap_or(ComputeBudget::new_with_defaults(simd_0296_active))
-                            .to_budget(),
-                        false, /* deployment */
-                        false, /* debugging_features */
-                    )
-                    .unwrap(),
-                )),
-                Some(Arc::new(create_program_runtime_environment_v2(
-                    &self
-                        .compute_budget()
-                        .unwrap_or(ComputeBudget::new_with_defaults(simd_0296_active))
-                        .to_budget(),
-                    false, /* debugging_features */
-                ))),
+                Some(Arc::new(program_runtime_environment_v1)),
+                Some(Arc::new(program_runtime_environment_v2)),
             );
     }
 
@@ -5540,6 +5832,34 @@ impl Bank {
         self.check_program_modification_slot
     }
 
+    pub fn dedup_log_messages(&self) -> bool {
+        self.dedup_log_messages
+    }
+
+    pub fn log_program_filter(&self) -> Option<Arc<ProgramLogFilter>> {
+        self.log_program_filter.clone()
+    }
+
+    pub fn profile_all_transactions(&self) -> bool {
+        self.profile_all_transactions
+    }
+
+    pub fn profile_verbosity(&self) -> ProfileVerbosity {
+        self.profile_verbosity
+    }
+
+    pub fn implicit_profiling_sections(&self) -> bool {
+        self.implicit_profiling_sections
+    }
+
+    pub fn profile_cpis(&self) -> bool {
+        self.profile_cpis
+    }
+
+    pub fn log_messages_bytes_limit(&self) -> Option<usize> {
+        self.log_messages_bytes_limit
+    }
+
     pub fn set_check_program_modification_slot(&mut self, check: bool) {
         self.check_program_modification_slot = check;
     }
@@ -97,9 +97,9 @@ impl Bank {
             .zip(lock_results)
             .map(|(tx, lock_res)| match lock_res {
                 Ok(()) => {
-                    let compute_budget_and_limits = tx
-                        .borrow()
-                        .compute_budget_instruction_details()
+                    let compute_budget_instruction_details =
+                        tx.borrow().compute_budget_instruction_details();
+                    let compute_budget_and_limits = compute_budget_instruction_details
                         .sanitize_and_convert_to_compute_budget_limits(feature_set)
                         .map(|limit| {
                             let fee_budget = FeeBudgetLimits::from(limit);
@@ -126,6 +126,14 @@ impl Bank {
                                         .is_active(&raise_cpi_nesting_limit_to_8::id()),
                                 )
                             }
+                        })
+                        .map(|mut compute_budget_and_limits| {
+                            // Only reported by the profile of the transaction
+                            compute_budget_and_limits
+                                .budget
+                                .requested_compute_unit_limit =
+                                compute_budget_instruction_details.requested_compute_unit_limit();
+                            compute_budget_and_limits
                         });
                     self.check_transaction_age(
                         tx.borrow(),
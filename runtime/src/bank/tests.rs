@@ -254,9 +254,14 @@ fn new_executed_processing_result(
                 status,
                 log_messages: None,
                 inner_instructions: None,
+                instruction_trace: None,
                 return_data: None,
                 executed_units: 0,
+                instruction_executed_units: vec![],
                 accounts_data_len_delta: 0,
+                profiling_results: None,
+                program_load_profiles: None,
+                load_profile: None,
             },
             programs_modified_by_tx: HashMap::new(),
         },
@@ -1947,12 +1952,15 @@ fn test_load_and_execute_commit_transactions_fees_only() {
             inner_instructions: None,
             return_data: None,
             executed_units: 0,
+            instruction_executed_units: vec![],
             fee_details: FeeDetails::new(5000, 0),
             loaded_account_stats: TransactionLoadedAccountsStats {
                 loaded_accounts_count: 2,
                 loaded_accounts_data_size: nonce_size as u32,
             },
             fee_payer_post_balance: genesis_config.rent.minimum_balance(0) - 1 - 5000,
+            profiling_results: None,
+            program_load_profiles: None,
         })]
     );
 }
@@ -1999,7 +2007,10 @@ fn test_load_and_execute_commit_transactions_failure() {
         .load_execute_and_commit_transactions(
             &batch,
             MAX_PROCESSING_AGE,
-            ExecutionRecordingConfig::new_single_setting(true),
+            ExecutionRecordingConfig {
+                enable_profiling_recording: false,
+                ..ExecutionRecordingConfig::new_single_setting(true)
+            },
             &mut ExecuteTimings::default(),
             None,
         )
@@ -2022,12 +2033,15 @@ fn test_load_and_execute_commit_transactions_failure() {
             inner_instructions: Some(vec![vec![], vec![]]),
             return_data: None,
             executed_units: 300,
+            instruction_executed_units: vec![150, 150],
             fee_details: FeeDetails::new(5000, 0),
             loaded_account_stats: TransactionLoadedAccountsStats {
                 loaded_accounts_count: 3,
                 loaded_accounts_data_size: 142, // size of system account (initially recipient does not exist)
             },
             fee_payer_post_balance: starting_balance - 5000,
+            profiling_results: None,
+            program_load_profiles: None,
         })]
     );
 }
@@ -2073,7 +2087,10 @@ fn test_load_and_execute_commit_transactions_success() {
         .load_execute_and_commit_transactions(
             &batch,
             MAX_PROCESSING_AGE,
-            ExecutionRecordingConfig::new_single_setting(true),
+            ExecutionRecordingConfig {
+                enable_profiling_recording: false,
+                ..ExecutionRecordingConfig::new_single_setting(true)
+            },
             &mut ExecuteTimings::default(),
             None,
         )
@@ -2090,12 +2107,15 @@ fn test_load_and_execute_commit_transactions_success() {
             inner_instructions: Some(vec![vec![]]),
             return_data: None,
             executed_units: 150,
+            instruction_executed_units: vec![150],
             fee_details: FeeDetails::new(5000, 0),
             loaded_account_stats: TransactionLoadedAccountsStats {
                 loaded_accounts_count: 3,
                 loaded_accounts_data_size: 142, // size of system account (initially recipient does not exist)
             },
             fee_payer_post_balance: starting_balance - 5000 - transfer_amount,
+            profiling_results: None,
+            program_load_profiles: None,
         })]
     );
 }
@@ -4778,6 +4798,7 @@ fn test_pre_post_transaction_balances() {
             enable_log_recording: false,
             enable_return_data_recording: false,
             enable_transaction_balance_recording: true,
+            enable_profiling_recording: false,
         },
         &mut ExecuteTimings::default(),
         None,
@@ -4824,6 +4845,56 @@ fn test_pre_post_transaction_balances() {
     );
 }
 
+#[test]
+fn test_transaction_load_profile() {
+    let (genesis_config, mint_keypair) = create_genesis_config(500_000);
+    let (bank, _bank_forks) = Bank::new_with_bank_forks_for_tests(&genesis_config);
+    let tx = system_transaction::transfer(
+        &mint_keypair,
+        &solana_pubkey::new_rand(),
+        2_000,
+        bank.last_blockhash(),
+    );
+    let mut batch = bank.prepare_batch_for_tests(vec![tx]);
+
+    let load_profile = |batch: &TransactionBatch<_>, enable_profiling_recording| {
+        let LoadAndExecuteTransactionsOutput {
+            processing_results, ..
+        } = bank.load_and_execute_transactions(
+            batch,
+            MAX_PROCESSING_AGE,
+            &mut ExecuteTimings::default(),
+            &mut TransactionErrorMetrics::default(),
+            TransactionProcessingConfig {
+                recording_config: ExecutionRecordingConfig {
+                    enable_profiling_recording,
+                    ..ExecutionRecordingConfig::new_single_setting(false)
+                },
+                ..TransactionProcessingConfig::default()
+            },
+        );
+        match processing_results.into_iter().next().unwrap() {
+            Ok(ProcessedTransaction::Executed(executed_tx)) => {
+                executed_tx.execution_details.load_profile
+            }
+            result => panic!("unexpected processing result: {result:?}"),
+        }
+    };
+
+    assert_eq!(load_profile(&batch, false), None);
+    // The lock time is unknown unless measured by the bank
+    assert_eq!(
+        load_profile(&batch, true).map(|load_profile| load_profile.lock_wait_us),
+        Some(None)
+    );
+    // The lock time of the batch is attributed to every transaction in it
+    batch.set_lock_us(42);
+    assert_eq!(
+        load_profile(&batch, true).map(|load_profile| load_profile.lock_wait_us),
+        Some(Some(42))
+    );
+}
+
 #[test]
 fn test_transaction_with_duplicate_accounts_in_instruction() {
     let (genesis_config, mint_keypair) = create_genesis_config_no_tx_fee_no_rent(500);
@@ -8424,6 +8495,7 @@ fn test_tx_log_order(relax_intrabatch_account_locks: bool) {
                 enable_log_recording: true,
                 enable_return_data_recording: false,
                 enable_transaction_balance_recording: false,
+                enable_profiling_recording: false,
             },
             &mut ExecuteTimings::default(),
             None,
@@ -8536,6 +8608,7 @@ fn test_tx_return_data() {
                     enable_log_recording: false,
                     enable_return_data_recording: true,
                     enable_transaction_balance_recording: false,
+                    enable_profiling_recording: false,
                 },
                 &mut ExecuteTimings::default(),
                 None,
@@ -11561,6 +11634,456 @@ fn test_failed_simulation_compute_units() {
     );
 }
 
+/// Test that a simulation can enable profiling when the bank only profiles requested transactions
+#[test]
+fn test_simulation_enable_profiling() {
+    let (genesis_config, mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let program_id = Pubkey::new_unique();
+    let mut bank = Bank::new_for_tests(&genesis_config);
+    bank.add_mockup_builtin(program_id, MockBuiltin::vm);
+    let (bank, _bank_forks) = bank.wrap_with_bank_forks_for_tests();
+
+    declare_process_instruction!(MockBuiltin, 1, |invoke_context| {
+        if invoke_context.enable_profiling {
+            Ok(())
+        } else {
+            Err(InstructionError::InvalidInstructionData)
+        }
+    });
+
+    let message = Message::new(
+        &[Instruction::new_with_bincode(program_id, &0, vec![])],
+        Some(&mint_keypair.pubkey()),
+    );
+    let transaction = Transaction::new(&[&mint_keypair], message, bank.last_blockhash());
+
+    bank.freeze();
+    let sanitized = RuntimeTransaction::from_transaction_for_tests(transaction);
+    let simulation = bank.simulate_transaction(&sanitized, false);
+    assert_eq!(
+        simulation.result,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidInstructionData
+        ))
+    );
+    let simulation = bank.simulate_transaction_with_min_log_level(
+        &sanitized,
+        false,
+        LogLevel::default(),
+        true,
+        None,
+        None,
+        None,
+        false,
+        None,
+    );
+    assert_eq!(simulation.result, Ok(()));
+}
+
+/// Test that a simulation returns the profile report of the transaction when it is requested
+#[test]
+fn test_simulation_include_profile() {
+    let (genesis_config, mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let program_id = Pubkey::new_unique();
+    let mut bank = Bank::new_for_tests(&genesis_config);
+    bank.add_mockup_builtin(program_id, MockBuiltin::vm);
+    let (bank, _bank_forks) = bank.wrap_with_bank_forks_for_tests();
+
+    declare_process_instruction!(MockBuiltin, 1, |_invoke_context| { Ok(()) });
+
+    let message = Message::new(
+        &[Instruction::new_with_bincode(program_id, &0, vec![])],
+        Some(&mint_keypair.pubkey()),
+    );
+    let transaction = Transaction::new(&[&mint_keypair], message, bank.last_blockhash());
+
+    bank.freeze();
+    let sanitized = RuntimeTransaction::from_transaction_for_tests(transaction);
+    let simulation = bank.simulate_transaction(&sanitized, false);
+    assert_eq!(simulation.profile, None);
+
+    let simulation = bank.simulate_transaction_with_min_log_level(
+        &sanitized,
+        false,
+        LogLevel::default(),
+        false,
+        None,
+        None,
+        None,
+        true,
+        None,
+    );
+    assert_eq!(simulation.result, Ok(()));
+    let profile = simulation.profile.unwrap();
+    assert_eq!(
+        profile.transaction.signature,
+        Some(sanitized.signature().to_string())
+    );
+    assert_eq!(profile.transaction.slot, Some(bank.slot()));
+    assert_eq!(
+        profile.transaction.compute_units_consumed,
+        simulation.units_consumed
+    );
+    assert_eq!(profile.transaction.error, None);
+    assert_eq!(
+        profile
+            .instructions
+            .iter()
+            .map(|instruction| instruction.program_id)
+            .collect::<Vec<_>>(),
+        vec![program_id]
+    );
+}
+
+/// Test that a simulation runs against the overridden accounts without storing them
+#[test]
+fn test_simulation_account_overrides() {
+    let (genesis_config, _mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let (bank, _bank_forks) = Bank::new_with_bank_forks_for_tests(&genesis_config);
+    let payer = Keypair::new();
+    let recipient = Pubkey::new_unique();
+    let lamports = bank.get_minimum_balance_for_rent_exemption(0);
+    let transaction =
+        system_transaction::transfer(&payer, &recipient, lamports, bank.last_blockhash());
+
+    bank.freeze();
+    let sanitized = RuntimeTransaction::from_transaction_for_tests(transaction);
+    let simulate = |account_overrides| {
+        bank.simulate_transaction_with_min_log_level(
+            &sanitized,
+            false,
+            LogLevel::default(),
+            false,
+            None,
+            None,
+            None,
+            false,
+            account_overrides,
+        )
+    };
+
+    let simulation = simulate(None);
+    assert_eq!(simulation.result, Err(TransactionError::AccountNotFound));
+
+    let simulation = simulate(Some(HashMap::from([(
+        payer.pubkey(),
+        AccountSharedData::new(LAMPORTS_PER_SOL, 0, &system_program::id()),
+    )])));
+    assert_eq!(simulation.result, Ok(()));
+    let recipient_account = simulation
+        .post_simulation_accounts
+        .iter()
+        .find_map(|(pubkey, account)| (*pubkey == recipient).then_some(account))
+        .unwrap();
+    assert_eq!(recipient_account.lamports(), lamports);
+    assert_eq!(bank.get_balance(&payer.pubkey()), 0);
+    assert_eq!(bank.get_balance(&recipient), 0);
+}
+
+/// Test that the transactions of a bundle are simulated against the accounts left by the
+/// transactions before them
+#[test]
+fn test_simulate_transaction_bundle() {
+    let (genesis_config, mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let (bank, _bank_forks) = Bank::new_with_bank_forks_for_tests(&genesis_config);
+    let keypair = Keypair::new();
+    let recipient = Pubkey::new_unique();
+    let fund = system_transaction::transfer(
+        &mint_keypair,
+        &keypair.pubkey(),
+        LAMPORTS_PER_SOL / 2,
+        bank.last_blockhash(),
+    );
+    let lamports = bank.get_minimum_balance_for_rent_exemption(0);
+    let spend = system_transaction::transfer(&keypair, &recipient, lamports, bank.last_blockhash());
+
+    bank.freeze();
+    let fund = RuntimeTransaction::from_transaction_for_tests(fund);
+    let spend = RuntimeTransaction::from_transaction_for_tests(spend);
+    let simulate = |transactions: &[_]| {
+        bank.simulate_transaction_bundle(
+            transactions,
+            false,
+            LogLevel::default(),
+            false,
+            None,
+            None,
+            None,
+            false,
+            None,
+        )
+    };
+
+    let results = simulate(&[fund.clone(), spend.clone()]);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].result, Ok(()));
+    assert_eq!(results[1].result, Ok(()));
+    let recipient_account = results[1]
+        .post_simulation_accounts
+        .iter()
+        .find_map(|(pubkey, account)| (*pubkey == recipient).then_some(account))
+        .unwrap();
+    assert_eq!(recipient_account.lamports(), lamports);
+    assert_eq!(bank.get_balance(&keypair.pubkey()), 0);
+    assert_eq!(bank.get_balance(&recipient), 0);
+
+    // The bundle stops at the first transaction which fails
+    let results = simulate(&[spend, fund]);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].result, Err(TransactionError::AccountNotFound));
+}
+
+/// Test that the instruction trace of a failed simulation is returned with the compute units of
+/// every instruction
+#[test]
+fn test_simulate_transaction_instruction_trace() {
+    let (genesis_config, mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let (bank, _bank_forks) = Bank::new_with_bank_forks_for_tests(&genesis_config);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&mint_keypair.pubkey(), &Pubkey::new_unique(), 1_000),
+            Instruction::new_with_bincode(system_program::id(), &(), vec![]),
+        ],
+        Some(&mint_keypair.pubkey()),
+        &[&mint_keypair],
+        bank.last_blockhash(),
+    );
+
+    bank.freeze();
+    let sanitized = RuntimeTransaction::from_transaction_for_tests(transaction);
+    let simulation = bank.simulate_transaction(&sanitized, false);
+    assert_eq!(simulation.instruction_trace, None);
+
+    let simulation = bank.simulate_transaction(&sanitized, true);
+    assert_eq!(
+        simulation.result,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::InvalidInstructionData
+        ))
+    );
+    assert_eq!(
+        simulation.instruction_trace,
+        Some(vec![
+            TracedInstruction {
+                top_level_instruction_index: 0,
+                stack_height: 1,
+                program_id: system_program::id(),
+                compute_units_consumed: 150,
+                failed: false,
+            },
+            TracedInstruction {
+                top_level_instruction_index: 1,
+                stack_height: 1,
+                program_id: system_program::id(),
+                compute_units_consumed: 150,
+                failed: true,
+            },
+        ])
+    );
+}
+
+/// Test that the profile verbosity can be configured for the bank and for each simulation
+#[test]
+fn test_simulation_profile_verbosity() {
+    let (genesis_config, mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let program_id = Pubkey::new_unique();
+    let mut bank = Bank::new_for_tests(&genesis_config);
+    bank.add_mockup_builtin(program_id, MockBuiltin::vm);
+    bank.profile_verbosity = ProfileVerbosity::Tree;
+    let (bank, _bank_forks) = bank.wrap_with_bank_forks_for_tests();
+
+    declare_process_instruction!(MockBuiltin, 1, |invoke_context| {
+        solana_svm_log_collector::ic_msg!(
+            invoke_context,
+            "verbosity {}",
+            invoke_context.profile_verbosity
+        );
+        Ok(())
+    });
+
+    let message = Message::new(
+        &[Instruction::new_with_bincode(program_id, &0, vec![])],
+        Some(&mint_keypair.pubkey()),
+    );
+    let transaction = Transaction::new(&[&mint_keypair], message, bank.last_blockhash());
+
+    bank.freeze();
+    let sanitized = RuntimeTransaction::from_transaction_for_tests(transaction);
+    let simulation = bank.simulate_transaction(&sanitized, false);
+    assert!(simulation.logs.contains(&"verbosity tree".to_string()));
+
+    let simulation = bank.simulate_transaction_with_min_log_level(
+        &sanitized,
+        false,
+        LogLevel::default(),
+        false,
+        None,
+        None,
+        Some(ProfileVerbosity::Off),
+        false,
+        None,
+    );
+    assert!(simulation.logs.contains(&"verbosity off".to_string()));
+}
+
+/// Test that recording a profiling section around every CPI can be configured for the bank
+#[test]
+fn test_simulation_profile_cpis() {
+    let (genesis_config, mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let program_id = Pubkey::new_unique();
+    let mut bank = Bank::new_for_tests(&genesis_config);
+    bank.add_mockup_builtin(program_id, MockBuiltin::vm);
+    bank.profile_cpis = true;
+    let (bank, _bank_forks) = bank.wrap_with_bank_forks_for_tests();
+
+    declare_process_instruction!(MockBuiltin, 1, |invoke_context| {
+        solana_svm_log_collector::ic_msg!(
+            invoke_context,
+            "profile cpis {}",
+            invoke_context.profile_cpis
+        );
+        Ok(())
+    });
+
+    let message = Message::new(
+        &[Instruction::new_with_bincode(program_id, &0, vec![])],
+        Some(&mint_keypair.pubkey()),
+    );
+    let transaction = Transaction::new(&[&mint_keypair], message, bank.last_blockhash());
+
+    bank.freeze();
+    let sanitized = RuntimeTransaction::from_transaction_for_tests(transaction);
+    let simulation = bank.simulate_transaction(&sanitized, false);
+    assert!(simulation.logs.contains(&"profile cpis true".to_string()));
+}
+
+/// Test that opening a profiling section around every instruction can be configured for the bank
+#[test]
+fn test_simulation_implicit_profiling_sections() {
+    let (genesis_config, mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let program_id = Pubkey::new_unique();
+    let mut bank = Bank::new_for_tests(&genesis_config);
+    bank.add_mockup_builtin(program_id, MockBuiltin::vm);
+    bank.implicit_profiling_sections = true;
+    let (bank, _bank_forks) = bank.wrap_with_bank_forks_for_tests();
+
+    declare_process_instruction!(MockBuiltin, 1, |invoke_context| {
+        solana_svm_log_collector::ic_msg!(
+            invoke_context,
+            "implicit profiling sections {}",
+            invoke_context.implicit_profiling_sections
+        );
+        Ok(())
+    });
+
+    let message = Message::new(
+        &[Instruction::new_with_bincode(program_id, &0, vec![])],
+        Some(&mint_keypair.pubkey()),
+    );
+    let transaction = Transaction::new(&[&mint_keypair], message, bank.last_blockhash());
+
+    bank.freeze();
+    let sanitized = RuntimeTransaction::from_transaction_for_tests(transaction);
+    let simulation = bank.simulate_transaction(&sanitized, false);
+    assert!(simulation
+        .logs
+        .contains(&"implicit profiling sections true".to_string()));
+}
+
+/// Test that the byte limit of the logs can be configured for the bank and for each simulation
+#[test]
+fn test_simulation_log_messages_bytes_limit() {
+    let (genesis_config, mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let program_id = Pubkey::new_unique();
+    let mut bank = Bank::new_for_tests(&genesis_config);
+    bank.add_mockup_builtin(program_id, MockBuiltin::vm);
+    bank.log_messages_bytes_limit = Some(100);
+    let (bank, _bank_forks) = bank.wrap_with_bank_forks_for_tests();
+
+    declare_process_instruction!(MockBuiltin, 1, |invoke_context| {
+        for _ in 0..10 {
+            solana_svm_log_collector::ic_msg!(invoke_context, "0123456789");
+        }
+        Ok(())
+    });
+
+    let message = Message::new(
+        &[Instruction::new_with_bincode(program_id, &0, vec![])],
+        Some(&mint_keypair.pubkey()),
+    );
+    let transaction = Transaction::new(&[&mint_keypair], message, bank.last_blockhash());
+
+    bank.freeze();
+    let sanitized = RuntimeTransaction::from_transaction_for_tests(transaction);
+    let simulation = bank.simulate_transaction(&sanitized, false);
+    assert_eq!(simulation.result, Ok(()));
+    assert_eq!(simulation.logs.last().unwrap(), "Log truncated");
+
+    let simulation = bank.simulate_transaction_with_min_log_level(
+        &sanitized,
+        false,
+        LogLevel::default(),
+        false,
+        Some(1_000),
+        None,
+        None,
+        false,
+        None,
+    );
+    assert_eq!(simulation.result, Ok(()));
+    assert_eq!(simulation.logs.len(), 12);
+    assert_eq!(
+        simulation.logs.last().unwrap(),
+        &format!("Program {program_id} success")
+    );
+}
+
+/// Test that the messages logged by a program can be filtered out of a simulation
+#[test]
+fn test_simulation_log_program_filter() {
+    let (genesis_config, mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let program_id = Pubkey::new_unique();
+    let (bank, _bank_forks) =
+        Bank::new_with_mockup_builtin_for_tests(&genesis_config, program_id, MockBuiltin::vm);
+
+    declare_process_instruction!(MockBuiltin, 1, |invoke_context| {
+        solana_svm_log_collector::ic_msg!(invoke_context, "hello");
+        Ok(())
+    });
+
+    let message = Message::new(
+        &[Instruction::new_with_bincode(program_id, &0, vec![])],
+        Some(&mint_keypair.pubkey()),
+    );
+    let transaction = Transaction::new(&[&mint_keypair], message, bank.last_blockhash());
+
+    bank.freeze();
+    let sanitized = RuntimeTransaction::from_transaction_for_tests(transaction);
+    let simulate = |log_program_filter| {
+        bank.simulate_transaction_with_min_log_level(
+            &sanitized,
+            false,
+            LogLevel::default(),
+            false,
+            None,
+            Some(Arc::new(log_program_filter)),
+            None,
+            false,
+            None,
+        )
+    };
+
+    let simulation = simulate(ProgramLogFilter::Allow(HashSet::from([program_id])));
+    assert_eq!(simulation.result, Ok(()));
+    assert!(simulation.logs.contains(&"hello".to_string()));
+
+    let simulation = simulate(ProgramLogFilter::Deny(HashSet::from([program_id])));
+    assert_eq!(simulation.result, Ok(()));
+    assert!(simulation.logs.is_empty());
+}
+
 /// Test that simulations report the load error of fees-only transactions
 #[test]
 fn test_failed_simulation_load_error() {
@@ -11592,11 +12115,13 @@ fn test_failed_simulation_load_error() {
             loaded_accounts_data_size: 0,
             return_data: None,
             inner_instructions: None,
+            instruction_trace: None,
             fee: Some(0),
             pre_balances: Some(vec![mint_balance, 0]),
             post_balances: Some(vec![mint_balance, 0]),
             pre_token_balances: Some(vec![]),
             post_token_balances: Some(vec![]),
+            profile: None,
         }
     );
 }
@@ -12423,3 +12948,40 @@ fn test_startup_from_snapshot_after_precompile_transition() {
     // Simulate starting up from snapshot finishing the initialization for a frozen bank
     bank.finish_init(&genesis_config, false);
 }
+
+#[test]
+fn test_enable_diagnostics_syscalls() {
+    let (genesis_config, _mint_keypair) = create_genesis_config(100_000);
+    // Programs of every loader are executed in one of these environments
+    let is_host_time_registered = |enable_diagnostics_syscalls: bool| {
+        let bank = Bank::new_with_paths_for_tests(
+            &genesis_config,
+            Arc::new(RuntimeConfig {
+                enable_diagnostics_syscalls,
+                ..RuntimeConfig::default()
+            }),
+            BankTestConfig::default(),
+            Vec::new(),
+        );
+        let bank = Bank::new_from_parent(Arc::new(bank), &Pubkey::default(), 1);
+        let environments = bank
+            .transaction_processor
+            .global_program_cache
+            .read()
+            .unwrap()
+            .get_environments_for_epoch(0)
+            .clone();
+        [
+            environments.program_runtime_v1,
+            environments.program_runtime_v2,
+        ]
+        .map(|program_runtime_environment| {
+            program_runtime_environment
+                .get_function_registry()
+                .iter()
+                .any(|(_key, (name, _function))| name == b"sol_host_time")
+        })
+    };
+    assert_eq!(is_host_time_registered(true), [true, true]);
+    assert_eq!(is_host_time_registered(false), [false, false]);
+}
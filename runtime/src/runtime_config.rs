@@ -1,4 +1,8 @@
-use solana_compute_budget::compute_budget::ComputeBudget;
+use {
+    solana_compute_budget::compute_budget::ComputeBudget,
+    solana_program_runtime::profiling::ProfileVerbosity,
+    solana_svm_log_collector::ProgramLogFilter, std::sync::Arc,
+};
 
 #[cfg(feature = "frozen-abi")]
 impl ::solana_frozen_abi::abi_example::AbiExample for RuntimeConfig {
@@ -14,4 +18,23 @@ pub struct RuntimeConfig {
     pub compute_budget: Option<ComputeBudget>,
     pub log_messages_bytes_limit: Option<usize>,
     pub transaction_account_lock_limit: Option<usize>,
+    /// Register syscalls exposing non-deterministic host state, such as `sol_host_time`. Must
+    /// only be set on nodes which do not take part in consensus, such as test validators.
+    pub enable_diagnostics_syscalls: bool,
+    /// Collapse consecutive identical log messages of a transaction into `<message> (x<count>)`.
+    pub dedup_log_messages: bool,
+    /// Only record the log messages of the programs selected by this filter.
+    pub log_program_filter: Option<Arc<ProgramLogFilter>>,
+    /// Profile every transaction, instead of only the transactions requesting it with the
+    /// compute budget enable profiling instruction. Must only be set on nodes which do not take
+    /// part in consensus, such as test validators.
+    pub profile_all_transactions: bool,
+    /// How much of the profile of each instruction is logged.
+    pub profile_verbosity: ProfileVerbosity,
+    /// Open a profiling section named by the program id around every instruction of the profiled
+    /// transactions, so that uninstrumented programs are profiled too.
+    pub implicit_profiling_sections: bool,
+    /// Record a profiling section around every CPI of the profiled transactions, even if no program
+    /// on the invocation stack has opened a section.
+    pub profile_cpis: bool,
 }
@@ -25,6 +25,7 @@ pub struct TransactionBatch<'a, 'b, Tx: SVMMessage> {
     bank: &'a Bank,
     sanitized_txs: OwnedOrBorrowed<'b, Tx>,
     needs_unlock: bool,
+    lock_us: Option<u64>,
 }
 
 impl<'a, 'b, Tx: SVMMessage> TransactionBatch<'a, 'b, Tx> {
@@ -39,6 +40,7 @@ impl<'a, 'b, Tx: SVMMessage> TransactionBatch<'a, 'b, Tx> {
             bank,
             sanitized_txs,
             needs_unlock: true,
+            lock_us: None,
         }
     }
 
@@ -62,6 +64,17 @@ impl<'a, 'b, Tx: SVMMessage> TransactionBatch<'a, 'b, Tx> {
         self.needs_unlock
     }
 
+    /// Record the microseconds it took to acquire the account locks of the batch
+    pub fn set_lock_us(&mut self, lock_us: u64) {
+        self.lock_us = Some(lock_us);
+    }
+
+    /// Microseconds it took to acquire the account locks of the batch, `None` if they were not
+    /// measured by the bank
+    pub fn lock_us(&self) -> Option<u64> {
+        self.lock_us
+    }
+
     /// For every error result, if the corresponding transaction is
     /// still locked, unlock the transaction and then record the new error.
     pub fn unlock_failures(&mut self, transaction_results: Vec<Result<()>>) {
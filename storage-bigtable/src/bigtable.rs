@@ -1026,6 +1026,8 @@ mod tests {
                 return_data: Some(TransactionReturnData::default()),
                 compute_units_consumed: Some(1234),
                 cost_units: Some(5678),
+                profile: None,
+                instruction_compute_units_consumed: None,
             },
         });
         let expected_block = ConfirmedBlock {
@@ -258,6 +258,8 @@ impl From<StoredConfirmedBlockTransactionStatusMeta> for TransactionStatusMeta {
             return_data: None,
             compute_units_consumed: None,
             cost_units: None,
+            profile: None,
+            instruction_compute_units_consumed: None,
         }
     }
 }
@@ -422,6 +422,8 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             return_data,
             compute_units_consumed,
             cost_units,
+            profile,
+            instruction_compute_units_consumed,
         } = value;
         let err = match status {
             Ok(()) => None,
@@ -464,6 +466,12 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             .collect();
         let return_data_none = return_data.is_none();
         let return_data = return_data.map(|return_data| return_data.into());
+        let instruction_compute_units_consumed =
+            instruction_compute_units_consumed.map(|compute_units_consumed| {
+                generated::InstructionComputeUnits {
+                    compute_units_consumed,
+                }
+            });
 
         Self {
             err,
@@ -483,6 +491,8 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             return_data_none,
             compute_units_consumed,
             cost_units,
+            profile,
+            instruction_compute_units_consumed,
         }
     }
 }
@@ -516,6 +526,8 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             return_data_none,
             compute_units_consumed,
             cost_units,
+            profile,
+            instruction_compute_units_consumed,
         } = value;
         let status = match &err {
             None => Ok(()),
@@ -572,6 +584,8 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
         } else {
             return_data.map(|return_data| return_data.into())
         };
+        let instruction_compute_units_consumed = instruction_compute_units_consumed
+            .map(|instruction_compute_units| instruction_compute_units.compute_units_consumed);
         Ok(Self {
             status,
             fee,
@@ -586,6 +600,8 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             return_data,
             compute_units_consumed,
             cost_units,
+            profile,
+            instruction_compute_units_consumed,
         })
     }
 }
@@ -196,6 +196,10 @@ pub struct StoredTransactionStatusMeta {
     pub compute_units_consumed: Option<u64>,
     #[serde(deserialize_with = "default_on_eof")]
     pub cost_units: Option<u64>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub profile: Option<Vec<u8>>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub instruction_compute_units_consumed: Option<Vec<u64>>,
 }
 
 impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
@@ -213,6 +217,8 @@ impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
             return_data,
             compute_units_consumed,
             cost_units,
+            profile,
+            instruction_compute_units_consumed,
         } = value;
         Self {
             status,
@@ -231,6 +237,8 @@ impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
             return_data,
             compute_units_consumed,
             cost_units,
+            profile,
+            instruction_compute_units_consumed,
         }
     }
 }
@@ -252,6 +260,8 @@ impl TryFrom<TransactionStatusMeta> for StoredTransactionStatusMeta {
             return_data,
             compute_units_consumed,
             cost_units,
+            profile,
+            instruction_compute_units_consumed,
         } = value;
 
         if !loaded_addresses.is_empty() {
@@ -278,6 +288,8 @@ impl TryFrom<TransactionStatusMeta> for StoredTransactionStatusMeta {
             return_data,
             compute_units_consumed,
             cost_units,
+            profile,
+            instruction_compute_units_consumed,
         })
     }
 }
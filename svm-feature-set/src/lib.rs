@@ -36,6 +36,7 @@ pub struct SVMFeatureSet {
     pub disable_zk_elgamal_proof_program: bool,
     pub reenable_zk_elgamal_proof_program: bool,
     pub raise_cpi_nesting_limit_to_8: bool,
+    pub enable_profiling_syscalls: bool,
 }
 
 impl SVMFeatureSet {
@@ -77,6 +78,7 @@ impl SVMFeatureSet {
             disable_zk_elgamal_proof_program: true,
             reenable_zk_elgamal_proof_program: true,
             raise_cpi_nesting_limit_to_8: true,
+            enable_profiling_syscalls: true,
         }
     }
 }
@@ -1,47 +1,492 @@
 pub use log;
-use std::{cell::RefCell, rc::Rc};
+use {
+    base64::{display::Base64Display, prelude::BASE64_STANDARD},
+    solana_pubkey::Pubkey,
+    std::{
+        cell::RefCell,
+        collections::HashSet,
+        fmt::{self, Write},
+        rc::Rc,
+        str::FromStr,
+        sync::Arc,
+    },
+};
 
 const LOG_MESSAGES_BYTES_LIMIT: usize = 10 * 1000;
+const PROFILE_MESSAGES_BYTES_LIMIT: usize = 100 * 1000;
+
+/// Severity of a message logged by a program with `sol_log_level`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    #[default]
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+
+    /// The level passed to `sol_log_level`, from `0` (trace) to `4` (error)
+    pub fn from_u64(level: u64) -> Option<Self> {
+        usize::try_from(level)
+            .ok()
+            .and_then(|level| Self::ALL.get(level).copied())
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseLogLevelError(String);
+
+impl fmt::Display for ParseLogLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid log level {:?}, expected one of trace, debug, info, warn or error",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseLogLevelError {}
+
+impl FromStr for LogLevel {
+    type Err = ParseLogLevelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|level| level.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ParseLogLevelError(s.to_string()))
+    }
+}
+
+/// Fields of a completed profiling entry, logged as
+///
+/// ```notrust
+/// "Program profile: v<version> <kind> <depth> <start-cu> <consumed-cu> <heap-bytes> <return-data-bytes> <flags> <name>"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProfileLogEntry {
+    pub version: u8,
+    pub kind: String,
+    pub depth: usize,
+    pub start_cu: u64,
+    pub consumed_cu: u64,
+    /// Heap usage when the entry was closed, `-` if the program did not sample it
+    pub heap: Option<u64>,
+    pub return_data_bytes: u64,
+    /// Comma separated flags, `-` if there are none
+    pub flags: String,
+    pub name: String,
+}
+
+/// A message recorded by the [`LogCollector`]
+///
+/// Entries keep the values the stable program log messages are formatted from, so that
+/// consumers do not need to parse them again. [`fmt::Display`] renders an entry exactly as the
+/// message it stands for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LogEntry {
+    /// `Program <address> invoke [<depth>]`
+    ProgramInvoke { program_id: Pubkey, depth: usize },
+    /// `Program log: <message>`
+    ProgramLog(String),
+    /// `Program log: [<level>] <message>`
+    ProgramLogLevel { level: LogLevel, message: String },
+    /// `Program data: <base64-encoded-field>*`
+    ///
+    /// Holds the fields already encoded and separated by spaces, so that programs streaming data
+    /// through `sol_log_data` are not copied once more before their message is rendered.
+    ProgramData(String),
+    /// `Program return: <address> <base64-encoded-data>`
+    ProgramReturn { program_id: Pubkey, data: Vec<u8> },
+    /// `Program <address> consumed <consumed> of <limit> compute units`
+    ProgramConsumed {
+        program_id: Pubkey,
+        consumed: u64,
+        limit: u64,
+    },
+    /// `Program <address> success`
+    ProgramSuccess { program_id: Pubkey },
+    /// `Program <address> failed: <error>`
+    ProgramFailure { program_id: Pubkey, error: String },
+    /// `Program profile: v<version> ...`, see [`ProfileLogEntry`]
+    ProfileEntry(ProfileLogEntry),
+    /// `Log truncated`, recorded instead of the first message over the byte limit
+    Truncated,
+    /// `Profile truncated`, recorded instead of the first profiling message over the profile byte
+    /// limit, see [`LogCollector::log_profile`]
+    ProfileTruncated,
+    /// `<entry> (x<count>)`, an entry logged `count` times in a row, see
+    /// [`LogCollector::dedup_consecutive`]
+    Repeated { entry: Box<LogEntry>, count: usize },
+    /// Any other message
+    Message(String),
+}
+
+impl LogEntry {
+    /// Length of the rendered message in bytes
+    pub fn message_len(&self) -> usize {
+        struct Counter(usize);
+        impl Write for Counter {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0 = self.0.saturating_add(s.len());
+                Ok(())
+            }
+        }
+        let mut counter = Counter(0);
+        let _ = write!(counter, "{self}");
+        counter.0
+    }
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ProgramInvoke { program_id, depth } => {
+                write!(f, "Program {program_id} invoke [{depth}]")
+            }
+            Self::ProgramLog(message) => write!(f, "Program log: {message}"),
+            Self::ProgramLogLevel { level, message } => {
+                write!(f, "Program log: [{level}] {message}")
+            }
+            Self::ProgramData(fields) => write!(f, "Program data: {fields}"),
+            Self::ProgramReturn { program_id, data } => write!(
+                f,
+                "Program return: {program_id} {}",
+                Base64Display::new(data, &BASE64_STANDARD)
+            ),
+            Self::ProgramConsumed {
+                program_id,
+                consumed,
+                limit,
+            } => write!(
+                f,
+                "Program {program_id} consumed {consumed} of {limit} compute units"
+            ),
+            Self::ProgramSuccess { program_id } => write!(f, "Program {program_id} success"),
+            Self::ProgramFailure { program_id, error } => {
+                write!(f, "Program {program_id} failed: {error}")
+            }
+            Self::ProfileEntry(entry) => {
+                write!(
+                    f,
+                    "Program profile: v{} {} {} {} {} ",
+                    entry.version, entry.kind, entry.depth, entry.start_cu, entry.consumed_cu,
+                )?;
+                match entry.heap {
+                    Some(heap) => write!(f, "{heap}")?,
+                    None => f.write_str("-")?,
+                }
+                write!(
+                    f,
+                    " {} {} {}",
+                    entry.return_data_bytes, entry.flags, entry.name
+                )
+            }
+            Self::Truncated => f.write_str("Log truncated"),
+            Self::ProfileTruncated => f.write_str("Profile truncated"),
+            Self::Repeated { entry, count } => write!(f, "{entry} (x{count})"),
+            Self::Message(message) => f.write_str(message),
+        }
+    }
+}
+
+/// Selects the programs whose messages are recorded by a [`LogCollector`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProgramLogFilter {
+    /// Only record the messages logged while one of these programs executes
+    Allow(HashSet<Pubkey>),
+    /// Drop the messages logged while one of these programs executes
+    Deny(HashSet<Pubkey>),
+}
+
+impl ProgramLogFilter {
+    /// Whether the messages logged while `program_id` executes are recorded
+    pub fn is_logged(&self, program_id: &Pubkey) -> bool {
+        match self {
+            Self::Allow(program_ids) => program_ids.contains(program_id),
+            Self::Deny(program_ids) => !program_ids.contains(program_id),
+        }
+    }
+}
+
+/// Key/value fields logged by a program together with their log message
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogRecord {
+    /// Index of the message in [`LogCollector::entries`]
+    pub message_index: usize,
+    pub fields: Vec<(String, String)>,
+}
+
+/// Callback observing every entry logged to a [`LogCollector`], see [`LogCollector::listener`]
+pub type LogListener = Box<dyn Fn(&LogEntry)>;
 
 pub struct LogCollector {
-    pub messages: Vec<String>,
+    /// The recorded messages as structured entries, one per message, see
+    /// [`LogCollector::get_recorded_content`] for their string format
+    pub entries: Vec<LogEntry>,
+    /// Structured records, only kept if their message was not truncated
+    pub records: Vec<LogRecord>,
     pub bytes_written: usize,
     pub bytes_limit: Option<usize>,
     pub limit_warning: bool,
+    /// Bytes of the profiling messages, which are counted separately from the other messages
+    pub profile_bytes_written: usize,
+    pub profile_bytes_limit: Option<usize>,
+    pub profile_limit_warning: bool,
+    /// Leveled messages below this level are dropped
+    pub min_level: LogLevel,
+    /// Collapse consecutive identical messages into `<message> (x<count>)`
+    pub dedup_consecutive: bool,
+    /// How many times in a row the last message was logged, 0 if it can not be repeated
+    pub repeat_count: usize,
+    /// Only record the messages logged while the programs selected by this filter execute
+    pub program_filter: Option<Arc<ProgramLogFilter>>,
+    /// Programs on the invocation stack, the executing program last
+    pub program_stack: Vec<Pubkey>,
+    /// Called with every entry logged, before it is filtered or truncated
+    pub listener: Option<LogListener>,
 }
 
 impl Default for LogCollector {
     fn default() -> Self {
         Self {
-            messages: Vec::new(),
+            entries: Vec::new(),
+            records: Vec::new(),
             bytes_written: 0,
             bytes_limit: Some(LOG_MESSAGES_BYTES_LIMIT),
             limit_warning: false,
+            profile_bytes_written: 0,
+            profile_bytes_limit: Some(PROFILE_MESSAGES_BYTES_LIMIT),
+            profile_limit_warning: false,
+            min_level: LogLevel::default(),
+            dedup_consecutive: false,
+            repeat_count: 0,
+            program_filter: None,
+            program_stack: Vec::new(),
+            listener: None,
         }
     }
 }
 
 impl LogCollector {
     pub fn log(&mut self, message: &str) {
+        self.log_entry(LogEntry::Message(message.to_string()));
+    }
+
+    /// Record a structured entry, counting its rendered message against the byte limit
+    pub fn log_entry(&mut self, entry: LogEntry) {
+        if let Some(listener) = &self.listener {
+            listener(&entry);
+        }
+        if !self.is_program_logged() {
+            return;
+        }
+        let len = entry.message_len();
+        if self.dedup_consecutive && self.collapse_repeated(&entry, len) {
+            return;
+        }
+
         let Some(limit) = self.bytes_limit else {
-            self.messages.push(message.to_string());
+            self.entries.push(entry);
+            self.repeat_count = 1;
             return;
         };
 
-        let bytes_written = self.bytes_written.saturating_add(message.len());
+        let bytes_written = self.bytes_written.saturating_add(len);
         if bytes_written >= limit {
             if !self.limit_warning {
                 self.limit_warning = true;
-                self.messages.push(String::from("Log truncated"));
+                self.entries.push(LogEntry::Truncated);
+            }
+            self.repeat_count = 0;
+        } else {
+            self.bytes_written = bytes_written;
+            self.entries.push(entry);
+            self.repeat_count = 1;
+        }
+    }
+
+    /// Record a profiling entry, counting its rendered message against the profile byte limit
+    ///
+    /// Profiles are recorded in order with the other messages, so that they can be attributed to
+    /// the instruction which produced them, but the two never compete for the same bytes: large
+    /// profiles do not truncate the program logs and verbose programs do not truncate profiles.
+    pub fn log_profile(&mut self, entry: LogEntry) {
+        if let Some(listener) = &self.listener {
+            listener(&entry);
+        }
+        if !self.is_program_logged() {
+            return;
+        }
+        // Messages logged after a profile are not folded into it
+        self.repeat_count = 0;
+
+        let profile_bytes_written = self
+            .profile_bytes_written
+            .saturating_add(entry.message_len());
+        if self
+            .profile_bytes_limit
+            .is_some_and(|limit| profile_bytes_written >= limit)
+        {
+            if !self.profile_limit_warning {
+                self.profile_limit_warning = true;
+                self.entries.push(LogEntry::ProfileTruncated);
             }
         } else {
+            self.profile_bytes_written = profile_bytes_written;
+            self.entries.push(entry);
+        }
+    }
+
+    /// Whether any profiling entry was recorded, including ones dropped by the profile byte limit
+    pub fn is_profiled(&self) -> bool {
+        self.profile_bytes_written > 0 || self.profile_limit_warning
+    }
+
+    /// Whether logging a message of `len` bytes could change the recorded messages
+    ///
+    /// Returns `false` once the byte limit was hit and the message does not fit in the remaining
+    /// bytes, so that callers can skip formatting it. Always returns `true` if there is a
+    /// [`listener`](LogCollector::listener).
+    pub fn would_log(&self, len: usize) -> bool {
+        if self.listener.is_some() {
+            return true;
+        }
+        if !self.is_program_logged() {
+            return false;
+        }
+        let Some(limit) = self.bytes_limit else {
+            return true;
+        };
+        !self.limit_warning
+            || (self.dedup_consecutive && self.repeat_count > 0)
+            || self.bytes_written.saturating_add(len) < limit
+    }
+
+    /// Fold `entry`, whose message is `len` bytes long, into the last entry if it repeats it,
+    /// returns whether it did
+    fn collapse_repeated(&mut self, entry: &LogEntry, len: usize) -> bool {
+        let repeat_count = self.repeat_count;
+        let Some(last) = self.entries.last_mut().filter(|_| repeat_count > 0) else {
+            return false;
+        };
+        let is_repeated = if repeat_count == 1 {
+            *last == *entry
+        } else {
+            matches!(last, LogEntry::Repeated { entry: repeated, .. } if **repeated == *entry)
+        };
+        if !is_repeated {
+            return false;
+        }
+
+        let suffix_len = |count: usize| format!(" (x{count})").len();
+        let was_repeated = repeat_count > 1;
+        let last_len = if was_repeated {
+            len.saturating_add(suffix_len(repeat_count))
+        } else {
+            len
+        };
+        let repeat_count = repeat_count.saturating_add(1);
+        if let Some(limit) = self.bytes_limit {
+            let bytes_written = self.bytes_written.saturating_add(
+                len.saturating_add(suffix_len(repeat_count))
+                    .saturating_sub(last_len),
+            );
+            if bytes_written >= limit {
+                return false;
+            }
             self.bytes_written = bytes_written;
-            self.messages.push(message.to_string());
+        }
+        match last {
+            LogEntry::Repeated { count, .. } if was_repeated => *count = repeat_count,
+            last => {
+                let entry = std::mem::replace(last, LogEntry::Truncated);
+                *last = LogEntry::Repeated {
+                    entry: Box::new(entry),
+                    count: repeat_count,
+                };
+            }
+        }
+        self.repeat_count = repeat_count;
+        true
+    }
+
+    /// Log a message together with the structured fields it was formatted from
+    pub fn log_record(&mut self, message: &str, fields: Vec<(String, String)>) {
+        let message_index = self.entries.len();
+        let limit_warning = self.limit_warning;
+        self.log(message);
+        if self.entries.len() > message_index && self.limit_warning == limit_warning {
+            self.records.push(LogRecord {
+                message_index,
+                fields,
+            });
         }
     }
 
-    pub fn get_recorded_content(&self) -> &[String] {
-        self.messages.as_slice()
+    /// Track the program starting to execute, see [`LogCollector::program_filter`]
+    pub fn push_program(&mut self, program_id: Pubkey) {
+        self.program_stack.push(program_id);
+    }
+
+    /// Track the return of the executing program, see [`LogCollector::program_filter`]
+    pub fn pop_program(&mut self) {
+        self.program_stack.pop();
+    }
+
+    /// Whether the messages logged by the executing program are recorded
+    ///
+    /// Messages logged outside of any program are always recorded.
+    fn is_program_logged(&self) -> bool {
+        match (&self.program_filter, self.program_stack.last()) {
+            (Some(program_filter), Some(program_id)) => program_filter.is_logged(program_id),
+            _ => true,
+        }
+    }
+
+    /// Whether a message logged at `level` would be kept
+    pub fn is_level_enabled(&self, level: LogLevel) -> bool {
+        level >= self.min_level
+    }
+
+    /// The recorded messages in the string format of the stable program logs
+    pub fn get_recorded_content(&self) -> Vec<String> {
+        self.entries.iter().map(LogEntry::to_string).collect()
+    }
+
+    pub fn get_recorded_entries(&self) -> &[LogEntry] {
+        self.entries.as_slice()
+    }
+
+    pub fn get_recorded_records(&self) -> &[LogRecord] {
+        self.records.as_slice()
     }
 
     pub fn new_ref() -> Rc<RefCell<Self>> {
@@ -56,7 +501,11 @@ impl LogCollector {
     }
 
     pub fn into_messages(self) -> Vec<String> {
-        self.messages
+        self.get_recorded_content()
+    }
+
+    pub fn into_entries(self) -> Vec<LogEntry> {
+        self.entries
     }
 }
 
@@ -119,4 +568,363 @@ pub(crate) mod tests {
         }
         assert_eq!(logs.last(), Some(&"Log truncated".to_string()));
     }
+
+    #[test]
+    fn test_would_log() {
+        let mut lc = LogCollector {
+            bytes_limit: Some(8),
+            ..LogCollector::default()
+        };
+        assert!(lc.would_log(100));
+
+        lc.log("abcd");
+        // The first message over the limit still logs the truncation warning
+        assert!(lc.would_log(4));
+        lc.log("efgh");
+        assert_eq!(lc.get_recorded_content(), &["abcd", "Log truncated"]);
+        assert!(!lc.would_log(4));
+        assert!(lc.would_log(3));
+
+        let lc = LogCollector {
+            bytes_limit: None,
+            limit_warning: true,
+            ..LogCollector::default()
+        };
+        assert!(lc.would_log(usize::MAX));
+    }
+
+    #[test]
+    fn test_log_records() {
+        let mut lc = LogCollector {
+            bytes_limit: Some(16),
+            ..LogCollector::default()
+        };
+
+        lc.log("first");
+        let fields = vec![("key".to_string(), "value".to_string())];
+        lc.log_record("key=value", fields.clone());
+        // Records whose message is truncated are dropped
+        lc.log_record("key=truncated", fields.clone());
+        lc.log_record("k", fields.clone());
+
+        assert_eq!(
+            lc.get_recorded_content(),
+            &["first", "key=value", "Log truncated", "k"]
+        );
+        assert_eq!(
+            lc.get_recorded_records(),
+            &[
+                LogRecord {
+                    message_index: 1,
+                    fields: fields.clone(),
+                },
+                LogRecord {
+                    message_index: 3,
+                    fields,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dedup_consecutive() {
+        let mut lc = LogCollector {
+            dedup_consecutive: true,
+            ..LogCollector::default()
+        };
+
+        for _ in 0..3 {
+            lc.log("loop");
+        }
+        let fields = vec![("key".to_string(), "value".to_string())];
+        lc.log_record("key=value", fields.clone());
+        lc.log_record("key=value", fields.clone());
+        lc.log("loop");
+        lc.log("loop (x2)");
+        lc.log("loop (x2)");
+        assert_eq!(
+            lc.get_recorded_content(),
+            &["loop (x3)", "key=value (x2)", "loop", "loop (x2) (x2)"]
+        );
+        assert_eq!(
+            lc.get_recorded_records(),
+            &[LogRecord {
+                message_index: 1,
+                fields,
+            }]
+        );
+        assert_eq!(lc.bytes_written, 41);
+
+        // Repetitions count against the limit
+        let mut lc = LogCollector {
+            bytes_limit: Some(10),
+            dedup_consecutive: true,
+            ..LogCollector::default()
+        };
+        for _ in 0..10 {
+            lc.log("abcd");
+        }
+        assert_eq!(lc.get_recorded_content(), &["abcd (x9)", "Log truncated"]);
+
+        // Deduplication is opt-in
+        let mut lc = LogCollector::default();
+        lc.log("loop");
+        lc.log("loop");
+        assert_eq!(lc.get_recorded_content(), &["loop", "loop"]);
+    }
+
+    #[test]
+    fn test_messages_render_entries() {
+        let mut lc = LogCollector {
+            bytes_limit: Some(32),
+            profile_bytes_limit: Some(1),
+            dedup_consecutive: true,
+            ..LogCollector::default()
+        };
+        for _ in 0..3 {
+            lc.log_entry(LogEntry::ProgramLog("loop".to_string()));
+        }
+        lc.log_profile(LogEntry::ProfileEntry(ProfileLogEntry::default()));
+        lc.log("over the byte limit of the collector");
+
+        assert_eq!(
+            lc.get_recorded_content(),
+            &[
+                "Program log: loop (x3)",
+                "Profile truncated",
+                "Log truncated"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_log_entries() {
+        let program_id = Pubkey::new_from_array([1; 32]);
+        let entries = [
+            (
+                LogEntry::ProgramInvoke {
+                    program_id,
+                    depth: 1,
+                },
+                format!("Program {program_id} invoke [1]"),
+            ),
+            (
+                LogEntry::ProgramLog("hello".to_string()),
+                "Program log: hello".to_string(),
+            ),
+            (
+                LogEntry::ProgramLogLevel {
+                    level: LogLevel::Warn,
+                    message: "careful".to_string(),
+                },
+                "Program log: [warn] careful".to_string(),
+            ),
+            (
+                LogEntry::ProgramData("YWI= ".to_string()),
+                "Program data: YWI= ".to_string(),
+            ),
+            (
+                LogEntry::ProgramData(String::new()),
+                "Program data: ".to_string(),
+            ),
+            (
+                LogEntry::ProgramReturn {
+                    program_id,
+                    data: vec![1, 2, 3],
+                },
+                format!("Program return: {program_id} AQID"),
+            ),
+            (
+                LogEntry::ProgramConsumed {
+                    program_id,
+                    consumed: 150,
+                    limit: 200_000,
+                },
+                format!("Program {program_id} consumed 150 of 200000 compute units"),
+            ),
+            (
+                LogEntry::ProgramSuccess { program_id },
+                format!("Program {program_id} success"),
+            ),
+            (
+                LogEntry::ProgramFailure {
+                    program_id,
+                    error: "custom program error: 0x1".to_string(),
+                },
+                format!("Program {program_id} failed: custom program error: 0x1"),
+            ),
+            (
+                LogEntry::ProfileEntry(ProfileLogEntry {
+                    version: 3,
+                    kind: "section".to_string(),
+                    depth: 1,
+                    start_cu: 10,
+                    consumed_cu: 20,
+                    heap: None,
+                    return_data_bytes: 0,
+                    flags: "-".to_string(),
+                    name: "my section".to_string(),
+                }),
+                "Program profile: v3 section 1 10 20 - 0 - my section".to_string(),
+            ),
+            (LogEntry::Message("raw".to_string()), "raw".to_string()),
+        ];
+
+        let mut lc = LogCollector::default();
+        for (entry, message) in entries.iter() {
+            assert_eq!(entry.to_string(), *message);
+            assert_eq!(entry.message_len(), message.len());
+            lc.log_entry(entry.clone());
+        }
+        assert_eq!(
+            lc.get_recorded_entries(),
+            entries
+                .iter()
+                .map(|(entry, _message)| entry.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            lc.get_recorded_content(),
+            entries
+                .iter()
+                .map(|(_entry, message)| message.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            lc.bytes_written,
+            entries.iter().map(|(_entry, message)| message.len()).sum()
+        );
+
+        // Repeated entries keep their structure
+        let mut lc = LogCollector {
+            dedup_consecutive: true,
+            ..LogCollector::default()
+        };
+        for _ in 0..3 {
+            lc.log_entry(LogEntry::ProgramSuccess { program_id });
+        }
+        assert_eq!(
+            lc.get_recorded_entries(),
+            &[LogEntry::Repeated {
+                entry: Box::new(LogEntry::ProgramSuccess { program_id }),
+                count: 3,
+            }]
+        );
+        assert_eq!(
+            lc.into_messages(),
+            vec![format!("Program {program_id} success (x3)")]
+        );
+    }
+
+    #[test]
+    fn test_program_filter() {
+        let noisy = Pubkey::new_from_array([1; 32]);
+        let program = Pubkey::new_from_array([2; 32]);
+        let log_nested = |program_filter: ProgramLogFilter| {
+            let mut lc = LogCollector {
+                program_filter: Some(Arc::new(program_filter)),
+                ..LogCollector::default()
+            };
+            lc.log("outside");
+            lc.push_program(program);
+            lc.log("program");
+            lc.push_program(noisy);
+            lc.log("noisy");
+            lc.pop_program();
+            lc.log("program again");
+            lc.pop_program();
+            lc.into_messages()
+        };
+
+        assert_eq!(
+            log_nested(ProgramLogFilter::Deny(HashSet::from([noisy]))),
+            vec!["outside", "program", "program again"]
+        );
+        assert_eq!(
+            log_nested(ProgramLogFilter::Allow(HashSet::from([noisy]))),
+            vec!["outside", "noisy"]
+        );
+        assert!(!LogCollector {
+            program_filter: Some(Arc::new(ProgramLogFilter::Allow(HashSet::new()))),
+            program_stack: vec![program],
+            ..LogCollector::default()
+        }
+        .would_log(0));
+    }
+
+    #[test]
+    fn test_listener() {
+        let program_id = Pubkey::new_from_array([1; 32]);
+        let emitted = Rc::new(RefCell::new(Vec::new()));
+        let mut lc = LogCollector {
+            bytes_limit: Some(10),
+            program_filter: Some(Arc::new(ProgramLogFilter::Deny(HashSet::from([
+                program_id,
+            ])))),
+            listener: Some(Box::new({
+                let emitted = emitted.clone();
+                move |entry: &LogEntry| emitted.borrow_mut().push(entry.to_string())
+            })),
+            ..LogCollector::default()
+        };
+        lc.log("0123456789");
+        lc.push_program(program_id);
+        assert!(lc.would_log(100));
+        lc.log("filtered");
+        lc.pop_program();
+
+        // The listener observes the messages which are truncated or filtered out
+        assert_eq!(lc.get_recorded_content(), vec!["Log truncated"]);
+        assert_eq!(*emitted.borrow(), vec!["0123456789", "filtered"]);
+    }
+
+    #[test]
+    fn test_profile_limit() {
+        let profile = || LogEntry::Message("Program profile: v3 counter 0 1".to_string());
+        let profile_len = profile().message_len();
+        let mut lc = LogCollector {
+            bytes_limit: Some(10),
+            profile_bytes_limit: Some(profile_len * 2 + 1),
+            ..LogCollector::default()
+        };
+        lc.log("x".repeat(20).as_str());
+        assert!(!lc.is_profiled());
+        lc.log_profile(profile());
+        assert!(lc.is_profiled());
+        lc.log_profile(profile());
+        lc.log_profile(profile());
+        lc.log_profile(profile());
+        lc.log("y");
+        assert_eq!(
+            lc.get_recorded_content(),
+            vec![
+                "Log truncated",
+                "Program profile: v3 counter 0 1",
+                "Program profile: v3 counter 0 1",
+                "Profile truncated",
+                "y",
+            ]
+        );
+        assert_eq!(lc.bytes_written, 1);
+        assert_eq!(lc.profile_bytes_written, profile_len * 2);
+    }
+
+    #[test]
+    fn test_log_level() {
+        for (index, level) in LogLevel::ALL.into_iter().enumerate() {
+            assert_eq!(LogLevel::from_u64(index as u64), Some(level));
+            assert_eq!(level.as_str().parse(), Ok(level));
+        }
+        assert_eq!(LogLevel::from_u64(5), None);
+        assert_eq!(LogLevel::from_u64(u64::MAX), None);
+        assert_eq!("WARN".parse(), Ok(LogLevel::Warn));
+        assert!("verbose".parse::<LogLevel>().is_err());
+
+        let mut lc = LogCollector::default();
+        assert!(lc.is_level_enabled(LogLevel::Trace));
+        lc.min_level = LogLevel::Warn;
+        assert!(!lc.is_level_enabled(LogLevel::Info));
+        assert!(lc.is_level_enabled(LogLevel::Warn));
+        assert!(lc.is_level_enabled(LogLevel::Error));
+    }
 }
@@ -28,10 +28,7 @@ use {
     },
     solana_pubkey::Pubkey,
     solana_rent::Rent,
-    solana_sdk_ids::{
-        bpf_loader_upgradeable, native_loader,
-        sysvar::{self, slot_history},
-    },
+    solana_sdk_ids::{bpf_loader_upgradeable, native_loader, sysvar},
     solana_svm_callback::{AccountState, TransactionProcessingCallback},
     solana_svm_feature_set::SVMFeatureSet,
     solana_svm_transaction::svm_message::SVMMessage,
@@ -176,12 +173,14 @@ impl<'a, CB: TransactionProcessingCallback> AccountLoader<'a, CB> {
     ) -> AccountLoader<'a, CB> {
         let mut loaded_accounts = AHashMap::with_capacity(capacity);
 
-        // SlotHistory may be overridden for simulation.
-        // No other uses of AccountOverrides are expected.
-        if let Some(slot_history) =
-            account_overrides.and_then(|overrides| overrides.get(&slot_history::id()))
-        {
-            loaded_accounts.insert(slot_history::id(), slot_history.clone());
+        // SlotHistory and the accounts requested by the caller may be overridden for simulation.
+        // Seeding them takes precedence over any load from accounts-db.
+        if let Some(account_overrides) = account_overrides {
+            loaded_accounts.extend(
+                account_overrides
+                    .iter()
+                    .map(|(pubkey, account)| (*pubkey, account.clone())),
+            );
         }
 
         Self {
@@ -1330,6 +1329,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_overrides_replace_stored_accounts() {
+        solana_logger::setup();
+        let keypair = Keypair::new();
+        let other_key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        // The fee payer is loaded, and overridden, during fee validation rather than here
+        let mut account_overrides = AccountOverrides::default();
+        let mut other_account = AccountSharedData::new(7, 3, &owner);
+        other_account.set_data_from_slice(&[1, 2, 3]);
+        account_overrides.set_account(&other_key, Some(other_account));
+
+        let mut program_account = AccountSharedData::default();
+        program_account.set_lamports(1);
+        program_account.set_executable(true);
+        program_account.set_owner(native_loader::id());
+
+        let instructions = vec![CompiledInstruction::new(2, &(), vec![0, 1])];
+        let tx = Transaction::new_with_compiled_instructions(
+            &[&keypair],
+            &[other_key],
+            Hash::default(),
+            vec![bpf_loader::id()],
+            instructions,
+        );
+
+        let loaded_accounts = load_accounts_no_store(
+            &[
+                (
+                    keypair.pubkey(),
+                    AccountSharedData::new(1_000_000, 0, &Pubkey::default()),
+                ),
+                (other_key, AccountSharedData::new(1, 0, &Pubkey::default())),
+                (bpf_loader::id(), program_account),
+            ],
+            tx,
+            Some(&account_overrides),
+        );
+        match &loaded_accounts {
+            TransactionLoadResult::Loaded(loaded_transaction) => {
+                assert_eq!(loaded_transaction.accounts[0].0, keypair.pubkey());
+                assert_eq!(loaded_transaction.accounts[1].0, other_key);
+                assert_eq!(loaded_transaction.accounts[1].1.lamports(), 7);
+                assert_eq!(loaded_transaction.accounts[1].1.owner(), &owner);
+                assert_eq!(loaded_transaction.accounts[1].1.data(), &[1, 2, 3]);
+            }
+            TransactionLoadResult::FeesOnly(fees_only_tx) => panic!("{}", fees_only_tx.load_error),
+            TransactionLoadResult::NotLoaded(e) => panic!("{e}"),
+        }
+    }
+
     #[test]
     fn test_accumulate_and_check_loaded_account_data_size() {
         let mut error_metrics = TransactionErrorMetrics::default();
@@ -12,12 +12,15 @@ use {
 /// For each instruction it calls the program entrypoint method and verifies that the result of
 /// the call does not violate the bank's accounting rules.
 /// The accounts are committed back to the bank only if every instruction succeeds.
+/// The units consumed by each top-level instruction that ran are pushed to
+/// `instruction_consumed_units`, including the one that failed.
 pub(crate) fn process_message(
     message: &impl SVMMessage,
     program_indices: &[IndexOfAccount],
     invoke_context: &mut InvokeContext,
     execute_timings: &mut ExecuteTimings,
     accumulated_consumed_units: &mut u64,
+    instruction_consumed_units: &mut Vec<u64>,
 ) -> Result<(), TransactionError> {
     debug_assert_eq!(program_indices.len(), message.num_instructions());
     for (top_level_instruction_index, ((program_id, instruction), program_account_index)) in message
@@ -46,6 +49,7 @@ pub(crate) fn process_message(
 
         *accumulated_consumed_units =
             accumulated_consumed_units.saturating_add(compute_units_consumed);
+        instruction_consumed_units.push(compute_units_consumed);
         // The per_program_timings are only used for metrics reporting at the trace
         // level, so they should only be accumulated when trace level is enabled.
         if log::log_enabled!(log::Level::Trace) {
@@ -234,14 +238,17 @@ mod tests {
             SVMTransactionExecutionBudget::default(),
             SVMTransactionExecutionCost::default(),
         );
+        let mut instruction_consumed_units = Vec::new();
         let result = process_message(
             &message,
             &program_indices,
             &mut invoke_context,
             &mut ExecuteTimings::default(),
             &mut 0,
+            &mut instruction_consumed_units,
         );
         assert!(result.is_ok());
+        assert_eq!(instruction_consumed_units, vec![1]);
         assert_eq!(
             transaction_context
                 .accounts()
@@ -294,6 +301,7 @@ mod tests {
             &mut invoke_context,
             &mut ExecuteTimings::default(),
             &mut 0,
+            &mut Vec::new(),
         );
         assert_eq!(
             result,
@@ -338,6 +346,7 @@ mod tests {
             &mut invoke_context,
             &mut ExecuteTimings::default(),
             &mut 0,
+            &mut Vec::new(),
         );
         assert_eq!(
             result,
@@ -471,6 +480,7 @@ mod tests {
             &mut invoke_context,
             &mut ExecuteTimings::default(),
             &mut 0,
+            &mut Vec::new(),
         );
         assert_eq!(
             result,
@@ -510,6 +520,7 @@ mod tests {
             &mut invoke_context,
             &mut ExecuteTimings::default(),
             &mut 0,
+            &mut Vec::new(),
         );
         assert!(result.is_ok());
 
@@ -546,6 +557,7 @@ mod tests {
             &mut invoke_context,
             &mut ExecuteTimings::default(),
             &mut 0,
+            &mut Vec::new(),
         );
         assert!(result.is_ok());
         assert_eq!(
@@ -694,6 +706,7 @@ mod tests {
             &mut invoke_context,
             &mut ExecuteTimings::default(),
             &mut 0,
+            &mut Vec::new(),
         );
 
         assert_eq!(
@@ -4,9 +4,12 @@ use {
     solana_instruction::error::InstructionError,
     solana_loader_v3_interface::state::UpgradeableLoaderState,
     solana_loader_v4_interface::state::{LoaderV4State, LoaderV4Status},
-    solana_program_runtime::loaded_programs::{
-        LoadProgramMetrics, ProgramCacheEntry, ProgramCacheEntryOwner, ProgramCacheEntryType,
-        ProgramRuntimeEnvironment, ProgramRuntimeEnvironments, DELAY_VISIBILITY_SLOT_OFFSET,
+    solana_program_runtime::{
+        loaded_programs::{
+            LoadProgramMetrics, ProgramCacheEntry, ProgramCacheEntryOwner, ProgramCacheEntryType,
+            ProgramRuntimeEnvironment, ProgramRuntimeEnvironments, DELAY_VISIBILITY_SLOT_OFFSET,
+        },
+        profiling::ProgramLoadProfile,
     },
     solana_pubkey::Pubkey,
     solana_sdk_ids::{bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable, loader_v4},
@@ -126,6 +129,27 @@ pub fn load_program_with_pubkey<CB: TransactionProcessingCallback>(
     execute_timings: &mut ExecuteTimings,
     reload: bool,
 ) -> Option<Arc<ProgramCacheEntry>> {
+    load_program_with_pubkey_and_profile(
+        callbacks,
+        environments,
+        pubkey,
+        slot,
+        execute_timings,
+        reload,
+    )
+    .map(|(loaded_program, _profile)| loaded_program)
+}
+
+/// Loads the program with the given pubkey like [`load_program_with_pubkey`], also returning the
+/// time spent loading it.
+pub(crate) fn load_program_with_pubkey_and_profile<CB: TransactionProcessingCallback>(
+    callbacks: &CB,
+    environments: &ProgramRuntimeEnvironments,
+    pubkey: &Pubkey,
+    slot: Slot,
+    execute_timings: &mut ExecuteTimings,
+    reload: bool,
+) -> Option<(Arc<ProgramCacheEntry>, ProgramLoadProfile)> {
     let mut load_program_metrics = LoadProgramMetrics {
         program_id: pubkey.to_string(),
         ..LoadProgramMetrics::default()
@@ -208,7 +232,14 @@ pub fn load_program_with_pubkey<CB: TransactionProcessingCallback>(
 
     load_program_metrics.submit_datapoint(&mut execute_timings.details);
     loaded_program.update_access_slot(slot);
-    Some(Arc::new(loaded_program))
+    let profile = ProgramLoadProfile {
+        program_id: *pubkey,
+        register_syscalls_us: load_program_metrics.register_syscalls_us,
+        load_elf_us: load_program_metrics.load_elf_us,
+        verify_code_us: load_program_metrics.verify_code_us,
+        jit_compile_us: load_program_metrics.jit_compile_us,
+    };
+    Some((Arc::new(loaded_program), profile))
 }
 
 /// Find the slot in which the program was most recently modified.
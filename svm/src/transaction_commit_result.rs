@@ -1,7 +1,10 @@
 use {
     crate::transaction_execution_result::TransactionLoadedAccountsStats,
-    solana_fee_structure::FeeDetails, solana_message::inner_instruction::InnerInstructionsList,
-    solana_transaction_context::TransactionReturnData, solana_transaction_error::TransactionResult,
+    solana_fee_structure::FeeDetails,
+    solana_message::inner_instruction::InnerInstructionsList,
+    solana_program_runtime::profiling::{InstructionProfile, ProgramLoadProfile},
+    solana_transaction_context::TransactionReturnData,
+    solana_transaction_error::TransactionResult,
 };
 
 pub type TransactionCommitResult = TransactionResult<CommittedTransaction>;
@@ -14,9 +17,18 @@ pub struct CommittedTransaction {
     pub inner_instructions: Option<InnerInstructionsList>,
     pub return_data: Option<TransactionReturnData>,
     pub executed_units: u64,
+    /// Compute units consumed by each top-level instruction which was executed, ending at the
+    /// instruction which failed if any
+    pub instruction_executed_units: Vec<u64>,
     pub fee_details: FeeDetails,
     pub loaded_account_stats: TransactionLoadedAccountsStats,
     pub fee_payer_post_balance: u64,
+    /// Profiles recorded by the executed instructions, only set if profiling recording is
+    /// enabled
+    pub profiling_results: Option<Vec<InstructionProfile>>,
+    /// Load timings of the programs loaded for the transaction, only set if profiling recording
+    /// is enabled
+    pub program_load_profiles: Option<Vec<ProgramLoadProfile>>,
 }
 
 pub trait TransactionCommitResultExtensions {
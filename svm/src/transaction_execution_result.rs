@@ -1,7 +1,10 @@
 use {
     crate::account_loader::LoadedTransaction,
     solana_message::inner_instruction::InnerInstructionsList,
-    solana_program_runtime::loaded_programs::ProgramCacheEntry,
+    solana_program_runtime::{
+        loaded_programs::ProgramCacheEntry,
+        profiling::{InstructionProfile, ProgramLoadProfile},
+    },
     solana_pubkey::Pubkey,
     solana_transaction_context::TransactionReturnData,
     solana_transaction_error::TransactionResult,
@@ -32,11 +35,54 @@ pub struct TransactionExecutionDetails {
     pub status: TransactionResult<()>,
     pub log_messages: Option<Vec<String>>,
     pub inner_instructions: Option<InnerInstructionsList>,
+    /// Every instruction which was executed, including the top-level ones, in the order of
+    /// the instruction trace, only set if CPI recording is enabled
+    pub instruction_trace: Option<Vec<TracedInstruction>>,
     pub return_data: Option<TransactionReturnData>,
     pub executed_units: u64,
+    /// Compute units consumed by each top-level instruction which was executed, ending at the
+    /// instruction which failed if any
+    pub instruction_executed_units: Vec<u64>,
     /// The change in accounts data len for this transaction.
     /// NOTE: This value is valid IFF `status` is `Ok`.
     pub accounts_data_len_delta: i64,
+    /// Profiles recorded by the executed instructions, only set if profiling recording is
+    /// enabled
+    pub profiling_results: Option<Vec<InstructionProfile>>,
+    /// Load timings of the programs which had to be loaded into the program cache before the
+    /// transaction could be executed, only set if profiling recording is enabled
+    pub program_load_profiles: Option<Vec<ProgramLoadProfile>>,
+    /// Time spent waiting for the accounts of the transaction, only set if profiling recording
+    /// is enabled
+    pub load_profile: Option<TransactionLoadProfile>,
+}
+
+/// An instruction of the instruction trace of an executed transaction
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TracedInstruction {
+    /// Index of the top-level instruction which is or invoked this instruction
+    pub top_level_instruction_index: usize,
+    /// Stack height of the instruction, top-level instructions are at
+    /// `TRANSACTION_LEVEL_STACK_HEIGHT`
+    pub stack_height: usize,
+    pub program_id: Pubkey,
+    /// Compute units consumed by the instruction, including the instructions it invoked
+    pub compute_units_consumed: u64,
+    /// Whether the instruction returned an error
+    pub failed: bool,
+}
+
+/// Host time spent before a transaction could start executing
+///
+/// Tells a transaction blocked on hot account locks or large account loads apart from a slow
+/// program.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TransactionLoadProfile {
+    /// Microseconds spent loading the accounts of the transaction
+    pub account_load_us: u64,
+    /// Microseconds spent acquiring the account locks of the batch the transaction was executed
+    /// in, `None` unless set by the caller which acquired them
+    pub lock_wait_us: Option<u64>,
 }
 
 impl TransactionExecutionDetails {
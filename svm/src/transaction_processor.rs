@@ -10,12 +10,15 @@ use {
         account_overrides::AccountOverrides,
         message_processor::process_message,
         nonce_info::NonceInfo,
-        program_loader::{get_program_modification_slot, load_program_with_pubkey},
+        program_loader::{get_program_modification_slot, load_program_with_pubkey_and_profile},
         rollback_accounts::RollbackAccounts,
         transaction_account_state_info::TransactionAccountStateInfo,
         transaction_balances::{BalanceCollectionRoutines, BalanceCollector},
         transaction_error_metrics::TransactionErrorMetrics,
-        transaction_execution_result::{ExecutedTransaction, TransactionExecutionDetails},
+        transaction_execution_result::{
+            ExecutedTransaction, TracedInstruction, TransactionExecutionDetails,
+            TransactionLoadProfile,
+        },
         transaction_processing_result::{ProcessedTransaction, TransactionProcessingResult},
     },
     log::debug,
@@ -34,12 +37,15 @@ use {
     },
     solana_program_runtime::{
         execution_budget::SVMTransactionExecutionCost,
-        invoke_context::{EnvironmentConfig, InvokeContext},
+        instrumentation::InstrumentationObserver,
+        invoke_context::{EnvironmentConfig, InstructionOutcome, InvokeContext},
         loaded_programs::{
             ForkGraph, ProgramCache, ProgramCacheEntry, ProgramCacheForTxBatch,
             ProgramCacheMatchCriteria, ProgramRuntimeEnvironment,
         },
+        profiling::{ProfileBudget, ProfileVerbosity},
         solana_sbpf::{program::BuiltinProgram, vm::Config as VmConfig},
+        stable_log,
         sysvar_cache::SysvarCache,
     },
     solana_pubkey::Pubkey,
@@ -47,7 +53,7 @@ use {
     solana_sdk_ids::system_program,
     solana_svm_callback::TransactionProcessingCallback,
     solana_svm_feature_set::SVMFeatureSet,
-    solana_svm_log_collector::LogCollector,
+    solana_svm_log_collector::{LogCollector, LogLevel, ProgramLogFilter},
     solana_svm_measure::{measure::Measure, measure_us},
     solana_svm_timings::{ExecuteTimingType, ExecuteTimings},
     solana_svm_transaction::{svm_message::SVMMessage, svm_transaction::SVMTransaction},
@@ -88,6 +94,10 @@ pub struct ExecutionRecordingConfig {
     pub enable_log_recording: bool,
     pub enable_return_data_recording: bool,
     pub enable_transaction_balance_recording: bool,
+    /// Whether to return the profiles recorded by the executed instructions, see
+    /// [`InvokeContext::take_profiling_results`], and the load timings of the programs loaded
+    /// for the transaction
+    pub enable_profiling_recording: bool,
 }
 
 impl ExecutionRecordingConfig {
@@ -97,6 +107,7 @@ impl ExecutionRecordingConfig {
             enable_log_recording: option,
             enable_cpi_recording: option,
             enable_transaction_balance_recording: option,
+            enable_profiling_recording: option,
         }
     }
 }
@@ -112,6 +123,36 @@ pub struct TransactionProcessingConfig<'a> {
     pub check_program_modification_slot: bool,
     /// The maximum number of bytes that log messages can consume.
     pub log_messages_bytes_limit: Option<usize>,
+    /// Messages logged by programs with a level below this one are
+    /// dropped.
+    pub min_log_level: LogLevel,
+    /// Whether to collapse consecutive identical log messages into a
+    /// single `<message> (x<count>)` message.
+    pub dedup_log_messages: bool,
+    /// Only record the messages logged while the programs selected by
+    /// this filter execute.
+    pub log_program_filter: Option<std::sync::Arc<ProgramLogFilter>>,
+    /// Whether to profile every transaction, instead of only the
+    /// transactions requesting it with the compute budget enable profiling
+    /// instruction. Must not be set on nodes taking part in consensus.
+    pub profile_all_transactions: bool,
+    /// The maximum number of return data bytes kept in the profile of
+    /// each instruction, `PROFILE_RETURN_DATA_BYTES_LIMIT` if `None`.
+    pub profile_return_data_bytes_limit: Option<usize>,
+    /// How much of the profile of each instruction is logged.
+    pub profile_verbosity: ProfileVerbosity,
+    /// Whether to open a profiling section named by the program id
+    /// around every instruction of the profiled transactions, so that
+    /// uninstrumented programs are profiled too.
+    pub implicit_profiling_sections: bool,
+    /// Whether to record a profiling section around every CPI of the
+    /// profiled transactions, even if no program on the invocation
+    /// stack has opened a section.
+    pub profile_cpis: bool,
+    /// Notified of the execution of every transaction, for tools such as
+    /// tracers. Syscalls are only observed for the programs loaded in an
+    /// observed program runtime environment.
+    pub instrumentation_observer: Option<std::sync::Arc<dyn InstrumentationObserver>>,
     /// Whether to limit the number of programs loaded for the transaction
     /// batch.
     pub limit_to_load_programs: bool,
@@ -478,7 +519,7 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                         };
                     }
 
-                    let executed_tx = self.execute_loaded_transaction(
+                    let mut executed_tx = self.execute_loaded_transaction(
                         callbacks,
                         tx,
                         loaded_transaction,
@@ -488,6 +529,12 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                         environment,
                         config,
                     );
+                    if config.recording_config.enable_profiling_recording {
+                        executed_tx.execution_details.load_profile = Some(TransactionLoadProfile {
+                            account_load_us: single_load_us,
+                            lock_wait_us: None,
+                        });
+                    }
 
                     // Update loaded accounts cache with account states which might have changed.
                     // Also update local program cache with modifications made by the transaction,
@@ -768,7 +815,7 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
 
                 let program_to_store = program_to_load.map(|key| {
                     // Load, verify and compile one program.
-                    let program = load_program_with_pubkey(
+                    let (program, profile) = load_program_with_pubkey_and_profile(
                         account_loader,
                         &global_program_cache.get_environments_for_epoch(self.epoch),
                         &key,
@@ -777,7 +824,7 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                         false,
                     )
                     .expect("called load_program_with_pubkey() with nonexistent account");
-                    (key, program)
+                    (key, program, profile)
                 });
 
                 let task_waiter = Arc::clone(&global_program_cache.loading_task_waiter);
@@ -785,8 +832,11 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                 // Unlock the global cache again.
             };
 
-            if let Some((key, program)) = program_to_store {
+            if let Some((key, program, profile)) = program_to_store {
                 program_cache_for_tx_batch.loaded_missing = true;
+                program_cache_for_tx_batch
+                    .loaded_program_profiles
+                    .push(profile);
                 let mut global_program_cache = self.global_program_cache.write().unwrap();
                 // Submit our last completed loading task.
                 if global_program_cache.finish_cooperative_loading_task(self.slot, key, program)
@@ -859,17 +909,22 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
             TransactionAccountStateInfo::new(&transaction_context, tx, &environment.rent);
 
         let log_collector = if config.recording_config.enable_log_recording {
-            match config.log_messages_bytes_limit {
-                None => Some(LogCollector::new_ref()),
-                Some(log_messages_bytes_limit) => Some(LogCollector::new_ref_with_limit(Some(
-                    log_messages_bytes_limit,
-                ))),
-            }
+            let log_collector = match config.log_messages_bytes_limit {
+                None => LogCollector::new_ref(),
+                Some(log_messages_bytes_limit) => {
+                    LogCollector::new_ref_with_limit(Some(log_messages_bytes_limit))
+                }
+            };
+            log_collector.borrow_mut().min_level = config.min_log_level;
+            log_collector.borrow_mut().dedup_consecutive = config.dedup_log_messages;
+            log_collector.borrow_mut().program_filter = config.log_program_filter.clone();
+            Some(log_collector)
         } else {
             None
         };
 
         let mut executed_units = 0u64;
+        let mut instruction_executed_units = Vec::with_capacity(tx.num_instructions());
         let sysvar_cache = &self.sysvar_cache.read().unwrap();
 
         let mut invoke_context = InvokeContext::new(
@@ -881,11 +936,20 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                 callback,
                 &environment.feature_set,
                 sysvar_cache,
-            ),
+            )
+            .with_instrumentation_observer(config.instrumentation_observer.clone()),
             log_collector.clone(),
             compute_budget,
             self.execution_cost,
         );
+        // Otherwise profiling is turned on by the enable profiling instruction
+        invoke_context.enable_profiling = config.profile_all_transactions;
+        if let Some(profile_return_data_bytes_limit) = config.profile_return_data_bytes_limit {
+            invoke_context.profile_return_data_bytes_limit = profile_return_data_bytes_limit;
+        }
+        invoke_context.profile_verbosity = config.profile_verbosity;
+        invoke_context.implicit_profiling_sections = config.implicit_profiling_sections;
+        invoke_context.profile_cpis = config.profile_cpis;
 
         let mut process_message_time = Measure::start("process_message_time");
         let process_result = process_message(
@@ -894,10 +958,40 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
             &mut invoke_context,
             execute_timings,
             &mut executed_units,
+            &mut instruction_executed_units,
         );
         process_message_time.stop();
 
+        // Profiled transactions end with the execution envelope they ran in
+        if log_collector
+            .as_ref()
+            .is_some_and(|log_collector| log_collector.borrow().is_profiled())
+        {
+            stable_log::program_profile_budget(
+                &log_collector,
+                &ProfileBudget {
+                    compute_unit_limit: compute_budget.compute_unit_limit,
+                    requested_compute_unit_limit: compute_budget.requested_compute_unit_limit,
+                    consumed_cu: executed_units,
+                    heap_bytes: compute_budget.heap_size,
+                    prioritization_fee: loaded_transaction.fee_details.prioritization_fee(),
+                },
+            );
+        }
+
+        let profiling_results = config
+            .recording_config
+            .enable_profiling_recording
+            .then(|| invoke_context.take_profiling_results());
+        let instruction_trace_outcomes = invoke_context.take_instruction_trace_outcomes();
         drop(invoke_context);
+        // The programs loaded since the previous transaction were loaded for this one
+        let loaded_program_profiles =
+            std::mem::take(&mut program_cache_for_tx_batch.loaded_program_profiles);
+        let program_load_profiles = config
+            .recording_config
+            .enable_profiling_recording
+            .then_some(loaded_program_profiles);
 
         execute_timings.execute_accessories.process_message_us += process_message_time.as_us();
 
@@ -942,6 +1036,12 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
         } else {
             None
         };
+        let instruction_trace = config.recording_config.enable_cpi_recording.then(|| {
+            Self::traced_instructions_from_instruction_trace(
+                &transaction_context,
+                &instruction_trace_outcomes,
+            )
+        });
 
         let ExecutionRecord {
             accounts,
@@ -976,9 +1076,14 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                 status,
                 log_messages,
                 inner_instructions,
+                instruction_trace,
                 return_data,
                 executed_units,
+                instruction_executed_units,
                 accounts_data_len_delta,
+                profiling_results,
+                program_load_profiles,
+                load_profile: None,
             },
             loaded_transaction,
             programs_modified_by_tx: program_cache_for_tx_batch.drain_modified_entries(),
@@ -1033,6 +1138,40 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
         outer_instructions
     }
 
+    /// Extract every executed instruction, with its outcome, from a TransactionContext
+    fn traced_instructions_from_instruction_trace(
+        transaction_context: &TransactionContext,
+        instruction_trace_outcomes: &[InstructionOutcome],
+    ) -> Vec<TracedInstruction> {
+        let mut top_level_instruction_index = None::<usize>;
+        (0..transaction_context.get_instruction_trace_length())
+            .filter_map(|index_in_trace| {
+                let instruction_context = transaction_context
+                    .get_instruction_context_at_index_in_trace(index_in_trace)
+                    .ok()?;
+                let stack_height = instruction_context.get_stack_height();
+                if stack_height == TRANSACTION_LEVEL_STACK_HEIGHT {
+                    top_level_instruction_index =
+                        Some(top_level_instruction_index.map_or(0, |index| index + 1));
+                }
+                let outcome = instruction_trace_outcomes
+                    .get(index_in_trace)
+                    .copied()
+                    .unwrap_or_default();
+                Some(TracedInstruction {
+                    top_level_instruction_index: top_level_instruction_index?,
+                    stack_height,
+                    program_id: instruction_context
+                        .get_program_key()
+                        .copied()
+                        .unwrap_or_default(),
+                    compute_units_consumed: outcome.compute_units_consumed,
+                    failed: outcome.failed,
+                })
+            })
+            .collect()
+    }
+
     pub fn fill_missing_sysvar_cache_entries<CB: TransactionProcessingCallback>(
         &self,
         callbacks: &CB,
@@ -1109,6 +1248,7 @@ mod tests {
                 SVMTransactionExecutionAndFeeBudgetLimits, SVMTransactionExecutionBudget,
             },
             loaded_programs::{BlockRelation, ProgramCacheEntryType},
+            profiling::ProgramLoadProfile,
         },
         solana_rent::Rent,
         solana_sdk_ids::{bpf_loader, loader_v4, system_program, sysvar},
@@ -1317,6 +1457,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_traced_instructions_from_instruction_trace() {
+        let program_id = Pubkey::new_unique();
+        let instruction_trace = [1, 2, 1, 1, 2, 3, 2];
+        let mut transaction_context = TransactionContext::new(
+            vec![(program_id, AccountSharedData::new(1, 1, &bpf_loader::ID))],
+            Rent::default(),
+            3,
+            instruction_trace.len(),
+        );
+        for (index_in_trace, stack_height) in instruction_trace.into_iter().enumerate() {
+            while stack_height <= transaction_context.get_instruction_stack_height() {
+                transaction_context.pop().unwrap();
+            }
+            if stack_height > transaction_context.get_instruction_stack_height() {
+                transaction_context
+                    .configure_next_instruction_for_tests(0, vec![], &[index_in_trace as u8])
+                    .unwrap();
+                transaction_context.push().unwrap();
+            }
+        }
+        // The outcome of the last instruction is missing, like one of a precompile
+        let instruction_trace_outcomes = [300, 100, 0, 500, 250, 50]
+            .into_iter()
+            .enumerate()
+            .map(
+                |(index_in_trace, compute_units_consumed)| InstructionOutcome {
+                    compute_units_consumed,
+                    failed: index_in_trace == 5,
+                },
+            )
+            .collect::<Vec<_>>();
+        let traced_instructions =
+            TransactionBatchProcessor::<TestForkGraph>::traced_instructions_from_instruction_trace(
+                &transaction_context,
+                &instruction_trace_outcomes,
+            );
+
+        let traced_instruction =
+            |top_level_instruction_index, stack_height, compute_units_consumed, failed| {
+                TracedInstruction {
+                    top_level_instruction_index,
+                    stack_height,
+                    program_id,
+                    compute_units_consumed,
+                    failed,
+                }
+            };
+        assert_eq!(
+            traced_instructions,
+            vec![
+                traced_instruction(0, 1, 300, false),
+                traced_instruction(0, 2, 100, false),
+                traced_instruction(1, 1, 0, false),
+                traced_instruction(2, 1, 500, false),
+                traced_instruction(2, 2, 250, false),
+                traced_instruction(2, 3, 50, true),
+                traced_instruction(2, 2, 0, false),
+            ]
+        );
+    }
+
     #[test]
     fn test_execute_loaded_transaction_recordings() {
         // Setting all the arguments correctly is too burdensome for testing
@@ -1385,10 +1587,26 @@ mod tests {
         );
         assert!(executed_tx.execution_details.log_messages.is_some());
         assert!(executed_tx.execution_details.inner_instructions.is_none());
+        assert!(executed_tx.execution_details.profiling_results.is_none());
+        assert!(executed_tx
+            .execution_details
+            .program_load_profiles
+            .is_none());
 
         processing_config.recording_config.enable_log_recording = false;
         processing_config.recording_config.enable_cpi_recording = true;
+        processing_config
+            .recording_config
+            .enable_profiling_recording = true;
         processing_config.log_messages_bytes_limit = None;
+        let load_profile = ProgramLoadProfile {
+            program_id: Pubkey::new_unique(),
+            load_elf_us: 1,
+            ..ProgramLoadProfile::default()
+        };
+        program_cache_for_tx_batch
+            .loaded_program_profiles
+            .push(load_profile.clone());
 
         let executed_tx = batch_processor.execute_loaded_transaction(
             &mock_bank,
@@ -1403,6 +1621,17 @@ mod tests {
 
         assert!(executed_tx.execution_details.log_messages.is_none());
         assert!(executed_tx.execution_details.inner_instructions.is_some());
+        assert_eq!(
+            executed_tx.execution_details.profiling_results,
+            Some(Vec::new())
+        );
+        assert_eq!(
+            executed_tx.execution_details.program_load_profiles,
+            Some(vec![load_profile])
+        );
+        assert!(program_cache_for_tx_batch
+            .loaded_program_profiles
+            .is_empty());
     }
 
     #[test]
@@ -5,9 +5,13 @@ use {
     solana_loader_v3_interface::instruction as bpf_loader_upgradeable,
     solana_program_runtime::{
         invoke_context::SerializedAccountMetadata,
+        profiling::{
+            cpi_section_name, is_transfer_hook_execute, CPI_DESERIALIZE_SECTION,
+            CPI_SERIALIZE_SECTION, TRANSFER_HOOK_RESOLVE_SECTION,
+        },
         serialization::{create_memory_region_of_account, modify_memory_region_of_account},
     },
-    solana_sbpf::ebpf,
+    solana_sbpf::{ebpf, vm::ContextObject},
     solana_stable_layout::stable_instruction::StableInstruction,
     solana_svm_measure::measure::Measure,
     solana_transaction_context::{
@@ -992,6 +996,25 @@ fn cpi_common<S: SyscallInvokeSigned>(
         invoke_context,
         check_aligned,
     )?;
+
+    // While any program on the stack is profiling, or for every CPI if `profile_cpis` is set,
+    // label the CPI with a section in the caller's profile. Transfer hooks additionally get a
+    // child section covering the translation of the extra accounts resolved by the token
+    // program, and their cost is attributed to the enclosing section of the transfer which
+    // invoked them. The translation of the accounts before and after the callee executes is
+    // reported as the `cpi_serialize` and `cpi_deserialize` child sections, separating the
+    // invoke overhead from the callee's work.
+    let cpi_section = invoke_context
+        .should_profile_cpi()
+        .then(|| cpi_section_name(&instruction.program_id, &instruction.data));
+    let is_transfer_hook = cpi_section.is_some() && is_transfer_hook_execute(&instruction.data);
+    if let Some(name) = &cpi_section {
+        start_cpi_section(invoke_context, name)?;
+        if is_transfer_hook {
+            start_cpi_section(invoke_context, TRANSFER_HOOK_RESOLVE_SECTION)?;
+        }
+    }
+
     let transaction_context = &invoke_context.transaction_context;
     let instruction_context = transaction_context.get_current_instruction_context()?;
     let caller_program_id = instruction_context.get_program_key()?;
@@ -1005,6 +1028,9 @@ fn cpi_common<S: SyscallInvokeSigned>(
     check_authorized_program(&instruction.program_id, &instruction.data, invoke_context)?;
     invoke_context.prepare_next_instruction(&instruction, &signers)?;
 
+    if cpi_section.is_some() {
+        start_cpi_section(invoke_context, CPI_SERIALIZE_SECTION)?;
+    }
     let mut accounts = S::translate_accounts(
         account_infos_addr,
         account_infos_len,
@@ -1012,11 +1038,35 @@ fn cpi_common<S: SyscallInvokeSigned>(
         invoke_context,
         check_aligned,
     )?;
+    if cpi_section.is_some() {
+        end_cpi_section(invoke_context, CPI_SERIALIZE_SECTION)?;
+    }
+
+    if is_transfer_hook {
+        end_cpi_section(invoke_context, TRANSFER_HOOK_RESOLVE_SECTION)?;
+    }
 
     // Process the callee instruction
+    invoke_context.notify_cpi_begin(&instruction.program_id);
     let mut compute_units_consumed = 0;
-    invoke_context
-        .process_instruction(&mut compute_units_consumed, &mut ExecuteTimings::default())?;
+    let result = invoke_context
+        .process_instruction(&mut compute_units_consumed, &mut ExecuteTimings::default());
+    invoke_context.notify_cpi_end(&instruction.program_id, &result);
+    result?;
+
+    // Return data is reset whenever a program is invoked, so a non-empty buffer was set during
+    // this CPI. The runtime keeps it alive on behalf of the caller.
+    let (_return_data_program_id, return_data) =
+        invoke_context.transaction_context.get_return_data();
+    if !return_data.is_empty() {
+        let return_data_len = return_data.len() as u64;
+        if let Some(profiling) = &mut invoke_context.get_syscall_context_mut()?.profiling {
+            profiling.callee_return_data(return_data_len);
+        }
+    }
+    if cpi_section.is_some() {
+        start_cpi_section(invoke_context, CPI_DESERIALIZE_SECTION)?;
+    }
 
     // re-bind to please the borrow checker
     let transaction_context = &invoke_context.transaction_context;
@@ -1060,10 +1110,31 @@ fn cpi_common<S: SyscallInvokeSigned>(
         }
     }
 
+    if let Some(name) = &cpi_section {
+        end_cpi_section(invoke_context, CPI_DESERIALIZE_SECTION)?;
+        end_cpi_section(invoke_context, name)?;
+    }
+
     invoke_context.execute_time = Some(Measure::start("execute"));
     Ok(SUCCESS)
 }
 
+fn start_cpi_section(invoke_context: &mut InvokeContext, name: &str) -> Result<(), Error> {
+    let remaining = invoke_context.get_remaining();
+    if let Some(profiling) = &mut invoke_context.get_syscall_context_mut()?.profiling {
+        profiling.start_section(name, remaining, None);
+    }
+    Ok(())
+}
+
+fn end_cpi_section(invoke_context: &mut InvokeContext, name: &str) -> Result<(), Error> {
+    let remaining = invoke_context.get_remaining();
+    if let Some(profiling) = &mut invoke_context.get_syscall_context_mut()?.profiling {
+        profiling.end_section(name, remaining, None);
+    }
+    Ok(())
+}
+
 // Update the given account before executing CPI.
 //
 // caller_account and callee_account describe the same account. At CPI entry
@@ -1896,6 +1967,7 @@ mod tests {
                 allocator: BpfAllocator::new(solana_program_entrypoint::HEAP_LENGTH as u64),
                 accounts_metadata: vec![account_metadata],
                 trace_log: Vec::new(),
+                profiling: Some(ProfilingState::default()),
             })
             .unwrap();
 
@@ -0,0 +1,402 @@
+use {
+    super::*,
+    solana_program_runtime::{
+        loaded_programs::ProgramCacheEntryType,
+        profiling::{submit_program_metric, ProgramMetricKind},
+    },
+    solana_sbpf::{
+        ebpf::MM_STACK_START,
+        error::EbpfError,
+        vm::{get_runtime_environment_key, ContextObject, EbpfVm},
+    },
+    std::{sync::LazyLock, time::Instant},
+};
+
+/// Maximum number of frames logged by `sol_backtrace`
+pub const MAX_BACKTRACE_FRAMES: usize = 64;
+
+/// Log the SBF call stack of the executing program, innermost frame first
+///
+/// Frames are symbolized with the function names of the program's ELF, which are only kept when
+/// the program was loaded with debugging features, so this syscall is only registered then.
+/// Return addresses are only tracked by the interpreter: programs executed by the JIT log the
+/// frames which could be recovered, possibly none.
+pub struct SyscallBacktrace {}
+
+impl SyscallBacktrace {
+    /// Rust interface, takes the program counter of each frame, innermost first
+    pub fn rust(invoke_context: &mut InvokeContext, frame_pcs: &[u64]) -> Result<u64, Error> {
+        let cost = invoke_context.get_execution_cost().syscall_base_cost;
+        consume_compute_meter(invoke_context, cost)?;
+        log_backtrace(invoke_context, frame_pcs)?;
+        Ok(0)
+    }
+
+    /// VM interface
+    pub fn vm(
+        vm: *mut EbpfVm<InvokeContext>,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+    ) {
+        call_with_vm(vm, &mut |vm| {
+            let frame_pcs = call_frame_pcs(vm);
+            Self::rust(vm.context_object_pointer, &frame_pcs)
+        })
+    }
+}
+
+/// Stop at a point of interest and dump the state of the VM to the program logs
+///
+/// Logs the program counter, the compute units left, all registers and the call stack as
+/// `sol_backtrace` does. Execution then resumes, so that the dump can be used as a breakpoint in
+/// instrumented sections without changing their outcome. Like `sol_backtrace`, this syscall is
+/// only registered when the program runtime environment enables debugging features.
+pub struct SyscallDebugBreak {}
+
+impl SyscallDebugBreak {
+    /// Rust interface, takes the registers `r0` to `r10` followed by the program counter and the
+    /// program counter of each frame, innermost first
+    pub fn rust(
+        invoke_context: &mut InvokeContext,
+        registers: &[u64; 12],
+        frame_pcs: &[u64],
+    ) -> Result<u64, Error> {
+        let cost = invoke_context.get_execution_cost().syscall_base_cost;
+        consume_compute_meter(invoke_context, cost)?;
+
+        let log_collector = invoke_context.get_log_collector();
+        stable_log::program_debug_break(
+            &log_collector,
+            registers[11],
+            invoke_context.get_remaining(),
+            &registers[..11],
+        );
+        log_backtrace(invoke_context, frame_pcs)?;
+        Ok(0)
+    }
+
+    /// VM interface
+    pub fn vm(
+        vm: *mut EbpfVm<InvokeContext>,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+    ) {
+        call_with_vm(vm, &mut |vm| {
+            let registers = vm.registers;
+            let frame_pcs = call_frame_pcs(vm);
+            Self::rust(vm.context_object_pointer, &registers, &frame_pcs)
+        })
+    }
+}
+
+/// Symbolize the given frames and log them, innermost frame first
+fn log_backtrace(invoke_context: &InvokeContext, frame_pcs: &[u64]) -> Result<(), Error> {
+    let program_id = *invoke_context
+        .transaction_context
+        .get_current_instruction_context()?
+        .get_program_key()?;
+    let mut symbols: Vec<(u64, String)> = invoke_context
+        .program_cache_for_tx_batch
+        .find(&program_id)
+        .map(|entry| match &entry.program {
+            ProgramCacheEntryType::Loaded(executable) => executable
+                .get_function_registry()
+                .iter()
+                .map(|(_key, (name, pc))| (pc as u64, String::from_utf8_lossy(name).into()))
+                .collect(),
+            _ => Vec::new(),
+        })
+        .unwrap_or_default();
+    symbols.sort_unstable();
+
+    let log_collector = invoke_context.get_log_collector();
+    for (index, pc) in frame_pcs.iter().take(MAX_BACKTRACE_FRAMES).enumerate() {
+        let symbol = symbols
+            .iter()
+            .rev()
+            .find(|(start_pc, _name)| start_pc <= pc)
+            .map(|(start_pc, name)| (name.as_str(), pc.saturating_sub(*start_pc)));
+        stable_log::program_backtrace_frame(&log_collector, index, *pc, symbol);
+    }
+    Ok(())
+}
+
+/// The program counter of each frame of the call stack, innermost first
+fn call_frame_pcs(vm: &EbpfVm<InvokeContext>) -> Vec<u64> {
+    // The call instruction of each caller precedes its return address
+    std::iter::once(vm.registers[11])
+        .chain(
+            vm.call_frames
+                .iter()
+                .take(vm.call_depth as usize)
+                .rev()
+                .take_while(|frame| frame.target_pc != 0)
+                .map(|frame| frame.target_pc.saturating_sub(1)),
+        )
+        .collect()
+}
+
+/// Run `syscall` with access to the whole VM and store its result
+///
+/// Mirrors the VM interface generated by `declare_builtin_function!`, which does not give access
+/// to the registers and call frames.
+fn call_with_vm(
+    vm: *mut EbpfVm<InvokeContext>,
+    syscall: &mut dyn FnMut(&mut EbpfVm<InvokeContext>) -> Result<u64, Error>,
+) {
+    let vm = unsafe {
+        &mut *(vm
+            .cast::<u64>()
+            .offset((get_runtime_environment_key() as isize).wrapping_neg())
+            .cast::<EbpfVm<InvokeContext>>())
+    };
+    let enable_instruction_meter = vm.loader.get_config().enable_instruction_meter;
+    if enable_instruction_meter {
+        vm.context_object_pointer.consume(
+            vm.previous_instruction_meter
+                .saturating_sub(vm.due_insn_count),
+        );
+    }
+    vm.program_result = syscall(vm).map_err(EbpfError::SyscallError).into();
+    if enable_instruction_meter {
+        vm.previous_instruction_meter = vm.context_object_pointer.get_remaining();
+    }
+}
+
+/// Signature of the Rust interface generated by `declare_builtin_function!`
+type RustSyscall =
+    fn(&mut InvokeContext, u64, u64, u64, u64, u64, &mut MemoryMapping) -> Result<u64, Error>;
+
+/// Run `syscall` after recording the program counter of the calling SBF instruction as the call
+/// site of the next profiling entry, see `ProfilingState::set_call_site`
+///
+/// Only the interpreter keeps the program counter register up to date across syscalls, so
+/// programs executed by the JIT may record stale call sites.
+fn call_with_call_site(vm: *mut EbpfVm<InvokeContext>, args: [u64; 5], syscall: RustSyscall) {
+    call_with_vm(vm, &mut |vm| {
+        let pc = vm.registers[11];
+        if let Some(profiling) = &mut vm
+            .context_object_pointer
+            .get_syscall_context_mut()?
+            .profiling
+        {
+            profiling.set_call_site(pc);
+        }
+        let [arg1, arg2, arg3, arg4, arg5] = args;
+        syscall(
+            vm.context_object_pointer,
+            arg1,
+            arg2,
+            arg3,
+            arg4,
+            arg5,
+            &mut vm.memory_mapping,
+        )
+    })
+}
+
+/// Add a VM interface which records the call site of the entry opened by the syscall, see
+/// [`call_with_call_site`]
+macro_rules! declare_call_site_vm {
+    ($($syscall:ident),+ $(,)?) => {
+        $(
+            impl $syscall {
+                /// VM interface, recording the call site of the profiling entry
+                pub fn vm_with_call_site(
+                    vm: *mut EbpfVm<InvokeContext>,
+                    arg1: u64,
+                    arg2: u64,
+                    arg3: u64,
+                    arg4: u64,
+                    arg5: u64,
+                ) {
+                    call_with_call_site(vm, [arg1, arg2, arg3, arg4, arg5], Self::rust);
+                }
+            }
+        )+
+    };
+}
+
+declare_call_site_vm!(
+    SyscallLogComputeUnitsStart,
+    SyscallLogComputeUnitsStartId,
+    SyscallProfileMark,
+);
+
+/// Reference point of the clock read by `sol_host_time`
+static HOST_TIME_EPOCH: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+declare_builtin_function!(
+    /// Read the host's monotonic clock, in nanoseconds
+    ///
+    /// The clock starts at an arbitrary point and is only comparable within the same process.
+    /// Programs calling this syscall cannot be replayed deterministically, so it is only
+    /// registered in diagnostics mode, see `register_diagnostics_syscalls`.
+    SyscallHostTime,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let cost = invoke_context.get_execution_cost().syscall_base_cost;
+        consume_compute_meter(invoke_context, cost)?;
+        Ok(u64::try_from(HOST_TIME_EPOCH.elapsed().as_nanos()).unwrap_or(u64::MAX))
+    }
+);
+
+declare_builtin_function!(
+    /// Submit a named value to the validator's metrics pipeline
+    ///
+    /// `kind` is `0` for a gauge and `1` for a counter. The value is reported as a
+    /// `program-metric` datapoint tagged with the executing program id. Metrics are a side
+    /// channel of the validator the program runs on, so this syscall is only registered in
+    /// diagnostics mode, see `register_diagnostics_syscalls`.
+    SyscallEmitMetric,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        name_addr: u64,
+        name_len: u64,
+        value: u64,
+        kind: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let cost = invoke_context
+            .get_execution_cost()
+            .syscall_base_cost
+            .max(name_len);
+        consume_compute_meter(invoke_context, cost)?;
+        let kind = ProgramMetricKind::from_u64(kind).ok_or(SyscallError::InvalidMetricKind(kind))?;
+        if name_len > MAX_PROFILE_NAME_LEN {
+            return Err(SyscallError::InvalidLength.into());
+        }
+        let program_id = *invoke_context
+            .transaction_context
+            .get_current_instruction_context()?
+            .get_program_key()?;
+        translate_string_and_do(
+            memory_mapping,
+            name_addr,
+            name_len,
+            invoke_context.get_check_aligned(),
+            &mut |name: &str| {
+                submit_program_metric(&program_id, name, kind, value);
+                Ok(0)
+            },
+        )
+    }
+);
+
+/// Stack usage written by `sol_get_stack_usage`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StackUsage {
+    /// Number of SBF call frames below the current one
+    pub call_depth: u64,
+    /// Maximum call depth of the program runtime environment
+    pub max_call_depth: u64,
+    /// Approximate bytes of the stack region used by the current and the calling frames
+    pub stack_bytes: u64,
+    /// Size of the stack region
+    pub stack_size: u64,
+}
+
+/// Write the current SBF call depth and the approximate stack bytes in use into program memory
+///
+/// With fixed stack frames every frame uses the full frame size. With dynamic stack frames the
+/// usage is derived from the frame pointer, which is only tracked by the interpreter: programs
+/// executed by the JIT may observe a stale value. As the result depends on the VM, it is meant for
+/// profiling only and is registered with the profiling syscalls, see
+/// `register_profiling_syscalls`.
+pub struct SyscallGetStackUsage {}
+
+impl SyscallGetStackUsage {
+    /// Rust interface, takes the stack usage observed by the VM
+    pub fn rust(
+        invoke_context: &mut InvokeContext,
+        var_addr: u64,
+        memory_mapping: &MemoryMapping,
+        stack_usage: StackUsage,
+    ) -> Result<u64, Error> {
+        let execution_cost = invoke_context.get_execution_cost();
+        consume_compute_meter(
+            invoke_context,
+            execution_cost
+                .syscall_base_cost
+                .saturating_add(execution_cost.mem_op_base_cost),
+        )?;
+        *translate_type_mut::<StackUsage>(
+            memory_mapping,
+            var_addr,
+            invoke_context.get_check_aligned(),
+        )? = stack_usage;
+        Ok(0)
+    }
+
+    /// VM interface
+    pub fn vm(
+        vm: *mut EbpfVm<InvokeContext>,
+        var_addr: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+    ) {
+        call_with_vm(vm, &mut |vm| {
+            let dynamic_stack_frames = uses_dynamic_stack_frames(vm.context_object_pointer)?;
+            let config = vm.loader.get_config();
+            let stack_frame_size = config.stack_frame_size as u64;
+            let stack_size = config.stack_size() as u64;
+            let stack_bytes = if dynamic_stack_frames {
+                // Dynamic stack frames grow down from the end of the stack region
+                MM_STACK_START
+                    .saturating_add(stack_size)
+                    .saturating_sub(vm.registers[10])
+            } else {
+                vm.call_depth
+                    .saturating_add(1)
+                    .saturating_mul(stack_frame_size)
+            };
+            let stack_usage = StackUsage {
+                call_depth: vm.call_depth,
+                max_call_depth: config.max_call_depth as u64,
+                stack_bytes: stack_bytes.min(stack_size),
+                stack_size,
+            };
+            Self::rust(
+                vm.context_object_pointer,
+                var_addr,
+                &vm.memory_mapping,
+                stack_usage,
+            )
+        })
+    }
+}
+
+/// Whether the executing program uses dynamic stack frames, according to the SBPF version of its
+/// executable
+fn uses_dynamic_stack_frames(invoke_context: &InvokeContext) -> Result<bool, Error> {
+    let program_id = *invoke_context
+        .transaction_context
+        .get_current_instruction_context()?
+        .get_program_key()?;
+    Ok(invoke_context
+        .program_cache_for_tx_batch
+        .find(&program_id)
+        .is_some_and(|entry| match &entry.program {
+            ProgramCacheEntryType::Loaded(executable) => {
+                executable.get_sbpf_version().dynamic_stack_frames()
+            }
+            _ => false,
+        }))
+}
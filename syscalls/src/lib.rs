@@ -1,7 +1,20 @@
 pub use self::{
     cpi::{SyscallInvokeSignedC, SyscallInvokeSignedRust},
+    debugging::{
+        StackUsage, SyscallBacktrace, SyscallDebugBreak, SyscallEmitMetric, SyscallGetStackUsage,
+        SyscallHostTime, MAX_BACKTRACE_FRAMES,
+    },
     logging::{
-        SyscallLog, SyscallLogBpfComputeUnits, SyscallLogData, SyscallLogPubkey, SyscallLogU64,
+        SyscallAbortWithMessage, SyscallLog, SyscallLogAccount, SyscallLogAmount,
+        SyscallLogBpfComputeUnits, SyscallLogBpfComputeUnitsAt, SyscallLogComputeUnitsEnd,
+        SyscallLogComputeUnitsEndId, SyscallLogComputeUnitsStart, SyscallLogComputeUnitsStartId,
+        SyscallLogData, SyscallLogEvent, SyscallLogFmt, SyscallLogHex, SyscallLogI64, SyscallLogKv,
+        SyscallLogLevel, SyscallLogPubkey, SyscallLogU64, SyscallLogU64Dec, SyscallMemprofTagBegin,
+        SyscallMemprofTagEnd, SyscallProfileAssertCuBudget, SyscallProfileCounterAdd,
+        SyscallProfileEnabled, SyscallProfileEndAll, SyscallProfileHeapCheckpoint,
+        SyscallProfileMark, SyscallProfileRegisterName, SyscallProfileSectionCu,
+        SyscallProfileSetAttr, DEFAULT_LOG_HEX_BYTES_PER_LINE, MAX_LOG_EVENT_NAME_LEN,
+        MAX_LOG_FMT_ARGS, MAX_LOG_HEX_BYTES_PER_LINE, MAX_LOG_KV_FIELDS,
     },
     mem_ops::{SyscallMemcmp, SyscallMemcpy, SyscallMemmove, SyscallMemset},
     sysvar::{
@@ -23,17 +36,25 @@ use {
     },
     solana_cpi::MAX_RETURN_DATA,
     solana_hash::Hash,
-    solana_instruction::{error::InstructionError, AccountMeta, ProcessedSiblingInstruction},
+    solana_instruction::{
+        error::InstructionError, AccountMeta, ProcessedSiblingInstruction,
+        TRANSACTION_LEVEL_STACK_HEIGHT,
+    },
     solana_keccak_hasher as keccak, solana_poseidon as poseidon,
     solana_program_entrypoint::{BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, SUCCESS},
     solana_program_runtime::{
         execution_budget::{SVMTransactionExecutionBudget, SVMTransactionExecutionCost},
         invoke_context::InvokeContext,
+        profiling::{
+            HeapCheckpoint, ProfilingState, MAX_PROFILE_ATTRIBUTE_VALUE_LEN, MAX_PROFILE_NAME_LEN,
+            NO_HEAP_SAMPLE,
+        },
         stable_log,
     },
     solana_pubkey::{Pubkey, PubkeyError, MAX_SEEDS, MAX_SEED_LEN, PUBKEY_BYTES},
     solana_sbpf::{
         declare_builtin_function,
+        ebpf::MM_HEAP_START,
         memory_region::{AccessType, MemoryMapping},
         program::{BuiltinProgram, SBPFVersion},
         vm::Config,
@@ -44,7 +65,7 @@ use {
     },
     solana_sha256_hasher::Hasher,
     solana_svm_feature_set::SVMFeatureSet,
-    solana_svm_log_collector::{ic_logger_msg, ic_msg},
+    solana_svm_log_collector::{ic_logger_msg, ic_msg, LogLevel},
     solana_svm_timings::ExecuteTimings,
     solana_svm_type_overrides::sync::Arc,
     solana_sysvar::SysvarSerialize,
@@ -60,6 +81,7 @@ use {
 };
 
 mod cpi;
+mod debugging;
 mod logging;
 mod mem_ops;
 mod sysvar;
@@ -76,6 +98,8 @@ pub enum SyscallError {
     Abort,
     #[error("SBF program Panicked in {0} at {1}:{2}")]
     Panic(String, u64, u64),
+    #[error("Invalid abort code {0}, codes must fit in 32 bits")]
+    InvalidAbortCode(u64),
     #[error("Cannot borrow invoke context")]
     InvokeContextBorrowFailed,
     #[error("Malformed signer seed: {0}: {1:?}")]
@@ -100,6 +124,16 @@ pub enum SyscallError {
     TooManySlices,
     #[error("InvalidLength")]
     InvalidLength,
+    #[error("Invalid log level {0}")]
+    InvalidLogLevel(u64),
+    #[error("Invalid log key {0:?}, keys must be non-empty and contain no whitespace or '='")]
+    InvalidLogKey(String),
+    #[error("Invalid log format {0:?}, placeholders must match the arguments")]
+    InvalidLogFormat(String),
+    #[error("Invalid event name {0:?}, names must be non-empty and contain no whitespace")]
+    InvalidEventName(String),
+    #[error("Invalid metric kind {0}")]
+    InvalidMetricKind(u64),
     #[error("Invoked an instruction with data that is too large ({data_len} > {max_data_len})")]
     MaxInstructionDataLenExceeded { data_len: u64, max_data_len: u64 },
     #[error("Invoked an instruction with too many accounts ({num_accounts} > {max_accounts})")]
@@ -281,6 +315,66 @@ fn consume_compute_meter(invoke_context: &InvokeContext, amount: u64) -> Result<
     Ok(())
 }
 
+/// Register a syscall declared by `declare_builtin_function!`
+///
+/// If `observe_syscalls` is set the syscall is wrapped so that it notifies the instrumentation
+/// observer of the invoke context, see [`call_observed_syscall`], otherwise its VM interface is
+/// registered directly.
+macro_rules! register_syscall {
+    (
+        $result:expr,
+        $observe_syscalls:expr,
+        $name:expr,
+        $syscall:ident $(::<$generic:ty>)? $(,)?
+    ) => {{
+        declare_builtin_function!(
+            ObservedSyscall,
+            fn rust(
+                invoke_context: &mut InvokeContext,
+                arg1: u64,
+                arg2: u64,
+                arg3: u64,
+                arg4: u64,
+                arg5: u64,
+                memory_mapping: &mut MemoryMapping,
+            ) -> Result<u64, Error> {
+                call_observed_syscall(invoke_context, $name, |invoke_context| {
+                    $syscall::rust $(::<$generic>)? (
+                        invoke_context,
+                        arg1,
+                        arg2,
+                        arg3,
+                        arg4,
+                        arg5,
+                        memory_mapping,
+                    )
+                })
+            }
+        );
+        if $observe_syscalls {
+            $result.register_function($name, ObservedSyscall::vm)
+        } else {
+            $result.register_function($name, $syscall::vm $(::<$generic>)?)
+        }
+    }};
+}
+
+macro_rules! register_feature_gated_syscall {
+    (
+        $result:expr,
+        $observe_syscalls:expr,
+        $is_feature_active:expr,
+        $name:expr,
+        $syscall:ident $(::<$generic:ty>)? $(,)?
+    ) => {
+        if $is_feature_active {
+            register_syscall!($result, $observe_syscalls, $name, $syscall $(::<$generic>)?)
+        } else {
+            Ok(())
+        }
+    };
+}
+
 macro_rules! register_feature_gated_function {
     ($result:expr, $is_feature_active:expr, $name:expr, $call:expr $(,)?) => {
         if $is_feature_active {
@@ -291,11 +385,68 @@ macro_rules! register_feature_gated_function {
     };
 }
 
+/// Call `syscall` between the syscall hooks of the instrumentation observer of the invoke
+/// context, see [`InstrumentationObserver`]
+///
+/// [`InstrumentationObserver`]: solana_program_runtime::instrumentation::InstrumentationObserver
+fn call_observed_syscall(
+    invoke_context: &mut InvokeContext,
+    name: &str,
+    syscall: impl FnOnce(&mut InvokeContext) -> Result<u64, Error>,
+) -> Result<u64, Error> {
+    let Some(observer) = invoke_context.instrumentation_observer().cloned() else {
+        return syscall(invoke_context);
+    };
+    observer.syscall_begin(name);
+    let result = syscall(invoke_context);
+    observer.syscall_end(name, &result);
+    result
+}
+
 pub fn create_program_runtime_environment_v1<'a>(
     feature_set: &SVMFeatureSet,
     compute_budget: &SVMTransactionExecutionBudget,
     reject_deployment_of_broken_elfs: bool,
     debugging_features: bool,
+) -> Result<BuiltinProgram<InvokeContext<'a>>, Error> {
+    new_program_runtime_environment_v1(
+        feature_set,
+        compute_budget,
+        reject_deployment_of_broken_elfs,
+        debugging_features,
+        false, /* observe_syscalls */
+    )
+}
+
+/// Like [`create_program_runtime_environment_v1`], but the syscalls notify the instrumentation
+/// observer of the invoke context, see [`InstrumentationObserver::syscall_begin`]
+///
+/// Only the syscalls declared by `declare_builtin_function!` are observed, the debugging,
+/// profiling and diagnostics syscalls are registered as they are.
+///
+/// [`InstrumentationObserver::syscall_begin`]:
+///     solana_program_runtime::instrumentation::InstrumentationObserver::syscall_begin
+pub fn create_observed_program_runtime_environment_v1<'a>(
+    feature_set: &SVMFeatureSet,
+    compute_budget: &SVMTransactionExecutionBudget,
+    reject_deployment_of_broken_elfs: bool,
+    debugging_features: bool,
+) -> Result<BuiltinProgram<InvokeContext<'a>>, Error> {
+    new_program_runtime_environment_v1(
+        feature_set,
+        compute_budget,
+        reject_deployment_of_broken_elfs,
+        debugging_features,
+        true, /* observe_syscalls */
+    )
+}
+
+fn new_program_runtime_environment_v1<'a>(
+    feature_set: &SVMFeatureSet,
+    compute_budget: &SVMTransactionExecutionBudget,
+    reject_deployment_of_broken_elfs: bool,
+    debugging_features: bool,
+    observe_syscalls: bool,
 ) -> Result<BuiltinProgram<InvokeContext<'a>>, Error> {
     let enable_alt_bn128_syscall = feature_set.enable_alt_bn128_syscall;
     let enable_alt_bn128_compression_syscall = feature_set.enable_alt_bn128_compression_syscall;
@@ -311,6 +462,7 @@ pub fn create_program_runtime_environment_v1<'a>(
         feature_set.remaining_compute_units_syscall_enabled;
     let get_sysvar_syscall_enabled = feature_set.get_sysvar_syscall_enabled;
     let enable_get_epoch_stake_syscall = feature_set.enable_get_epoch_stake_syscall;
+    let enable_profiling_syscalls = feature_set.enable_profiling_syscalls;
     let min_sbpf_version =
         if !feature_set.disable_sbpf_v0_execution || feature_set.reenable_sbpf_v0_execution {
             SBPFVersion::V0
@@ -348,187 +500,524 @@ pub fn create_program_runtime_environment_v1<'a>(
     let mut result = BuiltinProgram::new_loader(config);
 
     // Abort
-    result.register_function("abort", SyscallAbort::vm)?;
+    register_syscall!(result, observe_syscalls, "abort", SyscallAbort)?;
 
     // Panic
-    result.register_function("sol_panic_", SyscallPanic::vm)?;
+    register_syscall!(result, observe_syscalls, "sol_panic_", SyscallPanic)?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_abort_with_message",
+        SyscallAbortWithMessage,
+    )?;
 
     // Logging
-    result.register_function("sol_log_", SyscallLog::vm)?;
-    result.register_function("sol_log_64_", SyscallLogU64::vm)?;
-    result.register_function("sol_log_pubkey", SyscallLogPubkey::vm)?;
-    result.register_function("sol_log_compute_units_", SyscallLogBpfComputeUnits::vm)?;
+    register_syscall!(result, observe_syscalls, "sol_log_", SyscallLog)?;
+    register_syscall!(result, observe_syscalls, "sol_log_64_", SyscallLogU64)?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_log_64_dec",
+        SyscallLogU64Dec,
+    )?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_log_i64",
+        SyscallLogI64,
+    )?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_log_amount",
+        SyscallLogAmount,
+    )?;
+    register_syscall!(result, observe_syscalls, "sol_log_pubkey", SyscallLogPubkey)?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_log_account",
+        SyscallLogAccount,
+    )?;
+    register_syscall!(
+        result,
+        observe_syscalls,
+        "sol_log_compute_units_",
+        SyscallLogBpfComputeUnits,
+    )?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_log_compute_units_at",
+        SyscallLogBpfComputeUnitsAt,
+    )?;
 
     // Program defined addresses (PDA)
-    result.register_function(
+    register_syscall!(
+        result,
+        observe_syscalls,
         "sol_create_program_address",
-        SyscallCreateProgramAddress::vm,
+        SyscallCreateProgramAddress,
     )?;
-    result.register_function(
+    register_syscall!(
+        result,
+        observe_syscalls,
         "sol_try_find_program_address",
-        SyscallTryFindProgramAddress::vm,
+        SyscallTryFindProgramAddress,
     )?;
 
     // Sha256
-    result.register_function("sol_sha256", SyscallHash::vm::<Sha256Hasher>)?;
+    register_syscall!(
+        result,
+        observe_syscalls,
+        "sol_sha256",
+        SyscallHash::<Sha256Hasher>,
+    )?;
 
     // Keccak256
-    result.register_function("sol_keccak256", SyscallHash::vm::<Keccak256Hasher>)?;
+    register_syscall!(
+        result,
+        observe_syscalls,
+        "sol_keccak256",
+        SyscallHash::<Keccak256Hasher>,
+    )?;
 
     // Secp256k1 Recover
-    result.register_function("sol_secp256k1_recover", SyscallSecp256k1Recover::vm)?;
+    register_syscall!(
+        result,
+        observe_syscalls,
+        "sol_secp256k1_recover",
+        SyscallSecp256k1Recover,
+    )?;
 
     // Blake3
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         blake3_syscall_enabled,
         "sol_blake3",
-        SyscallHash::vm::<Blake3Hasher>,
+        SyscallHash::<Blake3Hasher>,
     )?;
 
     // Elliptic Curve Operations
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         curve25519_syscall_enabled,
         "sol_curve_validate_point",
-        SyscallCurvePointValidation::vm,
+        SyscallCurvePointValidation,
     )?;
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         curve25519_syscall_enabled,
         "sol_curve_group_op",
-        SyscallCurveGroupOps::vm,
+        SyscallCurveGroupOps,
     )?;
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         curve25519_syscall_enabled,
         "sol_curve_multiscalar_mul",
-        SyscallCurveMultiscalarMultiplication::vm,
+        SyscallCurveMultiscalarMultiplication,
     )?;
 
     // Sysvars
-    result.register_function("sol_get_clock_sysvar", SyscallGetClockSysvar::vm)?;
-    result.register_function(
+    register_syscall!(
+        result,
+        observe_syscalls,
+        "sol_get_clock_sysvar",
+        SyscallGetClockSysvar,
+    )?;
+    register_syscall!(
+        result,
+        observe_syscalls,
         "sol_get_epoch_schedule_sysvar",
-        SyscallGetEpochScheduleSysvar::vm,
+        SyscallGetEpochScheduleSysvar,
     )?;
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         !disable_fees_sysvar,
         "sol_get_fees_sysvar",
-        SyscallGetFeesSysvar::vm,
+        SyscallGetFeesSysvar,
+    )?;
+    register_syscall!(
+        result,
+        observe_syscalls,
+        "sol_get_rent_sysvar",
+        SyscallGetRentSysvar,
     )?;
-    result.register_function("sol_get_rent_sysvar", SyscallGetRentSysvar::vm)?;
 
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         last_restart_slot_syscall_enabled,
         "sol_get_last_restart_slot",
-        SyscallGetLastRestartSlotSysvar::vm,
+        SyscallGetLastRestartSlotSysvar,
     )?;
 
-    result.register_function(
+    register_syscall!(
+        result,
+        observe_syscalls,
         "sol_get_epoch_rewards_sysvar",
-        SyscallGetEpochRewardsSysvar::vm,
+        SyscallGetEpochRewardsSysvar,
     )?;
 
     // Memory ops
-    result.register_function("sol_memcpy_", SyscallMemcpy::vm)?;
-    result.register_function("sol_memmove_", SyscallMemmove::vm)?;
-    result.register_function("sol_memset_", SyscallMemset::vm)?;
-    result.register_function("sol_memcmp_", SyscallMemcmp::vm)?;
+    register_syscall!(result, observe_syscalls, "sol_memcpy_", SyscallMemcpy)?;
+    register_syscall!(result, observe_syscalls, "sol_memmove_", SyscallMemmove)?;
+    register_syscall!(result, observe_syscalls, "sol_memset_", SyscallMemset)?;
+    register_syscall!(result, observe_syscalls, "sol_memcmp_", SyscallMemcmp)?;
 
     // Processed sibling instructions
-    result.register_function(
+    register_syscall!(
+        result,
+        observe_syscalls,
         "sol_get_processed_sibling_instruction",
-        SyscallGetProcessedSiblingInstruction::vm,
+        SyscallGetProcessedSiblingInstruction,
     )?;
 
     // Stack height
-    result.register_function("sol_get_stack_height", SyscallGetStackHeight::vm)?;
+    register_syscall!(
+        result,
+        observe_syscalls,
+        "sol_get_stack_height",
+        SyscallGetStackHeight,
+    )?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_get_instruction_counts",
+        SyscallGetInstructionCounts,
+    )?;
 
     // Return data
-    result.register_function("sol_set_return_data", SyscallSetReturnData::vm)?;
-    result.register_function("sol_get_return_data", SyscallGetReturnData::vm)?;
+    register_syscall!(
+        result,
+        observe_syscalls,
+        "sol_set_return_data",
+        SyscallSetReturnData,
+    )?;
+    register_syscall!(
+        result,
+        observe_syscalls,
+        "sol_get_return_data",
+        SyscallGetReturnData,
+    )?;
 
     // Cross-program invocation
-    result.register_function("sol_invoke_signed_c", SyscallInvokeSignedC::vm)?;
-    result.register_function("sol_invoke_signed_rust", SyscallInvokeSignedRust::vm)?;
+    register_syscall!(
+        result,
+        observe_syscalls,
+        "sol_invoke_signed_c",
+        SyscallInvokeSignedC,
+    )?;
+    register_syscall!(
+        result,
+        observe_syscalls,
+        "sol_invoke_signed_rust",
+        SyscallInvokeSignedRust,
+    )?;
 
     // Memory allocator
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         !disable_deploy_of_alloc_free_syscall,
         "sol_alloc_free_",
-        SyscallAllocFree::vm,
+        SyscallAllocFree,
     )?;
 
     // Alt_bn128
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         enable_alt_bn128_syscall,
         "sol_alt_bn128_group_op",
-        SyscallAltBn128::vm,
+        SyscallAltBn128,
     )?;
 
     // Big_mod_exp
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         enable_big_mod_exp_syscall,
         "sol_big_mod_exp",
-        SyscallBigModExp::vm,
+        SyscallBigModExp,
     )?;
 
     // Poseidon
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         enable_poseidon_syscall,
         "sol_poseidon",
-        SyscallPoseidon::vm,
+        SyscallPoseidon,
     )?;
 
     // Accessing remaining compute units
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         remaining_compute_units_syscall_enabled,
         "sol_remaining_compute_units",
-        SyscallRemainingComputeUnits::vm
+        SyscallRemainingComputeUnits,
+    )?;
+
+    // Accessing remaining heap
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_remaining_heap",
+        SyscallRemainingHeap,
+    )?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_get_heap_size",
+        SyscallGetHeapSize,
+    )?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_log_heap_stats",
+        SyscallLogHeapStats,
+    )?;
+
+    // Accessing the compute budget
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_get_compute_budget",
+        SyscallGetComputeBudget,
     )?;
 
     // Alt_bn128_compression
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         enable_alt_bn128_compression_syscall,
         "sol_alt_bn128_compression",
-        SyscallAltBn128Compression::vm,
+        SyscallAltBn128Compression,
     )?;
 
     // Sysvar getter
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         get_sysvar_syscall_enabled,
         "sol_get_sysvar",
-        SyscallGetSysvar::vm,
+        SyscallGetSysvar,
     )?;
 
     // Get Epoch Stake
-    register_feature_gated_function!(
+    register_feature_gated_syscall!(
         result,
+        observe_syscalls,
         enable_get_epoch_stake_syscall,
         "sol_get_epoch_stake",
-        SyscallGetEpochStake::vm,
+        SyscallGetEpochStake,
     )?;
 
     // Log data
-    result.register_function("sol_log_data", SyscallLogData::vm)?;
+    register_syscall!(result, observe_syscalls, "sol_log_data", SyscallLogData)?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_log_hex",
+        SyscallLogHex,
+    )?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_log_kv",
+        SyscallLogKv,
+    )?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_log_event",
+        SyscallLogEvent,
+    )?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_log_fmt",
+        SyscallLogFmt,
+    )?;
+    register_feature_gated_syscall!(
+        result,
+        observe_syscalls,
+        enable_profiling_syscalls,
+        "sol_log_level",
+        SyscallLogLevel,
+    )?;
+
+    // Debugging
+    if debugging_features {
+        result.register_function("sol_backtrace", SyscallBacktrace::vm)?;
+        result.register_function("sol_debug_break", SyscallDebugBreak::vm)?;
+    }
+
+    // Profiling
+    register_profiling_syscalls(&mut result, enable_profiling_syscalls)?;
 
     Ok(result)
 }
 
+/// Register the syscalls which instrument programs with compute unit profiling sections, and
+/// `sol_get_stack_usage`
+///
+/// These are available in every program runtime environment, so that programs can be profiled
+/// whichever loader they were deployed with.
+pub fn register_profiling_syscalls(
+    program_runtime_environment: &mut BuiltinProgram<InvokeContext>,
+    enable_profiling_syscalls: bool,
+) -> Result<(), Error> {
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_log_compute_units_start",
+        SyscallLogComputeUnitsStart::vm_with_call_site,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_log_compute_units_end",
+        SyscallLogComputeUnitsEnd::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_log_compute_units_start_id",
+        SyscallLogComputeUnitsStartId::vm_with_call_site,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_log_compute_units_end_id",
+        SyscallLogComputeUnitsEndId::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_profile_section_cu",
+        SyscallProfileSectionCu::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_profile_register_name",
+        SyscallProfileRegisterName::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_profile_end_all",
+        SyscallProfileEndAll::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_profile_mark",
+        SyscallProfileMark::vm_with_call_site,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_profile_counter_add",
+        SyscallProfileCounterAdd::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_profile_enabled",
+        SyscallProfileEnabled::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_assert_cu_budget",
+        SyscallProfileAssertCuBudget::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_profile_set_attr",
+        SyscallProfileSetAttr::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_memprof_tag_begin",
+        SyscallMemprofTagBegin::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_memprof_tag_end",
+        SyscallMemprofTagEnd::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_profile_heap_checkpoint",
+        SyscallProfileHeapCheckpoint::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        enable_profiling_syscalls,
+        "sol_get_stack_usage",
+        SyscallGetStackUsage::vm,
+    )?;
+    Ok(())
+}
+
+/// Register the syscalls which expose non-deterministic host state to programs, such as
+/// `sol_host_time`
+///
+/// Transactions calling these syscalls cannot be replayed, so they are refused unless
+/// `diagnostics_mode` is set, which must never be the case on a node taking part in consensus.
+pub fn register_diagnostics_syscalls(
+    program_runtime_environment: &mut BuiltinProgram<InvokeContext>,
+    diagnostics_mode: bool,
+) -> Result<(), Error> {
+    register_feature_gated_function!(
+        program_runtime_environment,
+        diagnostics_mode,
+        "sol_host_time",
+        SyscallHostTime::vm,
+    )?;
+    register_feature_gated_function!(
+        program_runtime_environment,
+        diagnostics_mode,
+        "sol_emit_metric",
+        SyscallEmitMetric::vm,
+    )?;
+    Ok(())
+}
+
 pub fn create_program_runtime_environment_v2<'a>(
+    feature_set: &SVMFeatureSet,
     compute_budget: &SVMTransactionExecutionBudget,
     debugging_features: bool,
-) -> BuiltinProgram<InvokeContext<'a>> {
+) -> Result<BuiltinProgram<InvokeContext<'a>>, Error> {
     let config = Config {
         max_call_depth: compute_budget.max_call_depth,
         stack_frame_size: compute_budget.stack_frame_size,
@@ -546,7 +1035,9 @@ pub fn create_program_runtime_environment_v2<'a>(
         aligned_memory_mapping: true,
         // Warning, do not use `Config::default()` so that configuration here is explicit.
     };
-    BuiltinProgram::new_loader(config)
+    let mut result = BuiltinProgram::new_loader(config);
+    register_profiling_syscalls(&mut result, feature_set.enable_profiling_syscalls)?;
+    Ok(result)
 }
 
 fn address_is_aligned<T>(address: u64) -> bool {
@@ -1624,6 +2115,54 @@ declare_builtin_function!(
     }
 );
 
+/// Number of instructions executed so far in the transaction, written by
+/// `sol_get_instruction_counts`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InstructionCounts {
+    /// Top-level instructions, including the one currently executing
+    pub top_level: u64,
+    /// Instructions invoked through CPI, including the one currently executing if any
+    pub cpi: u64,
+}
+
+declare_builtin_function!(
+    /// Count the top-level and CPI instructions executed so far in the transaction according to
+    /// the instruction trace
+    SyscallGetInstructionCounts,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        var_addr: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let execution_cost = invoke_context.get_execution_cost();
+
+        consume_compute_meter(invoke_context, execution_cost.syscall_base_cost)?;
+
+        let transaction_context = &invoke_context.transaction_context;
+        let mut counts = InstructionCounts::default();
+        for index_in_trace in 0..transaction_context.get_instruction_trace_length() {
+            let instruction_context =
+                transaction_context.get_instruction_context_at_index_in_trace(index_in_trace)?;
+            if instruction_context.get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT {
+                counts.top_level = counts.top_level.saturating_add(1);
+            } else {
+                counts.cpi = counts.cpi.saturating_add(1);
+            }
+        }
+        *translate_type_mut::<InstructionCounts>(
+            memory_mapping,
+            var_addr,
+            invoke_context.get_check_aligned(),
+        )? = counts;
+        Ok(0)
+    }
+);
+
 declare_builtin_function!(
     /// alt_bn128 group operations
     SyscallAltBn128,
@@ -1864,68 +2403,234 @@ declare_builtin_function!(
 );
 
 declare_builtin_function!(
-    /// alt_bn128 g1 and g2 compression and decompression
-    SyscallAltBn128Compression,
+    /// Read the bytes remaining in the heap region
+    ///
+    /// Only allocations made through `sol_alloc_free_` are tracked by the runtime, programs using
+    /// their own allocator get the full heap length.
+    SyscallRemainingHeap,
     fn rust(
         invoke_context: &mut InvokeContext,
-        op: u64,
-        input_addr: u64,
-        input_size: u64,
-        result_addr: u64,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
         _arg5: u64,
-        memory_mapping: &mut MemoryMapping,
+        _memory_mapping: &mut MemoryMapping,
     ) -> Result<u64, Error> {
-        use solana_bn254::compression::prelude::{
-            alt_bn128_g1_compress, alt_bn128_g1_decompress, alt_bn128_g2_compress,
-            alt_bn128_g2_decompress, ALT_BN128_G1_COMPRESS, ALT_BN128_G1_DECOMPRESS,
-            ALT_BN128_G2_COMPRESS, ALT_BN128_G2_DECOMPRESS, G1, G1_COMPRESSED, G2, G2_COMPRESSED,
-        };
         let execution_cost = invoke_context.get_execution_cost();
-        let base_cost = execution_cost.syscall_base_cost;
-        let (cost, output): (u64, usize) = match op {
-            ALT_BN128_G1_COMPRESS => (
-                base_cost.saturating_add(execution_cost.alt_bn128_g1_compress),
-                G1_COMPRESSED,
-            ),
-            ALT_BN128_G1_DECOMPRESS => {
-                (base_cost.saturating_add(execution_cost.alt_bn128_g1_decompress), G1)
-            }
-            ALT_BN128_G2_COMPRESS => (
-                base_cost.saturating_add(execution_cost.alt_bn128_g2_compress),
-                G2_COMPRESSED,
-            ),
-            ALT_BN128_G2_DECOMPRESS => {
-                (base_cost.saturating_add(execution_cost.alt_bn128_g2_decompress), G2)
-            }
-            _ => {
-                return Err(SyscallError::InvalidAttribute.into());
-            }
-        };
-
-        consume_compute_meter(invoke_context, cost)?;
-
-        translate_mut!(
-            memory_mapping,
-            invoke_context.get_check_aligned(),
-            let call_result: &mut [u8] = map(result_addr, output as u64)?;
-        );
-        let input = translate_slice::<u8>(
-            memory_mapping,
-            input_addr,
-            input_size,
-            invoke_context.get_check_aligned(),
-        )?;
+        consume_compute_meter(invoke_context, execution_cost.syscall_base_cost)?;
 
-        let simplify_alt_bn128_syscall_error_codes = invoke_context
-            .get_feature_set()
-            .simplify_alt_bn128_syscall_error_codes;
+        Ok(invoke_context
+            .get_syscall_context()?
+            .allocator
+            .remaining())
+    }
+);
 
-        match op {
-            ALT_BN128_G1_COMPRESS => {
-                let result_point = match alt_bn128_g1_compress(input) {
-                    Ok(result_point) => result_point,
-                    Err(e) => {
-                        return if simplify_alt_bn128_syscall_error_codes {
+declare_builtin_function!(
+    /// Read the length of the heap region
+    ///
+    /// This is the size requested through the compute budget program, or the default heap length.
+    SyscallGetHeapSize,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let execution_cost = invoke_context.get_execution_cost();
+        consume_compute_meter(invoke_context, execution_cost.syscall_base_cost)?;
+
+        Ok(invoke_context.get_syscall_context()?.allocator.size())
+    }
+);
+
+/// Version of the [`ComputeBudgetInfo`] layout written by `sol_get_compute_budget`
+pub const COMPUTE_BUDGET_INFO_VERSION: u64 = 1;
+
+/// Limits of the compute budget of the current transaction, as written by
+/// `sol_get_compute_budget`
+///
+/// All fields are little-endian `u64`s. Later versions only append fields, so that programs built
+/// against an earlier version keep reading a valid prefix.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ComputeBudgetInfo {
+    pub version: u64,
+    pub compute_unit_limit: u64,
+    pub heap_size: u64,
+    pub max_instruction_stack_depth: u64,
+    pub max_instruction_trace_length: u64,
+    pub max_call_depth: u64,
+    pub stack_frame_size: u64,
+}
+
+impl ComputeBudgetInfo {
+    pub fn new(compute_budget: &SVMTransactionExecutionBudget) -> Self {
+        Self {
+            version: COMPUTE_BUDGET_INFO_VERSION,
+            compute_unit_limit: compute_budget.compute_unit_limit,
+            heap_size: u64::from(compute_budget.heap_size),
+            max_instruction_stack_depth: compute_budget.max_instruction_stack_depth as u64,
+            max_instruction_trace_length: compute_budget.max_instruction_trace_length as u64,
+            max_call_depth: compute_budget.max_call_depth as u64,
+            stack_frame_size: compute_budget.stack_frame_size as u64,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.version,
+            self.compute_unit_limit,
+            self.heap_size,
+            self.max_instruction_stack_depth,
+            self.max_instruction_trace_length,
+            self.max_call_depth,
+            self.stack_frame_size,
+        ]
+        .iter()
+        .flat_map(|field| field.to_le_bytes())
+        .collect()
+    }
+}
+
+declare_builtin_function!(
+    /// Write the limits of the compute budget of the current transaction into program memory
+    ///
+    /// At most `len` bytes of the [`ComputeBudgetInfo`] are written, so that programs may pass a
+    /// buffer sized for an earlier version. Returns the size of the full structure.
+    SyscallGetComputeBudget,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        var_addr: u64,
+        len: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let execution_cost = invoke_context.get_execution_cost();
+        consume_compute_meter(
+            invoke_context,
+            execution_cost
+                .syscall_base_cost
+                .saturating_add(execution_cost.mem_op_base_cost),
+        )?;
+
+        let bytes = ComputeBudgetInfo::new(invoke_context.get_compute_budget()).to_bytes();
+        let len = len.min(bytes.len() as u64);
+        let var = translate_slice_mut::<u8>(
+            memory_mapping,
+            var_addr,
+            len,
+            invoke_context.get_check_aligned(),
+        )?;
+        var.copy_from_slice(bytes.get(..len as usize).unwrap_or_default());
+        Ok(bytes.len() as u64)
+    }
+);
+
+declare_builtin_function!(
+    /// Log the statistics of the heap allocator and record the heap usage into the innermost
+    /// active profiling section
+    ///
+    /// Like `sol_remaining_heap`, only allocations made through `sol_alloc_free_` are tracked.
+    SyscallLogHeapStats,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let execution_cost = invoke_context.get_execution_cost();
+        consume_compute_meter(invoke_context, execution_cost.syscall_base_cost)?;
+
+        let syscall_context = invoke_context.get_syscall_context_mut()?;
+        let used = syscall_context.allocator.used();
+        let free = syscall_context.allocator.remaining();
+        let allocations = syscall_context.allocator.allocations();
+        if let Some(profiling) = &mut syscall_context.profiling {
+            profiling.record_heap_usage(used);
+        }
+        stable_log::program_heap_stats(
+            &invoke_context.get_log_collector(),
+            used,
+            free,
+            allocations,
+        );
+        Ok(0)
+    }
+);
+
+declare_builtin_function!(
+    /// alt_bn128 g1 and g2 compression and decompression
+    SyscallAltBn128Compression,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        op: u64,
+        input_addr: u64,
+        input_size: u64,
+        result_addr: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        use solana_bn254::compression::prelude::{
+            alt_bn128_g1_compress, alt_bn128_g1_decompress, alt_bn128_g2_compress,
+            alt_bn128_g2_decompress, ALT_BN128_G1_COMPRESS, ALT_BN128_G1_DECOMPRESS,
+            ALT_BN128_G2_COMPRESS, ALT_BN128_G2_DECOMPRESS, G1, G1_COMPRESSED, G2, G2_COMPRESSED,
+        };
+        let execution_cost = invoke_context.get_execution_cost();
+        let base_cost = execution_cost.syscall_base_cost;
+        let (cost, output): (u64, usize) = match op {
+            ALT_BN128_G1_COMPRESS => (
+                base_cost.saturating_add(execution_cost.alt_bn128_g1_compress),
+                G1_COMPRESSED,
+            ),
+            ALT_BN128_G1_DECOMPRESS => {
+                (base_cost.saturating_add(execution_cost.alt_bn128_g1_decompress), G1)
+            }
+            ALT_BN128_G2_COMPRESS => (
+                base_cost.saturating_add(execution_cost.alt_bn128_g2_compress),
+                G2_COMPRESSED,
+            ),
+            ALT_BN128_G2_DECOMPRESS => {
+                (base_cost.saturating_add(execution_cost.alt_bn128_g2_decompress), G2)
+            }
+            _ => {
+                return Err(SyscallError::InvalidAttribute.into());
+            }
+        };
+
+        consume_compute_meter(invoke_context, cost)?;
+
+        translate_mut!(
+            memory_mapping,
+            invoke_context.get_check_aligned(),
+            let call_result: &mut [u8] = map(result_addr, output as u64)?;
+        );
+        let input = translate_slice::<u8>(
+            memory_mapping,
+            input_addr,
+            input_size,
+            invoke_context.get_check_aligned(),
+        )?;
+
+        let simplify_alt_bn128_syscall_error_codes = invoke_context
+            .get_feature_set()
+            .simplify_alt_bn128_syscall_error_codes;
+
+        match op {
+            ALT_BN128_G1_COMPRESS => {
+                let result_point = match alt_bn128_g1_compress(input) {
+                    Ok(result_point) => result_point,
+                    Err(e) => {
+                        return if simplify_alt_bn128_syscall_error_codes {
                             Ok(1)
                         } else {
                             Ok(e.into())
@@ -2134,6 +2839,10 @@ mod tests {
         solana_program_runtime::{
             execution_budget::MAX_HEAP_FRAME_BYTES,
             invoke_context::{BpfAllocator, InvokeContext, SyscallContext},
+            profiling::{
+                ProfileEntryKind, HEAP_CHECKPOINT_FREE_ATTRIBUTE,
+                HEAP_CHECKPOINT_LARGEST_FREE_ATTRIBUTE,
+            },
             with_mock_invoke_context,
         },
         solana_sbpf::{
@@ -2142,16 +2851,18 @@ mod tests {
             error::EbpfError,
             memory_region::{MemoryMapping, MemoryRegion},
             program::SBPFVersion,
-            vm::Config,
+            vm::{Config, ContextObject},
         },
         solana_sdk_ids::{bpf_loader, bpf_loader_upgradeable, sysvar},
         solana_sha256_hasher::hashv,
         solana_slot_hashes::{self as slot_hashes, SlotHashes},
         solana_stable_layout::stable_instruction::StableInstruction,
         solana_stake_interface::stake_history::{self, StakeHistory, StakeHistoryEntry},
+        solana_svm_log_collector::LogRecord,
         solana_sysvar_id::SysvarId,
         solana_transaction_context::InstructionAccount,
         std::{
+            collections::BTreeMap,
             hash::{DefaultHasher, Hash, Hasher},
             mem,
             str::FromStr,
@@ -2497,89 +3208,2521 @@ mod tests {
         );
 
         assert_eq!(
-            invoke_context
-                .get_log_collector()
-                .unwrap()
-                .borrow()
-                .get_recorded_content(),
-            &["Program log: Gaggablaghblagh!".to_string()]
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &["Program log: Gaggablaghblagh!".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_over_limit() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let log_collector = invoke_context.get_log_collector().unwrap();
+        log_collector.borrow_mut().bytes_limit = Some(16);
+
+        let string = "Gaggablaghblagh!";
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(string.as_bytes(), 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        invoke_context.mock_set_remaining(300);
+        for _ in 0..2 {
+            let result = SyscallLog::rust(
+                &mut invoke_context,
+                0x100000000,
+                string.len() as u64,
+                0,
+                0,
+                0,
+                &mut memory_mapping,
+            );
+            result.unwrap();
+        }
+        assert_eq!(
+            log_collector.borrow().get_recorded_content(),
+            &["Log truncated".to_string()]
+        );
+
+        // Messages which are not recorded are still translated
+        let result = SyscallLog::rust(
+            &mut invoke_context,
+            0x100000001,
+            string.len() as u64,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_access_violation!(result, 0x100000001, string.len() as u64);
+        assert_eq!(invoke_context.get_remaining(), 0);
+    }
+
+    #[test]
+    fn test_syscall_sol_log_level() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+
+        let string = "Gaggablaghblagh!";
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(string.as_bytes(), 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+        let cost = invoke_context.get_execution_cost().syscall_base_cost;
+        let log_collector = invoke_context.get_log_collector().unwrap();
+        log_collector.borrow_mut().min_level = LogLevel::Info;
+
+        for level in LogLevel::ALL {
+            invoke_context.mock_set_remaining(cost);
+            let result = SyscallLogLevel::rust(
+                &mut invoke_context,
+                level as u64,
+                0x100000000,
+                string.len() as u64,
+                0,
+                0,
+                &mut memory_mapping,
+            );
+            result.unwrap();
+            // Filtered messages are charged all the same
+            assert_eq!(invoke_context.get_remaining(), 0);
+        }
+
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogLevel::rust(
+            &mut invoke_context,
+            LogLevel::ALL.len() as u64,
+            0x100000000,
+            string.len() as u64,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidLogLevel(5)
+        );
+
+        assert_eq!(
+            log_collector.borrow().get_recorded_content(),
+            &[
+                "Program log: [info] Gaggablaghblagh!".to_string(),
+                "Program log: [warn] Gaggablaghblagh!".to_string(),
+                "Program log: [error] Gaggablaghblagh!".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_backtrace() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let cost = invoke_context.get_execution_cost().syscall_base_cost;
+        invoke_context.mock_set_remaining(cost);
+
+        // The mock program is not loaded, so its frames cannot be symbolized
+        let result = SyscallBacktrace::rust(&mut invoke_context, &[12, 3]);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(invoke_context.get_remaining(), 0);
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &[
+                "Program backtrace: #0 12 ?".to_string(),
+                "Program backtrace: #1 3 ?".to_string(),
+            ]
+        );
+
+        let result = SyscallBacktrace::rust(&mut invoke_context, &[12]);
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::ComputationalBudgetExceeded
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_debug_break() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let cost = invoke_context.get_execution_cost().syscall_base_cost;
+        invoke_context.mock_set_remaining(cost + 5);
+
+        let registers: [u64; 12] = std::array::from_fn(|index| index as u64 * 0x10);
+        let result = SyscallDebugBreak::rust(&mut invoke_context, &registers, &[110, 7]);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(invoke_context.get_remaining(), 5);
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &[
+                "Program debug break: pc 176 remaining 5".to_string(),
+                "Program debug break: r0=0x0 r1=0x10 r2=0x20 r3=0x30 r4=0x40 r5=0x50 r6=0x60 \
+                 r7=0x70 r8=0x80 r9=0x90 r10=0xa0"
+                    .to_string(),
+                "Program backtrace: #0 110 ?".to_string(),
+                "Program backtrace: #1 7 ?".to_string(),
+            ]
+        );
+
+        let result = SyscallDebugBreak::rust(&mut invoke_context, &registers, &[]);
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::ComputationalBudgetExceeded
+        );
+    }
+
+    #[test_case(b"sol_backtrace")]
+    #[test_case(b"sol_debug_break")]
+    fn test_debugging_syscall_registration(syscall_name: &[u8]) {
+        let is_registered = |debugging_features: bool| {
+            create_program_runtime_environment_v1(
+                &SVMFeatureSet::all_enabled(),
+                &SVMTransactionExecutionBudget::default(),
+                false, /* deployment */
+                debugging_features,
+            )
+            .unwrap()
+            .get_function_registry()
+            .iter()
+            .any(|(_key, (name, _function))| name == syscall_name)
+        };
+        assert!(is_registered(true));
+        assert!(!is_registered(false));
+    }
+
+    #[test]
+    fn test_syscall_sol_host_time() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let cost = invoke_context.get_execution_cost().syscall_base_cost;
+        invoke_context.mock_set_remaining(2 * cost);
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(vec![], &config, SBPFVersion::V3).unwrap();
+
+        let first =
+            SyscallHostTime::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping).unwrap();
+        let second =
+            SyscallHostTime::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping).unwrap();
+        assert!(second >= first);
+        assert_eq!(invoke_context.get_remaining(), 0);
+
+        let result = SyscallHostTime::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::ComputationalBudgetExceeded
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_get_stack_usage() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let execution_cost = invoke_context.get_execution_cost();
+        let cost = execution_cost.syscall_base_cost + execution_cost.mem_op_base_cost;
+        invoke_context.mock_set_remaining(cost);
+        let mut var = StackUsage::default();
+        let config = Config::default();
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_writable(
+                bytes_of_mut(&mut var),
+                0x100000000,
+            )],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        let stack_usage = StackUsage {
+            call_depth: 2,
+            max_call_depth: 64,
+            stack_bytes: 3 * 4096,
+            stack_size: 64 * 4096,
+        };
+        let result = SyscallGetStackUsage::rust(
+            &mut invoke_context,
+            0x100000000,
+            &memory_mapping,
+            stack_usage,
+        );
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(invoke_context.get_remaining(), 0);
+
+        let result = SyscallGetStackUsage::rust(
+            &mut invoke_context,
+            0x100000000,
+            &memory_mapping,
+            stack_usage,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::ComputationalBudgetExceeded
+        );
+        drop(memory_mapping);
+        assert_eq!(var, stack_usage);
+    }
+
+    #[test]
+    fn test_syscall_sol_emit_metric() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let cost = invoke_context.get_execution_cost().syscall_base_cost;
+        invoke_context.mock_set_remaining(3 * cost);
+        let name = b"swaps";
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(name, 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        let result = SyscallEmitMetric::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            42,
+            1,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        let result = SyscallEmitMetric::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            42,
+            2,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidMetricKind(2)
+        );
+
+        let result = SyscallEmitMetric::rust(
+            &mut invoke_context,
+            0x100000000,
+            MAX_PROFILE_NAME_LEN + 1,
+            42,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidLength
+        );
+        assert_eq!(invoke_context.get_remaining(), 0);
+
+        let result = SyscallEmitMetric::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            42,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::ComputationalBudgetExceeded
+        );
+    }
+
+    #[test_case("sol_host_time")]
+    #[test_case("sol_emit_metric")]
+    fn test_register_diagnostics_syscalls(syscall_name: &str) {
+        let is_registered = |diagnostics_mode: bool| {
+            let mut program_runtime_environment = create_program_runtime_environment_v1(
+                &SVMFeatureSet::all_enabled(),
+                &SVMTransactionExecutionBudget::default(),
+                false, /* deployment */
+                true,  /* debugging_features */
+            )
+            .unwrap();
+            register_diagnostics_syscalls(&mut program_runtime_environment, diagnostics_mode)
+                .unwrap();
+            let registered = program_runtime_environment
+                .get_function_registry()
+                .iter()
+                .any(|(_key, (name, _function))| name == syscall_name.as_bytes());
+            registered
+        };
+        assert!(is_registered(true));
+        assert!(!is_registered(false));
+    }
+
+    #[test_case("sol_log_compute_units_start")]
+    #[test_case("sol_get_stack_usage")]
+    fn test_register_profiling_syscalls(syscall_name: &str) {
+        let is_registered = |enable_profiling_syscalls: bool| {
+            let feature_set = SVMFeatureSet {
+                enable_profiling_syscalls,
+                ..SVMFeatureSet::all_enabled()
+            };
+            let compute_budget = SVMTransactionExecutionBudget::default();
+            let program_runtime_environments = [
+                create_program_runtime_environment_v1(
+                    &feature_set,
+                    &compute_budget,
+                    false, /* deployment */
+                    false, /* debugging_features */
+                )
+                .unwrap(),
+                create_program_runtime_environment_v2(
+                    &feature_set,
+                    &compute_budget,
+                    false, /* debugging_features */
+                )
+                .unwrap(),
+            ];
+            program_runtime_environments.map(|program_runtime_environment| {
+                program_runtime_environment
+                    .get_function_registry()
+                    .iter()
+                    .any(|(_key, (name, _function))| name == syscall_name.as_bytes())
+            })
+        };
+        assert_eq!(is_registered(true), [true, true]);
+        assert_eq!(is_registered(false), [false, false]);
+    }
+
+    #[test]
+    fn test_create_observed_program_runtime_environment_v1() {
+        let feature_set = SVMFeatureSet::all_enabled();
+        let compute_budget = SVMTransactionExecutionBudget::default();
+        let sol_log = |program_runtime_environment: &BuiltinProgram<InvokeContext>| {
+            program_runtime_environment
+                .get_function_registry()
+                .iter()
+                .find(|(_key, (name, _function))| name == b"sol_log_")
+                .map(|(_key, (_name, function))| function as usize)
+                .unwrap()
+        };
+        let program_runtime_environment = create_program_runtime_environment_v1(
+            &feature_set,
+            &compute_budget,
+            false, /* deployment */
+            false, /* debugging_features */
+        )
+        .unwrap();
+        let observed_program_runtime_environment = create_observed_program_runtime_environment_v1(
+            &feature_set,
+            &compute_budget,
+            false, /* deployment */
+            false, /* debugging_features */
+        )
+        .unwrap();
+
+        // Syscalls are only wrapped in the observed environment
+        assert_eq!(
+            sol_log(&program_runtime_environment),
+            SyscallLog::vm as usize
+        );
+        assert_ne!(
+            sol_log(&observed_program_runtime_environment),
+            SyscallLog::vm as usize
+        );
+        let names = |program_runtime_environment: &BuiltinProgram<InvokeContext>| {
+            program_runtime_environment
+                .get_function_registry()
+                .iter()
+                .map(|(_key, (name, _function))| name.to_vec())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(&program_runtime_environment),
+            names(&observed_program_runtime_environment)
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_u64() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let cost = invoke_context.get_execution_cost().log_64_units;
+
+        invoke_context.mock_set_remaining(cost);
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(vec![], &config, SBPFVersion::V3).unwrap();
+        let result = SyscallLogU64::rust(&mut invoke_context, 1, 2, 3, 4, 5, &mut memory_mapping);
+        result.unwrap();
+
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &["Program log: 0x1, 0x2, 0x3, 0x4, 0x5".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_u64_dec() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let cost = invoke_context.get_execution_cost().log_64_units;
+
+        invoke_context.mock_set_remaining(cost);
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(vec![], &config, SBPFVersion::V3).unwrap();
+        let result = SyscallLogU64Dec::rust(
+            &mut invoke_context,
+            1,
+            20,
+            300,
+            u64::MAX,
+            0,
+            &mut memory_mapping,
+        );
+        result.unwrap();
+
+        let result =
+            SyscallLogU64Dec::rust(&mut invoke_context, 1, 2, 3, 4, 5, &mut memory_mapping);
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::ComputationalBudgetExceeded
+        );
+
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &["Program log: 1, 20, 300, 18446744073709551615, 0".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_i64() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let cost = invoke_context.get_execution_cost().log_64_units;
+
+        invoke_context.mock_set_remaining(cost);
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(vec![], &config, SBPFVersion::V3).unwrap();
+        let result = SyscallLogI64::rust(
+            &mut invoke_context,
+            (-1i64) as u64,
+            42,
+            i64::MIN as u64,
+            i64::MAX as u64,
+            0,
+            &mut memory_mapping,
+        );
+        result.unwrap();
+
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &["Program log: -1, 42, -9223372036854775808, 9223372036854775807, 0".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_amount() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let cost = invoke_context.get_execution_cost().log_64_units;
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(vec![], &config, SBPFVersion::V3).unwrap();
+
+        for (value, decimals) in [(1_500_000, 6), (42, 6), (0, 2), (u64::MAX, 9), (7, 0)] {
+            invoke_context.mock_set_remaining(cost);
+            let result = SyscallLogAmount::rust(
+                &mut invoke_context,
+                value,
+                decimals,
+                0,
+                0,
+                0,
+                &mut memory_mapping,
+            );
+            result.unwrap();
+            assert_eq!(invoke_context.get_remaining(), 0);
+        }
+
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogAmount::rust(
+            &mut invoke_context,
+            1,
+            u64::from(u8::MAX) + 1,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidLength
+        );
+
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &[
+                "Program log: 1.500000".to_string(),
+                "Program log: 0.000042".to_string(),
+                "Program log: 0.00".to_string(),
+                "Program log: 18446744073.709551615".to_string(),
+                "Program log: 7".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_compute_units_at() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+
+        let label = "after_transfer";
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(label.as_bytes(), 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+        let cost = invoke_context.get_execution_cost().syscall_base_cost;
+
+        invoke_context.mock_set_remaining(cost + 42);
+        let result = SyscallLogBpfComputeUnitsAt::rust(
+            &mut invoke_context,
+            0x100000000,
+            label.len() as u64,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        result.unwrap();
+
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogBpfComputeUnitsAt::rust(
+            &mut invoke_context,
+            0x100000001,
+            label.len() as u64,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_access_violation!(result, 0x100000001, label.len() as u64);
+
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &["Program consumption at after_transfer: 42 units remaining".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_account() {
+        let program_id = Pubkey::new_unique();
+        let account_key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let transaction_accounts = vec![
+            (
+                bpf_loader::id(),
+                AccountSharedData::new(0, 0, &native_loader::id()),
+            ),
+            (program_id, AccountSharedData::new(0, 0, &bpf_loader::id())),
+            (account_key, AccountSharedData::new(42, 10, &owner)),
+        ];
+        with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+        invoke_context
+            .transaction_context
+            .configure_next_instruction_for_tests(
+                1,
+                vec![InstructionAccount::new(2, false, true)],
+                &[],
+            )
+            .unwrap();
+        invoke_context.push().unwrap();
+        let cost = invoke_context.get_execution_cost().log_pubkey_units;
+        invoke_context.mock_set_remaining(2 * cost);
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(vec![], &config, SBPFVersion::V3).unwrap();
+
+        let result =
+            SyscallLogAccount::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(invoke_context.get_remaining(), 0);
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &[format!(
+                "Program account: #0 {account_key} owner {owner} lamports 42 data_len 10"
+            )]
+        );
+
+        invoke_context.mock_set_remaining(2 * cost);
+        let result =
+            SyscallLogAccount::rust(&mut invoke_context, 1, 0, 0, 0, 0, &mut memory_mapping);
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::NotEnoughAccountKeys
+        );
+        invoke_context.mock_set_remaining(2 * cost);
+        let result = SyscallLogAccount::rust(
+            &mut invoke_context,
+            u64::MAX,
+            0,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::NotEnoughAccountKeys
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_data() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let fields = ["hello", "", "world"];
+        let mut mock_slices: Vec<_> = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| MockSlice {
+                vm_addr: 0x200000000 + 0x100000000 * index as u64,
+                len: field.len(),
+            })
+            .collect();
+        // Not mapped
+        mock_slices.push(MockSlice {
+            vm_addr: 0x900000000,
+            len: 1,
+        });
+        let config = Config::default();
+        let mut regions = vec![MemoryRegion::new_readonly(
+            bytes_of_slice(&mock_slices),
+            0x100000000,
+        )];
+        for (field, mock_slice) in fields.iter().zip(&mock_slices) {
+            regions.push(MemoryRegion::new_readonly(
+                field.as_bytes(),
+                mock_slice.vm_addr,
+            ));
+        }
+        let mut memory_mapping = MemoryMapping::new(regions, &config, SBPFVersion::V3).unwrap();
+
+        let cost = invoke_context.get_execution_cost().syscall_base_cost * 4
+            + fields.iter().map(|field| field.len() as u64).sum::<u64>();
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogData::rust(
+            &mut invoke_context,
+            0x100000000,
+            fields.len() as u64,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        result.unwrap();
+        assert_eq!(invoke_context.get_remaining(), 0);
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &["Program data: aGVsbG8=  d29ybGQ=".to_string()]
+        );
+
+        // Nothing is logged if a field cannot be translated
+        invoke_context.mock_set_remaining(u64::MAX);
+        let result = SyscallLogData::rust(
+            &mut invoke_context,
+            0x100000000,
+            mock_slices.len() as u64,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert!(result.is_err());
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_fmt() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let template = "balance {} of {:x} {{{}}}";
+        let args = [1_000u64, 255, 7];
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![
+                MemoryRegion::new_readonly(template.as_bytes(), 0x100000000),
+                MemoryRegion::new_readonly(bytes_of_slice(&args), 0x200000000),
+            ],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        let cost = invoke_context.get_execution_cost().syscall_base_cost;
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogFmt::rust(
+            &mut invoke_context,
+            0x100000000,
+            template.len() as u64,
+            0x200000000,
+            args.len() as u64,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(invoke_context.get_remaining(), 0);
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &["Program log: balance 1000 of ff {7}".to_string()]
+        );
+
+        // The placeholders must match the arguments
+        for args_len in [2, 0] {
+            invoke_context.mock_set_remaining(cost);
+            let result = SyscallLogFmt::rust(
+                &mut invoke_context,
+                0x100000000,
+                template.len() as u64,
+                0x200000000,
+                args_len,
+                0,
+                &mut memory_mapping,
+            );
+            assert_matches!(
+                result,
+                Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidLogFormat(template.to_string())
+            );
+        }
+
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogFmt::rust(
+            &mut invoke_context,
+            0x100000000,
+            template.len() as u64,
+            0x200000000,
+            MAX_LOG_FMT_ARGS + 1,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidLength
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_event() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let name = b"swap";
+        let data = [b"foo".as_slice(), b"bar!".as_slice()];
+        let mock_slices: Vec<_> = data
+            .iter()
+            .enumerate()
+            .map(|(index, field)| MockSlice {
+                vm_addr: 0x300000000 + 0x100000000 * index as u64,
+                len: field.len(),
+            })
+            .collect();
+        let config = Config::default();
+        let mut regions = vec![
+            MemoryRegion::new_readonly(name, 0x100000000),
+            MemoryRegion::new_readonly(bytes_of_slice(&mock_slices), 0x200000000),
+        ];
+        for (field, mock_slice) in data.iter().zip(&mock_slices) {
+            regions.push(MemoryRegion::new_readonly(field, mock_slice.vm_addr));
+        }
+        let mut memory_mapping = MemoryMapping::new(regions, &config, SBPFVersion::V3).unwrap();
+
+        let cost = invoke_context.get_execution_cost().syscall_base_cost * 3
+            + name.len() as u64
+            + data.iter().map(|field| field.len() as u64).sum::<u64>();
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogEvent::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            0x200000000,
+            data.len() as u64,
+            0,
+            &mut memory_mapping,
+        );
+        result.unwrap();
+        assert_eq!(invoke_context.get_remaining(), 0);
+
+        let log_collector = invoke_context.get_log_collector().unwrap();
+        assert_eq!(
+            log_collector.borrow().get_recorded_content(),
+            &["Program event: swap Zm9v YmFyIQ==".to_string()]
+        );
+        assert_eq!(
+            log_collector.borrow().get_recorded_records(),
+            &[LogRecord {
+                message_index: 0,
+                fields: vec![
+                    ("event".to_string(), "swap".to_string()),
+                    ("data".to_string(), "Zm9v YmFyIQ==".to_string()),
+                ],
+            }]
+        );
+
+        // Event names must not be empty
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogEvent::rust(
+            &mut invoke_context,
+            0x100000000,
+            0,
+            0x200000000,
+            data.len() as u64,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidEventName(String::new())
+        );
+
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogEvent::rust(
+            &mut invoke_context,
+            0x100000000,
+            MAX_LOG_EVENT_NAME_LEN + 1,
+            0x200000000,
+            data.len() as u64,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidLength
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_kv() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let strings = ["slot", "name", "42", "a \"b\""];
+        let mock_slices: Vec<_> = strings
+            .iter()
+            .enumerate()
+            .map(|(index, string)| MockSlice {
+                vm_addr: 0x300000000 + 0x100000000 * index as u64,
+                len: string.len(),
+            })
+            .collect();
+        let (keys, values) = mock_slices.split_at(2);
+        let config = Config::default();
+        let mut regions = vec![
+            MemoryRegion::new_readonly(bytes_of_slice(keys), 0x100000000),
+            MemoryRegion::new_readonly(bytes_of_slice(values), 0x200000000),
+        ];
+        for (string, mock_slice) in strings.iter().zip(&mock_slices) {
+            regions.push(MemoryRegion::new_readonly(
+                string.as_bytes(),
+                mock_slice.vm_addr,
+            ));
+        }
+        let mut memory_mapping = MemoryMapping::new(regions, &config, SBPFVersion::V3).unwrap();
+
+        let cost = invoke_context.get_execution_cost().syscall_base_cost * 3
+            + strings
+                .iter()
+                .map(|string| string.len() as u64)
+                .sum::<u64>();
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogKv::rust(
+            &mut invoke_context,
+            0x100000000,
+            0x200000000,
+            2,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        result.unwrap();
+        assert_eq!(invoke_context.get_remaining(), 0);
+
+        let log_collector = invoke_context.get_log_collector().unwrap();
+        assert_eq!(
+            log_collector.borrow().get_recorded_content(),
+            &[r#"Program kv: slot="42" name="a \"b\"""#.to_string()]
+        );
+        assert_eq!(
+            log_collector.borrow().get_recorded_records(),
+            &[LogRecord {
+                message_index: 0,
+                fields: vec![
+                    ("slot".to_string(), "42".to_string()),
+                    ("name".to_string(), "a \"b\"".to_string()),
+                ],
+            }]
+        );
+
+        // Keys must not contain whitespace
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogKv::rust(
+            &mut invoke_context,
+            0x200000000,
+            0x100000000,
+            2,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidLogKey("a \"b\"".to_string())
+        );
+
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogKv::rust(
+            &mut invoke_context,
+            0x100000000,
+            0x200000000,
+            MAX_LOG_KV_FIELDS + 1,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::TooManySlices
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_hex() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let data = b"hello\x00\x01\xffworld!";
+        let len = data.len() as u64;
+        let cost = invoke_context.get_execution_cost().syscall_base_cost + len;
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(data, 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        invoke_context.mock_set_remaining(cost * 2);
+        let result = SyscallLogHex::rust(
+            &mut invoke_context,
+            0x100000000,
+            len,
+            8,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        result.unwrap();
+        // The default line width fits the whole slice
+        let result = SyscallLogHex::rust(
+            &mut invoke_context,
+            0x100000000,
+            len,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        result.unwrap();
+        assert_eq!(invoke_context.get_remaining(), 0);
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &[
+                "Program log: 00000000: 68 65 6c 6c 6f 00 01 ff  hello...".to_string(),
+                "Program log: 00000008: 77 6f 72 6c 64 21        world!".to_string(),
+                "Program log: 00000000: 68 65 6c 6c 6f 00 01 ff 77 6f 72 6c 64 21        \
+                 hello...world!"
+                    .to_string(),
+            ]
+        );
+
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogHex::rust(
+            &mut invoke_context,
+            0x100000000,
+            len,
+            MAX_LOG_HEX_BYTES_PER_LINE + 1,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidLength
+        );
+
+        invoke_context.mock_set_remaining(cost + 1);
+        let result = SyscallLogHex::rust(
+            &mut invoke_context,
+            0x100000000,
+            len + 1,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_access_violation!(result, 0x100000000, len + 1);
+
+        invoke_context.mock_set_remaining(cost - 1);
+        let result = SyscallLogHex::rust(
+            &mut invoke_context,
+            0x100000000,
+            len,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::ComputationalBudgetExceeded
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_pubkey() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let cost = invoke_context.get_execution_cost().log_pubkey_units;
+
+        let pubkey = Pubkey::from_str("MoqiU1vryuCGQSxFKA1SZ316JdLEFFhoAu6cKUNk7dN").unwrap();
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(bytes_of(&pubkey), 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        let result = SyscallLogPubkey::rust(
+            &mut invoke_context,
+            0x100000001, // AccessViolation
+            32,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_access_violation!(result, 0x100000001, 32);
+
+        invoke_context.mock_set_remaining(1);
+        let result =
+            SyscallLogPubkey::rust(&mut invoke_context, 100, 32, 0, 0, 0, &mut memory_mapping);
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::ComputationalBudgetExceeded
+        );
+
+        invoke_context.mock_set_remaining(cost);
+        let result = SyscallLogPubkey::rust(
+            &mut invoke_context,
+            0x100000000,
+            0,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        result.unwrap();
+
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &["Program log: MoqiU1vryuCGQSxFKA1SZ316JdLEFFhoAu6cKUNk7dN".to_string()]
+        );
+    }
+
+    macro_rules! setup_profiling_test {
+        ($invoke_context:ident, $remaining:expr) => {
+            setup_profiling_test!(
+                $invoke_context,
+                $remaining,
+                solana_program_entrypoint::HEAP_LENGTH as u64
+            );
+        };
+        ($invoke_context:ident, $remaining:expr, $heap_size:expr) => {
+            prepare_mockup!($invoke_context, program_id, bpf_loader::id());
+            $invoke_context.enable_profiling = true;
+            $invoke_context.mock_set_remaining($remaining);
+            $invoke_context
+                .set_syscall_context(SyscallContext {
+                    allocator: BpfAllocator::new($heap_size),
+                    accounts_metadata: Vec::new(),
+                    trace_log: Vec::new(),
+                    profiling: $invoke_context.new_profiling_state().unwrap(),
+                })
+                .unwrap();
+        };
+    }
+
+    #[test]
+    fn test_syscall_sol_log_compute_units_start_end() {
+        setup_profiling_test!(invoke_context, 1_000);
+
+        let name = "section";
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(name.as_bytes(), 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        invoke_context.mock_set_remaining(950);
+        let result = SyscallLogComputeUnitsStart::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            32,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        invoke_context.mock_set_remaining(700);
+        let result = SyscallLogComputeUnitsEnd::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            64,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        // Ending a section which is not active
+        let result = SyscallLogComputeUnitsEnd::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(invoke_context.get_remaining(), 700);
+
+        let entries = invoke_context
+            .get_syscall_context()
+            .unwrap()
+            .profiling
+            .as_ref()
+            .unwrap()
+            .entries();
+        assert_eq!(entries.len(), 1);
+        let entry = entries.first().unwrap();
+        assert_eq!(entry.kind, ProfileEntryKind::Section);
+        assert_eq!(entry.name, name);
+        assert_eq!(entry.start_cu, 50);
+        assert_eq!(entry.consumed(), 250);
+        assert_eq!(entry.heap_start, Some(32));
+        assert_eq!(entry.heap_end, Some(64));
+    }
+
+    #[test]
+    fn test_syscall_sol_log_compute_units_end_across_cpi() {
+        setup_profiling_test!(invoke_context, 1_000);
+
+        let names = b"outerinnermissing";
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(names, 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+        let (outer, inner, missing) = ((0x100000000, 5), (0x100000005, 5), (0x10000000a, 7));
+
+        invoke_context.mock_set_remaining(950);
+        let result = SyscallLogComputeUnitsStart::rust(
+            &mut invoke_context,
+            outer.0,
+            outer.1,
+            32,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        // Invoke the program again, as a CPI would
+        invoke_context
+            .transaction_context
+            .configure_next_instruction_for_tests(1, vec![], &[])
+            .unwrap();
+        invoke_context.push().unwrap();
+        let profiling = invoke_context.new_profiling_state().unwrap();
+        invoke_context
+            .set_syscall_context(SyscallContext {
+                allocator: BpfAllocator::new(solana_program_entrypoint::HEAP_LENGTH as u64),
+                accounts_metadata: Vec::new(),
+                trace_log: Vec::new(),
+                profiling,
+            })
+            .unwrap();
+
+        invoke_context.mock_set_remaining(900);
+        let result = SyscallLogComputeUnitsStart::rust(
+            &mut invoke_context,
+            inner.0,
+            inner.1,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        invoke_context.mock_set_remaining(850);
+        let result = SyscallLogComputeUnitsEnd::rust(
+            &mut invoke_context,
+            inner.0,
+            inner.1,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        // The callee closes the section opened by the caller
+        invoke_context.mock_set_remaining(800);
+        let result = SyscallLogComputeUnitsEnd::rust(
+            &mut invoke_context,
+            outer.0,
+            outer.1,
+            64,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        let result = SyscallLogComputeUnitsEnd::rust(
+            &mut invoke_context,
+            missing.0,
+            missing.1,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 1);
+
+        // Entries of the callee are nested under the section of the caller
+        let callee_entries = invoke_context
+            .get_syscall_context()
+            .unwrap()
+            .profiling
+            .as_ref()
+            .unwrap()
+            .entries();
+        assert_eq!(callee_entries.len(), 1);
+        assert_eq!(callee_entries[0].name, "inner");
+        assert_eq!(callee_entries[0].depth, 1);
+        assert_eq!(callee_entries[0].consumed(), 50);
+
+        let caller_entries = invoke_context.syscall_context[0]
+            .as_ref()
+            .unwrap()
+            .profiling
+            .as_ref()
+            .unwrap()
+            .entries();
+        assert_eq!(caller_entries.len(), 1);
+        assert_eq!(caller_entries[0].name, "outer");
+        assert_eq!(caller_entries[0].depth, 0);
+        assert_eq!(caller_entries[0].consumed(), 150);
+        // The callee's heap sample does not describe the caller's heap
+        assert_eq!(caller_entries[0].heap_start, Some(32));
+        assert_eq!(caller_entries[0].heap_end, None);
+    }
+
+    #[test]
+    fn test_syscall_sol_log_compute_units_start_end_id() {
+        setup_profiling_test!(invoke_context, 1_000);
+
+        let name = "deserialize";
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(name.as_bytes(), 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        let result = SyscallProfileRegisterName::rust(
+            &mut invoke_context,
+            7,
+            0x100000000,
+            name.len() as u64,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        for id in [7, 8] {
+            invoke_context.mock_set_remaining(900);
+            let result = SyscallLogComputeUnitsStartId::rust(
+                &mut invoke_context,
+                id,
+                NO_HEAP_SAMPLE,
+                0,
+                0,
+                0,
+                &mut memory_mapping,
+            );
+            assert_eq!(result.unwrap(), 0);
+
+            invoke_context.mock_set_remaining(600);
+            let result = SyscallLogComputeUnitsEndId::rust(
+                &mut invoke_context,
+                id,
+                16,
+                0,
+                0,
+                0,
+                &mut memory_mapping,
+            );
+            assert_eq!(result.unwrap(), 0);
+        }
+
+        // Ending a section which is not active
+        let result = SyscallLogComputeUnitsEndId::rust(
+            &mut invoke_context,
+            7,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(invoke_context.get_remaining(), 600);
+
+        let entries = invoke_context
+            .get_syscall_context()
+            .unwrap()
+            .profiling
+            .as_ref()
+            .unwrap()
+            .entries();
+        assert_eq!(entries.len(), 2);
+        for entry in entries {
+            assert_eq!(entry.kind, ProfileEntryKind::Section);
+            assert_eq!(entry.start_cu, 100);
+            assert_eq!(entry.consumed(), 300);
+            assert_eq!(entry.heap_end, Some(16));
+        }
+        assert_eq!(entries.first().unwrap().name, name);
+        assert_eq!(entries.last().unwrap().name, "#8");
+    }
+
+    #[test]
+    fn test_syscall_sol_profile_section_cu() {
+        setup_profiling_test!(invoke_context, 1_000);
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(vec![], &config, SBPFVersion::V3).unwrap();
+
+        let result =
+            SyscallProfileSectionCu::rust(&mut invoke_context, 7, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), u64::MAX);
+
+        invoke_context.mock_set_remaining(900);
+        let result = SyscallLogComputeUnitsStartId::rust(
+            &mut invoke_context,
+            7,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        invoke_context.mock_set_remaining(750);
+        let result =
+            SyscallProfileSectionCu::rust(&mut invoke_context, 7, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), 150);
+        assert_eq!(invoke_context.get_remaining(), 750);
+    }
+
+    #[test]
+    fn test_profiling_state_is_observable() {
+        setup_profiling_test!(invoke_context, 1_000);
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(vec![], &config, SBPFVersion::V3).unwrap();
+
+        // Unlike the other profiling syscalls, these two return values which depend on whether
+        // the instruction is profiled
+        let observe = |invoke_context: &mut InvokeContext, memory_mapping: &mut MemoryMapping| {
+            invoke_context.mock_set_remaining(900);
+            let result = SyscallLogComputeUnitsStartId::rust(
+                invoke_context,
+                7,
+                NO_HEAP_SAMPLE,
+                0,
+                0,
+                0,
+                memory_mapping,
+            );
+            assert_eq!(result.unwrap(), 0);
+            invoke_context.mock_set_remaining(850);
+            let enabled =
+                SyscallProfileEnabled::rust(invoke_context, 0, 0, 0, 0, 0, memory_mapping).unwrap();
+            let consumed =
+                SyscallProfileSectionCu::rust(invoke_context, 7, 0, 0, 0, 0, memory_mapping)
+                    .unwrap();
+            (enabled, consumed)
+        };
+        assert_eq!(observe(&mut invoke_context, &mut memory_mapping), (1, 50));
+        invoke_context.get_syscall_context_mut().unwrap().profiling = None;
+        assert_eq!(
+            observe(&mut invoke_context, &mut memory_mapping),
+            (0, u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_assert_cu_budget() {
+        setup_profiling_test!(invoke_context, 1_000);
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(vec![], &config, SBPFVersion::V3).unwrap();
+
+        // Asserting the budget of a section which is not active
+        let result = SyscallProfileAssertCuBudget::rust(
+            &mut invoke_context,
+            7,
+            100,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 1);
+
+        for _ in 0..2 {
+            invoke_context.mock_set_remaining(900);
+            let result = SyscallLogComputeUnitsStartId::rust(
+                &mut invoke_context,
+                7,
+                NO_HEAP_SAMPLE,
+                0,
+                0,
+                0,
+                &mut memory_mapping,
+            );
+            assert_eq!(result.unwrap(), 0);
+        }
+
+        // Within budget
+        invoke_context.mock_set_remaining(800);
+        let result = SyscallProfileAssertCuBudget::rust(
+            &mut invoke_context,
+            7,
+            100,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        // Over budget, the section is closed nonetheless
+        invoke_context.mock_set_remaining(650);
+        let result = SyscallProfileAssertCuBudget::rust(
+            &mut invoke_context,
+            7,
+            200,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 2);
+
+        let profiling = invoke_context
+            .get_syscall_context()
+            .unwrap()
+            .profiling
+            .as_ref()
+            .unwrap();
+        assert_eq!(profiling.active_sections(), 0);
+        assert_eq!(
+            profiling
+                .entries()
+                .iter()
+                .map(|entry| entry.consumed())
+                .collect::<Vec<_>>(),
+            vec![100, 250]
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_profile_end_all() {
+        setup_profiling_test!(invoke_context, 1_000);
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(vec![], &config, SBPFVersion::V3).unwrap();
+
+        for (id, remaining) in [(1, 900), (2, 800)] {
+            invoke_context.mock_set_remaining(remaining);
+            let result = SyscallLogComputeUnitsStartId::rust(
+                &mut invoke_context,
+                id,
+                NO_HEAP_SAMPLE,
+                0,
+                0,
+                0,
+                &mut memory_mapping,
+            );
+            assert_eq!(result.unwrap(), 0);
+        }
+
+        invoke_context.mock_set_remaining(500);
+        let result =
+            SyscallProfileEndAll::rust(&mut invoke_context, 32, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), 2);
+        let result = SyscallProfileEndAll::rust(
+            &mut invoke_context,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(invoke_context.get_remaining(), 500);
+
+        let profiling = invoke_context
+            .get_syscall_context()
+            .unwrap()
+            .profiling
+            .as_ref()
+            .unwrap();
+        assert_eq!(profiling.active_sections(), 0);
+        assert_eq!(
+            profiling
+                .entries()
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.consumed(), entry.bulk_closed))
+                .collect::<Vec<_>>(),
+            vec![("#2", 300, true), ("#1", 400, true)]
+        );
+        for entry in profiling.entries() {
+            assert_eq!(entry.heap_end, Some(32));
+        }
+    }
+
+    #[test]
+    fn test_syscall_sol_abort_with_message() {
+        setup_profiling_test!(invoke_context, 1_000);
+        let message = "invalid state";
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(message.as_bytes(), 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        invoke_context.mock_set_remaining(900);
+        let result = SyscallLogComputeUnitsStartId::rust(
+            &mut invoke_context,
+            1,
+            0,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        let cost = invoke_context.get_execution_cost().syscall_base_cost;
+        invoke_context.mock_set_remaining(600 + cost);
+        let result = SyscallAbortWithMessage::rust(
+            &mut invoke_context,
+            7,
+            0x100000000,
+            message.len() as u64,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::Custom(7)
+        );
+        assert_eq!(invoke_context.get_remaining(), 600);
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &["Program aborted with code 7: invalid state".to_string()]
+        );
+
+        // Active sections are reported as bulk closed
+        let profiling = invoke_context
+            .get_syscall_context()
+            .unwrap()
+            .profiling
+            .as_ref()
+            .unwrap();
+        assert_eq!(profiling.active_sections(), 0);
+        assert_eq!(
+            profiling
+                .entries()
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.consumed(), entry.bulk_closed))
+                .collect::<Vec<_>>(),
+            vec![("#1", 300, true)]
+        );
+
+        let result = SyscallAbortWithMessage::rust(
+            &mut invoke_context,
+            u64::from(u32::MAX) + 1,
+            0x100000000,
+            message.len() as u64,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidAbortCode(u64::from(u32::MAX) + 1)
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_profile_mark() {
+        setup_profiling_test!(invoke_context, 1_000);
+
+        let name = "checkpoint";
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(name.as_bytes(), 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        invoke_context.mock_set_remaining(900);
+        let result = SyscallProfileMark::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        let result = SyscallProfileMark::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            128,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        // Markers are free
+        assert_eq!(invoke_context.get_remaining(), 900);
+
+        let result = SyscallProfileMark::rust(
+            &mut invoke_context,
+            0x100000000,
+            MAX_PROFILE_NAME_LEN + 1,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidLength
+        );
+
+        let entries = invoke_context
+            .get_syscall_context()
+            .unwrap()
+            .profiling
+            .as_ref()
+            .unwrap()
+            .entries();
+        assert_eq!(entries.len(), 2);
+        for entry in entries {
+            assert_eq!(entry.kind, ProfileEntryKind::Mark);
+            assert_eq!(entry.name, name);
+            assert_eq!(entry.start_cu, 100);
+            assert_eq!(entry.consumed(), 0);
+        }
+        assert_eq!(entries.first().unwrap().heap_end, None);
+        assert_eq!(entries.last().unwrap().heap_end, Some(128));
+    }
+
+    #[test]
+    fn test_syscall_sol_profile_counter_add() {
+        setup_profiling_test!(invoke_context, 1_000);
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(vec![], &config, SBPFVersion::V3).unwrap();
+
+        for (id, delta) in [(2, 5), (1, 1), (2, 10)] {
+            let result = SyscallProfileCounterAdd::rust(
+                &mut invoke_context,
+                id,
+                delta,
+                0,
+                0,
+                0,
+                &mut memory_mapping,
+            );
+            assert_eq!(result.unwrap(), 0);
+        }
+        assert_eq!(invoke_context.get_remaining(), 1_000);
+        assert_eq!(
+            invoke_context
+                .get_syscall_context()
+                .unwrap()
+                .profiling
+                .as_ref()
+                .unwrap()
+                .counters()
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![(&1, &1), (&2, &15)]
+        );
+    }
+
+    #[test]
+    fn test_profiling_syscalls_feature_gate() {
+        let is_registered = |feature_set: &SVMFeatureSet| {
+            create_program_runtime_environment_v1(
+                feature_set,
+                &SVMTransactionExecutionBudget::default(),
+                false, /* deployment */
+                false, /* debugging_features */
+            )
+            .unwrap()
+            .get_function_registry()
+            .iter()
+            .any(|(_key, (name, _function))| name == b"sol_log_compute_units_start")
+        };
+        assert!(is_registered(&SVMFeatureSet::all_enabled()));
+        assert!(!is_registered(&SVMFeatureSet {
+            enable_profiling_syscalls: false,
+            ..SVMFeatureSet::all_enabled()
+        }));
+    }
+
+    #[test]
+    fn test_syscall_sol_profile_unmetered_does_not_consume() {
+        // Profiling must not change the compute units consumed by the program, whether it is
+        // attached to the instruction or not
+        for profiling in [None, Some(ProfilingState::new(1_000))] {
+            setup_profiling_test!(invoke_context, 1_000);
+            invoke_context.get_syscall_context_mut().unwrap().profiling = profiling;
+            let name = "section";
+            let config = Config::default();
+            let mut memory_mapping = MemoryMapping::new(
+                vec![MemoryRegion::new_readonly(name.as_bytes(), 0x100000000)],
+                &config,
+                SBPFVersion::V3,
+            )
+            .unwrap();
+            let (addr, len) = (0x100000000, name.len() as u64);
+
+            SyscallLogComputeUnitsStart::rust(
+                &mut invoke_context,
+                addr,
+                len,
+                NO_HEAP_SAMPLE,
+                0,
+                0,
+                &mut memory_mapping,
+            )
+            .unwrap();
+            SyscallLogComputeUnitsStartId::rust(
+                &mut invoke_context,
+                1,
+                NO_HEAP_SAMPLE,
+                0,
+                0,
+                0,
+                &mut memory_mapping,
+            )
+            .unwrap();
+            SyscallProfileMark::rust(
+                &mut invoke_context,
+                addr,
+                len,
+                NO_HEAP_SAMPLE,
+                0,
+                0,
+                &mut memory_mapping,
+            )
+            .unwrap();
+            SyscallLogComputeUnitsEndId::rust(
+                &mut invoke_context,
+                1,
+                NO_HEAP_SAMPLE,
+                0,
+                0,
+                0,
+                &mut memory_mapping,
+            )
+            .unwrap();
+            SyscallLogComputeUnitsEnd::rust(
+                &mut invoke_context,
+                addr,
+                len,
+                NO_HEAP_SAMPLE,
+                0,
+                0,
+                &mut memory_mapping,
+            )
+            .unwrap();
+            SyscallProfileEndAll::rust(
+                &mut invoke_context,
+                NO_HEAP_SAMPLE,
+                0,
+                0,
+                0,
+                0,
+                &mut memory_mapping,
+            )
+            .unwrap();
+            // Invalid names fail the same way
+            assert!(SyscallProfileMark::rust(
+                &mut invoke_context,
+                addr,
+                MAX_PROFILE_NAME_LEN + 1,
+                NO_HEAP_SAMPLE,
+                0,
+                0,
+                &mut memory_mapping,
+            )
+            .is_err());
+            assert_eq!(invoke_context.get_remaining(), 1_000);
+        }
+    }
+
+    #[test]
+    fn test_syscall_sol_profile_metered() {
+        setup_profiling_test!(invoke_context, 1_000);
+        invoke_context.get_syscall_context_mut().unwrap().profiling =
+            Some(ProfilingState::new_metered(1_000));
+        let cost = invoke_context.get_execution_cost().syscall_base_cost;
+        let name = "section";
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(name.as_bytes(), 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        let result = SyscallLogComputeUnitsStart::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(invoke_context.get_remaining(), 1_000 - cost);
+
+        // The program consumes 200 compute units between each syscall
+        invoke_context.mock_set_remaining(800 - cost);
+        let result = SyscallProfileMark::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        invoke_context.mock_set_remaining(600 - 2 * cost);
+        let result = SyscallLogComputeUnitsEnd::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(invoke_context.get_remaining(), 600 - 3 * cost);
+
+        // Counters and names are charged like sections and markers
+        let result =
+            SyscallProfileCounterAdd::rust(&mut invoke_context, 1, 1, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), 0);
+        let result = SyscallProfileRegisterName::rust(
+            &mut invoke_context,
+            7,
+            0x100000000,
+            name.len() as u64,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(invoke_context.get_remaining(), 600 - 5 * cost);
+
+        let profiling = invoke_context
+            .get_syscall_context_mut()
+            .unwrap()
+            .profiling
+            .as_mut()
+            .unwrap();
+        profiling.post_process();
+        let bounds: Vec<_> = profiling
+            .entries()
+            .iter()
+            .map(|entry| (entry.kind, entry.start_cu, entry.end_cu))
+            .collect();
+        assert_eq!(
+            bounds,
+            vec![
+                (ProfileEntryKind::Mark, 200, 200),
+                (ProfileEntryKind::Section, 0, 400),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_compute_units_heap_sampling() {
+        setup_profiling_test!(invoke_context, 1_000);
+        let name = "section";
+        // The bump allocator of the program entrypoint claimed the top 100 bytes of the heap
+        let heap_end = ebpf::MM_HEAP_START + solana_program_entrypoint::HEAP_LENGTH as u64;
+        let mut heap =
+            AlignedMemory::<{ HOST_ALIGN }>::zero_filled(solana_program_entrypoint::HEAP_LENGTH);
+        heap.as_slice_mut()[..8].copy_from_slice(&(heap_end - 100).to_le_bytes());
+        let config = Config {
+            aligned_memory_mapping: false,
+            ..Config::default()
+        };
+        let mut memory_mapping = MemoryMapping::new(
+            vec![
+                MemoryRegion::new_readonly(name.as_bytes(), 0x100000000),
+                MemoryRegion::new_writable(heap.as_slice_mut(), ebpf::MM_HEAP_START),
+            ],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        let result = SyscallLogComputeUnitsStart::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        // Allocations made through sol_alloc_free_ are added to the bump allocator usage
+        let result =
+            SyscallAllocFree::rust(&mut invoke_context, 400, 0, 0, 0, 0, &mut memory_mapping);
+        assert_ne!(result.unwrap(), 0);
+        let result = SyscallLogComputeUnitsEnd::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        // Heap values passed by the program take precedence
+        let result = SyscallLogComputeUnitsStartId::rust(
+            &mut invoke_context,
+            1,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        let result = SyscallLogComputeUnitsEndId::rust(
+            &mut invoke_context,
+            1,
+            50,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        let heaps: Vec<_> = invoke_context
+            .get_syscall_context()
+            .unwrap()
+            .profiling
+            .as_ref()
+            .unwrap()
+            .entries()
+            .iter()
+            .map(|entry| (entry.heap_start, entry.heap_end))
+            .collect();
+        assert_eq!(heaps, vec![(Some(100), Some(500)), (Some(500), Some(50))]);
+    }
+
+    #[test]
+    fn test_syscall_sol_profile_enabled() {
+        setup_profiling_test!(invoke_context, 1_000);
+        let name = "section";
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_readonly(name.as_bytes(), 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        let result =
+            SyscallProfileEnabled::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), 1);
+
+        // Without a profiling state the other profiling syscalls do nothing
+        invoke_context.get_syscall_context_mut().unwrap().profiling = None;
+        let result =
+            SyscallProfileEnabled::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), 0);
+        let result = SyscallLogComputeUnitsStart::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        let result = SyscallLogComputeUnitsEndId::rust(
+            &mut invoke_context,
+            7,
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        assert!(invoke_context
+            .get_syscall_context()
+            .unwrap()
+            .profiling
+            .is_none());
+        assert_eq!(invoke_context.get_remaining(), 1_000);
+
+        // Names are still validated
+        let result = SyscallLogComputeUnitsStart::rust(
+            &mut invoke_context,
+            0x100000000,
+            MAX_PROFILE_NAME_LEN.saturating_add(1),
+            NO_HEAP_SAMPLE,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_syscall_sol_memprof_tag() {
+        setup_profiling_test!(invoke_context, 1_000);
+        let name = "accounts";
+        let mut heap =
+            AlignedMemory::<{ HOST_ALIGN }>::zero_filled(solana_program_entrypoint::HEAP_LENGTH);
+        let config = Config {
+            aligned_memory_mapping: false,
+            ..Config::default()
+        };
+        let mut memory_mapping = MemoryMapping::new(
+            vec![
+                MemoryRegion::new_readonly(name.as_bytes(), 0x100000000),
+                MemoryRegion::new_writable(heap.as_slice_mut(), ebpf::MM_HEAP_START),
+            ],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        // No tag is active
+        let result =
+            SyscallMemprofTagEnd::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), 1);
+
+        let result = SyscallMemprofTagBegin::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        let result =
+            SyscallAllocFree::rust(&mut invoke_context, 400, 0, 0, 0, 0, &mut memory_mapping);
+        assert_ne!(result.unwrap(), 0);
+        let result =
+            SyscallMemprofTagEnd::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), 0);
+
+        let result = SyscallMemprofTagBegin::rust(
+            &mut invoke_context,
+            0x100000000,
+            MAX_PROFILE_NAME_LEN.saturating_add(1),
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidLength
+        );
+
+        assert_eq!(
+            invoke_context
+                .get_syscall_context()
+                .unwrap()
+                .profiling
+                .as_ref()
+                .unwrap()
+                .heap_tags(),
+            &BTreeMap::from([(name.to_string(), 400)])
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_profile_heap_checkpoint() {
+        setup_profiling_test!(invoke_context, 1_000);
+        let name = "checkpoint";
+        let mut heap =
+            AlignedMemory::<{ HOST_ALIGN }>::zero_filled(solana_program_entrypoint::HEAP_LENGTH);
+        let config = Config {
+            aligned_memory_mapping: false,
+            ..Config::default()
+        };
+        let mut memory_mapping = MemoryMapping::new(
+            vec![
+                MemoryRegion::new_readonly(name.as_bytes(), 0x100000000),
+                MemoryRegion::new_writable(heap.as_slice_mut(), ebpf::MM_HEAP_START),
+            ],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        let result = SyscallProfileHeapCheckpoint::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        let result =
+            SyscallAllocFree::rust(&mut invoke_context, 400, 0, 0, 0, 0, &mut memory_mapping);
+        assert_ne!(result.unwrap(), 0);
+        let result = SyscallProfileHeapCheckpoint::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+        // The bump allocator of the program entrypoint claimed the top 100 bytes of the heap
+        let heap_end = ebpf::MM_HEAP_START + solana_program_entrypoint::HEAP_LENGTH as u64;
+        *translate_type_mut::<u64>(&memory_mapping, ebpf::MM_HEAP_START, true).unwrap() =
+            heap_end - 100;
+        let result = SyscallProfileHeapCheckpoint::rust(
+            &mut invoke_context,
+            0x100000000,
+            name.len() as u64,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        let profiling = invoke_context
+            .get_syscall_context()
+            .unwrap()
+            .profiling
+            .as_ref()
+            .unwrap();
+        assert_eq!(
+            profiling
+                .entries()
+                .iter()
+                .map(|entry| (
+                    entry.heap_end,
+                    entry
+                        .attributes
+                        .get(HEAP_CHECKPOINT_FREE_ATTRIBUTE)
+                        .map(String::as_str),
+                    entry
+                        .attributes
+                        .get(HEAP_CHECKPOINT_LARGEST_FREE_ATTRIBUTE)
+                        .map(String::as_str),
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                // Neither allocator is in use, the heap could be managed by a custom allocator
+                (Some(0), Some("32768"), None),
+                (Some(400), Some("32368"), Some("32368")),
+                (Some(500), Some("32268"), Some("32268")),
+            ]
         );
     }
 
     #[test]
-    fn test_syscall_sol_log_u64() {
-        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
-        let cost = invoke_context.get_execution_cost().log_64_units;
+    fn test_syscall_sol_profile_heap_checkpoint_requested_frames() {
+        let name = "checkpoint";
+        let default_heap_length = solana_program_entrypoint::HEAP_LENGTH as u64;
+        for heap_size in [64 * 1024, 128 * 1024, 256 * 1024] {
+            setup_profiling_test!(invoke_context, 10_000, heap_size);
+            let mut heap = AlignedMemory::<{ HOST_ALIGN }>::zero_filled(heap_size as usize);
+            let config = Config {
+                aligned_memory_mapping: false,
+                ..Config::default()
+            };
+            let mut memory_mapping = MemoryMapping::new(
+                vec![
+                    MemoryRegion::new_readonly(name.as_bytes(), 0x100000000),
+                    MemoryRegion::new_writable(heap.as_slice_mut(), ebpf::MM_HEAP_START),
+                ],
+                &config,
+                SBPFVersion::V3,
+            )
+            .unwrap();
+            let mut checkpoint = |invoke_context: &mut InvokeContext, bump_pos: Option<u64>| {
+                if let Some(bump_pos) = bump_pos {
+                    *translate_type_mut::<u64>(&memory_mapping, ebpf::MM_HEAP_START, true)
+                        .unwrap() = bump_pos;
+                }
+                let result = SyscallProfileHeapCheckpoint::rust(
+                    invoke_context,
+                    0x100000000,
+                    name.len() as u64,
+                    0,
+                    0,
+                    0,
+                    &mut memory_mapping,
+                );
+                assert_eq!(result.unwrap(), 0);
+            };
 
-        invoke_context.mock_set_remaining(cost);
-        let config = Config::default();
-        let mut memory_mapping = MemoryMapping::new(vec![], &config, SBPFVersion::V3).unwrap();
-        let result = SyscallLogU64::rust(&mut invoke_context, 1, 2, 3, 4, 5, &mut memory_mapping);
-        result.unwrap();
+            checkpoint(&mut invoke_context, None);
+            // The default entrypoint allocator grows down from the default heap length
+            checkpoint(
+                &mut invoke_context,
+                Some(ebpf::MM_HEAP_START + default_heap_length - 100),
+            );
+            // Allocators sized to the requested frame grow down from its end
+            checkpoint(
+                &mut invoke_context,
+                Some(ebpf::MM_HEAP_START + default_heap_length + 1_000),
+            );
 
-        assert_eq!(
-            invoke_context
-                .get_log_collector()
+            let profiling = invoke_context
+                .get_syscall_context()
                 .unwrap()
-                .borrow()
-                .get_recorded_content(),
-            &["Program log: 0x1, 0x2, 0x3, 0x4, 0x5".to_string()]
-        );
+                .profiling
+                .as_ref()
+                .unwrap();
+            let frame_used = heap_size - default_heap_length - 1_000;
+            assert_eq!(
+                profiling
+                    .entries()
+                    .iter()
+                    .map(|entry| (
+                        entry.heap_end,
+                        entry
+                            .attributes
+                            .get(HEAP_CHECKPOINT_FREE_ATTRIBUTE)
+                            .cloned(),
+                    ))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (Some(0), Some(heap_size.to_string())),
+                    (Some(100), Some((heap_size - 100).to_string())),
+                    (Some(frame_used), Some((heap_size - frame_used).to_string())),
+                ],
+                "heap frame of {heap_size} bytes",
+            );
+        }
     }
 
     #[test]
-    fn test_syscall_sol_pubkey() {
-        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
-        let cost = invoke_context.get_execution_cost().log_pubkey_units;
-
-        let pubkey = Pubkey::from_str("MoqiU1vryuCGQSxFKA1SZ316JdLEFFhoAu6cKUNk7dN").unwrap();
+    fn test_syscall_sol_profile_set_attr() {
+        setup_profiling_test!(invoke_context, 1_000);
+        let key = "accounts";
+        let value = "3 writable";
         let config = Config::default();
         let mut memory_mapping = MemoryMapping::new(
-            vec![MemoryRegion::new_readonly(bytes_of(&pubkey), 0x100000000)],
+            vec![
+                MemoryRegion::new_readonly(key.as_bytes(), 0x100000000),
+                MemoryRegion::new_readonly(value.as_bytes(), 0x200000000),
+            ],
             &config,
             SBPFVersion::V3,
         )
         .unwrap();
 
-        let result = SyscallLogPubkey::rust(
+        // No section is active
+        let result = SyscallProfileSetAttr::rust(
             &mut invoke_context,
-            0x100000001, // AccessViolation
-            32,
-            0,
+            0x100000000,
+            key.len() as u64,
+            0x200000000,
+            value.len() as u64,
             0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 1);
+
+        invoke_context
+            .get_syscall_context_mut()
+            .unwrap()
+            .profiling
+            .as_mut()
+            .unwrap()
+            .start_section("section", 1_000, None);
+        let result = SyscallProfileSetAttr::rust(
+            &mut invoke_context,
+            0x100000000,
+            key.len() as u64,
+            0x200000000,
+            value.len() as u64,
             0,
             &mut memory_mapping,
         );
-        assert_access_violation!(result, 0x100000001, 32);
+        assert_eq!(result.unwrap(), 0);
+        let profiling = invoke_context
+            .get_syscall_context_mut()
+            .unwrap()
+            .profiling
+            .as_mut()
+            .unwrap();
+        assert_eq!(profiling.end_all_sections(900, None), 1);
+        assert_eq!(
+            profiling.entries().first().unwrap().attributes,
+            BTreeMap::from([(key.to_string(), value.to_string())])
+        );
 
-        invoke_context.mock_set_remaining(1);
-        let result =
-            SyscallLogPubkey::rust(&mut invoke_context, 100, 32, 0, 0, 0, &mut memory_mapping);
+        // Keys must not contain whitespace
+        let result = SyscallProfileSetAttr::rust(
+            &mut invoke_context,
+            0x200000000,
+            value.len() as u64,
+            0x100000000,
+            key.len() as u64,
+            0,
+            &mut memory_mapping,
+        );
         assert_matches!(
             result,
-            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::ComputationalBudgetExceeded
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidLogKey(value.to_string())
         );
 
-        invoke_context.mock_set_remaining(cost);
-        let result = SyscallLogPubkey::rust(
+        // Lengths are checked even if profiling is disabled
+        invoke_context.get_syscall_context_mut().unwrap().profiling = None;
+        let result = SyscallProfileSetAttr::rust(
             &mut invoke_context,
             0x100000000,
-            0,
-            0,
-            0,
+            key.len() as u64,
+            0x200000000,
+            MAX_PROFILE_ATTRIBUTE_VALUE_LEN.saturating_add(1),
             0,
             &mut memory_mapping,
         );
-        result.unwrap();
-
-        assert_eq!(
-            invoke_context
-                .get_log_collector()
-                .unwrap()
-                .borrow()
-                .get_recorded_content(),
-            &["Program log: MoqiU1vryuCGQSxFKA1SZ316JdLEFFhoAu6cKUNk7dN".to_string()]
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<SyscallError>().unwrap() == &SyscallError::InvalidLength
+        );
+        let result = SyscallProfileSetAttr::rust(
+            &mut invoke_context,
+            0x100000000,
+            key.len() as u64,
+            0x200000000,
+            value.len() as u64,
+            0,
+            &mut memory_mapping,
         );
+        assert_eq!(result.unwrap(), 0);
     }
 
     macro_rules! setup_alloc_test {
@@ -2590,6 +5733,7 @@ mod tests {
                     allocator: BpfAllocator::new(solana_program_entrypoint::HEAP_LENGTH as u64),
                     accounts_metadata: Vec::new(),
                     trace_log: Vec::new(),
+                    profiling: Some(ProfilingState::default()),
                 })
                 .unwrap();
             let config = Config {
@@ -2708,6 +5852,172 @@ mod tests {
         aligned::<u128>();
     }
 
+    #[test]
+    fn test_syscall_sol_remaining_heap() {
+        setup_alloc_test!(invoke_context, memory_mapping, heap);
+        let heap_length = solana_program_entrypoint::HEAP_LENGTH as u64;
+        let syscall_base_cost = invoke_context.get_execution_cost().syscall_base_cost;
+        invoke_context.mock_set_remaining(syscall_base_cost.saturating_mul(2));
+
+        let result =
+            SyscallRemainingHeap::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), heap_length);
+
+        let result =
+            SyscallAllocFree::rust(&mut invoke_context, 100, 0, 0, 0, 0, &mut memory_mapping);
+        assert_ne!(result.unwrap(), 0);
+        let result =
+            SyscallRemainingHeap::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), heap_length.saturating_sub(100));
+
+        // Out of compute units
+        let result =
+            SyscallRemainingHeap::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::ComputationalBudgetExceeded
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_get_heap_size() {
+        setup_alloc_test!(invoke_context, memory_mapping, heap);
+        let heap_length = solana_program_entrypoint::HEAP_LENGTH as u64;
+        let syscall_base_cost = invoke_context.get_execution_cost().syscall_base_cost;
+        invoke_context.mock_set_remaining(syscall_base_cost.saturating_mul(2));
+
+        let result =
+            SyscallAllocFree::rust(&mut invoke_context, 100, 0, 0, 0, 0, &mut memory_mapping);
+        assert_ne!(result.unwrap(), 0);
+
+        // Allocations do not change the size of the heap
+        let result =
+            SyscallGetHeapSize::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), heap_length);
+        let result =
+            SyscallGetHeapSize::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), heap_length);
+
+        // Out of compute units
+        let result =
+            SyscallGetHeapSize::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::ComputationalBudgetExceeded
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_get_compute_budget() {
+        prepare_mockup!(invoke_context, program_id, bpf_loader::id());
+        let execution_cost = invoke_context.get_execution_cost();
+        let cost = execution_cost.syscall_base_cost + execution_cost.mem_op_base_cost;
+        invoke_context.mock_set_remaining(2 * cost);
+        let expected = ComputeBudgetInfo::new(invoke_context.get_compute_budget()).to_bytes();
+        assert_eq!(expected.len(), mem::size_of::<ComputeBudgetInfo>());
+        let config = Config::default();
+
+        // Buffers sized for an earlier version only receive a prefix
+        let mut var = [0xffu8; 64];
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_writable(&mut var, 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+        let result = SyscallGetComputeBudget::rust(
+            &mut invoke_context,
+            0x100000000,
+            8,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), expected.len() as u64);
+        drop(memory_mapping);
+        assert_eq!(var[..8], COMPUTE_BUDGET_INFO_VERSION.to_le_bytes());
+        assert!(var[8..].iter().all(|byte| *byte == 0xff));
+
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_writable(&mut var, 0x100000000)],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+        let result = SyscallGetComputeBudget::rust(
+            &mut invoke_context,
+            0x100000000,
+            64,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), expected.len() as u64);
+        assert_eq!(invoke_context.get_remaining(), 0);
+        drop(memory_mapping);
+        assert_eq!(&var[..expected.len()], expected.as_slice());
+        assert!(var[expected.len()..].iter().all(|byte| *byte == 0xff));
+    }
+
+    #[test]
+    fn test_syscall_sol_log_heap_stats() {
+        setup_alloc_test!(invoke_context, memory_mapping, heap);
+        let heap_length = solana_program_entrypoint::HEAP_LENGTH as u64;
+        let syscall_base_cost = invoke_context.get_execution_cost().syscall_base_cost;
+        invoke_context.mock_set_remaining(syscall_base_cost.saturating_mul(2));
+        invoke_context
+            .get_syscall_context_mut()
+            .unwrap()
+            .profiling
+            .as_mut()
+            .unwrap()
+            .start_section("heap", 0, None);
+
+        let result =
+            SyscallLogHeapStats::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), 0);
+        let result =
+            SyscallAllocFree::rust(&mut invoke_context, 100, 0, 0, 0, 0, &mut memory_mapping);
+        assert_ne!(result.unwrap(), 0);
+        let result =
+            SyscallLogHeapStats::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_eq!(result.unwrap(), 0);
+
+        assert_eq!(
+            invoke_context
+                .get_log_collector()
+                .unwrap()
+                .borrow()
+                .get_recorded_content(),
+            &[
+                format!("Program heap: used 0 free {heap_length} allocations 0"),
+                format!(
+                    "Program heap: used 100 free {} allocations 1",
+                    heap_length - 100
+                ),
+            ]
+        );
+
+        let profiling = invoke_context
+            .get_syscall_context_mut()
+            .unwrap()
+            .profiling
+            .as_mut()
+            .unwrap();
+        assert!(profiling.end_section("heap", 0, None));
+        assert_eq!(profiling.entries().first().unwrap().heap_end, Some(100));
+
+        // Out of compute units
+        let result =
+            SyscallLogHeapStats::rust(&mut invoke_context, 0, 0, 0, 0, 0, &mut memory_mapping);
+        assert_matches!(
+            result,
+            Result::Err(error) if error.downcast_ref::<InstructionError>().unwrap() == &InstructionError::ComputationalBudgetExceeded
+        );
+    }
+
     #[test]
     fn test_syscall_sha256() {
         let config = Config::default();
@@ -4383,6 +7693,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_syscall_sol_get_instruction_counts() {
+        let transaction_accounts = (0..9)
+            .map(|_| {
+                (
+                    Pubkey::new_unique(),
+                    AccountSharedData::new(0, 0, &bpf_loader::id()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let instruction_trace = [1, 2, 3, 2, 1, 2, 3];
+        with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+        for (index_in_trace, stack_height) in instruction_trace.into_iter().enumerate() {
+            while stack_height
+                <= invoke_context
+                    .transaction_context
+                    .get_instruction_stack_height()
+            {
+                invoke_context.transaction_context.pop().unwrap();
+            }
+            let instruction_accounts = vec![InstructionAccount::new(
+                index_in_trace.saturating_add(1) as IndexOfAccount,
+                false,
+                false,
+            )];
+            invoke_context
+                .transaction_context
+                .configure_next_instruction_for_tests(0, instruction_accounts, &[])
+                .unwrap();
+            invoke_context.transaction_context.push().unwrap();
+        }
+
+        let syscall_base_cost = invoke_context.get_execution_cost().syscall_base_cost;
+        let mut counts = InstructionCounts::default();
+        let config = Config::default();
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_writable(
+                bytes_of_mut(&mut counts),
+                0x100000000,
+            )],
+            &config,
+            SBPFVersion::V3,
+        )
+        .unwrap();
+
+        invoke_context.mock_set_remaining(syscall_base_cost);
+        let result = SyscallGetInstructionCounts::rust(
+            &mut invoke_context,
+            0x100000000,
+            0,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        invoke_context.mock_set_remaining(syscall_base_cost);
+        let result = SyscallGetInstructionCounts::rust(
+            &mut invoke_context,
+            0x100000001,
+            0,
+            0,
+            0,
+            0,
+            &mut memory_mapping,
+        );
+        assert_access_violation!(result, 0x100000001, size_of::<InstructionCounts>() as u64);
+
+        drop(memory_mapping);
+        assert_eq!(
+            counts,
+            InstructionCounts {
+                top_level: 2,
+                cpi: 5,
+            }
+        );
+    }
+
     #[test]
     fn test_syscall_sol_get_processed_sibling_instruction() {
         let transaction_accounts = (0..9)
@@ -2,6 +2,9 @@ use {super::*, solana_sbpf::vm::ContextObject};
 
 declare_builtin_function!(
     /// Log a user's info message
+    ///
+    /// The message is translated even if it is not recorded, so that the outcome does not depend
+    /// on whether logs are collected. Only formatting it is skipped.
     SyscallLog,
     fn rust(
         invoke_context: &mut InvokeContext,
@@ -32,6 +35,42 @@ declare_builtin_function!(
     }
 );
 
+declare_builtin_function!(
+    /// Log a user's message with a severity level
+    ///
+    /// Charges like `sol_log` whether or not the message is filtered out by the log collector's
+    /// minimum level.
+    SyscallLogLevel,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        level: u64,
+        addr: u64,
+        len: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let cost = invoke_context
+            .get_execution_cost()
+            .syscall_base_cost
+            .max(len);
+        consume_compute_meter(invoke_context, cost)?;
+
+        let level = LogLevel::from_u64(level).ok_or(SyscallError::InvalidLogLevel(level))?;
+        translate_string_and_do(
+            memory_mapping,
+            addr,
+            len,
+            invoke_context.get_check_aligned(),
+            &mut |string: &str| {
+                stable_log::program_log_level(&invoke_context.get_log_collector(), level, string);
+                Ok(0)
+            },
+        )?;
+        Ok(0)
+    }
+);
+
 declare_builtin_function!(
     /// Log 5 64-bit values
     SyscallLogU64,
@@ -55,6 +94,95 @@ declare_builtin_function!(
     }
 );
 
+declare_builtin_function!(
+    /// Log 5 64-bit values in decimal
+    ///
+    /// Charges like `sol_log_64_`.
+    SyscallLogU64Dec,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        arg1: u64,
+        arg2: u64,
+        arg3: u64,
+        arg4: u64,
+        arg5: u64,
+        _memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let cost = invoke_context.get_execution_cost().log_64_units;
+        consume_compute_meter(invoke_context, cost)?;
+
+        stable_log::program_log(
+            &invoke_context.get_log_collector(),
+            &format!("{arg1}, {arg2}, {arg3}, {arg4}, {arg5}"),
+        );
+        Ok(0)
+    }
+);
+
+declare_builtin_function!(
+    /// Log 5 64-bit values as signed integers
+    ///
+    /// Charges like `sol_log_64_`.
+    SyscallLogI64,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        arg1: u64,
+        arg2: u64,
+        arg3: u64,
+        arg4: u64,
+        arg5: u64,
+        _memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let cost = invoke_context.get_execution_cost().log_64_units;
+        consume_compute_meter(invoke_context, cost)?;
+
+        let [arg1, arg2, arg3, arg4, arg5] = [arg1, arg2, arg3, arg4, arg5].map(|arg| arg as i64);
+        stable_log::program_log(
+            &invoke_context.get_log_collector(),
+            &format!("{arg1}, {arg2}, {arg3}, {arg4}, {arg5}"),
+        );
+        Ok(0)
+    }
+);
+
+/// Format a fixed-point amount as `value / 10^decimals`, keeping all fractional digits
+fn format_amount(value: u64, decimals: u8) -> String {
+    let decimals = usize::from(decimals);
+    if decimals == 0 {
+        return value.to_string();
+    }
+    // Left-pad with zeros so that the integer part is at least `0`
+    let digits = format!("{value:0>width$}", width = decimals.saturating_add(1));
+    let (integer, fraction) = digits.split_at(digits.len().saturating_sub(decimals));
+    format!("{integer}.{fraction}")
+}
+
+declare_builtin_function!(
+    /// Log a fixed-point amount, such as a token amount, as `value / 10^decimals`
+    ///
+    /// Charges like `sol_log_64_` whatever the number of decimals.
+    SyscallLogAmount,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        value: u64,
+        decimals: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let cost = invoke_context.get_execution_cost().log_64_units;
+        consume_compute_meter(invoke_context, cost)?;
+
+        let decimals = u8::try_from(decimals).map_err(|_| SyscallError::InvalidLength)?;
+        stable_log::program_log(
+            &invoke_context.get_log_collector(),
+            &format_amount(value, decimals),
+        );
+        Ok(0)
+    }
+);
+
 declare_builtin_function!(
     /// Log current compute consumption
     SyscallLogBpfComputeUnits,
@@ -79,6 +207,45 @@ declare_builtin_function!(
     }
 );
 
+declare_builtin_function!(
+    /// Log current compute consumption along with a label identifying the code location
+    ///
+    /// Charges like `sol_log` for the label.
+    SyscallLogBpfComputeUnitsAt,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        addr: u64,
+        len: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let cost = invoke_context
+            .get_execution_cost()
+            .syscall_base_cost
+            .max(len);
+        consume_compute_meter(invoke_context, cost)?;
+
+        translate_string_and_do(
+            memory_mapping,
+            addr,
+            len,
+            invoke_context.get_check_aligned(),
+            &mut |label: &str| {
+                ic_logger_msg!(
+                    invoke_context.get_log_collector(),
+                    "Program consumption at {}: {} units remaining",
+                    label,
+                    invoke_context.get_remaining(),
+                );
+                Ok(0)
+            },
+        )?;
+        Ok(0)
+    }
+);
+
 declare_builtin_function!(
     /// Log a [`Pubkey`] as a base58 string
     SyscallLogPubkey,
@@ -104,6 +271,46 @@ declare_builtin_function!(
     }
 );
 
+declare_builtin_function!(
+    /// Log the key, owner, lamports and data length of an instruction account
+    ///
+    /// Costs as much as logging its key and owner with `sol_log_pubkey`, regardless of the size of
+    /// the account. The lamports and data length are those last seen by the runtime, which does
+    /// not observe changes made by the program until it returns or invokes another program.
+    SyscallLogAccount,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        index_in_instruction: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let cost = invoke_context
+            .get_execution_cost()
+            .log_pubkey_units
+            .saturating_mul(2);
+        consume_compute_meter(invoke_context, cost)?;
+
+        let index_in_instruction = IndexOfAccount::try_from(index_in_instruction)
+            .map_err(|_| InstructionError::NotEnoughAccountKeys)?;
+        let instruction_context = invoke_context
+            .transaction_context
+            .get_current_instruction_context()?;
+        let account = instruction_context.try_borrow_instruction_account(index_in_instruction)?;
+        stable_log::program_account(
+            &invoke_context.get_log_collector(),
+            index_in_instruction,
+            account.get_key(),
+            account.get_owner(),
+            account.get_lamports(),
+            account.get_data().len(),
+        );
+        Ok(0)
+    }
+);
+
 declare_builtin_function!(
     /// Log data handling
     SyscallLogData,
@@ -140,16 +347,908 @@ declare_builtin_function!(
                 .fold(0, |total, e| total.saturating_add(e.len())),
         )?;
 
-        let mut fields = Vec::with_capacity(untranslated_fields.len());
+        let check_aligned = invoke_context.get_check_aligned();
+        stable_log::program_data_streamed(
+            &invoke_context.get_log_collector(),
+            untranslated_fields
+                .iter()
+                .map(|untranslated_field| untranslated_field.translate(memory_mapping, check_aligned)),
+        )?;
+
+        Ok(0)
+    }
+);
+
+/// Maximum number of arguments substituted by `sol_log_fmt`
+pub const MAX_LOG_FMT_ARGS: u64 = 5;
+
+/// Substitute `args` for the placeholders of a `sol_log_fmt` template
+///
+/// `{}` formats the next argument in decimal and `{:x}` in hexadecimal, `{{` and `}}` escape
+/// braces. Returns `None` if the template is malformed or the number of placeholders does not
+/// match the number of arguments.
+fn format_log_template(template: &str, args: &[u64]) -> Option<String> {
+    let mut message = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(index) = rest.find(['{', '}']) {
+        let (literal, placeholder) = rest.split_at(index);
+        message.push_str(literal);
+        rest = if let Some(tail) = placeholder.strip_prefix("{{") {
+            message.push('{');
+            tail
+        } else if let Some(tail) = placeholder.strip_prefix("}}") {
+            message.push('}');
+            tail
+        } else if let Some(tail) = placeholder.strip_prefix("{}") {
+            message.push_str(&args.next()?.to_string());
+            tail
+        } else if let Some(tail) = placeholder.strip_prefix("{:x}") {
+            message.push_str(&format!("{:x}", args.next()?));
+            tail
+        } else {
+            return None;
+        };
+    }
+    message.push_str(rest);
+    args.next().is_none().then_some(message)
+}
+
+declare_builtin_function!(
+    /// Log a message formatted from a template and up to `MAX_LOG_FMT_ARGS` integers
+    ///
+    /// See `format_log_template` for the template syntax. Costs as much as logging the template
+    /// with `sol_log_`, the arguments are formatted for free.
+    SyscallLogFmt,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        template_addr: u64,
+        template_len: u64,
+        args_addr: u64,
+        args_len: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let cost = invoke_context
+            .get_execution_cost()
+            .syscall_base_cost
+            .max(template_len);
+        consume_compute_meter(invoke_context, cost)?;
 
-        for untranslated_field in untranslated_fields {
-            fields.push(untranslated_field.translate(memory_mapping, invoke_context.get_check_aligned())?);
+        if args_len > MAX_LOG_FMT_ARGS {
+            return Err(SyscallError::InvalidLength.into());
         }
+        let check_aligned = invoke_context.get_check_aligned();
+        let args = translate_slice::<u64>(memory_mapping, args_addr, args_len, check_aligned)?;
+        translate_string_and_do(
+            memory_mapping,
+            template_addr,
+            template_len,
+            check_aligned,
+            &mut |template: &str| {
+                let message = format_log_template(template, args)
+                    .ok_or_else(|| SyscallError::InvalidLogFormat(template.to_string()))?;
+                stable_log::program_log(&invoke_context.get_log_collector(), &message);
+                Ok(0)
+            },
+        )
+    }
+);
+
+/// Maximum number of fields in a `sol_log_kv` record
+pub const MAX_LOG_KV_FIELDS: u64 = 32;
+
+declare_builtin_function!(
+    /// Log a structured key/value record
+    ///
+    /// Takes two slices of the same length holding the UTF-8 keys and values. Charges like
+    /// `sol_log_data`, counting both keys and values.
+    SyscallLogKv,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        keys_addr: u64,
+        values_addr: u64,
+        len: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let execution_cost = invoke_context.get_execution_cost();
+
+        consume_compute_meter(invoke_context, execution_cost.syscall_base_cost)?;
+
+        if len > MAX_LOG_KV_FIELDS {
+            return Err(SyscallError::TooManySlices.into());
+        }
+        let check_aligned = invoke_context.get_check_aligned();
+        let untranslated_keys =
+            translate_slice::<VmSlice<u8>>(memory_mapping, keys_addr, len, check_aligned)?;
+        let untranslated_values =
+            translate_slice::<VmSlice<u8>>(memory_mapping, values_addr, len, check_aligned)?;
+
+        consume_compute_meter(
+            invoke_context,
+            execution_cost.syscall_base_cost.saturating_mul(len),
+        )?;
+        consume_compute_meter(
+            invoke_context,
+            untranslated_keys
+                .iter()
+                .chain(untranslated_values)
+                .fold(0, |total, e| total.saturating_add(e.len())),
+        )?;
+
+        let mut fields = Vec::with_capacity(untranslated_keys.len());
+        for (untranslated_key, untranslated_value) in
+            untranslated_keys.iter().zip(untranslated_values)
+        {
+            let key = untranslated_key.translate(memory_mapping, check_aligned)?;
+            let key = from_utf8(key)
+                .map_err(|err| SyscallError::InvalidString(err, key.to_vec()))?;
+            if key.is_empty() || key.contains(|c: char| c.is_whitespace() || c == '=') {
+                return Err(SyscallError::InvalidLogKey(key.to_string()).into());
+            }
+            let value = untranslated_value.translate(memory_mapping, check_aligned)?;
+            let value = from_utf8(value)
+                .map_err(|err| SyscallError::InvalidString(err, value.to_vec()))?;
+            fields.push((key, value));
+        }
+
+        stable_log::program_kv(&invoke_context.get_log_collector(), &fields);
+
+        Ok(0)
+    }
+);
+
+/// Maximum length in bytes of a `sol_log_event` event name
+pub const MAX_LOG_EVENT_NAME_LEN: u64 = 64;
+
+declare_builtin_function!(
+    /// Log data slices tagged with an event name
+    ///
+    /// Takes the UTF-8 event name followed by the data slices as passed to `sol_log_data`, and
+    /// charges like `sol_log_data` plus one compute unit per byte of the name. The name is
+    /// recorded as a structured field of the log record.
+    SyscallLogEvent,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        name_addr: u64,
+        name_len: u64,
+        addr: u64,
+        len: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let execution_cost = invoke_context.get_execution_cost();
+
+        consume_compute_meter(
+            invoke_context,
+            execution_cost.syscall_base_cost.saturating_add(name_len),
+        )?;
+
+        if name_len > MAX_LOG_EVENT_NAME_LEN {
+            return Err(SyscallError::InvalidLength.into());
+        }
+        let check_aligned = invoke_context.get_check_aligned();
+        let name = translate_slice::<u8>(memory_mapping, name_addr, name_len, check_aligned)?;
+        let name = from_utf8(name).map_err(|err| SyscallError::InvalidString(err, name.to_vec()))?;
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(SyscallError::InvalidEventName(name.to_string()).into());
+        }
+
+        let untranslated_fields =
+            translate_slice::<VmSlice<u8>>(memory_mapping, addr, len, check_aligned)?;
+
+        consume_compute_meter(
+            invoke_context,
+            execution_cost
+                .syscall_base_cost
+                .saturating_mul(untranslated_fields.len() as u64),
+        )?;
+        consume_compute_meter(
+            invoke_context,
+            untranslated_fields
+                .iter()
+                .fold(0, |total, e| total.saturating_add(e.len())),
+        )?;
+
+        let fields = untranslated_fields
+            .iter()
+            .map(|untranslated_field| untranslated_field.translate(memory_mapping, check_aligned))
+            .collect::<Result<Vec<_>, _>>()?;
+        stable_log::program_event(&invoke_context.get_log_collector(), name, &fields);
+
+        Ok(0)
+    }
+);
+
+/// Bytes per line used by `sol_log_hex` when the program passes 0
+pub const DEFAULT_LOG_HEX_BYTES_PER_LINE: u64 = 16;
+
+/// Maximum number of bytes per line accepted by `sol_log_hex`
+pub const MAX_LOG_HEX_BYTES_PER_LINE: u64 = 64;
+
+declare_builtin_function!(
+    /// Log a hexdump of a memory region
+    ///
+    /// Charges `syscall_base_cost` plus one compute unit per dumped byte. A `bytes_per_line` of 0
+    /// selects [`DEFAULT_LOG_HEX_BYTES_PER_LINE`].
+    SyscallLogHex,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        addr: u64,
+        len: u64,
+        bytes_per_line: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let cost = invoke_context
+            .get_execution_cost()
+            .syscall_base_cost
+            .saturating_add(len);
+        consume_compute_meter(invoke_context, cost)?;
+
+        let bytes_per_line = match bytes_per_line {
+            0 => DEFAULT_LOG_HEX_BYTES_PER_LINE,
+            1..=MAX_LOG_HEX_BYTES_PER_LINE => bytes_per_line,
+            _ => return Err(SyscallError::InvalidLength.into()),
+        };
+        let data = translate_slice::<u8>(
+            memory_mapping,
+            addr,
+            len,
+            invoke_context.get_check_aligned(),
+        )?;
+        stable_log::program_log_hex(
+            &invoke_context.get_log_collector(),
+            data,
+            bytes_per_line as usize,
+        );
+        Ok(0)
+    }
+);
+
+/// Charge `syscall_base_cost` for a profiling syscall which records something if the profiling
+/// of the current instruction is metered
+///
+/// Metered profiling is only enabled by `agave-ledger-tool program run`, see
+/// [`InvokeContext::enable_metered_profiling`].
+fn consume_profile_instrumentation_cost(invoke_context: &mut InvokeContext) -> Result<(), Error> {
+    let cost = invoke_context.get_execution_cost().syscall_base_cost;
+    let Some(profiling) = invoke_context
+        .get_syscall_context_mut()?
+        .profiling
+        .as_mut()
+        .filter(|profiling| profiling.is_metered())
+    else {
+        return Ok(());
+    };
+    profiling.charge_instrumentation(cost);
+    consume_compute_meter(invoke_context, cost)
+}
+
+/// Translate a profiling section or marker name and pass it to `work` together with the
+/// profiling state of the current instruction
+///
+/// The name is validated even if profiling is disabled, so that the outcome of the syscall does
+/// not depend on it, but `work` is skipped and 0 is returned.
+fn translate_profile_name_and_do(
+    invoke_context: &mut InvokeContext,
+    memory_mapping: &MemoryMapping,
+    addr: u64,
+    len: u64,
+    work: &mut dyn FnMut(&mut ProfilingState, &str) -> u64,
+) -> Result<u64, Error> {
+    if len > MAX_PROFILE_NAME_LEN {
+        return Err(SyscallError::InvalidLength.into());
+    }
+    let check_aligned = invoke_context.get_check_aligned();
+    let profiling = &mut invoke_context.get_syscall_context_mut()?.profiling;
+    translate_string_and_do(
+        memory_mapping,
+        addr,
+        len,
+        check_aligned,
+        &mut |name: &str| {
+            Ok(profiling
+                .as_mut()
+                .map_or(0, |profiling| work(profiling, name)))
+        },
+    )
+}
+
+/// Profiling state recording the innermost active section which `owner` finds, along with
+/// whether it is the state of the current instruction
+///
+/// The section may have been opened by another instruction, see
+/// `InvokeContext::get_profiling_state_mut`. Returns `None` if no such section is active or
+/// profiling is disabled.
+fn section_owner_mut<'a>(
+    invoke_context: &'a mut InvokeContext,
+    owner: impl FnOnce(&ProfilingState) -> Option<usize>,
+) -> Result<Option<(&'a mut ProfilingState, bool)>, Error> {
+    let Some(profiling) = &invoke_context.get_syscall_context()?.profiling else {
+        return Ok(None);
+    };
+    let current = profiling.stack_height();
+    let Some(stack_height) = owner(profiling) else {
+        return Ok(None);
+    };
+    if stack_height == current {
+        let profiling = invoke_context.get_syscall_context_mut()?.profiling.as_mut();
+        return Ok(profiling.map(|profiling| (profiling, true)));
+    }
+    Ok(invoke_context
+        .get_profiling_state_mut(stack_height)
+        .map(|profiling| (profiling, false)))
+}
+
+/// Close the innermost active section which `owner` finds with `end`, which is passed the heap
+/// sample
+///
+/// The heap sample is only passed for sections of the current instruction, as the callers use
+/// separate heaps. Returns 1 if no such section is active, and 0 if profiling is disabled.
+fn end_spanning_section(
+    invoke_context: &mut InvokeContext,
+    heap: Option<u64>,
+    owner: impl FnOnce(&ProfilingState) -> Option<usize>,
+    end: impl FnOnce(&mut ProfilingState, Option<u64>) -> bool,
+) -> Result<u64, Error> {
+    if invoke_context.get_syscall_context()?.profiling.is_none() {
+        return Ok(0);
+    }
+    let Some((profiling, is_current)) = section_owner_mut(invoke_context, owner)? else {
+        return Ok(1);
+    };
+    let closed = end(profiling, heap.filter(|_| is_current));
+    Ok(u64::from(!closed))
+}
+
+/// Convert the heap argument of a profiling section syscall into a heap sample
+///
+/// Programs pass `NO_HEAP_SAMPLE` to let the runtime sample the heap usage itself, see
+/// `heap_used`. Nothing is sampled if profiling is disabled.
+fn section_heap_sample(
+    invoke_context: &InvokeContext,
+    memory_mapping: &MemoryMapping,
+    heap: u64,
+) -> Result<Option<u64>, Error> {
+    if heap != NO_HEAP_SAMPLE || invoke_context.get_syscall_context()?.profiling.is_none() {
+        return Ok(ProfilingState::heap_sample(heap));
+    }
+    let (used, _in_use) = heap_used(invoke_context, memory_mapping)?;
+    Ok(Some(used))
+}
 
-        let log_collector = invoke_context.get_log_collector();
+/// Bytes used on the heap, as the sum of the bytes handed out by `sol_alloc_free_` from the start
+/// of the heap and the bytes claimed by the bump allocator of the program entrypoint from its end
+///
+/// Also returns whether either of them is in use, the usage of custom allocators is not visible
+/// to the runtime.
+fn heap_used(
+    invoke_context: &InvokeContext,
+    memory_mapping: &MemoryMapping,
+) -> Result<(u64, bool), Error> {
+    let allocator = &invoke_context.get_syscall_context()?.allocator;
+    let allocator_used = allocator.used();
+    let bump_allocator_used = bump_allocator_used(invoke_context, memory_mapping, allocator.size());
+    Ok((
+        allocator_used.saturating_add(bump_allocator_used.unwrap_or(0)),
+        allocator_used > 0 || bump_allocator_used.is_some(),
+    ))
+}
 
-        stable_log::program_data(&log_collector, &fields);
+/// Bytes claimed by the bump allocator of the program entrypoint, which grows down from the end
+/// of its region and stores its position at the start of the heap
+///
+/// The default entrypoint allocator only manages the first `HEAP_LENGTH` bytes, even if the
+/// transaction requested a larger heap frame, while allocators sized to the requested frame grow
+/// down from its end. A position within the default length is attributed to the former.
+///
+/// Returns `None` if the stored position is outside of the heap region, i.e. the bump allocator
+/// was not used yet or the program brings its own allocator.
+fn bump_allocator_used(
+    invoke_context: &InvokeContext,
+    memory_mapping: &MemoryMapping,
+    heap_size: u64,
+) -> Option<u64> {
+    let heap_end = MM_HEAP_START.saturating_add(heap_size);
+    let default_heap_end =
+        MM_HEAP_START.saturating_add(heap_size.min(solana_program_entrypoint::HEAP_LENGTH as u64));
+    translate_type::<u64>(
+        memory_mapping,
+        MM_HEAP_START,
+        invoke_context.get_check_aligned(),
+    )
+    .ok()
+    .filter(|pos| (MM_HEAP_START..=heap_end).contains(*pos))
+    .map(|pos| {
+        let end = if *pos <= default_heap_end {
+            default_heap_end
+        } else {
+            heap_end
+        };
+        end.saturating_sub(*pos)
+    })
+}
 
+declare_builtin_function!(
+    /// Open a named profiling section
+    ///
+    /// The heap usage is sampled by the runtime if `heap` is `NO_HEAP_SAMPLE`.
+    SyscallLogComputeUnitsStart,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        addr: u64,
+        len: u64,
+        heap: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        let remaining = invoke_context.get_remaining();
+        let heap = section_heap_sample(invoke_context, memory_mapping, heap)?;
+        translate_profile_name_and_do(
+            invoke_context,
+            memory_mapping,
+            addr,
+            len,
+            &mut |profiling, name| {
+                profiling.start_section(name, remaining, heap);
+                0
+            },
+        )
+    }
+);
+
+declare_builtin_function!(
+    /// Close the innermost profiling section with the given name
+    ///
+    /// The section may have been opened by another instruction. The heap usage is sampled by
+    /// the runtime if `heap` is `NO_HEAP_SAMPLE`. Returns 1 if no such section is active.
+    SyscallLogComputeUnitsEnd,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        addr: u64,
+        len: u64,
+        heap: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        if len > MAX_PROFILE_NAME_LEN {
+            return Err(SyscallError::InvalidLength.into());
+        }
+        let remaining = invoke_context.get_remaining();
+        let heap = section_heap_sample(invoke_context, memory_mapping, heap)?;
+        let check_aligned = invoke_context.get_check_aligned();
+        translate_string_and_do(
+            memory_mapping,
+            addr,
+            len,
+            check_aligned,
+            &mut |name: &str| {
+                end_spanning_section(
+                    invoke_context,
+                    heap,
+                    |profiling| profiling.section_owner(name),
+                    |profiling, heap| profiling.end_section(name, remaining, heap),
+                )
+            },
+        )
+    }
+);
+
+declare_builtin_function!(
+    /// Open a profiling section identified by a program-chosen id
+    ///
+    /// The heap usage is sampled by the runtime if `heap` is `NO_HEAP_SAMPLE`.
+    SyscallLogComputeUnitsStartId,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        id: u64,
+        heap: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        let remaining = invoke_context.get_remaining();
+        let heap = section_heap_sample(invoke_context, memory_mapping, heap)?;
+        if let Some(profiling) = &mut invoke_context.get_syscall_context_mut()?.profiling {
+            profiling.start_section_id(id, remaining, heap);
+        }
         Ok(0)
     }
 );
+
+declare_builtin_function!(
+    /// Close the innermost profiling section with the given id
+    ///
+    /// The section may have been opened by another instruction. The heap usage is sampled by
+    /// the runtime if `heap` is `NO_HEAP_SAMPLE`. Returns 1 if no such section is active.
+    SyscallLogComputeUnitsEndId,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        id: u64,
+        heap: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        let remaining = invoke_context.get_remaining();
+        let heap = section_heap_sample(invoke_context, memory_mapping, heap)?;
+        end_spanning_section(
+            invoke_context,
+            heap,
+            |profiling| profiling.section_owner_id(id),
+            |profiling, heap| profiling.end_section_id(id, remaining, heap),
+        )
+    }
+);
+
+declare_builtin_function!(
+    /// Read the compute units consumed so far by the innermost active profiling section with the
+    /// given id
+    ///
+    /// The section may have been opened by another instruction. Returns `u64::MAX` if no such
+    /// section is active or profiling is disabled.
+    ///
+    /// Like [`SyscallProfileEnabled`], this deliberately lets a program observe whether it is
+    /// profiled. Programs must not let the result change their outcome.
+    SyscallProfileSectionCu,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        id: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let remaining = invoke_context.get_remaining();
+        Ok(invoke_context
+            .get_syscall_context()?
+            .profiling
+            .as_ref()
+            .and_then(|profiling| profiling.section_consumed_id(id, remaining))
+            .unwrap_or(u64::MAX))
+    }
+);
+
+declare_builtin_function!(
+    /// Close the innermost profiling section with the given id and check that it consumed at most
+    /// `max_cu` compute units
+    ///
+    /// The section may have been opened by another instruction. Returns 0 if the section stayed
+    /// within its budget or profiling is disabled, 1 if no such section is active and 2 if the
+    /// section exceeded its budget. The syscall never fails on an exceeded budget, so that the
+    /// outcome of a transaction does not depend on whether it is profiled.
+    SyscallProfileAssertCuBudget,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        id: u64,
+        max_cu: u64,
+        heap: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        let remaining = invoke_context.get_remaining();
+        if invoke_context.get_syscall_context()?.profiling.is_none() {
+            return Ok(0);
+        }
+        let Some((profiling, is_current)) =
+            section_owner_mut(invoke_context, |profiling| profiling.section_owner_id(id))?
+        else {
+            return Ok(1);
+        };
+        let Some(consumed) = profiling.section_consumed_id(id, remaining) else {
+            return Ok(1);
+        };
+        let heap = ProfilingState::heap_sample(heap).filter(|_| is_current);
+        profiling.end_section_id(id, remaining, heap);
+        Ok(if consumed > max_cu { 2 } else { 0 })
+    }
+);
+
+declare_builtin_function!(
+    /// Name the profiling sections opened with the given id
+    ///
+    /// Returns 1 if the name could not be registered because the name limit was reached.
+    SyscallProfileRegisterName,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        id: u64,
+        addr: u64,
+        len: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        translate_profile_name_and_do(
+            invoke_context,
+            memory_mapping,
+            addr,
+            len,
+            &mut |profiling, name| u64::from(!profiling.register_name(id, name)),
+        )
+    }
+);
+
+declare_builtin_function!(
+    /// Close all active profiling sections, innermost first
+    ///
+    /// Returns the number of sections which were closed.
+    SyscallProfileEndAll,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        heap: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        let remaining = invoke_context.get_remaining();
+        let Some(profiling) = &mut invoke_context.get_syscall_context_mut()?.profiling else {
+            return Ok(0);
+        };
+        let closed = profiling.end_all_sections(remaining, ProfilingState::heap_sample(heap));
+        Ok(closed as u64)
+    }
+);
+
+declare_builtin_function!(
+    /// Abort execution with an error code and a message
+    ///
+    /// The message is logged and the instruction fails with `InstructionError::Custom(code)`, so
+    /// programs get rich context without formatting a panic string. Profiling sections which are
+    /// still active are closed first, so that they are reported as bulk closed entries.
+    SyscallAbortWithMessage,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        code: u64,
+        addr: u64,
+        len: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let cost = invoke_context
+            .get_execution_cost()
+            .syscall_base_cost
+            .max(len);
+        consume_compute_meter(invoke_context, cost)?;
+        let code = u32::try_from(code).map_err(|_| SyscallError::InvalidAbortCode(code))?;
+
+        let remaining = invoke_context.get_remaining();
+        let heap = section_heap_sample(invoke_context, memory_mapping, NO_HEAP_SAMPLE)?;
+        if let Some(profiling) = &mut invoke_context.get_syscall_context_mut()?.profiling {
+            profiling.end_all_sections(remaining, heap);
+        }
+
+        translate_string_and_do(
+            memory_mapping,
+            addr,
+            len,
+            invoke_context.get_check_aligned(),
+            &mut |message: &str| {
+                stable_log::program_abort(&invoke_context.get_log_collector(), code, message);
+                Err(InstructionError::Custom(code).into())
+            },
+        )
+    }
+);
+
+declare_builtin_function!(
+    /// Record a zero-length profiling marker
+    SyscallProfileMark,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        addr: u64,
+        len: u64,
+        heap: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        let remaining = invoke_context.get_remaining();
+        translate_profile_name_and_do(
+            invoke_context,
+            memory_mapping,
+            addr,
+            len,
+            &mut |profiling, name| {
+                profiling.mark(name, remaining, ProfilingState::heap_sample(heap));
+                0
+            },
+        )
+    }
+);
+
+declare_builtin_function!(
+    /// Record a profiling marker with a snapshot of the allocator state
+    ///
+    /// The used bytes are sampled as for profiling sections, see `heap_used`. Both allocators
+    /// leave the remaining free bytes contiguous, the largest free block is therefore only
+    /// reported when either of them is in use: the free lists of custom allocators are not
+    /// visible to the runtime.
+    SyscallProfileHeapCheckpoint,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        addr: u64,
+        len: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        let remaining = invoke_context.get_remaining();
+        let heap_size = invoke_context.get_syscall_context()?.allocator.size();
+        let (used, in_use) = heap_used(invoke_context, memory_mapping)?;
+        let free = heap_size.saturating_sub(used);
+        let checkpoint = HeapCheckpoint {
+            used,
+            free,
+            largest_free: in_use.then_some(free),
+        };
+        translate_profile_name_and_do(
+            invoke_context,
+            memory_mapping,
+            addr,
+            len,
+            &mut |profiling, name| {
+                profiling.heap_checkpoint(name, remaining, checkpoint);
+                0
+            },
+        )
+    }
+);
+
+declare_builtin_function!(
+    /// Add a delta to a profiling counter
+    ///
+    /// Returns 1 if the counter could not be created because the counter limit was reached.
+    SyscallProfileCounterAdd,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        id: u64,
+        delta: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        let Some(profiling) = &mut invoke_context.get_syscall_context_mut()?.profiling else {
+            return Ok(0);
+        };
+        Ok(u64::from(!profiling.counter_add(id, delta)))
+    }
+);
+
+declare_builtin_function!(
+    /// Query whether the current instruction records a profile
+    ///
+    /// Returns 1 if profiling is enabled and 0 otherwise, in which case the other profiling
+    /// syscalls have no effect. Programs can use this to skip building section names.
+    ///
+    /// This is a deliberate exception to profiling being transparent to programs: it is the
+    /// program's responsibility to only use the result to skip profiling work, and not to change
+    /// its outcome.
+    SyscallProfileEnabled,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        let profiling = &invoke_context.get_syscall_context()?.profiling;
+        Ok(u64::from(profiling.is_some()))
+    }
+);
+
+declare_builtin_function!(
+    /// Attach a key/value attribute to the innermost active profiling section
+    ///
+    /// Setting a key again overwrites its value. Returns 1 if no section is active or the
+    /// attribute could not be added because the section reached the attribute limit.
+    SyscallProfileSetAttr,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        key_addr: u64,
+        key_len: u64,
+        value_addr: u64,
+        value_len: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        if key_len > MAX_PROFILE_NAME_LEN || value_len > MAX_PROFILE_ATTRIBUTE_VALUE_LEN {
+            return Err(SyscallError::InvalidLength.into());
+        }
+        let check_aligned = invoke_context.get_check_aligned();
+        let key = translate_slice::<u8>(memory_mapping, key_addr, key_len, check_aligned)?;
+        let key = from_utf8(key).map_err(|err| SyscallError::InvalidString(err, key.to_vec()))?;
+        if key.is_empty() || key.contains(|c: char| c.is_whitespace() || c == '=') {
+            return Err(SyscallError::InvalidLogKey(key.to_string()).into());
+        }
+        let value = translate_slice::<u8>(memory_mapping, value_addr, value_len, check_aligned)?;
+        let value =
+            from_utf8(value).map_err(|err| SyscallError::InvalidString(err, value.to_vec()))?;
+        let Some(profiling) = &mut invoke_context.get_syscall_context_mut()?.profiling else {
+            return Ok(0);
+        };
+        Ok(u64::from(!profiling.set_attribute(key, value)))
+    }
+);
+
+declare_builtin_function!(
+    /// Open an allocation tag, attributing the heap growth until the matching
+    /// `sol_memprof_tag_end` to the given name in the profile's heap report
+    ///
+    /// The heap usage is sampled by the runtime. Returns 1 if the tag could not be opened because
+    /// the tag limit was reached.
+    SyscallMemprofTagBegin,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        addr: u64,
+        len: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        let heap = section_heap_sample(invoke_context, memory_mapping, NO_HEAP_SAMPLE)?
+            .unwrap_or_default();
+        translate_profile_name_and_do(
+            invoke_context,
+            memory_mapping,
+            addr,
+            len,
+            &mut |profiling, name| u64::from(!profiling.heap_tag_begin(name, heap)),
+        )
+    }
+);
+
+declare_builtin_function!(
+    /// Close the innermost allocation tag opened by `sol_memprof_tag_begin`
+    ///
+    /// Returns 1 if no tag is active.
+    SyscallMemprofTagEnd,
+    fn rust(
+        invoke_context: &mut InvokeContext,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &mut MemoryMapping,
+    ) -> Result<u64, Error> {
+        consume_profile_instrumentation_cost(invoke_context)?;
+        let Some(heap) = section_heap_sample(invoke_context, memory_mapping, NO_HEAP_SAMPLE)?
+        else {
+            return Ok(0);
+        };
+        let Some(profiling) = &mut invoke_context.get_syscall_context_mut()?.profiling else {
+            return Ok(0);
+        };
+        Ok(u64::from(!profiling.heap_tag_end(heap)))
+    }
+);
@@ -40,6 +40,7 @@ use {
     solana_message::Message,
     solana_native_token::LAMPORTS_PER_SOL,
     solana_net_utils::{find_available_ports_in_range, multihomed_sockets::BindIpAddrs, PortRange},
+    solana_program_runtime::profiling::ProfileVerbosity,
     solana_pubkey::Pubkey,
     solana_rent::Rent,
     solana_rpc::{rpc::JsonRpcConfig, rpc_pubsub_service::PubSubConfig},
@@ -55,6 +56,7 @@ use {
     solana_sdk_ids::address_lookup_table,
     solana_signer::Signer,
     solana_streamer::{quic::DEFAULT_QUIC_ENDPOINTS, socket::SocketAddrSpace},
+    solana_svm_log_collector::ProgramLogFilter,
     solana_tpu_client::tpu_client::DEFAULT_TPU_ENABLE_UDP,
     solana_transaction::Transaction,
     solana_validator_exit::Exit,
@@ -136,6 +138,13 @@ pub struct TestValidatorGenesis {
     compute_unit_limit: Option<u64>,
     pub log_messages_bytes_limit: Option<usize>,
     pub transaction_account_lock_limit: Option<usize>,
+    pub enable_diagnostics_syscalls: bool,
+    pub dedup_log_messages: bool,
+    pub log_program_filter: Option<Arc<ProgramLogFilter>>,
+    pub profile_requested_transactions_only: bool,
+    pub profile_verbosity: ProfileVerbosity,
+    pub implicit_profiling_sections: bool,
+    pub profile_cpis: bool,
     pub tpu_enable_udp: bool,
     pub geyser_plugin_manager: Arc<RwLock<GeyserPluginManager>>,
     admin_rpc_service_post_init: Arc<RwLock<Option<AdminRpcRequestMetadataPostInit>>>,
@@ -171,6 +180,13 @@ impl Default for TestValidatorGenesis {
             compute_unit_limit: Option::<u64>::default(),
             log_messages_bytes_limit: Option::<usize>::default(),
             transaction_account_lock_limit: Option::<usize>::default(),
+            enable_diagnostics_syscalls: false,
+            dedup_log_messages: false,
+            log_program_filter: Option::<Arc<ProgramLogFilter>>::default(),
+            profile_requested_transactions_only: false,
+            profile_verbosity: ProfileVerbosity::default(),
+            implicit_profiling_sections: false,
+            profile_cpis: false,
             tpu_enable_udp: DEFAULT_TPU_ENABLE_UDP,
             geyser_plugin_manager: Arc::new(RwLock::new(GeyserPluginManager::new())),
             admin_rpc_service_post_init:
@@ -1100,6 +1116,13 @@ impl TestValidator {
                 }),
             log_messages_bytes_limit: config.log_messages_bytes_limit,
             transaction_account_lock_limit: config.transaction_account_lock_limit,
+            enable_diagnostics_syscalls: config.enable_diagnostics_syscalls,
+            dedup_log_messages: config.dedup_log_messages,
+            log_program_filter: config.log_program_filter.clone(),
+            profile_all_transactions: !config.profile_requested_transactions_only,
+            profile_verbosity: config.profile_verbosity,
+            implicit_profiling_sections: config.implicit_profiling_sections,
+            profile_cpis: config.profile_cpis,
         };
 
         let mut validator_config = ValidatorConfig {
@@ -368,6 +368,18 @@ pub struct UiTransactionStatusMeta {
         skip_serializing_if = "OptionSerializer::should_skip"
     )]
     pub cost_units: OptionSerializer<u64>,
+    /// Profile report of the transaction, only returned by `getTransaction` when the profile
+    /// was requested and the node stored it
+    #[serde(
+        default = "OptionSerializer::skip",
+        skip_serializing_if = "OptionSerializer::should_skip"
+    )]
+    pub profile: OptionSerializer<serde_json::Value>,
+    #[serde(
+        default = "OptionSerializer::skip",
+        skip_serializing_if = "OptionSerializer::should_skip"
+    )]
+    pub instruction_compute_units_consumed: OptionSerializer<Vec<u64>>,
 }
 
 impl From<TransactionStatusMeta> for UiTransactionStatusMeta {
@@ -398,6 +410,10 @@ impl From<TransactionStatusMeta> for UiTransactionStatusMeta {
             ),
             compute_units_consumed: OptionSerializer::or_skip(meta.compute_units_consumed),
             cost_units: OptionSerializer::or_skip(meta.cost_units),
+            profile: OptionSerializer::Skip,
+            instruction_compute_units_consumed: OptionSerializer::or_skip(
+                meta.instruction_compute_units_consumed,
+            ),
         }
     }
 }
@@ -642,6 +658,12 @@ pub struct TransactionStatusMeta {
     pub return_data: Option<TransactionReturnData>,
     pub compute_units_consumed: Option<u64>,
     pub cost_units: Option<u64>,
+    /// Profile report of the transaction, encoded as a program runtime `TransactionProfile`
+    /// protobuf message, only stored by nodes which persist profiles
+    pub profile: Option<Vec<u8>>,
+    /// Compute units consumed by each top-level instruction which was executed, ending at the
+    /// instruction which failed if any
+    pub instruction_compute_units_consumed: Option<Vec<u64>>,
 }
 
 impl Default for TransactionStatusMeta {
@@ -660,6 +682,8 @@ impl Default for TransactionStatusMeta {
             return_data: None,
             compute_units_consumed: None,
             cost_units: None,
+            profile: None,
+            instruction_compute_units_consumed: None,
         }
     }
 }
@@ -187,6 +187,8 @@ fn build_simple_ui_transaction_status_meta(
         return_data: OptionSerializer::Skip,
         compute_units_consumed: OptionSerializer::Skip,
         cost_units: OptionSerializer::Skip,
+        profile: OptionSerializer::Skip,
+        instruction_compute_units_consumed: OptionSerializer::Skip,
     }
 }
 
@@ -226,6 +228,10 @@ fn parse_ui_transaction_status_meta(
         ),
         compute_units_consumed: OptionSerializer::or_skip(meta.compute_units_consumed),
         cost_units: OptionSerializer::or_skip(meta.cost_units),
+        profile: OptionSerializer::Skip,
+        instruction_compute_units_consumed: OptionSerializer::or_skip(
+            meta.instruction_compute_units_consumed,
+        ),
     }
 }
 
@@ -902,6 +908,8 @@ mod test {
             return_data: None,
             compute_units_consumed: None,
             cost_units: None,
+            profile: None,
+            instruction_compute_units_consumed: None,
         };
         #[rustfmt::skip]
         let expected_json_output_value: serde_json::Value = serde_json::from_str(
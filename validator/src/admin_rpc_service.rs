@@ -20,7 +20,7 @@ use {
     solana_gossip::contact_info::{ContactInfo, Protocol, SOCKET_ADDR_UNSPECIFIED},
     solana_keypair::{read_keypair_file, Keypair},
     solana_pubkey::Pubkey,
-    solana_rpc::rpc::verify_pubkey,
+    solana_rpc::{profile_histogram::ProfileLeaderboard, rpc::verify_pubkey},
     solana_rpc_client_api::{config::RpcAccountIndex, custom_error::RpcCustomError},
     solana_signer::Signer,
     solana_validator_exit::Exit,
@@ -94,6 +94,12 @@ pub struct AdminRpcRepairWhitelist {
     pub whitelist: Vec<Pubkey>,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdminRpcProfileLeaderboard {
+    #[serde(flatten)]
+    pub leaderboard: ProfileLeaderboard,
+}
+
 impl From<ContactInfo> for AdminRpcContactInfo {
     fn from(node: ContactInfo) -> Self {
         macro_rules! unwrap_socket {
@@ -149,6 +155,36 @@ impl Display for AdminRpcRepairWhitelist {
 impl solana_cli_output::VerboseDisplay for AdminRpcRepairWhitelist {}
 impl solana_cli_output::QuietDisplay for AdminRpcRepairWhitelist {}
 
+impl Display for AdminRpcProfileLeaderboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ProfileLeaderboard {
+            window_secs,
+            programs,
+            sections,
+        } = &self.leaderboard;
+        writeln!(f, "Compute units consumed over the last {window_secs}s")?;
+        writeln!(f, "Top programs:")?;
+        for program in programs {
+            writeln!(
+                f,
+                "  {}: {} CU in {} invocations",
+                program.program_id, program.total_cu, program.invocations
+            )?;
+        }
+        writeln!(f, "Top sections:")?;
+        for section in sections {
+            writeln!(
+                f,
+                "  {} {}: {} CU in {} occurrences",
+                section.program_id, section.section, section.total_cu, section.occurrences
+            )?;
+        }
+        Ok(())
+    }
+}
+impl solana_cli_output::VerboseDisplay for AdminRpcProfileLeaderboard {}
+impl solana_cli_output::QuietDisplay for AdminRpcProfileLeaderboard {}
+
 #[rpc]
 pub trait AdminRpc {
     type Metadata;
@@ -270,6 +306,14 @@ pub trait AdminRpc {
         transaction_struct: TransactionStructure,
         num_workers: NonZeroUsize,
     ) -> Result<()>;
+
+    #[rpc(meta, name = "profileLeaderboard")]
+    fn profile_leaderboard(
+        &self,
+        meta: Self::Metadata,
+        window_secs: u64,
+        limit: usize,
+    ) -> Result<AdminRpcProfileLeaderboard>;
 }
 
 pub struct AdminRpcImpl;
@@ -785,6 +829,27 @@ impl AdminRpc for AdminRpcImpl {
             Ok(())
         })
     }
+
+    fn profile_leaderboard(
+        &self,
+        meta: Self::Metadata,
+        window_secs: u64,
+        limit: usize,
+    ) -> Result<AdminRpcProfileLeaderboard> {
+        debug!("profile_leaderboard rpc request received: {window_secs}s, {limit}");
+
+        meta.with_post_init(|post_init| {
+            let profile_histograms = post_init.profile_histograms.as_ref().ok_or_else(|| {
+                jsonrpc_core::error::Error::invalid_params(
+                    "Profile leaderboard is not enabled, restart the validator with \
+                     --enable-profile-leaderboard",
+                )
+            })?;
+            Ok(AdminRpcProfileLeaderboard {
+                leaderboard: profile_histograms.leaderboard(window_secs, limit),
+            })
+        })
+    }
 }
 
 impl AdminRpcImpl {
@@ -996,7 +1061,10 @@ mod tests {
         solana_program_option::COption,
         solana_program_pack::Pack,
         solana_pubkey::Pubkey,
-        solana_rpc::rpc::create_validator_exit,
+        solana_rpc::{
+            profile_histogram::{ProgramCuTotal, SectionCuTotal},
+            rpc::create_validator_exit,
+        },
         solana_runtime::{
             bank::{Bank, BankTestConfig},
             bank_forks::BankForks,
@@ -1072,6 +1140,7 @@ mod tests {
                     ),
                     node: None,
                     banking_stage: Arc::new(RwLock::new(None)),
+                    profile_histograms: Some(Arc::default()),
                 }))),
                 staked_nodes_overrides: Arc::new(RwLock::new(HashMap::new())),
                 rpc_to_plugin_manager_sender: None,
@@ -1104,6 +1173,66 @@ mod tests {
         (BankForks::new_rw_arc(bank), Arc::new(voting_keypair))
     }
 
+    #[test]
+    fn test_profile_leaderboard() {
+        let rpc = RpcHandler::_start();
+        let program_id = Pubkey::new_unique().to_string();
+        let log_messages: Vec<String> = [
+            format!("Program {program_id} invoke [1]"),
+            "Program profile: v3 section 0 10 700 - 0 - deserialize".to_string(),
+            format!("Program {program_id} consumed 1000 of 200000 compute units"),
+            format!("Program {program_id} success"),
+        ]
+        .into();
+        rpc.meta
+            .post_init
+            .read()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .profile_histograms
+            .as_ref()
+            .unwrap()
+            .record_transaction(&log_messages);
+
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"profileLeaderboard","params":[60, 10]}"#;
+        let response = rpc.io.handle_request_sync(request, rpc.meta.clone());
+        let result: Value = serde_json::from_str(&response.expect("actual response"))
+            .expect("actual response deserialization");
+        let leaderboard: ProfileLeaderboard =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(
+            leaderboard,
+            ProfileLeaderboard {
+                window_secs: 60,
+                programs: vec![ProgramCuTotal {
+                    program_id: program_id.clone(),
+                    total_cu: 1000,
+                    invocations: 1,
+                }],
+                sections: vec![SectionCuTotal {
+                    program_id,
+                    section: "deserialize".to_string(),
+                    total_cu: 700,
+                    occurrences: 1,
+                }],
+            }
+        );
+
+        // The method fails unless the validator aggregates profiles
+        rpc.meta
+            .post_init
+            .write()
+            .unwrap()
+            .as_mut()
+            .unwrap()
+            .profile_histograms = None;
+        let response = rpc.io.handle_request_sync(request, rpc.meta.clone());
+        let result: Value = serde_json::from_str(&response.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
     #[test]
     fn test_secondary_index_key_sizes() {
         for secondary_index_enabled in [true, false] {
@@ -1,7 +1,7 @@
 use {
     agave_validator::{
         admin_rpc_service, cli, dashboard::Dashboard, ledger_lockfile, lock_ledger,
-        println_name_value,
+        preload_hints::PreloadHints, println_name_value, scenario::Scenario,
     },
     clap::{crate_name, value_t, value_t_or_exit, values_t_or_exit},
     crossbeam_channel::unbounded,
@@ -21,6 +21,7 @@ use {
     solana_keypair::{read_keypair_file, write_keypair_file, Keypair},
     solana_logger::redirect_stderr_to_file,
     solana_native_token::sol_str_to_lamports,
+    solana_program_runtime::profiling::ProfileVerbosity,
     solana_pubkey::Pubkey,
     solana_rent::Rent,
     solana_rpc::{
@@ -30,6 +31,7 @@ use {
     solana_rpc_client::rpc_client::RpcClient,
     solana_signer::Signer,
     solana_streamer::socket::SocketAddrSpace,
+    solana_svm_log_collector::{LogLevel, ProgramLogFilter},
     solana_system_interface::program as system_program,
     solana_test_validator::*,
     std::{
@@ -55,7 +57,43 @@ fn main() {
     let version = solana_version::version!();
     let matches = cli::test_app(version, &default_args).get_matches();
 
-    let output = if matches.is_present("quiet") {
+    // Preload hints are built from the history of a cluster without starting a validator
+    if let Some(subcommand_matches) = matches.subcommand_matches("preload-hints") {
+        let Ok(json_rpc_url) = value_t!(matches, "json_rpc_url", String) else {
+            println!("Error: preload-hints requires the --url argument");
+            exit(1);
+        };
+        let rpc_client = RpcClient::new(normalize_to_url_if_moniker(json_rpc_url));
+        let program_id = pubkey_of(subcommand_matches, "program_id").unwrap();
+        let limit = value_t_or_exit!(subcommand_matches, "limit", usize);
+        let output_file = value_t_or_exit!(subcommand_matches, "output", PathBuf);
+        if let Err(err) = PreloadHints::collect(&rpc_client, &program_id, limit)
+            .and_then(|preload_hints| preload_hints.save(&output_file))
+        {
+            println!("Error: {err}");
+            exit(1);
+        }
+        exit(0);
+    }
+    let preload_hints = value_t!(matches, "preload_accounts", PathBuf)
+        .ok()
+        .map(|hints_file| {
+            PreloadHints::load(&hints_file).unwrap_or_else(|err| {
+                println!("Error: {err}");
+                exit(1);
+            })
+        });
+
+    // A scenario runs against a fresh ledger and reports its results on stdout
+    let scenario = matches.subcommand_matches("run-scenario").map(|matches| {
+        let scenario_file = value_t_or_exit!(matches, "scenario_file", PathBuf);
+        Scenario::load(&scenario_file).unwrap_or_else(|err| {
+            println!("Error: {err}");
+            exit(1);
+        })
+    });
+
+    let output = if matches.is_present("quiet") || scenario.is_some() {
         Output::None
     } else if matches.is_present("log") {
         Output::Log
@@ -64,7 +102,7 @@ fn main() {
     };
 
     let ledger_path = value_t_or_exit!(matches, "ledger_path", PathBuf);
-    let reset_ledger = matches.is_present("reset");
+    let reset_ledger = matches.is_present("reset") || scenario.is_some();
 
     let indexes: HashSet<AccountIndex> = matches
         .values_of("account_indexes")
@@ -406,6 +444,20 @@ fn main() {
     genesis.log_messages_bytes_limit = value_t!(matches, "log_messages_bytes_limit", usize).ok();
     genesis.transaction_account_lock_limit =
         value_t!(matches, "transaction_account_lock_limit", usize).ok();
+    genesis.enable_diagnostics_syscalls = matches.is_present("enable_diagnostics_syscalls");
+    genesis.dedup_log_messages = matches.is_present("dedup_log_messages");
+    genesis.log_program_filter = pubkeys_of(&matches, "log_allow_program")
+        .map(|program_ids| ProgramLogFilter::Allow(program_ids.into_iter().collect()))
+        .or_else(|| {
+            pubkeys_of(&matches, "log_deny_program")
+                .map(|program_ids| ProgramLogFilter::Deny(program_ids.into_iter().collect()))
+        })
+        .map(Arc::new);
+    genesis.profile_requested_transactions_only =
+        matches.is_present("profile_requested_transactions_only");
+    genesis.profile_verbosity = value_t_or_exit!(matches, "profile_verbosity", ProfileVerbosity);
+    genesis.implicit_profiling_sections = matches.is_present("implicit_profiling_sections");
+    genesis.profile_cpis = matches.is_present("profile_cpis");
 
     let tower_storage = Arc::new(FileTowerStorage::new(ledger_path.clone()));
 
@@ -490,9 +542,14 @@ fn main() {
     genesis.rpc_config(JsonRpcConfig {
         enable_rpc_transaction_history: true,
         enable_extended_tx_metadata_storage: true,
+        enable_profile_leaderboard: true,
+        enable_profile_persistence: true,
+        profile_sqlite_path: value_t!(matches, "profile_sqlite_path", PathBuf).ok(),
         rpc_bigtable_config,
         faucet_addr: Some(faucet_addr),
         account_indexes,
+        simulation_min_log_level: value_t!(matches, "simulation_log_level", LogLevel)
+            .unwrap_or_default(),
         ..JsonRpcConfig::default_for_test()
     });
 
@@ -604,12 +661,43 @@ fn main() {
         genesis.compute_unit_limit(compute_unit_limit);
     }
 
+    if let Some(scenario) = &scenario {
+        if let Err(err) = scenario.add_to_genesis(&mut genesis) {
+            println!("Error: {err}");
+            exit(1);
+        }
+    }
+
     match genesis.start_with_mint_address_and_geyser_plugin_rpc(
         mint_address,
         socket_addr_space,
         rpc_to_plugin_manager_receiver,
     ) {
         Ok(test_validator) => {
+            if let Some(preload_hints) = &preload_hints {
+                let bank = test_validator.bank_forks().read().unwrap().working_bank();
+                match preload_hints.preload(&bank) {
+                    Ok(preloaded) => info!("Preloaded {preloaded} accounts"),
+                    Err(err) => {
+                        println!("Error: {err}");
+                        exit(1);
+                    }
+                }
+            }
+            if let Some(scenario) = scenario {
+                let reports = scenario
+                    .run(&test_validator.get_rpc_client())
+                    .unwrap_or_else(|err| {
+                        println!("Error: scenario failed: {err}");
+                        exit(1);
+                    });
+                for report in &reports {
+                    print!("{report}");
+                }
+                let failed = reports.iter().filter(|report| !report.passed()).count();
+                println!("{} steps, {failed} failed", reports.len());
+                exit(if failed == 0 { 0 } else { 1 });
+            }
             if let Some(dashboard) = dashboard {
                 dashboard.run(Duration::from_millis(250));
             }
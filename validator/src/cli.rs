@@ -74,7 +74,8 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
         .subcommand(commands::staked_nodes_overrides::command())
         .subcommand(commands::wait_for_restart_window::command())
         .subcommand(commands::set_public_address::command())
-        .subcommand(commands::manage_block_production::command());
+        .subcommand(commands::manage_block_production::command())
+        .subcommand(commands::profile_leaderboard::command());
 
     commands::run::add_args(app, default_args)
         .args(&thread_args(&default_args.thread_args))
@@ -914,6 +915,100 @@ pub fn test_app<'a>(version: &'a str, default_args: &'a DefaultTestArgs) -> App<
                 .takes_value(true)
                 .help("Maximum number of bytes written to the program log before truncation"),
         )
+        .arg(
+            Arg::with_name("simulation_log_level")
+                .long("simulation-log-level")
+                .value_name("LEVEL")
+                .takes_value(true)
+                .possible_values(&["trace", "debug", "info", "warn", "error"])
+                .help(
+                    "Drop program messages logged with sol_log_level below this level from \
+                     simulateTransaction results, unless the request sets minLogLevel",
+                ),
+        )
+        .arg(
+            Arg::with_name("dedup_log_messages")
+                .long("dedup-log-messages")
+                .takes_value(false)
+                .help(
+                    "Collapse consecutive identical log messages of a transaction into a single \
+                     \"<message> (x<count>)\" message",
+                ),
+        )
+        .arg(
+            Arg::with_name("log_allow_program")
+                .long("log-allow-program")
+                .takes_value(true)
+                .value_name("PROGRAM_ID")
+                .validator(is_pubkey)
+                .multiple(true)
+                .conflicts_with("log_deny_program")
+                .help(
+                    "Only record the messages logged by this program. May be specified multiple \
+                     times",
+                ),
+        )
+        .arg(
+            Arg::with_name("log_deny_program")
+                .long("log-deny-program")
+                .takes_value(true)
+                .value_name("PROGRAM_ID")
+                .validator(is_pubkey)
+                .multiple(true)
+                .help("Drop the messages logged by this program. May be specified multiple times"),
+        )
+        .arg(
+            Arg::with_name("profile_requested_transactions_only")
+                .long("profile-requested-transactions-only")
+                .takes_value(false)
+                .help(
+                    "Only profile transactions which include the compute budget enable \
+                     profiling instruction, instead of every transaction",
+                ),
+        )
+        .arg(
+            Arg::with_name("profile_verbosity")
+                .long("profile-verbosity")
+                .value_name("LEVEL")
+                .takes_value(true)
+                .possible_values(&["off", "summary", "tree", "full", "full-json"])
+                .default_value("full")
+                .help(
+                    "How much of the profile of each instruction is logged: nothing, the \
+                     busiest sections, the section tree, every entry or every entry as JSON. \
+                     simulateTransaction requests may override it with profileVerbosity",
+                ),
+        )
+        .arg(
+            Arg::with_name("implicit_profiling_sections")
+                .long("implicit-profiling-sections")
+                .takes_value(false)
+                .help(
+                    "Record a profiling section named after the program around every instruction \
+                     and CPI of the profiled transactions, so that uninstrumented programs are \
+                     profiled too",
+                ),
+        )
+        .arg(
+            Arg::with_name("profile_cpis")
+                .long("profile-cpis")
+                .takes_value(false)
+                .help(
+                    "Record a profiling section named after the callee around every CPI of the \
+                     profiled transactions, even outside of the sections opened by the program",
+                ),
+        )
+        .arg(
+            Arg::with_name("profile_sqlite_path")
+                .long("profile-sqlite")
+                .value_name("PATH")
+                .takes_value(true)
+                .help(
+                    "Append the profiling entries reported by executed transactions, along with \
+                     their slot, signature and program, to this SQLite database. Requires a \
+                     build with the profile-sqlite feature",
+                ),
+        )
         .arg(
             Arg::with_name("transaction_account_lock_limit")
                 .long("transaction-account-lock-limit")
@@ -922,6 +1017,15 @@ pub fn test_app<'a>(version: &'a str, default_args: &'a DefaultTestArgs) -> App<
                 .takes_value(true)
                 .help("Override the runtime's account lock limit per transaction"),
         )
+        .arg(
+            Arg::with_name("enable_diagnostics_syscalls")
+                .long("enable-diagnostics-syscalls")
+                .takes_value(false)
+                .help(
+                    "Let programs call syscalls exposing non-deterministic host state, such as \
+                     sol_host_time, to correlate their execution with off-chain events",
+                ),
+        )
         .arg(
             Arg::with_name("clone_feature_set")
                 .long("clone-feature-set")
@@ -933,6 +1037,67 @@ pub fn test_app<'a>(version: &'a str, default_args: &'a DefaultTestArgs) -> App<
                      silently ignored",
                 ),
         )
+        .arg(
+            Arg::with_name("preload_accounts")
+                .long("preload-accounts")
+                .value_name("HINTS_FILE")
+                .takes_value(true)
+                .help(
+                    "Load the accounts listed in a report written by the preload-hints subcommand \
+                     once the validator has started, so that they are cached before the first \
+                     transactions are processed",
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("run-scenario")
+                .about(
+                    "Start a validator with a fresh ledger, run the transactions of a scenario \
+                     file in order and check them against the scenario's compute budgets",
+                )
+                .arg(
+                    Arg::with_name("scenario_file")
+                        .index(1)
+                        .value_name("SCENARIO_FILE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("YAML or JSON file describing the scenario"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("preload-hints")
+                .about(
+                    "Report the accounts accessed by the profiled instructions of a program on \
+                     the cluster referenced by the --url argument, for use with \
+                     --preload-accounts",
+                )
+                .arg(
+                    Arg::with_name("program_id")
+                        .index(1)
+                        .value_name("PROGRAM_ID")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_pubkey)
+                        .help("Program to report on"),
+                )
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .value_name("NUM_TRANSACTIONS")
+                        .takes_value(true)
+                        .default_value("1000")
+                        .validator(is_parsable::<usize>)
+                        .help("Number of recent transactions of the program to read"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("JSON file to write the report to"),
+                ),
+        )
 }
 
 pub struct DefaultTestArgs {
@@ -4,6 +4,7 @@ pub mod exit;
 pub mod manage_block_production;
 pub mod monitor;
 pub mod plugin;
+pub mod profile_leaderboard;
 pub mod repair_shred_from_peer;
 pub mod repair_whitelist;
 pub mod run;
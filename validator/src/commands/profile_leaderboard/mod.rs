@@ -0,0 +1,132 @@
+use {
+    crate::{
+        admin_rpc_service,
+        commands::{FromClapArgMatches, Result},
+    },
+    clap::{value_t, App, Arg, ArgMatches, SubCommand},
+    solana_clap_utils::input_validators::is_parsable,
+    solana_cli_output::OutputFormat,
+    std::path::Path,
+};
+
+const COMMAND: &str = "profile-leaderboard";
+
+const DEFAULT_WINDOW_SECS: &str = "600";
+const DEFAULT_LIMIT: &str = "10";
+
+#[derive(Debug, PartialEq)]
+pub struct ProfileLeaderboardArgs {
+    pub window_secs: u64,
+    pub limit: usize,
+    pub output: OutputFormat,
+}
+
+impl FromClapArgMatches for ProfileLeaderboardArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(ProfileLeaderboardArgs {
+            window_secs: value_t!(matches, "window", u64)?,
+            limit: value_t!(matches, "limit", usize)?,
+            output: OutputFormat::from_matches(matches, "output", false),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about(
+            "Display the programs and profiling sections which consumed the most compute units \
+             recently. Requires --enable-profile-leaderboard",
+        )
+        .arg(
+            Arg::with_name("window")
+                .long("window")
+                .value_name("SECONDS")
+                .takes_value(true)
+                .default_value(DEFAULT_WINDOW_SECS)
+                .validator(is_parsable::<u64>)
+                .help("Trailing window to aggregate, at most one hour"),
+        )
+        .arg(
+            Arg::with_name("limit")
+                .long("limit")
+                .value_name("COUNT")
+                .takes_value(true)
+                .default_value(DEFAULT_LIMIT)
+                .validator(is_parsable::<usize>)
+                .help("Number of programs and sections to display"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .takes_value(true)
+                .value_name("MODE")
+                .possible_values(&["json", "json-compact"])
+                .help("Output display mode"),
+        )
+}
+
+pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let ProfileLeaderboardArgs {
+        window_secs,
+        limit,
+        output,
+    } = ProfileLeaderboardArgs::from_clap_arg_match(matches)?;
+
+    let admin_client = admin_rpc_service::connect(ledger_path);
+    let leaderboard = admin_rpc_service::runtime().block_on(async move {
+        admin_client
+            .await?
+            .profile_leaderboard(window_secs, limit)
+            .await
+    })?;
+
+    println!("{}", output.formatted_string(&leaderboard));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::commands::tests::{
+            verify_args_struct_by_command, verify_args_struct_by_command_is_error,
+        },
+    };
+
+    #[test]
+    fn verify_args_struct_by_command_profile_leaderboard_default() {
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND],
+            ProfileLeaderboardArgs {
+                window_secs: 600,
+                limit: 10,
+                output: OutputFormat::Display,
+            },
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_profile_leaderboard_with_args() {
+        verify_args_struct_by_command(
+            command(),
+            vec![
+                COMMAND, "--window", "60", "--limit", "3", "--output", "json",
+            ],
+            ProfileLeaderboardArgs {
+                window_secs: 60,
+                limit: 3,
+                output: OutputFormat::Json,
+            },
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_profile_leaderboard_invalid_window() {
+        verify_args_struct_by_command_is_error::<ProfileLeaderboardArgs>(
+            command(),
+            vec![COMMAND, "--window", "-1"],
+        );
+    }
+}
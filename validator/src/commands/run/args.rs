@@ -312,6 +312,39 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  transaction info stored",
             ),
     )
+    .arg(
+        Arg::with_name("enable_profile_leaderboard")
+            .long("enable-profile-leaderboard")
+            .requires("enable_rpc_transaction_history")
+            .takes_value(false)
+            .help(
+                "Aggregate the compute units consumed by programs and their profiling sections \
+                 over the last hour, reported by the 'profile-leaderboard' command, the \
+                 getProgramComputeStats RPC method and as metrics every minute",
+            ),
+    )
+    .arg(
+        Arg::with_name("profile_sqlite_path")
+            .long("profile-sqlite")
+            .value_name("PATH")
+            .requires("enable_rpc_transaction_history")
+            .takes_value(true)
+            .help(
+                "Append the profiling entries reported by executed transactions, along with \
+                 their slot, signature and program, to this SQLite database. Requires a build \
+                 with the profile-sqlite feature",
+            ),
+    )
+    .arg(
+        Arg::with_name("enable_profile_persistence")
+            .long("enable-profile-persistence")
+            .requires("enable_rpc_transaction_history")
+            .takes_value(false)
+            .help(
+                "Include the profile report of each transaction in the historical transaction \
+                 info stored, returned by getTransaction with includeProfile",
+            ),
+    )
     .arg(
         Arg::with_name("rpc_max_multiple_accounts")
             .long("rpc-max-multiple-accounts")
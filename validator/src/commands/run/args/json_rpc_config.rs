@@ -3,6 +3,7 @@ use {
     clap::{value_t, ArgMatches},
     solana_accounts_db::accounts_index::AccountSecondaryIndexes,
     solana_rpc::rpc::{JsonRpcConfig, RpcBigtableConfig},
+    std::path::PathBuf,
 };
 
 impl FromClapArgMatches for JsonRpcConfig {
@@ -19,6 +20,9 @@ impl FromClapArgMatches for JsonRpcConfig {
             enable_rpc_transaction_history: matches.is_present("enable_rpc_transaction_history"),
             enable_extended_tx_metadata_storage: matches
                 .is_present("enable_extended_tx_metadata_storage"),
+            enable_profile_leaderboard: matches.is_present("enable_profile_leaderboard"),
+            profile_sqlite_path: matches.value_of("profile_sqlite_path").map(PathBuf::from),
+            enable_profile_persistence: matches.is_present("enable_profile_persistence"),
             faucet_addr: matches
                 .value_of("rpc_faucet_addr")
                 .map(|address| {
@@ -103,6 +107,76 @@ mod tests {
         }
     }
 
+    #[test]
+    fn verify_args_struct_by_command_run_with_enable_profile_leaderboard() {
+        {
+            let default_run_args = crate::commands::run::args::RunArgs::default();
+            let expected_args = RunArgs {
+                json_rpc_config: JsonRpcConfig {
+                    enable_rpc_transaction_history: true,
+                    enable_profile_leaderboard: true,
+                    ..default_run_args.json_rpc_config.clone()
+                },
+                ..default_run_args.clone()
+            };
+            verify_args_struct_by_command_run_with_identity_setup(
+                default_run_args,
+                vec![
+                    "--enable-rpc-transaction-history", // required by enable_profile_leaderboard
+                    "--enable-profile-leaderboard",
+                ],
+                expected_args,
+            );
+        }
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_profile_sqlite() {
+        {
+            let default_run_args = crate::commands::run::args::RunArgs::default();
+            let expected_args = RunArgs {
+                json_rpc_config: JsonRpcConfig {
+                    enable_rpc_transaction_history: true,
+                    profile_sqlite_path: Some(PathBuf::from("profiles.sqlite")),
+                    ..default_run_args.json_rpc_config.clone()
+                },
+                ..default_run_args.clone()
+            };
+            verify_args_struct_by_command_run_with_identity_setup(
+                default_run_args,
+                vec![
+                    "--enable-rpc-transaction-history", // required by profile_sqlite_path
+                    "--profile-sqlite",
+                    "profiles.sqlite",
+                ],
+                expected_args,
+            );
+        }
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_enable_profile_persistence() {
+        {
+            let default_run_args = crate::commands::run::args::RunArgs::default();
+            let expected_args = RunArgs {
+                json_rpc_config: JsonRpcConfig {
+                    enable_rpc_transaction_history: true,
+                    enable_profile_persistence: true,
+                    ..default_run_args.json_rpc_config.clone()
+                },
+                ..default_run_args.clone()
+            };
+            verify_args_struct_by_command_run_with_identity_setup(
+                default_run_args,
+                vec![
+                    "--enable-rpc-transaction-history", // required by enable_profile_persistence
+                    "--enable-profile-persistence",
+                ],
+                expected_args,
+            );
+        }
+    }
+
     #[test]
     fn verify_args_struct_by_command_run_with_rpc_faucet_addr() {
         {
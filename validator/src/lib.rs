@@ -19,6 +19,8 @@ pub mod bootstrap;
 pub mod cli;
 pub mod commands;
 pub mod dashboard;
+pub mod preload_hints;
+pub mod scenario;
 
 pub fn format_name_value(name: &str, value: &str) -> String {
     format!("{} {}", style(name).bold(), value)
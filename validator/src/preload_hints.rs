@@ -0,0 +1,372 @@
+//! Account preloading hints for `solana-test-validator`
+//!
+//! `solana-test-validator preload-hints` reads the recent transactions of a program from a
+//! cluster and reports, for the instructions of that program which recorded a profile, the
+//! accounts they accessed, the profiling sections which ran while they were accessed and the
+//! pairs of accounts accessed together. Passing the report to `--preload-accounts` loads those
+//! accounts once the validator has started, so that profiling runs start with a warm accounts
+//! cache and their wall-clock measurements are not skewed by cold account loads.
+
+use {
+    serde::{Deserialize, Serialize},
+    solana_commitment_config::CommitmentConfig,
+    solana_program_runtime::profiling::PROFILE_LOG_VERSION,
+    solana_pubkey::Pubkey,
+    solana_rpc::transaction_profile::instruction_profiles_from_logs,
+    solana_rpc_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    solana_rpc_client_api::config::RpcTransactionConfig,
+    solana_runtime::bank::Bank,
+    solana_signature::Signature,
+    solana_transaction_status_client_types::{
+        EncodedConfirmedTransactionWithStatusMeta, UiLoadedAddresses, UiTransactionEncoding,
+    },
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        fs,
+        path::Path,
+    },
+};
+
+/// Number of account pairs reported per program, most frequent first
+const MAX_ACCOUNT_PAIRS: usize = 100;
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreloadHints {
+    /// Access report of each program, keyed by program id
+    pub programs: BTreeMap<String, ProgramAccountAccess>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramAccountAccess {
+    /// Number of profiled instructions the report was built from
+    pub profiled_instructions: u64,
+    /// Accounts passed to the profiled instructions, most frequently accessed first
+    pub accounts: Vec<AccountAccess>,
+    /// Pairs of accounts passed to the same instruction, most frequent first
+    pub accessed_together: Vec<AccountPair>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountAccess {
+    pub address: String,
+    /// Number of profiled instructions which accessed the account
+    pub count: u64,
+    /// Profiling sections reported by the instructions which accessed the account
+    pub sections: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountPair {
+    pub addresses: [String; 2],
+    pub count: u64,
+}
+
+#[derive(Default)]
+struct ProgramAccumulator {
+    profiled_instructions: u64,
+    accounts: BTreeMap<String, (u64, BTreeSet<String>)>,
+    pairs: BTreeMap<(String, String), u64>,
+}
+
+impl ProgramAccumulator {
+    fn finish(self) -> ProgramAccountAccess {
+        let mut accounts: Vec<_> = self
+            .accounts
+            .into_iter()
+            .map(|(address, (count, sections))| AccountAccess {
+                address,
+                count,
+                sections: sections.into_iter().collect(),
+            })
+            .collect();
+        accounts.sort_by(|a, b| b.count.cmp(&a.count));
+        let mut accessed_together: Vec<_> = self
+            .pairs
+            .into_iter()
+            .map(|((first, second), count)| AccountPair {
+                addresses: [first, second],
+                count,
+            })
+            .collect();
+        accessed_together.sort_by(|a, b| b.count.cmp(&a.count));
+        accessed_together.truncate(MAX_ACCOUNT_PAIRS);
+        ProgramAccountAccess {
+            profiled_instructions: self.profiled_instructions,
+            accounts,
+            accessed_together,
+        }
+    }
+}
+
+/// Builds [`PreloadHints`] from a sequence of transactions
+#[derive(Default)]
+pub struct PreloadHintsCollector {
+    programs: BTreeMap<String, ProgramAccumulator>,
+}
+
+impl PreloadHintsCollector {
+    /// Record the top level instructions of a transaction
+    ///
+    /// `instructions` yields the program id index and account indexes of each instruction into
+    /// `account_keys`. Log lines only identify the program of a profile, so every top level
+    /// instruction of a program which reported a profile in this transaction is attributed the
+    /// sections of all its profiles.
+    pub fn add_instructions<'a>(
+        &mut self,
+        account_keys: &[String],
+        instructions: impl IntoIterator<Item = (u8, &'a [u8])>,
+        log_messages: &[String],
+    ) {
+        let mut profiled_sections = BTreeMap::<&str, BTreeSet<String>>::new();
+        for profile in instruction_profiles_from_logs(log_messages, PROFILE_LOG_VERSION) {
+            if profile.stack_height != 1 {
+                continue;
+            }
+            let Some(program_id) = account_keys.iter().find(|key| **key == profile.program_id)
+            else {
+                continue;
+            };
+            profiled_sections
+                .entry(program_id.as_str())
+                .or_default()
+                .extend(profile.entries.into_iter().map(|entry| entry.name));
+        }
+
+        for (program_id_index, accounts) in instructions {
+            let Some(program_id) = account_keys.get(usize::from(program_id_index)) else {
+                continue;
+            };
+            let Some(sections) = profiled_sections.get(program_id.as_str()) else {
+                continue;
+            };
+            let accounts: BTreeSet<&String> = accounts
+                .iter()
+                .filter_map(|index| account_keys.get(usize::from(*index)))
+                .collect();
+
+            let program = self.programs.entry(program_id.clone()).or_default();
+            program.profiled_instructions = program.profiled_instructions.saturating_add(1);
+            for address in &accounts {
+                let (count, account_sections) =
+                    program.accounts.entry((*address).clone()).or_default();
+                *count = count.saturating_add(1);
+                account_sections.extend(sections.iter().cloned());
+            }
+            for (position, first) in accounts.iter().enumerate() {
+                for second in accounts.iter().skip(position.saturating_add(1)) {
+                    let count = program
+                        .pairs
+                        .entry(((*first).clone(), (*second).clone()))
+                        .or_default();
+                    *count = count.saturating_add(1);
+                }
+            }
+        }
+    }
+
+    /// Record a transaction fetched with a binary encoding
+    pub fn add_transaction(
+        &mut self,
+        transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Result<(), String> {
+        let transaction_with_meta = &transaction.transaction;
+        let versioned_transaction = transaction_with_meta
+            .transaction
+            .decode()
+            .ok_or("Failed to decode transaction")?;
+        let Some(meta) = &transaction_with_meta.meta else {
+            return Ok(());
+        };
+        let log_messages: Option<Vec<String>> = meta.log_messages.clone().into();
+        let loaded_addresses: Option<UiLoadedAddresses> = meta.loaded_addresses.clone().into();
+
+        let message = &versioned_transaction.message;
+        let mut account_keys: Vec<String> = message
+            .static_account_keys()
+            .iter()
+            .map(Pubkey::to_string)
+            .collect();
+        if let Some(loaded_addresses) = loaded_addresses {
+            account_keys.extend(loaded_addresses.writable);
+            account_keys.extend(loaded_addresses.readonly);
+        }
+        self.add_instructions(
+            &account_keys,
+            message.instructions().iter().map(|instruction| {
+                (
+                    instruction.program_id_index,
+                    instruction.accounts.as_slice(),
+                )
+            }),
+            &log_messages.unwrap_or_default(),
+        );
+        Ok(())
+    }
+
+    pub fn finish(self) -> PreloadHints {
+        PreloadHints {
+            programs: self
+                .programs
+                .into_iter()
+                .map(|(program_id, program)| (program_id, program.finish()))
+                .collect(),
+        }
+    }
+}
+
+impl PreloadHints {
+    /// Build hints from the most recent `limit` transactions referencing `program_id`
+    pub fn collect(
+        rpc_client: &RpcClient,
+        program_id: &Pubkey,
+        limit: usize,
+    ) -> Result<Self, String> {
+        let commitment = CommitmentConfig::confirmed();
+        let signatures = rpc_client
+            .get_signatures_for_address_with_config(
+                program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    limit: Some(limit),
+                    commitment: Some(commitment),
+                    ..GetConfirmedSignaturesForAddress2Config::default()
+                },
+            )
+            .map_err(|err| format!("Failed to fetch signatures of {program_id}: {err}"))?;
+
+        let mut collector = PreloadHintsCollector::default();
+        for status in signatures {
+            let signature: Signature = status
+                .signature
+                .parse()
+                .map_err(|err| format!("Invalid signature {}: {err}", status.signature))?;
+            let transaction = rpc_client
+                .get_transaction_with_config(
+                    &signature,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Base64),
+                        commitment: Some(commitment),
+                        max_supported_transaction_version: Some(0),
+                        include_profile: false,
+                    },
+                )
+                .map_err(|err| format!("Failed to fetch transaction {signature}: {err}"))?;
+            collector.add_transaction(&transaction)?;
+        }
+        Ok(collector.finish())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = fs::File::open(path)
+            .map_err(|err| format!("Unable to open {}: {err}", path.display()))?;
+        serde_json::from_reader(file)
+            .map_err(|err| format!("Unable to parse {}: {err}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = fs::File::create(path)
+            .map_err(|err| format!("Unable to create {}: {err}", path.display()))?;
+        serde_json::to_writer_pretty(file, self)
+            .map_err(|err| format!("Unable to write {}: {err}", path.display()))
+    }
+
+    /// Every account and program in the report, each listed once
+    pub fn accounts_to_preload(&self) -> Result<Vec<Pubkey>, String> {
+        let addresses: BTreeSet<&String> = self
+            .programs
+            .iter()
+            .flat_map(|(program_id, program)| {
+                std::iter::once(program_id)
+                    .chain(program.accounts.iter().map(|account| &account.address))
+            })
+            .collect();
+        addresses
+            .into_iter()
+            .map(|address| {
+                address
+                    .parse()
+                    .map_err(|err| format!("Invalid address {address}: {err}"))
+            })
+            .collect()
+    }
+
+    /// Load the hinted accounts from the bank so that they are cached for the following
+    /// transactions
+    ///
+    /// Returns the number of accounts which exist in the bank.
+    pub fn preload(&self, bank: &Bank) -> Result<usize, String> {
+        Ok(self
+            .accounts_to_preload()?
+            .iter()
+            .filter(|address| bank.get_account_with_fixed_root(address).is_some())
+            .count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_preload_hints() {
+        let program_id = Pubkey::new_unique().to_string();
+        let other_program_id = Pubkey::new_unique().to_string();
+        let state = Pubkey::new_unique().to_string();
+        let config = Pubkey::new_unique().to_string();
+        let user = Pubkey::new_unique().to_string();
+        let account_keys = vec![
+            user.clone(),
+            state.clone(),
+            config.clone(),
+            program_id.clone(),
+            other_program_id.clone(),
+        ];
+        let log_messages = [
+            format!("Program {program_id} invoke [1]"),
+            "Program profile: v3 section 0 10 400 - 0 - load state".to_string(),
+            format!("Program {program_id} success"),
+            format!("Program {other_program_id} invoke [1]"),
+            format!("Program {other_program_id} success"),
+        ]
+        .to_vec();
+
+        let mut collector = PreloadHintsCollector::default();
+        for _ in 0..2 {
+            collector.add_instructions(
+                &account_keys,
+                [(3, [0, 1, 2].as_slice()), (4, [0, 1].as_slice())],
+                &log_messages,
+            );
+        }
+        // Transactions without a profile are ignored
+        collector.add_instructions(&account_keys, [(3, [0, 1].as_slice())], &[]);
+        let hints = collector.finish();
+
+        // Only the profiled program is reported
+        assert_eq!(hints.programs.len(), 1);
+        let program = hints.programs.get(&program_id).unwrap();
+        assert_eq!(program.profiled_instructions, 2);
+        assert_eq!(program.accounts.len(), 3);
+        for account in &program.accounts {
+            assert_eq!(account.count, 2);
+            assert_eq!(account.sections, vec!["load state".to_string()]);
+        }
+        assert_eq!(program.accessed_together.len(), 3);
+        assert!(program
+            .accessed_together
+            .iter()
+            .all(|pair| pair.count == 2 && pair.addresses.first() != pair.addresses.last()));
+
+        let mut expected: Vec<Pubkey> = [&program_id, &state, &config, &user]
+            .iter()
+            .map(|address| address.parse().unwrap())
+            .collect();
+        expected.sort();
+        let mut preload = hints.accounts_to_preload().unwrap();
+        preload.sort();
+        assert_eq!(preload, expected);
+    }
+}
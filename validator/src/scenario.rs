@@ -0,0 +1,543 @@
+//! Scenario files for `solana-test-validator run-scenario`
+//!
+//! A scenario describes accounts and programs to preload into genesis, transactions to send in
+//! order and the profile budgets each step is expected to stay within. Scenarios are YAML
+//! documents, and since YAML is a superset of JSON, JSON files are accepted as well:
+//!
+//! ```yaml
+//! accounts:
+//!   - address: 9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM
+//!     filename: accounts/state.json
+//! programs:
+//!   - program_id: Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS
+//!     path: target/deploy/my_program.so
+//! steps:
+//!   - name: initialize
+//!     signers: [payer.json]
+//!     instructions:
+//!       - program_id: Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS
+//!         accounts:
+//!           - pubkey: 9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM
+//!             is_writable: true
+//!         data: AQID
+//!     expect:
+//!       max_compute_units: 20000
+//!       sections:
+//!         - name: deserialize
+//!           max_compute_units: 1500
+//! ```
+//!
+//! Relative paths are resolved against the directory containing the scenario file. The first
+//! signer of each step pays the transaction fee and is funded from the faucet if its balance is
+//! zero. Instruction data is base64 encoded.
+
+use {
+    base64::{prelude::BASE64_STANDARD, Engine},
+    serde::Deserialize,
+    solana_commitment_config::CommitmentConfig,
+    solana_instruction::{AccountMeta, Instruction},
+    solana_keypair::{read_keypair_file, Keypair},
+    solana_native_token::LAMPORTS_PER_SOL,
+    solana_program_runtime::profiling::{ProfileEntry, ProfileEntryKind, ProfileLogLine},
+    solana_pubkey::Pubkey,
+    solana_rpc_client::rpc_client::RpcClient,
+    solana_rpc_client_api::config::{RpcSendTransactionConfig, RpcTransactionConfig},
+    solana_signer::Signer,
+    solana_test_validator::{AccountInfo, TestValidatorGenesis, UpgradeableProgramInfo},
+    solana_transaction::Transaction,
+    solana_transaction_status_client_types::UiTransactionEncoding,
+    std::{
+        fmt, fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Lamports airdropped to a step's fee payer if its balance is zero
+const PAYER_AIRDROP_LAMPORTS: u64 = 10 * LAMPORTS_PER_SOL;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(default)]
+    pub accounts: Vec<ScenarioAccount>,
+    #[serde(default)]
+    pub programs: Vec<ScenarioProgram>,
+    pub steps: Vec<ScenarioStep>,
+    /// Directory against which relative paths are resolved
+    #[serde(skip)]
+    base_dir: PathBuf,
+}
+
+/// An account to preload, in the same JSON format accepted by `--account`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioAccount {
+    /// Overrides the address stored in the account file
+    pub address: Option<String>,
+    pub filename: PathBuf,
+}
+
+/// An upgradeable program to preload
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioProgram {
+    pub program_id: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioStep {
+    pub name: String,
+    /// Keypair files signing the transaction, the first one is the fee payer
+    pub signers: Vec<PathBuf>,
+    pub instructions: Vec<ScenarioInstruction>,
+    #[serde(default)]
+    pub expect: ScenarioExpectation,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioInstruction {
+    pub program_id: String,
+    #[serde(default)]
+    pub accounts: Vec<ScenarioAccountMeta>,
+    /// Base64 encoded instruction data
+    #[serde(default)]
+    pub data: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioAccountMeta {
+    pub pubkey: String,
+    #[serde(default)]
+    pub is_signer: bool,
+    #[serde(default)]
+    pub is_writable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioExpectation {
+    #[serde(default = "default_success")]
+    pub success: bool,
+    /// Budget for the compute units consumed by the whole transaction
+    pub max_compute_units: Option<u64>,
+    #[serde(default)]
+    pub sections: Vec<SectionBudget>,
+}
+
+impl Default for ScenarioExpectation {
+    fn default() -> Self {
+        Self {
+            success: default_success(),
+            max_compute_units: None,
+            sections: Vec::new(),
+        }
+    }
+}
+
+fn default_success() -> bool {
+    true
+}
+
+/// Budget applied to every completed profiling section with the given name
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SectionBudget {
+    pub name: String,
+    pub max_compute_units: u64,
+}
+
+/// Outcome of a single scenario step
+#[derive(Debug)]
+pub struct StepReport {
+    pub name: String,
+    pub signature: String,
+    pub success: bool,
+    pub compute_units_consumed: Option<u64>,
+    pub profile: Vec<ProfileEntry>,
+    /// Violated expectations, empty if the step passed
+    pub failures: Vec<String>,
+}
+
+impl StepReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for StepReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} {}: {}",
+            if self.passed() { "PASS" } else { "FAIL" },
+            self.name,
+            self.signature
+        )?;
+        if let Some(compute_units_consumed) = self.compute_units_consumed {
+            writeln!(f, "  consumed {compute_units_consumed} compute units")?;
+        }
+        for entry in self
+            .profile
+            .iter()
+            .filter(|entry| entry.kind == ProfileEntryKind::Section)
+        {
+            writeln!(
+                f,
+                "  {:indent$}{}: {} compute units",
+                "",
+                entry.name,
+                entry.consumed(),
+                indent = entry.depth.saturating_mul(2),
+            )?;
+        }
+        for failure in &self.failures {
+            writeln!(f, "  error: {failure}")?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<Pubkey, String> {
+    pubkey
+        .parse()
+        .map_err(|err| format!("invalid pubkey {pubkey}: {err}"))
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = fs::File::open(path)
+            .map_err(|err| format!("unable to open {}: {err}", path.display()))?;
+        let mut scenario: Self = serde_yaml::from_reader(file)
+            .map_err(|err| format!("unable to parse {}: {err}", path.display()))?;
+        scenario.base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(scenario)
+    }
+
+    fn resolve(&self, path: &Path) -> PathBuf {
+        self.base_dir.join(path)
+    }
+
+    /// Add the scenario's accounts and programs to the genesis configuration
+    pub fn add_to_genesis(&self, genesis: &mut TestValidatorGenesis) -> Result<(), String> {
+        let filenames = self
+            .accounts
+            .iter()
+            .map(|account| {
+                let filename = self.resolve(&account.filename);
+                filename
+                    .to_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("invalid account filename {}", filename.display()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let accounts = self
+            .accounts
+            .iter()
+            .zip(filenames.iter())
+            .map(|(account, filename)| {
+                Ok(AccountInfo {
+                    address: account.address.as_deref().map(parse_pubkey).transpose()?,
+                    filename,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let programs = self
+            .programs
+            .iter()
+            .map(|program| {
+                Ok(UpgradeableProgramInfo {
+                    program_id: parse_pubkey(&program.program_id)?,
+                    loader: solana_sdk_ids::bpf_loader_upgradeable::id(),
+                    upgrade_authority: Pubkey::default(),
+                    program_path: self.resolve(&program.path),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        genesis
+            .add_upgradeable_programs_with_path(&programs)
+            .add_accounts_from_json_files(&accounts)?;
+        Ok(())
+    }
+
+    /// Send every step's transaction in order and check it against its expectations
+    ///
+    /// Stops at the first step whose transaction could not be built or sent. Steps which merely
+    /// violate their expectations are reported and the scenario continues.
+    pub fn run(&self, rpc_client: &RpcClient) -> Result<Vec<StepReport>, String> {
+        self.steps
+            .iter()
+            .map(|step| {
+                self.run_step(rpc_client, step)
+                    .map_err(|err| format!("step {}: {err}", step.name))
+            })
+            .collect()
+    }
+
+    fn run_step(&self, rpc_client: &RpcClient, step: &ScenarioStep) -> Result<StepReport, String> {
+        let signers = step
+            .signers
+            .iter()
+            .map(|path| {
+                let path = self.resolve(path);
+                read_keypair_file(&path)
+                    .map_err(|err| format!("unable to read keypair {}: {err}", path.display()))
+            })
+            .collect::<Result<Vec<Keypair>, _>>()?;
+        let payer = signers
+            .first()
+            .ok_or_else(|| "at least one signer is required".to_string())?;
+        fund_payer(rpc_client, &payer.pubkey())?;
+
+        let instructions = step
+            .instructions
+            .iter()
+            .map(|instruction| {
+                let accounts = instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| {
+                        Ok(AccountMeta {
+                            pubkey: parse_pubkey(&meta.pubkey)?,
+                            is_signer: meta.is_signer,
+                            is_writable: meta.is_writable,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                let data = BASE64_STANDARD
+                    .decode(&instruction.data)
+                    .map_err(|err| format!("invalid instruction data: {err}"))?;
+                Ok(Instruction::new_with_bytes(
+                    parse_pubkey(&instruction.program_id)?,
+                    &data,
+                    accounts,
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let blockhash = rpc_client
+            .get_latest_blockhash()
+            .map_err(|err| format!("unable to get blockhash: {err}"))?;
+        let signers = signers
+            .iter()
+            .map(|keypair| keypair as &dyn Signer)
+            .collect::<Vec<_>>();
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &signers,
+            blockhash,
+        );
+
+        // Failing transactions are part of what a scenario can assert on, so skip preflight
+        let signature = rpc_client
+            .send_transaction_with_config(
+                &transaction,
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    ..RpcSendTransactionConfig::default()
+                },
+            )
+            .map_err(|err| format!("unable to send transaction: {err}"))?;
+        rpc_client
+            .poll_for_signature_with_commitment(&signature, CommitmentConfig::confirmed())
+            .map_err(|err| format!("transaction {signature} not confirmed: {err}"))?;
+        let meta = rpc_client
+            .get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                    include_profile: false,
+                },
+            )
+            .map_err(|err| format!("unable to fetch transaction {signature}: {err}"))?
+            .transaction
+            .meta
+            .ok_or_else(|| format!("transaction {signature} has no status meta"))?;
+
+        let success = meta.err.is_none();
+        let compute_units_consumed: Option<u64> = meta.compute_units_consumed.into();
+        let log_messages: Option<Vec<String>> = meta.log_messages.into();
+        let profile = log_messages
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|line| match ProfileLogLine::parse(line) {
+                Some(ProfileLogLine::Entry(entry)) => Some(entry),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut report = StepReport {
+            name: step.name.clone(),
+            signature: signature.to_string(),
+            success,
+            compute_units_consumed,
+            profile,
+            failures: Vec::new(),
+        };
+        check_expectation(&step.expect, &mut report);
+        Ok(report)
+    }
+}
+
+fn fund_payer(rpc_client: &RpcClient, payer: &Pubkey) -> Result<(), String> {
+    let balance = rpc_client
+        .get_balance(payer)
+        .map_err(|err| format!("unable to get balance of {payer}: {err}"))?;
+    if balance == 0 {
+        let signature = rpc_client
+            .request_airdrop(payer, PAYER_AIRDROP_LAMPORTS)
+            .map_err(|err| format!("airdrop to {payer} failed: {err}"))?;
+        rpc_client
+            .poll_for_signature_with_commitment(&signature, CommitmentConfig::confirmed())
+            .map_err(|err| format!("airdrop to {payer} not confirmed: {err}"))?;
+    }
+    Ok(())
+}
+
+fn check_expectation(expect: &ScenarioExpectation, report: &mut StepReport) {
+    if report.success != expect.success {
+        report.failures.push(if expect.success {
+            "transaction failed".to_string()
+        } else {
+            "transaction succeeded but was expected to fail".to_string()
+        });
+    }
+    if let Some(max_compute_units) = expect.max_compute_units {
+        match report.compute_units_consumed {
+            Some(consumed) if consumed > max_compute_units => report.failures.push(format!(
+                "consumed {consumed} compute units, budget is {max_compute_units}"
+            )),
+            Some(_) => {}
+            None => report
+                .failures
+                .push("compute units consumed were not reported".to_string()),
+        }
+    }
+    for budget in &expect.sections {
+        let mut sections = report
+            .profile
+            .iter()
+            .filter(|entry| entry.kind == ProfileEntryKind::Section && entry.name == budget.name)
+            .peekable();
+        if sections.peek().is_none() {
+            report
+                .failures
+                .push(format!("section {} was not profiled", budget.name));
+            continue;
+        }
+        let failures = sections
+            .filter(|entry| entry.consumed() > budget.max_compute_units)
+            .map(|entry| {
+                format!(
+                    "section {} consumed {} compute units, budget is {}",
+                    budget.name,
+                    entry.consumed(),
+                    budget.max_compute_units
+                )
+            })
+            .collect::<Vec<_>>();
+        report.failures.extend(failures);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::collections::BTreeMap};
+
+    fn section(name: &str, consumed: u64) -> ProfileEntry {
+        ProfileEntry {
+            kind: ProfileEntryKind::Section,
+            name: name.to_string(),
+            depth: 0,
+            start_cu: 0,
+            end_cu: consumed,
+            heap_start: None,
+            heap_end: None,
+            return_data_bytes: 0,
+            bulk_closed: false,
+            attributes: BTreeMap::new(),
+            call_site: None,
+        }
+    }
+
+    fn report(success: bool, consumed: u64, profile: Vec<ProfileEntry>) -> StepReport {
+        StepReport {
+            name: "step".to_string(),
+            signature: String::new(),
+            success,
+            compute_units_consumed: Some(consumed),
+            profile,
+            failures: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_scenario() {
+        let scenario: Scenario = serde_yaml::from_str(
+            r#"
+            {
+                "accounts": [{"filename": "account.json"}],
+                "steps": [{
+                    "name": "noop",
+                    "signers": ["payer.json"],
+                    "instructions": [{"program_id": "11111111111111111111111111111111"}]
+                }]
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(scenario.accounts.len(), 1);
+        assert!(scenario.programs.is_empty());
+        let step = scenario.steps.first().unwrap();
+        assert!(step.expect.success);
+        assert!(step.expect.sections.is_empty());
+        assert!(step.instructions.first().unwrap().data.is_empty());
+
+        assert!(serde_yaml::from_str::<Scenario>("steps: []\nunknown: 1").is_err());
+    }
+
+    #[test]
+    fn test_check_expectation() {
+        let expect = ScenarioExpectation {
+            success: true,
+            max_compute_units: Some(1_000),
+            sections: vec![SectionBudget {
+                name: "hash".to_string(),
+                max_compute_units: 100,
+            }],
+        };
+
+        let mut passing = report(true, 900, vec![section("hash", 100), section("other", 500)]);
+        check_expectation(&expect, &mut passing);
+        assert!(passing.passed());
+
+        let mut failing = report(
+            false,
+            1_001,
+            vec![section("hash", 50), section("hash", 101)],
+        );
+        check_expectation(&expect, &mut failing);
+        assert_eq!(
+            failing.failures,
+            vec![
+                "transaction failed".to_string(),
+                "consumed 1001 compute units, budget is 1000".to_string(),
+                "section hash consumed 101 compute units, budget is 100".to_string(),
+            ]
+        );
+
+        let mut missing = report(true, 0, vec![]);
+        check_expectation(&expect, &mut missing);
+        assert_eq!(
+            missing.failures,
+            vec!["section hash was not profiled".to_string()]
+        );
+    }
+}